@@ -13,10 +13,22 @@ use clap_verbosity_flag::Verbosity;
 pub struct Cli {
     #[command(flatten)]
     pub verbose: Verbosity,
+    #[command(flatten)]
+    pub output: OutputArgs,
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Debug, Clone, Copy, Default, Args)]
+pub struct OutputArgs {
+    /// Print the results to stdout as JSON instead of human-readable text
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Never ask for input; fail instead when a confirmation is needed
+    #[arg(long, global = true)]
+    pub no_input: bool,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Create a new Cot project
@@ -26,6 +38,10 @@ pub enum Commands {
     #[command(subcommand)]
     Migration(MigrationCommands),
 
+    /// Manage the secret keys of a Cot project
+    #[command(subcommand)]
+    Secret(SecretCommands),
+
     /// Manage Cot CLI
     #[command(subcommand)]
     Cli(CliCommands),
@@ -50,6 +66,12 @@ pub enum MigrationCommands {
     Make(MigrationMakeArgs),
     /// Create a new empty migration
     New(MigrationNewArgs),
+    /// Apply the pending migrations of a Cot project to its database
+    Apply(MigrationApplyArgs),
+    /// Show the applied and pending migrations of a Cot project
+    Status(MigrationStatusArgs),
+    /// Revert the applied migrations of a Cot project
+    Revert(MigrationRevertArgs),
 }
 
 #[derive(Debug, Args)]
@@ -83,6 +105,59 @@ pub struct MigrationMakeArgs {
     /// in the crate's src/ directory]
     #[arg(long)]
     pub output_dir: Option<PathBuf>,
+    /// Allow generating migrations that remove models or fields, along with
+    /// the data stored in them, without asking for confirmation
+    #[arg(long)]
+    pub allow_destructive: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct MigrationApplyArgs {
+    /// Path to the crate directory of the project [default: current directory]
+    pub path: Option<PathBuf>,
+    /// Name of the project config to use, determining the database to apply
+    /// the migrations to [default: the project's default config]
+    #[arg(long)]
+    pub config: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct MigrationStatusArgs {
+    /// Path to the crate directory of the project [default: current directory]
+    pub path: Option<PathBuf>,
+    /// Name of the project config to use, determining the database to check
+    /// the migrations in [default: the project's default config]
+    #[arg(long)]
+    pub config: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct MigrationRevertArgs {
+    /// Path to the crate directory of the project [default: current directory]
+    pub path: Option<PathBuf>,
+    /// Revert all the migrations applied after this one, optionally prefixed
+    /// with the app name (e.g. `app:m_0001_initial`) [default: revert only the
+    /// most recently applied migration]
+    #[arg(long, value_name = "NAME")]
+    pub to: Option<String>,
+    /// Name of the project config to use, determining the database to revert
+    /// the migrations in [default: the project's default config]
+    #[arg(long)]
+    pub config: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Subcommand)]
+pub enum SecretCommands {
+    /// Generate a random secret key, e.g. for the `secret_key` config option
+    Generate(SecretGenerateArgs),
+}
+
+#[derive(Debug, Clone, Copy, Args)]
+pub struct SecretGenerateArgs {
+    /// Length of the secret key in bytes; the key is printed as a hex string
+    /// twice as long
+    #[arg(short, long, default_value_t = 32, value_parser = clap::value_parser!(u16).range(16..))]
+    pub length: u16,
 }
 
 #[derive(Debug, Args)]
@@ -4,16 +4,20 @@ use anyhow::Context;
 use clap::CommandFactory;
 
 use crate::args::{
-    Cli, CompletionsArgs, ManpagesArgs, MigrationListArgs, MigrationMakeArgs, MigrationNewArgs,
-    ProjectNewArgs,
+    Cli, CompletionsArgs, ManpagesArgs, MigrationApplyArgs, MigrationListArgs, MigrationMakeArgs,
+    MigrationNewArgs, MigrationRevertArgs, MigrationStatusArgs, OutputArgs, ProjectNewArgs,
+    SecretGenerateArgs,
 };
 use crate::migration_generator::{
-    MigrationGeneratorOptions, create_new_migration, list_migrations, make_migrations,
+    MigrationGeneratorOptions, apply_migrations, create_new_migration, list_migrations,
+    make_migrations, migration_status, revert_migrations,
 };
 use crate::new_project::{CotSource, new_project};
+use crate::secret::generate_secret_key;
 
 pub fn handle_new_project(
     ProjectNewArgs { path, name, source }: ProjectNewArgs,
+    output: OutputArgs,
 ) -> anyhow::Result<()> {
     let project_name = match name {
         None => {
@@ -32,14 +36,37 @@ pub fn handle_new_project(
     } else {
         CotSource::PublishedCrate
     };
-    new_project(&path, &project_name, &cot_source).with_context(|| "unable to create project")
+    new_project(&path, &project_name, &cot_source).with_context(|| "unable to create project")?;
+
+    if output.json {
+        print_json(&serde_json::json!({
+            "name": project_name,
+            "path": path,
+        }));
+    }
+    Ok(())
 }
 
-pub fn handle_migration_list(MigrationListArgs { path }: MigrationListArgs) -> anyhow::Result<()> {
+pub fn handle_migration_list(
+    MigrationListArgs { path }: MigrationListArgs,
+    output: OutputArgs,
+) -> anyhow::Result<()> {
     let path = path.unwrap_or(PathBuf::from("."));
     let migrations = list_migrations(&path).with_context(|| "unable to list migrations")?;
-    for (app_name, migs) in migrations {
-        for mig in migs {
+    let mut migrations: Vec<_> = migrations
+        .into_iter()
+        .flat_map(|(app_name, migs)| migs.into_iter().map(move |mig| (app_name.clone(), mig)))
+        .collect();
+    migrations.sort();
+
+    if output.json {
+        let migrations: Vec<_> = migrations
+            .into_iter()
+            .map(|(app_name, mig)| serde_json::json!({ "app": app_name, "name": mig }))
+            .collect();
+        print_json(&migrations);
+    } else {
+        for (app_name, mig) in migrations {
             println!("{app_name}\t{mig}");
         }
     }
@@ -52,14 +79,24 @@ pub fn handle_migration_make(
         path,
         app_name,
         output_dir,
+        allow_destructive,
     }: MigrationMakeArgs,
+    output: OutputArgs,
 ) -> anyhow::Result<()> {
     let path = path.unwrap_or(PathBuf::from("."));
     let options = MigrationGeneratorOptions {
         app_name,
         output_dir,
+        allow_destructive,
+        no_input: output.no_input,
     };
-    make_migrations(&path, options).with_context(|| "unable to create migrations")
+    let summaries =
+        make_migrations(&path, options).with_context(|| "unable to create migrations")?;
+
+    if output.json {
+        print_json(&serde_json::json!({ "migrations": summaries }));
+    }
+    Ok(())
 }
 
 pub fn handle_migration_new(
@@ -68,13 +105,68 @@ pub fn handle_migration_new(
         path,
         app_name,
     }: MigrationNewArgs,
+    output: OutputArgs,
 ) -> anyhow::Result<()> {
     let path = path.unwrap_or(PathBuf::from("."));
     let options = MigrationGeneratorOptions {
         app_name,
         output_dir: None,
+        allow_destructive: false,
+        no_input: output.no_input,
     };
-    create_new_migration(&path, &name, options).with_context(|| "unable to create migration")
+    let summary = create_new_migration(&path, &name, options)
+        .with_context(|| "unable to create migration")?;
+
+    if output.json {
+        print_json(&serde_json::json!({ "migration": summary }));
+    }
+    Ok(())
+}
+
+pub fn handle_migration_apply(
+    MigrationApplyArgs { path, config }: MigrationApplyArgs,
+) -> anyhow::Result<()> {
+    let path = path.unwrap_or(PathBuf::from("."));
+    apply_migrations(&path, config.as_deref()).with_context(|| "unable to apply migrations")
+}
+
+pub fn handle_migration_status(
+    MigrationStatusArgs { path, config }: MigrationStatusArgs,
+) -> anyhow::Result<()> {
+    let path = path.unwrap_or(PathBuf::from("."));
+    migration_status(&path, config.as_deref())
+        .with_context(|| "unable to check the migration status")
+}
+
+pub fn handle_migration_revert(
+    MigrationRevertArgs { path, to, config }: MigrationRevertArgs,
+) -> anyhow::Result<()> {
+    let path = path.unwrap_or(PathBuf::from("."));
+    revert_migrations(&path, to.as_deref(), config.as_deref())
+        .with_context(|| "unable to revert migrations")
+}
+
+#[expect(clippy::unnecessary_wraps)] // return Result<()> for consistency
+pub fn handle_secret_generate(
+    SecretGenerateArgs { length }: SecretGenerateArgs,
+    output: OutputArgs,
+) -> anyhow::Result<()> {
+    let secret_key = generate_secret_key(length.into());
+
+    if output.json {
+        print_json(&serde_json::json!({ "secret_key": secret_key }));
+    } else {
+        println!("{secret_key}");
+    }
+    Ok(())
+}
+
+/// Prints the given value to stdout as a single line of JSON.
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) {
+    println!(
+        "{}",
+        serde_json::to_string(value).expect("CLI output should always serialize to JSON")
+    );
 }
 
 pub fn handle_cli_manpages(
@@ -116,7 +208,7 @@ mod tests {
             },
         };
 
-        let result = handle_new_project(args);
+        let result = handle_new_project(args, OutputArgs::default());
 
         assert!(result.is_err());
     }
@@ -127,7 +219,7 @@ mod tests {
             path: Some(PathBuf::from("nonexistent")),
         };
 
-        let result = handle_migration_list(args);
+        let result = handle_migration_list(args, OutputArgs::default());
 
         assert!(result.is_err());
     }
@@ -138,9 +230,10 @@ mod tests {
             path: Some(PathBuf::from("nonexistent")),
             app_name: None,
             output_dir: None,
+            allow_destructive: false,
         };
 
-        let result = handle_migration_make(args);
+        let result = handle_migration_make(args, OutputArgs::default());
 
         assert!(result.is_err());
     }
@@ -153,7 +246,44 @@ mod tests {
             app_name: None,
         };
 
-        let result = handle_migration_new(args);
+        let result = handle_migration_new(args, OutputArgs::default());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn migration_apply_wrong_directory() {
+        let args = MigrationApplyArgs {
+            path: Some(PathBuf::from("nonexistent")),
+            config: None,
+        };
+
+        let result = handle_migration_apply(args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn migration_status_wrong_directory() {
+        let args = MigrationStatusArgs {
+            path: Some(PathBuf::from("nonexistent")),
+            config: None,
+        };
+
+        let result = handle_migration_status(args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn migration_revert_wrong_directory() {
+        let args = MigrationRevertArgs {
+            path: Some(PathBuf::from("nonexistent")),
+            to: None,
+            config: None,
+        };
+
+        let result = handle_migration_revert(args);
 
         assert!(result.is_err());
     }
@@ -1,7 +1,7 @@
 #![allow(unreachable_pub)] // triggers false positives because we have both a binary and library
 
 use clap::Parser;
-use cot_cli::args::{Cli, CliCommands, Commands, MigrationCommands};
+use cot_cli::args::{Cli, CliCommands, Commands, MigrationCommands, SecretCommands};
 use cot_cli::handlers;
 use tracing_subscriber::util::SubscriberInitExt;
 
@@ -16,16 +16,31 @@ fn main() -> anyhow::Result<()> {
         .finish()
         .init();
 
-    match cli.command {
-        Commands::New(args) => handlers::handle_new_project(args),
+    let output = cli.output;
+    let result = match cli.command {
+        Commands::New(args) => handlers::handle_new_project(args, output),
         Commands::Cli(cmd) => match cmd {
             CliCommands::Manpages(args) => handlers::handle_cli_manpages(args),
             CliCommands::Completions(args) => handlers::handle_cli_completions(args),
         },
         Commands::Migration(cmd) => match cmd {
-            MigrationCommands::List(args) => handlers::handle_migration_list(args),
-            MigrationCommands::Make(args) => handlers::handle_migration_make(args),
-            MigrationCommands::New(args) => handlers::handle_migration_new(args),
+            MigrationCommands::List(args) => handlers::handle_migration_list(args, output),
+            MigrationCommands::Make(args) => handlers::handle_migration_make(args, output),
+            MigrationCommands::New(args) => handlers::handle_migration_new(args, output),
+            MigrationCommands::Apply(args) => handlers::handle_migration_apply(args),
+            MigrationCommands::Status(args) => handlers::handle_migration_status(args),
+            MigrationCommands::Revert(args) => handlers::handle_migration_revert(args),
         },
+        Commands::Secret(cmd) => match cmd {
+            SecretCommands::Generate(args) => handlers::handle_secret_generate(args, output),
+        },
+    };
+
+    if output.json
+        && let Err(error) = &result
+    {
+        println!("{}", serde_json::json!({ "error": format!("{error:#}") }));
+        std::process::exit(1);
     }
+    result
 }
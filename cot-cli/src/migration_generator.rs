@@ -2,61 +2,123 @@ use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt::{Debug, Display};
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, bail};
 use cot::db::migrations::{DynMigration, MigrationEngine};
-use cot_codegen::model::{Field, Model, ModelArgs, ModelOpts, ModelType};
+use cot_codegen::model::{
+    Field, FieldOpts, MANY_TO_MANY_SOURCE_COLUMN, MANY_TO_MANY_TARGET_COLUMN, Model, ModelArgs,
+    ModelOpts, ModelType,
+};
 use cot_codegen::symbol_resolver::SymbolResolver;
-use darling::FromMeta;
+use darling::{FromField, FromMeta};
 use heck::ToSnakeCase;
 use petgraph::graph::DiGraph;
 use petgraph::visit::EdgeRef;
 use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
+use serde::Serialize;
 use syn::{Meta, parse_quote};
 use tracing::{debug, trace};
 
-use crate::utils::{CargoTomlManager, PackageManager, StatusType, print_status_msg};
+use crate::utils::{
+    CargoTomlManager, PackageManager, StatusType, WorkspaceManager, confirm, print_status_msg,
+};
 
-pub fn make_migrations(path: &Path, options: MigrationGeneratorOptions) -> anyhow::Result<()> {
+/// Generates migrations for the package at the given path.
+///
+/// If the path points to a workspace that is not a package itself, the
+/// migrations are generated for every member package that depends on `cot`.
+pub fn make_migrations(
+    path: &Path,
+    options: MigrationGeneratorOptions,
+) -> anyhow::Result<Vec<MigrationSummary>> {
     let Some(manager) = CargoTomlManager::from_path(path)? else {
         bail!("Cargo.toml not found in the specified directory or any parent directory.")
     };
 
-    match manager {
+    let summary = match manager {
         CargoTomlManager::Workspace(workspace) => {
             let Some(package) = workspace.get_current_package_manager() else {
-                bail!(
-                    "Generating migrations for workspaces is not supported yet. \
-                        Please generate migrations for each package separately."
-                );
+                return make_workspace_migrations(&workspace, &options);
             };
-            make_package_migrations(package, options)
+            make_package_migrations(package, options)?
         }
-        CargoTomlManager::Package(package) => make_package_migrations(&package, options),
+        CargoTomlManager::Package(package) => make_package_migrations(&package, options)?,
+    };
+    Ok(summary.into_iter().collect())
+}
+
+fn make_workspace_migrations(
+    workspace: &WorkspaceManager,
+    options: &MigrationGeneratorOptions,
+) -> anyhow::Result<Vec<MigrationSummary>> {
+    if options.app_name.is_some() || options.output_dir.is_some() {
+        bail!(
+            "The app name and the output directory can't be set when generating migrations \
+                for a whole workspace. Please generate migrations for each package separately."
+        );
+    }
+
+    // Packages are processed in dependency order, so that the migrations for
+    // the models referenced by foreign keys are generated first
+    let mut summaries = Vec::new();
+    for package in workspace.get_packages_in_dependency_order() {
+        if !package.depends_on("cot") {
+            continue;
+        }
+
+        let package_name = package.get_package_name();
+        print_status_msg(StatusType::Notice, &format!("Package `{package_name}`"));
+        let summary = make_package_migrations(package, options.clone()).with_context(|| {
+            format!("unable to generate migrations for package `{package_name}`")
+        })?;
+        summaries.extend(summary);
     }
+
+    Ok(summaries)
 }
 
 fn make_package_migrations(
     manager: &PackageManager,
     options: MigrationGeneratorOptions,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Option<MigrationSummary>> {
     let crate_name = manager.get_package_name().to_string();
     let manifest_path = manager.get_manifest_path();
 
+    let allow_destructive = options.allow_destructive;
+    let interactive = !options.no_input && std::io::stdin().is_terminal();
     let generator = MigrationGenerator::new(manifest_path, crate_name, options);
-    let migrations = generator
-        .generate_migrations_as_source()
+    let migration = generator
+        .get_source_files()
+        .and_then(|source_files| {
+            generator.generate_migrations_as_generated_from_files(source_files)
+        })
         .context("unable to generate migrations")?;
-    let Some(migrations) = migrations else {
+    let Some(migration) = migration else {
         print_status_msg(
             StatusType::Notice,
             "No changes in models detected; no migrations were generated",
         );
-        return Ok(());
+        return Ok(None);
+    };
+
+    check_destructive_operations(&migration.operations, allow_destructive, interactive)?;
+    let summary = MigrationSummary {
+        app: generator.app_name().to_string(),
+        name: migration.migration_name.clone(),
+        path: generator.migration_file_path(&migration.migration_name),
+        operations: migration
+            .operations
+            .iter()
+            .map(DynOperation::describe)
+            .collect(),
     };
+    let migrations = MigrationAsSource::new(
+        migration.migration_name.clone(),
+        generator.generate_migration_file_content(migration),
+    );
 
     generator
         .write_migrations(&migrations)
@@ -65,14 +127,99 @@ fn make_package_migrations(
         .write_migrations_module()
         .context("unable to write migrations.rs")?;
 
-    Ok(())
+    Ok(Some(summary))
+}
+
+/// Warns about the operations that cause data loss and fails unless
+/// generating such migrations has been explicitly allowed, either with a flag
+/// or by confirming it interactively.
+fn check_destructive_operations(
+    operations: &[DynOperation],
+    allow_destructive: bool,
+    interactive: bool,
+) -> anyhow::Result<()> {
+    let destructive_operations: Vec<_> = operations
+        .iter()
+        .filter(|operation| operation.is_destructive())
+        .collect();
+    if destructive_operations.is_empty() {
+        return Ok(());
+    }
+
+    for operation in &destructive_operations {
+        print_status_msg(StatusType::Warning, &operation.describe());
+    }
+    for (table_name, old_name, new_name) in likely_renames(operations) {
+        print_status_msg(
+            StatusType::Warning,
+            &format!(
+                "Field `{old_name}` in `{table_name}` looks like it was renamed to \
+                    `{new_name}`; add `#[model(rename_from = \"{old_name}\")]` to the \
+                    field to keep its data"
+            ),
+        );
+    }
+
+    if allow_destructive || (interactive && confirm("Generate the migration anyway?")?) {
+        return Ok(());
+    }
+    bail!(
+        "The migration would remove or change {} model(s) or field(s) in a way that loses \
+            their data. Re-run with `--allow-destructive` to generate it anyway.",
+        destructive_operations.len()
+    );
+}
+
+/// Returns the fields that were likely renamed, as `(table_name, old_name,
+/// new_name)` tuples.
+///
+/// A field is considered renamed if a field with the same definition (other
+/// than the name) is removed from and added to the same table, and that's the
+/// only such pair in the table.
+fn likely_renames(operations: &[DynOperation]) -> Vec<(&str, &str, &str)> {
+    let same_definition = |removed: &Field, added: &Field| {
+        removed.ty == added.ty
+            && removed.primary_key == added.primary_key
+            && removed.auto_value == added.auto_value
+            && removed.unique == added.unique
+            && removed.foreign_key == added.foreign_key
+    };
+
+    let mut renames = Vec::new();
+    for operation in operations {
+        let DynOperation::RemoveField {
+            table_name, field, ..
+        } = operation
+        else {
+            continue;
+        };
+
+        let candidates: Vec<_> = operations
+            .iter()
+            .filter_map(|operation| match operation {
+                DynOperation::AddField {
+                    table_name: added_table,
+                    field: added,
+                    ..
+                } if added_table == table_name && same_definition(field, added) => {
+                    Some(added.column_name.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        if let [new_name] = candidates[..] {
+            renames.push((table_name.as_str(), field.column_name.as_str(), new_name));
+        }
+    }
+
+    renames
 }
 
 pub fn create_new_migration(
     path: &Path,
     name: &str,
     options: MigrationGeneratorOptions,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<MigrationSummary> {
     let Some(manager) = CargoTomlManager::from_path(path)? else {
         bail!("Cargo.toml not found in the specified directory or any parent directory.")
     };
@@ -95,7 +242,7 @@ fn create_package_new_migration(
     manager: &PackageManager,
     name: &str,
     options: MigrationGeneratorOptions,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<MigrationSummary> {
     let crate_name = manager.get_package_name().to_string();
     let manifest_path = manager.get_manifest_path();
 
@@ -111,7 +258,12 @@ fn create_package_new_migration(
         .write_migrations_module()
         .context("unable to write migrations.rs")?;
 
-    Ok(())
+    Ok(MigrationSummary {
+        app: generator.app_name().to_string(),
+        path: generator.migration_file_path(&migration.name),
+        name: migration.name,
+        operations: Vec::new(),
+    })
 }
 
 pub fn list_migrations(path: &Path) -> anyhow::Result<HashMap<String, Vec<String>>> {
@@ -140,10 +292,99 @@ pub fn list_migrations(path: &Path) -> anyhow::Result<HashMap<String, Vec<String
     }
 }
 
+/// Applies the pending migrations of the project at the given path.
+///
+/// See [`run_project_migration_command`] for how this is done.
+pub fn apply_migrations(path: &Path, config: Option<&str>) -> anyhow::Result<()> {
+    run_project_migration_command(path, config, &["apply"])
+        .context("applying the migrations failed")
+}
+
+/// Shows which migrations of the project at the given path are applied and
+/// which are pending.
+///
+/// See [`run_project_migration_command`] for how this is done.
+pub fn migration_status(path: &Path, config: Option<&str>) -> anyhow::Result<()> {
+    run_project_migration_command(path, config, &["status"])
+        .context("checking the migration status failed")
+}
+
+/// Reverts the applied migrations of the project at the given path.
+///
+/// See [`run_project_migration_command`] for how this is done.
+pub fn revert_migrations(
+    path: &Path,
+    to: Option<&str>,
+    config: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut args = vec!["revert"];
+    if let Some(to) = to {
+        args.extend(["--to", to]);
+    }
+    run_project_migration_command(path, config, &args).context("reverting the migrations failed")
+}
+
+/// Runs a `migration` subcommand of the project at the given path.
+///
+/// The migrations can only be applied or reverted by the project itself, as
+/// it's the one knowing the database and the full list of migrations of all
+/// its apps, so this builds and runs the project with Cargo.
+fn run_project_migration_command(
+    path: &Path,
+    config: Option<&str>,
+    args: &[&str],
+) -> anyhow::Result<()> {
+    let Some(manager) = CargoTomlManager::from_path(path)? else {
+        bail!("Cargo.toml not found in the specified directory or any parent directory.")
+    };
+    let package = match &manager {
+        CargoTomlManager::Workspace(workspace) => {
+            let Some(package) = workspace.get_current_package_manager() else {
+                bail!(
+                    "Managing the migrations of a whole workspace is not supported. Please run \
+                        the command for the package of the project."
+                );
+            };
+            package
+        }
+        CargoTomlManager::Package(package) => package,
+    };
+
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut command = std::process::Command::new(cargo);
+    command
+        .arg("run")
+        .arg("--manifest-path")
+        .arg(package.get_manifest_path())
+        .arg("--");
+    if let Some(config) = config {
+        command.args(["--config", config]);
+    }
+    command.arg("migration").args(args);
+
+    let status = command.status().context("unable to run cargo")?;
+    if !status.success() {
+        bail!("the project exited with {status}");
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct MigrationGeneratorOptions {
     pub app_name: Option<String>,
     pub output_dir: Option<PathBuf>,
+    pub allow_destructive: bool,
+    pub no_input: bool,
+}
+
+/// A summary of a migration written by the generator.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationSummary {
+    pub app: String,
+    pub name: String,
+    pub path: PathBuf,
+    /// Human-readable descriptions of the operations in the migration.
+    pub operations: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -394,7 +635,12 @@ impl MigrationGenerator {
                                     "Found an Application model: {}",
                                     model_in_source.model.name.to_string()
                                 );
+                                let join_models = model_in_source.many_to_many_join_models(
+                                    self.crate_name.as_str(),
+                                    &symbol_resolver,
+                                )?;
                                 app_state.models.push(model_in_source);
+                                app_state.models.extend(join_models);
                             }
                             ModelType::Migration => {
                                 trace!(
@@ -545,15 +791,26 @@ impl MigrationGenerator {
         Self::generate_migration(migration_def, models_def)
     }
 
-    fn save_migration_to_file(&self, migration_name: &String, bytes: &[u8]) -> anyhow::Result<()> {
-        let src_path = self.get_src_path();
-        let migration_path = src_path.join(MIGRATIONS_MODULE_NAME);
-        let migration_file = migration_path.join(format!("{migration_name}.rs"));
+    fn app_name(&self) -> &str {
+        self.options.app_name.as_ref().unwrap_or(&self.crate_name)
+    }
+
+    fn migration_file_path(&self, migration_name: &str) -> PathBuf {
+        self.get_src_path()
+            .join(MIGRATIONS_MODULE_NAME)
+            .join(format!("{migration_name}.rs"))
+    }
+
+    fn save_migration_to_file(&self, migration_name: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        let migration_file = self.migration_file_path(migration_name);
+        let migration_path = migration_file
+            .parent()
+            .expect("migration file path should always have a parent");
         print_status_msg(
             StatusType::Creating,
             &format!("Migration file '{}'", migration_file.display()),
         );
-        std::fs::create_dir_all(&migration_path).with_context(|| {
+        std::fs::create_dir_all(migration_path).with_context(|| {
             format!(
                 "unable to create migrations directory: {}",
                 migration_path.display()
@@ -725,11 +982,45 @@ impl MigrationOperationGenerator {
             migration_model_fields.insert(field.column_name.clone(), field);
         }
 
+        let mut operations = Vec::new();
+        for (old_name, new_name) in app_model.field_renames() {
+            if migration_model_fields.contains_key(&new_name)
+                || app_model_fields.contains_key(&old_name)
+            {
+                // already renamed, or the old name is still in use
+                continue;
+            }
+            let (Some(app_field), Some(migration_field)) = (
+                app_model_fields.remove(&new_name),
+                migration_model_fields.remove(&old_name),
+            ) else {
+                continue;
+            };
+            all_field_names.remove(&old_name);
+            all_field_names.remove(&new_name);
+
+            operations.push(Self::make_rename_field_operation(
+                app_model,
+                &migration_field.column_name,
+                &app_field.column_name,
+            ));
+            let renamed_field = Field {
+                name: app_field.name.clone(),
+                column_name: app_field.column_name.clone(),
+                ..migration_field.clone()
+            };
+            operations.extend(Self::make_alter_field_operation(
+                app_model,
+                app_field,
+                migration_model,
+                &renamed_field,
+            ));
+        }
+
         let mut all_field_names: Vec<_> = all_field_names.into_iter().collect();
         // sort to ensure deterministic order
         all_field_names.sort();
 
-        let mut operations = Vec::new();
         for field_name in all_field_names {
             let app_field = app_model_fields.get(&field_name);
             let migration_field = migration_model_fields.get(&field_name);
@@ -814,9 +1105,41 @@ impl MigrationOperationGenerator {
         op
     }
 
+    #[must_use]
+    fn make_rename_field_operation(
+        app_model: &ModelInSource,
+        old_name: &str,
+        new_name: &str,
+    ) -> DynOperation {
+        print_status_msg(
+            StatusType::Modifying,
+            &format!(
+                "Field '{old_name}' to '{new_name}' in Model '{}'",
+                app_model.model.name
+            ),
+        );
+
+        let op = DynOperation::RenameField {
+            table_name: app_model.model.table_name.clone(),
+            model_ty: app_model.model.resolved_ty.clone(),
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+        };
+
+        print_status_msg(
+            StatusType::Modified,
+            &format!(
+                "Field '{old_name}' to '{new_name}' in Model '{}'",
+                app_model.model.name
+            ),
+        );
+
+        op
+    }
+
     #[must_use]
     fn make_alter_field_operation(
-        _app_model: &ModelInSource,
+        app_model: &ModelInSource,
         app_field: &Field,
         migration_model: &ModelInSource,
         migration_field: &Field,
@@ -832,9 +1155,24 @@ impl MigrationOperationGenerator {
             ),
         );
 
-        todo!();
+        let only_type_changed = app_field.auto_value == migration_field.auto_value
+            && app_field.primary_key == migration_field.primary_key
+            && app_field.unique == migration_field.unique
+            && app_field.foreign_key == migration_field.foreign_key;
+        assert!(
+            only_type_changed,
+            "changing anything other than the type of field '{}' in Model '{}' is not supported \
+            yet; remove the field and add a new one instead",
+            migration_field.name, migration_model.model.name
+        );
+
+        let op = DynOperation::AlterField {
+            table_name: app_model.model.table_name.clone(),
+            model_ty: app_model.model.resolved_ty.clone(),
+            old_field: Box::new(migration_field.clone()),
+            new_field: Box::new(app_field.clone()),
+        };
 
-        #[expect(unreachable_code)]
         print_status_msg(
             StatusType::Modified,
             &format!(
@@ -842,6 +1180,8 @@ impl MigrationOperationGenerator {
                 &migration_field.name, migration_model.model.name
             ),
         );
+
+        Some(op)
     }
 
     #[must_use]
@@ -1034,6 +1374,54 @@ impl ModelInSource {
             model,
         })
     }
+
+    /// Returns the models representing the join tables of the many-to-many
+    /// relations of this model.
+    ///
+    /// These mirror the join models defined by the `#[model]` macro for each
+    /// `ManyToMany` field, so that the migrations create the join tables.
+    fn many_to_many_join_models(
+        &self,
+        app_name: &str,
+        symbol_resolver: &SymbolResolver,
+    ) -> anyhow::Result<Vec<Self>> {
+        let source_ty = &self.model.resolved_ty;
+        let source_column = format_ident!("{}", MANY_TO_MANY_SOURCE_COLUMN);
+        let target_column = format_ident!("{}", MANY_TO_MANY_TARGET_COLUMN);
+
+        self.model
+            .many_to_many_fields
+            .iter()
+            .map(|field| {
+                let join_model_name = format_ident!("{}", field.join_model_name);
+                let target_ty = &field.to_model;
+                let item: syn::ItemStruct = parse_quote! {
+                    #[model]
+                    struct #join_model_name {
+                        #[model(primary_key)]
+                        id: cot::db::Auto<i64>,
+                        #source_column: cot::db::ForeignKey<#source_ty>,
+                        #target_column: cot::db::ForeignKey<#target_ty>,
+                    }
+                };
+
+                Self::from_item(app_name, item, &ModelArgs::default(), symbol_resolver)
+            })
+            .collect()
+    }
+
+    /// Returns the fields renamed with the `#[model(rename_from = "...")]`
+    /// attribute as `(old_name, new_name)` pairs.
+    fn field_renames(&self) -> Vec<(String, String)> {
+        self.model_item
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let opts = FieldOpts::from_field(field).ok()?;
+                Some((opts.rename_from?, opts.ident?.to_string()))
+            })
+            .collect()
+    }
 }
 
 /// A migration generated by the CLI and before converting to a Rust
@@ -1142,6 +1530,18 @@ impl GeneratedMigration {
                         because it doesn't create a new model"
                         )
                     }
+                    DynOperation::RenameField { .. } => {
+                        unreachable!(
+                            "RenameField operation shouldn't be a dependency of CreateModel \
+                        because it doesn't create a new model"
+                        )
+                    }
+                    DynOperation::AlterField { .. } => {
+                        unreachable!(
+                            "AlterField operation shouldn't be a dependency of CreateModel \
+                        because it doesn't create a new model"
+                        )
+                    }
                     DynOperation::RemoveModel { .. } => {
                         unreachable!(
                             "RemoveModel operation shouldn't be a dependency of CreateModel \
@@ -1180,6 +1580,14 @@ impl GeneratedMigration {
                 // RemoveField doesn't create dependencies, it only removes a field
                 unreachable!("RemoveField operation should never create cycles")
             }
+            DynOperation::RenameField { .. } => {
+                // RenameField doesn't create dependencies, it only renames a field
+                unreachable!("RenameField operation should never create cycles")
+            }
+            DynOperation::AlterField { .. } => {
+                // AlterField doesn't create dependencies, it only changes the type of a field
+                unreachable!("AlterField operation should never create cycles")
+            }
             DynOperation::RemoveModel { .. } => {
                 // RemoveModel doesn't create dependencies, it only removes a model
                 unreachable!("RemoveModel operation should never create cycles")
@@ -1282,6 +1690,14 @@ impl GeneratedMigration {
                     // RemoveField Doesnt Add Foreign Keys
                     Vec::new()
                 }
+                DynOperation::RenameField { .. } => {
+                    // RenameField Doesnt Add Foreign Keys
+                    Vec::new()
+                }
+                DynOperation::AlterField { .. } => {
+                    // AlterField Doesnt Add Foreign Keys
+                    Vec::new()
+                }
                 DynOperation::RemoveModel { .. } => {
                     // RemoveModel Doesnt Add Foreign Keys
                     Vec::new()
@@ -1433,6 +1849,19 @@ pub enum DynOperation {
         // boxed to reduce size difference between enum variations
         field: Box<Field>,
     },
+    RenameField {
+        table_name: String,
+        model_ty: syn::Type,
+        old_name: String,
+        new_name: String,
+    },
+    AlterField {
+        table_name: String,
+        model_ty: syn::Type,
+        // boxed to reduce size difference between enum variations
+        old_field: Box<Field>,
+        new_field: Box<Field>,
+    },
     RemoveModel {
         table_name: String,
         model_ty: syn::Type,
@@ -1440,6 +1869,160 @@ pub enum DynOperation {
     },
 }
 
+impl DynOperation {
+    /// Returns whether the operation causes data loss when applied.
+    #[must_use]
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            Self::RemoveField { .. } | Self::RemoveModel { .. } => true,
+            Self::AlterField {
+                old_field,
+                new_field,
+                ..
+            } => is_destructive_type_change(&old_field.ty, &new_field.ty),
+            _ => false,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::CreateModel { table_name, .. } => format!("Create model `{table_name}`"),
+            Self::AddField {
+                table_name, field, ..
+            } => format!("Add field `{}` to `{table_name}`", field.column_name),
+            Self::RemoveField {
+                table_name, field, ..
+            } => format!(
+                "Remove field `{}` from `{table_name}`; its data will be lost",
+                field.column_name
+            ),
+            Self::RenameField {
+                table_name,
+                old_name,
+                new_name,
+                ..
+            } => format!("Rename field `{old_name}` in `{table_name}` to `{new_name}`"),
+            Self::AlterField {
+                table_name,
+                new_field,
+                ..
+            } => {
+                if self.is_destructive() {
+                    format!(
+                        "Change the type of field `{}` in `{table_name}`; the existing values \
+                        might not fit the new type",
+                        new_field.column_name
+                    )
+                } else {
+                    format!(
+                        "Change the type of field `{}` in `{table_name}`",
+                        new_field.column_name
+                    )
+                }
+            }
+            Self::RemoveModel { table_name, .. } => {
+                format!("Remove model `{table_name}`; all its data will be lost")
+            }
+        }
+    }
+}
+
+/// Returns whether changing the type of a field from `old` to `new` can lose
+/// data, i.e. whether the field stops being nullable, or its type is narrowed
+/// or changed to an unrelated one.
+fn is_destructive_type_change(old: &syn::Type, new: &syn::Type) -> bool {
+    let (old, old_nullable) = option_inner_type(old).map_or((old, false), |inner| (inner, true));
+    let (new, new_nullable) = option_inner_type(new).map_or((new, false), |inner| (inner, true));
+    if old_nullable && !new_nullable {
+        return true;
+    }
+
+    old != new && !is_widening_type_change(old, new)
+}
+
+/// Returns whether every value of the `old` type can be stored in a field of
+/// the `new` type.
+fn is_widening_type_change(old: &syn::Type, new: &syn::Type) -> bool {
+    let (Some((old_name, old_length)), Some((new_name, new_length))) =
+        (type_name_and_length(old), type_name_and_length(new))
+    else {
+        return false;
+    };
+
+    match (old_name.as_str(), new_name.as_str()) {
+        ("f32", "f64") | ("LimitedString", "String") => true,
+        ("LimitedString", "LimitedString") => {
+            matches!((old_length, new_length), (Some(old), Some(new)) if new >= old)
+        }
+        (old_name, new_name) => match (integer_size(old_name), integer_size(new_name)) {
+            (Some((old_signed, old_bits)), Some((new_signed, new_bits))) => {
+                if old_signed == new_signed {
+                    new_bits >= old_bits
+                } else {
+                    // only a signed type that is bigger can hold all unsigned values
+                    !old_signed && new_bits > old_bits
+                }
+            }
+            _ => false,
+        },
+    }
+}
+
+/// Returns the name of the type, along with its length if it's a type such as
+/// `LimitedString<N>`.
+fn type_name_and_length(ty: &syn::Type) -> Option<(String, Option<u32>)> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    let length = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            syn::GenericArgument::Const(syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(length),
+                ..
+            })) => length.base10_parse().ok(),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    Some((segment.ident.to_string(), length))
+}
+
+/// Returns whether the integer type is signed, and its size in bits, or `None`
+/// if the type is not an integer.
+fn integer_size(type_name: &str) -> Option<(bool, u8)> {
+    match type_name {
+        "i8" => Some((true, 8)),
+        "i16" => Some((true, 16)),
+        "i32" => Some((true, 32)),
+        "i64" => Some((true, 64)),
+        "u8" => Some((false, 8)),
+        "u16" => Some((false, 16)),
+        "u32" => Some((false, 32)),
+        "u64" => Some((false, 64)),
+        _ => None,
+    }
+}
+
+/// Returns `T` if the given type is `Option<T>`.
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        syn::GenericArgument::Type(inner) if arguments.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
 /// Returns whether given [`Field`] is a foreign key to given type.
 fn is_field_foreign_key_to(field: &Field, ty: &syn::Type) -> bool {
     foreign_key_for_field(field).is_some_and(|to_model| &to_model == ty)
@@ -1492,6 +2075,36 @@ impl Repr for DynOperation {
                         .build()
                 }
             }
+            Self::RenameField {
+                table_name,
+                old_name,
+                new_name,
+                ..
+            } => {
+                quote! {
+                    ::cot::db::migrations::Operation::rename_field()
+                        .table_name(::cot::db::Identifier::new(#table_name))
+                        .old_name(::cot::db::Identifier::new(#old_name))
+                        .new_name(::cot::db::Identifier::new(#new_name))
+                        .build()
+                }
+            }
+            Self::AlterField {
+                table_name,
+                old_field,
+                new_field,
+                ..
+            } => {
+                let old_field = old_field.repr();
+                let new_field = new_field.repr();
+                quote! {
+                    ::cot::db::migrations::Operation::alter_field()
+                        .table_name(::cot::db::Identifier::new(#table_name))
+                        .old_field(#old_field)
+                        .new_field(#new_field)
+                        .build()
+                }
+            }
             Self::RemoveModel {
                 table_name, fields, ..
             } => {
@@ -1889,6 +2502,8 @@ mod tests {
                     unique: false,
                     foreign_key: None,
                 }],
+                many_to_many_fields: vec![],
+                workflow: None,
             },
         }
     }
@@ -1938,6 +2553,8 @@ mod tests {
                         foreign_key: None,
                     },
                 ],
+                many_to_many_fields: vec![],
+                workflow: None,
             },
         }
     }
@@ -2149,6 +2766,199 @@ mod tests {
             "Expected a RemoveField operation for 'field2'"
         );
     }
+
+    #[test]
+    fn destructive_operations() {
+        let (_, added) = MigrationGenerator::generate_operations(
+            &vec![get_bigger_test_model()],
+            &vec![get_test_model()],
+        );
+        let (_, removed) = MigrationGenerator::generate_operations(
+            &vec![get_test_model()],
+            &vec![get_bigger_test_model()],
+        );
+
+        assert!(added.iter().all(|op| !op.is_destructive()));
+        assert!(removed.iter().any(DynOperation::is_destructive));
+
+        assert!(check_destructive_operations(&added, false, false).is_ok());
+        assert!(check_destructive_operations(&removed, true, false).is_ok());
+        let error = check_destructive_operations(&removed, false, false).unwrap_err();
+        assert!(error.to_string().contains("--allow-destructive"));
+    }
+
+    #[test]
+    fn generate_operations_with_altered_field() {
+        let migration_model = get_test_model();
+        let mut app_model = get_test_model();
+        app_model.model.fields[0].ty = parse_quote!(Option<String>);
+
+        let (modified_models, operations) = MigrationGenerator::generate_operations(
+            &vec![app_model.clone()],
+            &vec![migration_model.clone()],
+        );
+
+        assert_eq!(modified_models.len(), 1);
+        assert_eq!(
+            operations,
+            vec![DynOperation::AlterField {
+                table_name: "test_model".to_string(),
+                model_ty: parse_quote!(TestModel),
+                old_field: Box::new(migration_model.model.fields[0].clone()),
+                new_field: Box::new(app_model.model.fields[0].clone()),
+            }]
+        );
+        assert!(!operations[0].is_destructive());
+
+        let (_, operations) =
+            MigrationGenerator::generate_operations(&vec![migration_model], &vec![app_model]);
+
+        assert!(operations[0].is_destructive());
+    }
+
+    #[test]
+    fn destructive_type_changes() {
+        let destructive = |old: syn::Type, new: syn::Type| is_destructive_type_change(&old, &new);
+
+        assert!(!destructive(parse_quote!(i32), parse_quote!(i32)));
+        assert!(!destructive(parse_quote!(i32), parse_quote!(i64)));
+        assert!(!destructive(parse_quote!(u32), parse_quote!(i64)));
+        assert!(!destructive(parse_quote!(f32), parse_quote!(f64)));
+        assert!(!destructive(parse_quote!(i32), parse_quote!(Option<i32>)));
+        assert!(!destructive(
+            parse_quote!(Option<i16>),
+            parse_quote!(Option<i32>)
+        ));
+        assert!(!destructive(
+            parse_quote!(LimitedString<50>),
+            parse_quote!(LimitedString<100>)
+        ));
+        assert!(!destructive(
+            parse_quote!(LimitedString<50>),
+            parse_quote!(String)
+        ));
+
+        assert!(destructive(parse_quote!(i64), parse_quote!(i32)));
+        assert!(destructive(parse_quote!(u32), parse_quote!(i32)));
+        assert!(destructive(parse_quote!(i32), parse_quote!(u64)));
+        assert!(destructive(parse_quote!(f64), parse_quote!(f32)));
+        assert!(destructive(parse_quote!(Option<i32>), parse_quote!(i32)));
+        assert!(destructive(
+            parse_quote!(LimitedString<100>),
+            parse_quote!(LimitedString<50>)
+        ));
+        assert!(destructive(
+            parse_quote!(String),
+            parse_quote!(LimitedString<100>)
+        ));
+        assert!(destructive(parse_quote!(String), parse_quote!(i32)));
+    }
+
+    #[test]
+    fn likely_renames_detected() {
+        let field = |name: &str, ty: syn::Type| {
+            Box::new(Field {
+                name: format_ident!("{}", name),
+                column_name: name.to_string(),
+                ty,
+                auto_value: false,
+                primary_key: false,
+                unique: false,
+                foreign_key: None,
+            })
+        };
+        let operations = vec![
+            DynOperation::RemoveField {
+                table_name: "todo".to_string(),
+                model_ty: parse_quote!(Todo),
+                field: field("title", parse_quote!(String)),
+            },
+            DynOperation::AddField {
+                table_name: "todo".to_string(),
+                model_ty: parse_quote!(Todo),
+                field: field("name", parse_quote!(String)),
+            },
+            DynOperation::RemoveField {
+                table_name: "user".to_string(),
+                model_ty: parse_quote!(User),
+                field: field("birth_year", parse_quote!(i32)),
+            },
+            DynOperation::AddField {
+                table_name: "user".to_string(),
+                model_ty: parse_quote!(User),
+                field: field("birth_date", parse_quote!(String)),
+            },
+        ];
+
+        assert_eq!(likely_renames(&operations), vec![("todo", "title", "name")]);
+    }
+
+    #[test]
+    fn generate_operations_with_renamed_field() {
+        let migration_model = get_test_model();
+        let mut app_model = get_test_model();
+        app_model.model_item = parse_quote! {
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                #[model(rename_from = "field1")]
+                title: String,
+            }
+        };
+        app_model.model.fields[0].name = format_ident!("title");
+        app_model.model.fields[0].column_name = "title".to_string();
+
+        let (modified_models, operations) =
+            MigrationGenerator::generate_operations(&vec![app_model], &vec![migration_model]);
+
+        assert_eq!(modified_models.len(), 1);
+        assert_eq!(
+            operations,
+            vec![DynOperation::RenameField {
+                table_name: "test_model".to_string(),
+                model_ty: parse_quote!(TestModel),
+                old_name: "field1".to_string(),
+                new_name: "title".to_string(),
+            }]
+        );
+        assert!(!operations[0].is_destructive());
+    }
+
+    #[test]
+    fn generate_operations_with_renamed_field_without_attribute() {
+        let migration_model = get_test_model();
+        let mut app_model = get_test_model();
+        app_model.model.fields[0].name = format_ident!("title");
+        app_model.model.fields[0].column_name = "title".to_string();
+
+        let (_, operations) =
+            MigrationGenerator::generate_operations(&vec![app_model], &vec![migration_model]);
+
+        assert_eq!(operations.len(), 2);
+        assert_eq!(
+            likely_renames(&operations),
+            vec![("test_model", "field1", "title")]
+        );
+    }
+
+    #[test]
+    fn repr_for_rename_field_operation() {
+        let op = DynOperation::RenameField {
+            table_name: "test_table".to_string(),
+            model_ty: parse_quote!(TestModel),
+            old_name: "old_field".to_string(),
+            new_name: "new_field".to_string(),
+        };
+
+        let expected = quote! {
+            ::cot::db::migrations::Operation::rename_field()
+                .table_name(::cot::db::Identifier::new("test_table"))
+                .old_name(::cot::db::Identifier::new("old_field"))
+                .new_name(::cot::db::Identifier::new("new_field"))
+                .build()
+        };
+        assert_eq!(op.repr().to_string(), expected.to_string());
+    }
     #[test]
     fn get_migration_list() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -1,10 +1,9 @@
 use std::path::Path;
 
 use heck::ToPascalCase;
-use rand::rngs::StdRng;
-use rand::{RngCore, SeedableRng};
 use tracing::trace;
 
+use crate::secret::{DEFAULT_SECRET_KEY_LENGTH, generate_secret_key};
 use crate::utils::{StatusType, print_status_msg};
 
 macro_rules! project_file {
@@ -68,7 +67,7 @@ pub fn new_project(
     let project_struct_name = format!("{}Project", project_name.to_pascal_case());
     let app_name = format!("{}App", project_name.to_pascal_case());
     let cot_source = cot_source.as_cargo_toml_source();
-    let dev_secret_key = generate_secret_key();
+    let dev_secret_key = generate_secret_key(DEFAULT_SECRET_KEY_LENGTH);
 
     for (file_name, content) in PROJECT_FILES {
         // Cargo reads and parses all files that are named "Cargo.toml" in a repository,
@@ -102,16 +101,6 @@ pub fn new_project(
     Ok(())
 }
 
-fn generate_secret_key() -> String {
-    // Cryptographically secure random number generator:
-    // https://rust-random.github.io/book/guide-rngs.html#cryptographically-secure-pseudo-random-number-generators-csprngs
-    // https://cheatsheetseries.owasp.org/cheatsheets/Cryptographic_Storage_Cheat_Sheet.html#secure-random-number-generation
-    let mut rng = StdRng::from_os_rng();
-    let mut key = [0u8; 32];
-    rng.fill_bytes(&mut key);
-    hex::encode(key)
-}
-
 #[cfg(test)]
 mod tests {
     use std::path::Path;
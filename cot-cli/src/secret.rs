@@ -0,0 +1,31 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// The default length of the generated secret keys, in bytes.
+pub const DEFAULT_SECRET_KEY_LENGTH: usize = 32;
+
+/// Generates a random secret key of `length` bytes, encoded as a hex string.
+#[must_use]
+pub fn generate_secret_key(length: usize) -> String {
+    // Cryptographically secure random number generator:
+    // https://rust-random.github.io/book/guide-rngs.html#cryptographically-secure-pseudo-random-number-generators-csprngs
+    // https://cheatsheetseries.owasp.org/cheatsheets/Cryptographic_Storage_Cheat_Sheet.html#secure-random-number-generation
+    let mut rng = StdRng::from_os_rng();
+    let mut key = vec![0u8; length];
+    rng.fill_bytes(&mut key);
+    hex::encode(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_secret_key_length() {
+        let key = generate_secret_key(16);
+
+        assert_eq!(key.len(), 32);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(key, generate_secret_key(16));
+    }
+}
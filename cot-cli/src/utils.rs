@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use anstyle::{AnsiColor, Color, Effects, Style};
@@ -12,6 +13,22 @@ pub(crate) fn print_status_msg(status: StatusType, message: &str) {
     eprintln!("{style}{status_str:>12}{style:#} {message}");
 }
 
+/// Asks the user a yes/no question on the terminal; the default answer is
+/// "no".
+pub(crate) fn confirm(question: &str) -> anyhow::Result<bool> {
+    eprint!("{question} [y/N] ");
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .context("unable to read the answer")?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum StatusType {
     // In-Progress Ops
@@ -28,7 +45,6 @@ pub(crate) enum StatusType {
     // Status types
     #[expect(dead_code)]
     Error, // Should be used in Error handling inside remove operations
-    #[expect(dead_code)]
     Warning, // Should be used as cautionary messages.
     Notice,
 }
@@ -249,6 +265,42 @@ impl WorkspaceManager {
         self.package_manifests.values().collect()
     }
 
+    /// Returns the member packages sorted so that each package comes after all
+    /// the workspace members it depends on. Packages that don't depend on each
+    /// other are sorted by name.
+    pub(crate) fn get_packages_in_dependency_order(&self) -> Vec<&PackageManager> {
+        let mut remaining: BTreeMap<&str, &PackageManager> = self
+            .package_manifests
+            .iter()
+            .map(|(name, package)| (name.as_str(), package))
+            .collect();
+        let mut sorted = Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let ready = remaining
+                .iter()
+                .find(|(_, package)| {
+                    !remaining
+                        .keys()
+                        .any(|&dependency| package.depends_on(dependency))
+                })
+                // if there is a dependency cycle (which cargo won't build anyway),
+                // just take the first package
+                .map_or_else(
+                    || {
+                        *remaining
+                            .keys()
+                            .next()
+                            .expect("remaining packages are not empty")
+                    },
+                    |(&name, _)| name,
+                );
+            sorted.push(remaining.remove(ready).expect("package should exist"));
+        }
+
+        sorted
+    }
+
     #[cfg(test)]
     pub(crate) fn get_root_manifest(&self) -> &Manifest {
         &self.root_manifest
@@ -290,6 +342,12 @@ impl PackageManager {
         self.package_root.as_path()
     }
 
+    /// Returns whether the package has a (normal) dependency with the given
+    /// name.
+    pub(crate) fn depends_on(&self, dependency: &str) -> bool {
+        self.manifest.dependencies.contains_key(dependency)
+    }
+
     pub(crate) fn get_manifest_path(&self) -> PathBuf {
         let path = &self.get_package_path().join("Cargo.toml");
         path.to_owned()
@@ -595,6 +653,51 @@ mod tests {
             let package = manager.get_package_manager_by_path(&non_existent);
             assert!(package.is_none());
         }
+
+        #[test]
+        #[cfg_attr(
+            miri,
+            ignore = "unsupported operation: can't call foreign function `OPENSSL_init_ssl` on OS `linux`"
+        )]
+        fn get_packages_in_dependency_order() {
+            let temp_dir = tempfile::TempDir::with_prefix("cot-test-").unwrap();
+            test_utils::make_workspace_package(temp_dir.path(), 3).unwrap();
+            let mut cargo_toml = std::fs::OpenOptions::new()
+                .append(true)
+                .open(
+                    temp_dir
+                        .path()
+                        .join("cargo-test-crate-1")
+                        .join("Cargo.toml"),
+                )
+                .unwrap();
+            writeln!(
+                cargo_toml,
+                r#"cargo-test-crate-3 = {{ path = "../cargo-test-crate-3" }}"#
+            )
+            .unwrap();
+            let CargoTomlManager::Workspace(manager) = CargoTomlManager::from_path(temp_dir.path())
+                .unwrap()
+                .unwrap()
+            else {
+                unreachable!()
+            };
+
+            let packages: Vec<_> = manager
+                .get_packages_in_dependency_order()
+                .into_iter()
+                .map(PackageManager::get_package_name)
+                .collect();
+
+            assert_eq!(
+                packages,
+                [
+                    "cargo-test-crate-2",
+                    "cargo-test-crate-3",
+                    "cargo-test-crate-1"
+                ]
+            );
+        }
     }
     mod package_manager {
         use super::*;
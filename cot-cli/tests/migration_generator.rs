@@ -124,6 +124,69 @@ fn create_models_foreign_key_cycle() {
     assert_eq!(field.name, "child");
 }
 
+#[test]
+fn create_models_many_to_many() {
+    let generator = test_generator();
+    let src = include_str!("migration_generator/many_to_many.rs");
+    let source_files = vec![SourceFile::parse(PathBuf::from("main.rs"), src).unwrap()];
+
+    let migration = generator
+        .generate_migrations_as_generated_from_files(source_files)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(migration.dependencies.len(), 0);
+    assert_eq!(migration.operations.len(), 3);
+
+    let mut table_names = migration.operations[..2]
+        .iter()
+        .map(|operation| unwrap_create_model(operation).0)
+        .collect::<Vec<_>>();
+    table_names.sort_unstable();
+    assert_eq!(table_names, ["cot__post", "cot__tag"]);
+
+    // The join table must be created after both sides of the relation
+    let (table_name, fields) = unwrap_create_model(&migration.operations[2]);
+    assert_eq!(table_name, "cot__post_tags");
+    assert_eq!(fields.len(), 3);
+
+    let field = &fields[0];
+    assert_eq!(field.column_name, "id");
+    assert!(field.primary_key);
+    assert!(field.auto_value);
+
+    let field = &fields[1];
+    assert_eq!(field.column_name, "source");
+    assert!(field.foreign_key.clone().is_some());
+
+    let field = &fields[2];
+    assert_eq!(field.column_name, "target");
+    assert!(field.foreign_key.clone().is_some());
+}
+
+/// Test that the migration generator can generate a migration with
+/// many-to-many relations which compiles successfully.
+#[test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: extern static `pidfd_spawnp` is not supported by Miri"
+)]
+fn many_to_many_compile_test() {
+    let generator = test_generator();
+    let src = include_str!("migration_generator/many_to_many.rs");
+    let source_files = vec![SourceFile::parse(PathBuf::from("main.rs"), src).unwrap()];
+
+    let MigrationAsSource {
+        name: migration_name,
+        content: migration_content,
+    } = generator
+        .generate_migrations_as_source_from_files(source_files)
+        .unwrap()
+        .unwrap();
+
+    compile_test(src, &migration_name, &migration_content);
+}
+
 #[test]
 fn create_models_foreign_key_two_migrations() {
     let generator = test_generator();
@@ -235,6 +298,8 @@ fn write_migrations_module() {
         MigrationGeneratorOptions {
             app_name: Some("cot".to_string()),
             output_dir: Some(tempdir.path().to_path_buf()),
+            allow_destructive: false,
+            no_input: true,
         },
     );
 
@@ -301,6 +366,8 @@ fn list_migrations() {
         MigrationGeneratorOptions {
             app_name: None,
             output_dir: None,
+            allow_destructive: false,
+            no_input: true,
         },
     )
     .unwrap();
@@ -312,6 +379,69 @@ fn list_migrations() {
     assert_eq!(migrations.get(package_name).unwrap()[0], "m_0001_initial");
 }
 
+#[test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: extern static `pidfd_spawnp` is not supported by Miri"
+)]
+fn make_migrations_workspace() {
+    let temp_dir = tempfile::TempDir::with_prefix("cot-test-").unwrap();
+    test_utils::make_workspace_package(temp_dir.path(), 3).unwrap();
+    // the third package doesn't depend on cot, so it should be skipped
+    for package in ["cargo-test-crate-1", "cargo-test-crate-2"] {
+        let package_path = temp_dir.path().join(package);
+        let mut cargo_toml = std::fs::OpenOptions::new()
+            .append(true)
+            .open(package_path.join("Cargo.toml"))
+            .unwrap();
+        writeln!(cargo_toml, r#"cot = "0.5""#).unwrap();
+        let mut main = std::fs::OpenOptions::new()
+            .append(true)
+            .open(package_path.join("src").join("main.rs"))
+            .unwrap();
+        write!(main, "{EXAMPLE_DATABASE_MODEL}").unwrap();
+    }
+
+    let summaries = migration_generator::make_migrations(
+        temp_dir.path(),
+        MigrationGeneratorOptions {
+            no_input: true,
+            ..MigrationGeneratorOptions::default()
+        },
+    )
+    .unwrap();
+
+    let apps: Vec<_> = summaries
+        .iter()
+        .map(|summary| summary.app.as_str())
+        .collect();
+    assert_eq!(apps, ["cargo-test-crate-1", "cargo-test-crate-2"]);
+    for summary in &summaries {
+        assert_eq!(summary.name, "m_0001_initial");
+        assert!(summary.path.exists());
+    }
+}
+
+#[test]
+fn make_migrations_workspace_with_app_name() {
+    let temp_dir = tempfile::TempDir::with_prefix("cot-test-").unwrap();
+    std::fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        test_utils::WORKSPACE_STUB,
+    )
+    .unwrap();
+
+    let result = migration_generator::make_migrations(
+        temp_dir.path(),
+        MigrationGeneratorOptions {
+            app_name: Some("app".to_string()),
+            ..MigrationGeneratorOptions::default()
+        },
+    );
+
+    assert!(result.is_err());
+}
+
 #[test]
 fn list_migrations_missing_cargo_toml() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,18 @@
+use cot::db::{model, Auto, ManyToMany};
+
+#[derive(Debug)]
+#[model]
+struct Post {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    tags: ManyToMany<Tag>,
+}
+
+#[derive(Debug)]
+#[model]
+struct Tag {
+    #[model(primary_key)]
+    id: Auto<i32>,
+}
+
+fn main() {}
@@ -63,6 +63,30 @@ fn help_migration_make() {
     );
 }
 
+#[test]
+fn help_migration_apply() {
+    insta::with_settings!(
+        { filters => GENERIC_FILTERS.to_owned() },
+        { assert_cmd_snapshot!(cot_cli!("help", "migration", "apply")) }
+    );
+}
+
+#[test]
+fn help_migration_status() {
+    insta::with_settings!(
+        { filters => GENERIC_FILTERS.to_owned() },
+        { assert_cmd_snapshot!(cot_cli!("help", "migration", "status")) }
+    );
+}
+
+#[test]
+fn help_migration_revert() {
+    insta::with_settings!(
+        { filters => GENERIC_FILTERS.to_owned() },
+        { assert_cmd_snapshot!(cot_cli!("help", "migration", "revert")) }
+    );
+}
+
 #[test]
 fn help_cli_manpages() {
     insta::with_settings!(
@@ -78,3 +102,19 @@ fn help_cli_completions() {
         { assert_cmd_snapshot!(cot_cli!("help", "cli", "completions")) }
     );
 }
+
+#[test]
+fn help_secret() {
+    insta::with_settings!(
+        { filters => GENERIC_FILTERS.to_owned() },
+        { assert_cmd_snapshot!(cot_cli!("help", "secret")) }
+    );
+}
+
+#[test]
+fn help_secret_generate() {
+    insta::with_settings!(
+        { filters => GENERIC_FILTERS.to_owned() },
+        { assert_cmd_snapshot!(cot_cli!("help", "secret", "generate")) }
+    );
+}
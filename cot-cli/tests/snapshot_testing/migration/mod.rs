@@ -46,6 +46,8 @@ fn migration_list_existing() {
         MigrationGeneratorOptions {
             app_name: None,
             output_dir: None,
+            allow_destructive: false,
+            no_input: true,
         },
     )
     .unwrap();
@@ -134,3 +136,39 @@ fn migration_new() {
         );
     }
 }
+
+#[test]
+fn migration_make_json() {
+    let temp_dir = tempfile::TempDir::with_prefix("cot-test-").unwrap();
+    let proj_path = temp_dir.path().join("cot-test");
+
+    test_utils::make_package(&proj_path).unwrap();
+    let mut main = std::fs::OpenOptions::new()
+        .append(true)
+        .open(proj_path.join("src").join("main.rs"))
+        .unwrap();
+    write!(main, "{EXAMPLE_DATABASE_MODEL}").unwrap();
+
+    let mut cli = cot_cli!("migration", "make", "--json", "--no-input");
+    insta::with_settings!(
+        { filters => [GENERIC_FILTERS, TEMP_PATH_FILTERS, TEMP_PROJECT_FILTERS].concat() },
+        { assert_cmd_snapshot!(cli.current_dir(&proj_path)) }
+    );
+
+    let mut cli = cot_cli!("migration", "list", "--json");
+    insta::with_settings!(
+        { filters => [GENERIC_FILTERS, TEMP_PATH_FILTERS, TEMP_PROJECT_FILTERS].concat() },
+        { assert_cmd_snapshot!("migration_list_json", cli.current_dir(&proj_path)) }
+    );
+}
+
+#[test]
+fn migration_make_json_error() {
+    let temp_dir = tempfile::TempDir::with_prefix("cot-test-").unwrap();
+
+    let mut cli = cot_cli!("migration", "make", "--json");
+    insta::with_settings!(
+        { filters => [GENERIC_FILTERS, TEMP_PATH_FILTERS, TEMP_PROJECT_FILTERS].concat() },
+        { assert_cmd_snapshot!(cli.current_dir(temp_dir.path())) }
+    );
+}
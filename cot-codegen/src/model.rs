@@ -1,5 +1,5 @@
 use darling::{FromDeriveInput, FromField, FromMeta};
-use heck::ToSnakeCase;
+use heck::{ToSnakeCase, ToUpperCamelCase};
 use syn::spanned::Spanned;
 
 use crate::symbol_resolver::SymbolResolver;
@@ -10,6 +10,126 @@ pub struct ModelArgs {
     #[darling(default)]
     pub model_type: ModelType,
     pub table_name: Option<String>,
+    pub workflow: Option<WorkflowArgs>,
+}
+
+/// The workflow of a model, declared with
+/// `#[model(workflow(draft -> review -> published, review -> draft))]`.
+///
+/// Each `a -> b` pair is an allowed transition; the first state is the initial
+/// one. The state is stored in the `state` field, unless another one is given
+/// with the `field = "name"` option.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WorkflowArgs {
+    pub field: Option<String>,
+    pub states: Vec<String>,
+    pub transitions: Vec<(String, String)>,
+}
+
+impl WorkflowArgs {
+    const DEFAULT_FIELD: &'static str = "state";
+
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
+        let mut args = Self {
+            field: None,
+            states: Vec::new(),
+            transitions: Vec::new(),
+        };
+
+        while !input.is_empty() {
+            let ident: syn::Ident = input.parse()?;
+            if input.peek(syn::Token![=]) {
+                input.parse::<syn::Token![=]>()?;
+                if ident != "field" {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("unknown workflow option `{ident}`"),
+                    ));
+                }
+                args.field = Some(input.parse::<syn::LitStr>()?.value());
+            } else {
+                args.add_state(&ident);
+                let mut from = ident;
+                while input.peek(syn::Token![->]) {
+                    input.parse::<syn::Token![->]>()?;
+                    let to: syn::Ident = input.parse()?;
+                    args.add_state(&to);
+                    args.add_transition(&from, &to)?;
+                    from = to;
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+
+        if args.states.is_empty() {
+            return Err(syn::Error::new(
+                input.span(),
+                "workflows must declare at least one state",
+            ));
+        }
+        Ok(args)
+    }
+
+    fn add_state(&mut self, state: &syn::Ident) {
+        let state = state.to_string();
+        if !self.states.contains(&state) {
+            self.states.push(state);
+        }
+    }
+
+    fn add_transition(&mut self, from: &syn::Ident, to: &syn::Ident) -> syn::Result<()> {
+        if from == to {
+            return Err(syn::Error::new(
+                to.span(),
+                format!("state `{to}` can't transition to itself"),
+            ));
+        }
+        let transition = (from.to_string(), to.to_string());
+        if self.transitions.contains(&transition) {
+            return Err(syn::Error::new(
+                to.span(),
+                format!("duplicate transition from `{from}` to `{to}`"),
+            ));
+        }
+        self.transitions.push(transition);
+        Ok(())
+    }
+
+    fn as_workflow(&self, model: &syn::Ident, fields: &[Field]) -> Result<Workflow, syn::Error> {
+        let field_name = self.field.as_deref().unwrap_or(Self::DEFAULT_FIELD);
+        let Some(field) = fields.iter().find(|field| field.name == field_name) else {
+            return Err(syn::Error::new(
+                model.span(),
+                format!(
+                    "workflow state field `{field_name}` not found; add a `{field_name}: String` \
+                    field to the model, or choose another field with `field = \"name\"`"
+                ),
+            ));
+        };
+
+        Ok(Workflow {
+            field: field.name.clone(),
+            states: self.states.clone(),
+            transitions: self.transitions.clone(),
+        })
+    }
+}
+
+impl FromMeta for WorkflowArgs {
+    fn from_meta(item: &syn::Meta) -> darling::Result<Self> {
+        match item {
+            syn::Meta::List(list) => list
+                .parse_args_with(Self::parse)
+                .map_err(darling::Error::from),
+            _ => Err(darling::Error::custom(
+                "expected a list of states, such as `workflow(draft -> published)`",
+            )
+            .with_span(item)),
+        }
+    }
 }
 
 #[expect(clippy::module_name_repetitions)]
@@ -69,12 +189,13 @@ impl ModelOpts {
         symbol_resolver: &SymbolResolver,
     ) -> Result<Model, syn::Error> {
         let self_reference = self.ident.to_string();
-        let as_field = |field: &&FieldOpts| field.as_field(symbol_resolver, Some(&self_reference));
-
-        let fields = self
+        let (many_to_many_fields, fields): (Vec<_>, Vec<_>) = self
             .fields()
-            .iter()
-            .map(as_field)
+            .into_iter()
+            .partition(|field| field.is_many_to_many());
+        let fields = fields
+            .into_iter()
+            .map(|field| field.as_field(symbol_resolver, Some(&self_reference)))
             .collect::<Result<Vec<_>, _>>()?;
 
         let mut original_name = self.ident.to_string();
@@ -96,6 +217,17 @@ impl ModelOpts {
         };
 
         let primary_key_field = self.get_primary_key_field(&fields)?;
+        let workflow = args
+            .workflow
+            .as_ref()
+            .map(|workflow| workflow.as_workflow(&self.ident, &fields))
+            .transpose()?;
+        let many_to_many_fields = many_to_many_fields
+            .into_iter()
+            .map(|field| {
+                field.as_many_to_many_field(symbol_resolver, &self_reference, &original_name)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         let ty = {
             let mut ty = syn::Type::Path(syn::TypePath {
@@ -115,6 +247,8 @@ impl ModelOpts {
             table_name,
             pk_field: primary_key_field.clone(),
             fields,
+            many_to_many_fields,
+            workflow,
         })
     }
 
@@ -145,6 +279,9 @@ pub struct FieldOpts {
     pub ty: syn::Type,
     pub primary_key: darling::util::Flag,
     pub unique: darling::util::Flag,
+    /// The previous name of the field, used by the migration generator to
+    /// rename the column instead of removing it and adding a new one.
+    pub rename_from: Option<String>,
 }
 
 impl FieldOpts {
@@ -193,6 +330,48 @@ impl FieldOpts {
         })
     }
 
+    /// Returns whether the field is a `cot::db::ManyToMany` relation.
+    ///
+    /// Only the last segment of the type path is checked, as the type is
+    /// usually not resolved to the full path when the model macro is
+    /// expanded.
+    #[must_use]
+    pub fn is_many_to_many(&self) -> bool {
+        if let syn::Type::Path(type_path) = &self.ty {
+            type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "ManyToMany")
+        } else {
+            false
+        }
+    }
+
+    /// Convert the field options into a many-to-many relation field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not have an identifier (i.e. it is a tuple
+    /// struct).
+    fn as_many_to_many_field(
+        &self,
+        symbol_resolver: &SymbolResolver,
+        self_reference: &String,
+        model_name: &str,
+    ) -> Result<ManyToManyField, syn::Error> {
+        let name = self.ident.as_ref().unwrap();
+        let mut resolved_ty = self.ty.clone();
+        symbol_resolver.resolve(&mut resolved_ty, Some(self_reference));
+        let to_model = single_generic_argument(&resolved_ty, "ManyToMany")?;
+
+        Ok(ManyToManyField {
+            name: name.clone(),
+            to_model,
+            join_model_name: format!("{model_name}{}", name.to_string().to_upper_camel_case()),
+        })
+    }
+
     /// Convert the field options into a field.
     ///
     /// # Panics
@@ -240,6 +419,12 @@ pub struct Model {
     pub table_name: String,
     pub pk_field: Field,
     pub fields: Vec<Field>,
+    /// The many-to-many relations of the model. These are not stored as
+    /// columns in the model's table, so they are not a part of
+    /// [`Self::fields`].
+    pub many_to_many_fields: Vec<ManyToManyField>,
+    /// The workflow of the model, if declared with `#[model(workflow(...))]`.
+    pub workflow: Option<Workflow>,
 }
 
 impl Model {
@@ -249,6 +434,17 @@ impl Model {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Workflow {
+    /// The field storing the current state.
+    pub field: syn::Ident,
+    /// The states, in the order of declaration; the first one is the initial
+    /// state.
+    pub states: Vec<String>,
+    /// The allowed transitions, as `(from, to)` pairs.
+    pub transitions: Vec<(String, String)>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Field {
     pub name: syn::Ident,
@@ -272,41 +468,70 @@ impl TryFrom<syn::Type> for ForeignKeySpec {
     type Error = syn::Error;
 
     fn try_from(ty: syn::Type) -> Result<Self, Self::Error> {
-        let syn::Type::Path(type_path) = &ty else {
-            panic!("Expected a path type for a foreign key");
-        };
+        Ok(Self {
+            to_model: single_generic_argument(&ty, "ForeignKey")?,
+        })
+    }
+}
 
-        let syn::PathArguments::AngleBracketed(args) = &type_path
-            .path
-            .segments
-            .last()
-            .expect("type path must have at least one segment")
-            .arguments
-        else {
-            return Err(syn::Error::new(
-                ty.span(),
-                "expected ForeignKey to have angle-bracketed generic arguments",
-            ));
-        };
+/// The name of the column in a many-to-many join table that references the
+/// model the relation is defined in.
+pub const MANY_TO_MANY_SOURCE_COLUMN: &str = "source";
+/// The name of the column in a many-to-many join table that references the
+/// related model.
+pub const MANY_TO_MANY_TARGET_COLUMN: &str = "target";
 
-        if args.args.len() != 1 {
-            return Err(syn::Error::new(
-                ty.span(),
-                "expected ForeignKey to have only one generic parameter",
-            ));
-        }
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManyToManyField {
+    pub name: syn::Ident,
+    pub to_model: syn::Type,
+    /// The name of the model representing the join table of the relation,
+    /// such as `PostTags` for the `tags` field of the `Post` model.
+    pub join_model_name: String,
+}
 
-        let inner = &args.args[0];
-        if let syn::GenericArgument::Type(ty) = inner {
-            Ok(Self {
-                to_model: ty.clone(),
-            })
-        } else {
-            Err(syn::Error::new(
-                ty.span(),
-                "expected ForeignKey to have a type generic argument",
-            ))
-        }
+impl ManyToManyField {
+    /// Returns the name of the join table, without the app name prefix.
+    #[must_use]
+    pub fn join_table_name(&self) -> String {
+        self.join_model_name.to_snake_case()
+    }
+}
+
+/// Returns the only generic argument of a type such as `ForeignKey<T>`.
+fn single_generic_argument(ty: &syn::Type, type_name: &str) -> Result<syn::Type, syn::Error> {
+    let syn::Type::Path(type_path) = ty else {
+        panic!("Expected a path type for a {type_name}");
+    };
+
+    let syn::PathArguments::AngleBracketed(args) = &type_path
+        .path
+        .segments
+        .last()
+        .expect("type path must have at least one segment")
+        .arguments
+    else {
+        return Err(syn::Error::new(
+            ty.span(),
+            format!("expected {type_name} to have angle-bracketed generic arguments"),
+        ));
+    };
+
+    if args.args.len() != 1 {
+        return Err(syn::Error::new(
+            ty.span(),
+            format!("expected {type_name} to have only one generic parameter"),
+        ));
+    }
+
+    let inner = &args.args[0];
+    if let syn::GenericArgument::Type(ty) = inner {
+        Ok(ty.clone())
+    } else {
+        Err(syn::Error::new(
+            ty.span(),
+            format!("expected {type_name} to have a type generic argument"),
+        ))
     }
 }
 
@@ -442,6 +667,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn model_opts_as_model_many_to_many() {
+        let input: syn::DeriveInput = parse_quote! {
+            struct Post {
+                #[model(primary_key)]
+                id: i32,
+                tags: ManyToMany<Tag>,
+                related_posts: cot::db::ManyToMany<Self>,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::default();
+        let resolver = SymbolResolver::new(vec![VisibleSymbol::new(
+            "Post",
+            "Post",
+            VisibleSymbolKind::Struct,
+        )]);
+        let model = opts.as_model(&args, &resolver).unwrap();
+
+        assert_eq!(model.fields.len(), 1);
+        assert_eq!(model.many_to_many_fields.len(), 2);
+
+        let tags = &model.many_to_many_fields[0];
+        assert_eq!(tags.name.to_string(), "tags");
+        assert_eq!(tags.to_model, parse_quote!(Tag));
+        assert_eq!(tags.join_model_name, "PostTags");
+        assert_eq!(tags.join_table_name(), "post_tags");
+
+        let related_posts = &model.many_to_many_fields[1];
+        assert_eq!(related_posts.to_model, parse_quote!(Post));
+        assert_eq!(related_posts.join_table_name(), "post_related_posts");
+    }
+
+    #[test]
+    fn model_opts_as_model_workflow() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(workflow(draft -> review -> published, review -> draft, field = "status"))]
+            struct Post {
+                #[model(primary_key)]
+                id: i32,
+                status: String,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let model = opts.as_model(&args, &SymbolResolver::new(vec![])).unwrap();
+
+        let workflow = model.workflow.unwrap();
+        assert_eq!(workflow.field.to_string(), "status");
+        assert_eq!(workflow.states, ["draft", "review", "published"]);
+        assert_eq!(
+            workflow.transitions,
+            [
+                ("draft".to_owned(), "review".to_owned()),
+                ("review".to_owned(), "published".to_owned()),
+                ("review".to_owned(), "draft".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn model_opts_as_model_workflow_missing_field() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(workflow(draft -> published))]
+            struct Post {
+                #[model(primary_key)]
+                id: i32,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let err = opts
+            .as_model(&args, &SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("workflow state field `state` not found")
+        );
+    }
+
+    #[test]
+    fn workflow_args_invalid() {
+        let parse = |meta: syn::Meta| WorkflowArgs::from_meta(&meta).unwrap_err().to_string();
+
+        assert_eq!(
+            parse(parse_quote!(workflow(draft -> draft))),
+            "state `draft` can't transition to itself"
+        );
+        assert_eq!(
+            parse(parse_quote!(workflow(draft -> published, draft -> published))),
+            "duplicate transition from `draft` to `published`"
+        );
+        assert_eq!(
+            parse(parse_quote!(workflow(draft, column = "status"))),
+            "unknown workflow option `column`"
+        );
+        assert_eq!(
+            parse(parse_quote!(workflow())),
+            "workflows must declare at least one state"
+        );
+    }
+
     #[test]
     fn field_opts_as_field() {
         let input: syn::Field = parse_quote! {
@@ -482,6 +809,7 @@ mod tests {
             ty: parse_quote! { MyContainer<std::string::String> },
             primary_key: darling::util::Flag::default(),
             unique: darling::util::Flag::default(),
+            rename_from: None,
         };
 
         assert!(opts.find_type("my_crate::MyContainer", &resolver).is_some());
@@ -10,8 +10,8 @@ use std::pin::Pin;
 
 use tower::util::BoxCloneSyncService;
 
-use crate::request::Request;
 use crate::request::extractors::{FromRequest, FromRequestHead};
+use crate::request::{ExpectedPathParams, Request};
 use crate::response::{IntoResponse, Response};
 use crate::{Error, Result};
 
@@ -51,6 +51,17 @@ pub trait RequestHandler<T = ()> {
     ///
     /// This method can return an error if it fails to handle the request.
     fn handle(&self, request: Request) -> impl Future<Output = Result<Response>> + Send;
+
+    /// Returns the path parameters the route must have for the extractors of
+    /// this handler to succeed.
+    ///
+    /// This is used to verify that the routes provide the path parameters
+    /// their handlers need. The default implementation returns an empty list,
+    /// which means there is nothing to verify.
+    #[must_use]
+    fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+        Vec::new()
+    }
 }
 
 pub trait BoxRequestHandler {
@@ -105,6 +116,12 @@ macro_rules! impl_request_handler {
 
                 self.clone()($($ty,)*).await.into_response()
             }
+
+            fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+                std::iter::empty()
+                    $(.chain(<$ty as FromRequestHead>::expected_path_params()))*
+                    .collect()
+            }
         }
     };
 }
@@ -143,6 +160,13 @@ macro_rules! impl_request_handler_from_request {
 
                 self.clone()($($ty_lhs,)* $ty_from_request, $($ty_rhs),*).await.into_response()
             }
+
+            fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+                std::iter::empty()
+                    $(.chain(<$ty_lhs as FromRequestHead>::expected_path_params()))*
+                    $(.chain(<$ty_rhs as FromRequestHead>::expected_path_params()))*
+                    .collect()
+            }
         }
     };
 }
@@ -250,6 +250,73 @@ impl HtmlTag {
         self
     }
 
+    /// Sets an attribute of the HTML tag, replacing its value if the
+    /// attribute already exists.
+    ///
+    /// # Safety
+    ///
+    /// This function escapes the attribute value. Note that it does not
+    /// escape the attribute name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::HtmlTag;
+    ///
+    /// let mut tag = HtmlTag::input("text");
+    /// tag.set_attr("type", "search").set_attr("class", "input");
+    /// assert_eq!(
+    ///     tag.render().as_str(),
+    ///     "<input type=\"search\" class=\"input\"/>"
+    /// );
+    /// ```
+    pub fn set_attr<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) -> &mut Self {
+        let key = key.into();
+        let value = value.into();
+        if let Some((_, existing)) = self.attributes.iter_mut().find(|(k, _)| k == &key) {
+            *existing = value;
+        } else {
+            self.attributes.push((key, value));
+        }
+        self
+    }
+
+    /// Returns the value of an attribute of the HTML tag, if it exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::HtmlTag;
+    ///
+    /// let tag = HtmlTag::input("text");
+    /// assert_eq!(tag.get_attr("type"), Some("text"));
+    /// assert_eq!(tag.get_attr("value"), None);
+    /// ```
+    #[must_use]
+    pub fn get_attr(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns whether the HTML tag has the given boolean attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::HtmlTag;
+    ///
+    /// let mut tag = HtmlTag::input("text");
+    /// tag.bool_attr("required");
+    /// assert!(tag.has_bool_attr("required"));
+    /// assert!(!tag.has_bool_attr("disabled"));
+    /// ```
+    #[must_use]
+    pub fn has_bool_attr(&self, key: &str) -> bool {
+        self.boolean_attributes.iter().any(|k| k == key)
+    }
+
     fn push_child(&mut self, node: HtmlNode) -> &mut Self {
         self.children.push(node);
         self
@@ -423,6 +490,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_html_tag_set_attr() {
+        let mut tag = HtmlTag::new("input");
+        tag.attr("type", "text").attr("class", "a");
+        tag.set_attr("class", "b")
+            .set_attr("placeholder", "Enter text");
+        assert_eq!(tag.get_attr("class"), Some("b"));
+        assert_eq!(
+            tag.render().as_str(),
+            "<input type=\"text\" class=\"b\" placeholder=\"Enter text\"/>"
+        );
+    }
+
     #[test]
     fn test_html_tag_escaping() {
         let mut tag = HtmlTag::new("input");
@@ -265,6 +265,76 @@ impl PathParams {
     }
 }
 
+/// The path parameters a route must have so that they can be deserialized
+/// into a given type.
+///
+/// This is returned by [`FromRequestHead::expected_path_params`] and used to
+/// verify that the routes provide the parameters their handlers need.
+///
+/// [`FromRequestHead::expected_path_params`]: extractors::FromRequestHead::expected_path_params
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::ExpectedPathParams;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Params {
+///     user_id: i32,
+///     post_id: i32,
+/// }
+///
+/// assert_eq!(
+///     ExpectedPathParams::of::<Params>(),
+///     Some(ExpectedPathParams::Named(&["user_id", "post_id"]))
+/// );
+/// assert_eq!(
+///     ExpectedPathParams::of::<(i32, String)>(),
+///     Some(ExpectedPathParams::Count(2))
+/// );
+/// assert!(ExpectedPathParams::Count(1).matches(&["todo_id"]));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ExpectedPathParams {
+    /// The given number of parameters, with any names (e.g. for tuples and
+    /// single values).
+    Count(usize),
+    /// Parameters with the given names (e.g. for structs).
+    Named(&'static [&'static str]),
+}
+
+impl ExpectedPathParams {
+    /// Returns the path parameters needed to deserialize `T`, or [`None`] if
+    /// `T` accepts any parameters (e.g. for maps) or can't be deserialized
+    /// from path parameters at all.
+    #[must_use]
+    pub fn of<T: serde::de::DeserializeOwned>() -> Option<Self> {
+        path_params_deserializer::expected_path_params::<T>()
+    }
+
+    /// Returns `true` if a route with parameters with given names provides the
+    /// expected parameters.
+    #[must_use]
+    pub fn matches(&self, param_names: &[&str]) -> bool {
+        match self {
+            Self::Count(count) => param_names.len() == *count,
+            Self::Named(names) => {
+                param_names.len() == names.len()
+                    && names.iter().all(|name| param_names.contains(name))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ExpectedPathParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Count(count) => write!(f, "{count} parameter(s)"),
+            Self::Named(names) => write!(f, "{} parameter(s) ({})", names.len(), names.join(", ")),
+        }
+    }
+}
+
 /// An error that occurs when deserializing path parameters.
 #[derive(Debug, Clone, thiserror::Error)]
 #[error("could not parse path parameters: {0}")]
@@ -54,7 +54,7 @@ use serde::de::DeserializeOwned;
 
 #[cfg(feature = "json")]
 use crate::json::Json;
-use crate::request::{InvalidContentType, PathParams, Request, RequestHead};
+use crate::request::{ExpectedPathParams, InvalidContentType, PathParams, Request, RequestHead};
 use crate::{Body, Method};
 
 pub trait FromRequest: Sized {
@@ -85,6 +85,17 @@ pub trait FromRequestHead: Sized {
     /// Throws an error if the extractor fails to extract the data from the
     /// request head.
     fn from_request_head(head: &RequestHead) -> impl Future<Output = crate::Result<Self>> + Send;
+
+    /// Returns the path parameters the route must have for this extractor to
+    /// succeed, or [`None`] if the extractor doesn't need any specific path
+    /// parameters.
+    ///
+    /// This is used to verify that the routes provide the path parameters
+    /// their handlers need. The default implementation returns [`None`].
+    #[must_use]
+    fn expected_path_params() -> Option<ExpectedPathParams> {
+        None
+    }
 }
 
 /// An extractor that extracts data from the URL params.
@@ -92,6 +103,11 @@ pub trait FromRequestHead: Sized {
 /// The extractor is generic over a type that implements
 /// [`DeserializeOwned`].
 ///
+/// The `verify` command of the project CLI checks that the route of each
+/// handler has the path parameters its `Path` extractor expects: the same
+/// number of parameters for single values and tuples, and the same names for
+/// structs.
+///
 /// # Examples
 ///
 /// ```
@@ -140,6 +156,10 @@ impl<D: DeserializeOwned> FromRequestHead for Path<D> {
             .parse()?;
         Ok(Self(params))
     }
+
+    fn expected_path_params() -> Option<ExpectedPathParams> {
+        ExpectedPathParams::of::<D>()
+    }
 }
 
 /// An extractor that extracts data from the URL query parameters.
@@ -207,6 +227,69 @@ where
 struct QueryParametersParseError(serde_path_to_error::Error<serde::de::value::Error>);
 impl_into_cot_error!(QueryParametersParseError, BAD_REQUEST);
 
+/// An extractor that gets a typed value out of the request extensions.
+///
+/// The value is cloned from the extensions, so this is typically used with
+/// cheaply cloneable values inserted by a middleware, such as the currently
+/// logged in user or the ID of a tenant.
+///
+/// # Errors
+///
+/// Throws an error if the request extensions don't contain a value of type
+/// `T`. Since this typically means a middleware hasn't been added to the
+/// project, the error results in a 500 Internal Server Error response.
+///
+/// # Examples
+///
+/// ```
+/// use cot::RequestHandler;
+/// use cot::html::Html;
+/// use cot::request::extractors::Extension;
+/// use cot::test::TestRequestBuilder;
+///
+/// #[derive(Debug, Clone)]
+/// struct TenantId(i32);
+///
+/// async fn my_handler(Extension(TenantId(tenant_id)): Extension<TenantId>) -> Html {
+///     Html::new(format!("Tenant {tenant_id}"))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let mut request = TestRequestBuilder::get("/").build();
+/// request.extensions_mut().insert(TenantId(42));
+///
+/// assert_eq!(
+///     my_handler
+///         .handle(request)
+///         .await?
+///         .into_body()
+///         .into_bytes()
+///         .await?,
+///     "Tenant 42"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Extension<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> FromRequestHead for Extension<T> {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        let value = head
+            .extensions
+            .get::<T>()
+            .cloned()
+            .ok_or(MissingExtension(std::any::type_name::<T>()))?;
+        Ok(Self(value))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("request extension of type `{0}` not found")]
+struct MissingExtension(&'static str);
+impl_into_cot_error!(MissingExtension);
+
 /// Extractor that gets the request body as JSON and deserializes it into a type
 /// `T` implementing [`DeserializeOwned`].
 ///
@@ -335,6 +418,39 @@ impl FromRequestHead for Method {
 ///     static_files: StaticFiles,
 /// }
 /// ```
+///
+/// # Field attributes
+///
+/// The way each field is extracted can be customized with the
+/// `#[from_request(...)]` attribute:
+///
+/// - `extension` – the value is cloned from the request extensions, as with
+///   the [`Extension`] extractor. This is useful for values inserted by
+///   middlewares.
+/// - `path` or `path = "name"` – the value is deserialized from a single path
+///   parameter, named the same as the field or as given explicitly.
+/// - `optional` – the field must be of type `Option<T>`; `T` is extracted and
+///   the field is set to `None` if the extraction fails.
+/// - `default` or `default = "path::to::fn"` – if the extraction fails, the
+///   field is set to [`Default::default()`] or to the value returned by the
+///   given function.
+///
+/// ```no_run
+/// use cot::request::extractors::FromRequestHead;
+/// use cot::router::Urls;
+///
+/// #[derive(Debug, Clone)]
+/// struct CurrentUser(String);
+///
+/// #[derive(Debug, FromRequestHead)]
+/// struct PostContext {
+///     urls: Urls,
+///     #[from_request(extension, optional)]
+///     user: Option<CurrentUser>,
+///     #[from_request(path = "id")]
+///     post_id: i32,
+/// }
+/// ```
 pub use cot_macros::FromRequestHead;
 
 use crate::error::impl_into_cot_error;
@@ -471,6 +587,35 @@ mod tests {
         assert!(matches!(result, UrlQuery(_)));
     }
 
+    #[cot::test]
+    async fn extension_extraction() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct TenantId(i32);
+
+        let (mut head, _body) = Request::new(Body::empty()).into_parts();
+        head.extensions.insert(TenantId(42));
+
+        let Extension(tenant_id): Extension<TenantId> =
+            Extension::from_request_head(&head).await.unwrap();
+
+        assert_eq!(tenant_id, TenantId(42));
+    }
+
+    #[cot::test]
+    async fn extension_missing() {
+        let (head, _body) = Request::new(Body::empty()).into_parts();
+
+        let error = Extension::<i32>::from_request_head(&head)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status_code(), http::StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            error.to_string(),
+            "request extension of type `i32` not found"
+        );
+    }
+
     #[cfg(feature = "json")]
     #[cot::test]
     async fn json_invalid_content_type() {
@@ -1,10 +1,12 @@
 use std::fmt::Display;
 
 use serde::Deserializer;
-use serde::de::{DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
 use thiserror::Error;
 
-use crate::request::PathParams;
+use crate::request::{ExpectedPathParams, PathParams};
 
 /// An error that occurs when deserializing path parameters.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Error)]
@@ -638,6 +640,131 @@ impl<'de> VariantAccess<'de> for UnitVariant {
     }
 }
 
+/// Returns the path parameters needed to deserialize `T`.
+///
+/// This runs the deserialization of `T` with a deserializer that doesn't
+/// provide any data, but instead records the first request it gets from the
+/// type and aborts. The request tells what [`PathParamsDeserializer`] would
+/// need to have in order to succeed.
+pub(super) fn expected_path_params<T: DeserializeOwned>() -> Option<ExpectedPathParams> {
+    match T::deserialize(ExpectedParamsDeserializer) {
+        Ok(_) => None,
+        Err(ExpectedParamsFound(expected)) => expected,
+    }
+}
+
+/// The "error" used to abort the deserialization in [`expected_path_params`].
+#[derive(Debug, Error)]
+#[error("expected path parameters found")]
+struct ExpectedParamsFound(Option<ExpectedPathParams>);
+
+impl serde::de::Error for ExpectedParamsFound {
+    fn custom<T>(_msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self(None)
+    }
+}
+
+#[derive(Debug)]
+struct ExpectedParamsDeserializer;
+
+macro_rules! expect_params {
+    ($deserialize_fn_name:ident, $expected:expr) => {
+        fn $deserialize_fn_name<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            Err(ExpectedParamsFound($expected))
+        }
+    };
+}
+
+impl<'de> Deserializer<'de> for ExpectedParamsDeserializer {
+    type Error = ExpectedParamsFound;
+
+    // single values, which need exactly one parameter
+    expect_params!(deserialize_any, Some(ExpectedPathParams::Count(1)));
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf ignored_any
+    }
+
+    // types that accept any parameters or can't be deserialized from them
+    expect_params!(deserialize_option, None);
+    expect_params!(deserialize_unit, None);
+    expect_params!(deserialize_seq, None);
+    expect_params!(deserialize_map, None);
+    expect_params!(deserialize_identifier, None);
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ExpectedParamsFound(None))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ExpectedParamsFound(Some(ExpectedPathParams::Count(len))))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ExpectedParamsFound(Some(ExpectedPathParams::Count(len))))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ExpectedParamsFound(Some(ExpectedPathParams::Named(fields))))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ExpectedParamsFound(Some(ExpectedPathParams::Count(1))))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -866,4 +993,55 @@ mod tests {
 
         path_params
     }
+
+    #[test]
+    fn expected_path_params_single_value() {
+        assert_eq!(
+            expected_path_params::<i32>(),
+            Some(ExpectedPathParams::Count(1))
+        );
+        assert_eq!(
+            expected_path_params::<String>(),
+            Some(ExpectedPathParams::Count(1))
+        );
+    }
+
+    #[test]
+    fn expected_path_params_tuple() {
+        assert_eq!(
+            expected_path_params::<(String, i32, bool)>(),
+            Some(ExpectedPathParams::Count(3))
+        );
+    }
+
+    #[test]
+    fn expected_path_params_struct() {
+        #[derive(Deserialize)]
+        struct Params {
+            #[expect(dead_code)]
+            user_id: i32,
+            #[expect(dead_code)]
+            #[serde(rename = "post")]
+            post_id: i32,
+        }
+
+        #[derive(Deserialize)]
+        struct Wrapper(#[expect(dead_code)] Params);
+
+        assert_eq!(
+            expected_path_params::<Params>(),
+            Some(ExpectedPathParams::Named(&["user_id", "post"]))
+        );
+        assert_eq!(
+            expected_path_params::<Wrapper>(),
+            Some(ExpectedPathParams::Named(&["user_id", "post"]))
+        );
+    }
+
+    #[test]
+    fn expected_path_params_any() {
+        assert_eq!(expected_path_params::<HashMap<String, String>>(), None);
+        assert_eq!(expected_path_params::<Vec<String>>(), None);
+        assert_eq!(expected_path_params::<()>(), None);
+    }
 }
@@ -23,10 +23,17 @@ pub(super) fn impl_admin_model_for_struct(ast: &syn::DeriveInput) -> TokenStream
 }
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(forward_attrs(allow, doc, cfg), supports(struct_named))]
+#[darling(
+    attributes(admin),
+    forward_attrs(allow, doc, cfg),
+    supports(struct_named)
+)]
 struct AdminModelOpts {
     ident: syn::Ident,
     data: darling::ast::Data<darling::util::Ignored, FieldOpts>,
+    /// Whether the model implements `Workflow`, so that its transitions are
+    /// available in the admin panel.
+    workflow: darling::util::Flag,
 }
 
 impl AdminModelOpts {
@@ -42,6 +49,7 @@ impl AdminModelOpts {
         AdminModelDeriveBuilder {
             name: self.ident.clone(),
             primary_key: None,
+            workflow: self.workflow.is_present(),
         }
     }
 }
@@ -50,6 +58,7 @@ impl AdminModelOpts {
 struct AdminModelDeriveBuilder {
     name: syn::Ident,
     primary_key: Option<FieldOpts>,
+    workflow: bool,
 }
 
 impl ToTokens for AdminModelDeriveBuilder {
@@ -93,6 +102,11 @@ impl AdminModelDeriveBuilder {
             )
             .into_compile_error();
         };
+        let workflow_impl = if self.workflow {
+            build_workflow_impl()
+        } else {
+            TokenStream::new()
+        };
 
         quote! {
             #[#crate_ident::__private::async_trait]
@@ -204,6 +218,8 @@ impl AdminModelDeriveBuilder {
 
                     Ok(())
                 }
+
+                #workflow_impl
             }
 
             fn parse_id<T>(id: &str) -> #crate_ident::Result<<T as #crate_ident::db::Model>::PrimaryKey>
@@ -224,3 +240,36 @@ impl AdminModelDeriveBuilder {
         }
     }
 }
+
+/// Builds the `AdminModel` methods for the models with a workflow.
+fn build_workflow_impl() -> TokenStream {
+    let crate_ident = cot_ident();
+
+    quote! {
+        fn workflow_transitions(
+            &self,
+            user: &dyn #crate_ident::auth::User,
+        ) -> ::std::vec::Vec<#crate_ident::workflow::WorkflowTransition> {
+            <Self as #crate_ident::workflow::Workflow>::available_transitions(self, user)
+        }
+
+        async fn workflow_transition(
+            &mut self,
+            request: &#crate_ident::request::Request,
+            user: &(dyn #crate_ident::auth::User + ::core::marker::Send + ::core::marker::Sync),
+            to: &str,
+        ) -> #crate_ident::Result<()> {
+            use #crate_ident::request::RequestExt;
+
+            let context = request.context();
+            <Self as #crate_ident::workflow::Workflow>::transition(
+                self,
+                context.database(),
+                context.notifier(),
+                user,
+                to,
+            )
+            .await
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::cot_ident;
+
+pub(super) fn impl_api_serialize_for_struct(ast: &syn::DeriveInput) -> TokenStream {
+    let opts = match ApiSerializeOpts::from_derive_input(ast) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+    let cot = cot_ident();
+
+    let struct_name = &opts.ident;
+    let fields = opts
+        .data
+        .as_ref()
+        .take_struct()
+        .expect("Only structs are supported")
+        .fields;
+    let entries = fields
+        .into_iter()
+        .filter(|field| !field.exclude)
+        .map(|field| field.as_map_entry(&cot));
+
+    quote! {
+        #[automatically_derived]
+        impl #cot::serialize::ApiSerialize for #struct_name {
+            #[allow(unused_variables, reason = "not all structs have fields limited to profiles")]
+            fn serialize_profile<S: #cot::__private::serde::Serializer>(
+                &self,
+                profile: &str,
+                serializer: S,
+            ) -> ::core::result::Result<S::Ok, S::Error> {
+                use #cot::__private::serde::ser::SerializeMap;
+
+                let mut map = serializer.serialize_map(::core::option::Option::None)?;
+                #(#entries)*
+                map.end()
+            }
+        }
+    }
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(forward_attrs(allow, doc, cfg), supports(struct_named))]
+struct ApiSerializeOpts {
+    ident: syn::Ident,
+    data: darling::ast::Data<darling::util::Ignored, Field>,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(api))]
+struct Field {
+    ident: Option<syn::Ident>,
+    #[darling(default)]
+    exclude: bool,
+    #[darling(default)]
+    rename: Option<String>,
+    #[darling(multiple)]
+    profile: Vec<String>,
+    #[darling(default)]
+    nested: bool,
+}
+
+impl Field {
+    fn as_map_entry(&self, cot: &TokenStream) -> TokenStream {
+        let ident = self
+            .ident
+            .as_ref()
+            .expect("Only structs with named fields are supported");
+        let name = self
+            .rename
+            .clone()
+            .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_owned());
+
+        let value = if self.nested {
+            quote! { &#cot::serialize::ProfiledRef::new(&self.#ident, profile) }
+        } else {
+            quote! { &self.#ident }
+        };
+        let entry = quote! { map.serialize_entry(#name, #value)?; };
+
+        if self.profile.is_empty() {
+            entry
+        } else {
+            let profiles = &self.profile;
+            quote! {
+                if ::core::matches!(profile, #(#profiles)|*) {
+                    #entry
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::cot_ident;
+
+pub(super) fn impl_factory_for_struct(ast: &syn::DeriveInput) -> TokenStream {
+    let opts = match FactoryOpts::from_derive_input(ast) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+    let cot = cot_ident();
+
+    let model_name = &opts.ident;
+    let vis = &opts.vis;
+    let builder_name = format_ident!("{model_name}Factory");
+    let fields = opts
+        .data
+        .as_ref()
+        .take_struct()
+        .expect("Only structs are supported")
+        .fields;
+
+    let builder_fields = fields.iter().map(|field| {
+        let ident = field.ident();
+        let ty = &field.ty;
+        quote! { #ident: ::core::option::Option<#ty> }
+    });
+    let setters = fields.iter().map(|field| {
+        let ident = field.ident();
+        let ty = &field.ty;
+        let doc = format!("Sets the value of the `{ident}` field.");
+        quote! {
+            #[doc = #doc]
+            #[must_use]
+            #vis fn #ident(mut self, #ident: impl ::core::convert::Into<#ty>) -> Self {
+                self.#ident = ::core::option::Option::Some(#ident.into());
+                self
+            }
+        }
+    });
+    let field_names: Vec<_> = fields.iter().map(|field| field.ident()).collect();
+    let build_values = fields
+        .iter()
+        .map(|field| field.default_value(&cot, model_name, false));
+    let create_values = fields
+        .iter()
+        .map(|field| field.default_value(&cot, model_name, true));
+    let builder_doc = format!(
+        "A factory building [`{model_name}`] instances for tests, generated by the \
+        `Factory` derive macro."
+    );
+
+    quote! {
+        #[doc = #builder_doc]
+        #[derive(::core::default::Default)]
+        #vis struct #builder_name {
+            #(#builder_fields,)*
+        }
+
+        impl #builder_name {
+            #(#setters)*
+        }
+
+        #[automatically_derived]
+        impl #cot::test::factory::Factory for #model_name {
+            type Builder = #builder_name;
+        }
+
+        #[automatically_derived]
+        #[#cot::__private::async_trait]
+        impl #cot::test::factory::FactoryBuilder for #builder_name {
+            type Model = #model_name;
+
+            fn build(self) -> #model_name {
+                #model_name {
+                    #(#field_names: #build_values,)*
+                }
+            }
+
+            async fn create<DB: #cot::db::DatabaseBackend>(
+                self,
+                db: &DB,
+            ) -> #cot::db::Result<#model_name> {
+                use #cot::db::Model;
+
+                let mut model = #model_name {
+                    #(#field_names: #create_values,)*
+                };
+                model.insert(db).await?;
+                ::core::result::Result::Ok(model)
+            }
+        }
+    }
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(forward_attrs(allow, doc, cfg), supports(struct_named))]
+struct FactoryOpts {
+    ident: syn::Ident,
+    vis: syn::Visibility,
+    data: darling::ast::Data<darling::util::Ignored, Field>,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(factory))]
+struct Field {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    /// An expression used as the value of the field if it's not set.
+    #[darling(default)]
+    default: Option<syn::Expr>,
+    /// A `fake` crate faker used to generate the value of the field if it's
+    /// not set.
+    #[darling(default)]
+    fake: Option<syn::Expr>,
+}
+
+impl Field {
+    fn ident(&self) -> &syn::Ident {
+        self.ident
+            .as_ref()
+            .expect("Only structs with named fields are supported")
+    }
+
+    /// Returns the expression that evaluates to the value of the field, using
+    /// the value set in the builder, or a generated one if it's not set.
+    ///
+    /// If `create` is `true`, the expression is evaluated inside the
+    /// `create` method, so the referenced models are saved to the database.
+    fn default_value(
+        &self,
+        cot: &TokenStream,
+        model_name: &syn::Ident,
+        create: bool,
+    ) -> TokenStream {
+        let ident = self.ident();
+        let ty = &self.ty;
+
+        let generated = if let Some(default) = &self.default {
+            quote! { #default }
+        } else if let Some(fake) = &self.fake {
+            quote! { #cot::__private::fake::Fake::fake::<#ty>(&(#fake)) }
+        } else {
+            match last_segment(ty).map(|segment| (segment.ident.to_string(), segment)) {
+                Some((name, _)) if name == "Auto" => quote! { #cot::db::Auto::auto() },
+                Some((name, _)) if name == "Option" => quote! { ::core::option::Option::None },
+                Some((name, _)) if name == "ManyToMany" => {
+                    quote! { ::core::default::Default::default() }
+                }
+                Some((name, segment)) if name == "ForeignKey" => {
+                    let Some(to_model) = single_generic_argument(segment) else {
+                        return syn::Error::new_spanned(
+                            ty,
+                            "expected ForeignKey to have exactly one generic argument",
+                        )
+                        .into_compile_error();
+                    };
+                    if is_self_reference(to_model, model_name) {
+                        let message = format!(
+                            "the `{ident}` field refers to the model itself, so it has to be set \
+                            explicitly"
+                        );
+                        quote! { ::core::panic!(#message) }
+                    } else if create {
+                        quote! {
+                            #cot::db::ForeignKey::from(
+                                <#to_model as #cot::test::factory::Factory>::create(db).await?
+                            )
+                        }
+                    } else {
+                        quote! {
+                            #cot::db::ForeignKey::from(
+                                <#to_model as #cot::test::factory::Factory>::build()
+                            )
+                        }
+                    }
+                }
+                Some((name, _)) if name == "String" => string_faker(cot, &ident.to_string()),
+                _ => quote! {
+                    #cot::__private::fake::Fake::fake::<#ty>(&#cot::__private::fake::Faker)
+                },
+            }
+        };
+
+        quote! {
+            match self.#ident {
+                ::core::option::Option::Some(value) => value,
+                ::core::option::Option::None => #generated,
+            }
+        }
+    }
+}
+
+/// Returns a faker generating a realistic value for a string field, based on
+/// the name of the field.
+fn string_faker(cot: &TokenStream, field_name: &str) -> TokenStream {
+    let faker = match field_name {
+        "email" => quote! { internet::en::SafeEmail() },
+        name if name.ends_with("_email") => quote! { internet::en::SafeEmail() },
+        "username" => quote! { internet::en::Username() },
+        "first_name" => quote! { name::en::FirstName() },
+        "last_name" => quote! { name::en::LastName() },
+        "name" | "full_name" => quote! { name::en::Name() },
+        "title" => quote! { lorem::en::Sentence(2..6) },
+        "description" | "content" | "body" => quote! { lorem::en::Paragraph(1..3) },
+        _ => {
+            return quote! {
+                #cot::__private::fake::Fake::fake::<::std::string::String>(
+                    &#cot::__private::fake::Faker
+                )
+            };
+        }
+    };
+
+    quote! {
+        #cot::__private::fake::Fake::fake::<::std::string::String>(
+            &#cot::__private::fake::faker::#faker
+        )
+    }
+}
+
+fn last_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+    if let syn::Type::Path(type_path) = ty {
+        type_path.path.segments.last()
+    } else {
+        None
+    }
+}
+
+fn single_generic_argument(segment: &syn::PathSegment) -> Option<&syn::Type> {
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(syn::GenericArgument::Type(ty)) if args.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
+
+fn is_self_reference(ty: &syn::Type, model_name: &syn::Ident) -> bool {
+    last_segment(ty).is_some_and(|segment| segment.ident == "Self" || segment.ident == *model_name)
+}
@@ -5,6 +5,7 @@ use darling::{FromDeriveInput, FromField};
 use heck::ToTitleCase;
 use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
+use syn::ext::IdentExt;
 
 use crate::cot_ident;
 
@@ -16,6 +17,26 @@ pub(super) fn impl_form_for_struct(ast: &syn::DeriveInput) -> TokenStream {
         }
     };
 
+    let field_idents: Vec<_> = opts
+        .fields()
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .collect();
+    let mut errors = darling::Error::accumulator();
+    for field in opts.fields() {
+        for other in [&field.min_from, &field.max_from].into_iter().flatten() {
+            if !field_idents.contains(&other) || field.ident.as_ref() == Some(other) {
+                errors.push(
+                    darling::Error::custom(format!("`{other}` must be another field of this form"))
+                        .with_span(other),
+                );
+            }
+        }
+    }
+    if let Err(err) = errors.finish() {
+        return err.write_errors();
+    }
+
     let mut builder = opts.as_form_derive_builder();
     for field in opts.fields() {
         builder.push_field(field);
@@ -25,10 +46,15 @@ pub(super) fn impl_form_for_struct(ast: &syn::DeriveInput) -> TokenStream {
 }
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(forward_attrs(allow, doc, cfg), supports(struct_named))]
+#[darling(
+    attributes(form),
+    forward_attrs(allow, doc, cfg),
+    supports(struct_named)
+)]
 struct FormOpts {
     ident: syn::Ident,
     data: darling::ast::Data<darling::util::Ignored, Field>,
+    validate: Option<syn::Path>,
 }
 
 impl FormOpts {
@@ -54,6 +80,8 @@ impl FormOpts {
             fields_as_context_from_request: Vec::with_capacity(self.field_count()),
             fields_as_from_context_vars: Vec::with_capacity(self.field_count()),
             fields_as_from_context: Vec::with_capacity(self.field_count()),
+            fields_as_cross_field_checks: Vec::new(),
+            validate: self.validate.clone(),
             fields_as_to_context: Vec::with_capacity(self.field_count()),
             fields_as_errors: Vec::with_capacity(self.field_count()),
             fields_as_errors_for: Vec::with_capacity(self.field_count()),
@@ -72,6 +100,32 @@ struct Field {
     ident: Option<syn::Ident>,
     ty: syn::Type,
     opts: Option<HashMap<syn::Ident, PreservedStrExpr>>,
+    help_text: Option<String>,
+    widget: Option<syn::Ident>,
+    // `attrs` is reserved by darling for the forwarded attributes
+    #[darling(rename = "attrs")]
+    html_attrs: Option<HashMap<syn::Ident, String>>,
+    min_from: Option<syn::Ident>,
+    max_from: Option<syn::Ident>,
+    validate_with: Option<syn::Path>,
+}
+
+impl Field {
+    /// Returns the HTML attributes of the field as `(name, value)` tuples.
+    fn html_attrs_as_tuples(&self) -> Vec<TokenStream> {
+        // sort the attributes so that the rendered HTML is deterministic
+        let mut attrs: Vec<_> = self
+            .html_attrs
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key.unraw().to_string().replace('_', "-"), value))
+            .collect();
+        attrs.sort();
+        attrs
+            .into_iter()
+            .map(|(key, value)| quote!((#key.to_owned(), #value.to_owned())))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -84,6 +138,8 @@ struct FormDeriveBuilder {
     fields_as_context_from_request: Vec<TokenStream>,
     fields_as_from_context_vars: Vec<TokenStream>,
     fields_as_from_context: Vec<TokenStream>,
+    fields_as_cross_field_checks: Vec<TokenStream>,
+    validate: Option<syn::Path>,
     fields_as_to_context: Vec<TokenStream>,
     fields_as_errors: Vec<TokenStream>,
     fields_as_errors_for: Vec<TokenStream>,
@@ -120,6 +176,17 @@ impl FormDeriveBuilder {
         let opts = &field.opts;
 
         let name = field_ident.to_string().to_title_case();
+        let help_text = if let Some(help_text) = &field.help_text {
+            quote!(::core::option::Option::Some(#help_text.to_owned()))
+        } else {
+            quote!(::core::option::Option::None)
+        };
+        let widget = if let Some(widget) = &field.widget {
+            quote!(::core::option::Option::Some(#crate_ident::form::Widget::#widget))
+        } else {
+            quote!(::core::option::Option::None)
+        };
+        let attrs = field.html_attrs_as_tuples();
 
         self.fields_as_struct_fields
             .push(quote!(#field_ident: <#ty as #crate_ident::form::AsFormField>::Type));
@@ -136,9 +203,12 @@ impl FormDeriveBuilder {
             };
             quote!(#field_ident: {
                 let options = #crate_ident::form::FormFieldOptions {
-                    id: stringify!(#field_ident).to_owned(),
+                    id: #crate_ident::__private::prefixed_form_field_id(prefix, stringify!(#field_ident)),
                     name: #name.to_owned(),
                     required: true,
+                    help_text: #help_text,
+                    widget: #widget,
+                    attrs: ::std::vec![#( #attrs ),*],
                 };
                 type Field = <#ty as #crate_ident::form::AsFormField>::Type;
                 type CustomOptions = <Field as #crate_ident::form::FormField>::CustomOptions;
@@ -153,15 +223,8 @@ impl FormDeriveBuilder {
                 #crate_ident::form::FormField::set_value(&mut self.#field_ident, value).await?
             }));
 
-        let val_ident = format_ident!("val_{}", field_ident);
-        self.fields_as_from_context_vars.push(quote! {
-            let #val_ident = <#ty as #crate_ident::form::AsFormField>::clean_value(&context.#field_ident).map_err(|error| {
-                context.add_error(#crate_ident::form::FormErrorTarget::Field(stringify!(#field_ident)), error);
-            })
-        });
-        self.fields_as_from_context.push(
-            quote!(#field_ident: #val_ident.expect("Errors should have been returned by now")),
-        );
+        self.push_field_validation(field);
+
         self.fields_as_to_context
             .push(quote!(context.#field_ident.set_value(#crate_ident::form::FormFieldValue::new_text(self.#field_ident.to_field_value())).await.expect("Setting value from text should never fail")));
 
@@ -187,13 +250,68 @@ impl FormDeriveBuilder {
             .push(quote!(&'dummy <#ty as #crate_ident::form::AsFormField>::Type: ::core::fmt::Display + #crate_ident::__private::askama::filters::HtmlSafe));
     }
 
+    fn push_field_validation(&mut self, field: &Field) {
+        let crate_ident = cot_ident();
+        let field_ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+
+        let val_ident = format_ident!("val_{}", field_ident);
+        let validate_with = field.validate_with.as_ref().map(|validate_with| {
+            quote! {
+                .and_then(|value| #validate_with(&value).map(|()| value))
+            }
+        });
+        self.fields_as_from_context_vars.push(quote! {
+            let #val_ident = <#ty as #crate_ident::form::AsFormField>::clean_value(&context.#field_ident)
+                #validate_with
+                .map_err(|error| {
+                context.add_error(#crate_ident::form::FormErrorTarget::Field(stringify!(#field_ident)), error);
+            })
+        });
+        self.fields_as_from_context.push(
+            quote!(#field_ident: #val_ident.expect("Errors should have been returned by now")),
+        );
+        for (other, check) in [
+            (&field.min_from, format_ident!("check_min")),
+            (&field.max_from, format_ident!("check_max")),
+        ] {
+            let Some(other) = other else {
+                continue;
+            };
+            let other_val_ident = format_ident!("val_{}", other);
+            self.fields_as_cross_field_checks.push(quote! {
+                if let (::core::result::Result::Ok(value), ::core::result::Result::Ok(bound)) =
+                    (&#val_ident, &#other_val_ident)
+                {
+                    if let ::core::result::Result::Err(error) =
+                        #crate_ident::form::FormFieldBound::#check(value, bound)
+                    {
+                        context.add_error(
+                            #crate_ident::form::FormErrorTarget::Field(stringify!(#field_ident)),
+                            error,
+                        );
+                    }
+                }
+            });
+        }
+    }
+
     fn build_form_impl(&self) -> TokenStream {
         let crate_ident = cot_ident();
         let name = &self.name;
         let context_struct_name = &self.context_struct_name;
         let fields_as_from_context_vars = &self.fields_as_from_context_vars;
         let fields_as_from_context = &self.fields_as_from_context;
+        let fields_as_cross_field_checks = &self.fields_as_cross_field_checks;
         let fields_as_to_context = &self.fields_as_to_context;
+        let validate = self.validate.as_ref().map(|validate| {
+            quote! {
+                #validate(&form, &mut *context);
+                if context.has_errors() {
+                    return ::core::option::Option::None;
+                }
+            }
+        });
 
         quote! {
             #[#crate_ident::__private::async_trait]
@@ -206,16 +324,29 @@ impl FormDeriveBuilder {
                 ) -> ::core::result::Result<#crate_ident::form::FormResult<Self>, #crate_ident::form::FormError> {
                     let mut context = <Self as #crate_ident::form::Form>::build_context(request).await?;
 
+                    match <Self as #crate_ident::form::Form>::from_context(&mut context).await {
+                        ::core::option::Option::Some(form) => Ok(#crate_ident::form::FormResult::Ok(form)),
+                        ::core::option::Option::None => Ok(#crate_ident::form::FormResult::ValidationError(context)),
+                    }
+                }
+
+                async fn from_context(
+                    context: &mut Self::Context
+                ) -> ::core::option::Option<Self> {
                     use #crate_ident::form::FormContext;
                     #( #fields_as_from_context_vars; )*
+                    #( #fields_as_cross_field_checks )*
 
                     if context.has_errors() {
-                        Ok(#crate_ident::form::FormResult::ValidationError(context))
-                    } else {
-                        Ok(#crate_ident::form::FormResult::Ok(Self {
-                            #( #fields_as_from_context, )*
-                        }))
+                        return ::core::option::Option::None;
                     }
+
+                    let form = Self {
+                        #( #fields_as_from_context, )*
+                    };
+                    #validate
+
+                    ::core::option::Option::Some(form)
                 }
 
                 async fn to_context(
@@ -272,18 +403,34 @@ impl FormDeriveBuilder {
             #[derive(::core::fmt::Debug)]
             pub struct #context_struct_name {
                 __errors: #context_struct_errors_name,
+                __prefix: ::core::option::Option<::std::string::String>,
                 #( #fields_as_struct_fields, )*
             }
 
-            #[#crate_ident::__private::async_trait]
-            #[automatically_derived]
-            impl #crate_ident::form::FormContext for #context_struct_name {
-                fn new() -> Self {
+            impl #context_struct_name {
+                fn __new(prefix: ::core::option::Option<&str>) -> Self {
                     Self {
                         __errors: ::core::default::Default::default(),
+                        __prefix: prefix.map(::std::borrow::ToOwned::to_owned),
                         #( #fields_as_struct_fields_new, )*
                     }
                 }
+            }
+
+            #[#crate_ident::__private::async_trait]
+            #[automatically_derived]
+            impl #crate_ident::form::FormContext for #context_struct_name {
+                fn new() -> Self {
+                    Self::__new(::core::option::Option::None)
+                }
+
+                fn with_prefix(prefix: &str) -> Self {
+                    Self::__new(::core::option::Option::Some(prefix))
+                }
+
+                fn prefix(&self) -> ::core::option::Option<&str> {
+                    self.__prefix.as_deref()
+                }
 
                 fn fields(
                     &self,
@@ -298,7 +445,7 @@ impl FormDeriveBuilder {
                     field_id: &str,
                     value: #crate_ident::form::FormFieldValue<'_>,
                 ) -> ::core::result::Result<(), #crate_ident::form::FormFieldValidationError> {
-                    match field_id {
+                    match #crate_ident::__private::strip_form_prefix(self.__prefix.as_deref(), field_id) {
                         #( #fields_as_context_from_request, )*
                         _ => {}
                     }
@@ -311,7 +458,7 @@ impl FormDeriveBuilder {
                 ) -> &[#crate_ident::form::FormFieldValidationError] {
                     match target {
                         #crate_ident::form::FormErrorTarget::Field(field_id) => {
-                            match field_id {
+                            match #crate_ident::__private::strip_form_prefix(self.__prefix.as_deref(), field_id) {
                                 #( #fields_as_errors_for, )*
                                 _ => {
                                     panic!("Unknown field name passed to get_errors: `{}`", field_id);
@@ -330,7 +477,7 @@ impl FormDeriveBuilder {
                 ) -> &mut Vec<#crate_ident::form::FormFieldValidationError> {
                     match target {
                         #crate_ident::form::FormErrorTarget::Field(field_id) => {
-                            match field_id {
+                            match #crate_ident::__private::strip_form_prefix(self.__prefix.as_deref(), field_id) {
                                 #( #fields_as_errors_for_mut, )*
                                 _ => {
                                     panic!("Unknown field name passed to get_errors_mut: `{}`", field_id);
@@ -1,46 +1,55 @@
-use darling::Error;
+use darling::util::Override;
+use darling::{Error, FromField};
+use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Data, Field, Fields};
+use syn::{Data, Fields, GenericArgument, PathArguments, Type};
 
 use crate::cot_ident;
 
-pub(super) fn impl_from_request_head_for_struct(
-    ast: &syn::DeriveInput,
-) -> proc_macro2::TokenStream {
+pub(super) fn impl_from_request_head_for_struct(ast: &syn::DeriveInput) -> TokenStream {
     let struct_name = &ast.ident;
     let cot = cot_ident();
 
-    let constructor = match &ast.data {
-        Data::Struct(data_struct) => match &data_struct.fields {
-            Fields::Named(fields_named) => {
-                let initializers = fields_named.named.iter().map(|field: &Field| {
+    let Data::Struct(data_struct) = &ast.data else {
+        return Error::custom("Only structs can derive `FromRequestHead`").write_errors();
+    };
+
+    let mut errors = Error::accumulator();
+    let constructor = match &data_struct.fields {
+        Fields::Named(fields_named) => {
+            let initializers: Vec<_> = fields_named
+                .named
+                .iter()
+                .filter_map(|field| {
                     let field_name = &field.ident;
-                    let field_type = &field.ty;
-                    quote! {
-                        #field_name: <#field_type as #cot::request::extractors::FromRequestHead>::from_request_head(head).await?,
-                    }
-                });
-                quote! { Self { #(#initializers)* } }
-            }
+                    let value = errors.handle(field_value(field, &cot))?;
+                    Some(quote! { #field_name: #value, })
+                })
+                .collect();
+            quote! { Self { #(#initializers)* } }
+        }
 
-            Fields::Unnamed(fields_unnamed) => {
-                let initializers = fields_unnamed.unnamed.iter().map(|field: &Field| {
-                    let field_type = &field.ty;
-                    quote! {
-                        <#field_type as #cot::request::extractors::FromRequestHead>::from_request_head(head).await?,
-                    }
-                });
-                quote! { Self(#(#initializers)*) }
-            }
+        Fields::Unnamed(fields_unnamed) => {
+            let initializers: Vec<_> = fields_unnamed
+                .unnamed
+                .iter()
+                .filter_map(|field| {
+                    let value = errors.handle(field_value(field, &cot))?;
+                    Some(quote! { #value, })
+                })
+                .collect();
+            quote! { Self(#(#initializers)*) }
+        }
 
-            Fields::Unit => {
-                quote! {
-                    Self
-                }
+        Fields::Unit => {
+            quote! {
+                Self
             }
-        },
-        _ => return Error::custom("Only structs can derive `FromRequestHead`").write_errors(),
+        }
     };
+    if let Err(err) = errors.finish() {
+        return err.write_errors();
+    }
 
     quote! {
         #[automatically_derived]
@@ -53,3 +62,119 @@ pub(super) fn impl_from_request_head_for_struct(
         }
     }
 }
+
+#[derive(Debug, FromField)]
+#[darling(attributes(from_request))]
+struct FieldOpts {
+    ident: Option<syn::Ident>,
+    ty: Type,
+    /// Take the value from the request extensions instead of using the
+    /// field type's `FromRequestHead` implementation.
+    #[darling(default)]
+    extension: bool,
+    /// Extract a single path parameter, optionally with a name different
+    /// from the field name.
+    path: Option<Override<String>>,
+    /// Set the field (of type `Option<T>`) to `None` if the extraction fails.
+    #[darling(default)]
+    optional: bool,
+    /// Use the default value (or the value returned by the given function) if
+    /// the extraction fails.
+    default: Option<Override<syn::Path>>,
+}
+
+/// Returns an expression evaluating to the value of the given field.
+fn field_value(field: &syn::Field, cot: &TokenStream) -> darling::Result<TokenStream> {
+    let opts = FieldOpts::from_field(field)?;
+
+    if opts.extension && opts.path.is_some() {
+        return Err(
+            Error::custom("`extension` and `path` cannot be used together").with_span(field),
+        );
+    }
+    if opts.optional && opts.default.is_some() {
+        return Err(
+            Error::custom("`optional` and `default` cannot be used together").with_span(field),
+        );
+    }
+
+    let ty = if opts.optional {
+        option_inner_type(&opts.ty).ok_or_else(|| {
+            Error::custom("`optional` fields must be of type `Option<T>`").with_span(&opts.ty)
+        })?
+    } else {
+        &opts.ty
+    };
+
+    // an expression evaluating to `cot::Result<#ty>`
+    let result = if opts.extension {
+        quote! {
+            <#cot::request::extractors::Extension<#ty> as #cot::request::extractors::FromRequestHead>::from_request_head(head)
+                .await
+                .map(|#cot::request::extractors::Extension(value)| value)
+        }
+    } else if let Some(path) = &opts.path {
+        let name = match path {
+            Override::Explicit(name) => name.clone(),
+            Override::Inherit => match &opts.ident {
+                Some(ident) => ident.to_string(),
+                None => {
+                    return Err(Error::custom(
+                        "the name of the path parameter must be specified for unnamed fields",
+                    )
+                    .with_span(field));
+                }
+            },
+        };
+        let missing_error = format!("path parameter `{name}` not found");
+        quote! {
+            match head
+                .extensions
+                .get::<#cot::request::PathParams>()
+                .and_then(|params| params.get(#name))
+            {
+                Some(value) => {
+                    let mut params = #cot::request::PathParams::new();
+                    params.insert(#name.to_owned(), value.to_owned());
+                    params.parse::<#ty>().map_err(#cot::Error::from)
+                }
+                None => Err(#cot::Error::internal(#missing_error)),
+            }
+        }
+    } else {
+        quote! {
+            <#ty as #cot::request::extractors::FromRequestHead>::from_request_head(head).await
+        }
+    };
+
+    let value = if opts.optional {
+        quote! { (#result).ok() }
+    } else {
+        match &opts.default {
+            Some(Override::Inherit) => quote! { (#result).unwrap_or_default() },
+            Some(Override::Explicit(default_fn)) => {
+                quote! { (#result).unwrap_or_else(|_| #default_fn()) }
+            }
+            None => quote! { (#result)? },
+        }
+    };
+    Ok(value)
+}
+
+/// Returns `T` if the given type is `Option<T>`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) if arguments.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
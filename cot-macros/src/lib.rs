@@ -1,12 +1,15 @@
 mod admin;
 mod api_response_enum;
+mod api_serialize;
 mod cache;
 mod dbtest;
+mod factory;
 mod form;
 mod from_request;
 mod main_fn;
 mod migration_op;
 mod model;
+mod model_form;
 mod query;
 mod select_as_form_field;
 mod select_choice;
@@ -20,12 +23,15 @@ use syn::{DeriveInput, ItemFn, parse_macro_input};
 
 use crate::admin::impl_admin_model_for_struct;
 use crate::api_response_enum::{impl_api_operation_response_for_enum, impl_into_response_for_enum};
+use crate::api_serialize::impl_api_serialize_for_struct;
 use crate::dbtest::fn_to_dbtest;
+use crate::factory::impl_factory_for_struct;
 use crate::form::impl_form_for_struct;
 use crate::from_request::impl_from_request_head_for_struct;
 use crate::main_fn::{fn_to_cot_e2e_test, fn_to_cot_main, fn_to_cot_test};
 use crate::migration_op::fn_to_migration_op;
 use crate::model::impl_model_for_struct;
+use crate::model_form::impl_model_form_for_struct;
 use crate::query::{Query, query_to_tokens};
 use crate::select_as_form_field::impl_select_as_form_field_for_enum;
 use crate::select_choice::impl_select_choice_for_enum;
@@ -37,7 +43,14 @@ pub fn derive_form(input: TokenStream) -> TokenStream {
     token_stream.into()
 }
 
-#[proc_macro_derive(AdminModel)]
+#[proc_macro_derive(ModelForm, attributes(model_form, form))]
+pub fn derive_model_form(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let token_stream = impl_model_form_for_struct(&ast);
+    token_stream.into()
+}
+
+#[proc_macro_derive(AdminModel, attributes(admin))]
 pub fn derive_admin_model(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let token_stream = impl_admin_model_for_struct(&ast);
@@ -114,8 +127,29 @@ pub fn derive_admin_model(input: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// # Workflows
+///
+/// The `workflow` parameter declares the states the model instances go
+/// through, along with the allowed transitions between them, and implements
+/// the [`Workflow`] trait for the model. The first state is the initial one.
+/// The current state is stored in the `state` field, which has to be a
+/// [`String`]; another field can be chosen with the `field = "name"` option.
+///
+/// ```
+/// use cot::db::{Auto, model};
+///
+/// #[model(workflow(draft -> review -> published, review -> draft))]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+///     state: String,
+/// }
+/// ```
+///
 /// [`Model`]: trait.Model.html
 /// [`DatabaseField`]: trait.DatabaseField.html
+/// [`Workflow`]: ../workflow/trait.Workflow.html
 #[proc_macro_attribute]
 pub fn model(args: TokenStream, input: TokenStream) -> TokenStream {
     let attr_args = match NestedMeta::parse_meta_list(args.into()) {
@@ -241,7 +275,7 @@ pub(crate) fn cot_ident() -> proc_macro2::TokenStream {
     }
 }
 
-#[proc_macro_derive(FromRequestHead)]
+#[proc_macro_derive(FromRequestHead, attributes(from_request))]
 pub fn derive_from_request_head(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let token_stream = impl_from_request_head_for_struct(&ast);
@@ -274,6 +308,18 @@ pub fn derive_api_operation_response(input: TokenStream) -> TokenStream {
     impl_api_operation_response_for_enum(&ast).into()
 }
 
+#[proc_macro_derive(ApiSerialize, attributes(api))]
+pub fn derive_api_serialize(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    impl_api_serialize_for_struct(&ast).into()
+}
+
+#[proc_macro_derive(Factory, attributes(factory))]
+pub fn derive_factory(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    impl_factory_for_struct(&ast).into()
+}
+
 /// The `Template` derive macro and its `template()` attribute.
 ///
 /// Please see our [template guide](https://cot.rs/guide/latest/templates/) and [askama's book](
@@ -1,4 +1,7 @@
-use cot_codegen::model::{Field, Model, ModelArgs, ModelOpts, ModelType};
+use cot_codegen::model::{
+    Field, MANY_TO_MANY_SOURCE_COLUMN, MANY_TO_MANY_TARGET_COLUMN, ManyToManyField, Model,
+    ModelArgs, ModelOpts, ModelType, Workflow,
+};
 use cot_codegen::symbol_resolver::{SymbolResolver, VisibleSymbol, VisibleSymbolKind};
 use darling::FromMeta;
 use darling::ast::NestedMeta;
@@ -82,12 +85,17 @@ struct ModelBuilder {
     fields_as_update_from_db: Vec<TokenStream>,
     fields_as_get_values: Vec<TokenStream>,
     fields_as_field_refs: Vec<TokenStream>,
+    many_to_many_join_models: Vec<TokenStream>,
+    many_to_many_accessors: Vec<TokenStream>,
+    workflow: Option<Workflow>,
 }
 
 impl ToTokens for ModelBuilder {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         tokens.append_all(self.build_model_impl());
         tokens.append_all(self.build_fields_struct());
+        tokens.append_all(self.build_many_to_many());
+        tokens.append_all(self.build_workflow());
     }
 }
 
@@ -116,14 +124,114 @@ impl ModelBuilder {
             fields_as_update_from_db: Vec::with_capacity(field_count),
             fields_as_get_values: Vec::with_capacity(field_count),
             fields_as_field_refs: Vec::with_capacity(field_count),
+            many_to_many_join_models: Vec::new(),
+            many_to_many_accessors: Vec::new(),
+            workflow: model.workflow.clone(),
         };
         for field in &model.fields {
             model_builder.push_field(field);
         }
+        for field in &model.many_to_many_fields {
+            model_builder.push_many_to_many_field(field, model.model_type);
+        }
 
         model_builder
     }
 
+    fn push_many_to_many_field(&mut self, field: &ManyToManyField, model_type: ModelType) {
+        let orm_ident = orm_ident();
+
+        let name = &field.name;
+        let model_name = &self.name;
+        let to_model = &field.to_model;
+        let vis = &self.vis;
+
+        self.fields_as_from_db.push(quote!(
+            #name: #orm_ident::ManyToMany::new()
+        ));
+
+        // the migration models only describe the database state, which
+        // already contains the join model
+        if model_type == ModelType::Migration {
+            return;
+        }
+
+        let join_model_name = format_ident!("{}Join", field.join_model_name);
+        let model_args = if model_type == ModelType::Internal {
+            let table_name = format!("{}_{}", self.table_name, name);
+            quote!(model_type = "internal", table_name = #table_name)
+        } else {
+            let table_name = field.join_table_name();
+            quote!(table_name = #table_name)
+        };
+        let source_column = format_ident!("{}", MANY_TO_MANY_SOURCE_COLUMN);
+        let target_column = format_ident!("{}", MANY_TO_MANY_TARGET_COLUMN);
+
+        self.many_to_many_join_models.push(quote! {
+            #[doc = concat!(
+                "The join model of the [`", stringify!(#model_name), "::", stringify!(#name),
+                "`] many-to-many relation."
+            )]
+            #[#orm_ident::model(#model_args)]
+            #vis struct #join_model_name {
+                #[model(primary_key)]
+                id: #orm_ident::Auto<i64>,
+                #source_column: #orm_ident::ForeignKey<#model_name>,
+                #target_column: #orm_ident::ForeignKey<#to_model>,
+            }
+
+            // implemented manually, so that the related models don't have to
+            // implement `Debug`
+            #[automatically_derived]
+            impl ::core::fmt::Debug for #join_model_name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.debug_struct(::core::stringify!(#join_model_name))
+                        .field("id", &self.id)
+                        .finish_non_exhaustive()
+                }
+            }
+
+            #[automatically_derived]
+            impl #orm_ident::ManyToManyJoin for #join_model_name {
+                type Source = #model_name;
+                type Target = #to_model;
+
+                const SOURCE_COLUMN: #orm_ident::Identifier =
+                    #orm_ident::Identifier::new(#MANY_TO_MANY_SOURCE_COLUMN);
+                const TARGET_COLUMN: #orm_ident::Identifier =
+                    #orm_ident::Identifier::new(#MANY_TO_MANY_TARGET_COLUMN);
+
+                fn new(
+                    source: #orm_ident::ForeignKey<Self::Source>,
+                    target: #orm_ident::ForeignKey<Self::Target>,
+                ) -> Self {
+                    Self {
+                        id: #orm_ident::Auto::auto(),
+                        #source_column: source,
+                        #target_column: target,
+                    }
+                }
+
+                fn target(&self) -> &#orm_ident::ForeignKey<Self::Target> {
+                    &self.#target_column
+                }
+            }
+        });
+
+        self.many_to_many_accessors.push(quote! {
+            #[doc = concat!(
+                "Returns the instances related through the [`", stringify!(#model_name), "::",
+                stringify!(#name), "`] many-to-many relation."
+            )]
+            #vis fn #name(&self) -> #orm_ident::ManyToManyRelation<'_, #join_model_name> {
+                // the field is only a marker; reading it here keeps it from being
+                // reported as dead code
+                let _: &#orm_ident::ManyToMany<#to_model> = &self.#name;
+                #orm_ident::ManyToManyRelation::new(self)
+            }
+        });
+    }
+
     fn push_field(&mut self, field: &Field) {
         let orm_ident = orm_ident();
 
@@ -236,6 +344,66 @@ impl ModelBuilder {
         }
     }
 
+    #[must_use]
+    fn build_many_to_many(&self) -> TokenStream {
+        if self.many_to_many_accessors.is_empty() {
+            return TokenStream::new();
+        }
+
+        let name = &self.name;
+        let join_models = &self.many_to_many_join_models;
+        let accessors = &self.many_to_many_accessors;
+
+        quote! {
+            #(#join_models)*
+
+            impl #name {
+                #(#accessors)*
+            }
+        }
+    }
+
+    #[must_use]
+    fn build_workflow(&self) -> TokenStream {
+        let Some(workflow) = &self.workflow else {
+            return TokenStream::new();
+        };
+        let crate_ident = cot_ident();
+
+        let name = &self.name;
+        let field = &workflow.field;
+        let states = &workflow.states;
+        let initial_state = &workflow.states[0];
+        let permission_prefix = format!(
+            "{}.{}",
+            self.app_name.to_snake_case(),
+            name.to_string().to_snake_case()
+        );
+        let transitions = workflow.transitions.iter().map(|(from, to)| {
+            let permission = format!("{permission_prefix}.{from}_to_{to}");
+            quote!(#crate_ident::workflow::WorkflowTransition::new(#from, #to, #permission))
+        });
+
+        quote! {
+            #[automatically_derived]
+            impl #crate_ident::workflow::Workflow for #name {
+                const STATES: &'static [&'static str] = &[#(#states),*];
+                const INITIAL_STATE: &'static str = #initial_state;
+                const TRANSITIONS: &'static [#crate_ident::workflow::WorkflowTransition] = &[
+                    #(#transitions),*
+                ];
+
+                fn workflow_state(&self) -> &str {
+                    &self.#field
+                }
+
+                fn set_workflow_state(&mut self, state: &'static str) {
+                    self.#field = ::std::string::String::from(state);
+                }
+            }
+        }
+    }
+
     #[must_use]
     fn build_fields_struct(&self) -> TokenStream {
         let name = &self.name;
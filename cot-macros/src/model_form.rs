@@ -0,0 +1,318 @@
+use cot_codegen::model::FieldOpts;
+use darling::util::PathList;
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+
+use crate::cot_ident;
+
+pub(super) fn impl_model_form_for_struct(ast: &syn::DeriveInput) -> TokenStream {
+    let opts = match ModelFormOpts::from_derive_input(ast) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+
+    let syn::Data::Struct(data) = &ast.data else {
+        unreachable!("darling only accepts named structs")
+    };
+    let fields = match opts.split_fields(&data.fields) {
+        Ok(val) => val,
+        Err(err) => {
+            return err.write_errors();
+        }
+    };
+
+    let builder = ModelFormDeriveBuilder {
+        name: opts.ident.clone(),
+        form_name: format_ident!("{}Form", opts.ident),
+        vis: opts.vis.clone(),
+        fields,
+    };
+
+    quote!(#builder)
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(
+    attributes(model_form),
+    forward_attrs(allow, doc, cfg),
+    supports(struct_named)
+)]
+struct ModelFormOpts {
+    ident: syn::Ident,
+    vis: syn::Visibility,
+    fields: Option<PathList>,
+    exclude: Option<PathList>,
+}
+
+impl ModelFormOpts {
+    /// Splits the fields of the model into the ones that are part of the form
+    /// and the ones that are not.
+    fn split_fields(&self, fields: &syn::Fields) -> darling::Result<ModelFormFields> {
+        let mut errors = darling::Error::accumulator();
+        if let (Some(_), Some(exclude)) = (&self.fields, &self.exclude) {
+            let error = darling::Error::custom("`fields` and `exclude` cannot be used together");
+            errors.push(match exclude.first() {
+                Some(path) => error.with_span(path),
+                None => error.with_span(&self.ident),
+            });
+        }
+
+        let mut model_fields = Vec::with_capacity(fields.len());
+        for field in fields {
+            if let Some(field) = errors.handle(ModelField::from_field(field)) {
+                model_fields.push(field);
+            }
+        }
+
+        for path in self
+            .fields
+            .iter()
+            .chain(&self.exclude)
+            .flat_map(|list| list.iter())
+        {
+            let known = path
+                .get_ident()
+                .is_some_and(|ident| model_fields.iter().any(|field| &field.ident == ident));
+            if !known {
+                errors.push(
+                    darling::Error::custom(format!(
+                        "`{}` is not a field of this model",
+                        path.to_token_stream()
+                    ))
+                    .with_span(path),
+                );
+            }
+        }
+        errors.finish()?;
+
+        let is_listed = |list: &PathList, field: &ModelField| {
+            list.iter().any(|path| path.is_ident(&field.ident))
+        };
+        let (form, excluded) = if let Some(list) = &self.fields {
+            // keep the order in which the fields are listed
+            let form = list
+                .iter()
+                .filter_map(|path| {
+                    model_fields
+                        .iter()
+                        .find(|field| path.is_ident(&field.ident))
+                })
+                .cloned()
+                .collect();
+            let excluded = model_fields
+                .iter()
+                .filter(|field| !is_listed(list, field))
+                .cloned()
+                .collect();
+            (form, excluded)
+        } else {
+            model_fields.into_iter().partition(|field| {
+                !field.primary_key
+                    && !self
+                        .exclude
+                        .as_ref()
+                        .is_some_and(|list| is_listed(list, field))
+            })
+        };
+
+        Ok(ModelFormFields { form, excluded })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ModelField {
+    ident: syn::Ident,
+    vis: syn::Visibility,
+    ty: syn::Type,
+    primary_key: bool,
+    /// The `#[form(...)]` and doc attributes of the field, copied to the form.
+    attrs: Vec<syn::Attribute>,
+}
+
+impl ModelField {
+    fn from_field(field: &syn::Field) -> darling::Result<Self> {
+        let opts = FieldOpts::from_field(field)?;
+
+        Ok(Self {
+            ident: field
+                .ident
+                .clone()
+                .expect("Only named fields are supported"),
+            vis: field.vis.clone(),
+            ty: field.ty.clone(),
+            primary_key: opts.primary_key.is_present(),
+            attrs: field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("form") || attr.path().is_ident("doc"))
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct ModelFormFields {
+    form: Vec<ModelField>,
+    excluded: Vec<ModelField>,
+}
+
+#[derive(Debug)]
+struct ModelFormDeriveBuilder {
+    name: syn::Ident,
+    form_name: syn::Ident,
+    vis: syn::Visibility,
+    fields: ModelFormFields,
+}
+
+impl ToTokens for ModelFormDeriveBuilder {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let form_struct = self.build_form_struct();
+        let form_impl = self.build_form_impl();
+        let from_impls = self.build_from_impls();
+
+        let new_tokens = quote! {
+            #form_struct
+            #form_impl
+            #from_impls
+        };
+
+        new_tokens.to_tokens(tokens);
+    }
+}
+
+impl ModelFormDeriveBuilder {
+    fn build_form_struct(&self) -> TokenStream {
+        let crate_ident = cot_ident();
+        let name = &self.name;
+        let form_name = &self.form_name;
+        let vis = &self.vis;
+        let fields = self.fields.form.iter().map(|field| {
+            let ModelField {
+                ident,
+                vis,
+                ty,
+                attrs,
+                ..
+            } = field;
+            quote!(#( #attrs )* #vis #ident: #ty)
+        });
+        let doc = format!("A form for creating and editing [`{name}`] instances.");
+
+        quote! {
+            #[doc = #doc]
+            #[derive(#crate_ident::form::Form)]
+            #vis struct #form_name {
+                #( #fields, )*
+            }
+        }
+    }
+
+    fn build_form_impl(&self) -> TokenStream {
+        let crate_ident = cot_ident();
+        let name = &self.name;
+        let form_name = &self.form_name;
+        let vis = &self.vis;
+        let form_idents: Vec<_> = self.fields.form.iter().map(|field| &field.ident).collect();
+        let default_bounds = self.default_bounds();
+
+        quote! {
+            #[automatically_derived]
+            impl #form_name {
+                /// Creates a new model instance from the form data and inserts
+                /// it into the database.
+                ///
+                /// The fields that are not part of the form are set to their
+                /// default values.
+                ///
+                /// # Errors
+                ///
+                /// Returns an error if the model instance could not be inserted
+                /// into the database.
+                #vis async fn save<DB: #crate_ident::db::DatabaseBackend>(
+                    self,
+                    db: &DB,
+                ) -> #crate_ident::db::Result<#name>
+                #default_bounds
+                {
+                    let mut model = <#name as ::core::convert::From<Self>>::from(self);
+                    #crate_ident::db::Model::insert(&mut model, db).await?;
+                    ::core::result::Result::Ok(model)
+                }
+
+                /// Copies the form data into an existing model instance and
+                /// updates it in the database.
+                ///
+                /// # Errors
+                ///
+                /// Returns an error if the model instance could not be updated
+                /// in the database.
+                #vis async fn save_to<DB: #crate_ident::db::DatabaseBackend>(
+                    self,
+                    model: &mut #name,
+                    db: &DB,
+                ) -> #crate_ident::db::Result<()> {
+                    self.apply_to(model);
+                    #crate_ident::db::Model::update(model, db).await
+                }
+
+                /// Copies the form data into an existing model instance,
+                /// without saving it.
+                #vis fn apply_to(self, model: &mut #name) {
+                    #( model.#form_idents = self.#form_idents; )*
+                }
+            }
+        }
+    }
+
+    fn build_from_impls(&self) -> TokenStream {
+        let name = &self.name;
+        let form_name = &self.form_name;
+        let form_idents: Vec<_> = self.fields.form.iter().map(|field| &field.ident).collect();
+        let excluded_idents = self.fields.excluded.iter().map(|field| &field.ident);
+        let default_bounds = self.default_bounds();
+
+        quote! {
+            #[automatically_derived]
+            impl ::core::convert::From<#form_name> for #name
+            #default_bounds
+            {
+                fn from(form: #form_name) -> Self {
+                    Self {
+                        #( #form_idents: form.#form_idents, )*
+                        #( #excluded_idents: ::core::default::Default::default(), )*
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl ::core::convert::From<#name> for #form_name {
+                fn from(model: #name) -> Self {
+                    Self {
+                        #( #form_idents: model.#form_idents, )*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the `where` clause requiring the fields excluded from the form to
+    /// implement [`Default`].
+    fn default_bounds(&self) -> TokenStream {
+        if self.fields.excluded.is_empty() {
+            return TokenStream::new();
+        }
+
+        // The higher-ranked lifetime makes the bounds non-trivial, so that the
+        // model can still be used with the form if a field doesn't implement
+        // `Default`; only creating new model instances is not possible then.
+        // See https://github.com/rust-lang/rust/issues/48214 for details.
+        let excluded_types = self.fields.excluded.iter().map(|field| &field.ty);
+        quote! {
+            where #( for<'dummy> #excluded_types: ::core::default::Default, )*
+        }
+    }
+}
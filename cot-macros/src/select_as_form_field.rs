@@ -24,10 +24,7 @@ pub(super) fn impl_select_as_form_field_for_enum(ast: &DeriveInput) -> proc_macr
             fn clean_value(
                 field: &Self::Type
             ) -> ::core::result::Result<Self, #cot::form::FormFieldValidationError> {
-                match #cot::form::FormField::value(field) {
-                    ::core::option::Option::Some(v) if !v.is_empty() => <Self as #cot::form::fields::SelectChoice>::from_str(v),
-                    _ => ::core::result::Result::Err(#cot::form::FormFieldValidationError::Required),
-                }
+                field.clean_choice()
             }
 
             fn to_field_value(&self) -> ::std::string::String {
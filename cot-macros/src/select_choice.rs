@@ -12,6 +12,8 @@ struct SelectChoiceVariant {
     id: Option<String>,
     #[darling(default)]
     name: Option<String>,
+    #[darling(default)]
+    group: Option<String>,
 }
 
 pub(super) fn impl_select_choice_for_enum(ast: &DeriveInput) -> proc_macro2::TokenStream {
@@ -72,6 +74,31 @@ pub(super) fn impl_select_choice_for_enum(ast: &DeriveInput) -> proc_macro2::Tok
         quote! { Self::#ident => #display, }
     });
 
+    // group
+    let group_impl = if darling_variants.iter().any(|v| v.group.is_some()) {
+        let group_match_arms = darling_variants.iter().map(|v| {
+            let ident = &v.ident;
+            if let Some(group) = &v.group {
+                quote! {
+                    Self::#ident => ::core::option::Option::Some(
+                        ::std::string::String::from(#group)
+                    ),
+                }
+            } else {
+                quote! { Self::#ident => ::core::option::Option::None, }
+            }
+        });
+        quote! {
+            fn group(&self) -> ::core::option::Option<::std::string::String> {
+                match self {
+                    #( #group_match_arms )*
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     quote! {
         #[automatically_derived]
         impl #cot::form::fields::SelectChoice for #enum_name {
@@ -103,6 +130,8 @@ pub(super) fn impl_select_choice_for_enum(ast: &DeriveInput) -> proc_macro2::Tok
                     #( #to_string_match_arms )*
                 })
             }
+
+            #group_impl
         }
     }
 }
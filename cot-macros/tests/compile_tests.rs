@@ -30,6 +30,7 @@ fn attr_model() {
     t.compile_fail("tests/ui/attr_model_generic.rs");
     t.compile_fail("tests/ui/attr_model_no_pk.rs");
     t.compile_fail("tests/ui/attr_model_multiple_pks.rs");
+    t.compile_fail("tests/ui/attr_model_workflow_no_field.rs");
 }
 
 #[rustversion::attr(
@@ -45,6 +46,22 @@ fn derive_admin_model() {
     let t = trybuild::TestCases::new();
     t.pass("tests/ui/derive_admin_model.rs");
     t.pass("tests/ui/derive_admin_model_derive_first.rs");
+    t.pass("tests/ui/derive_admin_model_workflow.rs");
+}
+
+#[rustversion::attr(
+    not(nightly),
+    ignore = "only test on nightly for consistent error messages"
+)]
+#[test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: extern static `pidfd_spawnp` is not supported by Miri"
+)]
+fn derive_model_form() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_model_form.rs");
+    t.compile_fail("tests/ui/derive_model_form_invalid_field.rs");
 }
 
 #[rustversion::attr(
@@ -93,7 +110,9 @@ fn attr_main() {
 fn derive_from_struct() {
     let t = trybuild::TestCases::new();
     t.pass("tests/ui/derive_from_request_head.rs");
+    t.pass("tests/ui/derive_from_request_head_attrs.rs");
     t.compile_fail("tests/ui/derive_from_request_head_enum.rs");
+    t.compile_fail("tests/ui/derive_from_request_head_optional_not_option.rs");
 }
 
 #[rustversion::attr(
@@ -181,3 +200,31 @@ fn derive_api_operation_response() {
     t.compile_fail("tests/ui/derive_api_operation_response_invalid_variant_multi_tuple.rs");
     t.compile_fail("tests/ui/derive_api_operation_response_invalid_variant_struct.rs");
 }
+
+#[rustversion::attr(
+    not(nightly),
+    ignore = "only test on nightly for consistent error messages"
+)]
+#[test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: extern static `pidfd_spawnp` is not supported by Miri"
+)]
+fn derive_api_serialize() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_api_serialize.rs");
+}
+
+#[rustversion::attr(
+    not(nightly),
+    ignore = "only test on nightly for consistent error messages"
+)]
+#[test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: extern static `pidfd_spawnp` is not supported by Miri"
+)]
+fn derive_factory() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_factory.rs");
+}
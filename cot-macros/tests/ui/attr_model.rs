@@ -6,6 +6,7 @@ struct MyModel {
     #[model(primary_key)]
     id: i32,
     name: std::string::String,
+    #[model(rename_from = "summary")]
     description: String,
     visits: i32,
 }
@@ -0,0 +1,9 @@
+use cot::db::model;
+
+#[model(workflow(draft -> published))]
+struct MyModel {
+    #[model(primary_key)]
+    id: i32,
+}
+
+fn main() {}
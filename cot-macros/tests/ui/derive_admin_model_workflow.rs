@@ -0,0 +1,27 @@
+use std::fmt::Display;
+
+use cot::admin::AdminModel;
+use cot::db::{Auto, model};
+use cot::form::Form;
+use cot::workflow::Workflow;
+
+#[model(workflow(draft -> review -> published, review -> draft, field = "status"))]
+#[derive(Debug, Form, AdminModel)]
+#[admin(workflow)]
+struct Post {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    title: std::string::String,
+    status: std::string::String,
+}
+
+impl Display for Post {
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        unimplemented!()
+    }
+}
+
+fn main() {
+    println!("{:?}", Post::STATES);
+    println!("{:?}", Post::TRANSITIONS);
+}
@@ -0,0 +1,39 @@
+use cot::serialize::{ApiSerialize, DEFAULT_PROFILE};
+
+#[derive(ApiSerialize)]
+struct Tag {
+    name: String,
+}
+
+#[derive(ApiSerialize)]
+struct Post {
+    id: i32,
+    #[api(rename = "headline")]
+    title: String,
+    #[api(profile = "detail", profile = "admin")]
+    content: String,
+    #[api(exclude)]
+    #[expect(unused)]
+    secret: String,
+    #[api(nested, profile = "detail")]
+    tags: Vec<Tag>,
+    r#type: String,
+}
+
+#[derive(ApiSerialize)]
+struct Empty {}
+
+fn main() {
+    let post = Post {
+        id: 1,
+        title: "Hello".to_owned(),
+        content: "Hello, world!".to_owned(),
+        secret: "hidden".to_owned(),
+        tags: vec![Tag {
+            name: "rust".to_owned(),
+        }],
+        r#type: "article".to_owned(),
+    };
+    let _ = post.with_profile(DEFAULT_PROFILE);
+    let _ = Empty {}.with_profile("list");
+}
@@ -0,0 +1,36 @@
+use cot::common_types::Email;
+use cot::db::{Auto, ForeignKey, LimitedString, model};
+use cot::test::factory::{Factory, FactoryBuilder};
+
+#[model]
+#[derive(Factory)]
+struct Author {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    name: String,
+    email: Email,
+}
+
+#[model]
+#[derive(Factory)]
+struct Post {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    author: ForeignKey<Author>,
+    reviewer: Option<ForeignKey<Author>>,
+    title: String,
+    slug: LimitedString<64>,
+    #[factory(default = false)]
+    published: bool,
+    #[factory(fake = cot::test::factory::faker::lorem::en::Paragraph(1..2))]
+    summary: String,
+}
+
+fn main() {
+    let post = Post::factory().title("Hello").build();
+    assert_eq!(post.title, "Hello");
+    assert!(!post.published);
+    assert!(post.reviewer.is_none());
+
+    let _ = Author::build();
+}
@@ -0,0 +1,44 @@
+use cot::request::RequestHead;
+use cot::request::extractors::FromRequestHead;
+
+#[derive(FromRequestHead)]
+struct MyStruct {
+    #[from_request(extension)]
+    tenant: TenantId,
+    #[from_request(extension, optional)]
+    user: Option<TenantId>,
+    #[from_request(path)]
+    id: i32,
+    #[from_request(path = "slug")]
+    post_slug: String,
+    #[from_request(optional)]
+    optional: Option<DummyExtractor>,
+    #[from_request(default)]
+    defaulted: DummyExtractor,
+    #[from_request(default = "default_extractor")]
+    defaulted_with: DummyExtractor,
+}
+
+#[derive(FromRequestHead)]
+struct MyTupleStruct(
+    #[from_request(extension)] TenantId,
+    #[from_request(path = "id")] i32,
+);
+
+#[derive(Clone)]
+struct TenantId(i32);
+
+#[derive(Default)]
+struct DummyExtractor;
+
+impl FromRequestHead for DummyExtractor {
+    async fn from_request_head(_head: &RequestHead) -> cot::Result<Self> {
+        Ok(Self)
+    }
+}
+
+fn default_extractor() -> DummyExtractor {
+    DummyExtractor
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use cot::request::extractors::FromRequestHead;
+
+#[derive(FromRequestHead)]
+struct MyStruct {
+    #[from_request(extension, optional)]
+    tenant_id: i32,
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+use cot::db::{Auto, Database, model};
+use cot::form::{Form, ModelForm};
+use cot::request::Request;
+
+#[derive(Debug, ModelForm)]
+#[model]
+struct MyModel {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    #[form(opts(max_length = 100))]
+    name: std::string::String,
+    description: String,
+}
+
+#[derive(Debug, ModelForm)]
+#[model_form(fields(description, name))]
+#[model]
+struct MyOtherModel {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    name: String,
+    description: String,
+    views: i64,
+}
+
+#[expect(unused)]
+async fn test_endpoint(mut request: Request, db: Database) -> cot::Result<()> {
+    let form = MyModelForm::from_request(&mut request).await?.unwrap();
+    let mut model = form.save(&db).await?;
+
+    let form = MyModelForm::from_request(&mut request).await?.unwrap();
+    form.save_to(&mut model, &db).await?;
+
+    let form = MyOtherModelForm::from_request(&mut request).await?.unwrap();
+    let model = form.save(&db).await?;
+    println!("{}, {}", model.name, model.description);
+
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use cot::db::{Auto, model};
+use cot::form::ModelForm;
+
+#[derive(ModelForm)]
+#[model_form(fields(name, description))]
+#[model]
+struct MyModel {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    name: String,
+}
+
+fn main() {}
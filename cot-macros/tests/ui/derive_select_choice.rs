@@ -25,4 +25,13 @@ enum WithOverrides {
     Default,
 }
 
+#[derive(SelectChoice, Debug, PartialEq, Eq)]
+enum WithGroups {
+    #[select_choice(group = "Fruits")]
+    Apple,
+    #[select_choice(id = "carrot", group = "Vegetables")]
+    Carrot,
+    Other,
+}
+
 fn main() {}
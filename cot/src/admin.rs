@@ -4,7 +4,10 @@
 //! registered in the application, straight from the web interface.
 
 use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -15,23 +18,31 @@ use bytes::Bytes;
 /// **must** implement [`Model`](crate::db::Model) and
 /// [`Form`] traits. These can also be derived using the `#[model]` and
 /// `#[derive(Form)]` attributes.
+///
+/// If the model has a [workflow](crate::workflow), add the
+/// `#[admin(workflow)]` attribute to display a button for each transition
+/// the current user is allowed to perform on the edit page.
 pub use cot_macros::AdminModel;
-use derive_more::Debug;
+use derive_more::{Debug, Deref};
 use serde::Deserialize;
 
-use crate::auth::Auth;
+use crate::auth::{Auth, User, UserId};
 use crate::common_types::Password;
 use crate::error::NotFound;
 use crate::form::{
     Form, FormContext, FormErrorTarget, FormField, FormFieldValidationError, FormResult,
 };
 use crate::html::Html;
+use crate::messages::FlashMessages;
+use crate::nav::Nav;
 use crate::request::extractors::{FromRequestHead, Path, StaticFiles, UrlQuery};
 use crate::request::{Request, RequestExt, RequestHead};
 use crate::response::{IntoResponse, Response};
 use crate::router::{Router, Urls};
+use crate::session::store::SessionStoreManager;
 use crate::static_files::StaticFile;
-use crate::{App, Error, Method, RequestHandler, Template, reverse_redirect};
+use crate::workflow::WorkflowTransition;
+use crate::{App, Error, Method, RequestHandler, StatusCode, Template, reverse_redirect};
 
 struct AdminAuthenticated<T, H: Send + Sync>(H, PhantomData<fn() -> T>);
 
@@ -53,27 +64,113 @@ impl<T, H: RequestHandler<T> + Send + Sync> RequestHandler<T> for AdminAuthentic
     }
 }
 
+/// Makes the [`AdminSiteConfig`] available to the wrapped admin handler.
+struct WithAdminSite<T, H: Send + Sync>(H, AdminSite, PhantomData<fn() -> T>);
+
+impl<T, H: RequestHandler<T> + Send + Sync> WithAdminSite<T, H> {
+    #[must_use]
+    fn new(handler: H, site: AdminSite) -> Self {
+        Self(handler, site, PhantomData)
+    }
+}
+
+impl<T, H: RequestHandler<T> + Send + Sync> RequestHandler<T> for WithAdminSite<T, H> {
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        request.extensions_mut().insert(self.1.clone());
+
+        self.0.handle(request).await
+    }
+}
+
+#[derive(Debug, Clone, Default, Deref)]
+struct AdminSite(Arc<AdminSiteConfig>);
+
+impl FromRequestHead for AdminSite {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        Ok(head.extensions.get::<Self>().cloned().unwrap_or_default())
+    }
+}
+
 #[derive(Debug, FromRequestHead)]
 struct BaseContext {
     urls: Urls,
     static_files: StaticFiles,
+    nav: Nav,
+    messages: FlashMessages,
+    site: AdminSite,
+}
+
+#[derive(Debug)]
+struct ModelGroup<M> {
+    name: Cow<'static, str>,
+    models: Vec<M>,
+}
+
+/// Splits the models into the groups configured in [`AdminSiteConfig`].
+///
+/// The models that don't belong to any group are put in the last group, in
+/// the order they were registered in.
+fn group_models<M>(
+    config: &AdminSiteConfig,
+    models: Vec<M>,
+    url_name: impl Fn(&M) -> &str,
+) -> Vec<ModelGroup<M>> {
+    let mut models: Vec<Option<M>> = models.into_iter().map(Some).collect();
+    let mut take_model = |name: &str| {
+        models
+            .iter_mut()
+            .find(|model| model.as_ref().is_some_and(|model| url_name(model) == name))
+            .and_then(Option::take)
+    };
+
+    let mut groups: Vec<_> = config
+        .groups
+        .iter()
+        .map(|group| ModelGroup {
+            name: group.name.clone(),
+            models: group
+                .models
+                .iter()
+                .filter_map(|name| take_model(name))
+                .collect(),
+        })
+        .filter(|group| !group.models.is_empty())
+        .collect();
+
+    let ungrouped: Vec<_> = models.into_iter().flatten().collect();
+    if !ungrouped.is_empty() {
+        let name = if groups.is_empty() {
+            "Choose a model to manage"
+        } else {
+            "Other models"
+        };
+        groups.push(ModelGroup {
+            name: Cow::Borrowed(name),
+            models: ungrouped,
+        });
+    }
+    groups
 }
 
 async fn index(
     base_context: BaseContext,
     AdminModelManagers(managers): AdminModelManagers,
+    ErrorLogRegistered(error_log): ErrorLogRegistered,
 ) -> crate::Result<Html> {
     #[derive(Debug, Template)]
     #[template(path = "admin/model_list.html")]
     struct ModelListTemplate<'a> {
         ctx: &'a BaseContext,
         #[debug("..")]
-        model_managers: Vec<Box<dyn AdminModelManager>>,
+        model_groups: Vec<ModelGroup<Box<dyn AdminModelManager>>>,
+        error_log: bool,
     }
 
+    let model_groups = group_models(&base_context.site, managers, |manager| manager.url_name());
     let template = ModelListTemplate {
         ctx: &base_context,
-        model_managers: managers,
+        model_groups,
+        error_log,
     };
     Ok(Html::new(template.render()?))
 }
@@ -109,7 +206,10 @@ async fn login(
                 let mut context = LoginForm::build_context(&mut request).await?;
                 context.add_error(
                     FormErrorTarget::Form,
-                    FormFieldValidationError::from_static("Invalid username or password"),
+                    FormFieldValidationError::with_code(
+                        "invalid_credentials",
+                        "Invalid username or password",
+                    ),
                 );
                 context
             }
@@ -185,6 +285,7 @@ struct PaginationParams {
 async fn view_model(
     base_context: BaseContext,
     managers: AdminModelManagers,
+    auth: Auth,
     Path(model_name): Path<String>,
     UrlQuery(pagination_params): UrlQuery<PaginationParams>,
     request: Request,
@@ -195,8 +296,7 @@ async fn view_model(
         ctx: &'a BaseContext,
         #[debug("..")]
         model: &'a dyn AdminModelManager,
-        #[debug("..")]
-        objects: Vec<Box<dyn AdminModel>>,
+        objects: Vec<AdminObjectRow>,
         page: u64,
         page_size: &'a u64,
         total_object_counts: u64,
@@ -221,7 +321,35 @@ async fn view_model(
 
     let pagination = Pagination::new(page_size, page);
 
-    let objects = manager.get_objects(&request, pagination).await?;
+    let user = auth.user();
+    let objects: Vec<_> = manager
+        .get_objects(&request, pagination)
+        .await?
+        .into_iter()
+        .filter(|object| manager.can_view(&*user, &**object))
+        .map(|object| AdminObjectRow {
+            can_change: manager.can_change(&*user, &*object),
+            can_delete: manager.can_delete(&*user, &*object),
+            object,
+        })
+        .collect();
+
+    if let Some(render) = base_context
+        .site
+        .model_list_templates
+        .get(manager.url_name())
+    {
+        let page = AdminModelListPage {
+            urls: &base_context.urls,
+            model: &*manager,
+            objects: &objects,
+            page,
+            page_size,
+            total_object_count: total_object_counts,
+            total_pages,
+        };
+        return render(&page).into_response();
+    }
 
     let template = ModelTemplate {
         ctx: &base_context,
@@ -239,21 +367,24 @@ async fn view_model(
 async fn create_model_instance(
     base_context: BaseContext,
     managers: AdminModelManagers,
+    auth: Auth,
     Path(model_name): Path<String>,
     request: Request,
 ) -> cot::Result<Response> {
-    edit_model_instance_impl(base_context, managers, request, &model_name, None).await
+    edit_model_instance_impl(base_context, managers, &auth, request, &model_name, None).await
 }
 
 async fn edit_model_instance(
     base_context: BaseContext,
     managers: AdminModelManagers,
+    auth: Auth,
     Path((model_name, object_id)): Path<(String, String)>,
     request: Request,
 ) -> cot::Result<Response> {
     edit_model_instance_impl(
         base_context,
         managers,
+        &auth,
         request,
         &model_name,
         Some(&object_id),
@@ -264,6 +395,7 @@ async fn edit_model_instance(
 async fn edit_model_instance_impl(
     base_context: BaseContext,
     managers: AdminModelManagers,
+    auth: &Auth,
     mut request: Request,
     model_name: &str,
     object_id: Option<&str>,
@@ -276,25 +408,39 @@ async fn edit_model_instance_impl(
         model: &'a dyn AdminModelManager,
         form_context: Box<dyn FormContext>,
         is_edit: bool,
+        object_id: Option<&'a str>,
+        transitions: Vec<WorkflowTransition>,
     }
 
     let manager = get_manager(managers, model_name)?;
+    let mut transitions = Vec::new();
+    let object = if let Some(object_id) = object_id {
+        let object = get_object(&mut request, &*manager, object_id).await?;
+        let user = auth.user();
+        if !manager.can_view(&*user, &*object) || !manager.can_change(&*user, &*object) {
+            return Err(permission_denied("change", &*manager, object_id));
+        }
+        transitions = object.workflow_transitions(&*user);
+        Some(object)
+    } else {
+        None
+    };
 
     let form_context = if request.method() == Method::POST {
-        let form_context = manager.save_from_request(&mut request, object_id).await?;
-
-        if let Some(form_context) = form_context {
+        if let Some(form_context) = manager.save_from_request(&mut request, object_id).await? {
             form_context
         } else {
+            request
+                .messages()
+                .success(format!("The {} was saved successfully.", manager.name()))
+                .await?;
             return Ok(reverse_redirect!(
                 base_context.urls,
                 "view_model",
                 model_name = manager.url_name()
             )?);
         }
-    } else if let Some(object_id) = object_id {
-        let object = get_object(&mut request, &*manager, object_id).await?;
-
+    } else if let Some(object) = object {
         manager.form_context_from_object(object).await
     } else {
         manager.form_context()
@@ -305,14 +451,50 @@ async fn edit_model_instance_impl(
         model: &*manager,
         form_context,
         is_edit: object_id.is_some(),
+        object_id,
+        transitions,
     };
 
     Html::new(template.render()?).into_response()
 }
 
+async fn transition_model_instance(
+    base_context: BaseContext,
+    managers: AdminModelManagers,
+    auth: Auth,
+    Path((model_name, object_id, state)): Path<(String, String, String)>,
+    mut request: Request,
+) -> cot::Result<Response> {
+    let manager = get_manager(managers, &model_name)?;
+    let mut object = get_object(&mut request, &*manager, &object_id).await?;
+    let user = auth.user();
+    if !manager.can_view(&*user, &*object) || !manager.can_change(&*user, &*object) {
+        return Err(permission_denied("change", &*manager, &object_id));
+    }
+
+    if request.method() == Method::POST {
+        object.workflow_transition(&request, &*user, &state).await?;
+        request
+            .messages()
+            .success(format!(
+                "The {} was moved to the \"{state}\" state.",
+                manager.name()
+            ))
+            .await?;
+    }
+
+    Ok(reverse_redirect!(
+        base_context.urls,
+        "edit_model_instance",
+        model_name = manager.url_name(),
+        pk = object_id
+    )?)
+}
+
 async fn remove_model_instance(
     base_context: BaseContext,
     managers: AdminModelManagers,
+    auth: Auth,
     Path((model_name, object_id)): Path<(String, String)>,
     mut request: Request,
 ) -> cot::Result<Response> {
@@ -328,9 +510,17 @@ async fn remove_model_instance(
 
     let manager = get_manager(managers, &model_name)?;
     let object = get_object(&mut request, &*manager, &object_id).await?;
+    let user = auth.user();
+    if !manager.can_view(&*user, &*object) || !manager.can_delete(&*user, &*object) {
+        return Err(permission_denied("remove", &*manager, &object_id));
+    }
 
     if request.method() == Method::POST {
         manager.remove_by_id(&mut request, &object_id).await?;
+        request
+            .messages()
+            .success(format!("The {} was removed successfully.", manager.name()))
+            .await?;
 
         Ok(reverse_redirect!(
             base_context.urls,
@@ -348,6 +538,109 @@ async fn remove_model_instance(
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct UserSessionsParams {
+    user_id: Option<String>,
+}
+
+async fn user_sessions(
+    base_context: BaseContext,
+    sessions: SessionStoreManager,
+    UrlQuery(params): UrlQuery<UserSessionsParams>,
+    request: Request,
+) -> cot::Result<Response> {
+    #[derive(Debug)]
+    struct SessionRow {
+        id: String,
+        expiry_date: String,
+    }
+
+    #[derive(Debug, Template)]
+    #[template(path = "admin/user_sessions.html")]
+    struct UserSessionsTemplate<'a> {
+        ctx: &'a BaseContext,
+        user_id: Option<&'a str>,
+        sessions: Vec<SessionRow>,
+        removed: Option<u64>,
+    }
+
+    let user_id = params
+        .user_id
+        .as_deref()
+        .filter(|user_id| !user_id.is_empty());
+
+    let mut removed = None;
+    let mut rows = Vec::new();
+    if let Some(user_id) = user_id {
+        let parsed_user_id = parse_user_id(user_id);
+        if request.method() == Method::POST {
+            removed = Some(sessions.delete_for_user(&parsed_user_id).await?);
+        }
+
+        rows = sessions
+            .list_for_user(&parsed_user_id)
+            .await?
+            .into_iter()
+            .map(|session| SessionRow {
+                id: mask_session_id(&session.id().to_string()),
+                expiry_date: session.expiry_date().to_string(),
+            })
+            .collect();
+    }
+
+    let template = UserSessionsTemplate {
+        ctx: &base_context,
+        user_id,
+        sessions: rows,
+        removed,
+    };
+    Html::new(template.render()?).into_response()
+}
+
+#[cfg(feature = "db")]
+async fn error_log(
+    base_context: BaseContext,
+    ErrorLogRegistered(registered): ErrorLogRegistered,
+    database: crate::db::Database,
+) -> cot::Result<Html> {
+    #[derive(Debug, Template)]
+    #[template(path = "admin/error_log.html")]
+    struct ErrorLogTemplate<'a> {
+        ctx: &'a BaseContext,
+        entries: Vec<crate::error::log::ErrorLogEntry>,
+    }
+
+    if !registered {
+        // the error log table only exists if the app is registered
+        return Err(Error::from(NotFound::with_message(
+            "the error log app is not registered",
+        )));
+    }
+
+    let template = ErrorLogTemplate {
+        ctx: &base_context,
+        entries: crate::error::log::ErrorLogEntry::recent(&database).await?,
+    };
+    Ok(Html::new(template.render()?))
+}
+
+/// Parses a user ID passed in the URL. Numeric IDs are treated as integer IDs,
+/// as this is what the database auth backend uses.
+pub(crate) fn parse_user_id(user_id: &str) -> UserId {
+    user_id
+        .parse()
+        .map_or_else(|_| UserId::String(user_id.to_owned()), UserId::Int)
+}
+
+/// Hides most of the session ID, as it's effectively a password allowing to
+/// impersonate the user.
+fn mask_session_id(id: &str) -> String {
+    const VISIBLE_CHARS: usize = 6;
+
+    let visible: String = id.chars().take(VISIBLE_CHARS).collect();
+    format!("{visible}…")
+}
+
 async fn get_object(
     request: &mut Request,
     manager: &dyn AdminModelManager,
@@ -365,6 +658,16 @@ async fn get_object(
         })
 }
 
+fn permission_denied(action: &str, manager: &dyn AdminModelManager, object_id: &str) -> Error {
+    Error::with_status(
+        format!(
+            "You are not allowed to {action} the object with ID `{object_id}` in model `{}`",
+            manager.name()
+        ),
+        StatusCode::FORBIDDEN,
+    )
+}
+
 fn get_manager(
     AdminModelManagers(model_managers): AdminModelManagers,
     model_name: &str,
@@ -394,6 +697,29 @@ impl FromRequestHead for AdminModelManagers {
     }
 }
 
+/// Whether [`ErrorLogApp`](crate::error::log::ErrorLogApp) is registered in
+/// the project, so the recent errors can be browsed in the admin panel.
+struct ErrorLogRegistered(bool);
+
+impl FromRequestHead for ErrorLogRegistered {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        Ok(Self(is_error_log_registered(head.context().apps())))
+    }
+}
+
+fn is_error_log_registered(apps: &[Box<dyn App>]) -> bool {
+    #[cfg(feature = "db")]
+    {
+        let error_log_app = crate::error::log::ErrorLogApp::new();
+        apps.iter().any(|app| app.name() == error_log_app.name())
+    }
+    #[cfg(not(feature = "db"))]
+    {
+        let _ = apps;
+        false
+    }
+}
+
 /// A trait for adding admin models to the app.
 ///
 /// This exposes an API over [`AdminModel`] that is dyn-compatible and
@@ -459,6 +785,45 @@ pub trait AdminModelManager: Send + Sync {
     /// Returns an error if the object could not be removed, for example,
     /// a database error.
     async fn remove_by_id(&self, request: &mut Request, object_id: &str) -> cot::Result<()>;
+
+    /// Returns whether the given user is allowed to view the object.
+    ///
+    /// Objects that can't be viewed are hidden from the list of objects,
+    /// and can't be edited or removed. Note that they are still included in
+    /// the total object count; if this is not desired, filter the objects
+    /// out in [`Self::get_objects`] and [`Self::get_total_object_counts`]
+    /// instead.
+    ///
+    /// This can be used to implement row-level access policies, such as
+    /// only allowing the users to manage their own records. Like in
+    /// [`Self::form_context_from_object`], the object is guaranteed to be
+    /// returned by either [`Self::get_objects`] or
+    /// [`Self::get_object_by_id`], so it can be safely downcast to the model
+    /// type. The default implementation allows viewing all objects.
+    fn can_view(&self, user: &dyn User, object: &dyn AdminModel) -> bool {
+        let _ = (user, object);
+        true
+    }
+
+    /// Returns whether the given user is allowed to change the object.
+    ///
+    /// If this returns `false`, the edit page of the object responds with
+    /// `403 Forbidden`. The default implementation allows changing all
+    /// objects.
+    fn can_change(&self, user: &dyn User, object: &dyn AdminModel) -> bool {
+        let _ = (user, object);
+        true
+    }
+
+    /// Returns whether the given user is allowed to remove the object.
+    ///
+    /// If this returns `false`, the remove page of the object responds with
+    /// `403 Forbidden`. The default implementation allows removing all
+    /// objects.
+    fn can_delete(&self, user: &dyn User, object: &dyn AdminModel) -> bool {
+        let _ = (user, object);
+        true
+    }
 }
 
 /// A default implementation of [`AdminModelManager`] for an [`AdminModel`].
@@ -617,6 +982,352 @@ pub trait AdminModel: Any + Send + 'static {
     async fn remove_by_id(request: &mut Request, object_id: &str) -> cot::Result<()>
     where
         Self: Sized;
+
+    /// Returns the workflow transitions of this model instance that the user
+    /// is allowed to perform.
+    ///
+    /// A button is displayed for each of them on the edit page of the
+    /// instance. The default implementation returns no transitions; the
+    /// derive macro implements this using
+    /// [`Workflow`](crate::workflow::Workflow) when the
+    /// `#[admin(workflow)]` attribute is present.
+    fn workflow_transitions(&self, user: &dyn User) -> Vec<WorkflowTransition> {
+        let _ = user;
+        Vec::new()
+    }
+
+    /// Performs the workflow transition of this model instance to the given
+    /// state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transition is not allowed, or if the instance
+    /// could not be saved. The default implementation always returns a
+    /// `404 Not Found` error, as the model has no workflow.
+    async fn workflow_transition(
+        &mut self,
+        request: &Request,
+        user: &(dyn User + Send + Sync),
+        to: &str,
+    ) -> cot::Result<()> {
+        let _ = (request, user);
+        Err(Error::from(NotFound::with_message(format!(
+            "Workflow state `{to}` not found"
+        ))))
+    }
+}
+
+/// An object displayed on a model's page in the admin panel.
+///
+/// This is passed to the custom model list templates in
+/// [`AdminModelListPage::objects`].
+#[derive(Debug)]
+pub struct AdminObjectRow {
+    #[debug("..")]
+    object: Box<dyn AdminModel>,
+    can_change: bool,
+    can_delete: bool,
+}
+
+impl AdminObjectRow {
+    /// Returns the object.
+    #[must_use]
+    pub fn object(&self) -> &dyn AdminModel {
+        &*self.object
+    }
+
+    /// Returns whether the current user is allowed to change the object.
+    #[must_use]
+    pub fn can_change(&self) -> bool {
+        self.can_change
+    }
+
+    /// Returns whether the current user is allowed to delete the object.
+    #[must_use]
+    pub fn can_delete(&self) -> bool {
+        self.can_delete
+    }
+}
+
+/// The data of a model's page in the admin panel, passed to the custom
+/// templates set with [`AdminSiteConfig::with_model_list_template`].
+#[derive(Debug)]
+pub struct AdminModelListPage<'a> {
+    urls: &'a Urls,
+    #[debug("..")]
+    model: &'a dyn AdminModelManager,
+    objects: &'a [AdminObjectRow],
+    page: u64,
+    page_size: u64,
+    total_object_count: u64,
+    total_pages: u64,
+}
+
+impl<'a> AdminModelListPage<'a> {
+    /// Returns the URLs of the admin app, which can be used with
+    /// [`reverse!`](crate::reverse) to link to the other admin pages.
+    #[must_use]
+    pub fn urls(&self) -> &'a Urls {
+        self.urls
+    }
+
+    /// Returns the manager of the displayed model.
+    #[must_use]
+    pub fn model(&self) -> &'a dyn AdminModelManager {
+        self.model
+    }
+
+    /// Returns the objects on the current page that the user is allowed to
+    /// view.
+    #[must_use]
+    pub fn objects(&self) -> &'a [AdminObjectRow] {
+        self.objects
+    }
+
+    /// Returns the number of the current page, starting from 1.
+    #[must_use]
+    pub fn page(&self) -> u64 {
+        self.page
+    }
+
+    /// Returns the maximum number of objects on a page.
+    #[must_use]
+    pub fn page_size(&self) -> u64 {
+        self.page_size
+    }
+
+    /// Returns the total number of the model's objects.
+    #[must_use]
+    pub fn total_object_count(&self) -> u64 {
+        self.total_object_count
+    }
+
+    /// Returns the total number of pages.
+    #[must_use]
+    pub fn total_pages(&self) -> u64 {
+        self.total_pages
+    }
+}
+
+type ModelListTemplateFn =
+    Arc<dyn Fn(&AdminModelListPage<'_>) -> crate::Result<Html> + Send + Sync>;
+
+#[derive(Debug, Clone)]
+struct AdminModelGroup {
+    name: Cow<'static, str>,
+    models: Vec<String>,
+}
+
+/// The appearance of the admin panel.
+///
+/// This is passed to [`AdminApp::new`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::{AdminApp, AdminSiteConfig};
+///
+/// let config = AdminSiteConfig::new()
+///     .with_site_title("My Blog")
+///     .with_site_header("My Blog Administration")
+///     .with_logo_url("/static/logo.svg")
+///     .with_group("Content", ["post", "comment"])
+///     .with_group("Users", ["database_user"]);
+/// let app = AdminApp::new(config);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdminSiteConfig {
+    site_title: Cow<'static, str>,
+    site_header: Cow<'static, str>,
+    logo_url: Option<Cow<'static, str>>,
+    groups: Vec<AdminModelGroup>,
+    #[debug("..")]
+    model_list_templates: HashMap<String, ModelListTemplateFn>,
+}
+
+impl Default for AdminSiteConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdminSiteConfig {
+    /// Creates the default admin panel configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminSiteConfig;
+    ///
+    /// let config = AdminSiteConfig::new();
+    /// assert_eq!(config.site_title(), "Cot Admin");
+    /// assert_eq!(config.site_header(), "Cot Administration");
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            site_title: Cow::Borrowed("Cot Admin"),
+            site_header: Cow::Borrowed("Cot Administration"),
+            logo_url: None,
+            groups: Vec::new(),
+            model_list_templates: HashMap::new(),
+        }
+    }
+
+    /// Sets the name of the site displayed in the browser's title bar
+    /// (`"Cot Admin"` by default).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminSiteConfig;
+    ///
+    /// let config = AdminSiteConfig::new().with_site_title("My Blog");
+    /// assert_eq!(config.site_title(), "My Blog");
+    /// ```
+    #[must_use]
+    pub fn with_site_title(mut self, site_title: impl Into<Cow<'static, str>>) -> Self {
+        self.site_title = site_title.into();
+        self
+    }
+
+    /// Sets the header displayed at the top of every admin page
+    /// (`"Cot Administration"` by default).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminSiteConfig;
+    ///
+    /// let config = AdminSiteConfig::new().with_site_header("My Blog Administration");
+    /// assert_eq!(config.site_header(), "My Blog Administration");
+    /// ```
+    #[must_use]
+    pub fn with_site_header(mut self, site_header: impl Into<Cow<'static, str>>) -> Self {
+        self.site_header = site_header.into();
+        self
+    }
+
+    /// Sets the URL of the logo displayed next to the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminSiteConfig;
+    ///
+    /// let config = AdminSiteConfig::new().with_logo_url("/static/logo.svg");
+    /// assert_eq!(config.logo_url(), Some("/static/logo.svg"));
+    /// ```
+    #[must_use]
+    pub fn with_logo_url(mut self, logo_url: impl Into<Cow<'static, str>>) -> Self {
+        self.logo_url = Some(logo_url.into());
+        self
+    }
+
+    /// Adds a group of models displayed together on the admin panel's main
+    /// page.
+    ///
+    /// The models are identified by their
+    /// [URL names](AdminModelManager::url_name) and displayed in the given
+    /// order. The groups are displayed in the order they were added, followed
+    /// by the models that don't belong to any group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminSiteConfig;
+    ///
+    /// let config = AdminSiteConfig::new()
+    ///     .with_group("Content", ["post", "comment"])
+    ///     .with_group("Users", ["database_user"]);
+    /// ```
+    #[must_use]
+    pub fn with_group<I, M>(mut self, name: impl Into<Cow<'static, str>>, models: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<String>,
+    {
+        self.groups.push(AdminModelGroup {
+            name: name.into(),
+            models: models.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    /// Overrides the template of the page listing the objects of the model
+    /// with the given [URL name](AdminModelManager::url_name).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Template;
+    /// use cot::admin::{AdminModelListPage, AdminSiteConfig};
+    /// use cot::html::Html;
+    ///
+    /// #[derive(Template)]
+    /// #[template(
+    ///     source = "<h1>{{ page.model().name() }}</h1>{% for row in page.objects() %}<p>{{ row.object().display() }}</p>{% endfor %}",
+    ///     ext = "html"
+    /// )]
+    /// struct PostListTemplate<'a> {
+    ///     page: &'a AdminModelListPage<'a>,
+    /// }
+    ///
+    /// let config = AdminSiteConfig::new().with_model_list_template("post", |page| {
+    ///     Ok(Html::new(PostListTemplate { page }.render()?))
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_model_list_template<F>(mut self, model: impl Into<String>, template: F) -> Self
+    where
+        F: Fn(&AdminModelListPage<'_>) -> crate::Result<Html> + Send + Sync + 'static,
+    {
+        self.model_list_templates
+            .insert(model.into(), Arc::new(template));
+        self
+    }
+
+    /// Returns the name of the site displayed in the browser's title bar.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminSiteConfig;
+    ///
+    /// assert_eq!(AdminSiteConfig::new().site_title(), "Cot Admin");
+    /// ```
+    #[must_use]
+    pub fn site_title(&self) -> &str {
+        &self.site_title
+    }
+
+    /// Returns the header displayed at the top of every admin page.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminSiteConfig;
+    ///
+    /// assert_eq!(AdminSiteConfig::new().site_header(), "Cot Administration");
+    /// ```
+    #[must_use]
+    pub fn site_header(&self) -> &str {
+        &self.site_header
+    }
+
+    /// Returns the URL of the logo displayed next to the header, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminSiteConfig;
+    ///
+    /// assert_eq!(AdminSiteConfig::new().logo_url(), None);
+    /// ```
+    #[must_use]
+    pub fn logo_url(&self) -> Option<&str> {
+        self.logo_url.as_deref()
+    }
 }
 
 /// The admin app.
@@ -631,76 +1342,114 @@ pub trait AdminModel: Any + Send + 'static {
 /// struct MyProject;
 /// impl Project for MyProject {
 ///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
-///         apps.register_with_views(AdminApp::new(), "/admin");
+///         apps.register_with_views(AdminApp::default(), "/admin");
 ///     }
 /// }
 /// ```
-#[derive(Debug, Copy, Clone)]
-pub struct AdminApp;
-
-impl Default for AdminApp {
-    fn default() -> Self {
-        Self::new()
-    }
+#[derive(Debug, Clone, Default)]
+pub struct AdminApp {
+    site: AdminSite,
 }
 
 impl AdminApp {
-    /// Creates an admin app instance.
+    /// Creates an admin app instance with the given appearance.
+    ///
+    /// Use [`AdminApp::default`] to create the admin app with the default
+    /// appearance.
     ///
     /// # Examples
     ///
     /// ```
-    /// use cot::admin::AdminApp;
+    /// use cot::admin::{AdminApp, AdminSiteConfig};
     /// use cot::project::RegisterAppsContext;
     /// use cot::{AppBuilder, Project};
     ///
     /// struct MyProject;
     /// impl Project for MyProject {
     ///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
-    ///         apps.register_with_views(AdminApp::new(), "/admin");
+    ///         let config = AdminSiteConfig::new().with_site_header("My Project");
+    ///         apps.register_with_views(AdminApp::new(config), "/admin");
     ///     }
     /// }
     /// ```
     #[must_use]
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(config: AdminSiteConfig) -> Self {
+        Self {
+            site: AdminSite(Arc::new(config)),
+        }
     }
 }
 
+fn admin_route<T, H>(site: &AdminSite, url: &str, handler: H, name: &str) -> crate::router::Route
+where
+    T: 'static,
+    H: RequestHandler<T> + Send + Sync + 'static,
+{
+    crate::router::Route::with_handler_and_name(
+        url,
+        WithAdminSite::new(handler, site.clone()),
+        name,
+    )
+}
+
 impl App for AdminApp {
     fn name(&self) -> &'static str {
         "cot_admin"
     }
 
     fn router(&self) -> Router {
-        Router::with_urls([
-            crate::router::Route::with_handler_and_name(
-                "/",
-                AdminAuthenticated::new(index),
-                "index",
+        let mut urls = vec![
+            admin_route(&self.site, "/", AdminAuthenticated::new(index), "index"),
+            admin_route(&self.site, "/login/", login, "login"),
+            admin_route(
+                &self.site,
+                "/_sessions/",
+                AdminAuthenticated::new(user_sessions),
+                "user_sessions",
             ),
-            crate::router::Route::with_handler_and_name("/login/", login, "login"),
-            crate::router::Route::with_handler_and_name(
+        ];
+        // registered before the model routes, so that it's not shadowed by them
+        #[cfg(feature = "db")]
+        urls.push(admin_route(
+            &self.site,
+            "/_errors/",
+            AdminAuthenticated::new(error_log),
+            "error_log",
+        ));
+        urls.extend([
+            admin_route(
+                &self.site,
                 "/{model_name}/",
                 AdminAuthenticated::new(view_model),
                 "view_model",
             ),
-            crate::router::Route::with_handler_and_name(
+            admin_route(
+                &self.site,
                 "/{model_name}/create/",
                 AdminAuthenticated::new(create_model_instance),
                 "create_model_instance",
             ),
-            crate::router::Route::with_handler_and_name(
+            admin_route(
+                &self.site,
                 "/{model_name}/{pk}/edit/",
                 AdminAuthenticated::new(edit_model_instance),
                 "edit_model_instance",
             ),
-            crate::router::Route::with_handler_and_name(
+            admin_route(
+                &self.site,
                 "/{model_name}/{pk}/remove/",
                 AdminAuthenticated::new(remove_model_instance),
                 "remove_model_instance",
             ),
-        ])
+            admin_route(
+                &self.site,
+                "/{model_name}/{pk}/transition/{state}/",
+                AdminAuthenticated::new(transition_model_instance),
+                "transition_model_instance",
+            ),
+        ]);
+
+        Router::with_urls(urls)
     }
 
     fn static_files(&self) -> Vec<StaticFile> {
@@ -713,3 +1462,185 @@ impl App for AdminApp {
         )]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AnonymousUser;
+    use crate::test::TestRequestBuilder;
+
+    struct Note;
+
+    #[async_trait]
+    impl AdminModel for Note {
+        async fn get_objects(
+            _request: &Request,
+            _pagination: Pagination,
+        ) -> cot::Result<Vec<Self>> {
+            unimplemented!()
+        }
+
+        async fn get_total_object_counts(_request: &Request) -> cot::Result<u64> {
+            unimplemented!()
+        }
+
+        async fn get_object_by_id(_request: &Request, _id: &str) -> cot::Result<Option<Self>> {
+            unimplemented!()
+        }
+
+        fn name() -> &'static str {
+            "Note"
+        }
+
+        fn url_name() -> &'static str {
+            "note"
+        }
+
+        fn id(&self) -> String {
+            "1".to_owned()
+        }
+
+        fn display(&self) -> String {
+            "Note".to_owned()
+        }
+
+        fn form_context() -> Box<dyn FormContext> {
+            unimplemented!()
+        }
+
+        async fn form_context_from_self(&self) -> Box<dyn FormContext> {
+            unimplemented!()
+        }
+
+        async fn save_from_request(
+            _request: &mut Request,
+            _object_id: Option<&str>,
+        ) -> cot::Result<Option<Box<dyn FormContext>>> {
+            unimplemented!()
+        }
+
+        async fn remove_by_id(_request: &mut Request, _object_id: &str) -> cot::Result<()> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn default_object_permissions() {
+        let manager = DefaultAdminModelManager::<Note>::new();
+
+        assert!(manager.can_view(&AnonymousUser, &Note));
+        assert!(manager.can_change(&AnonymousUser, &Note));
+        assert!(manager.can_delete(&AnonymousUser, &Note));
+    }
+
+    #[cot::test]
+    async fn default_workflow() {
+        let mut note = Note;
+
+        assert!(note.workflow_transitions(&AnonymousUser).is_empty());
+        let error = note
+            .workflow_transition(
+                &TestRequestBuilder::get("/").build(),
+                &AnonymousUser,
+                "published",
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn permission_denied_is_forbidden() {
+        let manager = DefaultAdminModelManager::<Note>::new();
+
+        let error = permission_denied("change", &manager, "1");
+
+        assert_eq!(error.status_code(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            error.to_string(),
+            "You are not allowed to change the object with ID `1` in model `Note`"
+        );
+    }
+
+    fn group_names(groups: &[ModelGroup<&'static str>]) -> Vec<(String, Vec<&'static str>)> {
+        groups
+            .iter()
+            .map(|group| (group.name.to_string(), group.models.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn group_models_default() {
+        let groups = group_models(&AdminSiteConfig::new(), vec!["b", "a"], |name| name);
+
+        assert_eq!(
+            group_names(&groups),
+            [("Choose a model to manage".to_owned(), vec!["b", "a"])]
+        );
+    }
+
+    #[test]
+    fn group_models_configured() {
+        let config = AdminSiteConfig::new()
+            .with_group("Content", ["post", "missing", "comment"])
+            .with_group("Empty", ["missing"])
+            .with_group("Users", ["user"]);
+
+        let groups = group_models(&config, vec!["comment", "user", "tag", "post"], |name| name);
+
+        assert_eq!(
+            group_names(&groups),
+            [
+                ("Content".to_owned(), vec!["post", "comment"]),
+                ("Users".to_owned(), vec!["user"]),
+                ("Other models".to_owned(), vec!["tag"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn group_models_all_grouped() {
+        let config = AdminSiteConfig::new().with_group("Content", ["post"]);
+
+        let groups = group_models(&config, vec!["post"], |name| name);
+
+        assert_eq!(group_names(&groups), [("Content".to_owned(), vec!["post"])]);
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn error_log_registered() {
+        let apps: Vec<Box<dyn App>> = vec![Box::new(AdminApp::default())];
+        assert!(!is_error_log_registered(&apps));
+
+        let apps: Vec<Box<dyn App>> = vec![
+            Box::new(AdminApp::default()),
+            Box::new(crate::error::log::ErrorLogApp::new()),
+        ];
+        assert!(is_error_log_registered(&apps));
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    async fn error_log_not_registered() {
+        let request = TestRequestBuilder::get("/_errors/").build();
+        let (head, _body) = request.into_parts();
+        let registered = ErrorLogRegistered::from_request_head(&head).await.unwrap();
+
+        assert!(!registered.0);
+    }
+
+    #[test]
+    fn admin_site_config() {
+        let config = AdminSiteConfig::new()
+            .with_site_title("Title")
+            .with_site_header("Header")
+            .with_logo_url("/logo.svg")
+            .with_model_list_template("note", |_page| Ok(Html::new("notes")));
+
+        assert_eq!(config.site_title(), "Title");
+        assert_eq!(config.site_header(), "Header");
+        assert_eq!(config.logo_url(), Some("/logo.svg"));
+        assert!(config.model_list_templates.contains_key("note"));
+    }
+}
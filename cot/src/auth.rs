@@ -2,7 +2,8 @@
 //!
 //! This module provides the authentication system for Cot. It includes
 //! traits for user objects and backends, as well as password hashing and
-//! verification.
+//! verification. Handlers can be restricted to the users with a given
+//! permission with [`PermissionRequired`].
 //!
 //! For the default way to store users in the database, see the [`db`] module.
 
@@ -11,6 +12,7 @@ pub mod db;
 
 use std::any::Any;
 use std::borrow::Cow;
+use std::marker::PhantomData;
 use std::sync::{Arc, Mutex, MutexGuard};
 
 /// backwards compatible shim for form Password type.
@@ -21,14 +23,21 @@ use derive_more::with_trait::Debug;
 #[cfg(test)]
 use mockall::automock;
 use password_auth::VerifyError;
+use pbkdf2::password_hash::rand_core::OsRng;
+use pbkdf2::password_hash::{
+    ParamsString, PasswordHash as PhcHash, PasswordHasher as _, SaltString,
+};
 use serde::{Deserialize, Serialize};
 use subtle::ConstantTimeEq;
 use thiserror::Error;
 
+use crate::RequestHandler;
 use crate::config::SecretKey;
 #[cfg(feature = "db")]
 use crate::db::{ColumnType, DatabaseField, DbValue, FromDbValue, SqlxValueRef, ToDbValue};
-use crate::request::{Request, RequestExt};
+use crate::request::extractors::FromRequestHead;
+use crate::request::{ExpectedPathParams, Request, RequestExt, RequestHead};
+use crate::response::Response;
 use crate::session::Session;
 
 const ERROR_PREFIX: &str = "failed to authenticate user:";
@@ -54,6 +63,13 @@ pub enum AuthError {
     /// supported.
     #[error("{ERROR_PREFIX} tried to get a user by an unsupported user ID type")]
     UserIdTypeNotSupported,
+    /// The request doesn't contain a valid `Authorization: Bearer` header.
+    #[error("{ERROR_PREFIX} missing or malformed bearer token")]
+    BearerTokenMissing,
+    /// The bearer token provided in the request is invalid, expired, or
+    /// doesn't belong to an active user.
+    #[error("{ERROR_PREFIX} invalid bearer token")]
+    BearerTokenInvalid,
 }
 impl_into_cot_error!(AuthError, UNAUTHORIZED);
 
@@ -224,6 +240,42 @@ pub trait User {
     fn session_auth_hash(&self, secret_key: &SecretKey) -> Option<SessionAuthHash> {
         None
     }
+
+    /// Returns whether the user has the given permission.
+    ///
+    /// The permissions are arbitrary strings, such as `"blog.edit_post"`,
+    /// whose meaning is defined by the project. They are used, for instance,
+    /// to filter the navigation items declared with
+    /// [`App::nav_items`](crate::App::nav_items).
+    ///
+    /// [`AnonymousUser`] always returns `false`. The default implementation
+    /// returns `false` as well, so the users need to implement this method to
+    /// be granted any permissions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::User;
+    ///
+    /// struct AdminUser;
+    ///
+    /// impl User for AdminUser {
+    ///     fn is_authenticated(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn has_permission(&self, permission: &str) -> bool {
+    ///         // administrators can do anything
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// assert!(AdminUser.has_permission("blog.edit_post"));
+    /// ```
+    #[expect(unused_variables)]
+    fn has_permission(&self, permission: &str) -> bool {
+        false
+    }
 }
 
 /// A user ID that uniquely identifies a user in a backend.
@@ -294,7 +346,17 @@ impl UserId {
     }
 }
 
+impl std::fmt::Display for UserId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int(id) => write!(f, "{id}"),
+            Self::String(id) => f.write_str(id),
+        }
+    }
+}
+
 /// A helper wrapper over `Arc<dyn User>` to provide a `Debug` implementation.
+#[derive(Clone)]
 #[repr(transparent)]
 struct UserWrapper(Arc<dyn User + Send + Sync>);
 
@@ -462,11 +524,129 @@ impl Debug for SessionAuthHash {
     }
 }
 
+/// A password hashing algorithm.
+///
+/// The algorithm used for newly created password hashes can be chosen with
+/// [`ProjectConfig::password_hasher`](crate::config::ProjectConfig::password_hasher).
+/// Each [`PasswordHash`] records the algorithm it was created with, so changing
+/// the preferred hasher doesn't invalidate existing passwords. Instead, the
+/// passwords are transparently re-hashed with the preferred algorithm the next
+/// time the user logs in (see [`PasswordHash::verify_with`]).
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::{PasswordHash, PasswordHasher};
+/// use cot::common_types::Password;
+///
+/// let hash = PasswordHash::from_password_with(&Password::new("password"), PasswordHasher::Pbkdf2);
+/// assert_eq!(hash.hasher(), PasswordHasher::Pbkdf2);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum PasswordHasher {
+    /// Argon2id, the winner of the Password Hashing Competition.
+    ///
+    /// This is the default and recommended algorithm.
+    #[default]
+    Argon2,
+    /// bcrypt, with the default cost factor of
+    /// [`bcrypt::DEFAULT_COST`](https://docs.rs/bcrypt/latest/bcrypt/constant.DEFAULT_COST.html).
+    ///
+    /// Note that bcrypt only uses the first 72 bytes of the password.
+    Bcrypt,
+    /// PBKDF2 with HMAC-SHA256.
+    Pbkdf2,
+}
+
+impl PasswordHasher {
+    fn hash(self, password: &str) -> String {
+        match self {
+            Self::Argon2 => password_auth::generate_hash(password),
+            Self::Bcrypt => bcrypt::hash(password, bcrypt::DEFAULT_COST)
+                .expect("bcrypt hashing should never fail with the default cost"),
+            Self::Pbkdf2 => {
+                let salt = SaltString::generate(&mut OsRng);
+                pbkdf2::Pbkdf2
+                    .hash_password(password.as_bytes(), &salt)
+                    .expect("PBKDF2 hashing should never fail with the default params")
+                    .to_string()
+            }
+        }
+    }
+
+    /// Returns the algorithm the hash was created with, or `None` if the hash
+    /// is not a valid hash of any of the supported algorithms.
+    ///
+    /// The whole hash is parsed, including the algorithm parameters, the salt
+    /// and the hash itself, so that a malformed hash is rejected up front
+    /// rather than when a password is verified against it.
+    fn identify(hash: &str) -> Option<Self> {
+        if hash.parse::<bcrypt::HashParts>().is_ok() {
+            return Some(Self::Bcrypt);
+        }
+
+        let hash = PhcHash::new(hash).ok()?;
+        if hash.salt.is_none() || hash.hash.is_none() {
+            return None;
+        }
+        match hash.algorithm.as_str() {
+            "argon2id" | "argon2i" | "argon2d" => {
+                argon2::Params::try_from(&hash).ok().map(|_| Self::Argon2)
+            }
+            "pbkdf2-sha256" | "pbkdf2-sha512" => {
+                pbkdf2::Params::try_from(&hash).ok().map(|_| Self::Pbkdf2)
+            }
+            _ => None,
+        }
+    }
+
+    /// Verifies the password against the hash created with this algorithm.
+    ///
+    /// Returns `false` if the hash cannot be parsed, although this shouldn't
+    /// happen for the hashes validated by [`PasswordHash::new`].
+    fn verify(self, password: &str, hash: &str) -> bool {
+        match self {
+            Self::Argon2 | Self::Pbkdf2 => match password_auth::verify_password(password, hash) {
+                Ok(()) => true,
+                Err(VerifyError::PasswordInvalid | VerifyError::Parse(_)) => false,
+            },
+            Self::Bcrypt => bcrypt::verify(password, hash).unwrap_or(false),
+        }
+    }
+
+    /// Returns whether the hash, created with this algorithm, uses parameters
+    /// other than the currently recommended ones.
+    ///
+    /// Returns `false` if the hash cannot be parsed, although this shouldn't
+    /// happen for the hashes validated by [`PasswordHash::new`].
+    fn is_hash_obsolete(self, hash: &str) -> bool {
+        match self {
+            Self::Argon2 => password_auth::is_hash_obsolete(hash).unwrap_or(false),
+            Self::Bcrypt => hash
+                .parse::<bcrypt::HashParts>()
+                .is_ok_and(|parts| parts.get_cost() < bcrypt::DEFAULT_COST),
+            Self::Pbkdf2 => {
+                let Ok(hash) = PhcHash::new(hash) else {
+                    return false;
+                };
+                let default_params = ParamsString::try_from(pbkdf2::Params::default())
+                    .expect("default PBKDF2 params should always be valid");
+                hash.algorithm != pbkdf2::Algorithm::default().ident()
+                    || hash.params != default_params
+            }
+        }
+    }
+}
+
+const VALID_HASH_ERROR_STR: &str = "password hash should always be valid if created with `PasswordHash::new` or `PasswordHash::from_password`";
+
 /// A hashed password.
 ///
 /// This is used to store a hashed user password in the database. The password
-/// hash is created using the `password_auth` crate internally, so by default,
-/// it generates the hash using the latest recommended algorithm.
+/// hash is created using the latest recommended algorithm by default, but
+/// other algorithms are supported as well; see [`PasswordHasher`].
 ///
 /// # Security
 ///
@@ -504,17 +684,19 @@ impl PasswordHash {
     pub fn new<T: Into<String>>(hash: T) -> Result<Self> {
         let hash = hash.into();
 
-        if hash.len() > MAX_PASSWORD_HASH_LENGTH as usize {
+        if hash.len() > MAX_PASSWORD_HASH_LENGTH as usize
+            || PasswordHasher::identify(&hash).is_none()
+        {
             return Err(AuthError::PasswordHashInvalid);
         }
-        password_auth::is_hash_obsolete(&hash).map_err(|_| AuthError::PasswordHashInvalid)?;
 
         Ok(Self(hash))
     }
 
     /// Creates a new password hash from a password.
     ///
-    /// The password is hashed using the latest recommended algorithm.
+    /// The password is hashed using the latest recommended algorithm. To use a
+    /// different algorithm, use [`from_password_with`](Self::from_password_with).
     ///
     /// # Examples
     ///
@@ -526,7 +708,27 @@ impl PasswordHash {
     /// ```
     #[must_use]
     pub fn from_password(password: &crate::common_types::Password) -> Self {
-        let hash = password_auth::generate_hash(password.as_str());
+        Self::from_password_with(password, PasswordHasher::default())
+    }
+
+    /// Creates a new password hash from a password, using the given hashing
+    /// algorithm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::{PasswordHash, PasswordHasher};
+    /// use cot::common_types::Password;
+    ///
+    /// let hash = PasswordHash::from_password_with(&Password::new("password"), PasswordHasher::Bcrypt);
+    /// assert_eq!(hash.hasher(), PasswordHasher::Bcrypt);
+    /// ```
+    #[must_use]
+    pub fn from_password_with(
+        password: &crate::common_types::Password,
+        hasher: PasswordHasher,
+    ) -> Self {
+        let hash = hasher.hash(password.as_str());
 
         if hash.len() > MAX_PASSWORD_HASH_LENGTH as usize {
             unreachable!("password hash should never exceed {MAX_PASSWORD_HASH_LENGTH} bytes");
@@ -563,26 +765,68 @@ impl PasswordHash {
     /// }
     /// ```
     pub fn verify(&self, password: &crate::common_types::Password) -> PasswordVerificationResult {
-        const VALID_ERROR_STR: &str = "password hash should always be valid if created with `PasswordHash::new` or `PasswordHash::from_password`";
+        self.verify_with(password, PasswordHasher::default())
+    }
 
-        match password_auth::verify_password(password.as_str(), &self.0) {
-            Ok(()) => {
-                let Ok(is_obsolete) = password_auth::is_hash_obsolete(&self.0) else {
-                    unreachable!("{VALID_ERROR_STR}");
-                };
-                if is_obsolete {
-                    PasswordVerificationResult::OkObsolete(PasswordHash::from_password(password))
-                } else {
-                    PasswordVerificationResult::Ok
-                }
-            }
-            Err(error) => match error {
-                VerifyError::PasswordInvalid => PasswordVerificationResult::Invalid,
-                VerifyError::Parse(_) => unreachable!("{VALID_ERROR_STR}"),
-            },
+    /// Verifies a password against the hash, with the given hashing algorithm
+    /// being the preferred one.
+    ///
+    /// This works like [`verify`](Self::verify), except that
+    /// [`PasswordVerificationResult::OkObsolete`] is also returned when the
+    /// hash was created with an algorithm other than `preferred`. In that
+    /// case, the new hash is calculated using `preferred`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::{PasswordHash, PasswordHasher, PasswordVerificationResult};
+    /// use cot::common_types::Password;
+    ///
+    /// let password = Password::new("password");
+    /// let hash = PasswordHash::from_password_with(&password, PasswordHasher::Pbkdf2);
+    ///
+    /// match hash.verify_with(&password, PasswordHasher::Argon2) {
+    ///     PasswordVerificationResult::OkObsolete(new_hash) => {
+    ///         assert_eq!(new_hash.hasher(), PasswordHasher::Argon2);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn verify_with(
+        &self,
+        password: &crate::common_types::Password,
+        preferred: PasswordHasher,
+    ) -> PasswordVerificationResult {
+        let hasher = self.hasher();
+
+        if !hasher.verify(password.as_str(), &self.0) {
+            PasswordVerificationResult::Invalid
+        } else if hasher != preferred || hasher.is_hash_obsolete(&self.0) {
+            PasswordVerificationResult::OkObsolete(Self::from_password_with(password, preferred))
+        } else {
+            PasswordVerificationResult::Ok
         }
     }
 
+    /// Returns the algorithm that was used to create the hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::{PasswordHash, PasswordHasher};
+    /// use cot::common_types::Password;
+    ///
+    /// let hash = PasswordHash::from_password(&Password::new("password"));
+    /// assert_eq!(hash.hasher(), PasswordHasher::Argon2);
+    /// ```
+    #[must_use]
+    pub fn hasher(&self) -> PasswordHasher {
+        let Some(hasher) = PasswordHasher::identify(&self.0) else {
+            unreachable!("{VALID_HASH_ERROR_STR}");
+        };
+        hasher
+    }
+
     /// Returns the password hash as a string.
     ///
     /// For security reasons, you should avoid using this method as much as
@@ -794,6 +1038,231 @@ impl Auth {
     }
 }
 
+/// An error returned by [`PermissionRequired`] when the current user doesn't
+/// have the required permission.
+///
+/// This results in a `403 Forbidden` response.
+#[derive(Debug, Clone, Error)]
+#[error("permission denied: the `{permission}` permission is required")]
+pub struct PermissionDenied {
+    permission: Cow<'static, str>,
+}
+impl_into_cot_error!(PermissionDenied, FORBIDDEN);
+
+impl PermissionDenied {
+    /// Returns the permission the user doesn't have.
+    #[must_use]
+    pub fn permission(&self) -> &str {
+        &self.permission
+    }
+}
+
+/// A request handler that only lets through the users with the given
+/// permission.
+///
+/// The permission is checked with [`User::has_permission`] on the current
+/// user (see [`Auth::user`]); if the user doesn't have it, the wrapped handler
+/// is not called and a [`PermissionDenied`] error is returned instead.
+///
+/// The [`DatabaseUser`](db::DatabaseUser)s returned by the
+/// [`DatabaseUserBackend`](db::DatabaseUserBackend) have the permissions
+/// granted to them directly or through their groups; see the [`db`] module.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::PermissionRequired;
+/// use cot::html::Html;
+/// use cot::router::{Route, Router};
+///
+/// async fn edit_post() -> Html {
+///     Html::new("Edit the post")
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler_and_name(
+///     "/posts/edit/",
+///     PermissionRequired::new("blog.change_post", edit_post),
+///     "edit_post",
+/// )]);
+/// ```
+#[derive(Debug)]
+pub struct PermissionRequired<T, H> {
+    permission: Cow<'static, str>,
+    #[debug("..")]
+    handler: H,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, H: RequestHandler<T> + Send + Sync> PermissionRequired<T, H> {
+    /// Wraps the given handler so that it's only called for the users with
+    /// the given permission.
+    #[must_use]
+    pub fn new<P: Into<Cow<'static, str>>>(permission: P, handler: H) -> Self {
+        Self {
+            permission: permission.into(),
+            handler,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, H: RequestHandler<T> + Send + Sync> RequestHandler<T> for PermissionRequired<T, H> {
+    async fn handle(&self, mut request: Request) -> cot::Result<Response> {
+        let auth: Auth = request.extract_from_head().await?;
+        if !auth.user().has_permission(&self.permission) {
+            return Err(PermissionDenied {
+                permission: self.permission.clone(),
+            }
+            .into());
+        }
+
+        self.handler.handle(request).await
+    }
+
+    fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+        self.handler.expected_path_params()
+    }
+}
+
+/// A bearer token sent by the client in the `Authorization` header.
+///
+/// This is both an extractor, returning the raw token from the
+/// `Authorization: Bearer <token>` header, and a credentials type that can be
+/// passed to [`Auth::authenticate`] or [`AuthBackend::authenticate`]. The
+/// [`ApiTokenBackend`](db::token::ApiTokenBackend) authenticates users with
+/// bearer tokens; see also the [`BearerAuth`] extractor, which does it
+/// automatically.
+///
+/// When used as an extractor, a request without a well-formed bearer token
+/// results in a `401 Unauthorized` response.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::BearerToken;
+/// use cot::html::Html;
+///
+/// async fn view(token: BearerToken) -> Html {
+///     Html::new(format!("Token length: {}", token.as_str().len()))
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct BearerToken(String);
+
+impl BearerToken {
+    /// Creates a new bearer token from a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::BearerToken;
+    ///
+    /// let token = BearerToken::new("abc123");
+    /// assert_eq!(token.as_str(), "abc123");
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(token: T) -> Self {
+        Self(token.into())
+    }
+
+    /// Returns the token as a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::BearerToken;
+    ///
+    /// let token = BearerToken::new("abc123");
+    /// assert_eq!(token.as_str(), "abc123");
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn from_head(head: &RequestHead) -> Option<Self> {
+        let header = head
+            .headers
+            .get(http::header::AUTHORIZATION)?
+            .to_str()
+            .ok()?;
+        let (scheme, token) = header.split_once(' ')?;
+        let token = token.trim();
+
+        if scheme.eq_ignore_ascii_case("bearer") && !token.is_empty() {
+            Some(Self::new(token))
+        } else {
+            None
+        }
+    }
+}
+
+impl Debug for BearerToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BearerToken").field(&"**********").finish()
+    }
+}
+
+impl FromRequestHead for BearerToken {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        Self::from_head(head).ok_or_else(|| AuthError::BearerTokenMissing.into())
+    }
+}
+
+/// An extractor that authenticates the client with the bearer token sent in
+/// the `Authorization` header.
+///
+/// The token is passed as a [`BearerToken`] to the auth backend configured
+/// in [`ProjectConfig::auth_backend`](crate::config::ProjectConfig::auth_backend),
+/// which should therefore support it, such as
+/// [`ApiTokenBackend`](db::token::ApiTokenBackend). Unlike [`Auth`], this
+/// doesn't use the session, so it's suitable for JSON APIs whose clients
+/// don't store cookies.
+///
+/// If the token is missing or the backend doesn't return a user for it, a
+/// `401 Unauthorized` response is returned instead of calling the handler.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::BearerAuth;
+/// use cot::json::Json;
+///
+/// async fn me(auth: BearerAuth) -> Json<Option<String>> {
+///     Json(auth.user().username().map(|name| name.into_owned()))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BearerAuth {
+    user: UserWrapper,
+}
+
+impl BearerAuth {
+    /// Returns the user the bearer token belongs to.
+    #[must_use]
+    pub fn user(&self) -> Arc<dyn User + Send + Sync> {
+        Arc::clone(&self.user.0)
+    }
+}
+
+impl FromRequestHead for BearerAuth {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let token = BearerToken::from_request_head(head).await?;
+        let user = head
+            .context()
+            .auth_backend()
+            .authenticate(&token)
+            .await?
+            .filter(|user| user.is_active())
+            .ok_or(AuthError::BearerTokenInvalid)?;
+        record_user_id(&*user);
+
+        Ok(Self {
+            user: UserWrapper(Arc::from(user)),
+        })
+    }
+}
+
 #[derive(Debug)]
 struct AuthInner {
     session: Session,
@@ -822,6 +1291,7 @@ impl AuthInner {
                 || Arc::new(AnonymousUser) as Arc<dyn User + Send + Sync>,
                 Arc::from,
             );
+        record_user_id(&*user);
 
         Ok(Self {
             session,
@@ -855,7 +1325,7 @@ impl AuthInner {
     async fn login(&self, user: Box<dyn User + Send + Sync + 'static>) -> Result<()> {
         // Mitigate the session fixation attack by changing the session ID:
         // https://cheatsheetseries.owasp.org/cheatsheets/Session_Management_Cheat_Sheet.html#renew-the-session-id-after-any-privilege-level-change
-        self.session.cycle_id().await?;
+        self.session.cycle_key().await?;
 
         if let Some(user_id) = user.id() {
             self.session.insert(USER_ID_SESSION_KEY, user_id).await?;
@@ -866,6 +1336,7 @@ impl AuthInner {
                 .insert(SESSION_HASH_SESSION_KEY, session_auth_hash.as_bytes())
                 .await?;
         }
+        record_user_id(&*user);
         *self.user_lock() = UserWrapper(Arc::from(user));
 
         Ok(())
@@ -888,7 +1359,19 @@ impl AuthInner {
     }
 }
 
-const USER_ID_SESSION_KEY: &str = "__cot_auth_user_id";
+/// Records the ID of the authenticated user in the span of the current
+/// request.
+fn record_user_id(user: &(dyn User + Send + Sync)) {
+    let span = tracing::Span::current();
+    if !span.has_field("user_id") {
+        return;
+    }
+    if let Some(user_id) = user.id() {
+        span.record("user_id", tracing::field::display(user_id));
+    }
+}
+
+pub(crate) const USER_ID_SESSION_KEY: &str = "__cot_auth_user_id";
 const SESSION_HASH_SESSION_KEY: &str = "__cot_auth_session_hash";
 
 async fn get_user_with_saved_id(
@@ -1035,6 +1518,7 @@ mod tests {
     use mockall::predicate::eq;
 
     use super::*;
+    use crate::StatusCode;
     use crate::common_types::Password;
     use crate::config::ProjectConfig;
     use crate::test::TestRequestBuilder;
@@ -1157,6 +1641,121 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn password_hash_all_hashers() {
+        let password = Password::new("password");
+        let wrong_password = Password::new("wrongpassword");
+
+        for hasher in [
+            PasswordHasher::Argon2,
+            PasswordHasher::Bcrypt,
+            PasswordHasher::Pbkdf2,
+        ] {
+            let hash = PasswordHash::from_password_with(&password, hasher);
+            assert_eq!(hash.hasher(), hasher);
+            assert!(matches!(
+                hash.verify_with(&password, hasher),
+                PasswordVerificationResult::Ok
+            ));
+            assert!(matches!(
+                hash.verify_with(&wrong_password, hasher),
+                PasswordVerificationResult::Invalid
+            ));
+
+            let hash = PasswordHash::new(hash.into_string()).unwrap();
+            assert_eq!(hash.hasher(), hasher);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn password_hash_verify_migrates_hasher() {
+        let password = Password::new("password");
+        let hash = PasswordHash::from_password_with(&password, PasswordHasher::Bcrypt);
+
+        match hash.verify(&password) {
+            PasswordVerificationResult::OkObsolete(new_hash) => {
+                assert_eq!(new_hash.hasher(), PasswordHasher::Argon2);
+                assert!(matches!(
+                    new_hash.verify(&password),
+                    PasswordVerificationResult::Ok
+                ));
+            }
+            _ => panic!("Bcrypt hash should be obsolete when Argon2 is preferred"),
+        }
+
+        match hash.verify_with(&Password::new("wrongpassword"), PasswordHasher::Argon2) {
+            PasswordVerificationResult::Invalid => {}
+            _ => panic!("Password hash verification failed"),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn password_hash_verify_obsolete_params() {
+        let password = Password::new("password");
+        // bcrypt hash with a cost lower than the default
+        let low_cost_hash = bcrypt::hash(password.as_str(), 4).unwrap();
+
+        let hash = PasswordHash::new(low_cost_hash).unwrap();
+        assert_eq!(hash.hasher(), PasswordHasher::Bcrypt);
+        match hash.verify_with(&password, PasswordHasher::Bcrypt) {
+            PasswordVerificationResult::OkObsolete(new_hash) => {
+                assert_eq!(new_hash.hasher(), PasswordHasher::Bcrypt);
+                assert!(!PasswordHasher::Bcrypt.is_hash_obsolete(new_hash.as_str()));
+            }
+            _ => panic!("Low cost bcrypt hash should be obsolete"),
+        }
+    }
+
+    #[test]
+    fn password_hash_invalid() {
+        assert!(PasswordHash::new("").is_err());
+        assert!(PasswordHash::new("plaintext").is_err());
+        assert!(PasswordHash::new("$scrypt$ln=16,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E").is_err());
+        assert!(PasswordHash::new(format!("$argon2id${}", "a".repeat(200))).is_err());
+    }
+
+    #[test]
+    fn password_hash_malformed_phc() {
+        // bad params
+        assert!(PasswordHash::new("$argon2id$v=19$m=abc").is_err());
+        assert!(
+            PasswordHash::new(
+                "$argon2id$v=19$m=abc,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG"
+            )
+            .is_err()
+        );
+        assert!(
+            PasswordHash::new(
+                "$pbkdf2-sha256$i=abc,l=32$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG"
+            )
+            .is_err()
+        );
+        // no salt or hash
+        assert!(PasswordHash::new("$argon2id$v=19$m=19456,t=2,p=1").is_err());
+        assert!(PasswordHash::new("$argon2id$v=19$m=19456,t=2,p=1$c29tZXNhbHQ").is_err());
+        // truncated bcrypt hash
+        assert!(PasswordHash::new("$2b$12$abcdefghijklmnopqrstuv").is_err());
+    }
+
+    #[test]
+    fn password_hasher_malformed_hash() {
+        let password = "password";
+        let hash = "$argon2id$v=19$m=abc";
+
+        for hasher in [
+            PasswordHasher::Argon2,
+            PasswordHasher::Pbkdf2,
+            PasswordHasher::Bcrypt,
+        ] {
+            assert!(!hasher.verify(password, hash));
+            // must not panic; the result is irrelevant as the hash never verifies
+            let _ = hasher.is_hash_obsolete(hash);
+        }
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn password_hash_str() {
@@ -1200,6 +1799,48 @@ mod tests {
         assert_eq!(auth.user().username(), Some(Cow::from("mockuser")));
     }
 
+    async fn test_request_with_permission(has_permission: bool) -> Request {
+        let mut request = test_request(move || {
+            let mut mock_user = MockUser::new();
+            mock_user.expect_id().return_const(UserId::Int(1));
+            mock_user.expect_session_auth_hash().return_const(None);
+            mock_user
+                .expect_has_permission()
+                .withf(|permission| permission == "blog.change_post")
+                .return_const(has_permission);
+            mock_user
+        });
+
+        Session::from_request(&request)
+            .insert(USER_ID_SESSION_KEY, UserId::Int(1))
+            .await
+            .unwrap();
+        let auth = Auth::from_request(&mut request).await.unwrap();
+        request.extensions_mut().insert(auth);
+        request
+    }
+
+    #[cot::test]
+    async fn permission_required() {
+        async fn handler() -> crate::html::Html {
+            crate::html::Html::new("OK")
+        }
+
+        let handler = PermissionRequired::new("blog.change_post", handler);
+
+        let response = handler
+            .handle(test_request_with_permission(true).await)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let error = handler
+            .handle(test_request_with_permission(false).await)
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::FORBIDDEN);
+    }
+
     #[cot::test]
     async fn authenticate() {
         let mut request = test_request(|| {
@@ -1216,6 +1857,57 @@ mod tests {
         assert_eq!(user.username(), Some(Cow::from("mockuser")));
     }
 
+    #[cot::test]
+    async fn bearer_token() {
+        let mut request = TestRequestBuilder::get("/").build();
+        let result: cot::Result<BearerToken> = request.extract_from_head().await;
+        assert!(result.is_err());
+
+        for header in ["Basic dXNlcjpwYXNz", "Bearer", "Bearer  ", "abc123"] {
+            request.headers_mut().insert(
+                http::header::AUTHORIZATION,
+                http::HeaderValue::from_static(header),
+            );
+            let result: cot::Result<BearerToken> = request.extract_from_head().await;
+            assert!(result.is_err(), "{header}");
+        }
+
+        request.headers_mut().insert(
+            http::header::AUTHORIZATION,
+            http::HeaderValue::from_static("bearer abc123"),
+        );
+        let token: BearerToken = request.extract_from_head().await.unwrap();
+        assert_eq!(token, BearerToken::new("abc123"));
+        assert_eq!(format!("{token:?}"), "BearerToken(\"**********\")");
+    }
+
+    #[cot::test]
+    async fn bearer_auth() {
+        let mut request = test_request(|| {
+            let mut mock_user = MockUser::new();
+            mock_user.expect_is_active().return_const(true);
+            mock_user
+                .expect_username()
+                .return_const(Some(Cow::from("mockuser")));
+            mock_user
+        });
+        assert!(request.extract_from_head::<BearerAuth>().await.is_err());
+
+        request.headers_mut().insert(
+            http::header::AUTHORIZATION,
+            http::HeaderValue::from_static("Bearer abc123"),
+        );
+        let auth: BearerAuth = request.extract_from_head().await.unwrap();
+        assert_eq!(auth.user().username(), Some(Cow::from("mockuser")));
+
+        let mut request = test_request_with_auth_backend(NoAuthBackend);
+        request.headers_mut().insert(
+            http::header::AUTHORIZATION,
+            http::HeaderValue::from_static("Bearer abc123"),
+        );
+        assert!(request.extract_from_head::<BearerAuth>().await.is_err());
+    }
+
     #[cot::test]
     async fn login_logout() {
         let mut request = test_request(MockUser::new);
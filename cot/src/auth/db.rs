@@ -5,9 +5,11 @@
 
 use std::any::Any;
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 
 use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Utc};
 // Importing `Auto` from `cot` instead of `crate` so that the migration generator
 // can figure out it's an autogenerated field
 use cot::db::Auto;
@@ -19,18 +21,24 @@ use thiserror::Error;
 use crate::App;
 use crate::admin::{AdminModelManager, DefaultAdminModelManager};
 use crate::auth::{
-    AuthBackend, AuthError, PasswordHash, PasswordVerificationResult, Result, SessionAuthHash,
-    User, UserId,
+    AuthBackend, AuthError, PasswordHash, PasswordHasher, PasswordVerificationResult, Result,
+    SessionAuthHash, User, UserId,
 };
-use crate::common_types::Password;
+use crate::common_types::{Email, Password};
 use crate::config::SecretKey;
-use crate::db::migrations::SyncDynMigration;
-use crate::db::{Database, DatabaseBackend, LimitedString, Model, model, query};
+use crate::db::migrations::{ModelSchema, SyncDynMigration};
+use crate::db::query::{Expr, Query};
+use crate::db::{Database, DatabaseBackend, LimitedString, ManyToMany, Model, model, query};
 use crate::form::Form;
 
+#[cfg(feature = "email")]
+pub mod account;
 pub mod migrations;
+pub mod token;
 
 pub(crate) const MAX_USERNAME_LENGTH: u32 = 255;
+pub(crate) const MAX_PERMISSION_LENGTH: u32 = 255;
+pub(crate) const MAX_GROUP_NAME_LENGTH: u32 = 150;
 
 /// A user stored in the database.
 #[derive(Debug, Clone, Form, AdminModel)]
@@ -41,6 +49,8 @@ pub struct DatabaseUser {
     #[model(unique)]
     username: LimitedString<MAX_USERNAME_LENGTH>,
     password: PasswordHash,
+    email: Option<Email>,
+    email_confirmed_at: Option<DateTime<FixedOffset>>,
 }
 
 /// An error that occurs when creating a user.
@@ -58,11 +68,14 @@ impl DatabaseUser {
         id: Auto<i64>,
         username: LimitedString<MAX_USERNAME_LENGTH>,
         password: &Password,
+        hasher: PasswordHasher,
     ) -> Self {
         Self {
             id,
             username,
-            password: PasswordHash::from_password(password),
+            password: PasswordHash::from_password_with(password, hasher),
+            email: None,
+            email_confirmed_at: None,
         }
     }
 
@@ -102,6 +115,24 @@ impl DatabaseUser {
         db: &DB,
         username: T,
         password: U,
+    ) -> Result<Self> {
+        Self::create_user_with(db, username, password, PasswordHasher::default()).await
+    }
+
+    /// Creates a new user and saves it to the database, hashing the password
+    /// with the given algorithm.
+    ///
+    /// [`DatabaseUserBackend::create_user`] calls this with the password
+    /// hasher configured in the backend.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user could not be saved.
+    pub async fn create_user_with<DB: DatabaseBackend, T: Into<String>, U: Into<Password>>(
+        db: &DB,
+        username: T,
+        password: U,
+        hasher: PasswordHasher,
     ) -> Result<Self> {
         let username = username.into();
         let username_length = username.len();
@@ -109,7 +140,7 @@ impl DatabaseUser {
             AuthError::backend_error(CreateUserError::UsernameTooLong(username_length))
         })?;
 
-        let mut user = Self::new(Auto::auto(), username, &password.into());
+        let mut user = Self::new(Auto::auto(), username, &password.into(), hasher);
         user.insert(db).await.map_err(AuthError::backend_error)?;
 
         Ok(user)
@@ -215,14 +246,86 @@ impl DatabaseUser {
         Ok(db_user)
     }
 
+    /// Retrieves all users with the given email address.
+    ///
+    /// Email addresses are not required to be unique, so this can return more
+    /// than one user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::common_types::{Email, Password};
+    /// use cot::db::{Database, Model};
+    /// use cot::html::Html;
+    ///
+    /// async fn view(db: Database) -> cot::Result<Html> {
+    ///     let email = Email::new("testuser@example.com").unwrap();
+    ///     let mut user =
+    ///         DatabaseUser::create_user(&db, "testuser".to_string(), &Password::new("password123"))
+    ///             .await?;
+    ///     user.set_email(Some(email.clone()));
+    ///     user.save(&db).await?;
+    ///
+    ///     let users = DatabaseUser::find_by_email(&db, &email).await?;
+    ///     assert_eq!(users.len(), 1);
+    ///
+    ///     Ok(Html::new("User found!"))
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// #     use cot::test::TestDatabase;
+    /// #     let mut test_database = TestDatabase::new_sqlite().await?;
+    /// #     test_database.with_auth().run_migrations().await;
+    /// #     view(test_database.database()).await?;
+    /// #     test_database.cleanup().await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub async fn find_by_email<DB: DatabaseBackend>(db: &DB, email: &Email) -> Result<Vec<Self>> {
+        let email = Some(email.clone());
+        let db_users = query!(DatabaseUser, $email == email)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(db_users)
+    }
+
     /// Authenticates a user using the provided credentials.
     ///
+    /// If the password is valid, but its hash is obsolete, the password is
+    /// re-hashed with the default [`PasswordHasher`] and the user is saved.
+    ///
     /// # Errors
     ///
     /// Returns an error if there was an error querying the database.
     pub async fn authenticate<DB: DatabaseBackend>(
         db: &DB,
         credentials: &DatabaseUserCredentials,
+    ) -> Result<Option<Self>> {
+        Self::authenticate_with(db, credentials, PasswordHasher::default()).await
+    }
+
+    /// Authenticates a user using the provided credentials, with the given
+    /// password hashing algorithm being the preferred one.
+    ///
+    /// If the password is valid, but its hash was created with a different
+    /// algorithm or is otherwise obsolete, the password is re-hashed with
+    /// `preferred` and the user is saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    pub async fn authenticate_with<DB: DatabaseBackend>(
+        db: &DB,
+        credentials: &DatabaseUserCredentials,
+        preferred: PasswordHasher,
     ) -> Result<Option<Self>> {
         let username = credentials.username();
         let username_limited = LimitedString::<MAX_USERNAME_LENGTH>::new(username.to_string())
@@ -236,7 +339,7 @@ impl DatabaseUser {
 
         if let Some(mut user) = user {
             let password_hash = &user.password;
-            match password_hash.verify(credentials.password()) {
+            match password_hash.verify_with(credentials.password(), preferred) {
                 PasswordVerificationResult::Ok => Ok(Some(user)),
                 PasswordVerificationResult::OkObsolete(new_hash) => {
                     user.password = new_hash;
@@ -251,8 +354,10 @@ impl DatabaseUser {
             // do something with the result to prevent the compiler from optimizing out the
             // operation.
             // TODO: benchmark this to make sure it works as expected
-            let dummy_hash = PasswordHash::from_password(credentials.password());
-            if let PasswordVerificationResult::Invalid = dummy_hash.verify(credentials.password()) {
+            let dummy_hash = PasswordHash::from_password_with(credentials.password(), preferred);
+            if let PasswordVerificationResult::Invalid =
+                dummy_hash.verify_with(credentials.password(), preferred)
+            {
                 unreachable!(
                     "Password hash verification should never fail for a newly generated hash"
                 );
@@ -343,6 +448,229 @@ impl DatabaseUser {
     pub fn username(&self) -> &str {
         &self.username
     }
+
+    /// Sets a new password for the user.
+    ///
+    /// This only changes the user object; call
+    /// [`Model::save`](crate::db::Model::save) to persist the change. Since
+    /// the session auth hash is derived from the password, changing it logs the
+    /// user out of all their sessions.
+    pub fn set_password(&mut self, password: &Password) {
+        self.set_password_with(password, PasswordHasher::default());
+    }
+
+    /// Sets a new password for the user, hashing it with the given algorithm.
+    ///
+    /// Like [`DatabaseUser::set_password`], this only changes the user object.
+    pub fn set_password_with(&mut self, password: &Password, hasher: PasswordHasher) {
+        self.password = PasswordHash::from_password_with(password, hasher);
+    }
+
+    /// Returns the email address of the user, if set.
+    #[must_use]
+    pub fn email(&self) -> Option<&Email> {
+        self.email.as_ref()
+    }
+
+    /// Sets the email address of the user.
+    ///
+    /// If the address is different from the current one, it is marked as not
+    /// confirmed. This only changes the user object; call
+    /// [`Model::save`](crate::db::Model::save) to persist the change.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::common_types::{Email, Password};
+    /// use cot::db::{Database, Model};
+    /// use cot::html::Html;
+    ///
+    /// async fn view(db: Database) -> cot::Result<Html> {
+    ///     let mut user =
+    ///         DatabaseUser::create_user(&db, "testuser".to_string(), &Password::new("password123"))
+    ///             .await?;
+    ///     user.set_email(Some(Email::new("testuser@example.com").unwrap()));
+    ///     user.save(&db).await?;
+    ///     assert!(!user.is_email_confirmed());
+    ///
+    ///     Ok(Html::new("Email set!"))
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// #     use cot::test::TestDatabase;
+    /// #     let mut test_database = TestDatabase::new_sqlite().await?;
+    /// #     test_database.with_auth().run_migrations().await;
+    /// #     view(test_database.database()).await?;
+    /// #     test_database.cleanup().await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn set_email(&mut self, email: Option<Email>) {
+        if self.email != email {
+            self.email = email;
+            self.email_confirmed_at = None;
+        }
+    }
+
+    /// Returns the time the email address of the user was confirmed at, or
+    /// [`None`] if it hasn't been confirmed (or is not set).
+    #[must_use]
+    pub fn email_confirmed_at(&self) -> Option<DateTime<FixedOffset>> {
+        self.email_confirmed_at
+    }
+
+    /// Returns whether the user has confirmed their email address.
+    #[must_use]
+    pub fn is_email_confirmed(&self) -> bool {
+        self.email.is_some() && self.email_confirmed_at.is_some()
+    }
+
+    /// Marks the current email address of the user as confirmed.
+    ///
+    /// This does nothing if the user doesn't have an email address. This only
+    /// changes the user object; call [`Model::save`](crate::db::Model::save)
+    /// to persist the change.
+    pub fn confirm_email(&mut self) {
+        if self.email.is_some() {
+            self.email_confirmed_at = Some(Utc::now().fixed_offset());
+        }
+    }
+
+    /// Returns the groups the user belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    pub async fn groups<DB: DatabaseBackend>(&self, db: &DB) -> Result<Vec<Group>> {
+        let user = self;
+        let joins = query!(GroupUsersJoin, $target == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        let filter = joins
+            .iter()
+            .map(|join| {
+                Expr::eq(
+                    Expr::field(Group::PRIMARY_KEY_NAME),
+                    Expr::value(*join.source.primary_key()),
+                )
+            })
+            .reduce(Expr::or);
+        let Some(filter) = filter else {
+            return Ok(Vec::new());
+        };
+
+        Query::<Group>::new()
+            .filter(filter)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Returns the codenames of all the permissions of the user, both the ones
+    /// granted directly and the ones granted through the groups the user
+    /// belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    pub async fn permissions<DB: DatabaseBackend>(&self, db: &DB) -> Result<HashSet<String>> {
+        let user = self;
+        let mut permission_ids: Vec<_> = query!(PermissionUsersJoin, $target == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?
+            .into_iter()
+            .map(|join| *join.source.primary_key())
+            .collect();
+
+        let group_joins = query!(GroupUsersJoin, $target == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        for group_join in group_joins {
+            let group = group_join.source;
+            let permission_joins = query!(GroupPermissionsJoin, $source == group)
+                .all(db)
+                .await
+                .map_err(AuthError::backend_error)?;
+            permission_ids.extend(
+                permission_joins
+                    .into_iter()
+                    .map(|join| *join.target.primary_key()),
+            );
+        }
+
+        let filter = permission_ids
+            .into_iter()
+            .map(|id| Expr::eq(Expr::field(Permission::PRIMARY_KEY_NAME), Expr::value(id)))
+            .reduce(Expr::or);
+        let Some(filter) = filter else {
+            return Ok(HashSet::new());
+        };
+
+        let permissions = Query::<Permission>::new()
+            .filter(filter)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        Ok(permissions
+            .into_iter()
+            .map(|permission| permission.codename.as_str().to_owned())
+            .collect())
+    }
+
+    /// Returns whether the user has the permission with the given codename,
+    /// either granted directly or through one of their groups.
+    ///
+    /// This always queries the database. The users returned by the
+    /// [`DatabaseUserBackend`] (for instance, by
+    /// [`Auth::user`](crate::auth::Auth::user)) load their permissions once,
+    /// so [`User::has_permission`] can be used on them instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{DatabaseUser, Group, Permission};
+    /// use cot::common_types::Password;
+    /// use cot::db::Database;
+    /// use cot::html::Html;
+    ///
+    /// async fn view(db: Database) -> cot::Result<Html> {
+    ///     let user =
+    ///         DatabaseUser::create_user(&db, "testuser".to_string(), &Password::new("password123"))
+    ///             .await?;
+    ///     let permission = Permission::get_or_create(&db, "blog.change_post").await?;
+    ///     let editors = Group::create(&db, "editors").await?;
+    ///     editors.permissions().add(&db, &permission).await?;
+    ///     editors.users().add(&db, &user).await?;
+    ///
+    ///     assert!(user.has_perm(&db, "blog.change_post").await?);
+    ///     assert!(!user.has_perm(&db, "blog.delete_post").await?);
+    ///
+    ///     Ok(Html::new("Permissions checked!"))
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// #     use cot::test::TestDatabase;
+    /// #     let mut test_database = TestDatabase::new_sqlite().await?;
+    /// #     test_database.with_auth().run_migrations().await;
+    /// #     view(test_database.database()).await?;
+    /// #     test_database.cleanup().await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub async fn has_perm<DB: DatabaseBackend>(&self, db: &DB, permission: &str) -> Result<bool> {
+        Ok(self.permissions(db).await?.contains(permission))
+    }
 }
 
 type SessionAuthHmac = Hmac<Sha512>;
@@ -380,6 +708,284 @@ impl Display for DatabaseUser {
     }
 }
 
+/// A permission that can be granted to [`DatabaseUser`]s, either directly or
+/// through a [`Group`].
+///
+/// A permission is identified by its codename, which by convention has the
+/// form of `app.action_model`, such as `blog.change_post`. When the
+/// migrations are applied, the `view`, `add`, `change`, and `delete`
+/// permissions are created for each of the models registered in the admin
+/// panel of the project's apps, such as `cot_db_user.change_database_user`.
+///
+/// The users a permission is granted to directly are available through the
+/// [`Permission::users`] relation.
+///
+/// Note that the relations have to be removed before a permission or a user
+/// can be deleted.
+#[derive(Debug, Clone)]
+#[model]
+pub struct Permission {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    #[model(unique)]
+    codename: LimitedString<MAX_PERMISSION_LENGTH>,
+    users: ManyToMany<DatabaseUser>,
+}
+
+/// An error that occurs when creating a permission or a group.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum CreatePermissionError {
+    /// The permission codename is too long.
+    #[error("permission codename is too long (max {MAX_PERMISSION_LENGTH} characters, got {0})")]
+    CodenameTooLong(usize),
+    /// The group name is too long.
+    #[error("group name is too long (max {MAX_GROUP_NAME_LENGTH} characters, got {0})")]
+    GroupNameTooLong(usize),
+}
+
+impl Permission {
+    /// Retrieves the permission with the given codename, creating it if it
+    /// doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the codename is too long or if there was an error
+    /// querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{DatabaseUser, Permission};
+    /// use cot::common_types::Password;
+    /// use cot::db::Database;
+    /// use cot::html::Html;
+    ///
+    /// async fn view(db: Database) -> cot::Result<Html> {
+    ///     let user =
+    ///         DatabaseUser::create_user(&db, "testuser".to_string(), &Password::new("password123"))
+    ///             .await?;
+    ///     let permission = Permission::get_or_create(&db, "blog.change_post").await?;
+    ///     permission.users().add(&db, &user).await?;
+    ///
+    ///     Ok(Html::new("Permission granted!"))
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// #     use cot::test::TestDatabase;
+    /// #     let mut test_database = TestDatabase::new_sqlite().await?;
+    /// #     test_database.with_auth().run_migrations().await;
+    /// #     view(test_database.database()).await?;
+    /// #     test_database.cleanup().await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub async fn get_or_create<DB: DatabaseBackend>(db: &DB, codename: &str) -> Result<Self> {
+        if let Some(permission) = Self::get_by_codename(db, codename).await? {
+            return Ok(permission);
+        }
+
+        let mut permission = Self {
+            id: Auto::auto(),
+            codename: Self::limited_codename(codename)?,
+            users: ManyToMany::new(),
+        };
+        permission
+            .insert(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(permission)
+    }
+
+    /// Retrieves a permission by its codename. It returns [`None`] if the
+    /// permission does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the codename is too long or if there was an error
+    /// querying the database.
+    pub async fn get_by_codename<DB: DatabaseBackend>(
+        db: &DB,
+        codename: &str,
+    ) -> Result<Option<Self>> {
+        let codename = Self::limited_codename(codename)?;
+        query!(Permission, $codename == codename)
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Returns the codename of the permission.
+    #[must_use]
+    pub fn codename(&self) -> &str {
+        &self.codename
+    }
+
+    fn limited_codename(codename: &str) -> Result<LimitedString<MAX_PERMISSION_LENGTH>> {
+        LimitedString::new(codename).map_err(|_| {
+            AuthError::backend_error(CreatePermissionError::CodenameTooLong(codename.len()))
+        })
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.codename)
+    }
+}
+
+/// A group of [`DatabaseUser`]s.
+///
+/// The users belonging to a group (available through the [`Group::users`]
+/// relation) are granted all the permissions of the group (available through
+/// the [`Group::permissions`] relation).
+///
+/// Note that the relations have to be removed before a group or a user can be
+/// deleted.
+///
+/// # Example
+///
+/// ```
+/// use cot::auth::db::{DatabaseUser, Group, Permission};
+/// use cot::common_types::Password;
+/// use cot::db::Database;
+/// use cot::html::Html;
+///
+/// async fn view(db: Database) -> cot::Result<Html> {
+///     let user =
+///         DatabaseUser::create_user(&db, "testuser".to_string(), &Password::new("password123"))
+///             .await?;
+///     let editors = Group::create(&db, "editors").await?;
+///     let permission = Permission::get_or_create(&db, "blog.change_post").await?;
+///     editors.permissions().add(&db, &permission).await?;
+///     editors.users().add(&db, &user).await?;
+///
+///     Ok(Html::new("User added to the group!"))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// #     use cot::test::TestDatabase;
+/// #     let mut test_database = TestDatabase::new_sqlite().await?;
+/// #     test_database.with_auth().run_migrations().await;
+/// #     view(test_database.database()).await?;
+/// #     test_database.cleanup().await?;
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[model]
+pub struct Group {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    #[model(unique)]
+    name: LimitedString<MAX_GROUP_NAME_LENGTH>,
+    permissions: ManyToMany<Permission>,
+    users: ManyToMany<DatabaseUser>,
+}
+
+impl Group {
+    /// Creates a new group and saves it to the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is too long or if the group could not be
+    /// saved.
+    pub async fn create<DB: DatabaseBackend, T: Into<String>>(db: &DB, name: T) -> Result<Self> {
+        let name = name.into();
+        let name_length = name.len();
+        let name = LimitedString::new(name).map_err(|_| {
+            AuthError::backend_error(CreatePermissionError::GroupNameTooLong(name_length))
+        })?;
+
+        let mut group = Self {
+            id: Auto::auto(),
+            name,
+            permissions: ManyToMany::new(),
+            users: ManyToMany::new(),
+        };
+        group.insert(db).await.map_err(AuthError::backend_error)?;
+
+        Ok(group)
+    }
+
+    /// Retrieves a group by its name. It returns [`None`] if the group does
+    /// not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is too long or if there was an error
+    /// querying the database.
+    pub async fn get_by_name<DB: DatabaseBackend>(db: &DB, name: &str) -> Result<Option<Self>> {
+        let name = LimitedString::<MAX_GROUP_NAME_LENGTH>::new(name).map_err(|_| {
+            AuthError::backend_error(CreatePermissionError::GroupNameTooLong(name.len()))
+        })?;
+        query!(Group, $name == name)
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Returns the name of the group.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for Group {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Creates the default permissions of the models registered in the admin
+/// panel, if the [`DatabaseUserApp`] is registered in the project.
+pub(crate) async fn create_default_permissions(
+    apps: &[Box<dyn App>],
+    db: &Database,
+) -> crate::db::Result<()> {
+    const ACTIONS: [&str; 4] = ["view", "add", "change", "delete"];
+
+    let user_app = DatabaseUserApp::new();
+    if !apps.iter().any(|app| app.name() == user_app.name()) {
+        return Ok(());
+    }
+
+    let existing: HashSet<String> = Permission::objects()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|permission| permission.codename.as_str().to_owned())
+        .collect();
+
+    for app in apps {
+        for manager in app.admin_model_managers() {
+            for action in ACTIONS {
+                let codename = format!("{}.{action}_{}", app.name(), manager.url_name());
+                if existing.contains(&codename) {
+                    continue;
+                }
+                let Ok(codename) = LimitedString::new(codename.as_str()) else {
+                    tracing::warn!("The `{codename}` permission codename is too long; skipping");
+                    continue;
+                };
+
+                let mut permission = Permission {
+                    id: Auto::auto(),
+                    codename,
+                    users: ManyToMany::new(),
+                };
+                permission.insert(db).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Credentials for authenticating a user stored in the database.
 ///
 /// This struct is used to authenticate a user stored in the database. It
@@ -455,6 +1061,7 @@ impl DatabaseUserCredentials {
 #[derive(Debug, Clone)]
 pub struct DatabaseUserBackend {
     database: Database,
+    password_hasher: PasswordHasher,
 }
 
 impl DatabaseUserBackend {
@@ -481,7 +1088,57 @@ impl DatabaseUserBackend {
     /// ```
     #[must_use]
     pub fn new(database: Database) -> Self {
-        Self { database }
+        Self {
+            database,
+            password_hasher: PasswordHasher::default(),
+        }
+    }
+
+    /// Sets the preferred password hashing algorithm.
+    ///
+    /// Users whose passwords were hashed with a different algorithm are
+    /// transparently migrated to this one when they log in. By default,
+    /// [`PasswordHasher::Argon2`] is used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::auth::db::DatabaseUserBackend;
+    /// use cot::auth::{AuthBackend, PasswordHasher};
+    /// use cot::project::AuthBackendContext;
+    /// use cot::Project;
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn auth_backend(&self, context: &AuthBackendContext) -> Arc<dyn AuthBackend> {
+    ///         Arc::new(
+    ///             DatabaseUserBackend::new(context.database().clone())
+    ///                 .password_hasher(PasswordHasher::Bcrypt),
+    ///         )
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn password_hasher(mut self, password_hasher: PasswordHasher) -> Self {
+        self.password_hasher = password_hasher;
+        self
+    }
+
+    /// Creates a new user and saves it to the database, hashing the password
+    /// with the preferred password hashing algorithm of this backend.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user could not be saved.
+    pub async fn create_user<T: Into<String>, U: Into<Password>>(
+        &self,
+        username: T,
+        password: U,
+    ) -> Result<DatabaseUser> {
+        DatabaseUser::create_user_with(&self.database, username, password, self.password_hasher)
+            .await
     }
 }
 
@@ -492,10 +1149,16 @@ impl AuthBackend for DatabaseUserBackend {
         credentials: &(dyn Any + Send + Sync),
     ) -> Result<Option<Box<dyn User + Send + Sync>>> {
         if let Some(credentials) = credentials.downcast_ref::<DatabaseUserCredentials>() {
-            #[expect(trivial_casts)] // Upcast to the correct Box type
-            Ok(DatabaseUser::authenticate(&self.database, credentials)
-                .await
-                .map(|user| user.map(|user| Box::new(user) as Box<dyn User + Send + Sync>))?)
+            let Some(user) =
+                DatabaseUser::authenticate_with(&self.database, credentials, self.password_hasher)
+                    .await?
+            else {
+                return Ok(None);
+            };
+
+            Ok(Some(Box::new(
+                DatabaseUserWithPermissions::load(&self.database, user).await?,
+            )))
         } else {
             Err(AuthError::CredentialsTypeNotSupported)
         }
@@ -506,16 +1169,84 @@ impl AuthBackend for DatabaseUserBackend {
             return Err(AuthError::UserIdTypeNotSupported);
         };
 
-        #[expect(trivial_casts)] // Upcast to the correct Box type
-        Ok(DatabaseUser::get_by_id(&self.database, id)
-            .await?
-            .map(|user| Box::new(user) as Box<dyn User + Send + Sync>))
+        let Some(user) = DatabaseUser::get_by_id(&self.database, id).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Box::new(
+            DatabaseUserWithPermissions::load(&self.database, user).await?,
+        )))
+    }
+}
+
+/// A [`DatabaseUser`] returned by the [`DatabaseUserBackend`], along with its
+/// permissions loaded from the database, so that they can be checked with
+/// [`User::has_permission`].
+#[derive(Debug, Clone)]
+struct DatabaseUserWithPermissions {
+    user: DatabaseUser,
+    permissions: HashSet<String>,
+}
+
+impl DatabaseUserWithPermissions {
+    async fn load<DB: DatabaseBackend>(db: &DB, user: DatabaseUser) -> Result<Self> {
+        let permissions = user.permissions(db).await?;
+
+        Ok(Self { user, permissions })
+    }
+}
+
+impl User for DatabaseUserWithPermissions {
+    fn id(&self) -> Option<UserId> {
+        User::id(&self.user)
+    }
+
+    fn username(&self) -> Option<Cow<'_, str>> {
+        User::username(&self.user)
+    }
+
+    fn is_active(&self) -> bool {
+        self.user.is_active()
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.user.is_authenticated()
+    }
+
+    fn last_login(&self) -> Option<DateTime<FixedOffset>> {
+        self.user.last_login()
+    }
+
+    fn joined(&self) -> Option<DateTime<FixedOffset>> {
+        self.user.joined()
+    }
+
+    fn session_auth_hash(&self, secret_key: &SecretKey) -> Option<SessionAuthHash> {
+        self.user.session_auth_hash(secret_key)
+    }
+
+    fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.contains(permission)
     }
 }
 
 /// An app that provides authentication via a user model stored in the database.
-#[derive(Debug, Copy, Clone)]
-pub struct DatabaseUserApp;
+///
+/// When the `email` feature is enabled, the app can also provide password
+/// reset and email confirmation views; see
+/// [`DatabaseUserApp::with_account_views`].
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    not(feature = "email"),
+    expect(
+        missing_copy_implementations,
+        reason = "the app is not `Copy` when the `email` feature is enabled"
+    )
+)]
+pub struct DatabaseUserApp {
+    #[cfg(feature = "email")]
+    account_views: Option<account::AccountViews>,
+}
 
 impl Default for DatabaseUserApp {
     fn default() -> Self {
@@ -555,7 +1286,44 @@ impl DatabaseUserApp {
     /// ```
     #[must_use]
     pub fn new() -> Self {
-        Self {}
+        Self {
+            #[cfg(feature = "email")]
+            account_views: None,
+        }
+    }
+
+    /// Enables the password reset and email confirmation views.
+    ///
+    /// The views are served by the router of the app, so the app needs to be
+    /// registered with [`AppBuilder::register_with_views`](crate::AppBuilder::register_with_views).
+    /// See the [`account`] module for the list of the views and for the
+    /// details on how to customize them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUserApp;
+    /// use cot::auth::db::account::AccountViews;
+    /// use cot::common_types::Email;
+    /// use cot::project::RegisterAppsContext;
+    /// use cot::{AppBuilder, Project};
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+    ///         let account_views = AccountViews::new(Email::new("no-reply@example.com").unwrap());
+    ///         apps.register_with_views(
+    ///             DatabaseUserApp::new().with_account_views(account_views),
+    ///             "/accounts",
+    ///         );
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "email")]
+    #[must_use]
+    pub fn with_account_views(mut self, account_views: account::AccountViews) -> Self {
+        self.account_views = Some(account_views);
+        self
     }
 }
 
@@ -571,6 +1339,25 @@ impl App for DatabaseUserApp {
     fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
         cot::db::migrations::wrap_migrations(migrations::MIGRATIONS)
     }
+
+    fn models(&self) -> Vec<ModelSchema> {
+        vec![
+            ModelSchema::of::<DatabaseUser>(),
+            ModelSchema::of::<Permission>(),
+            ModelSchema::of::<Group>(),
+            ModelSchema::of::<PermissionUsersJoin>(),
+            ModelSchema::of::<GroupPermissionsJoin>(),
+            ModelSchema::of::<GroupUsersJoin>(),
+            ModelSchema::of::<token::ApiToken>(),
+        ]
+    }
+
+    #[cfg(feature = "email")]
+    fn router(&self) -> crate::router::Router {
+        self.account_views
+            .as_ref()
+            .map_or_else(crate::router::Router::empty, account::AccountViews::router)
+    }
 }
 
 #[cfg(test)]
@@ -578,6 +1365,7 @@ mod tests {
     use super::*;
     use crate::config::SecretKey;
     use crate::db::MockDatabaseBackend;
+    use crate::test::TestDatabase;
 
     #[test]
     #[cfg_attr(miri, ignore)]
@@ -586,6 +1374,7 @@ mod tests {
             Auto::fixed(1),
             LimitedString::new("testuser").unwrap(),
             &Password::new("password123"),
+            PasswordHasher::default(),
         );
         let secret_key = SecretKey::new(b"supersecretkey");
 
@@ -593,6 +1382,81 @@ mod tests {
         assert!(hash.is_some());
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn database_user_app_models_match_migrations() {
+        let app = DatabaseUserApp::new();
+
+        let engine = crate::db::migrations::MigrationEngine::new(app.migrations()).unwrap();
+
+        assert_eq!(engine.check_models(&app.models()), vec![]);
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn default_permissions() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let apps: Vec<Box<dyn App>> = vec![Box::new(DatabaseUserApp::new())];
+
+        // running it again must not create duplicates
+        create_default_permissions(&apps, &test_db.database())
+            .await
+            .unwrap();
+        create_default_permissions(&apps, &test_db.database())
+            .await
+            .unwrap();
+
+        let mut codenames: Vec<_> = Permission::objects()
+            .all(&test_db.database())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|permission| permission.codename().to_owned())
+            .collect();
+        codenames.sort_unstable();
+        assert_eq!(
+            codenames,
+            [
+                "cot_db_user.add_database_user",
+                "cot_db_user.change_database_user",
+                "cot_db_user.delete_database_user",
+                "cot_db_user.view_database_user",
+            ]
+        );
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn database_user_email() {
+        let mut user = DatabaseUser::new(
+            Auto::fixed(1),
+            LimitedString::new("testuser").unwrap(),
+            &Password::new("password123"),
+            PasswordHasher::default(),
+        );
+        let email = Email::new("testuser@example.com").unwrap();
+
+        user.confirm_email();
+        assert!(!user.is_email_confirmed());
+
+        user.set_email(Some(email.clone()));
+        user.confirm_email();
+        assert!(user.is_email_confirmed());
+
+        user.set_email(Some(email.clone()));
+        assert!(user.is_email_confirmed());
+
+        user.set_email(Some(Email::new("other@example.com").unwrap()));
+        assert!(!user.is_email_confirmed());
+        assert_eq!(user.email_confirmed_at(), None);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn database_user_traits() {
@@ -600,6 +1464,7 @@ mod tests {
             Auto::fixed(1),
             LimitedString::new("testuser").unwrap(),
             &Password::new("password123"),
+            PasswordHasher::default(),
         );
         let user_ref: &dyn User = &user;
         assert_eq!(user_ref.id(), Some(UserId::Int(1)));
@@ -630,6 +1495,74 @@ mod tests {
         assert_eq!(user.username(), username);
     }
 
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn create_user_with_hasher() {
+        let mut mock_db = MockDatabaseBackend::new();
+        mock_db
+            .expect_insert::<DatabaseUser>()
+            .withf(|user| user.password.hasher() == PasswordHasher::Bcrypt)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let user = DatabaseUser::create_user_with(
+            &mock_db,
+            "testuser",
+            &Password::new("password123"),
+            PasswordHasher::Bcrypt,
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.password.hasher(), PasswordHasher::Bcrypt);
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn backend_create_user_uses_configured_hasher() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let config =
+            crate::config::ProjectConfig::from_toml(r#"password_hasher = "bcrypt""#).unwrap();
+        let backend =
+            DatabaseUserBackend::new(test_db.database()).password_hasher(config.password_hasher);
+
+        let user = backend
+            .create_user("testuser", &Password::new("password123"))
+            .await
+            .unwrap();
+
+        let user = DatabaseUser::get_by_id(&test_db.database(), user.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.password.hasher(), PasswordHasher::Bcrypt);
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn set_password_with_hasher() {
+        let mut user = DatabaseUser::new(
+            Auto::fixed(1),
+            LimitedString::new("testuser").unwrap(),
+            &Password::new("password123"),
+            PasswordHasher::default(),
+        );
+
+        user.set_password_with(&Password::new("newpassword"), PasswordHasher::Bcrypt);
+
+        assert_eq!(user.password.hasher(), PasswordHasher::Bcrypt);
+        assert!(matches!(
+            user.password
+                .verify_with(&Password::new("newpassword"), PasswordHasher::Bcrypt),
+            PasswordVerificationResult::Ok
+        ));
+    }
+
     #[cot::test]
     #[cfg_attr(miri, ignore)]
     async fn get_by_id() {
@@ -638,6 +1571,7 @@ mod tests {
             Auto::fixed(1),
             LimitedString::new("testuser").unwrap(),
             &Password::new("password123"),
+            PasswordHasher::default(),
         );
 
         mock_db
@@ -657,6 +1591,7 @@ mod tests {
             Auto::fixed(1),
             LimitedString::new("testuser").unwrap(),
             &Password::new("password123"),
+            PasswordHasher::default(),
         );
 
         mock_db
@@ -672,6 +1607,36 @@ mod tests {
         assert_eq!(result.unwrap().username(), "testuser");
     }
 
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn authenticate_rehashes_with_preferred_hasher() {
+        let mut mock_db = MockDatabaseBackend::new();
+        let user = DatabaseUser::new(
+            Auto::fixed(1),
+            LimitedString::new("testuser").unwrap(),
+            &Password::new("password123"),
+            PasswordHasher::default(),
+        );
+
+        mock_db
+            .expect_get::<DatabaseUser>()
+            .returning(move |_| Ok(Some(user.clone())));
+        mock_db
+            .expect_insert_or_update::<DatabaseUser>()
+            .withf(|user| user.password.hasher() == PasswordHasher::Pbkdf2)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let credentials =
+            DatabaseUserCredentials::new("testuser".to_string(), Password::new("password123"));
+        let result =
+            DatabaseUser::authenticate_with(&mock_db, &credentials, PasswordHasher::Pbkdf2)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(result.password.hasher(), PasswordHasher::Pbkdf2);
+    }
+
     #[cot::test]
     #[cfg_attr(miri, ignore)]
     async fn authenticate_non_existing() {
@@ -697,6 +1662,7 @@ mod tests {
             Auto::fixed(1),
             LimitedString::new("testuser").unwrap(),
             &Password::new("password123"),
+            PasswordHasher::default(),
         );
 
         mock_db
@@ -0,0 +1,1187 @@
+//! Password reset and email confirmation views for [`DatabaseUser`]s.
+//!
+//! The views are enabled by passing [`AccountViews`] to
+//! [`DatabaseUserApp::with_account_views`](super::DatabaseUserApp::with_account_views).
+//! The app then serves the following views (relative to the URL prefix the app
+//! is registered with):
+//!
+//! * `/password-reset/` (`password_reset`) – a form asking for an email
+//!   address. If there are users with that address, each of them is sent an
+//!   email with a link to the next view. The response is the same whether the
+//!   address is known or not, so the form can't be used to find out which
+//!   addresses are registered.
+//! * `/password-reset/{user_id}/{token}/` (`password_reset_confirm`) – a form
+//!   for setting a new password.
+//! * `/confirm-email/{user_id}/{token}/` (`confirm_email`) – marks the email
+//!   address of the user as confirmed. The email with the link to this view is
+//!   sent by [`AccountViews::send_confirmation_email`], typically after the user
+//!   has signed up or changed their email address.
+//!
+//! The tokens in the links are not stored anywhere. Instead, they are signed
+//! with the project's [secret key](crate::config::ProjectConfig::secret_key)
+//! and contain the time they were created at, so they expire after a
+//! configurable amount of time. A password reset token is also invalidated once
+//! the password is changed, and an email confirmation token once the address
+//! is confirmed or changed.
+//!
+//! The links in the emails are absolute URLs built from the
+//! [`CanonicalUrlConfig::host`](crate::config::CanonicalUrlConfig::host)
+//! setting, which therefore needs to be set; otherwise, sending the emails
+//! fails with [`AccountError::NoCanonicalHost`]. The `Host` header of the request
+//! is deliberately not used, as it can be controlled by an attacker to make
+//! the emails point to a different website.
+//!
+//! The pages and emails are rendered with [`AccountTemplates`], which can be
+//! replaced to match the look of the website.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use cot_core::error::impl_into_cot_error;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use thiserror::Error;
+
+use crate::auth::db::DatabaseUser;
+use crate::common_types::{Email, Password};
+use crate::config::SecretKey;
+use crate::db::Model;
+use crate::email::{EmailMessage, EmailMessageBuilder};
+use crate::error::MethodNotAllowed;
+use crate::form::{
+    Form, FormContext, FormErrorTarget, FormField, FormFieldValidationError, FormResult,
+};
+use crate::html::Html;
+use crate::request::extractors::Path;
+use crate::request::{Request, RequestExt};
+use crate::response::{IntoResponse, Response};
+use crate::router::{Route, Router};
+use crate::{Method, RequestHandler, Template, reverse_canonical};
+
+/// The default amount of time a password reset link is valid for.
+const DEFAULT_PASSWORD_RESET_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+/// The default amount of time an email confirmation link is valid for.
+const DEFAULT_EMAIL_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// An error that can occur when using the account views.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AccountError {
+    /// The user doesn't have an email address to send the email to.
+    #[error("{ERROR_PREFIX} user `{0}` does not have an email address")]
+    NoEmailAddress(String),
+    /// The canonical host is not configured, so absolute links for the emails
+    /// can't be built.
+    #[error(
+        "{ERROR_PREFIX} `canonical_url.host` must be set to send emails with links to the account views"
+    )]
+    NoCanonicalHost,
+}
+
+const ERROR_PREFIX: &str = "account views error:";
+
+impl_into_cot_error!(AccountError);
+
+/// The configuration of the password reset and email confirmation views.
+///
+/// See the [module documentation](self) for the list of the views.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::auth::db::account::AccountViews;
+/// use cot::common_types::Email;
+///
+/// let account_views = AccountViews::new(Email::new("no-reply@example.com").unwrap())
+///     .password_reset_timeout(Duration::from_secs(60 * 60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AccountViews {
+    from: Email,
+    password_reset_timeout: Duration,
+    email_confirmation_timeout: Duration,
+    templates: Arc<dyn AccountTemplates>,
+}
+
+impl AccountViews {
+    /// Creates the account views configuration with the address the emails
+    /// are sent from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::db::account::AccountViews;
+    /// use cot::common_types::Email;
+    ///
+    /// let account_views = AccountViews::new(Email::new("no-reply@example.com").unwrap());
+    /// ```
+    #[must_use]
+    pub fn new(from: Email) -> Self {
+        Self {
+            from,
+            password_reset_timeout: DEFAULT_PASSWORD_RESET_TIMEOUT,
+            email_confirmation_timeout: DEFAULT_EMAIL_CONFIRMATION_TIMEOUT,
+            templates: Arc::new(DefaultAccountTemplates),
+        }
+    }
+
+    /// Sets the amount of time a password reset link is valid for.
+    ///
+    /// The default is 24 hours.
+    #[must_use]
+    pub fn password_reset_timeout(mut self, timeout: Duration) -> Self {
+        self.password_reset_timeout = timeout;
+        self
+    }
+
+    /// Sets the amount of time an email confirmation link is valid for.
+    ///
+    /// The default is 3 days.
+    #[must_use]
+    pub fn email_confirmation_timeout(mut self, timeout: Duration) -> Self {
+        self.email_confirmation_timeout = timeout;
+        self
+    }
+
+    /// Sets the templates used to render the pages and the emails.
+    ///
+    /// The default is [`DefaultAccountTemplates`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::db::account::{AccountEmail, AccountTemplates, AccountViews};
+    /// use cot::common_types::Email;
+    /// use cot::email::EmailMessageBuilder;
+    ///
+    /// #[derive(Debug)]
+    /// struct MyTemplates;
+    ///
+    /// impl AccountTemplates for MyTemplates {
+    ///     fn render_email(
+    ///         &self,
+    ///         email: &AccountEmail<'_>,
+    ///         message: &mut EmailMessageBuilder,
+    ///     ) -> cot::Result<()> {
+    ///         match email {
+    ///             AccountEmail::PasswordReset { link, .. } => {
+    ///                 message
+    ///                     .subject("Forgot your password?")
+    ///                     .body(format!("Set a new one here: {link}"));
+    ///             }
+    ///             AccountEmail::EmailConfirmation { link, .. } => {
+    ///                 message
+    ///                     .subject("Welcome!")
+    ///                     .body(format!("Confirm your email address here: {link}"));
+    ///             }
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let account_views =
+    ///     AccountViews::new(Email::new("no-reply@example.com").unwrap()).templates(MyTemplates);
+    /// ```
+    #[must_use]
+    pub fn templates<T: AccountTemplates + 'static>(mut self, templates: T) -> Self {
+        self.templates = Arc::new(templates);
+        self
+    }
+
+    /// Sends the user an email with a link to confirm their email address.
+    ///
+    /// The [`DatabaseUserApp`](super::DatabaseUserApp) must be registered
+    /// with these account views, as the link points to its `confirm_email`
+    /// view.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user doesn't have an email address, if
+    /// [`CanonicalUrlConfig::host`](crate::config::CanonicalUrlConfig::host)
+    /// is not set, if the URL of the view could not be built, or if the email
+    /// could not be sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::db::account::AccountViews;
+    /// use cot::common_types::Email;
+    /// use cot::html::Html;
+    /// use cot::request::Request;
+    ///
+    /// async fn signed_up(
+    ///     account_views: &AccountViews,
+    ///     request: &Request,
+    ///     user: &DatabaseUser,
+    /// ) -> cot::Result<Html> {
+    ///     account_views.send_confirmation_email(request, user).await?;
+    ///     Ok(Html::new("Check your inbox to confirm your email address."))
+    /// }
+    /// ```
+    pub async fn send_confirmation_email(
+        &self,
+        request: &Request,
+        user: &DatabaseUser,
+    ) -> crate::Result<()> {
+        let Some(email) = user.email() else {
+            return Err(AccountError::NoEmailAddress(user.username().to_owned()).into());
+        };
+        ensure_canonical_host(request)?;
+
+        let token = make_token(
+            user,
+            TokenPurpose::EmailConfirmation,
+            &request.project_config().secret_key,
+            Utc::now(),
+        );
+        let link = reverse_canonical!(
+            request,
+            "cot_db_user:confirm_email",
+            user_id = user.id(),
+            token = token
+        )?;
+
+        self.send_email(
+            request,
+            email,
+            &AccountEmail::EmailConfirmation { user, link: &link },
+        )
+        .await
+    }
+
+    async fn send_email(
+        &self,
+        request: &Request,
+        to: &Email,
+        email: &AccountEmail<'_>,
+    ) -> crate::Result<()> {
+        let mut message = EmailMessage::builder();
+        message.from(self.from.clone()).to(vec![to.clone()]);
+        self.templates.render_email(email, &mut message)?;
+
+        request.email().send(message.build()?).await?;
+        Ok(())
+    }
+
+    pub(super) fn router(&self) -> Router {
+        let views = Arc::new(self.clone());
+
+        Router::with_urls([
+            Route::with_handler_and_name(
+                "/password-reset/",
+                PasswordResetHandler(Arc::clone(&views)),
+                "password_reset",
+            ),
+            Route::with_handler_and_name(
+                "/password-reset/{user_id}/{token}/",
+                PasswordResetConfirmHandler(Arc::clone(&views)),
+                "password_reset_confirm",
+            ),
+            Route::with_handler_and_name(
+                "/confirm-email/{user_id}/{token}/",
+                ConfirmEmailHandler(views),
+                "confirm_email",
+            ),
+        ])
+    }
+}
+
+/// A page rendered by the account views.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AccountPage<'a> {
+    /// The form asking for the email address to send a password reset link
+    /// to.
+    PasswordReset {
+        /// The context of the form.
+        form: &'a <PasswordResetForm as Form>::Context,
+    },
+    /// Shown after the password reset form has been submitted.
+    PasswordResetSent,
+    /// The form for setting a new password.
+    SetPassword {
+        /// The user whose password is being reset.
+        user: &'a DatabaseUser,
+        /// The context of the form.
+        form: &'a <SetPasswordForm as Form>::Context,
+    },
+    /// Shown after the new password has been set.
+    PasswordResetComplete,
+    /// Shown after the email address has been confirmed.
+    EmailConfirmed {
+        /// The user whose email address has been confirmed.
+        user: &'a DatabaseUser,
+    },
+    /// Shown when the link is invalid, for instance because it has expired or
+    /// has already been used.
+    InvalidLink,
+}
+
+/// An email sent by the account views.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AccountEmail<'a> {
+    /// The email with a link to reset the password.
+    PasswordReset {
+        /// The user the email is sent to.
+        user: &'a DatabaseUser,
+        /// The absolute URL of the password reset page.
+        link: &'a str,
+    },
+    /// The email with a link to confirm the email address.
+    EmailConfirmation {
+        /// The user the email is sent to.
+        user: &'a DatabaseUser,
+        /// The absolute URL of the email confirmation page.
+        link: &'a str,
+    },
+}
+
+/// Renders the pages and emails of the account views.
+///
+/// Both methods have default implementations that use the templates built
+/// into Cot, so implementors only need to override the ones they want to
+/// customize.
+pub trait AccountTemplates: Debug + Send + Sync {
+    /// Renders a page of the account views.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the page could not be rendered.
+    fn render_page(&self, page: &AccountPage<'_>) -> crate::Result<Html> {
+        let html = match page {
+            AccountPage::PasswordReset { form } => PasswordResetTemplate { form }.render()?,
+            AccountPage::PasswordResetSent => PasswordResetSentTemplate.render()?,
+            AccountPage::SetPassword { form, .. } => SetPasswordTemplate { form }.render()?,
+            AccountPage::PasswordResetComplete => PasswordResetCompleteTemplate.render()?,
+            AccountPage::EmailConfirmed { user } => EmailConfirmedTemplate { user }.render()?,
+            AccountPage::InvalidLink => InvalidLinkTemplate.render()?,
+        };
+
+        Ok(Html::new(html))
+    }
+
+    /// Renders an email sent by the account views by setting its subject and
+    /// body on the message builder.
+    ///
+    /// The sender and the recipient are already set on the builder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the email could not be rendered.
+    fn render_email(
+        &self,
+        email: &AccountEmail<'_>,
+        message: &mut EmailMessageBuilder,
+    ) -> crate::Result<()> {
+        match email {
+            AccountEmail::PasswordReset { user, link } => {
+                message
+                    .subject("Password reset")
+                    .body(PasswordResetEmailTemplate { user, link }.render()?);
+            }
+            AccountEmail::EmailConfirmation { user, link } => {
+                message
+                    .subject("Confirm your email address")
+                    .body(EmailConfirmationEmailTemplate { user, link }.render()?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The templates built into Cot.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DefaultAccountTemplates;
+
+impl AccountTemplates for DefaultAccountTemplates {}
+
+#[derive(Debug, Template)]
+#[template(path = "auth/password_reset.html")]
+struct PasswordResetTemplate<'a> {
+    form: &'a <PasswordResetForm as Form>::Context,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "auth/password_reset_sent.html")]
+struct PasswordResetSentTemplate;
+
+#[derive(Debug, Template)]
+#[template(path = "auth/set_password.html")]
+struct SetPasswordTemplate<'a> {
+    form: &'a <SetPasswordForm as Form>::Context,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "auth/password_reset_complete.html")]
+struct PasswordResetCompleteTemplate;
+
+#[derive(Debug, Template)]
+#[template(path = "auth/email_confirmed.html")]
+struct EmailConfirmedTemplate<'a> {
+    user: &'a DatabaseUser,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "auth/invalid_link.html")]
+struct InvalidLinkTemplate;
+
+#[derive(Debug, Template)]
+#[template(path = "auth/password_reset_email.txt")]
+struct PasswordResetEmailTemplate<'a> {
+    user: &'a DatabaseUser,
+    link: &'a str,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "auth/email_confirmation_email.txt")]
+struct EmailConfirmationEmailTemplate<'a> {
+    user: &'a DatabaseUser,
+    link: &'a str,
+}
+
+/// The form asking for the email address to send a password reset link to.
+#[derive(Debug, Form)]
+pub struct PasswordResetForm {
+    email: Email,
+}
+
+/// The form for setting a new password.
+#[derive(Debug, Form)]
+pub struct SetPasswordForm {
+    password: Password,
+    password_confirmation: Password,
+}
+
+struct PasswordResetHandler(Arc<AccountViews>);
+
+impl RequestHandler for PasswordResetHandler {
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        let views = &self.0;
+
+        let form_context = if request.method() == Method::GET {
+            PasswordResetForm::build_context(&mut request).await?
+        } else if request.method() == Method::POST {
+            match PasswordResetForm::from_request(&mut request).await? {
+                FormResult::Ok(form) => {
+                    ensure_canonical_host(&request)?;
+                    let users =
+                        DatabaseUser::find_by_email(request.context().database(), &form.email)
+                            .await?;
+                    for user in &users {
+                        let token = make_token(
+                            user,
+                            TokenPurpose::PasswordReset,
+                            &request.project_config().secret_key,
+                            Utc::now(),
+                        );
+                        let link = reverse_canonical!(
+                            request,
+                            "cot_db_user:password_reset_confirm",
+                            user_id = user.id(),
+                            token = token
+                        )?;
+
+                        views
+                            .send_email(
+                                &request,
+                                &form.email,
+                                &AccountEmail::PasswordReset { user, link: &link },
+                            )
+                            .await?;
+                    }
+
+                    return views
+                        .templates
+                        .render_page(&AccountPage::PasswordResetSent)?
+                        .into_response();
+                }
+                FormResult::ValidationError(context) => context,
+            }
+        } else {
+            return Err(MethodNotAllowed::new(request.method().clone()).into());
+        };
+
+        views
+            .templates
+            .render_page(&AccountPage::PasswordReset {
+                form: &form_context,
+            })?
+            .into_response()
+    }
+}
+
+struct PasswordResetConfirmHandler(Arc<AccountViews>);
+
+impl RequestHandler for PasswordResetConfirmHandler {
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        let views = &self.0;
+
+        let Some(mut user) = user_for_token(
+            &mut request,
+            TokenPurpose::PasswordReset,
+            views.password_reset_timeout,
+        )
+        .await?
+        else {
+            return views
+                .templates
+                .render_page(&AccountPage::InvalidLink)?
+                .into_response();
+        };
+
+        let form_context = if request.method() == Method::GET {
+            SetPasswordForm::build_context(&mut request).await?
+        } else if request.method() == Method::POST {
+            match SetPasswordForm::from_request(&mut request).await? {
+                FormResult::Ok(form) => {
+                    if form.password.as_str() == form.password_confirmation.as_str() {
+                        user.set_password_with(
+                            &form.password,
+                            request.project_config().password_hasher,
+                        );
+                        user.save(request.context().database()).await?;
+
+                        return views
+                            .templates
+                            .render_page(&AccountPage::PasswordResetComplete)?
+                            .into_response();
+                    }
+
+                    let mut context = SetPasswordForm::build_context(&mut request).await?;
+                    context.add_error(
+                        FormErrorTarget::Field("password_confirmation"),
+                        FormFieldValidationError::with_code(
+                            "password_mismatch",
+                            "The passwords do not match",
+                        ),
+                    );
+                    context
+                }
+                FormResult::ValidationError(context) => context,
+            }
+        } else {
+            return Err(MethodNotAllowed::new(request.method().clone()).into());
+        };
+
+        views
+            .templates
+            .render_page(&AccountPage::SetPassword {
+                user: &user,
+                form: &form_context,
+            })?
+            .into_response()
+    }
+}
+
+struct ConfirmEmailHandler(Arc<AccountViews>);
+
+impl RequestHandler for ConfirmEmailHandler {
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        let views = &self.0;
+
+        if request.method() != Method::GET {
+            return Err(MethodNotAllowed::new(request.method().clone()).into());
+        }
+
+        let Some(mut user) = user_for_token(
+            &mut request,
+            TokenPurpose::EmailConfirmation,
+            views.email_confirmation_timeout,
+        )
+        .await?
+        else {
+            return views
+                .templates
+                .render_page(&AccountPage::InvalidLink)?
+                .into_response();
+        };
+
+        user.confirm_email();
+        user.save(request.context().database()).await?;
+
+        views
+            .templates
+            .render_page(&AccountPage::EmailConfirmed { user: &user })?
+            .into_response()
+    }
+}
+
+/// Makes sure that the links in the emails will be absolute URLs, which is
+/// only the case if the canonical host is configured.
+fn ensure_canonical_host(request: &Request) -> crate::Result<()> {
+    if request.canonical_url_config().host.is_none() {
+        return Err(AccountError::NoCanonicalHost.into());
+    }
+    Ok(())
+}
+
+/// Returns the user the token in the request path was generated for, or
+/// [`None`] if the user doesn't exist or the token is invalid.
+async fn user_for_token(
+    request: &mut Request,
+    purpose: TokenPurpose,
+    max_age: Duration,
+) -> crate::Result<Option<DatabaseUser>> {
+    let Path((user_id, token)): Path<(i64, String)> = request.extract_from_head().await?;
+
+    let Some(user) = DatabaseUser::get_by_id(request.context().database(), user_id).await? else {
+        return Ok(None);
+    };
+
+    let config = request.project_config();
+    let keys = std::iter::once(&config.secret_key).chain(&config.fallback_secret_keys);
+    if check_token(&user, purpose, &token, keys, max_age, Utc::now()) {
+        Ok(Some(user))
+    } else {
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TokenPurpose {
+    PasswordReset,
+    EmailConfirmation,
+}
+
+impl TokenPurpose {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::PasswordReset => "cot.auth.db.password_reset",
+            Self::EmailConfirmation => "cot.auth.db.email_confirmation",
+        }
+    }
+}
+
+type TokenHmac = Hmac<Sha512>;
+
+/// Creates a token in the `{timestamp}-{signature}` format, where the
+/// timestamp is the number of seconds since the Unix epoch in hexadecimal.
+fn make_token(
+    user: &DatabaseUser,
+    purpose: TokenPurpose,
+    secret_key: &SecretKey,
+    now: DateTime<Utc>,
+) -> String {
+    let timestamp = now.timestamp();
+    let signature = token_hmac(user, purpose, secret_key, timestamp)
+        .finalize()
+        .into_bytes();
+
+    format!("{timestamp:x}-{}", hex::encode(signature))
+}
+
+fn check_token<'a>(
+    user: &DatabaseUser,
+    purpose: TokenPurpose,
+    token: &str,
+    mut secret_keys: impl Iterator<Item = &'a SecretKey>,
+    max_age: Duration,
+    now: DateTime<Utc>,
+) -> bool {
+    let Some((timestamp, signature)) = token.split_once('-') else {
+        return false;
+    };
+    let Ok(timestamp) = i64::from_str_radix(timestamp, 16) else {
+        return false;
+    };
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+
+    let Ok(age) = u64::try_from(now.timestamp().saturating_sub(timestamp)) else {
+        // the token was created in the future
+        return false;
+    };
+    if age > max_age.as_secs() {
+        return false;
+    }
+
+    secret_keys.any(|key| {
+        token_hmac(user, purpose, key, timestamp)
+            .verify_slice(&signature)
+            .is_ok()
+    })
+}
+
+/// Returns the HMAC of the user state that the token depends on; changing any
+/// of it invalidates the tokens generated before.
+fn token_hmac(
+    user: &DatabaseUser,
+    purpose: TokenPurpose,
+    secret_key: &SecretKey,
+    timestamp: i64,
+) -> TokenHmac {
+    let mut mac =
+        TokenHmac::new_from_slice(secret_key.as_bytes()).expect("HMAC can take key of any size");
+
+    let email = user.email().map(ToString::to_string).unwrap_or_default();
+    let email_confirmed_at = user
+        .email_confirmed_at()
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default();
+    for part in [
+        purpose.as_str(),
+        &user.id().to_string(),
+        user.password.as_str(),
+        &email,
+        &email_confirmed_at,
+        &timestamp.to_string(),
+    ] {
+        mac.update(part.as_bytes());
+        mac.update(b"\0");
+    }
+
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use cot::db::Auto;
+    use cot_core::request::AppName;
+
+    use super::*;
+    use crate::App;
+    use crate::auth::PasswordHasher;
+    use crate::auth::db::DatabaseUserApp;
+    use crate::config::{CanonicalUrlConfig, ProjectConfig};
+    use crate::db::{Database, LimitedString};
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    fn test_user() -> DatabaseUser {
+        let mut user = DatabaseUser::new(
+            Auto::fixed(1),
+            LimitedString::new("testuser").unwrap(),
+            &Password::new("password123"),
+            PasswordHasher::default(),
+        );
+        user.set_email(Some(Email::new("testuser@example.com").unwrap()));
+        user
+    }
+
+    fn check(user: &DatabaseUser, purpose: TokenPurpose, token: &str, keys: &[SecretKey]) -> bool {
+        check_token(
+            user,
+            purpose,
+            token,
+            keys.iter(),
+            DEFAULT_PASSWORD_RESET_TIMEOUT,
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn token_valid() {
+        let user = test_user();
+        let key = SecretKey::new(b"supersecretkey");
+
+        let token = make_token(&user, TokenPurpose::PasswordReset, &key, Utc::now());
+
+        assert!(check(&user, TokenPurpose::PasswordReset, &token, &[key]));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn token_wrong_purpose() {
+        let user = test_user();
+        let key = SecretKey::new(b"supersecretkey");
+
+        let token = make_token(&user, TokenPurpose::PasswordReset, &key, Utc::now());
+
+        assert!(!check(
+            &user,
+            TokenPurpose::EmailConfirmation,
+            &token,
+            &[key]
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn token_fallback_key() {
+        let user = test_user();
+        let old_key = SecretKey::new(b"oldsecretkey");
+        let new_key = SecretKey::new(b"newsecretkey");
+
+        let token = make_token(&user, TokenPurpose::PasswordReset, &old_key, Utc::now());
+
+        assert!(!check(
+            &user,
+            TokenPurpose::PasswordReset,
+            &token,
+            std::slice::from_ref(&new_key)
+        ));
+        assert!(check(
+            &user,
+            TokenPurpose::PasswordReset,
+            &token,
+            &[new_key, old_key]
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn token_expired() {
+        let user = test_user();
+        let key = SecretKey::new(b"supersecretkey");
+        let created_at = Utc::now() - TimeDelta::hours(25);
+
+        let token = make_token(&user, TokenPurpose::PasswordReset, &key, created_at);
+
+        assert!(!check(&user, TokenPurpose::PasswordReset, &token, &[key]));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn token_from_the_future() {
+        let user = test_user();
+        let key = SecretKey::new(b"supersecretkey");
+        let created_at = Utc::now() + TimeDelta::hours(1);
+
+        let token = make_token(&user, TokenPurpose::PasswordReset, &key, created_at);
+
+        assert!(!check(&user, TokenPurpose::PasswordReset, &token, &[key]));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn token_invalidated_by_password_change() {
+        let mut user = test_user();
+        let key = SecretKey::new(b"supersecretkey");
+
+        let token = make_token(&user, TokenPurpose::PasswordReset, &key, Utc::now());
+        user.set_password(&Password::new("newpassword"));
+
+        assert!(!check(&user, TokenPurpose::PasswordReset, &token, &[key]));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn token_invalidated_by_email_confirmation() {
+        let mut user = test_user();
+        let key = SecretKey::new(b"supersecretkey");
+
+        let token = make_token(&user, TokenPurpose::EmailConfirmation, &key, Utc::now());
+        user.confirm_email();
+
+        assert!(!check(
+            &user,
+            TokenPurpose::EmailConfirmation,
+            &token,
+            &[key]
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn token_malformed() {
+        let user = test_user();
+        let key = SecretKey::new(b"supersecretkey");
+
+        for token in ["", "-", "abc", "zz-00", "1-not-hex", "1-00"] {
+            assert!(!check(
+                &user,
+                TokenPurpose::PasswordReset,
+                token,
+                std::slice::from_ref(&key)
+            ));
+        }
+    }
+
+    const SECRET_KEY: &[u8] = b"supersecretkey";
+
+    fn test_router() -> Router {
+        let app = DatabaseUserApp::new().with_account_views(AccountViews::new(
+            Email::new("no-reply@example.com").unwrap(),
+        ));
+        let mut app_router = app.router();
+        app_router.set_app_name(AppName(app.name().to_owned()));
+
+        Router::with_urls([Route::with_router("/accounts", app_router)])
+    }
+
+    fn test_config() -> ProjectConfig {
+        ProjectConfig::builder()
+            .secret_key(SecretKey::new(SECRET_KEY))
+            .canonical_url(CanonicalUrlConfig::builder().host("example.com").build())
+            .build()
+    }
+
+    async fn send(request: &mut TestRequestBuilder, database: Database) -> String {
+        send_with_config(request, database, test_config()).await
+    }
+
+    async fn send_with_config(
+        request: &mut TestRequestBuilder,
+        database: Database,
+        config: ProjectConfig,
+    ) -> String {
+        let router = test_router();
+        let request = request
+            .router(router.clone())
+            .config(config)
+            .database(database)
+            .build();
+
+        let response = router.handle(request).await.unwrap();
+        let body = response.into_body().into_bytes().await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    async fn create_test_user(database: &Database) -> DatabaseUser {
+        let mut user = DatabaseUser::create_user(database, "testuser", "password123")
+            .await
+            .unwrap();
+        user.set_email(Some(Email::new("testuser@example.com").unwrap()));
+        user.save(database).await.unwrap();
+        user
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_reset_request() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        create_test_user(&test_db.database()).await;
+
+        let body = send(
+            &mut TestRequestBuilder::get("/accounts/password-reset/"),
+            test_db.database(),
+        )
+        .await;
+        assert!(body.contains("<form"));
+
+        for email in ["testuser@example.com", "unknown@example.com"] {
+            let body = send(
+                TestRequestBuilder::post("/accounts/password-reset/")
+                    .form_data(&[("email", email)]),
+                test_db.database(),
+            )
+            .await;
+            assert!(body.contains("we have sent you a link"));
+        }
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_reset_request_no_canonical_host() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        create_test_user(&test_db.database()).await;
+        let router = test_router();
+        let config = ProjectConfig::builder()
+            .secret_key(SecretKey::new(SECRET_KEY))
+            .build();
+        let request = TestRequestBuilder::post("/accounts/password-reset/")
+            .form_data(&[("email", "testuser@example.com")])
+            .router(router.clone())
+            .config(config)
+            .database(test_db.database())
+            .build();
+
+        let error = router.handle(request).await.unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("`canonical_url.host` must be set")
+        );
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn send_confirmation_email_no_canonical_host() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let user = create_test_user(&test_db.database()).await;
+        let request = TestRequestBuilder::get("/")
+            .router(test_router())
+            .database(test_db.database())
+            .build();
+
+        let error = AccountViews::new(Email::new("no-reply@example.com").unwrap())
+            .send_confirmation_email(&request, &user)
+            .await
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("`canonical_url.host` must be set")
+        );
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_reset_confirm() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let user = create_test_user(&test_db.database()).await;
+        let token = make_token(
+            &user,
+            TokenPurpose::PasswordReset,
+            &SecretKey::new(SECRET_KEY),
+            Utc::now(),
+        );
+        let url = format!("/accounts/password-reset/{}/{token}/", user.id());
+
+        let body = send(&mut TestRequestBuilder::get(&url), test_db.database()).await;
+        assert!(body.contains("Set a new password"));
+
+        let body = send(
+            TestRequestBuilder::post(&url).form_data(&[
+                ("password", "newpassword"),
+                ("password_confirmation", "different"),
+            ]),
+            test_db.database(),
+        )
+        .await;
+        assert!(body.contains("The passwords do not match"));
+
+        let body = send(
+            TestRequestBuilder::post(&url).form_data(&[
+                ("password", "newpassword"),
+                ("password_confirmation", "newpassword"),
+            ]),
+            test_db.database(),
+        )
+        .await;
+        assert!(body.contains("Your password has been set"));
+
+        let credentials = crate::auth::db::DatabaseUserCredentials::new(
+            "testuser".to_owned(),
+            Password::new("newpassword"),
+        );
+        let authenticated = DatabaseUser::authenticate(&test_db.database(), &credentials)
+            .await
+            .unwrap();
+        assert!(authenticated.is_some());
+
+        // the token can't be used again after the password has been changed
+        let body = send(&mut TestRequestBuilder::get(&url), test_db.database()).await;
+        assert!(body.contains("Invalid link"));
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_reset_confirm_uses_configured_hasher() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let user = create_test_user(&test_db.database()).await;
+        let token = make_token(
+            &user,
+            TokenPurpose::PasswordReset,
+            &SecretKey::new(SECRET_KEY),
+            Utc::now(),
+        );
+        let url = format!("/accounts/password-reset/{}/{token}/", user.id());
+        let mut config = ProjectConfig::from_toml(r#"password_hasher = "bcrypt""#).unwrap();
+        config.secret_key = SecretKey::new(SECRET_KEY);
+        config.canonical_url = CanonicalUrlConfig::builder().host("example.com").build();
+
+        let body = send_with_config(
+            TestRequestBuilder::post(&url).form_data(&[
+                ("password", "newpassword"),
+                ("password_confirmation", "newpassword"),
+            ]),
+            test_db.database(),
+            config,
+        )
+        .await;
+        assert!(body.contains("Your password has been set"));
+
+        let user = DatabaseUser::get_by_id(&test_db.database(), user.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.password.hasher(), PasswordHasher::Bcrypt);
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn confirm_email() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let user = create_test_user(&test_db.database()).await;
+        let token = make_token(
+            &user,
+            TokenPurpose::EmailConfirmation,
+            &SecretKey::new(SECRET_KEY),
+            Utc::now(),
+        );
+        let url = format!("/accounts/confirm-email/{}/{token}/", user.id());
+
+        let body = send(&mut TestRequestBuilder::get(&url), test_db.database()).await;
+        assert!(body.contains("Your email address has been confirmed"));
+
+        let user = DatabaseUser::get_by_id(&test_db.database(), user.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(user.is_email_confirmed());
+
+        let body = send(&mut TestRequestBuilder::get(&url), test_db.database()).await;
+        assert!(body.contains("Invalid link"));
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn invalid_token() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let user = create_test_user(&test_db.database()).await;
+
+        for url in [
+            format!("/accounts/password-reset/{}/1-00/", user.id()),
+            format!("/accounts/confirm-email/{}/1-00/", user.id()),
+            "/accounts/confirm-email/1000/1-00/".to_owned(),
+        ] {
+            let body = send(&mut TestRequestBuilder::get(&url), test_db.database()).await;
+            assert!(body.contains("Invalid link"));
+        }
+
+        test_db.cleanup().await.unwrap();
+    }
+}
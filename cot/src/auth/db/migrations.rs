@@ -3,5 +3,13 @@
 //! Generated by cot CLI 0.1.0 on 2025-02-13 10:29:03+00:00
 
 pub mod m_0001_initial;
+pub mod m_0002_auto_20261016_182007;
+pub mod m_0003_auto_20261016_203114;
+pub mod m_0004_auto_20261016_224800;
 /// The list of migrations for current app.
-pub const MIGRATIONS: &[&::cot::db::migrations::SyncDynMigration] = &[&m_0001_initial::Migration];
+pub const MIGRATIONS: &[&::cot::db::migrations::SyncDynMigration] = &[
+    &m_0001_initial::Migration,
+    &m_0002_auto_20261016_182007::Migration,
+    &m_0003_auto_20261016_203114::Migration,
+    &m_0004_auto_20261016_224800::Migration,
+];
@@ -0,0 +1,271 @@
+//! Generated by cot CLI 0.5.0 on 2026-10-16 20:31:14+00:00
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Migration;
+impl ::cot::db::migrations::Migration for Migration {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0003_auto_20261016_203114";
+    const DEPENDENCIES: &'static [::cot::db::migrations::MigrationDependency] = &[
+        ::cot::db::migrations::MigrationDependency::migration("cot", "m_0002_auto_20261016_182007"),
+        ::cot::db::migrations::MigrationDependency::model(
+            <crate::auth::db::DatabaseUser as ::cot::db::Model>::APP_NAME,
+            <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+        ),
+    ];
+    const OPERATIONS: &'static [::cot::db::migrations::Operation] = &[
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__permission"))
+            .fields(
+                &[
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("id"),
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .auto()
+                        .primary_key()
+                        .set_null(
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("codename"),
+                            <crate::db::LimitedString<
+                                { crate::auth::db::MAX_PERMISSION_LENGTH },
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .set_null(
+                            <crate::db::LimitedString<
+                                { crate::auth::db::MAX_PERMISSION_LENGTH },
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        )
+                        .unique(),
+                ],
+            )
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__group"))
+            .fields(
+                &[
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("id"),
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .auto()
+                        .primary_key()
+                        .set_null(
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("name"),
+                            <crate::db::LimitedString<
+                                { crate::auth::db::MAX_GROUP_NAME_LENGTH },
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .set_null(
+                            <crate::db::LimitedString<
+                                { crate::auth::db::MAX_GROUP_NAME_LENGTH },
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        )
+                        .unique(),
+                ],
+            )
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__permission_users"))
+            .fields(
+                &[
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("id"),
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .auto()
+                        .primary_key()
+                        .set_null(
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("source"),
+                            <cot::db::ForeignKey<
+                                crate::auth::db::Permission,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .foreign_key(
+                            <crate::auth::db::Permission as ::cot::db::Model>::TABLE_NAME,
+                            <crate::auth::db::Permission as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                            ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                            ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                        )
+                        .set_null(
+                            <cot::db::ForeignKey<
+                                crate::auth::db::Permission,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("target"),
+                            <cot::db::ForeignKey<
+                                crate::auth::db::DatabaseUser,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .foreign_key(
+                            <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+                            <crate::auth::db::DatabaseUser as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                            ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                            ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                        )
+                        .set_null(
+                            <cot::db::ForeignKey<
+                                crate::auth::db::DatabaseUser,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                ],
+            )
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__group_permissions"))
+            .fields(
+                &[
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("id"),
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .auto()
+                        .primary_key()
+                        .set_null(
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("source"),
+                            <cot::db::ForeignKey<
+                                crate::auth::db::Group,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .foreign_key(
+                            <crate::auth::db::Group as ::cot::db::Model>::TABLE_NAME,
+                            <crate::auth::db::Group as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                            ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                            ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                        )
+                        .set_null(
+                            <cot::db::ForeignKey<
+                                crate::auth::db::Group,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("target"),
+                            <cot::db::ForeignKey<
+                                crate::auth::db::Permission,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .foreign_key(
+                            <crate::auth::db::Permission as ::cot::db::Model>::TABLE_NAME,
+                            <crate::auth::db::Permission as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                            ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                            ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                        )
+                        .set_null(
+                            <cot::db::ForeignKey<
+                                crate::auth::db::Permission,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                ],
+            )
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__group_users"))
+            .fields(
+                &[
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("id"),
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .auto()
+                        .primary_key()
+                        .set_null(
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("source"),
+                            <cot::db::ForeignKey<
+                                crate::auth::db::Group,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .foreign_key(
+                            <crate::auth::db::Group as ::cot::db::Model>::TABLE_NAME,
+                            <crate::auth::db::Group as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                            ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                            ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                        )
+                        .set_null(
+                            <cot::db::ForeignKey<
+                                crate::auth::db::Group,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("target"),
+                            <cot::db::ForeignKey<
+                                crate::auth::db::DatabaseUser,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .foreign_key(
+                            <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+                            <crate::auth::db::DatabaseUser as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                            ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                            ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                        )
+                        .set_null(
+                            <cot::db::ForeignKey<
+                                crate::auth::db::DatabaseUser,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                ],
+            )
+            .build(),
+    ];
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _Permission {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    #[model(unique)]
+    codename: crate::db::LimitedString<{ crate::auth::db::MAX_PERMISSION_LENGTH }>,
+    users: crate::db::ManyToMany<crate::auth::db::DatabaseUser>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _Group {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    #[model(unique)]
+    name: crate::db::LimitedString<{ crate::auth::db::MAX_GROUP_NAME_LENGTH }>,
+    permissions: crate::db::ManyToMany<crate::auth::db::Permission>,
+    users: crate::db::ManyToMany<crate::auth::db::DatabaseUser>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _PermissionUsers {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    source: cot::db::ForeignKey<crate::auth::db::Permission>,
+    target: cot::db::ForeignKey<crate::auth::db::DatabaseUser>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _GroupPermissions {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    source: cot::db::ForeignKey<crate::auth::db::Group>,
+    target: cot::db::ForeignKey<crate::auth::db::Permission>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _GroupUsers {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    source: cot::db::ForeignKey<crate::auth::db::Group>,
+    target: cot::db::ForeignKey<crate::auth::db::DatabaseUser>,
+}
@@ -0,0 +1,114 @@
+//! Generated by cot CLI 0.5.0 on 2026-10-16 22:48:00+00:00
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Migration;
+impl ::cot::db::migrations::Migration for Migration {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0004_auto_20261016_224800";
+    const DEPENDENCIES: &'static [::cot::db::migrations::MigrationDependency] = &[
+        ::cot::db::migrations::MigrationDependency::migration("cot", "m_0003_auto_20261016_203114"),
+        ::cot::db::migrations::MigrationDependency::model(
+            <crate::auth::db::DatabaseUser as ::cot::db::Model>::APP_NAME,
+            <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+        ),
+    ];
+    const OPERATIONS: &'static [::cot::db::migrations::Operation] = &[
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__api_token"))
+            .fields(
+                &[
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("id"),
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .auto()
+                        .primary_key()
+                        .set_null(
+                            <cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("user"),
+                            <crate::db::ForeignKey<
+                                crate::auth::db::DatabaseUser,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .foreign_key(
+                            <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+                            <crate::auth::db::DatabaseUser as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                            ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                            ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                        )
+                        .set_null(
+                            <crate::db::ForeignKey<
+                                crate::auth::db::DatabaseUser,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("name"),
+                            <crate::db::LimitedString<
+                                { crate::auth::db::token::MAX_TOKEN_NAME_LENGTH },
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .set_null(
+                            <crate::db::LimitedString<
+                                { crate::auth::db::token::MAX_TOKEN_NAME_LENGTH },
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("token_hash"),
+                            <crate::db::LimitedString<
+                                { crate::auth::db::token::TOKEN_HASH_LENGTH },
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .set_null(
+                            <crate::db::LimitedString<
+                                { crate::auth::db::token::TOKEN_HASH_LENGTH },
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        )
+                        .unique(),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("scopes"),
+                            <String as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .set_null(<String as ::cot::db::DatabaseField>::NULLABLE),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("created_at"),
+                            <chrono::DateTime<
+                                chrono::FixedOffset,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .set_null(
+                            <chrono::DateTime<
+                                chrono::FixedOffset,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                    ::cot::db::migrations::Field::new(
+                            ::cot::db::Identifier::new("expires_at"),
+                            <Option<
+                                chrono::DateTime<chrono::FixedOffset>,
+                            > as ::cot::db::DatabaseField>::TYPE,
+                        )
+                        .set_null(
+                            <Option<
+                                chrono::DateTime<chrono::FixedOffset>,
+                            > as ::cot::db::DatabaseField>::NULLABLE,
+                        ),
+                ],
+            )
+            .build(),
+    ];
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _ApiToken {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    user: crate::db::ForeignKey<crate::auth::db::DatabaseUser>,
+    name: crate::db::LimitedString<{ crate::auth::db::token::MAX_TOKEN_NAME_LENGTH }>,
+    #[model(unique)]
+    token_hash: crate::db::LimitedString<{ crate::auth::db::token::TOKEN_HASH_LENGTH }>,
+    scopes: String,
+    created_at: chrono::DateTime<chrono::FixedOffset>,
+    expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
@@ -0,0 +1,610 @@
+//! API tokens for authenticating [`DatabaseUser`]s without sessions.
+//!
+//! An [`ApiToken`] belongs to a user and can be used by a client, such as a
+//! script or a mobile app, to authenticate by sending it in the
+//! `Authorization: Bearer <token>` header. The tokens are accepted by the
+//! [`ApiTokenBackend`], which can be enabled with
+//! [`AuthBackendConfig::ApiToken`](crate::config::AuthBackendConfig::ApiToken),
+//! and are most conveniently checked with the
+//! [`BearerAuth`](crate::auth::BearerAuth) extractor.
+//!
+//! Only a SHA-256 hash of each token is stored in the database; the token
+//! itself is returned once, when it's created, and can't be retrieved later.
+//!
+//! Each token has a set of scopes, which are permission codenames (see
+//! [`Permission`](super::Permission)) restricting what the token can be used
+//! for. A user authenticated with a token only has the permissions that are
+//! both granted to the user and listed in the scopes of the token, so a token
+//! can never be used to do more than its owner could.
+//!
+//! Note that the tokens of a user have to be revoked before the user can be
+//! deleted.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::auth::db::DatabaseUser;
+//! use cot::auth::db::token::ApiToken;
+//! use cot::common_types::Password;
+//! use cot::db::Database;
+//! use cot::json::Json;
+//!
+//! async fn create_token(db: Database) -> cot::Result<Json<String>> {
+//!     let user =
+//!         DatabaseUser::create_user(&db, "testuser".to_string(), &Password::new("password123"))
+//!             .await?;
+//!     let (_token, secret) =
+//!         ApiToken::create(&db, &user, "deploy script", ["blog.add_post"], None).await?;
+//!
+//!     // the secret can't be retrieved later, so it has to be shown to the user now
+//!     Ok(Json(secret))
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> cot::Result<()> {
+//! #     use cot::test::TestDatabase;
+//! #     let mut test_database = TestDatabase::new_sqlite().await?;
+//! #     test_database.with_auth().run_migrations().await;
+//! #     create_token(test_database.database()).await?;
+//! #     test_database.cleanup().await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Utc};
+// Importing `Auto` from `cot` instead of `crate` so that the migration generator
+// can figure out it's an autogenerated field
+use cot::db::Auto;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::auth::db::{DatabaseUser, DatabaseUserBackend, DatabaseUserWithPermissions};
+use crate::auth::{
+    AuthBackend, AuthError, BearerToken, PasswordHasher, Result, SessionAuthHash, User, UserId,
+};
+use crate::config::SecretKey;
+use crate::db::{Database, DatabaseBackend, ForeignKey, LimitedString, Model, model, query};
+
+pub(crate) const MAX_TOKEN_NAME_LENGTH: u32 = 100;
+/// The length of a hex-encoded SHA-256 hash.
+pub(crate) const TOKEN_HASH_LENGTH: u32 = 64;
+/// The number of random bytes in a newly generated token.
+const TOKEN_BYTES: usize = 32;
+
+/// An API token, allowing the client to authenticate as a [`DatabaseUser`]
+/// by sending it in the `Authorization: Bearer` header.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone)]
+#[model]
+pub struct ApiToken {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    user: ForeignKey<DatabaseUser>,
+    name: LimitedString<MAX_TOKEN_NAME_LENGTH>,
+    #[model(unique)]
+    token_hash: LimitedString<TOKEN_HASH_LENGTH>,
+    scopes: String,
+    created_at: DateTime<FixedOffset>,
+    expires_at: Option<DateTime<FixedOffset>>,
+}
+
+/// An error that occurs when creating an API token.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum CreateApiTokenError {
+    /// The token name is too long.
+    #[error("API token name is too long (max {MAX_TOKEN_NAME_LENGTH} characters, got {0})")]
+    NameTooLong(usize),
+    /// A scope is empty or contains whitespace.
+    #[error("API token scope `{0}` is invalid: scopes can't be empty or contain whitespace")]
+    InvalidScope(String),
+}
+
+impl ApiToken {
+    /// Creates a new API token for the given user and saves it to the
+    /// database.
+    ///
+    /// Returns the token along with its secret value, which is what the
+    /// client needs to send in the `Authorization: Bearer` header. Since only
+    /// the hash of the secret is stored, it can't be retrieved later.
+    ///
+    /// The token can't be used after `expires_at`, if given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is too long, if any of the scopes is
+    /// invalid, or if the token could not be saved.
+    ///
+    /// # Example
+    ///
+    /// See the [module documentation](self).
+    pub async fn create<DB, N, I, S>(
+        db: &DB,
+        user: &DatabaseUser,
+        name: N,
+        scopes: I,
+        expires_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(Self, String)>
+    where
+        DB: DatabaseBackend,
+        N: Into<String>,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let name = name.into();
+        let name_length = name.len();
+        let name = LimitedString::new(name)
+            .map_err(|_| AuthError::backend_error(CreateApiTokenError::NameTooLong(name_length)))?;
+
+        let mut scope_list = Vec::new();
+        for scope in scopes {
+            let scope = scope.into();
+            if scope.is_empty() || scope.contains(char::is_whitespace) {
+                return Err(AuthError::backend_error(CreateApiTokenError::InvalidScope(
+                    scope,
+                )));
+            }
+            if !scope_list.contains(&scope) {
+                scope_list.push(scope);
+            }
+        }
+
+        let secret = generate_secret();
+        let mut token = Self {
+            id: Auto::auto(),
+            user: ForeignKey::from(user),
+            name,
+            token_hash: hash_secret(&secret),
+            scopes: scope_list.join(" "),
+            created_at: Utc::now().fixed_offset(),
+            expires_at,
+        };
+        token.insert(db).await.map_err(AuthError::backend_error)?;
+
+        Ok((token, secret))
+    }
+
+    /// Retrieves the token with the given secret value. It returns [`None`] if
+    /// there is no such token.
+    ///
+    /// Note that this also returns expired tokens; use
+    /// [`is_expired`](Self::is_expired) to check it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    pub async fn get_by_secret<DB: DatabaseBackend>(db: &DB, secret: &str) -> Result<Option<Self>> {
+        let token_hash = hash_secret(secret);
+        query!(ApiToken, $token_hash == token_hash)
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Retrieves all the tokens of the given user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    pub async fn for_user<DB: DatabaseBackend>(db: &DB, user: &DatabaseUser) -> Result<Vec<Self>> {
+        let user = ForeignKey::<DatabaseUser>::from(user);
+        query!(ApiToken, $user == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Revokes the token by removing it from the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    pub async fn revoke<DB: DatabaseBackend>(self, db: &DB) -> Result<()> {
+        let id = self.id();
+        query!(ApiToken, $id == id)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+
+    /// Returns the ID of the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token has not been saved to the database.
+    #[must_use]
+    pub fn id(&self) -> i64 {
+        match self.id {
+            Auto::Fixed(id) => id,
+            Auto::Auto => unreachable!("ApiToken constructed with an unknown ID"),
+        }
+    }
+
+    /// Returns the ID of the user the token belongs to.
+    #[must_use]
+    pub fn user_id(&self) -> i64 {
+        match self.user.primary_key() {
+            Auto::Fixed(id) => *id,
+            Auto::Auto => unreachable!("ApiToken created for an unsaved user"),
+        }
+    }
+
+    /// Returns the name of the token.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the scopes of the token.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scopes.split_whitespace()
+    }
+
+    /// Returns whether the token has the given scope.
+    #[must_use]
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().any(|token_scope| token_scope == scope)
+    }
+
+    /// Returns the time the token was created at.
+    #[must_use]
+    pub fn created_at(&self) -> DateTime<FixedOffset> {
+        self.created_at
+    }
+
+    /// Returns the time the token expires at, if any.
+    #[must_use]
+    pub fn expires_at(&self) -> Option<DateTime<FixedOffset>> {
+        self.expires_at
+    }
+
+    /// Returns whether the token has expired.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+}
+
+impl Display for ApiToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0_u8; TOKEN_BYTES];
+    rand::rng().fill(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn hash_secret(secret: &str) -> LimitedString<TOKEN_HASH_LENGTH> {
+    let hash = hex::encode(Sha256::digest(secret.as_bytes()));
+    LimitedString::new(hash).expect("hex-encoded SHA-256 hash should always be 64 characters")
+}
+
+/// The authentication backend for users authenticating with an [`ApiToken`].
+///
+/// This backend supports authenticating users with the [`BearerToken`]
+/// credentials. Other credential types, such as
+/// [`DatabaseUserCredentials`](super::DatabaseUserCredentials), are passed on
+/// to the [`DatabaseUserBackend`], so that the users can still log in with
+/// their passwords, for instance to the admin panel.
+///
+/// A token is only accepted if it hasn't expired. The returned user only has
+/// the permissions listed in the scopes of the token.
+#[derive(Debug, Clone)]
+pub struct ApiTokenBackend {
+    database: Database,
+    user_backend: DatabaseUserBackend,
+}
+
+impl ApiTokenBackend {
+    /// Create a new instance of the API token authentication backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::Project;
+    /// use cot::auth::AuthBackend;
+    /// use cot::auth::db::token::ApiTokenBackend;
+    /// use cot::project::AuthBackendContext;
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn auth_backend(&self, context: &AuthBackendContext) -> Arc<dyn AuthBackend> {
+    ///         Arc::new(ApiTokenBackend::new(context.database().clone()))
+    ///         // note that it's usually better to just set the auth backend in the config
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn new(database: Database) -> Self {
+        Self {
+            user_backend: DatabaseUserBackend::new(database.clone()),
+            database,
+        }
+    }
+
+    /// Sets the preferred password hashing algorithm used when the users log
+    /// in with their passwords.
+    ///
+    /// See [`DatabaseUserBackend::password_hasher`].
+    #[must_use]
+    pub fn password_hasher(mut self, password_hasher: PasswordHasher) -> Self {
+        self.user_backend = self.user_backend.password_hasher(password_hasher);
+        self
+    }
+
+    async fn authenticate_token(&self, token: &BearerToken) -> Result<Option<ApiTokenUser>> {
+        let Some(mut api_token) = ApiToken::get_by_secret(&self.database, token.as_str()).await?
+        else {
+            return Ok(None);
+        };
+        if api_token.is_expired() {
+            return Ok(None);
+        }
+
+        let user = api_token
+            .user
+            .get(&self.database)
+            .await
+            .map_err(AuthError::backend_error)?
+            .clone();
+        let user = DatabaseUserWithPermissions::load(&self.database, user).await?;
+
+        Ok(Some(ApiTokenUser {
+            user,
+            scopes: api_token.scopes().map(ToOwned::to_owned).collect(),
+        }))
+    }
+}
+
+#[async_trait]
+impl AuthBackend for ApiTokenBackend {
+    async fn authenticate(
+        &self,
+        credentials: &(dyn Any + Send + Sync),
+    ) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        if let Some(token) = credentials.downcast_ref::<BearerToken>() {
+            let Some(user) = self.authenticate_token(token).await? else {
+                return Ok(None);
+            };
+
+            Ok(Some(Box::new(user)))
+        } else {
+            self.user_backend.authenticate(credentials).await
+        }
+    }
+
+    async fn get_by_id(&self, id: UserId) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        self.user_backend.get_by_id(id).await
+    }
+}
+
+/// A [`DatabaseUser`] authenticated with an [`ApiToken`]; its permissions are
+/// limited to the scopes of the token.
+#[derive(Debug, Clone)]
+struct ApiTokenUser {
+    user: DatabaseUserWithPermissions,
+    scopes: HashSet<String>,
+}
+
+impl User for ApiTokenUser {
+    fn id(&self) -> Option<UserId> {
+        self.user.id()
+    }
+
+    fn username(&self) -> Option<Cow<'_, str>> {
+        self.user.username()
+    }
+
+    fn is_active(&self) -> bool {
+        self.user.is_active()
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.user.is_authenticated()
+    }
+
+    fn last_login(&self) -> Option<DateTime<FixedOffset>> {
+        self.user.last_login()
+    }
+
+    fn joined(&self) -> Option<DateTime<FixedOffset>> {
+        self.user.joined()
+    }
+
+    fn session_auth_hash(&self, secret_key: &SecretKey) -> Option<SessionAuthHash> {
+        self.user.session_auth_hash(secret_key)
+    }
+
+    fn has_permission(&self, permission: &str) -> bool {
+        self.scopes.contains(permission) && self.user.has_permission(permission)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::db::Permission;
+    use crate::common_types::Password;
+    use crate::test::TestDatabase;
+
+    #[test]
+    fn secret_hash() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), TOKEN_BYTES * 2);
+        assert_ne!(secret, generate_secret());
+
+        assert_eq!(hash_secret(&secret), hash_secret(&secret));
+        assert_ne!(hash_secret(&secret).as_str(), secret);
+    }
+
+    async fn create_test_user(db: &Database) -> DatabaseUser {
+        DatabaseUser::create_user(db, "testuser", &Password::new("password123"))
+            .await
+            .unwrap()
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn create_and_get() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let db = test_db.database();
+        let user = create_test_user(&db).await;
+
+        let (token, secret) = ApiToken::create(&db, &user, "ci", ["a.b", "c.d", "a.b"], None)
+            .await
+            .unwrap();
+        assert_eq!(token.name(), "ci");
+        assert_eq!(token.user_id(), user.id());
+        assert_eq!(token.scopes().collect::<Vec<_>>(), ["a.b", "c.d"]);
+        assert!(token.has_scope("c.d"));
+        assert!(!token.has_scope("e.f"));
+        assert!(!token.is_expired());
+
+        let found = ApiToken::get_by_secret(&db, &secret)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id(), token.id());
+        assert!(
+            ApiToken::get_by_secret(&db, "wrong")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(ApiToken::for_user(&db, &user).await.unwrap().len(), 1);
+
+        found.revoke(&db).await.unwrap();
+        assert!(
+            ApiToken::get_by_secret(&db, &secret)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn create_invalid() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let db = test_db.database();
+        let user = create_test_user(&db).await;
+
+        let no_scopes: [&str; 0] = [];
+        assert!(
+            ApiToken::create(&db, &user, "a".repeat(101), no_scopes, None)
+                .await
+                .is_err()
+        );
+        assert!(
+            ApiToken::create(&db, &user, "ci", ["a b"], None)
+                .await
+                .is_err()
+        );
+        assert!(
+            ApiToken::create(&db, &user, "ci", [""], None)
+                .await
+                .is_err()
+        );
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn backend_authenticate() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db.with_auth().run_migrations().await;
+        let db = test_db.database();
+        let user = create_test_user(&db).await;
+        for codename in ["blog.add_post", "blog.delete_post"] {
+            let permission = Permission::get_or_create(&db, codename).await.unwrap();
+            permission.users().add(&db, &user).await.unwrap();
+        }
+
+        let (_, secret) = ApiToken::create(
+            &db,
+            &user,
+            "ci",
+            ["blog.add_post", "blog.change_post"],
+            None,
+        )
+        .await
+        .unwrap();
+        let expired = Utc::now().fixed_offset() - chrono::Duration::seconds(1);
+        let (_, expired_secret) =
+            ApiToken::create(&db, &user, "old", ["blog.add_post"], Some(expired))
+                .await
+                .unwrap();
+
+        let backend = ApiTokenBackend::new(db.clone());
+        let token_user = backend
+            .authenticate(&BearerToken::new(secret))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(token_user.id(), Some(UserId::Int(user.id())));
+        // granted to the user and in the scopes of the token
+        assert!(token_user.has_permission("blog.add_post"));
+        // granted to the user, but not in the scopes of the token
+        assert!(!token_user.has_permission("blog.delete_post"));
+        // in the scopes of the token, but not granted to the user
+        assert!(!token_user.has_permission("blog.change_post"));
+
+        assert!(
+            backend
+                .authenticate(&BearerToken::new(expired_secret))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            backend
+                .authenticate(&BearerToken::new("invalid"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // passwords are still accepted
+        let password_user = backend
+            .authenticate(&crate::auth::db::DatabaseUserCredentials::new(
+                "testuser".to_owned(),
+                Password::new("password123"),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(password_user.has_permission("blog.delete_post"));
+
+        test_db.cleanup().await.unwrap();
+    }
+}
@@ -1,21 +1,71 @@
 //! A command line interface for Cot-based applications.
 
+mod outcome;
+mod verify;
+
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use async_trait::async_trait;
 pub use clap;
-use clap::{Arg, ArgMatches, Command, value_parser};
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
 use derive_more::Debug;
 
+#[cfg(feature = "db")]
+use crate::db::DatabaseError;
+#[cfg(feature = "db")]
+use crate::db::migrations::{MigrationEngineError, MigrationProgress};
+#[cfg(feature = "gdpr")]
+use crate::gdpr::GdprError;
 use crate::{Bootstrapper, Error, Result};
 
+pub use outcome::{OutputFormat, TaskOutcome};
+
 const CONFIG_PARAM: &str = "config";
+const OUTPUT_FORMAT_PARAM: &str = "output_format";
 const COLLECT_STATIC_SUBCOMMAND: &str = "collect-static";
 const CHECK_SUBCOMMAND: &str = "check";
+const VERIFY_SUBCOMMAND: &str = "verify";
+const SESSIONS_SUBCOMMAND: &str = "sessions";
+const SESSIONS_LIST_SUBCOMMAND: &str = "list";
+const SESSIONS_PURGE_EXPIRED_SUBCOMMAND: &str = "purge-expired";
+const SESSIONS_DELETE_SUBCOMMAND: &str = "delete";
+#[cfg(feature = "db")]
+const MIGRATION_SUBCOMMAND: &str = "migration";
+#[cfg(feature = "db")]
+const MIGRATION_APPLY_SUBCOMMAND: &str = "apply";
+#[cfg(feature = "db")]
+const MIGRATION_STATUS_SUBCOMMAND: &str = "status";
+#[cfg(feature = "db")]
+const MIGRATION_REVERT_SUBCOMMAND: &str = "revert";
+const CONFIG_SUBCOMMAND: &str = "config";
+const CONFIG_SHOW_SUBCOMMAND: &str = "show";
+#[cfg(feature = "tasks")]
+const WORKER_SUBCOMMAND: &str = "worker";
+#[cfg(feature = "gdpr")]
+const GDPR_SUBCOMMAND: &str = "gdpr";
+#[cfg(feature = "gdpr")]
+const GDPR_EXPORT_USER_SUBCOMMAND: &str = "export-user";
+#[cfg(feature = "gdpr")]
+const GDPR_ANONYMIZE_USER_SUBCOMMAND: &str = "anonymize-user";
 const LISTEN_PARAM: &str = "listen";
 const COLLECT_STATIC_DIR_PARAM: &str = "dir";
+#[cfg(feature = "storage")]
+const COLLECT_STATIC_STORAGE_PARAM: &str = "storage";
+#[cfg(feature = "storage")]
+const COLLECT_STATIC_DELETE_ORPHANS_PARAM: &str = "delete-orphans";
+const SESSION_KEY_PARAM: &str = "key";
+const CONFIG_NAME_PARAM: &str = "name";
+const TEMPLATES_DIR_PARAM: &str = "templates";
+#[cfg(feature = "db")]
+const MIGRATION_TO_PARAM: &str = "to";
+#[cfg(feature = "db")]
+const MIGRATION_ALLOW_DESTRUCTIVE_PARAM: &str = "allow-destructive";
+#[cfg(feature = "gdpr")]
+const USER_ID_PARAM: &str = "user_id";
+#[cfg(feature = "gdpr")]
+const FILE_PARAM: &str = "file";
 
 /// A central point for configuring the default Command Line Interface (CLI) for
 /// Cot-powered projects.
@@ -32,7 +82,7 @@ const COLLECT_STATIC_DIR_PARAM: &str = "dir";
 /// ```
 /// use async_trait::async_trait;
 /// use clap::{ArgMatches, Command};
-/// use cot::cli::{Cli, CliTask};
+/// use cot::cli::{Cli, CliTask, TaskOutcome};
 /// use cot::project::WithConfig;
 /// use cot::{Bootstrapper, Project};
 ///
@@ -48,10 +98,8 @@ const COLLECT_STATIC_DIR_PARAM: &str = "dir";
 ///         &mut self,
 ///         _matches: &ArgMatches,
 ///         _bootstrapper: Bootstrapper<WithConfig>,
-///     ) -> cot::Result<()> {
-///         println!("Frobnicating...");
-///
-///         Ok(())
+///     ) -> cot::Result<TaskOutcome> {
+///         Ok(TaskOutcome::new().with_message("Frobnicated"))
 ///     }
 /// }
 ///
@@ -83,13 +131,31 @@ impl Cli {
                 .default_value("dev")
                 .help("Sets a custom config file"),
         );
+        let command = command.arg(
+            Arg::new(OUTPUT_FORMAT_PARAM)
+                .long("output")
+                .value_name("FORMAT")
+                .value_parser(OutputFormat::possible_values())
+                .default_value(OutputFormat::TEXT)
+                .global(true)
+                .help("Sets the format the result of the command is printed in"),
+        );
 
         let mut tasks: HashMap<Option<String>, Box<dyn CliTask + Send + 'static>> = HashMap::new();
         tasks.insert(None, Box::new(default_task));
 
         let mut cli = Self { command, tasks };
         cli.add_task(Check);
+        cli.add_task(Verify);
         cli.add_task(CollectStatic);
+        cli.add_task(Sessions);
+        #[cfg(feature = "db")]
+        cli.add_task(Migration);
+        cli.add_task(Config);
+        #[cfg(feature = "tasks")]
+        cli.add_task(Worker);
+        #[cfg(feature = "gdpr")]
+        cli.add_task(Gdpr);
 
         cli
     }
@@ -128,7 +194,7 @@ impl Cli {
     /// ```
     /// use async_trait::async_trait;
     /// use clap::{ArgMatches, Command};
-    /// use cot::cli::{Cli, CliTask};
+    /// use cot::cli::{Cli, CliTask, TaskOutcome};
     /// use cot::project::WithConfig;
     /// use cot::{Bootstrapper, Project};
     ///
@@ -144,10 +210,8 @@ impl Cli {
     ///         &mut self,
     ///         _matches: &ArgMatches,
     ///         _bootstrapper: Bootstrapper<WithConfig>,
-    ///     ) -> cot::Result<()> {
-    ///         println!("Frobnicating...");
-    ///
-    ///         Ok(())
+    ///     ) -> cot::Result<TaskOutcome> {
+    ///         Ok(TaskOutcome::new().with_message("Frobnicated"))
     ///     }
     /// }
     ///
@@ -184,6 +248,7 @@ impl Cli {
     #[expect(clippy::future_not_send)] // Send not needed; CLI is run async in a single thread
     pub(crate) async fn execute(mut self, bootstrapper: Bootstrapper<WithConfig>) -> Result<()> {
         let matches = self.command.get_matches();
+        let output_format = CommonOptions::output_format_from(&matches);
 
         let subcommand_name = matches.subcommand_name();
         let task = self.tasks.get_mut(&subcommand_name.map(ToOwned::to_owned));
@@ -193,9 +258,26 @@ impl Cli {
             None => &matches,
         };
 
-        task.expect("subcommand should exist if get_matches() didn't fail")
+        let result = task
+            .expect("subcommand should exist if get_matches() didn't fail")
             .execute(matches, bootstrapper)
-            .await
+            .await;
+
+        match result {
+            Ok(outcome) => {
+                outcome.print(output_format);
+                if !outcome.is_success() {
+                    std::process::exit(i32::from(outcome.exit_code()));
+                }
+                Ok(())
+            }
+            Err(error) => {
+                if output_format != OutputFormat::Text {
+                    TaskOutcome::from_error(&error).print(output_format);
+                }
+                Err(error)
+            }
+        }
     }
 }
 
@@ -233,11 +315,17 @@ pub trait CliTask {
     fn subcommand(&self) -> Command;
 
     /// Executes the task with the given matches and project.
+    ///
+    /// The returned [`TaskOutcome`] is printed in the format selected with
+    /// the global `--output` flag, and its exit code becomes the exit code of
+    /// the process. If an error is returned instead, the process fails; with
+    /// the JSON output, the error is also printed as an outcome with the exit
+    /// code `1`.
     async fn execute(
         &mut self,
         matches: &ArgMatches,
         bootstrapper: Bootstrapper<WithConfig>,
-    ) -> Result<()>;
+    ) -> Result<TaskOutcome>;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -254,9 +342,16 @@ impl CommonOptions {
     #[must_use]
     pub(crate) fn config(&self) -> &str {
         self.matches
-            .get_one::<String>("config")
+            .get_one::<String>(CONFIG_PARAM)
             .expect("default provided")
     }
+
+    fn output_format_from(matches: &ArgMatches) -> OutputFormat {
+        matches
+            .get_one::<String>(OUTPUT_FORMAT_PARAM)
+            .and_then(|name| OutputFormat::from_name(name))
+            .expect("default provided and only possible values accepted")
+    }
 }
 
 struct RunServer;
@@ -266,7 +361,7 @@ impl CliTask for RunServer {
     fn subcommand(&self) -> Command {
         Command::default().arg(
             Arg::new(LISTEN_PARAM)
-                .help("Optional port to listen on, or address:port")
+                .help("Optional port to listen on, address:port, or unix:path for a Unix socket")
                 .short('l')
                 .long("listen")
                 .default_value("127.0.0.1:8000")
@@ -279,7 +374,7 @@ impl CliTask for RunServer {
         &mut self,
         matches: &ArgMatches,
         bootstrapper: Bootstrapper<WithConfig>,
-    ) -> Result<()> {
+    ) -> Result<TaskOutcome> {
         let addr_port = matches
             .get_one::<String>(LISTEN_PARAM)
             .expect("default provided");
@@ -299,7 +394,7 @@ impl CliTask for RunServer {
             eprintln!("{user_friendly_error}");
         }
 
-        result
+        result.map(|()| TaskOutcome::new())
     }
 }
 
@@ -337,35 +432,84 @@ struct CollectStatic;
 #[async_trait(?Send)]
 impl CliTask for CollectStatic {
     fn subcommand(&self) -> Command {
-        Command::new(COLLECT_STATIC_SUBCOMMAND)
+        let command = Command::new(COLLECT_STATIC_SUBCOMMAND)
             .about("Collects all static files into a static directory")
             .arg(
                 Arg::new(COLLECT_STATIC_DIR_PARAM)
                     .help("The directory to collect the static files into")
                     .value_parser(value_parser!(PathBuf))
                     .required(true),
+            );
+        #[cfg(feature = "storage")]
+        let command = command
+            .about("Collects all static files into a static directory or a file storage")
+            .mut_arg(COLLECT_STATIC_DIR_PARAM, |arg| {
+                arg.required(false)
+                    .required_unless_present(COLLECT_STATIC_STORAGE_PARAM)
+            })
+            .arg(
+                Arg::new(COLLECT_STATIC_STORAGE_PARAM)
+                    .long(COLLECT_STATIC_STORAGE_PARAM)
+                    .help(
+                        "Upload the static files to the storage configured in \
+                        `static_files.storage` instead of a directory",
+                    )
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with(COLLECT_STATIC_DIR_PARAM),
             )
+            .arg(
+                Arg::new(COLLECT_STATIC_DELETE_ORPHANS_PARAM)
+                    .long(COLLECT_STATIC_DELETE_ORPHANS_PARAM)
+                    .help("Delete the files in the storage that are not static files")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with(COLLECT_STATIC_DIR_PARAM),
+            );
+        command
     }
 
     async fn execute(
         &mut self,
         matches: &ArgMatches,
         bootstrapper: Bootstrapper<WithConfig>,
-    ) -> Result<()> {
-        let dir = matches
-            .get_one::<PathBuf>(COLLECT_STATIC_DIR_PARAM)
-            .expect("required argument");
-        println!("Collecting static files into {}", dir.display());
-
+    ) -> Result<TaskOutcome> {
         let bootstrapper = bootstrapper
             .with_apps()
             .with_database()
             .await?
             .with_cache()
             .await?;
-        StaticFiles::from(bootstrapper.context()).collect_into(dir)?;
+        let static_files = StaticFiles::from(bootstrapper.context());
+
+        #[cfg(feature = "storage")]
+        if matches.get_flag(COLLECT_STATIC_STORAGE_PARAM) {
+            let config = bootstrapper
+                .context()
+                .config()
+                .static_files
+                .storage
+                .as_ref()
+                .ok_or(crate::static_files::StaticFilesStorageNotConfigured)?;
+            let storage = crate::storage::FileStorage::from_config(config)?;
+            let collected = static_files
+                .collect_into_storage(
+                    &storage,
+                    matches.get_flag(COLLECT_STATIC_DELETE_ORPHANS_PARAM),
+                )
+                .await?;
+
+            return Ok(TaskOutcome::new().with_message(format!(
+                "Uploaded {} static files to the storage and deleted {} orphaned files",
+                collected.uploaded, collected.deleted
+            )));
+        }
 
-        Ok(())
+        let dir = matches
+            .get_one::<PathBuf>(COLLECT_STATIC_DIR_PARAM)
+            .expect("required argument");
+        static_files.collect_into(dir)?;
+
+        Ok(TaskOutcome::new()
+            .with_message(format!("Collected the static files into {}", dir.display())))
     }
 }
 
@@ -382,10 +526,433 @@ impl CliTask for Check {
         &mut self,
         _matches: &ArgMatches,
         bootstrapper: Bootstrapper<WithConfig>,
-    ) -> Result<()> {
+    ) -> Result<TaskOutcome> {
         bootstrapper.boot().await?;
-        println!("Success verifying the configuration");
-        Ok(())
+        Ok(TaskOutcome::new().with_message("Success verifying the configuration"))
+    }
+}
+
+struct Verify;
+#[async_trait(?Send)]
+impl CliTask for Verify {
+    fn subcommand(&self) -> Command {
+        Command::new(VERIFY_SUBCOMMAND)
+            .about(
+                "Verifies that the routes referenced in the templates and the navigation items \
+                of the apps exist, and that the routes have the path parameters their handlers \
+                expect",
+            )
+            .arg(
+                Arg::new(TEMPLATES_DIR_PARAM)
+                    .help("The directory containing the templates; can be given multiple times")
+                    .short('t')
+                    .long("templates")
+                    .value_name("DIR")
+                    .value_parser(value_parser!(PathBuf))
+                    .action(ArgAction::Append)
+                    .default_value("templates"),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<TaskOutcome> {
+        let bootstrapper = bootstrapper.with_apps();
+        let context = bootstrapper.context();
+
+        let mut references = verify::nav_references(context.apps());
+        for dir in matches
+            .get_many::<PathBuf>(TEMPLATES_DIR_PARAM)
+            .expect("default provided")
+        {
+            references.extend(verify::template_references(dir)?);
+        }
+
+        let problems: Vec<_> = references
+            .iter()
+            .filter_map(|reference| reference.check(context.router()).err())
+            .collect();
+        let mismatches = context.router().path_param_mismatches();
+        for problem in problems.iter().chain(&mismatches) {
+            eprintln!("{problem}");
+        }
+
+        if !problems.is_empty() {
+            Err(verify::VerifyError::DanglingReferences(problems.len()).into())
+        } else if !mismatches.is_empty() {
+            Err(verify::VerifyError::PathParamMismatches(mismatches.len()).into())
+        } else {
+            Ok(TaskOutcome::new().with_message(format!(
+                "Success verifying {} route reference(s)",
+                references.len()
+            )))
+        }
+    }
+}
+
+#[cfg(feature = "tasks")]
+struct Worker;
+
+#[cfg(feature = "tasks")]
+#[async_trait(?Send)]
+impl CliTask for Worker {
+    fn subcommand(&self) -> Command {
+        Command::new(WORKER_SUBCOMMAND)
+            .about("Runs the background tasks without starting the HTTP server")
+    }
+
+    async fn execute(
+        &mut self,
+        _matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<TaskOutcome> {
+        let bootstrapper = bootstrapper.boot().await?;
+        crate::project::run_worker(bootstrapper).await?;
+        Ok(TaskOutcome::new())
+    }
+}
+
+#[cfg(feature = "gdpr")]
+struct Gdpr;
+
+#[cfg(feature = "gdpr")]
+#[async_trait(?Send)]
+impl CliTask for Gdpr {
+    fn subcommand(&self) -> Command {
+        let user_id_arg = Arg::new(USER_ID_PARAM)
+            .help("The ID of the user")
+            .required(true);
+
+        Command::new(GDPR_SUBCOMMAND)
+            .about("Exports or anonymizes the personal data of a user")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new(GDPR_EXPORT_USER_SUBCOMMAND)
+                    .about("Exports the personal data of a user as JSON")
+                    .arg(user_id_arg.clone())
+                    .arg(
+                        Arg::new(FILE_PARAM)
+                            .help("The file to write the export to, instead of the standard output")
+                            .short('f')
+                            .long("file")
+                            .value_name("FILE")
+                            .value_parser(value_parser!(PathBuf)),
+                    ),
+            )
+            .subcommand(
+                Command::new(GDPR_ANONYMIZE_USER_SUBCOMMAND)
+                    .about("Scrubs the personal data of a user")
+                    .arg(user_id_arg),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<TaskOutcome> {
+        let bootstrapper = bootstrapper.with_apps().with_database().await?;
+        let context = bootstrapper.context();
+        let database = context
+            .try_database()
+            .ok_or(GdprError::DatabaseNotConfigured)?;
+
+        let (subcommand, matches) = matches.subcommand().expect("subcommand is required");
+        let user_id = crate::admin::parse_user_id(
+            matches
+                .get_one::<String>(USER_ID_PARAM)
+                .expect("required argument"),
+        );
+
+        let outcome = match subcommand {
+            GDPR_EXPORT_USER_SUBCOMMAND => {
+                let export = crate::gdpr::export_user(database, context.apps(), &user_id).await?;
+                let export = serde_json::to_string_pretty(&export)
+                    .expect("JSON values are always serializable");
+
+                if let Some(output) = matches.get_one::<PathBuf>(FILE_PARAM) {
+                    std::fs::write(output, export).map_err(GdprError::WriteExport)?;
+                    TaskOutcome::new().with_message(format!(
+                        "Exported the data of user {user_id} to {}",
+                        output.display()
+                    ))
+                } else {
+                    TaskOutcome::new().with_message(export)
+                }
+            }
+            GDPR_ANONYMIZE_USER_SUBCOMMAND => {
+                let count = crate::gdpr::anonymize_user(database, context.apps(), &user_id).await?;
+                TaskOutcome::new()
+                    .with_result([
+                        ("user_id", user_id.to_string()),
+                        ("rows", count.to_string()),
+                    ])
+                    .with_message(format!("Anonymized {count} row(s) of user {user_id}"))
+            }
+            _ => unreachable!("subcommand is required"),
+        };
+
+        Ok(outcome)
+    }
+}
+
+#[cfg(feature = "db")]
+struct Migration;
+
+#[cfg(feature = "db")]
+#[async_trait(?Send)]
+impl CliTask for Migration {
+    fn subcommand(&self) -> Command {
+        Command::new(MIGRATION_SUBCOMMAND)
+            .about("Manages the applied database migrations")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new(MIGRATION_APPLY_SUBCOMMAND)
+                    .about("Applies the pending migrations to the configured database")
+                    .arg(
+                        Arg::new(MIGRATION_ALLOW_DESTRUCTIVE_PARAM)
+                            .long(MIGRATION_ALLOW_DESTRUCTIVE_PARAM)
+                            .help(
+                                "Apply the migrations that remove models or fields, or change \
+                                fields in a way that can lose data",
+                            )
+                            .action(ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                Command::new(MIGRATION_STATUS_SUBCOMMAND)
+                    .about("Shows which migrations of each app are applied and which are pending"),
+            )
+            .subcommand(
+                Command::new(MIGRATION_REVERT_SUBCOMMAND)
+                    .about(
+                        "Reverts the most recently applied migration, or all the migrations \
+                        applied after the given one",
+                    )
+                    .arg(
+                        Arg::new(MIGRATION_TO_PARAM)
+                            .help(
+                                "The migration to revert to, optionally prefixed with the app \
+                                name (e.g. `app:m_0001_initial`); it stays applied",
+                            )
+                            .long("to")
+                            .value_name("NAME"),
+                    ),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<TaskOutcome> {
+        let bootstrapper = bootstrapper.with_apps().with_database().await?;
+        let context = bootstrapper.context();
+        let database = context.try_database().ok_or(DatabaseError::MigrationError(
+            MigrationEngineError::DatabaseNotConfigured,
+        ))?;
+
+        let outcome = match matches.subcommand() {
+            Some((MIGRATION_APPLY_SUBCOMMAND, matches)) => {
+                let mut applied = Vec::new();
+                crate::project::run_migrations_with_progress(
+                    context.apps(),
+                    database,
+                    matches.get_flag(MIGRATION_ALLOW_DESTRUCTIVE_PARAM),
+                    |progress| {
+                        // printed to stderr, so that it doesn't get mixed with the outcome
+                        eprintln!("{progress}");
+                        if let MigrationProgress::Applied {
+                            app_name,
+                            migration_name,
+                        } = progress
+                        {
+                            applied.push([
+                                ("app", app_name.to_owned()),
+                                ("migration", migration_name.to_owned()),
+                            ]);
+                        }
+                    },
+                )
+                .await?;
+                applied
+                    .into_iter()
+                    .fold(TaskOutcome::new(), TaskOutcome::with_result)
+                    .with_message("Success applying the migrations")
+            }
+            Some((MIGRATION_STATUS_SUBCOMMAND, _)) => {
+                let status = crate::project::migration_status(context.apps(), database).await?;
+                let mut outcome = TaskOutcome::new();
+                for migration in &status {
+                    let applied = migration.applied_at().map_or_else(
+                        || "pending".to_owned(),
+                        |applied_at| format!("applied at {applied_at}"),
+                    );
+                    outcome = outcome.with_result([
+                        ("app", migration.app_name()),
+                        ("migration", migration.name()),
+                        ("status", applied.as_str()),
+                    ]);
+                }
+                let applied = status
+                    .iter()
+                    .filter(|migration| migration.is_applied())
+                    .count();
+                outcome.with_message(format!(
+                    "{applied} applied, {} pending migration(s)",
+                    status.len() - applied
+                ))
+            }
+            Some((MIGRATION_REVERT_SUBCOMMAND, matches)) => {
+                let to = matches.get_one::<String>(MIGRATION_TO_PARAM);
+                crate::project::revert_migrations(context.apps(), database, to.map(String::as_str))
+                    .await?;
+                TaskOutcome::new().with_message("Success reverting the migrations")
+            }
+            _ => unreachable!("subcommand is required"),
+        };
+
+        Ok(outcome)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Sessions;
+
+#[async_trait(?Send)]
+impl CliTask for Sessions {
+    fn subcommand(&self) -> Command {
+        Command::new(SESSIONS_SUBCOMMAND)
+            .about("Manages the sessions stored in the configured session store")
+            .subcommand_required(true)
+            .subcommand(Command::new(SESSIONS_LIST_SUBCOMMAND).about("Lists all the sessions"))
+            .subcommand(
+                Command::new(SESSIONS_PURGE_EXPIRED_SUBCOMMAND)
+                    .about("Removes all the expired sessions"),
+            )
+            .subcommand(
+                Command::new(SESSIONS_DELETE_SUBCOMMAND)
+                    .about("Removes a session, logging out its user")
+                    .arg(
+                        Arg::new(SESSION_KEY_PARAM)
+                            .help("The key of the session to remove")
+                            .value_parser(value_parser!(Id))
+                            .allow_hyphen_values(true)
+                            .required(true),
+                    ),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<TaskOutcome> {
+        let bootstrapper = bootstrapper
+            .with_apps()
+            .with_database()
+            .await?
+            .with_cache()
+            .await?;
+        let context = bootstrapper.context();
+        let store_config = &context.config().middlewares.session.store.store_type;
+        let mut outcome = TaskOutcome::new();
+        if *store_config == SessionStoreTypeConfig::Memory {
+            outcome = outcome.with_warning(
+                "the in-memory session store is not shared with the running server, so it \
+                doesn't contain any sessions",
+            );
+        }
+        #[cfg(feature = "json")]
+        if matches!(store_config, SessionStoreTypeConfig::Cookie { .. }) {
+            outcome = outcome.with_warning(
+                "the cookie session store keeps the sessions in the clients' cookies, so they \
+                can't be listed or removed",
+            );
+        }
+        let manager = SessionStoreManager::new(session_store_from_config(store_config, context));
+
+        let outcome = match matches.subcommand() {
+            Some((SESSIONS_LIST_SUBCOMMAND, _)) => {
+                let sessions = manager.list().await?;
+                for session in &sessions {
+                    let user = session
+                        .user_id()
+                        .map_or_else(|| "-".to_owned(), ToString::to_string);
+                    let status = if session.is_expired() {
+                        "expired"
+                    } else {
+                        "active"
+                    };
+                    outcome = outcome.with_result([
+                        ("key", session.id().to_string()),
+                        ("expiry_date", session.expiry_date().to_string()),
+                        ("status", status.to_owned()),
+                        ("user", user),
+                    ]);
+                }
+                outcome.with_message(format!("{} session(s)", sessions.len()))
+            }
+            Some((SESSIONS_PURGE_EXPIRED_SUBCOMMAND, _)) => {
+                let deleted = manager.purge_expired().await?;
+                outcome.with_message(format!("Removed {deleted} expired session(s)"))
+            }
+            Some((SESSIONS_DELETE_SUBCOMMAND, matches)) => {
+                let id = matches
+                    .get_one::<Id>(SESSION_KEY_PARAM)
+                    .expect("required argument");
+                manager.delete(id).await?;
+                outcome.with_message(format!("Removed session {id}"))
+            }
+            _ => unreachable!("subcommand is required"),
+        };
+
+        Ok(outcome)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Config;
+
+#[async_trait(?Send)]
+impl CliTask for Config {
+    fn subcommand(&self) -> Command {
+        Command::new(CONFIG_SUBCOMMAND)
+            .about("Inspects the project configuration")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new(CONFIG_SHOW_SUBCOMMAND)
+                    .about("Prints the resolved configuration, with the secrets redacted")
+                    .arg(
+                        Arg::new(CONFIG_NAME_PARAM)
+                            .help(
+                                "The name of the configuration to show, instead of the one \
+                                selected with --config",
+                            )
+                            .short('n')
+                            .long("name")
+                            .value_name("NAME"),
+                    ),
+            )
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<TaskOutcome> {
+        match matches.subcommand() {
+            Some((CONFIG_SHOW_SUBCOMMAND, matches)) => {
+                let config = match matches.get_one::<String>(CONFIG_NAME_PARAM) {
+                    Some(name) => bootstrapper.project().config(name)?,
+                    None => bootstrapper.context().config().clone(),
+                };
+                Ok(TaskOutcome::new().with_message(config.to_redacted_toml()?))
+            }
+            _ => unreachable!("subcommand is required"),
+        }
     }
 }
 
@@ -404,7 +971,11 @@ macro_rules! metadata {
 
 pub use metadata;
 
+use tower_sessions::session::Id;
+
+use crate::config::SessionStoreTypeConfig;
 use crate::project::{StartServerError, WithConfig};
+use crate::session::store::{SessionStoreManager, session_store_from_config};
 use crate::static_files::StaticFiles;
 
 #[cfg(test)]
@@ -416,6 +987,7 @@ mod tests {
     use super::*;
     use crate::config::ProjectConfig;
     use crate::project::RegisterAppsContext;
+    use crate::router::{Route, Router};
     use crate::static_files::StaticFile;
     use crate::{App, AppBuilder};
 
@@ -460,8 +1032,8 @@ mod tests {
                 &mut self,
                 _matches: &ArgMatches,
                 _bootstrapper: Bootstrapper<WithConfig>,
-            ) -> Result<()> {
-                Ok(())
+            ) -> Result<TaskOutcome> {
+                Ok(TaskOutcome::new())
             }
         }
 
@@ -476,6 +1048,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cli_output_format() {
+        let mut cli = Cli::new();
+
+        let matches = cli.command.try_get_matches_from_mut(["test"]).unwrap();
+        assert_eq!(
+            CommonOptions::output_format_from(&matches),
+            OutputFormat::Text
+        );
+
+        let matches = cli
+            .command
+            .try_get_matches_from_mut(["test", "--output", "text", "check"])
+            .unwrap();
+        assert_eq!(
+            CommonOptions::output_format_from(&matches),
+            OutputFormat::Text
+        );
+
+        assert!(
+            cli.command
+                .try_get_matches_from_mut(["test", "--output", "xml"])
+                .is_err()
+        );
+    }
+
+    #[cfg(all(feature = "json", feature = "db"))]
+    #[test]
+    fn cli_output_format_after_subcommand() {
+        let mut cli = Cli::new();
+
+        let matches = cli
+            .command
+            .try_get_matches_from_mut(["test", "migration", "apply", "--output", "json"])
+            .unwrap();
+
+        assert_eq!(
+            CommonOptions::output_format_from(&matches),
+            OutputFormat::Json
+        );
+    }
+
+    #[cfg(all(feature = "json", feature = "gdpr"))]
+    #[test]
+    fn cli_gdpr_export_file_and_output_format() {
+        let mut cli = Cli::new();
+
+        let matches = cli
+            .command
+            .try_get_matches_from_mut([
+                "test",
+                "gdpr",
+                "export-user",
+                "1",
+                "--output",
+                "json",
+                "--file",
+                "export.json",
+            ])
+            .unwrap();
+
+        assert_eq!(
+            CommonOptions::output_format_from(&matches),
+            OutputFormat::Json
+        );
+        let (_, matches) = matches.subcommand().unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+        assert_eq!(
+            matches.get_one::<PathBuf>(FILE_PARAM).unwrap(),
+            &PathBuf::from("export.json")
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn cli_output_format_json() {
+        let mut cli = Cli::new();
+
+        let matches = cli
+            .command
+            .try_get_matches_from_mut(["test", "--output", "json", "check"])
+            .unwrap();
+
+        assert_eq!(
+            CommonOptions::output_format_from(&matches),
+            OutputFormat::Json
+        );
+    }
+
     #[test]
     fn run_server_subcommand() {
         let matches = RunServer
@@ -524,6 +1185,447 @@ mod tests {
         assert!(temp_path.join("test.txt").exists());
     }
 
+    #[cfg(feature = "storage")]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+    )]
+    async fn collect_static_execute_storage() {
+        struct TestApp;
+        impl App for TestApp {
+            fn name(&self) -> &'static str {
+                "test_app"
+            }
+
+            fn static_files(&self) -> Vec<StaticFile> {
+                vec![StaticFile::new("test.txt", "test")]
+            }
+        }
+
+        struct TestProject;
+        impl cot::Project for TestProject {
+            fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+                apps.register(TestApp);
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("orphan.txt"), "orphan").unwrap();
+        let config = ProjectConfig::from_toml(&format!(
+            "[static_files.storage.backend]\ntype = \"filesystem\"\nroot = {:?}\n",
+            temp_dir.path().to_str().unwrap()
+        ))
+        .unwrap();
+
+        let matches = CollectStatic.subcommand().get_matches_from(vec![
+            "test",
+            "--storage",
+            "--delete-orphans",
+        ]);
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(config);
+        let outcome = CollectStatic.execute(&matches, bootstrapper).await.unwrap();
+
+        assert_eq!(
+            outcome.message(),
+            Some("Uploaded 1 static files to the storage and deleted 1 orphaned files")
+        );
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("test.txt")).unwrap(),
+            "test"
+        );
+        assert!(!temp_dir.path().join("orphan.txt").exists());
+    }
+
+    #[cfg(feature = "storage")]
+    #[test]
+    fn collect_static_subcommand_storage_args() {
+        let command = CollectStatic.subcommand();
+
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["test", "--storage"])
+                .is_ok()
+        );
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["test", "dir", "--storage"])
+                .is_err()
+        );
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["test", "dir", "--delete-orphans"])
+                .is_err()
+        );
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["test", "--delete-orphans"])
+                .is_err()
+        );
+        assert!(command.try_get_matches_from(["test"]).is_err());
+    }
+
+    #[cot::test]
+    async fn verify_execute() {
+        async fn detail() -> crate::html::Html {
+            unimplemented!()
+        }
+
+        struct TestApp;
+        impl App for TestApp {
+            fn name(&self) -> &'static str {
+                "test_app"
+            }
+
+            fn router(&self) -> Router {
+                Router::with_urls([Route::with_handler_and_name("/{id}", detail, "detail")])
+            }
+        }
+
+        struct TestProject;
+        impl cot::Project for TestProject {
+            fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+                apps.register_with_views(TestApp, "");
+            }
+        }
+
+        #[expect(clippy::future_not_send)]
+        async fn verify(templates_dir: &std::path::Path) -> Result<TaskOutcome> {
+            let matches = Verify.subcommand().get_matches_from(vec![
+                "test",
+                "--templates",
+                templates_dir.to_str().unwrap(),
+            ]);
+            let bootstrapper = Bootstrapper::new(TestProject).with_config(ProjectConfig::default());
+            Verify.execute(&matches, bootstrapper).await
+        }
+
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("detail.html"),
+            r#"{{ cot::reverse!(urls, "test_app:detail", id = 1)? }}"#,
+        )
+        .unwrap();
+        let outcome = verify(temp_dir.path()).await.unwrap();
+        assert_eq!(
+            outcome.message(),
+            Some("Success verifying 1 route reference(s)")
+        );
+
+        std::fs::write(
+            temp_dir.path().join("broken.html"),
+            r#"{{ cot::reverse!(urls, "detail")? }}"#,
+        )
+        .unwrap();
+        let result = verify(temp_dir.path()).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "found 1 dangling route reference(s)"
+        );
+    }
+
+    #[cot::test]
+    async fn verify_execute_path_param_mismatch() {
+        async fn detail(
+            _params: crate::request::extractors::Path<(i32, i32)>,
+        ) -> crate::html::Html {
+            unimplemented!()
+        }
+
+        struct TestApp;
+        impl App for TestApp {
+            fn name(&self) -> &'static str {
+                "test_app"
+            }
+
+            fn router(&self) -> Router {
+                Router::with_urls([Route::with_handler("/{todo_id}", detail)])
+            }
+        }
+
+        struct TestProject;
+        impl cot::Project for TestProject {
+            fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+                apps.register_with_views(TestApp, "");
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let matches = Verify.subcommand().get_matches_from(vec![
+            "test",
+            "--templates",
+            temp_dir.path().to_str().unwrap(),
+        ]);
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(ProjectConfig::default());
+        let result = Verify.execute(&matches, bootstrapper).await;
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "found 1 route(s) with path parameters not matching their handlers"
+        );
+    }
+
+    #[test]
+    fn sessions_subcommand() {
+        let command = Sessions.subcommand();
+
+        assert!(command.clone().try_get_matches_from(["test"]).is_err());
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["test", "delete", "invalid"])
+                .is_err()
+        );
+        // session keys can start with a hyphen
+        let key = Id(0xf8).to_string();
+        assert!(key.starts_with('-'));
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["test", "delete", &key])
+                .is_ok()
+        );
+        assert!(
+            command
+                .try_get_matches_from(["test", "purge-expired"])
+                .is_ok()
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+    )]
+    async fn sessions_execute() {
+        use time::{Duration, OffsetDateTime};
+        use tower_sessions::SessionStore;
+        use tower_sessions::session::Record;
+
+        use crate::session::store::ManagedSessionStore;
+        use crate::session::store::file::FileStore;
+
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        #[expect(clippy::future_not_send)]
+        async fn execute_sessions(args: &[&str], path: &std::path::Path) {
+            let config = ProjectConfig::from_toml(&format!(
+                "[middlewares.session.store]\ntype = \"file\"\npath = {:?}",
+                path.to_str().unwrap()
+            ))
+            .unwrap();
+            let matches = Sessions
+                .subcommand()
+                .get_matches_from(std::iter::once("test").chain(args.iter().copied()));
+            let bootstrapper = Bootstrapper::new(TestProject).with_config(config);
+
+            Sessions.execute(&matches, bootstrapper).await.unwrap();
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let store = FileStore::new(temp_dir.path().to_path_buf()).unwrap();
+        let mut active = Record {
+            id: Id::default(),
+            data: HashMap::default(),
+            expiry_date: OffsetDateTime::now_utc() + Duration::hours(1),
+        };
+        let mut expired = Record {
+            expiry_date: OffsetDateTime::now_utc() - Duration::hours(1),
+            ..active.clone()
+        };
+        store.create(&mut active).await.unwrap();
+        store.create(&mut expired).await.unwrap();
+
+        execute_sessions(&["list"], temp_dir.path()).await;
+        execute_sessions(&["purge-expired"], temp_dir.path()).await;
+        assert_eq!(store.list().await.unwrap(), vec![active.clone()]);
+
+        execute_sessions(&["delete", &active.id.to_string()], temp_dir.path()).await;
+        assert!(store.list().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn config_subcommand() {
+        let command = Config.subcommand();
+
+        assert!(command.clone().try_get_matches_from(["test"]).is_err());
+        assert!(
+            command
+                .try_get_matches_from(["test", "show", "--name", "prod"])
+                .is_ok()
+        );
+    }
+
+    #[cot::test]
+    async fn config_show_execute() {
+        struct TestProject;
+        impl cot::Project for TestProject {
+            fn config(&self, config_name: &str) -> Result<ProjectConfig> {
+                assert_eq!(config_name, "prod");
+                ProjectConfig::from_toml(r#"secret_key = "123abc""#)
+            }
+        }
+
+        let matches = Config
+            .subcommand()
+            .get_matches_from(["test", "show", "--name", "prod"]);
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(ProjectConfig::default());
+        let outcome = Config.execute(&matches, bootstrapper).await.unwrap();
+
+        let config = outcome.message().unwrap();
+        assert!(config.contains("secret_key"));
+        assert!(!config.contains("123abc"));
+    }
+
+    #[cfg(feature = "tasks")]
+    #[test]
+    fn worker_subcommand() {
+        let cli = Cli::new();
+
+        assert!(cli.tasks.contains_key(&Some(WORKER_SUBCOMMAND.to_owned())));
+        assert!(
+            cli.command
+                .get_subcommands()
+                .any(|sc| sc.get_name() == WORKER_SUBCOMMAND)
+        );
+    }
+
+    #[cfg(feature = "gdpr")]
+    #[test]
+    fn gdpr_subcommand() {
+        let matches = Gdpr.subcommand().try_get_matches_from(vec![
+            "gdpr",
+            "export-user",
+            "42",
+            "--file",
+            "export.json",
+        ]);
+
+        let matches = matches.unwrap();
+        let (subcommand, matches) = matches.subcommand().unwrap();
+        assert_eq!(subcommand, GDPR_EXPORT_USER_SUBCOMMAND);
+        assert_eq!(matches.get_one::<String>(USER_ID_PARAM).unwrap(), "42");
+        assert_eq!(
+            matches.get_one::<PathBuf>(FILE_PARAM).unwrap(),
+            &PathBuf::from("export.json")
+        );
+
+        let matches = Gdpr
+            .subcommand()
+            .try_get_matches_from(vec!["gdpr", "anonymize-user"]);
+        assert!(matches.is_err());
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn migration_subcommand() {
+        let command = Migration.subcommand();
+
+        assert!(command.clone().try_get_matches_from(["test"]).is_err());
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["test", "apply"])
+                .is_ok()
+        );
+        assert!(
+            command
+                .clone()
+                .try_get_matches_from(["test", "status"])
+                .is_ok()
+        );
+        let matches = command
+            .try_get_matches_from(["test", "revert", "--to", "app:m_0001_initial"])
+            .unwrap();
+        let (subcommand, matches) = matches.subcommand().unwrap();
+        assert_eq!(subcommand, MIGRATION_REVERT_SUBCOMMAND);
+        assert_eq!(
+            matches.get_one::<String>(MIGRATION_TO_PARAM).unwrap(),
+            "app:m_0001_initial"
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+    )]
+    async fn migration_execute() {
+        struct TestProject;
+        impl cot::Project for TestProject {
+            fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+                apps.register(crate::auth::db::DatabaseUserApp::new());
+            }
+        }
+
+        #[expect(clippy::future_not_send)]
+        async fn execute_migration(args: &[&str], database_path: &std::path::Path) -> TaskOutcome {
+            let config = ProjectConfig::from_toml(&format!(
+                "[database]\nurl = \"sqlite://{}?mode=rwc\"",
+                database_path.display()
+            ))
+            .unwrap();
+            let matches = Migration
+                .subcommand()
+                .get_matches_from(std::iter::once("test").chain(args.iter().copied()));
+            let bootstrapper = Bootstrapper::new(TestProject).with_config(config);
+
+            Migration.execute(&matches, bootstrapper).await.unwrap()
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let database_path = temp_dir.path().join("db.sqlite3");
+
+        let pending = execute_migration(&["status"], &database_path).await;
+        let applied = execute_migration(&["apply"], &database_path).await;
+        let status = execute_migration(&["status"], &database_path).await;
+
+        assert!(!pending.results().is_empty());
+        assert_eq!(applied.results().len(), pending.results().len());
+        assert!(
+            status
+                .results()
+                .iter()
+                .all(|result| result["status"].starts_with("applied at"))
+        );
+
+        let database = crate::db::Database::new(format!("sqlite://{}", database_path.display()))
+            .await
+            .unwrap();
+        let apps: Vec<Box<dyn App>> = vec![Box::new(crate::auth::db::DatabaseUserApp::new())];
+        let status = crate::project::migration_status(&apps, &database)
+            .await
+            .unwrap();
+        assert!(!status.is_empty());
+        assert!(
+            status
+                .iter()
+                .all(crate::db::migrations::MigrationStatus::is_applied)
+        );
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    async fn migration_revert_execute_no_database() {
+        struct TestProject;
+        impl cot::Project for TestProject {}
+
+        let config = ProjectConfig::from_toml(r#"secret_key = "123abc""#).unwrap();
+        let matches = Migration.subcommand().get_matches_from(["test", "revert"]);
+        let bootstrapper = Bootstrapper::new(TestProject).with_config(config);
+
+        let result = Migration.execute(&matches, bootstrapper).await;
+
+        assert!(result.is_err());
+    }
+
     #[cot::test]
     async fn check_execute() {
         let config = r#"secret_key = "123abc""#;
@@ -549,7 +1651,7 @@ mod tests {
     }
 
     #[expect(clippy::future_not_send, clippy::await_holding_lock)]
-    async fn test_check(config: &str) -> Result<()> {
+    async fn test_check(config: &str) -> Result<TaskOutcome> {
         struct TestProject;
         impl cot::Project for TestProject {}
 
@@ -0,0 +1,342 @@
+//! The outcome of a CLI task and its rendering.
+//!
+//! Every [`CliTask`](super::CliTask) returns a [`TaskOutcome`] describing what
+//! it did. The CLI renders it in the format selected with the global
+//! `--output` flag: as plain text meant to be read by a human, or as a JSON
+//! document meant to be consumed by scripts and CI steps. The exit code of the
+//! process is taken from the outcome as well.
+
+use std::fmt::Write;
+
+use indexmap::IndexMap;
+
+/// The format the outcome of a CLI task is rendered in.
+///
+/// Selected using the global `--output` command line flag.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// Human-readable text. The results are printed one per line, with their
+    /// values separated by tabs, followed by the message of the outcome. The
+    /// warnings are printed to the standard error.
+    #[default]
+    Text,
+    /// A single JSON document containing the whole outcome, printed to the
+    /// standard output.
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl OutputFormat {
+    pub(super) const TEXT: &'static str = "text";
+    #[cfg(feature = "json")]
+    pub(super) const JSON: &'static str = "json";
+
+    pub(super) fn possible_values() -> Vec<&'static str> {
+        vec![
+            Self::TEXT,
+            #[cfg(feature = "json")]
+            Self::JSON,
+        ]
+    }
+
+    pub(super) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            Self::TEXT => Some(Self::Text),
+            #[cfg(feature = "json")]
+            Self::JSON => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The structured outcome of a [`CliTask`](super::CliTask).
+///
+/// An outcome consists of an optional message summarizing what the task did,
+/// a list of results (each being a set of named values, such as a row of a
+/// table), a list of warnings, and the exit code of the process. The CLI
+/// renders it according to the [`OutputFormat`] selected by the user, so
+/// tasks don't need to print anything to the standard output themselves.
+///
+/// # Examples
+///
+/// ```
+/// use cot::cli::TaskOutcome;
+///
+/// let outcome = TaskOutcome::new()
+///     .with_result([("name", "alice"), ("role", "admin")])
+///     .with_result([("name", "bob"), ("role", "user")])
+///     .with_warning("user bob has never logged in")
+///     .with_message("2 user(s)");
+///
+/// assert!(outcome.is_success());
+/// assert_eq!(outcome.results().len(), 2);
+/// assert_eq!(outcome.results()[0]["name"], "alice");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskOutcome {
+    message: Option<String>,
+    results: Vec<IndexMap<String, String>>,
+    warnings: Vec<String>,
+    exit_code: u8,
+}
+
+impl TaskOutcome {
+    /// Creates a new, successful outcome without any message, results, or
+    /// warnings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cli::TaskOutcome;
+    ///
+    /// let outcome = TaskOutcome::new();
+    /// assert!(outcome.is_success());
+    /// assert_eq!(outcome.message(), None);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub(super) fn from_error(error: &crate::Error) -> Self {
+        Self::new()
+            .with_message(error.to_string())
+            .with_exit_code(1)
+    }
+
+    /// Sets the message summarizing what the task did.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cli::TaskOutcome;
+    ///
+    /// let outcome = TaskOutcome::new().with_message("Removed 3 session(s)");
+    /// assert_eq!(outcome.message(), Some("Removed 3 session(s)"));
+    /// ```
+    #[must_use]
+    pub fn with_message<T: Into<String>>(mut self, message: T) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Adds a result, given as a list of named values.
+    ///
+    /// The order of the values is preserved when the outcome is rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cli::TaskOutcome;
+    ///
+    /// let outcome = TaskOutcome::new().with_result([("app", "blog"), ("status", "applied")]);
+    /// assert_eq!(outcome.results()[0]["status"], "applied");
+    /// ```
+    #[must_use]
+    pub fn with_result<I, K, V>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: ToString,
+    {
+        self.results.push(
+            values
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.to_string()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Adds a warning, which doesn't make the task fail, but should be brought
+    /// to the user's attention.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cli::TaskOutcome;
+    ///
+    /// let outcome = TaskOutcome::new().with_warning("the cache is disabled");
+    /// assert_eq!(outcome.warnings(), ["the cache is disabled"]);
+    /// assert!(outcome.is_success());
+    /// ```
+    #[must_use]
+    pub fn with_warning<T: Into<String>>(mut self, warning: T) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    /// Sets the exit code of the process. `0`, which is the default, means
+    /// success.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cli::TaskOutcome;
+    ///
+    /// let outcome = TaskOutcome::new()
+    ///     .with_message("2 pending migration(s)")
+    ///     .with_exit_code(2);
+    /// assert!(!outcome.is_success());
+    /// ```
+    #[must_use]
+    pub fn with_exit_code(mut self, exit_code: u8) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// Returns the message summarizing what the task did.
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Returns the results of the task.
+    #[must_use]
+    pub fn results(&self) -> &[IndexMap<String, String>] {
+        &self.results
+    }
+
+    /// Returns the warnings reported by the task.
+    #[must_use]
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Returns the exit code of the process.
+    #[must_use]
+    pub fn exit_code(&self) -> u8 {
+        self.exit_code
+    }
+
+    /// Returns whether the task succeeded, i.e. whether the exit code is `0`.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.exit_code == 0
+    }
+
+    /// Prints the outcome in the given format.
+    pub(super) fn print(&self, format: OutputFormat) {
+        match format {
+            OutputFormat::Text => {
+                for warning in &self.warnings {
+                    eprintln!("Warning: {warning}");
+                }
+                print!("{}", self.to_text());
+            }
+            #[cfg(feature = "json")]
+            OutputFormat::Json => println!("{}", self.to_json()),
+        }
+    }
+
+    /// Renders the results and the message as text, without the warnings,
+    /// which are printed to the standard error.
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        for result in &self.results {
+            let values: Vec<_> = result.values().map(String::as_str).collect();
+            writeln!(text, "{}", values.join("\t")).expect("writing to a String never fails");
+        }
+        if let Some(message) = &self.message {
+            text.push_str(message);
+            if !message.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    #[cfg(feature = "json")]
+    fn to_json(&self) -> String {
+        #[derive(serde::Serialize)]
+        struct JsonOutcome<'a> {
+            status: &'static str,
+            exit_code: u8,
+            message: Option<&'a str>,
+            results: &'a [IndexMap<String, String>],
+            warnings: &'a [String],
+        }
+
+        let outcome = JsonOutcome {
+            status: if self.is_success() {
+                "success"
+            } else {
+                "failure"
+            },
+            exit_code: self.exit_code,
+            message: self.message(),
+            results: &self.results,
+            warnings: &self.warnings,
+        };
+        serde_json::to_string_pretty(&outcome).expect("the outcome is always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_format_from_name() {
+        assert_eq!(OutputFormat::from_name("text"), Some(OutputFormat::Text));
+        assert_eq!(OutputFormat::from_name("xml"), None);
+        assert!(OutputFormat::possible_values().contains(&"text"));
+    }
+
+    #[test]
+    fn outcome_to_text() {
+        let outcome = TaskOutcome::new()
+            .with_result([("app", "blog"), ("status", "applied")])
+            .with_result([("app", "shop"), ("status", "pending")])
+            .with_warning("ignored")
+            .with_message("1 applied, 1 pending migration(s)");
+
+        assert_eq!(
+            outcome.to_text(),
+            "blog\tapplied\nshop\tpending\n1 applied, 1 pending migration(s)\n"
+        );
+    }
+
+    #[test]
+    fn outcome_to_text_empty() {
+        assert_eq!(TaskOutcome::new().to_text(), "");
+        assert_eq!(
+            TaskOutcome::new().with_message("a = 1\n").to_text(),
+            "a = 1\n"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn outcome_to_json() {
+        let outcome = TaskOutcome::new()
+            .with_result([("id", 1)])
+            .with_warning("careful")
+            .with_message("done")
+            .with_exit_code(2);
+
+        let json: serde_json::Value = serde_json::from_str(&outcome.to_json()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "status": "failure",
+                "exit_code": 2,
+                "message": "done",
+                "results": [{"id": "1"}],
+                "warnings": ["careful"],
+            })
+        );
+    }
+
+    #[test]
+    fn outcome_from_error() {
+        let outcome = TaskOutcome::from_error(&crate::Error::internal("boom"));
+
+        assert!(!outcome.is_success());
+        assert_eq!(outcome.exit_code(), 1);
+        assert!(outcome.message().unwrap().contains("boom"));
+    }
+}
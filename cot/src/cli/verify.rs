@@ -0,0 +1,470 @@
+//! Verification of the route references used by the project, run by the
+//! `verify` command.
+//!
+//! The references are collected from the `reverse!` and `reverse_canonical!`
+//! macro calls and the [`url`](crate::router::filters::url) filters in the
+//! template files and from the navigation items of the registered apps. Each of them is then checked against the project router,
+//! so that a renamed or removed route is reported before the project is
+//! deployed, rather than when someone clicks the broken link.
+//!
+//! The command also checks that every route has the path parameters that the
+//! [`Path`](crate::request::extractors::Path) extractors of its handler expect,
+//! so that a `/todos/{todo_id}/` route with a handler taking
+//! `Path<(i32, i32)>` doesn't fail only when requested.
+
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use cot_core::error::impl_into_cot_error;
+use thiserror::Error;
+
+use crate::App;
+use crate::router::{Router, split_view_name};
+
+const REVERSE_MACRO_NAME: &str = "reverse";
+const REVERSE_CANONICAL_SUFFIX: &str = "_canonical";
+const URL_FILTER_NAME: &str = "url";
+
+#[derive(Debug, Error)]
+pub(crate) enum VerifyError {
+    #[error("could not read the templates in `{}`: {source}", .path.display())]
+    ReadTemplates {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("found {0} dangling route reference(s)")]
+    DanglingReferences(usize),
+    #[error("found {0} route(s) with path parameters not matching their handlers")]
+    PathParamMismatches(usize),
+}
+impl_into_cot_error!(VerifyError);
+
+/// A reference to a named route, such as a `reverse!` call in a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RouteReference {
+    location: String,
+    app_name: Option<String>,
+    view_name: String,
+    params: ReferenceParams,
+}
+
+/// The route parameters given in a [`RouteReference`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ReferenceParams {
+    /// The parameters are given by name, as in the `reverse!` macro.
+    Named(Vec<String>),
+    /// The given number of parameters is given by position, as in the `url`
+    /// filter.
+    Positional(usize),
+}
+
+impl RouteReference {
+    /// Checks that the referenced route exists in the router and takes the
+    /// same parameters as the ones given in the reference.
+    ///
+    /// # Errors
+    ///
+    /// Returns the description of the problem if the reference is dangling.
+    pub(crate) fn check(&self, router: &Router) -> Result<(), String> {
+        let Some(mut expected) =
+            router.route_param_names(self.app_name.as_deref(), &self.view_name)
+        else {
+            return Err(format!("{self}: route does not exist"));
+        };
+
+        match &self.params {
+            ReferenceParams::Named(param_names) => {
+                expected.sort_unstable();
+
+                let mut given: Vec<_> = param_names.iter().map(String::as_str).collect();
+                given.sort_unstable();
+
+                if expected == given {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{self}: route expects {} parameter(s) ({}), but {} were given ({})",
+                        expected.len(),
+                        expected.join(", "),
+                        given.len(),
+                        given.join(", "),
+                    ))
+                }
+            }
+            ReferenceParams::Positional(count) => {
+                if expected.len() == *count {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{self}: route expects {} parameter(s) ({}), but {count} were given",
+                        expected.len(),
+                        expected.join(", "),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl Display for RouteReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: `", self.location)?;
+        if let Some(app_name) = &self.app_name {
+            write!(f, "{app_name}:")?;
+        }
+        write!(f, "{}`", self.view_name)
+    }
+}
+
+/// Returns the references to the routes of the apps in their navigation
+/// items.
+pub(crate) fn nav_references(apps: &[Box<dyn App>]) -> Vec<RouteReference> {
+    apps.iter()
+        .flat_map(|app| {
+            app.nav_items().into_iter().map(|item| RouteReference {
+                location: format!("navigation item `{}` of app `{}`", item.label(), app.name()),
+                app_name: Some(app.name().to_owned()),
+                view_name: item.route_name().to_owned(),
+                params: ReferenceParams::Named(Vec::new()),
+            })
+        })
+        .collect()
+}
+
+/// Returns the references to the routes in the template files in the given
+/// directory and its subdirectories.
+///
+/// A directory that doesn't exist is treated as an empty one.
+///
+/// # Errors
+///
+/// Returns an error if the directory or any of the template files could not be
+/// read.
+pub(crate) fn template_references(dir: &Path) -> Result<Vec<RouteReference>, VerifyError> {
+    let mut references = Vec::new();
+    collect_template_references(dir, &mut references).map_err(|source| {
+        VerifyError::ReadTemplates {
+            path: dir.to_owned(),
+            source,
+        }
+    })?;
+    Ok(references)
+}
+
+fn collect_template_references(
+    dir: &Path,
+    references: &mut Vec<RouteReference>,
+) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    };
+
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_template_references(&path, references)?;
+        } else {
+            let Ok(source) = std::fs::read_to_string(&path) else {
+                // not a text file, so it can't be a template
+                continue;
+            };
+            references.extend(parse_template(&source, &path));
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the `reverse!` and `reverse_canonical!` calls and the `url` filters in
+/// a template source.
+fn parse_template(source: &str, path: &Path) -> Vec<RouteReference> {
+    let mut references = parse_reverse_calls(source, path);
+    references.extend(parse_url_filters(source, path));
+    references.sort_by_key(|(start, _)| *start);
+
+    references
+        .into_iter()
+        .map(|(_start, reference)| reference)
+        .collect()
+}
+
+/// Creates the reference found at the given offset of a template source.
+fn template_reference(
+    source: &str,
+    path: &Path,
+    start: usize,
+    view_name: &str,
+    params: ReferenceParams,
+) -> RouteReference {
+    let (app_name, view_name) = split_view_name(view_name);
+    let line = source[..start].matches('\n').count() + 1;
+
+    RouteReference {
+        location: format!("{}:{line}", path.display()),
+        app_name: app_name.map(ToOwned::to_owned),
+        view_name: view_name.to_owned(),
+        params,
+    }
+}
+
+/// Finds the `reverse!` and `reverse_canonical!` calls in a template source,
+/// along with their offsets.
+fn parse_reverse_calls(source: &str, path: &Path) -> Vec<(usize, RouteReference)> {
+    let mut references = Vec::new();
+
+    for (start, _) in source.match_indices(REVERSE_MACRO_NAME) {
+        let is_part_of_identifier = source[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        if is_part_of_identifier {
+            continue;
+        }
+
+        let rest = &source[start + REVERSE_MACRO_NAME.len()..];
+        let rest = rest.strip_prefix(REVERSE_CANONICAL_SUFFIX).unwrap_or(rest);
+        let Some(rest) = rest
+            .strip_prefix('!')
+            .and_then(|rest| rest.trim_start().strip_prefix('('))
+        else {
+            continue;
+        };
+        let Some(args) = split_macro_args(rest) else {
+            continue;
+        };
+        let Some(view_name) = args
+            .get(1)
+            .and_then(|arg| arg.trim().strip_prefix('"'))
+            .and_then(|arg| arg.strip_suffix('"'))
+        else {
+            continue;
+        };
+
+        let param_names = args[2..]
+            .iter()
+            .filter_map(|arg| arg.split_once('='))
+            .map(|(name, _value)| name.trim().to_owned())
+            .collect();
+        let params = ReferenceParams::Named(param_names);
+        references.push((
+            start,
+            template_reference(source, path, start, view_name, params),
+        ));
+    }
+
+    references
+}
+
+/// Finds the `url` filters in a template source, along with their offsets.
+///
+/// The filter takes a single value for routes with one parameter, a tuple for
+/// routes with more, or `()` for routes without any, so the number of the
+/// parameters is the number of the tuple elements, if a tuple literal is
+/// given, and 1 otherwise.
+fn parse_url_filters(source: &str, path: &Path) -> Vec<(usize, RouteReference)> {
+    let mut references = Vec::new();
+
+    for (start, _) in source.match_indices(URL_FILTER_NAME) {
+        let before = source[..start].trim_end();
+        let is_filter = before.ends_with('|') && !before.ends_with("||");
+        if !is_filter {
+            continue;
+        }
+
+        let Some(rest) = source[start + URL_FILTER_NAME.len()..]
+            .trim_start()
+            .strip_prefix('(')
+        else {
+            continue;
+        };
+        let Some(args) = split_macro_args(rest) else {
+            continue;
+        };
+        let [view_name, params] = args.as_slice() else {
+            continue;
+        };
+        let Some(view_name) = view_name
+            .trim()
+            .strip_prefix('"')
+            .and_then(|arg| arg.strip_suffix('"'))
+        else {
+            continue;
+        };
+
+        let params = params.trim();
+        let param_count = match params.strip_prefix('(') {
+            Some(tuple) if params.ends_with(')') => split_macro_args(tuple).map_or(1, |elements| {
+                elements
+                    .iter()
+                    .filter(|element| !element.trim().is_empty())
+                    .count()
+            }),
+            _ => 1,
+        };
+        let params = ReferenceParams::Positional(param_count);
+        references.push((
+            start,
+            template_reference(source, path, start, view_name, params),
+        ));
+    }
+
+    references
+}
+
+/// Splits the arguments of a macro call at the top-level commas, given the
+/// source right after the opening parenthesis.
+///
+/// Returns `None` if the closing parenthesis is missing.
+fn split_macro_args(source: &str) -> Option<Vec<&str>> {
+    let mut args = Vec::new();
+    let mut depth = 0_usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut arg_start = 0;
+
+    for (index, c) in source.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' if depth == 0 => {
+                let arg = &source[arg_start..index];
+                if !arg.trim().is_empty() {
+                    args.push(arg);
+                }
+                return Some(args);
+            }
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(&source[arg_start..index]);
+                arg_start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::Request;
+    use crate::response::Response;
+    use crate::router::Route;
+
+    fn reference(view_name: &str, param_names: &[&str]) -> RouteReference {
+        let (app_name, view_name) = split_view_name(view_name);
+        RouteReference {
+            location: "index.html:1".to_owned(),
+            app_name: app_name.map(ToOwned::to_owned),
+            view_name: view_name.to_owned(),
+            params: ReferenceParams::Named(
+                param_names.iter().map(|&name| name.to_owned()).collect(),
+            ),
+        }
+    }
+
+    fn positional_reference(view_name: &str, param_count: usize) -> RouteReference {
+        RouteReference {
+            params: ReferenceParams::Positional(param_count),
+            ..reference(view_name, &[])
+        }
+    }
+
+    #[test]
+    fn parse_template_references() {
+        let source = r#"<a href="{{ cot::reverse!(urls, "index")? }}">Home</a>
+{%- let link = cot::reverse!(urls, "admin:edit", model_name = model.url_name(), pk = object.id())? -%}
+<link rel="canonical" href="{{ cot::reverse_canonical!(urls, "post", slug = post.slug)? }}">
+{{ cot::reverse_redirect!(urls, "ignored") }} {{ my_reverse!(urls, "ignored") }}
+<a href="{{ urls|url("index", ()) }}">{{ urls | url("todos:remove-todo", todo.id) }}</a>
+<a href="{{ urls|url("post", (post.year, &post.slug)) }}">{{ url("ignored", ()) }}</a>"#;
+
+        let references = parse_template(source, Path::new("index.html"));
+
+        assert_eq!(
+            references,
+            [
+                reference("index", &[]),
+                RouteReference {
+                    location: "index.html:2".to_owned(),
+                    ..reference("admin:edit", &["model_name", "pk"])
+                },
+                RouteReference {
+                    location: "index.html:3".to_owned(),
+                    ..reference("post", &["slug"])
+                },
+                RouteReference {
+                    location: "index.html:5".to_owned(),
+                    ..positional_reference("index", 0)
+                },
+                RouteReference {
+                    location: "index.html:5".to_owned(),
+                    ..positional_reference("todos:remove-todo", 1)
+                },
+                RouteReference {
+                    location: "index.html:6".to_owned(),
+                    ..positional_reference("post", 2)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn split_macro_args_nested() {
+        assert_eq!(
+            split_macro_args(r#"urls, "a,b", x = f(1, 2), y = [3, ")"]) rest"#),
+            Some(vec![
+                "urls",
+                r#" "a,b""#,
+                " x = f(1, 2)",
+                r#" y = [3, ")"]"#
+            ])
+        );
+        assert_eq!(split_macro_args("urls, \"index\""), None);
+    }
+
+    #[test]
+    fn check_reference() {
+        async fn handler(_request: Request) -> crate::Result<Response> {
+            unimplemented!()
+        }
+
+        let router = Router::with_urls([
+            Route::with_handler_and_name("/", handler, "index"),
+            Route::with_handler_and_name("/posts/{slug}", handler, "post"),
+        ]);
+
+        assert!(reference("index", &[]).check(&router).is_ok());
+        assert!(reference("post", &["slug"]).check(&router).is_ok());
+        assert_eq!(
+            reference("missing", &[]).check(&router).unwrap_err(),
+            "index.html:1: `missing`: route does not exist"
+        );
+        assert_eq!(
+            reference("post", &[]).check(&router).unwrap_err(),
+            "index.html:1: `post`: route expects 1 parameter(s) (slug), but 0 were given ()"
+        );
+        assert!(positional_reference("post", 1).check(&router).is_ok());
+        assert_eq!(
+            positional_reference("post", 2).check(&router).unwrap_err(),
+            "index.html:1: `post`: route expects 1 parameter(s) (slug), but 2 were given"
+        );
+    }
+}
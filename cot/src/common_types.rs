@@ -338,6 +338,17 @@ impl FromStr for Url {
     }
 }
 
+#[cfg(feature = "fake")]
+impl fake::Dummy<fake::Faker> for Url {
+    fn dummy_with_rng<R: rand::Rng + ?Sized>(_: &fake::Faker, rng: &mut R) -> Self {
+        use fake::Fake;
+
+        let domain: String = fake::faker::internet::en::DomainSuffix().fake_with_rng(rng);
+        let path: String = fake::faker::lorem::en::Word().fake_with_rng(rng);
+        Url::new(format!("https://example.{domain}/{path}")).expect("generated URL should be valid")
+    }
+}
+
 impl Display for Url {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(self.as_str())
@@ -355,7 +366,7 @@ pub struct UrlParseError(url::ParseError);
 
 impl From<UrlParseError> for FormFieldValidationError {
     fn from(error: UrlParseError) -> Self {
-        FormFieldValidationError::from_string(error.to_string())
+        FormFieldValidationError::with_code("invalid_url", error.to_string())
     }
 }
 
@@ -636,7 +647,7 @@ pub struct EmailParseError(email_address::Error);
 
 impl From<EmailParseError> for FormFieldValidationError {
     fn from(error: EmailParseError) -> Self {
-        FormFieldValidationError::from_string(error.to_string())
+        FormFieldValidationError::with_code("invalid_email", error.to_string())
     }
 }
 
@@ -683,6 +694,52 @@ impl FromDbValue for Email {
     }
 }
 
+#[cfg(feature = "db")]
+impl ToDbValue for Option<Email> {
+    fn to_db_value(&self) -> DbValue {
+        self.as_ref().map(|email| email.0.clone().email()).into()
+    }
+}
+
+#[cfg(feature = "db")]
+impl FromDbValue for Option<Email> {
+    #[cfg(feature = "sqlite")]
+    fn from_sqlite(value: SqliteValueRef<'_>) -> cot::db::Result<Self>
+    where
+        Self: Sized,
+    {
+        value
+            .get::<Option<String>>()?
+            .map(Email::new)
+            .transpose()
+            .map_err(cot::db::DatabaseError::value_decode)
+    }
+
+    #[cfg(feature = "postgres")]
+    fn from_postgres(value: PostgresValueRef<'_>) -> cot::db::Result<Self>
+    where
+        Self: Sized,
+    {
+        value
+            .get::<Option<String>>()?
+            .map(Email::new)
+            .transpose()
+            .map_err(cot::db::DatabaseError::value_decode)
+    }
+
+    #[cfg(feature = "mysql")]
+    fn from_mysql(value: MySqlValueRef<'_>) -> cot::db::Result<Self>
+    where
+        Self: Sized,
+    {
+        value
+            .get::<Option<String>>()?
+            .map(Email::new)
+            .transpose()
+            .map_err(cot::db::DatabaseError::value_decode)
+    }
+}
+
 /// Defines the database field type for `Email`.
 ///
 /// Emails are stored as strings with a maximum length of 254 characters,
@@ -692,12 +749,35 @@ impl DatabaseField for Email {
     const TYPE: ColumnType = ColumnType::String(MAX_EMAIL_LENGTH);
 }
 
+#[cfg(feature = "fake")]
+impl fake::Dummy<fake::Faker> for Email {
+    fn dummy_with_rng<R: rand::Rng + ?Sized>(_: &fake::Faker, rng: &mut R) -> Self {
+        use fake::Fake;
+
+        let email: String = fake::faker::internet::en::SafeEmail().fake_with_rng(rng);
+        Email::new(email).expect("generated email address should be valid")
+    }
+}
+
 impl Display for Email {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(self.0.as_str())
     }
 }
 
+impl serde::Serialize for Email {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Email {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let email = String::deserialize(deserializer)?;
+        Email::new(email).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::convert::TryFrom;
@@ -773,6 +853,17 @@ mod tests {
         assert_eq!(email.as_str(), "user@example.com");
     }
 
+    #[test]
+    #[cfg(feature = "json")]
+    fn email_serde() {
+        let email = Email::new("user@example.com").unwrap();
+        let json = serde_json::to_string(&email).unwrap();
+        assert_eq!(json, r#""user@example.com""#);
+        assert_eq!(serde_json::from_str::<Email>(&json).unwrap(), email);
+
+        assert!(serde_json::from_str::<Email>(r#""invalid""#).is_err());
+    }
+
     #[test]
     fn askama_renders_email_field() {
         #[derive(Template)]
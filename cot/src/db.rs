@@ -3,6 +3,9 @@
 //! This module contains the database connection structure, the model trait, and
 //! the error types that can occur when interacting with the database.
 
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod changes;
 mod fields;
 #[cfg(feature = "mysql")]
 pub mod impl_mysql;
@@ -12,6 +15,7 @@ pub mod impl_postgres;
 pub mod impl_sqlite;
 pub mod migrations;
 pub mod query;
+mod read_only;
 mod relations;
 mod sea_query_db;
 
@@ -21,21 +25,27 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use cot_core::error::impl_into_cot_error;
 pub use cot_macros::{model, query};
 use derive_more::{Debug, Deref, Display};
+use http::StatusCode;
 #[cfg(test)]
 use mockall::automock;
 use query::Query;
-pub use relations::{ForeignKey, ForeignKeyOnDeletePolicy, ForeignKeyOnUpdatePolicy};
+pub use relations::{
+    ForeignKey, ForeignKeyOnDeletePolicy, ForeignKeyOnUpdatePolicy, ManyToMany, ManyToManyJoin,
+    ManyToManyRelation,
+};
 use sea_query::{
     ColumnRef, Iden, IntoColumnRef, OnConflict, ReturningClause, SchemaStatementBuilder, SimpleExpr,
 };
 use sea_query_binder::{SqlxBinder, SqlxValues};
+use serde::{Deserialize, Serialize};
 use sqlx::{Type, TypeInfo};
 use thiserror::Error;
-use tracing::{Instrument, Level, span, trace};
+use tracing::{Instrument, Level, error, span, trace};
 
+use crate::config::DatabaseConfig;
+use crate::db::changes::ChangeKind;
 #[cfg(feature = "mysql")]
 use crate::db::impl_mysql::{DatabaseMySql, MySqlRow, MySqlValueRef};
 #[cfg(feature = "postgres")]
@@ -43,6 +53,8 @@ use crate::db::impl_postgres::{DatabasePostgres, PostgresRow, PostgresValueRef};
 #[cfg(feature = "sqlite")]
 use crate::db::impl_sqlite::{DatabaseSqlite, SqliteRow, SqliteValueRef};
 use crate::db::migrations::ColumnTypeMapper;
+use crate::deadline::Deadline;
+use crate::validation::{Validate, ValidationErrors};
 
 const ERROR_PREFIX: &str = "database error:";
 /// An error that can occur when interacting with the database.
@@ -50,8 +62,11 @@ const ERROR_PREFIX: &str = "database error:";
 #[non_exhaustive]
 pub enum DatabaseError {
     /// Database engine error.
+    ///
+    /// The errors that have a more specific variant (such as
+    /// [`DatabaseError::UniqueViolation`]) are never returned as this one.
     #[error("{ERROR_PREFIX} database engine error: {0}")]
-    DatabaseEngineError(#[from] sqlx::Error),
+    DatabaseEngineError(sqlx::Error),
     /// Error when building query.
     #[error("{ERROR_PREFIX} error when building query: {0}")]
     QueryBuildingError(#[from] sea_query::error::Error),
@@ -82,9 +97,71 @@ pub enum DatabaseError {
     /// was not found.
     #[error("{ERROR_PREFIX} error retrieving a Foreign Key from the database: record not found")]
     ForeignKeyNotFound,
+    /// A relation of a model instance was accessed before the instance was
+    /// saved to the database, so its primary key is not known yet.
+    #[error("{ERROR_PREFIX} the model instance has not been saved to the database yet")]
+    UnsavedModel,
+    /// The model instance is not valid, as reported by its
+    /// [`Validate`] implementation.
+    ///
+    /// This is returned by [`Model::validate_and_save`].
+    #[error("{ERROR_PREFIX} {0}")]
+    Validation(ValidationErrors),
     /// Error when a unique constraint is violated in the database.
-    #[error("{ERROR_PREFIX} unique constraint violation")]
-    UniqueViolation,
+    ///
+    /// This is typically used to report a form error (e.g. "this username is
+    /// already taken") instead of failing the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, Database, DatabaseError, Model, model};
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     #[model(unique)]
+    ///     username: String,
+    /// }
+    ///
+    /// /// Returns an error message to display in the form, if any.
+    /// async fn register(db: &Database, user: &mut User) -> cot::Result<Option<String>> {
+    ///     match user.insert(db).await {
+    ///         Ok(()) => Ok(None),
+    ///         Err(DatabaseError::UniqueViolation { .. }) => {
+    ///             Ok(Some("This username is already taken".to_owned()))
+    ///         }
+    ///         Err(error) => Err(error.into()),
+    ///     }
+    /// }
+    /// ```
+    #[error(
+        "{ERROR_PREFIX} unique constraint violation{}",
+        display_constraint(.constraint.as_deref())
+    )]
+    UniqueViolation {
+        /// The name of the violated constraint, if reported by the database.
+        constraint: Option<String>,
+    },
+    /// Error when a foreign key constraint is violated in the database, for
+    /// instance when deleting a record that is still referenced by another
+    /// one.
+    #[error(
+        "{ERROR_PREFIX} foreign key constraint violation{}",
+        display_constraint(.constraint.as_deref())
+    )]
+    ForeignKeyViolation {
+        /// The name of the violated constraint, if reported by the database.
+        constraint: Option<String>,
+    },
+    /// Error when a `NULL` value is inserted into a column that doesn't allow
+    /// it.
+    #[error("{ERROR_PREFIX} not null constraint violation")]
+    NotNullViolation,
+    /// Timed out while waiting for a database connection to become available.
+    #[error("{ERROR_PREFIX} timed out while waiting for a database connection")]
+    Timeout,
     /// Single model has more fields than database parameter limit.
     #[error(
         "{ERROR_PREFIX} model has {field_count} fields which exceeds the database parameter limit \
@@ -115,8 +192,71 @@ pub enum DatabaseError {
         /// The actual number of rows returned.
         actual: usize,
     },
+    /// The query did not finish before the deadline of the current request.
+    ///
+    /// See the [`deadline`](crate::deadline) module for more details.
+    #[error("{ERROR_PREFIX} the query did not finish before the request deadline")]
+    DeadlineExceeded,
+    /// The transaction handle was used after the transaction was committed or
+    /// rolled back.
+    ///
+    /// See [`Database::transaction`] for more details.
+    #[error("{ERROR_PREFIX} the transaction has already been committed or rolled back")]
+    TransactionFinished,
+    /// Attempted to write to the database while it is in read-only mode.
+    ///
+    /// This is converted into a `503 Service Unavailable` response. See
+    /// [`Database::set_read_only`] for more details.
+    #[error("{ERROR_PREFIX} the database is in read-only mode; cannot write to table `{table}`")]
+    ReadOnly {
+        /// The name of the table that was attempted to be written to.
+        table: String,
+    },
+}
+
+impl From<DatabaseError> for crate::Error {
+    fn from(error: DatabaseError) -> Self {
+        let status = match &error {
+            DatabaseError::ReadOnly { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        crate::Error::with_status(error, status)
+    }
+}
+
+impl From<sqlx::Error> for DatabaseError {
+    fn from(error: sqlx::Error) -> Self {
+        if matches!(error, sqlx::Error::PoolTimedOut) {
+            return Self::Timeout;
+        }
+
+        let Some(database_error) = error.as_database_error() else {
+            return Self::DatabaseEngineError(error);
+        };
+        let constraint = database_error.constraint().map(ToOwned::to_owned);
+        match database_error.kind() {
+            sqlx::error::ErrorKind::UniqueViolation => Self::UniqueViolation { constraint },
+            sqlx::error::ErrorKind::ForeignKeyViolation => Self::ForeignKeyViolation { constraint },
+            sqlx::error::ErrorKind::NotNullViolation => Self::NotNullViolation,
+            // SQLite reports the violations of `RESTRICT` foreign keys with
+            // the generic `SQLITE_CONSTRAINT_TRIGGER` code
+            _ if database_error.code().as_deref() == Some(SQLITE_CONSTRAINT_TRIGGER)
+                && database_error.message().starts_with("FOREIGN KEY") =>
+            {
+                Self::ForeignKeyViolation { constraint }
+            }
+            _ => Self::DatabaseEngineError(error),
+        }
+    }
+}
+
+const SQLITE_CONSTRAINT_TRIGGER: &str = "1811";
+
+fn display_constraint(constraint: Option<&str>) -> String {
+    constraint
+        .map(|constraint| format!(" (constraint `{constraint}`)"))
+        .unwrap_or_default()
 }
-impl_into_cot_error!(DatabaseError, INTERNAL_SERVER_ERROR);
 
 impl DatabaseError {
     /// Creates a new database error from a value decode error.
@@ -250,6 +390,63 @@ pub trait Model: Sized + Send + 'static {
         Ok(())
     }
 
+    /// Validate the model instance and save it to the database.
+    ///
+    /// This is the same as [`Self::save`], except that the model instance is
+    /// validated with its [`Validate`]
+    /// implementation first, and is not saved if it's not valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::Validation`] with the validation errors if the
+    /// model instance is not valid.
+    ///
+    /// This method can return an error if the model instance could not be
+    /// saved to the database, for instance because the migrations haven't
+    /// been applied, or there was a problem with the database connection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, Database, Model, model};
+    /// use cot::form::FormFieldValidationError;
+    /// use cot::validation::{Validate, ValidationErrors};
+    ///
+    /// #[model]
+    /// struct Event {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     capacity: u32,
+    ///     attendees: u32,
+    /// }
+    ///
+    /// impl Validate for Event {
+    ///     fn validate(&self) -> Result<(), ValidationErrors> {
+    ///         let mut errors = ValidationErrors::new();
+    ///         if self.attendees > self.capacity {
+    ///             errors.add_field_error(
+    ///                 "attendees",
+    ///                 FormFieldValidationError::maximum_value_exceeded(self.capacity),
+    ///             );
+    ///         }
+    ///         errors.into_result()
+    ///     }
+    /// }
+    ///
+    /// async fn add_attendee(db: &Database, event: &mut Event) -> cot::Result<()> {
+    ///     event.attendees += 1;
+    ///     event.validate_and_save(db).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn validate_and_save<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()>
+    where
+        Self: Validate,
+    {
+        self.validate().map_err(DatabaseError::Validation)?;
+        self.save(db).await
+    }
+
     /// Insert the model instance to the database.
     ///
     /// # Errors
@@ -414,6 +611,12 @@ impl Column {
     pub const fn new(name: Identifier) -> Self {
         Self { name }
     }
+
+    /// Returns the name of the column.
+    #[must_use]
+    pub const fn name(&self) -> Identifier {
+        self.name
+    }
 }
 
 /// A marker trait that denotes that a type can be used as a primary key in a
@@ -793,6 +996,13 @@ pub trait SqlxValueRef<'r>: Sized {
 #[derive(Debug, Clone)]
 pub struct Database {
     inner: Arc<DatabaseImpl>,
+    #[cfg(feature = "cache")]
+    query_cache: Option<crate::cache::Cache>,
+    #[cfg(feature = "cache")]
+    pending_invalidations: Option<Arc<cache::PendingInvalidations>>,
+    change_notifier: Option<crate::notify::Notifier>,
+    pending_changes: Option<Arc<changes::PendingChanges>>,
+    read_only: Arc<read_only::ReadOnlyMode>,
 }
 
 #[derive(Debug)]
@@ -831,29 +1041,98 @@ impl Database {
     /// }
     /// ```
     pub async fn new<T: Into<String>>(url: T) -> Result<Self> {
-        let url = url.into();
+        Self::connect(url.into(), &DatabaseConfig::default()).await
+    }
 
+    /// Creates a new database connection using the given configuration.
+    ///
+    /// Unlike [`Database::new`], this also applies the connection pool options
+    /// from the configuration, such as the maximum number of connections or
+    /// the statement timeout.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the connection to the database could
+    /// not be established.
+    ///
+    /// This method can return an error if the database URL is invalid.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the database URL is not set or is not
+    /// supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::DatabaseConfig;
+    /// use cot::db::Database;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let config = DatabaseConfig::builder()
+    ///         .url("sqlite::memory:")
+    ///         .max_connections(1)
+    ///         .acquire_timeout(Duration::from_secs(5))
+    ///         .build();
+    ///     let db = Database::with_config(&config).await.unwrap();
+    /// }
+    /// ```
+    pub async fn with_config(config: &DatabaseConfig) -> Result<Self> {
+        let url = config
+            .url
+            .as_ref()
+            .expect("Database URL is required")
+            .as_str()
+            .to_owned();
+        Self::connect(url, config).await
+    }
+
+    async fn connect(url: String, config: &DatabaseConfig) -> Result<Self> {
         #[cfg(feature = "sqlite")]
         if url.starts_with("sqlite:") {
-            let inner = DatabaseSqlite::new(&url).await?;
+            let inner = DatabaseSqlite::new(&url, config).await?;
             return Ok(Self {
                 inner: Arc::new(DatabaseImpl::Sqlite(inner)),
+                #[cfg(feature = "cache")]
+                query_cache: None,
+                #[cfg(feature = "cache")]
+                pending_invalidations: None,
+                change_notifier: None,
+                pending_changes: None,
+                read_only: Arc::new(read_only::ReadOnlyMode::new(config)),
             });
         }
 
         #[cfg(feature = "postgres")]
         if url.starts_with("postgresql:") {
-            let inner = DatabasePostgres::new(&url).await?;
+            let inner = DatabasePostgres::new(&url, config).await?;
             return Ok(Self {
                 inner: Arc::new(DatabaseImpl::Postgres(inner)),
+                #[cfg(feature = "cache")]
+                query_cache: None,
+                #[cfg(feature = "cache")]
+                pending_invalidations: None,
+                change_notifier: None,
+                pending_changes: None,
+                read_only: Arc::new(read_only::ReadOnlyMode::new(config)),
             });
         }
 
         #[cfg(feature = "mysql")]
         if url.starts_with("mysql:") {
-            let inner = DatabaseMySql::new(&url).await?;
+            let inner = DatabaseMySql::new(&url, config).await?;
             return Ok(Self {
                 inner: Arc::new(DatabaseImpl::MySql(inner)),
+                #[cfg(feature = "cache")]
+                query_cache: None,
+                #[cfg(feature = "cache")]
+                pending_invalidations: None,
+                change_notifier: None,
+                pending_changes: None,
+                read_only: Arc::new(read_only::ReadOnlyMode::new(config)),
             });
         }
 
@@ -893,6 +1172,154 @@ impl Database {
         }
     }
 
+    /// Runs the given closure inside a database transaction.
+    ///
+    /// The closure receives a [`Database`] handle bound to the transaction.
+    /// It can be used anywhere a regular database can, so all the queries
+    /// made with [`Model::save`], [`query!`], or [`Database::raw`] through it
+    /// are a part of the transaction. If the closure returns `Ok`, the
+    /// transaction is committed; if it returns an error (or panics), the
+    /// transaction is rolled back and none of its changes are persisted.
+    ///
+    /// Calling this method on a handle that is already bound to a transaction
+    /// doesn't start a new one; the closure is run as a part of the outer
+    /// transaction instead.
+    ///
+    /// The handle shouldn't be used after the closure finishes; doing so
+    /// returns [`DatabaseError::TransactionFinished`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by the closure, after rolling back the
+    /// transaction.
+    ///
+    /// This method can return an error if the transaction could not be
+    /// started or committed, for instance because the connection to the
+    /// database was lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, Database, Model, model};
+    ///
+    /// #[model]
+    /// struct Account {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     balance: i64,
+    /// }
+    ///
+    /// async fn transfer(db: &Database, from: &mut Account, to: &mut Account) -> cot::Result<()> {
+    ///     db.transaction(async |tx| {
+    ///         from.balance -= 100;
+    ///         from.save(&tx).await?;
+    ///         to.balance += 100;
+    ///         to.save(&tx).await?;
+    ///
+    ///         Ok(())
+    ///     })
+    ///     .await
+    /// }
+    /// ```
+    pub async fn transaction<F, R, E>(&self, f: F) -> std::result::Result<R, E>
+    where
+        F: AsyncFnOnce(Database) -> std::result::Result<R, E>,
+        E: From<DatabaseError>,
+    {
+        if self.in_transaction() {
+            return f(self.clone()).await;
+        }
+
+        let transaction = self.begin().await?;
+        match f(transaction.clone()).await {
+            Ok(result) => {
+                transaction.commit().await?;
+                Ok(result)
+            }
+            Err(error) => {
+                if let Err(rollback_error) = transaction.rollback().await {
+                    error!("failed to roll back the transaction: {rollback_error}");
+                }
+                Err(error)
+            }
+        }
+    }
+
+    fn in_transaction(&self) -> bool {
+        match &*self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.in_transaction(),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.in_transaction(),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.in_transaction(),
+        }
+    }
+
+    async fn begin(&self) -> Result<Self> {
+        let inner = match &*self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => DatabaseImpl::Sqlite(inner.begin().await?),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => DatabaseImpl::Postgres(inner.begin().await?),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => DatabaseImpl::MySql(inner.begin().await?),
+        };
+
+        Ok(Self {
+            inner: Arc::new(inner),
+            // the cache is bypassed inside the transaction, so that the
+            // uncommitted changes are never visible outside of it
+            #[cfg(feature = "cache")]
+            query_cache: None,
+            #[cfg(feature = "cache")]
+            pending_invalidations: match &self.pending_invalidations {
+                Some(pending) => Some(Arc::clone(pending)),
+                None => self
+                    .query_cache
+                    .clone()
+                    .map(|cache| Arc::new(cache::PendingInvalidations::new(cache))),
+            },
+            // the changes are only published once the transaction is committed
+            change_notifier: None,
+            pending_changes: match &self.pending_changes {
+                Some(pending) => Some(Arc::clone(pending)),
+                None => self
+                    .change_notifier
+                    .clone()
+                    .map(|notifier| Arc::new(changes::PendingChanges::new(notifier))),
+            },
+            read_only: Arc::clone(&self.read_only),
+        })
+    }
+
+    async fn commit(&self) -> Result<()> {
+        match &*self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.commit().await?,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.commit().await?,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.commit().await?,
+        }
+
+        #[cfg(feature = "cache")]
+        self.invalidate_pending_query_cache().await;
+        self.publish_pending_changes();
+        Ok(())
+    }
+
+    async fn rollback(&self) -> Result<()> {
+        match &*self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(inner) => inner.rollback().await,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(inner) => inner.rollback().await,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(inner) => inner.rollback().await,
+        }
+    }
+
     /// Inserts a new row into the database.
     ///
     /// # Errors
@@ -901,11 +1328,16 @@ impl Database {
     /// the database, for instance because the migrations haven't been
     /// applied, or there was a problem with the database connection.
     pub async fn insert<T: Model>(&self, data: &mut T) -> Result<()> {
+        self.check_writable(T::TABLE_NAME.as_str())?;
         let span = span!(Level::TRACE, "insert", table = %T::TABLE_NAME);
 
         Self::insert_or_update_impl(self, data, false)
             .instrument(span)
-            .await
+            .await?;
+        #[cfg(feature = "cache")]
+        self.invalidate_query_cache(T::TABLE_NAME.as_str()).await;
+        self.notify_change::<T>(ChangeKind::Saved, data.primary_key());
+        Ok(())
     }
 
     /// Inserts a new row into the database, or updates it if a row with the
@@ -917,6 +1349,7 @@ impl Database {
     /// the database, for instance because the migrations haven't been
     /// applied, or there was a problem with the database connection.
     pub async fn insert_or_update<T: Model>(&self, data: &mut T) -> Result<()> {
+        self.check_writable(T::TABLE_NAME.as_str())?;
         let span = span!(
             Level::TRACE,
             "insert_or_update",
@@ -925,7 +1358,11 @@ impl Database {
 
         Self::insert_or_update_impl(self, data, true)
             .instrument(span)
-            .await
+            .await?;
+        #[cfg(feature = "cache")]
+        self.invalidate_query_cache(T::TABLE_NAME.as_str()).await;
+        self.notify_change::<T>(ChangeKind::Saved, data.primary_key());
+        Ok(())
     }
 
     async fn insert_or_update_impl<T: Model>(&self, data: &mut T, update: bool) -> Result<()> {
@@ -1024,6 +1461,7 @@ impl Database {
     /// This method can return an error if the row with the given primary key
     /// could not be found in the database.
     pub async fn update<T: Model>(&self, data: &mut T) -> Result<()> {
+        self.check_writable(T::TABLE_NAME.as_str())?;
         let span = span!(
             Level::TRACE,
             "update",
@@ -1031,7 +1469,11 @@ impl Database {
             primary_key = ?data.primary_key().to_db_field_value(),
         );
 
-        Self::update_impl(self, data).instrument(span).await
+        Self::update_impl(self, data).instrument(span).await?;
+        #[cfg(feature = "cache")]
+        self.invalidate_query_cache(T::TABLE_NAME.as_str()).await;
+        self.notify_change::<T>(ChangeKind::Saved, data.primary_key());
+        Ok(())
     }
 
     async fn update_impl<T: Model>(&self, data: &mut T) -> Result<()> {
@@ -1086,11 +1528,18 @@ impl Database {
     /// the database, for instance because the migrations haven't been
     /// applied, or there was a problem with the database connection.
     pub async fn bulk_insert<T: Model>(&self, data: &mut [T]) -> Result<()> {
+        self.check_writable(T::TABLE_NAME.as_str())?;
         let span = span!(Level::TRACE, "bulk_insert", table = %T::TABLE_NAME, count = data.len());
 
         Self::bulk_insert_impl(self, data, false)
             .instrument(span)
-            .await
+            .await?;
+        #[cfg(feature = "cache")]
+        self.invalidate_query_cache(T::TABLE_NAME.as_str()).await;
+        for instance in &*data {
+            self.notify_change::<T>(ChangeKind::Saved, instance.primary_key());
+        }
+        Ok(())
     }
 
     /// Bulk inserts multiple rows into the database, or updates them if they
@@ -1102,6 +1551,7 @@ impl Database {
     /// the database, for instance because the migrations haven't been
     /// applied, or there was a problem with the database connection.
     pub async fn bulk_insert_or_update<T: Model>(&self, data: &mut [T]) -> Result<()> {
+        self.check_writable(T::TABLE_NAME.as_str())?;
         let span = span!(
             Level::TRACE,
             "bulk_insert_or_update",
@@ -1111,16 +1561,27 @@ impl Database {
 
         Self::bulk_insert_impl(self, data, true)
             .instrument(span)
-            .await
+            .await?;
+        #[cfg(feature = "cache")]
+        self.invalidate_query_cache(T::TABLE_NAME.as_str()).await;
+        for instance in &*data {
+            self.notify_change::<T>(ChangeKind::Saved, instance.primary_key());
+        }
+        Ok(())
     }
 
     async fn bulk_insert_impl<T: Model>(&self, data: &mut [T], update: bool) -> Result<()> {
-        // TODO: add transactions when implemented
-
         if data.is_empty() {
             return Ok(());
         }
 
+        // the rows can be split into multiple statements, which need to either
+        // all succeed or all fail
+        self.transaction(async |tx| tx.bulk_insert_batches(data, update).await)
+            .await
+    }
+
+    async fn bulk_insert_batches<T: Model>(&self, data: &mut [T], update: bool) -> Result<()> {
         let max_params = match &*self.inner {
             // https://sqlite.org/limits.html#max_variable_number
             // Assuming SQLite > 3.32.0 (2020-05-22)
@@ -1180,11 +1641,9 @@ impl Database {
             });
         }
 
-        let batch_size = if num_value_fields > 0 {
-            max_params / num_value_fields
-        } else {
-            return Err(DatabaseError::BulkInsertNoValueColumns);
-        };
+        let batch_size = max_params
+            .checked_div(num_value_fields)
+            .ok_or(DatabaseError::BulkInsertNoValueColumns)?;
 
         for chunk in data.chunks_mut(batch_size) {
             self.bulk_insert_chunk(
@@ -1331,6 +1790,7 @@ impl Database {
         let mut select = sea_query::Query::select();
         select.columns(columns_to_get).from(T::TABLE_NAME);
         query.add_filter_to_statement(&mut select);
+        query.add_order_to_statement(&mut select);
         query.add_limit_to_statement(&mut select);
         query.add_offset_to_statement(&mut select);
 
@@ -1402,11 +1862,25 @@ impl Database {
     ///
     /// Can return an error if the database connection is lost.
     pub async fn delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        self.check_writable(T::TABLE_NAME.as_str())?;
+        // the primary keys are only needed if someone listens to the changes
+        let deleted_primary_keys = if self.has_change_subscribers::<T>() {
+            self.primary_keys(query).await?
+        } else {
+            Vec::new()
+        };
+
         let mut delete = sea_query::Query::delete();
         delete.from_table(T::TABLE_NAME);
         query.add_filter_to_statement(&mut delete);
 
-        self.execute_statement(&delete).await
+        let result = self.execute_statement(&delete).await?;
+        #[cfg(feature = "cache")]
+        self.invalidate_query_cache(T::TABLE_NAME.as_str()).await;
+        for primary_key in deleted_primary_keys {
+            self.notify_change_value::<T>(ChangeKind::Deleted, primary_key);
+        }
+        Ok(result)
     }
 
     /// Executes a raw SQL query.
@@ -1468,34 +1942,42 @@ impl Database {
             .collect::<Vec<_>>();
         let values = SqlxValues(sea_query::Values(values));
 
-        let result = match &*self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(inner) => inner.raw_with(query, values).await?,
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(inner) => inner.raw_with(query, values).await?,
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(inner) => inner.raw_with(query, values).await?,
-        };
+        with_deadline(async {
+            let result = match &*self.inner {
+                #[cfg(feature = "sqlite")]
+                DatabaseImpl::Sqlite(inner) => inner.raw_with(query, values).await?,
+                #[cfg(feature = "postgres")]
+                DatabaseImpl::Postgres(inner) => inner.raw_with(query, values).await?,
+                #[cfg(feature = "mysql")]
+                DatabaseImpl::MySql(inner) => inner.raw_with(query, values).await?,
+            };
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     async fn fetch_option<T>(&self, statement: &T) -> Result<Option<Row>>
     where
         T: SqlxBinder + Send + Sync,
     {
-        let result = match &*self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(inner) => inner.fetch_option(statement).await?.map(Row::Sqlite),
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(inner) => {
-                inner.fetch_option(statement).await?.map(Row::Postgres)
-            }
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(inner) => inner.fetch_option(statement).await?.map(Row::MySql),
-        };
+        with_deadline(async {
+            let result = match &*self.inner {
+                #[cfg(feature = "sqlite")]
+                DatabaseImpl::Sqlite(inner) => {
+                    inner.fetch_option(statement).await?.map(Row::Sqlite)
+                }
+                #[cfg(feature = "postgres")]
+                DatabaseImpl::Postgres(inner) => {
+                    inner.fetch_option(statement).await?.map(Row::Postgres)
+                }
+                #[cfg(feature = "mysql")]
+                DatabaseImpl::MySql(inner) => inner.fetch_option(statement).await?.map(Row::MySql),
+            };
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     fn supports_returning(&self) -> bool {
@@ -1509,70 +1991,117 @@ impl Database {
         }
     }
 
+    fn supports_altering_columns(&self) -> bool {
+        match &*self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(_) => false,
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(_) => true,
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(_) => true,
+        }
+    }
+
+    /// Returns the statement that sets the lock timeout for the current
+    /// transaction, along with the statement that resets it afterwards if
+    /// it's not reset automatically, or `None` if the database doesn't
+    /// support lock timeouts.
+    fn lock_timeout_statements(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Option<(String, Option<&'static str>)> {
+        match &*self.inner {
+            #[cfg(feature = "sqlite")]
+            DatabaseImpl::Sqlite(_) => DatabaseSqlite::lock_timeout_statements(timeout),
+            #[cfg(feature = "postgres")]
+            DatabaseImpl::Postgres(_) => DatabasePostgres::lock_timeout_statements(timeout),
+            #[cfg(feature = "mysql")]
+            DatabaseImpl::MySql(_) => DatabaseMySql::lock_timeout_statements(timeout),
+        }
+    }
+
     async fn fetch_all<T>(&self, statement: &T) -> Result<Vec<Row>>
     where
         T: SqlxBinder + Send + Sync,
     {
-        let result = match &*self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(inner) => inner
-                .fetch_all(statement)
-                .await?
-                .into_iter()
-                .map(Row::Sqlite)
-                .collect(),
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(inner) => inner
-                .fetch_all(statement)
-                .await?
-                .into_iter()
-                .map(Row::Postgres)
-                .collect(),
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(inner) => inner
-                .fetch_all(statement)
-                .await?
-                .into_iter()
-                .map(Row::MySql)
-                .collect(),
-        };
+        with_deadline(async {
+            let result = match &*self.inner {
+                #[cfg(feature = "sqlite")]
+                DatabaseImpl::Sqlite(inner) => inner
+                    .fetch_all(statement)
+                    .await?
+                    .into_iter()
+                    .map(Row::Sqlite)
+                    .collect(),
+                #[cfg(feature = "postgres")]
+                DatabaseImpl::Postgres(inner) => inner
+                    .fetch_all(statement)
+                    .await?
+                    .into_iter()
+                    .map(Row::Postgres)
+                    .collect(),
+                #[cfg(feature = "mysql")]
+                DatabaseImpl::MySql(inner) => inner
+                    .fetch_all(statement)
+                    .await?
+                    .into_iter()
+                    .map(Row::MySql)
+                    .collect(),
+            };
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     async fn execute_statement<T>(&self, statement: &T) -> Result<StatementResult>
     where
         T: SqlxBinder + Send + Sync,
     {
-        let result = match &*self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(inner) => inner.execute_statement(statement).await?,
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(inner) => inner.execute_statement(statement).await?,
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(inner) => inner.execute_statement(statement).await?,
-        };
+        with_deadline(async {
+            let result = match &*self.inner {
+                #[cfg(feature = "sqlite")]
+                DatabaseImpl::Sqlite(inner) => inner.execute_statement(statement).await?,
+                #[cfg(feature = "postgres")]
+                DatabaseImpl::Postgres(inner) => inner.execute_statement(statement).await?,
+                #[cfg(feature = "mysql")]
+                DatabaseImpl::MySql(inner) => inner.execute_statement(statement).await?,
+            };
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     async fn execute_schema<T: SchemaStatementBuilder>(
         &self,
         statement: T,
     ) -> Result<StatementResult> {
-        let result = match &*self.inner {
-            #[cfg(feature = "sqlite")]
-            DatabaseImpl::Sqlite(inner) => inner.execute_schema(statement).await?,
-            #[cfg(feature = "postgres")]
-            DatabaseImpl::Postgres(inner) => inner.execute_schema(statement).await?,
-            #[cfg(feature = "mysql")]
-            DatabaseImpl::MySql(inner) => inner.execute_schema(statement).await?,
-        };
+        with_deadline(async {
+            let result = match &*self.inner {
+                #[cfg(feature = "sqlite")]
+                DatabaseImpl::Sqlite(inner) => inner.execute_schema(statement).await?,
+                #[cfg(feature = "postgres")]
+                DatabaseImpl::Postgres(inner) => inner.execute_schema(statement).await?,
+                #[cfg(feature = "mysql")]
+                DatabaseImpl::MySql(inner) => inner.execute_schema(statement).await?,
+            };
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 }
 
+/// Runs the given database operation, failing if it doesn't finish before
+/// the deadline of the current request.
+async fn with_deadline<T>(operation: impl Future<Output = Result<T>>) -> Result<T> {
+    Deadline::current()
+        .run(operation)
+        .await
+        .map_err(|_| DatabaseError::DeadlineExceeded)?
+}
+
 impl ColumnTypeMapper for Database {
     fn sea_query_column_type_for(&self, column_type: ColumnType) -> sea_query::ColumnType {
         match &*self.inner {
@@ -1808,7 +2337,7 @@ pub struct RowsNum(pub u64);
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum Auto<T> {
     /// A fixed value.
     Fixed(T),
@@ -1970,6 +2499,24 @@ impl<const LIMIT: u32> PartialEq<LimitedString<LIMIT>> for String {
     }
 }
 
+impl<const LIMIT: u32> Serialize for LimitedString<LIMIT> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, const LIMIT: u32> Deserialize<'de> for LimitedString<LIMIT> {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::new(value).map_err(serde::de::Error::custom)
+    }
+}
+
 /// An error returned by [`LimitedString::new`] when the string is longer than
 /// the specified limit.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Error)]
@@ -2008,8 +2555,8 @@ impl<const LIMIT: u32> LimitedString<LIMIT> {
 
 #[cfg(feature = "fake")]
 impl<const LIMIT: u32> fake::Dummy<usize> for LimitedString<LIMIT> {
-    fn dummy_with_rng<R: fake::rand::Rng + ?Sized>(len: &usize, rng: &mut R) -> Self {
-        use fake::rand::Rng;
+    fn dummy_with_rng<R: rand::Rng + ?Sized>(len: &usize, rng: &mut R) -> Self {
+        use rand::Rng;
 
         assert!(
             *len <= LIMIT as usize,
@@ -2021,7 +2568,7 @@ impl<const LIMIT: u32> fake::Dummy<usize> for LimitedString<LIMIT> {
         );
 
         let str: String = rng
-            .sample_iter(&fake::rand::distr::Alphanumeric)
+            .sample_iter(&rand::distr::Alphanumeric)
             .take(*len)
             .map(char::from)
             .collect();
@@ -2031,7 +2578,7 @@ impl<const LIMIT: u32> fake::Dummy<usize> for LimitedString<LIMIT> {
 
 #[cfg(feature = "fake")]
 impl<const LIMIT: u32> fake::Dummy<fake::Faker> for LimitedString<LIMIT> {
-    fn dummy_with_rng<R: fake::rand::Rng + ?Sized>(_: &fake::Faker, rng: &mut R) -> Self {
+    fn dummy_with_rng<R: rand::Rng + ?Sized>(_: &fake::Faker, rng: &mut R) -> Self {
         use fake::Fake;
 
         let len: usize = (0..LIMIT as usize).fake_with_rng(rng);
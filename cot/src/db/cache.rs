@@ -0,0 +1,327 @@
+//! Caching of query results.
+//!
+//! Query results can be cached by calling [`Query::cached`] and executing the
+//! returned [`CachedQuery`] against a [`Database`] that has a cache attached
+//! with [`Database::with_query_cache`]. When the project is bootstrapped with
+//! both the database and the cache enabled, the project's cache is attached
+//! to the database automatically.
+//!
+//! Cached results are invalidated whenever a model of the same type is
+//! inserted, updated, or deleted through the [`Database`]. This makes the
+//! cache a good fit for read-heavy reference data, such as categories or
+//! settings tables. Changes made with raw SQL queries are not detected, so the
+//! cached results are only refreshed after they expire.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use cot::cache::Cache;
+//! use cot::cache::store::memory::Memory;
+//! use cot::config::Timeout;
+//! use cot::db::{Auto, Database, Model, model};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[model]
+//! #[derive(Serialize, Deserialize)]
+//! struct Category {
+//!     #[model(primary_key)]
+//!     id: Auto<i32>,
+//!     name: String,
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> cot::Result<()> {
+//! let cache = Cache::new(Memory::new(), None, Timeout::Never);
+//! let db = Database::new("sqlite::memory:")
+//!     .await?
+//!     .with_query_cache(cache);
+//! # db.raw(&format!(
+//! #     "CREATE TABLE {} (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT)",
+//! #     Category::TABLE_NAME
+//! # ))
+//! # .await?;
+//!
+//! // executes the query and stores the results in the cache
+//! let categories = Category::objects()
+//!     .cached(Timeout::After(Duration::from_secs(60)))
+//!     .all(&db)
+//!     .await?;
+//! assert!(categories.is_empty());
+//!
+//! // invalidates the cached results
+//! let mut category = Category {
+//!     id: Auto::auto(),
+//!     name: "Books".to_owned(),
+//! };
+//! category.save(&db).await?;
+//!
+//! let categories = Category::objects()
+//!     .cached(Timeout::After(Duration::from_secs(60)))
+//!     .all(&db)
+//!     .await?;
+//! assert_eq!(categories.len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Mutex;
+
+use derive_more::with_trait::Debug;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use crate::cache::Cache;
+use crate::config::Timeout;
+use crate::db::query::Query;
+use crate::db::{Database, Model};
+
+const KEY_PREFIX: &str = "cot:query_cache";
+
+/// A query whose results are stored in the cache.
+///
+/// This is returned by [`Query::cached`]. See the [module
+/// documentation](self) for more details.
+pub struct CachedQuery<T> {
+    query: Query<T>,
+    expiry: Timeout,
+}
+
+// manual implementation to avoid `T: Debug` in the trait bounds
+impl<T> Debug for CachedQuery<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedQuery")
+            .field("query", &self.query)
+            .field("expiry", &self.expiry)
+            .finish()
+    }
+}
+
+impl<T: Model + Serialize + DeserializeOwned + Sync> CachedQuery<T> {
+    pub(super) fn new(query: Query<T>, expiry: Timeout) -> Self {
+        Self { query, expiry }
+    }
+
+    /// Execute the query and return all results, using the cached results if
+    /// they are available.
+    ///
+    /// If the database doesn't have a cache attached, this is equivalent to
+    /// [`Query::all`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if there was a problem
+    /// accessing the cache.
+    pub async fn all(&self, db: &Database) -> cot::Result<Vec<T>> {
+        let Some(cache) = &db.query_cache else {
+            return Ok(self.query.all(db).await?);
+        };
+
+        let key = self.cache_key(cache, "all").await?;
+        if let Some(result) = cache.get(&key).await? {
+            return Ok(result);
+        }
+
+        let result = self.query.all(db).await?;
+        cache.insert_expiring(key, &result, self.expiry).await?;
+        Ok(result)
+    }
+
+    /// Execute the query and return the first result, using the cached result
+    /// if it is available.
+    ///
+    /// If the database doesn't have a cache attached, this is equivalent to
+    /// [`Query::get`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or if there was a problem
+    /// accessing the cache.
+    pub async fn get(&self, db: &Database) -> cot::Result<Option<T>> {
+        let Some(cache) = &db.query_cache else {
+            return Ok(self.query.get(db).await?);
+        };
+
+        let key = self.cache_key(cache, "get").await?;
+        if let Some(result) = cache.get(&key).await? {
+            return Ok(result);
+        }
+
+        let result = self.query.get(db).await?;
+        cache.insert_expiring(key, &result, self.expiry).await?;
+        Ok(result)
+    }
+
+    async fn cache_key(&self, cache: &Cache, operation: &str) -> cot::Result<String> {
+        let generation = generation(cache, T::TABLE_NAME.as_str()).await?;
+        let digest = Sha256::digest(format!("{:?}", self.query));
+
+        Ok(format!(
+            "{KEY_PREFIX}:{}:{generation:016x}:{operation}:{}",
+            T::TABLE_NAME,
+            hex::encode(digest)
+        ))
+    }
+}
+
+fn generation_key(table_name: &str) -> String {
+    format!("{KEY_PREFIX}:{table_name}")
+}
+
+/// Returns the current generation of the cached results for the model with the
+/// given table name.
+///
+/// The generation is a random number that is a part of the keys of all the
+/// cached results. It is removed from the cache when a model instance changes,
+/// which makes all the previously cached results unreachable.
+async fn generation(cache: &Cache, table_name: &str) -> cot::Result<u64> {
+    let key = generation_key(table_name);
+    if let Some(generation) = cache.get(&key).await? {
+        return Ok(generation);
+    }
+
+    let generation = rand::random::<u64>();
+    cache
+        .insert_expiring(key, generation, Timeout::Never)
+        .await?;
+    Ok(generation)
+}
+
+impl Database {
+    /// Attaches a cache to the database, enabling caching of query results
+    /// with [`Query::cached`].
+    ///
+    /// Once the cache is attached, each insert, update, and delete done
+    /// through this database invalidates the cached results for the changed
+    /// model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::cache::Cache;
+    /// use cot::cache::store::memory::Memory;
+    /// use cot::config::Timeout;
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let cache = Cache::new(Memory::new(), None, Timeout::Never);
+    /// let db = Database::new("sqlite::memory:")
+    ///     .await?
+    ///     .with_query_cache(cache);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_query_cache(mut self, cache: Cache) -> Self {
+        self.query_cache = Some(cache);
+        self
+    }
+
+    /// Invalidates the cached query results for the model with the given
+    /// table name.
+    ///
+    /// A failure is only logged, as the changes have already been made in the
+    /// database at this point.
+    ///
+    /// Inside a transaction, the invalidation is deferred until the
+    /// transaction is committed.
+    pub(super) async fn invalidate_query_cache(&self, table_name: &str) {
+        if let Some(pending) = &self.pending_invalidations {
+            pending.add(table_name);
+            return;
+        }
+
+        if let Some(cache) = &self.query_cache {
+            invalidate(cache, table_name).await;
+        }
+    }
+
+    /// Invalidates the cached query results for all the models changed in the
+    /// transaction this database handle is bound to.
+    pub(super) async fn invalidate_pending_query_cache(&self) {
+        let Some(pending) = &self.pending_invalidations else {
+            return;
+        };
+
+        for table_name in pending.take() {
+            invalidate(&pending.cache, &table_name).await;
+        }
+    }
+}
+
+async fn invalidate(cache: &Cache, table_name: &str) {
+    if let Err(err) = cache.remove(generation_key(table_name)).await {
+        error!(
+            table = %table_name,
+            "failed to invalidate the cached query results: {err}"
+        );
+    }
+}
+
+/// The cache invalidations deferred until a transaction is committed.
+#[derive(Debug)]
+pub(super) struct PendingInvalidations {
+    cache: Cache,
+    table_names: Mutex<Vec<String>>,
+}
+
+impl PendingInvalidations {
+    pub(super) fn new(cache: Cache) -> Self {
+        Self {
+            cache,
+            table_names: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn add(&self, table_name: &str) {
+        let mut table_names = self
+            .table_names
+            .lock()
+            .expect("pending invalidations lock poisoned");
+        if !table_names.iter().any(|name| name == table_name) {
+            table_names.push(table_name.to_owned());
+        }
+    }
+
+    fn take(&self) -> Vec<String> {
+        std::mem::take(
+            &mut *self
+                .table_names
+                .lock()
+                .expect("pending invalidations lock poisoned"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::store::memory::Memory;
+
+    #[cot::test]
+    async fn generation_is_stable_until_removed() {
+        let cache = Cache::new(Memory::new(), None, Timeout::Never);
+
+        let first = generation(&cache, "test_model").await.unwrap();
+        assert_eq!(generation(&cache, "test_model").await.unwrap(), first);
+
+        cache.remove(generation_key("test_model")).await.unwrap();
+        assert!(
+            !cache
+                .contains_key(generation_key("test_model"))
+                .await
+                .unwrap()
+        );
+        generation(&cache, "test_model").await.unwrap();
+        assert!(
+            cache
+                .contains_key(generation_key("test_model"))
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Notifications about the changes of model instances.
+//!
+//! When a [`Notifier`] is attached to a [`Database`] with
+//! [`Database::with_change_notifier`], a [`ModelChange`] message is published
+//! to it whenever a model instance is inserted, updated, or deleted through
+//! the database. When the project is bootstrapped with the database enabled,
+//! the project's notifier is attached to the database automatically.
+//!
+//! The messages can be received with [`ModelChanges`], which can also be used
+//! as an extractor and optionally narrowed down to a single model instance.
+//! Combined with a streaming response or a web socket, this makes it possible
+//! to build live-updating pages without any external pub/sub infrastructure.
+//!
+//! Changes made inside a transaction are only published once the transaction
+//! is committed. Changes made with raw SQL queries are not detected. As with
+//! all the [`Notifier`] messages, the changes are only delivered within a
+//! single process.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::Body;
+//! use cot::db::changes::ModelChanges;
+//! use cot::db::{Auto, model};
+//! use cot::request::extractors::Path;
+//! use cot::response::{Response, ResponseExt};
+//! use futures_util::stream;
+//!
+//! #[model]
+//! struct TodoItem {
+//!     #[model(primary_key)]
+//!     id: Auto<i32>,
+//!     title: String,
+//! }
+//!
+//! async fn todo_events(
+//!     Path(id): Path<i32>,
+//!     changes: ModelChanges<TodoItem>,
+//! ) -> cot::Result<Response> {
+//!     let changes = changes.for_primary_key(&Auto::fixed(id));
+//!     let events = stream::unfold(changes, move |mut changes| async move {
+//!         let change = changes.recv().await?;
+//!         let event = format!("event: {}\ndata: {id}\n\n", change.kind());
+//!         Some((Ok(event.into()), changes))
+//!     });
+//!
+//!     Ok(Response::builder()
+//!         .header("content-type", "text/event-stream")
+//!         .body(Body::streaming(events))
+//!         .unwrap())
+//! }
+//! ```
+
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+use crate::db::query::Query;
+use crate::db::{Database, DbFieldValue, DbValue, Model, Result, ToDbFieldValue};
+use crate::notify::{Notifier, Subscription};
+use crate::request::extractors::FromRequestHead;
+use crate::request::{RequestExt, RequestHead};
+
+/// The kind of change made to a model instance.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// The instance was inserted or updated.
+    Saved,
+    /// The instance was deleted.
+    Deleted,
+}
+
+impl ChangeKind {
+    /// Returns the name of the change kind, in `snake_case`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::changes::ChangeKind;
+    ///
+    /// assert_eq!(ChangeKind::Saved.as_str(), "saved");
+    /// assert_eq!(ChangeKind::Deleted.as_str(), "deleted");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Saved => "saved",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A message published to the [`Notifier`] attached to the database whenever
+/// an instance of the model `M` is saved or deleted.
+///
+/// See the [module documentation](self) for more details.
+pub struct ModelChange<M> {
+    kind: ChangeKind,
+    primary_key: DbValue,
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: Model> ModelChange<M> {
+    fn new(kind: ChangeKind, primary_key: DbValue) -> Self {
+        Self {
+            kind,
+            primary_key,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the kind of the change.
+    #[must_use]
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// Returns the primary key of the changed instance, as stored in the
+    /// database.
+    #[must_use]
+    pub fn primary_key(&self) -> &DbValue {
+        &self.primary_key
+    }
+
+    /// Returns whether the change is for the instance with the given primary
+    /// key.
+    #[must_use]
+    pub fn is_for(&self, primary_key: &M::PrimaryKey) -> bool {
+        matches!(
+            primary_key.to_db_field_value(),
+            DbFieldValue::Value(value) if value == self.primary_key
+        )
+    }
+}
+
+// manual implementation to avoid `M: Clone` in the trait bounds
+impl<M> Clone for ModelChange<M> {
+    fn clone(&self) -> Self {
+        Self {
+            kind: self.kind,
+            primary_key: self.primary_key.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+// manual implementation to avoid `M: Debug` in the trait bounds
+impl<M> Debug for ModelChange<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelChange")
+            .field("kind", &self.kind)
+            .field("primary_key", &self.primary_key)
+            .finish()
+    }
+}
+
+/// A subscription to the changes of the instances of the model `M`.
+///
+/// When used as an extractor, it subscribes to the project's notifier before
+/// the request handler is called, so no change made while the handler runs is
+/// missed.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::changes::ModelChanges;
+/// use cot::db::{Auto, model};
+///
+/// #[model]
+/// struct TodoItem {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// async fn wait_for_change(changes: ModelChanges<TodoItem>) {
+///     let mut changes = changes.for_primary_key(&Auto::fixed(5));
+///     while let Some(change) = changes.recv().await {
+///         println!("todo item 5 was {}", change.kind());
+///     }
+/// }
+/// ```
+pub struct ModelChanges<M> {
+    subscription: Subscription<ModelChange<M>>,
+    primary_key: Option<DbValue>,
+}
+
+impl<M: Model> ModelChanges<M> {
+    /// Subscribes to the changes of all the instances of the model published
+    /// to the given notifier.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock guarding the notifier topics is poisoned.
+    #[must_use]
+    pub fn new(notifier: &Notifier) -> Self {
+        Self {
+            subscription: notifier.subscribe(),
+            primary_key: None,
+        }
+    }
+
+    /// Only receive the changes of the instance with the given primary key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the primary key is [`Auto::Auto`](crate::db::Auto::Auto).
+    #[must_use]
+    pub fn for_primary_key(mut self, primary_key: &M::PrimaryKey) -> Self {
+        self.primary_key = Some(
+            primary_key
+                .to_db_field_value()
+                .expect_value("primary key cannot be auto when filtering changes"),
+        );
+        self
+    }
+
+    /// Waits for the next change.
+    ///
+    /// Returns `None` if the notifier has been dropped.
+    pub async fn recv(&mut self) -> Option<ModelChange<M>> {
+        loop {
+            let change = self.subscription.recv().await?;
+            match &self.primary_key {
+                Some(primary_key) if *primary_key != change.primary_key => {}
+                _ => return Some(change),
+            }
+        }
+    }
+}
+
+// manual implementation to avoid `M: Debug` in the trait bounds
+impl<M> Debug for ModelChanges<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelChanges")
+            .field("subscription", &self.subscription)
+            .field("primary_key", &self.primary_key)
+            .finish()
+    }
+}
+
+impl<M: Model> FromRequestHead for ModelChanges<M> {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        Ok(Self::new(head.context().notifier()))
+    }
+}
+
+impl Database {
+    /// Attaches a notifier to the database, so that a [`ModelChange`] message
+    /// is published to it whenever a model instance is saved or deleted
+    /// through this database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::notify::Notifier;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let db = Database::new("sqlite::memory:")
+    ///     .await?
+    ///     .with_change_notifier(Notifier::new());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_change_notifier(mut self, notifier: Notifier) -> Self {
+        self.change_notifier = Some(notifier);
+        self
+    }
+
+    fn change_notifier(&self) -> Option<&Notifier> {
+        match &self.pending_changes {
+            Some(pending) => Some(&pending.notifier),
+            None => self.change_notifier.as_ref(),
+        }
+    }
+
+    /// Returns whether anyone is subscribed to the changes of the model `T`.
+    ///
+    /// This is used to avoid querying the primary keys of the deleted rows
+    /// when nobody is interested in them.
+    pub(super) fn has_change_subscribers<T: Model>(&self) -> bool {
+        self.change_notifier()
+            .is_some_and(|notifier| notifier.topic::<ModelChange<T>>().subscriber_count() > 0)
+    }
+
+    /// Publishes a change of the model instance with the given primary key.
+    ///
+    /// Inside a transaction, the change is deferred until the transaction is
+    /// committed.
+    pub(super) fn notify_change<T: Model>(&self, kind: ChangeKind, primary_key: &T::PrimaryKey) {
+        if self.change_notifier().is_none() {
+            return;
+        }
+        let DbFieldValue::Value(primary_key) = primary_key.to_db_field_value() else {
+            return;
+        };
+        self.notify_change_value::<T>(kind, primary_key);
+    }
+
+    /// Publishes a change of the model instance with the given primary key,
+    /// already converted to a database value.
+    pub(super) fn notify_change_value<T: Model>(&self, kind: ChangeKind, primary_key: DbValue) {
+        let change = ModelChange::<T>::new(kind, primary_key);
+
+        if let Some(pending) = &self.pending_changes {
+            pending.add(change);
+        } else if let Some(notifier) = &self.change_notifier {
+            notifier.publish(change);
+        }
+    }
+
+    /// Publishes the changes made in the transaction this database handle is
+    /// bound to.
+    pub(super) fn publish_pending_changes(&self) {
+        let Some(pending) = &self.pending_changes else {
+            return;
+        };
+
+        for publish in pending.take() {
+            publish(&pending.notifier);
+        }
+    }
+
+    /// Returns the primary keys of the rows that match the given query.
+    pub(super) async fn primary_keys<T: Model>(&self, query: &Query<T>) -> Result<Vec<DbValue>> {
+        let mut select = sea_query::Query::select();
+        select.column(T::PRIMARY_KEY_NAME).from(T::TABLE_NAME);
+        query.add_filter_to_statement(&mut select);
+
+        let mut primary_keys = Vec::new();
+        for row in self.fetch_all(&select).await? {
+            if let DbFieldValue::Value(primary_key) =
+                row.get::<T::PrimaryKey>(0)?.to_db_field_value()
+            {
+                primary_keys.push(primary_key);
+            }
+        }
+        Ok(primary_keys)
+    }
+}
+
+type PublishFn = Box<dyn FnOnce(&Notifier) + Send>;
+
+/// The change notifications deferred until a transaction is committed.
+pub(super) struct PendingChanges {
+    notifier: Notifier,
+    changes: Mutex<Vec<PublishFn>>,
+}
+
+impl PendingChanges {
+    pub(super) fn new(notifier: Notifier) -> Self {
+        Self {
+            notifier,
+            changes: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn add<T: Model>(&self, change: ModelChange<T>) {
+        self.changes
+            .lock()
+            .expect("pending changes lock poisoned")
+            .push(Box::new(move |notifier: &Notifier| {
+                notifier.publish(change);
+            }));
+    }
+
+    fn take(&self) -> Vec<PublishFn> {
+        std::mem::take(&mut *self.changes.lock().expect("pending changes lock poisoned"))
+    }
+}
+
+impl Debug for PendingChanges {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingChanges")
+            .field("notifier", &self.notifier)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Auto, model};
+
+    #[model]
+    struct TodoItem {
+        #[model(primary_key)]
+        id: Auto<i32>,
+    }
+
+    fn change(kind: ChangeKind, id: i32) -> ModelChange<TodoItem> {
+        ModelChange::new(kind, DbValue::Int(Some(id)))
+    }
+
+    #[cot::test]
+    async fn model_changes() {
+        let notifier = Notifier::new();
+        let mut all = ModelChanges::<TodoItem>::new(&notifier);
+        let mut single = ModelChanges::<TodoItem>::new(&notifier).for_primary_key(&Auto::fixed(5));
+
+        notifier.publish(change(ChangeKind::Saved, 4));
+        notifier.publish(change(ChangeKind::Deleted, 5));
+
+        let first = all.recv().await.unwrap();
+        assert_eq!(first.kind(), ChangeKind::Saved);
+        assert!(first.is_for(&Auto::fixed(4)));
+        assert!(!first.is_for(&Auto::fixed(5)));
+
+        let second = single.recv().await.unwrap();
+        assert_eq!(second.kind(), ChangeKind::Deleted);
+        assert_eq!(second.primary_key(), &DbValue::Int(Some(5)));
+    }
+
+    #[test]
+    fn change_kind_display() {
+        assert_eq!(ChangeKind::Saved.to_string(), "saved");
+        assert_eq!(ChangeKind::Deleted.to_string(), "deleted");
+    }
+}
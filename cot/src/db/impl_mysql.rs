@@ -11,6 +11,29 @@ impl DatabaseMySql {
         Ok(())
     }
 
+    #[expect(clippy::unnecessary_wraps)] // to have a unified interface between database impls
+    fn statement_timeout_sql(timeout: std::time::Duration) -> Option<String> {
+        Some(format!(
+            "SET SESSION max_execution_time = {}",
+            timeout.as_millis()
+        ))
+    }
+
+    #[expect(clippy::unnecessary_wraps)] // to have a unified interface between database impls
+    pub(super) fn lock_timeout_statements(
+        timeout: std::time::Duration,
+    ) -> Option<(String, Option<&'static str>)> {
+        // MySQL only supports whole seconds, and the session variable has to
+        // be reset, as it outlives the transaction
+        Some((
+            format!(
+                "SET SESSION lock_wait_timeout = {}",
+                timeout.as_secs().max(1)
+            ),
+            Some("SET SESSION lock_wait_timeout = DEFAULT"),
+        ))
+    }
+
     fn prepare_values(_values: &mut sea_query_binder::SqlxValues) {
         // No changes are needed for MySQL
     }
@@ -10,6 +10,22 @@ impl DatabasePostgres {
         Ok(())
     }
 
+    #[expect(clippy::unnecessary_wraps)] // to have a unified interface between database impls
+    fn statement_timeout_sql(timeout: std::time::Duration) -> Option<String> {
+        Some(format!("SET statement_timeout = {}", timeout.as_millis()))
+    }
+
+    #[expect(clippy::unnecessary_wraps)] // to have a unified interface between database impls
+    pub(super) fn lock_timeout_statements(
+        timeout: std::time::Duration,
+    ) -> Option<(String, Option<&'static str>)> {
+        // `SET LOCAL` only lasts until the end of the transaction
+        Some((
+            format!("SET LOCAL lock_timeout = {}", timeout.as_millis()),
+            None,
+        ))
+    }
+
     fn prepare_values(values: &mut sea_query_binder::SqlxValues) {
         for value in &mut values.0.0 {
             Self::tinyint_to_smallint(value);
@@ -12,6 +12,18 @@ impl DatabaseSqlite {
         Ok(())
     }
 
+    fn statement_timeout_sql(_timeout: std::time::Duration) -> Option<String> {
+        tracing::warn!("SQLite does not support statement timeouts; ignoring");
+        None
+    }
+
+    pub(super) fn lock_timeout_statements(
+        _timeout: std::time::Duration,
+    ) -> Option<(String, Option<&'static str>)> {
+        tracing::warn!("SQLite does not support lock timeouts; ignoring");
+        None
+    }
+
     async fn raw(&self, sql: &str) -> crate::db::Result<crate::db::StatementResult> {
         self.raw_with(sql, SqlxValues(sea_query::Values(Vec::new())))
             .await
@@ -2,18 +2,22 @@
 
 mod sorter;
 
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 use std::future::Future;
+use std::time::Duration;
 
 pub use cot_macros::migration_op;
 use sea_query::{ColumnDef, StringLen};
 use thiserror::Error;
-use tracing::{Level, info};
+use tracing::{Level, error, info, warn};
 
 use crate::db::migrations::sorter::{MigrationSorter, MigrationSorterError};
 use crate::db::relations::{ForeignKeyOnDeletePolicy, ForeignKeyOnUpdatePolicy};
-use crate::db::{Auto, ColumnType, Database, DatabaseField, Identifier, Result, model, query};
+use crate::db::{
+    Auto, Column, ColumnType, Database, DatabaseField, Identifier, Model, Result, model, query,
+};
 
 /// An error that occurred while running migrations.
 #[derive(Debug, Clone, Error)]
@@ -25,6 +29,49 @@ pub enum MigrationEngineError {
     /// A custom error occurred during a migration.
     #[error("error running migration: {0}")]
     Custom(String),
+    /// The migration to revert to does not exist.
+    #[error("migration `{0}` not found")]
+    MigrationNotFound(String),
+    /// The migration to revert to was given without an app name, and there
+    /// are migrations with this name in multiple apps.
+    #[error("migration name `{0}` is ambiguous; prefix it with the app name, e.g. `app:{0}`")]
+    AmbiguousMigration(String),
+    /// A migration to revert contains an operation that cannot be run
+    /// backwards.
+    #[error("migration {migration_name} for app {app_name} cannot be reverted")]
+    IrreversibleMigration {
+        /// The name of the app the migration belongs to.
+        app_name: String,
+        /// The name of the migration.
+        migration_name: String,
+    },
+    /// A migration to apply contains a destructive operation, and applying
+    /// destructive migrations was not allowed.
+    ///
+    /// See [`MigrationEngine::allow_destructive`].
+    #[error(
+        "migration {migration_name} for app {app_name} contains a destructive operation; \
+        back up the data and apply it with `migration apply --allow-destructive`"
+    )]
+    DestructiveMigration {
+        /// The name of the app the migration belongs to.
+        app_name: String,
+        /// The name of the migration.
+        migration_name: String,
+    },
+    /// A field cannot be altered, because the database doesn't support
+    /// changing existing columns.
+    #[error("field `{field_name}` of table `{table_name}` cannot be altered on this database")]
+    AlterFieldNotSupported {
+        /// The name of the table containing the field.
+        table_name: String,
+        /// The name of the field.
+        field_name: String,
+    },
+    /// The migrations could not be run, because the project has no database
+    /// configured.
+    #[error("the project has no database configured")]
+    DatabaseNotConfigured,
 }
 
 /// A migration engine responsible for managing and applying database
@@ -64,6 +111,8 @@ pub enum MigrationEngineError {
 #[derive(Debug)]
 pub struct MigrationEngine {
     migrations: Vec<MigrationWrapper>,
+    allow_destructive: bool,
+    allow_destructive_on_empty_database: bool,
 }
 
 impl MigrationEngine {
@@ -115,7 +164,57 @@ impl MigrationEngine {
 
     fn from_wrapper(mut migrations: Vec<MigrationWrapper>) -> Result<Self> {
         Self::sort_migrations(&mut migrations)?;
-        Ok(Self { migrations })
+        Ok(Self {
+            migrations,
+            allow_destructive: false,
+            allow_destructive_on_empty_database: false,
+        })
+    }
+
+    /// Sets whether migrations containing
+    /// [destructive operations](Operation::is_destructive) can be applied.
+    ///
+    /// By default, [`Self::run`] refuses to apply them and returns an error
+    /// before applying any of the pending migrations, so that data isn't
+    /// lost by accident. Migrations that are already applied are not checked.
+    ///
+    /// When the server starts, destructive migrations are still applied to a
+    /// database that doesn't have any migrations applied yet, as there is no
+    /// data to lose; only a warning is logged then. The `migration apply`
+    /// command always requires them to be allowed explicitly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::migrations::{MigrationEngine, SyncDynMigration};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine =
+    ///     MigrationEngine::new(Vec::<Box<SyncDynMigration>>::new())?.allow_destructive(true);
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// engine.run(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn allow_destructive(mut self, allow_destructive: bool) -> Self {
+        self.allow_destructive = allow_destructive;
+        self
+    }
+
+    /// Sets whether destructive migrations can be applied to a database that
+    /// doesn't have any migrations applied yet, logging a warning instead of
+    /// returning an error.
+    ///
+    /// There is no data to lose in such a database, so this is used when the
+    /// server starts, so that a project whose migration history removes a
+    /// field can still be deployed to a new environment.
+    #[must_use]
+    pub(crate) fn allow_destructive_on_empty_database(mut self, allow: bool) -> Self {
+        self.allow_destructive_on_empty_database = allow;
+        self
     }
 
     /// Sorts the migrations by app name and migration name to ensure that the
@@ -141,7 +240,9 @@ impl MigrationEngine {
     ///
     /// Returns an error if any of the migrations fail to apply, or if there is
     /// an error while interacting with the database, or if there is an
-    /// error while marking a migration as applied.
+    /// error while marking a migration as applied. Returns an error without
+    /// applying anything if a pending migration is destructive, unless it was
+    /// [allowed](Self::allow_destructive).
     ///
     /// # Examples
     ///
@@ -175,12 +276,53 @@ impl MigrationEngine {
     /// # }
     /// ```
     pub async fn run(&self, database: &Database) -> Result<()> {
+        self.run_with_progress(database, |_| {}).await
+    }
+
+    /// Runs the migrations like [`Self::run`], reporting the progress to the
+    /// given function.
+    ///
+    /// This is useful for showing the progress of long-running migrations,
+    /// such as [batched operations](Operation::batched) on large tables.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the migrations fail to apply, or if there
+    /// is an error while interacting with the database, or if there is an
+    /// error while marking a migration as applied. Returns an error without
+    /// applying anything if a pending migration is destructive, unless it was
+    /// [allowed](Self::allow_destructive).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::db::migrations::{MigrationEngine, SyncDynMigration};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new(Vec::<Box<SyncDynMigration>>::new())?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// engine
+    ///     .run_with_progress(&database, |progress| println!("{progress}"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_with_progress<F>(&self, database: &Database, mut progress: F) -> Result<()>
+    where
+        F: FnMut(MigrationProgress<'_>) + Send,
+    {
         info!("Running migrations");
 
         CREATE_APPLIED_MIGRATIONS_MIGRATION
             .forwards(database)
             .await?;
 
+        if !self.allow_destructive {
+            self.check_destructive(database).await?;
+        }
+
         for migration in &self.migrations {
             let span = tracing::span!(
                 Level::TRACE,
@@ -204,12 +346,284 @@ impl MigrationEngine {
                 migration.name(),
                 migration.app_name()
             );
+            progress(MigrationProgress::Applying {
+                app_name: migration.app_name(),
+                migration_name: migration.name(),
+            });
 
             for operation in migration.operations() {
-                operation.forwards(database).await?;
+                if operation.is_destructive() {
+                    warn!(
+                        ?operation,
+                        "Migration {} for app {} contains a destructive operation; \
+                         the affected data cannot be restored",
+                        migration.name(),
+                        migration.app_name()
+                    );
+                }
+                operation
+                    .run_forwards(database, &mut |batch, rows, total_rows| {
+                        progress(MigrationProgress::BatchProcessed {
+                            app_name: migration.app_name(),
+                            migration_name: migration.name(),
+                            batch,
+                            rows,
+                            total_rows,
+                        });
+                    })
+                    .await?;
             }
 
             Self::mark_migration_applied(database, migration).await?;
+            progress(MigrationProgress::Applied {
+                app_name: migration.app_name(),
+                migration_name: migration.name(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Reverts applied migrations by running their operations backwards, in
+    /// the reverse order of applying them.
+    ///
+    /// If `to` is [`None`], only the most recently applied migration is
+    /// reverted. Otherwise, `to` is the name of a migration, optionally
+    /// prefixed with its app name and a colon (e.g. `todoapp:m_0001_initial`);
+    /// all the applied migrations that come after it are reverted, while the
+    /// migration itself stays applied.
+    ///
+    /// Nothing is reverted if any of the migrations contains an operation that
+    /// cannot be run backwards, such as a custom operation without a
+    /// backwards function.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the migration given in `to` doesn't exist or its
+    /// name is ambiguous, if any of the migrations cannot be reverted, or if
+    /// there is an error while interacting with the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Migration, MigrationDependency, MigrationEngine, Operation};
+    /// use cot::db::{Database, DatabaseField, Identifier};
+    ///
+    /// struct MyMigration;
+    ///
+    /// impl Migration for MyMigration {
+    ///     const APP_NAME: &'static str = "todoapp";
+    ///     const MIGRATION_NAME: &'static str = "m_0001_initial";
+    ///     const DEPENDENCIES: &'static [MigrationDependency] = &[];
+    ///     const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+    ///         .table_name(Identifier::new("todoapp__my_model"))
+    ///         .fields(&[
+    ///             Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    ///                 .primary_key()
+    ///                 .auto(),
+    ///             Field::new(Identifier::new("app"), <String as DatabaseField>::TYPE),
+    ///         ])
+    ///         .build()];
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([MyMigration])?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    /// engine.run(&database).await?;
+    /// engine.revert(&database, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn revert(&self, database: &Database, to: Option<&str>) -> Result<()> {
+        info!("Reverting migrations");
+
+        CREATE_APPLIED_MIGRATIONS_MIGRATION
+            .forwards(database)
+            .await?;
+
+        let start = match to {
+            Some(to) => self.find_migration(to)? + 1,
+            None => 0,
+        };
+        let mut to_revert = Vec::new();
+        for migration in &self.migrations[start..] {
+            if Self::is_migration_applied(database, migration).await? {
+                to_revert.push(migration);
+            }
+        }
+        if to.is_none() {
+            to_revert = to_revert.pop().into_iter().collect();
+        }
+
+        if let Some(migration) = to_revert
+            .iter()
+            .find(|migration| !migration.operations().iter().all(Operation::is_reversible))
+        {
+            return Err(MigrationEngineError::IrreversibleMigration {
+                app_name: migration.app_name().to_owned(),
+                migration_name: migration.name().to_owned(),
+            }
+            .into());
+        }
+
+        for migration in to_revert.into_iter().rev() {
+            let span = tracing::span!(
+                Level::TRACE,
+                "revert_migration",
+                app_name = migration.app_name(),
+                migration_name = migration.name()
+            );
+            let _enter = span.enter();
+
+            info!(
+                "Reverting migration {} for app {}",
+                migration.name(),
+                migration.app_name()
+            );
+
+            for operation in migration.operations().iter().rev() {
+                operation.backwards(database).await?;
+            }
+
+            Self::mark_migration_unapplied(database, migration).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether each of the migrations has been applied, in the order
+    /// of applying them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is an error while interacting with the
+    /// database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Migration, MigrationDependency, MigrationEngine, Operation};
+    /// use cot::db::{Database, DatabaseField, Identifier};
+    ///
+    /// struct MyMigration;
+    ///
+    /// impl Migration for MyMigration {
+    ///     const APP_NAME: &'static str = "todoapp";
+    ///     const MIGRATION_NAME: &'static str = "m_0001_initial";
+    ///     const DEPENDENCIES: &'static [MigrationDependency] = &[];
+    ///     const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+    ///         .table_name(Identifier::new("todoapp__my_model"))
+    ///         .fields(&[
+    ///             Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    ///                 .primary_key()
+    ///                 .auto(),
+    ///         ])
+    ///         .build()];
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([MyMigration])?;
+    /// let database = Database::new("sqlite::memory:").await?;
+    ///
+    /// let status = engine.status(&database).await?;
+    /// assert!(!status[0].is_applied());
+    ///
+    /// engine.run(&database).await?;
+    /// let status = engine.status(&database).await?;
+    /// assert!(status[0].is_applied());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn status(&self, database: &Database) -> Result<Vec<MigrationStatus>> {
+        CREATE_APPLIED_MIGRATIONS_MIGRATION
+            .forwards(database)
+            .await?;
+
+        let applied_migrations = AppliedMigration::objects().all(database).await?;
+        let status = self
+            .migrations
+            .iter()
+            .map(|migration| MigrationStatus {
+                app_name: migration.app_name().to_owned(),
+                name: migration.name().to_owned(),
+                applied: applied_migrations
+                    .iter()
+                    .find(|applied| {
+                        applied.app == migration.app_name() && applied.name == migration.name()
+                    })
+                    .map(|applied| applied.applied),
+            })
+            .collect();
+
+        Ok(status)
+    }
+
+    /// Returns the index of the migration with the given name, optionally
+    /// prefixed with its app name and a colon.
+    fn find_migration(&self, name: &str) -> Result<usize> {
+        let (app_name, migration_name) = match name.split_once(':') {
+            Some((app_name, migration_name)) => (Some(app_name), migration_name),
+            None => (None, name),
+        };
+
+        let mut matching = self
+            .migrations
+            .iter()
+            .enumerate()
+            .filter(|(_, migration)| {
+                migration.name() == migration_name
+                    && app_name.is_none_or(|app_name| migration.app_name() == app_name)
+            })
+            .map(|(index, _)| index);
+
+        match (matching.next(), matching.next()) {
+            (Some(index), None) => Ok(index),
+            (Some(_), Some(_)) => {
+                Err(MigrationEngineError::AmbiguousMigration(name.to_owned()).into())
+            }
+            (None, _) => Err(MigrationEngineError::MigrationNotFound(name.to_owned()).into()),
+        }
+    }
+
+    /// Returns an error if any of the migrations that are not applied yet
+    /// contains a destructive operation.
+    ///
+    /// If [`Self::allow_destructive_on_empty_database`] is set and no
+    /// migrations have been applied to the database yet, only a warning is
+    /// logged instead.
+    async fn check_destructive(&self, database: &Database) -> Result<()> {
+        let mut empty_database = None;
+        for migration in &self.migrations {
+            if !migration.operations().iter().any(Operation::is_destructive)
+                || Self::is_migration_applied(database, migration).await?
+            {
+                continue;
+            }
+
+            if self.allow_destructive_on_empty_database {
+                let empty = match empty_database {
+                    Some(empty) => empty,
+                    None => !AppliedMigration::objects().exists(database).await?,
+                };
+                empty_database = Some(empty);
+                if empty {
+                    warn!(
+                        "Migration {} for app {} contains a destructive operation; applying it, \
+                         as the database doesn't have any migrations applied yet",
+                        migration.name(),
+                        migration.app_name()
+                    );
+                    continue;
+                }
+            }
+
+            return Err(MigrationEngineError::DestructiveMigration {
+                app_name: migration.app_name().to_owned(),
+                migration_name: migration.name().to_owned(),
+            }
+            .into());
         }
 
         Ok(())
@@ -241,6 +655,327 @@ impl MigrationEngine {
         database.insert(&mut applied_migration).await?;
         Ok(())
     }
+
+    async fn mark_migration_unapplied(
+        database: &Database,
+        migration: &MigrationWrapper,
+    ) -> Result<()> {
+        query!(
+            AppliedMigration,
+            $app == migration.app_name() && $name == migration.name()
+        )
+        .delete(database)
+        .await?;
+        Ok(())
+    }
+
+    /// Compares the given models with the database schema created by the
+    /// migrations and returns the differences between them.
+    ///
+    /// A non-empty result usually means that the models have been changed
+    /// without generating new migrations. Tables that are created by the
+    /// migrations, but don't have a corresponding model are ignored. Note that
+    /// the changes made by custom operations are not taken into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{
+    ///     Field, Migration, MigrationDependency, MigrationEngine, ModelSchema, Operation, SchemaDrift,
+    /// };
+    /// use cot::db::{Auto, DatabaseField, Identifier, Model, model};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     name: String,
+    /// }
+    ///
+    /// struct MyMigration;
+    ///
+    /// impl Migration for MyMigration {
+    ///     const APP_NAME: &'static str = "todoapp";
+    ///     const MIGRATION_NAME: &'static str = "m_0001_initial";
+    ///     const DEPENDENCIES: &'static [MigrationDependency] = &[];
+    ///     const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+    ///         .table_name(MyModel::TABLE_NAME)
+    ///         .fields(&[
+    ///             Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    ///                 .primary_key()
+    ///                 .auto(),
+    ///         ])
+    ///         .build()];
+    /// }
+    ///
+    /// # fn main() -> cot::Result<()> {
+    /// let engine = MigrationEngine::new([MyMigration])?;
+    /// let drift = engine.check_models(&[ModelSchema::of::<MyModel>()]);
+    /// // the `name` field is missing in the migrations
+    /// assert!(matches!(
+    ///     drift.as_slice(),
+    ///     [SchemaDrift::MissingColumn { column, .. }] if column == "name"
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn check_models(&self, models: &[ModelSchema]) -> Vec<SchemaDrift> {
+        let mut tables: HashMap<&str, Vec<&str>> = HashMap::new();
+        for migration in &self.migrations {
+            for operation in migration.operations() {
+                match &operation.inner {
+                    OperationInner::CreateModel {
+                        table_name, fields, ..
+                    } => {
+                        let columns = fields.iter().map(|field| field.name.as_str()).collect();
+                        tables.insert(table_name.as_str(), columns);
+                    }
+                    OperationInner::AddField { table_name, field } => {
+                        if let Some(columns) = tables.get_mut(table_name.as_str()) {
+                            columns.push(field.name.as_str());
+                        }
+                    }
+                    OperationInner::RemoveField { table_name, field } => {
+                        if let Some(columns) = tables.get_mut(table_name.as_str()) {
+                            columns.retain(|&column| column != field.name.as_str());
+                        }
+                    }
+                    OperationInner::RenameField {
+                        table_name,
+                        old_name,
+                        new_name,
+                    } => {
+                        if let Some(columns) = tables.get_mut(table_name.as_str()) {
+                            for column in columns.iter_mut() {
+                                if *column == old_name.as_str() {
+                                    *column = new_name.as_str();
+                                }
+                            }
+                        }
+                    }
+                    OperationInner::RemoveModel { table_name, .. } => {
+                        tables.remove(table_name.as_str());
+                    }
+                    OperationInner::AlterField { .. }
+                    | OperationInner::Custom { .. }
+                    | OperationInner::Batched { .. } => {}
+                }
+            }
+        }
+
+        let mut drift = Vec::new();
+        for model in models {
+            let table = model.table_name.as_str();
+            let Some(migration_columns) = tables.get(table) else {
+                drift.push(SchemaDrift::MissingTable {
+                    table: table.to_owned(),
+                });
+                continue;
+            };
+
+            for column in model.columns {
+                if !migration_columns.contains(&column.name().as_str()) {
+                    drift.push(SchemaDrift::MissingColumn {
+                        table: table.to_owned(),
+                        column: column.name().as_str().to_owned(),
+                    });
+                }
+            }
+            for &column in migration_columns {
+                if !model
+                    .columns
+                    .iter()
+                    .any(|model_column| model_column.name().as_str() == column)
+                {
+                    drift.push(SchemaDrift::UnknownColumn {
+                        table: table.to_owned(),
+                        column: column.to_owned(),
+                    });
+                }
+            }
+        }
+
+        drift
+    }
+}
+
+/// The database schema of a model, as defined in the code.
+///
+/// This is used to check whether the models match the migrations; see
+/// [`MigrationEngine::check_models`] and
+/// [`App::models`](crate::App::models).
+#[derive(Debug, Copy, Clone)]
+pub struct ModelSchema {
+    table_name: Identifier,
+    columns: &'static [Column],
+}
+
+impl ModelSchema {
+    /// Returns the schema of the given model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::ModelSchema;
+    /// use cot::db::{Auto, Model, model};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    /// }
+    ///
+    /// let schema = ModelSchema::of::<MyModel>();
+    /// assert_eq!(schema.table_name(), MyModel::TABLE_NAME);
+    /// ```
+    #[must_use]
+    pub const fn of<T: Model>() -> Self {
+        Self {
+            table_name: T::TABLE_NAME,
+            columns: T::COLUMNS,
+        }
+    }
+
+    /// Returns the name of the model's table.
+    #[must_use]
+    pub const fn table_name(&self) -> Identifier {
+        self.table_name
+    }
+
+    /// Returns the model's columns.
+    #[must_use]
+    pub const fn columns(&self) -> &'static [Column] {
+        self.columns
+    }
+}
+
+/// Whether a migration has been applied to the database.
+///
+/// Returned by [`MigrationEngine::status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    app_name: String,
+    name: String,
+    applied: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl MigrationStatus {
+    /// Returns the name of the app that the migration belongs to.
+    #[must_use]
+    pub fn app_name(&self) -> &str {
+        &self.app_name
+    }
+
+    /// Returns the name of the migration.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the time the migration was applied at, or [`None`] if it is
+    /// pending.
+    #[must_use]
+    pub fn applied_at(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.applied
+    }
+
+    /// Returns whether the migration has been applied.
+    #[must_use]
+    pub fn is_applied(&self) -> bool {
+        self.applied.is_some()
+    }
+}
+
+/// A difference between a model and the database schema created by the
+/// migrations.
+///
+/// Returned by [`MigrationEngine::check_models`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum SchemaDrift {
+    /// There is no migration that creates the model's table.
+    #[error("table `{table}` is not created by any migration")]
+    MissingTable {
+        /// The name of the table.
+        table: String,
+    },
+    /// The migrations don't create a column for one of the model's fields.
+    #[error("column `{column}` in table `{table}` is not created by any migration")]
+    MissingColumn {
+        /// The name of the table.
+        table: String,
+        /// The name of the column.
+        column: String,
+    },
+    /// The migrations create a column that the model doesn't have a field
+    /// for.
+    #[error("column `{column}` in table `{table}` does not exist in the model")]
+    UnknownColumn {
+        /// The name of the table.
+        table: String,
+        /// The name of the column.
+        column: String,
+    },
+}
+
+/// The progress of applying the migrations, reported by
+/// [`MigrationEngine::run_with_progress`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MigrationProgress<'a> {
+    /// A migration is about to be applied.
+    Applying {
+        /// The name of the app the migration belongs to.
+        app_name: &'a str,
+        /// The name of the migration.
+        migration_name: &'a str,
+    },
+    /// A batch of a [batched operation](Operation::batched) has been
+    /// processed.
+    BatchProcessed {
+        /// The name of the app the migration belongs to.
+        app_name: &'a str,
+        /// The name of the migration.
+        migration_name: &'a str,
+        /// The number of the batch, starting from 1.
+        batch: u64,
+        /// The number of rows processed in the batch.
+        rows: u64,
+        /// The total number of rows processed by the operation so far.
+        total_rows: u64,
+    },
+    /// A migration has been applied.
+    Applied {
+        /// The name of the app the migration belongs to.
+        app_name: &'a str,
+        /// The name of the migration.
+        migration_name: &'a str,
+    },
+}
+
+impl fmt::Display for MigrationProgress<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Applying {
+                app_name,
+                migration_name,
+            } => write!(f, "Applying migration {migration_name} for app {app_name}"),
+            Self::BatchProcessed {
+                batch,
+                rows,
+                total_rows,
+                ..
+            } => write!(
+                f,
+                "  batch {batch}: {rows} row(s) processed, {total_rows} in total"
+            ),
+            Self::Applied {
+                app_name,
+                migration_name,
+            } => write!(f, "Applied migration {migration_name} for app {app_name}"),
+        }
+    }
 }
 
 /// A migration operation that can be run forwards or backwards.
@@ -271,12 +1006,16 @@ impl MigrationEngine {
 #[derive(Debug, Copy, Clone)]
 pub struct Operation {
     inner: OperationInner,
+    lock_timeout: Option<Duration>,
 }
 
 impl Operation {
     #[must_use]
     const fn new(inner: OperationInner) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            lock_timeout: None,
+        }
     }
 
     /// Returns a builder for an operation that creates a model.
@@ -387,6 +1126,84 @@ impl Operation {
         RemoveFieldBuilder::new()
     }
 
+    /// Returns a builder for an operation that renames a field in a model.
+    ///
+    /// Unlike removing the field and adding a new one, renaming preserves the
+    /// data stored in the field.
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI when a field
+    /// is marked with `#[model(rename_from = "old_name")]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
+    /// #     .table_name(Identifier::new("todoapp__my_model"))
+    /// #     .fields(&[
+    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    /// #             .primary_key()
+    /// #             .auto(),
+    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+    /// #     ])
+    /// #     .build();
+    /// const OPERATION: Operation = Operation::rename_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_name(Identifier::new("name"))
+    ///     .new_name(Identifier::new("title"))
+    ///     .build();
+    ///
+    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
+    /// # OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn rename_field() -> RenameFieldBuilder {
+        RenameFieldBuilder::new()
+    }
+
+    /// Returns a builder for an operation that changes the type or the
+    /// nullability of a field in a model, preserving its data.
+    ///
+    /// Only the type and the nullability of the column are changed; the other
+    /// properties of the new field, such as uniqueness, are ignored. Changing
+    /// a column is not supported on SQLite, where running the operation
+    /// returns an error.
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI when the type
+    /// of a field changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// const OPERATION: Operation = Operation::alter_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i32 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .new_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i64 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub const fn alter_field() -> AlterFieldBuilder {
+        AlterFieldBuilder::new()
+    }
+
     /// Returns a builder for an operation that removes a model.
     ///
     /// Typically, you shouldn't need to use this directly. Instead, in most
@@ -451,6 +1268,156 @@ impl Operation {
         CustomBuilder::new(forwards)
     }
 
+    /// Returns a builder for an operation that processes the rows of a table
+    /// in batches, such as backfilling a newly added column.
+    ///
+    /// The given function is called repeatedly, each time with a
+    /// [`BatchContext`], and should process at most
+    /// [`BatchContext::batch_size`] rows that haven't been processed yet,
+    /// returning the number of processed rows. The operation is finished once
+    /// the function returns fewer rows than the batch size. Each batch is run
+    /// separately, so that the locks are only held for a short time, which
+    /// makes this suitable for migrating large tables in production.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::Result;
+    /// use cot::db::migrations::{BatchContext, Operation, migration_op};
+    ///
+    /// #[migration_op]
+    /// async fn backfill(ctx: BatchContext<'_>) -> Result<u64> {
+    ///     let result = ctx
+    ///         .db
+    ///         .raw(&format!(
+    ///             "UPDATE todoapp__todo SET priority = 0 WHERE id IN \
+    ///              (SELECT id FROM todoapp__todo WHERE priority IS NULL LIMIT {})",
+    ///             ctx.batch_size
+    ///         ))
+    ///         .await?;
+    ///     Ok(result.rows_affected().0)
+    /// }
+    ///
+    /// const OPERATION: Operation = Operation::batched(backfill)
+    ///     .batch_size(5000)
+    ///     .pause(Duration::from_millis(100))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub const fn batched(forwards: BatchOperationFn) -> BatchedBuilder {
+        BatchedBuilder::new(forwards)
+    }
+
+    /// Sets the maximum time the operation waits to acquire the locks it
+    /// needs, such as the table lock when adding a column.
+    ///
+    /// If the locks can't be acquired in time, the operation fails instead of
+    /// blocking all the other queries that use the table while waiting. For
+    /// [batched operations](Self::batched), the timeout applies to each batch
+    /// separately.
+    ///
+    /// This is supported by PostgreSQL (as `lock_timeout`) and MySQL (as
+    /// `lock_wait_timeout`, which is rounded up to whole seconds); SQLite
+    /// doesn't support lock timeouts, so the value is ignored there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// const OPERATION: Operation = Operation::add_field()
+    ///     .table_name(Identifier::new("todoapp__todo"))
+    ///     .field(Field::new(Identifier::new("priority"), <i32 as DatabaseField>::TYPE).null())
+    ///     .build()
+    ///     .lock_timeout(Duration::from_secs(5));
+    /// ```
+    #[must_use]
+    pub const fn lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns whether the operation is destructive, i.e. whether running it
+    /// can cause a loss of data that cannot be restored by running it
+    /// backwards.
+    ///
+    /// Removing a field or a model is destructive, and so is changing a field
+    /// in a way that the existing values might not survive: narrowing its type
+    /// (e.g. from `i64` to `i32`, or to a shorter string), changing it to an
+    /// unrelated type, or making it non-nullable. Custom operations are not
+    /// considered destructive, as their effects are not known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// const OPERATION: Operation = Operation::remove_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .field(Field::new(
+    ///         Identifier::new("name"),
+    ///         <String as DatabaseField>::TYPE,
+    ///     ))
+    ///     .build();
+    ///
+    /// assert!(OPERATION.is_destructive());
+    /// ```
+    #[must_use]
+    pub const fn is_destructive(&self) -> bool {
+        match self.inner {
+            OperationInner::RemoveField { .. } | OperationInner::RemoveModel { .. } => true,
+            OperationInner::AlterField {
+                old_field,
+                new_field,
+                ..
+            } => {
+                (old_field.null && !new_field.null)
+                    || !is_lossless_conversion(old_field.ty, new_field.ty)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns whether the operation can be run backwards.
+    ///
+    /// All operations can be run backwards, except for custom and batched
+    /// operations without a backwards function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Result;
+    /// use cot::db::migrations::{MigrationContext, Operation, migration_op};
+    ///
+    /// #[migration_op]
+    /// async fn forwards(ctx: MigrationContext<'_>) -> Result<()> {
+    ///     Ok(())
+    /// }
+    ///
+    /// const OPERATION: Operation = Operation::custom(forwards).build();
+    ///
+    /// assert!(!OPERATION.is_reversible());
+    /// ```
+    #[must_use]
+    pub const fn is_reversible(&self) -> bool {
+        !matches!(
+            self.inner,
+            OperationInner::Custom {
+                backwards: None,
+                ..
+            } | OperationInner::Batched {
+                backwards: None,
+                ..
+            }
+        )
+    }
+
     /// Runs the operation forwards.
     ///
     /// # Errors
@@ -481,6 +1448,35 @@ impl Operation {
     /// # }
     /// ```
     pub async fn forwards(&self, database: &Database) -> Result<()> {
+        self.run_forwards(database, &mut |_, _, _| {}).await
+    }
+
+    /// Runs the operation forwards, calling `on_batch` with the batch number,
+    /// the number of rows in the batch, and the total number of processed
+    /// rows after each batch of a batched operation.
+    async fn run_forwards(
+        &self,
+        database: &Database,
+        on_batch: &mut (dyn FnMut(u64, u64, u64) + Send),
+    ) -> Result<()> {
+        if let OperationInner::Batched {
+            forwards,
+            batch_size,
+            pause,
+            ..
+        } = self.inner
+        {
+            return self
+                .run_batched(database, forwards, batch_size, pause, on_batch)
+                .await;
+        }
+
+        let scope = LockTimeoutScope::begin(database, self.lock_timeout).await?;
+        let result = self.forwards_impl(scope.database(database)).await;
+        scope.end(database, result).await
+    }
+
+    async fn forwards_impl(&self, database: &Database) -> Result<()> {
         match &self.inner {
             OperationInner::CreateModel {
                 table_name,
@@ -521,6 +1517,24 @@ impl Operation {
                     .to_owned();
                 database.execute_schema(query).await?;
             }
+            OperationInner::RenameField {
+                table_name,
+                old_name,
+                new_name,
+            } => {
+                let query = sea_query::Table::alter()
+                    .table(*table_name)
+                    .rename_column(*old_name, *new_name)
+                    .to_owned();
+                database.execute_schema(query).await?;
+            }
+            OperationInner::AlterField {
+                table_name,
+                old_field: _,
+                new_field,
+            } => {
+                Self::alter_column(database, *table_name, new_field).await?;
+            }
             OperationInner::RemoveModel {
                 table_name,
                 fields: _,
@@ -535,6 +1549,9 @@ impl Operation {
                 let context = MigrationContext::new(database);
                 forwards(context).await?;
             }
+            OperationInner::Batched { .. } => {
+                unreachable!("batched operations are run batch by batch")
+            }
         }
         Ok(())
     }
@@ -571,6 +1588,39 @@ impl Operation {
     /// # }
     /// ```
     pub async fn backwards(&self, database: &Database) -> Result<()> {
+        self.run_backwards(database, &mut |_, _, _| {}).await
+    }
+
+    /// Runs the operation backwards, calling `on_batch` after each batch of a
+    /// batched operation, like [`Self::run_forwards`].
+    async fn run_backwards(
+        &self,
+        database: &Database,
+        on_batch: &mut (dyn FnMut(u64, u64, u64) + Send),
+    ) -> Result<()> {
+        if let OperationInner::Batched {
+            backwards,
+            batch_size,
+            pause,
+            ..
+        } = self.inner
+        {
+            let Some(backwards) = backwards else {
+                return Err(crate::db::DatabaseError::MigrationError(
+                    MigrationEngineError::Custom("Backwards migration not implemented".into()),
+                ));
+            };
+            return self
+                .run_batched(database, backwards, batch_size, pause, on_batch)
+                .await;
+        }
+
+        let scope = LockTimeoutScope::begin(database, self.lock_timeout).await?;
+        let result = self.backwards_impl(scope.database(database)).await;
+        scope.end(database, result).await
+    }
+
+    async fn backwards_impl(&self, database: &Database) -> Result<()> {
         match &self.inner {
             OperationInner::CreateModel {
                 table_name,
@@ -594,6 +1644,24 @@ impl Operation {
                     .to_owned();
                 database.execute_schema(query).await?;
             }
+            OperationInner::RenameField {
+                table_name,
+                old_name,
+                new_name,
+            } => {
+                let query = sea_query::Table::alter()
+                    .table(*table_name)
+                    .rename_column(*new_name, *old_name)
+                    .to_owned();
+                database.execute_schema(query).await?;
+            }
+            OperationInner::AlterField {
+                table_name,
+                old_field,
+                new_field: _,
+            } => {
+                Self::alter_column(database, *table_name, old_field).await?;
+            }
             OperationInner::RemoveModel { table_name, fields } => {
                 let mut query = sea_query::Table::create().table(*table_name).to_owned();
                 for field in *fields {
@@ -625,11 +1693,168 @@ impl Operation {
                     ));
                 }
             }
+            OperationInner::Batched { .. } => {
+                unreachable!("batched operations are run batch by batch")
+            }
+        }
+        Ok(())
+    }
+
+    /// Changes the type and the nullability of a column to the ones of the
+    /// given field.
+    async fn alter_column(
+        database: &Database,
+        table_name: Identifier,
+        field: &Field,
+    ) -> Result<()> {
+        if !database.supports_altering_columns() {
+            return Err(crate::db::DatabaseError::MigrationError(
+                MigrationEngineError::AlterFieldNotSupported {
+                    table_name: table_name.as_str().to_owned(),
+                    field_name: field.name.as_str().to_owned(),
+                },
+            ));
+        }
+
+        let mut column =
+            ColumnDef::new_with_type(field.name, database.sea_query_column_type_for(field.ty));
+        if field.null {
+            column.null();
+        } else {
+            column.not_null();
+        }
+        let query = sea_query::Table::alter()
+            .table(table_name)
+            .modify_column(&mut column)
+            .to_owned();
+        database.execute_schema(query).await?;
+        Ok(())
+    }
+
+    async fn run_batched(
+        &self,
+        database: &Database,
+        operation: BatchOperationFn,
+        batch_size: u64,
+        pause: Duration,
+        on_batch: &mut (dyn FnMut(u64, u64, u64) + Send),
+    ) -> Result<()> {
+        let mut total_rows = 0;
+        for batch in 1_u64.. {
+            let scope = LockTimeoutScope::begin(database, self.lock_timeout).await?;
+            let context = BatchContext::new(scope.database(database), batch_size, batch);
+            let result = operation(context).await;
+            let rows = scope.end(database, result).await?;
+
+            total_rows += rows;
+            info!(batch, rows, total_rows, "Processed a batch of rows");
+            on_batch(batch, rows, total_rows);
+
+            if rows < batch_size {
+                break;
+            }
+            if !pause.is_zero() {
+                tokio::time::sleep(pause).await;
+            }
         }
+
         Ok(())
     }
 }
 
+/// A transaction in which a single step of an operation with a lock timeout
+/// is run, as the lock timeout has to be set on the same connection.
+#[derive(Debug)]
+struct LockTimeoutScope {
+    transaction: Option<Database>,
+    reset_statement: Option<&'static str>,
+}
+
+impl LockTimeoutScope {
+    async fn begin(database: &Database, lock_timeout: Option<Duration>) -> Result<Self> {
+        let Some((set_statement, reset_statement)) =
+            lock_timeout.and_then(|timeout| database.lock_timeout_statements(timeout))
+        else {
+            return Ok(Self {
+                transaction: None,
+                reset_statement: None,
+            });
+        };
+
+        let transaction = if database.in_transaction() {
+            None
+        } else {
+            Some(database.begin().await?)
+        };
+        transaction
+            .as_ref()
+            .unwrap_or(database)
+            .raw(&set_statement)
+            .await?;
+
+        Ok(Self {
+            transaction,
+            reset_statement,
+        })
+    }
+
+    fn database<'a>(&'a self, database: &'a Database) -> &'a Database {
+        self.transaction.as_ref().unwrap_or(database)
+    }
+
+    async fn end<T>(self, database: &Database, result: Result<T>) -> Result<T> {
+        if let Some(reset_statement) = self.reset_statement {
+            self.database(database).raw(reset_statement).await?;
+        }
+
+        if let Some(transaction) = &self.transaction {
+            if result.is_ok() {
+                transaction.commit().await?;
+            } else if let Err(rollback_error) = transaction.rollback().await {
+                error!("failed to roll back the transaction: {rollback_error}");
+            }
+        }
+
+        result
+    }
+}
+
+/// The number of rows processed in a single batch of a
+/// [batched operation](Operation::batched), unless specified otherwise.
+pub const DEFAULT_BATCH_SIZE: u64 = 1000;
+
+/// A context for a single batch of a [batched operation](Operation::batched).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct BatchContext<'a> {
+    /// The database connection to run the batch against.
+    pub db: &'a Database,
+    /// The maximum number of rows to process in this batch.
+    pub batch_size: u64,
+    /// The number of this batch, starting from 1.
+    pub batch: u64,
+}
+
+impl<'a> BatchContext<'a> {
+    fn new(db: &'a Database, batch_size: u64, batch: u64) -> Self {
+        Self {
+            db,
+            batch_size,
+            batch,
+        }
+    }
+}
+
+/// A type alias for a function processing a single batch of a [batched
+/// operation](Operation::batched), returning the number of processed rows.
+///
+/// Typically, you should use the [`migration_op`] attribute macro to define
+/// functions of this type.
+pub type BatchOperationFn =
+    for<'a> fn(
+        BatchContext<'a>,
+    ) -> std::pin::Pin<Box<dyn Future<Output = Result<u64>> + Send + 'a>>;
+
 /// A context for a custom migration operation.
 ///
 /// This structure provides access to the database and other information that
@@ -674,6 +1899,18 @@ enum OperationInner {
         table_name: Identifier,
         field: Field,
     },
+    /// Rename a field in an existing model, preserving its data.
+    RenameField {
+        table_name: Identifier,
+        old_name: Identifier,
+        new_name: Identifier,
+    },
+    /// Change the type or the nullability of a field, preserving its data.
+    AlterField {
+        table_name: Identifier,
+        old_field: Field,
+        new_field: Field,
+    },
     /// Remove a model with the given fields
     RemoveModel {
         table_name: Identifier,
@@ -683,6 +1920,13 @@ enum OperationInner {
         forwards: CustomOperationFn,
         backwards: Option<CustomOperationFn>,
     },
+    /// Process the rows of a table in batches.
+    Batched {
+        forwards: BatchOperationFn,
+        backwards: Option<BatchOperationFn>,
+        batch_size: u64,
+        pause: Duration,
+    },
 }
 
 /// A field in a model.
@@ -915,6 +2159,53 @@ impl Field {
     }
 }
 
+/// Returns whether every value of the `old` column type can be stored in a
+/// column of the `new` type without losing data.
+const fn is_lossless_conversion(old: ColumnType, new: ColumnType) -> bool {
+    if let (Some((old_signed, old_bits)), Some((new_signed, new_bits))) =
+        (integer_size(old), integer_size(new))
+    {
+        return if old_signed == new_signed {
+            new_bits >= old_bits
+        } else {
+            // only a signed column that is bigger can hold all unsigned values
+            !old_signed && new_bits > old_bits
+        };
+    }
+
+    match (old, new) {
+        (ColumnType::String(old_length), ColumnType::String(new_length)) => {
+            new_length >= old_length
+        }
+        (ColumnType::Boolean, ColumnType::Boolean)
+        | (ColumnType::Float, ColumnType::Float | ColumnType::Double)
+        | (ColumnType::Double, ColumnType::Double)
+        | (ColumnType::Time, ColumnType::Time)
+        | (ColumnType::Date, ColumnType::Date)
+        | (ColumnType::DateTime, ColumnType::DateTime)
+        | (ColumnType::DateTimeWithTimeZone, ColumnType::DateTimeWithTimeZone)
+        | (ColumnType::Text | ColumnType::String(_), ColumnType::Text)
+        | (ColumnType::Blob, ColumnType::Blob) => true,
+        _ => false,
+    }
+}
+
+/// Returns whether the integer column type is signed, and its size in bits, or
+/// `None` if the column type is not an integer.
+const fn integer_size(ty: ColumnType) -> Option<(bool, u8)> {
+    match ty {
+        ColumnType::TinyInteger => Some((true, 8)),
+        ColumnType::SmallInteger => Some((true, 16)),
+        ColumnType::Integer => Some((true, 32)),
+        ColumnType::BigInteger => Some((true, 64)),
+        ColumnType::TinyUnsignedInteger => Some((false, 8)),
+        ColumnType::SmallUnsignedInteger => Some((false, 16)),
+        ColumnType::UnsignedInteger => Some((false, 32)),
+        ColumnType::BigUnsignedInteger => Some((false, 64)),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct ForeignKeyReference {
     model: Identifier,
@@ -954,37 +2245,214 @@ macro_rules! unwrap_builder_option {
 /// # async fn main() -> cot::Result<()> {
 /// const OPERATION: Operation = Operation::create_model()
 ///     .table_name(Identifier::new("todoapp__my_model"))
-///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+///     .build();
+/// # let database = cot::db::Database::new("sqlite::memory:").await?;
+/// # OPERATION.forwards(&database).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct CreateModelBuilder {
+    table_name: Option<Identifier>,
+    fields: Option<&'static [Field]>,
+    if_not_exists: bool,
+}
+
+impl Default for CreateModelBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CreateModelBuilder {
+    #[must_use]
+    const fn new() -> Self {
+        Self {
+            table_name: None,
+            fields: None,
+            if_not_exists: false,
+        }
+    }
+
+    /// Sets the name of the table to create.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// const OPERATION: Operation = Operation::create_model()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+    ///     .build();
+    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn table_name(mut self, table_name: Identifier) -> Self {
+        self.table_name = Some(table_name);
+        self
+    }
+
+    /// Sets the fields to create in the model.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// const OPERATION: Operation = Operation::create_model()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+    ///     .build();
+    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn fields(mut self, fields: &'static [Field]) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    /// Sets the model to be created only if it doesn't already exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// const OPERATION: Operation = Operation::create_model()
+    ///     .if_not_exists()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+    ///     .build();
+    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    /// Builds the operation.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// const OPERATION: Operation = Operation::create_model()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+    ///     .build();
+    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # OPERATION.forwards(&database).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn build(self) -> Operation {
+        Operation::new(OperationInner::CreateModel {
+            table_name: unwrap_builder_option!(self, table_name),
+            fields: unwrap_builder_option!(self, fields),
+            if_not_exists: self.if_not_exists,
+        })
+    }
+}
+
+/// A builder for adding a field to a model.
+///
+/// # Cot CLI Usage
+///
+/// Typically, you shouldn't need to use this directly. Instead, in most
+/// cases, this can be automatically generated by the Cot CLI.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::migrations::{Field, Operation};
+/// use cot::db::{DatabaseField, Identifier};
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
+/// #     .table_name(Identifier::new("todoapp__my_model"))
+/// #     .fields(&[
+/// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+/// #             .primary_key()
+/// #             .auto(),
+/// #     ])
+/// #     .build();
+/// const OPERATION: Operation = Operation::add_field()
+///     .table_name(Identifier::new("todoapp__my_model"))
+///     .field(Field::new(
+///         Identifier::new("name"),
+///         <String as DatabaseField>::TYPE,
+///     ))
 ///     .build();
 /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+/// # CREATE_MODEL_OPERATION.forwards(&database).await?;
 /// # OPERATION.forwards(&database).await?;
 /// # Ok(())
 /// # }
 /// ```
 #[derive(Debug, Copy, Clone)]
-pub struct CreateModelBuilder {
+pub struct AddFieldBuilder {
     table_name: Option<Identifier>,
-    fields: Option<&'static [Field]>,
-    if_not_exists: bool,
+    field: Option<Field>,
 }
 
-impl Default for CreateModelBuilder {
+impl Default for AddFieldBuilder {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl CreateModelBuilder {
+impl AddFieldBuilder {
     #[must_use]
     const fn new() -> Self {
         Self {
             table_name: None,
-            fields: None,
-            if_not_exists: false,
+            field: None,
         }
     }
 
-    /// Sets the name of the table to create.
+    /// Sets the name of the table to add the field to.
     ///
     /// # Cot CLI Usage
     ///
@@ -999,11 +2467,23 @@ impl CreateModelBuilder {
     ///
     /// # #[tokio::main]
     /// # async fn main() -> cot::Result<()> {
-    /// const OPERATION: Operation = Operation::create_model()
+    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
+    /// #     .table_name(Identifier::new("todoapp__my_model"))
+    /// #     .fields(&[
+    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    /// #             .primary_key()
+    /// #             .auto(),
+    /// #     ])
+    /// #     .build();
+    /// const OPERATION: Operation = Operation::add_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+    ///     .field(Field::new(
+    ///         Identifier::new("name"),
+    ///         <String as DatabaseField>::TYPE,
+    ///     ))
     ///     .build();
     /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
     /// # OPERATION.forwards(&database).await?;
     /// # Ok(())
     /// # }
@@ -1014,7 +2494,7 @@ impl CreateModelBuilder {
         self
     }
 
-    /// Sets the fields to create in the model.
+    /// Sets the field to add to the model.
     ///
     /// # Cot CLI Usage
     ///
@@ -1029,44 +2509,30 @@ impl CreateModelBuilder {
     ///
     /// # #[tokio::main]
     /// # async fn main() -> cot::Result<()> {
-    /// const OPERATION: Operation = Operation::create_model()
-    ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
-    ///     .build();
-    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
-    /// # OPERATION.forwards(&database).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    #[must_use]
-    pub const fn fields(mut self, fields: &'static [Field]) -> Self {
-        self.fields = Some(fields);
-        self
-    }
-
-    /// Sets the model to be created only if it doesn't already exist.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use cot::db::migrations::{Field, Operation};
-    /// use cot::db::{DatabaseField, Identifier};
-    ///
-    /// # #[tokio::main]
-    /// # async fn main() -> cot::Result<()> {
-    /// const OPERATION: Operation = Operation::create_model()
-    ///     .if_not_exists()
+    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
+    /// #     .table_name(Identifier::new("todoapp__my_model"))
+    /// #     .fields(&[
+    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    /// #             .primary_key()
+    /// #             .auto(),
+    /// #     ])
+    /// #     .build();
+    /// const OPERATION: Operation = Operation::add_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+    ///     .field(Field::new(
+    ///         Identifier::new("name"),
+    ///         <String as DatabaseField>::TYPE,
+    ///     ))
     ///     .build();
     /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
     /// # OPERATION.forwards(&database).await?;
     /// # Ok(())
     /// # }
     /// ```
     #[must_use]
-    pub const fn if_not_exists(mut self) -> Self {
-        self.if_not_exists = true;
+    pub const fn field(mut self, field: Field) -> Self {
+        self.field = Some(field);
         self
     }
 
@@ -1085,28 +2551,37 @@ impl CreateModelBuilder {
     ///
     /// # #[tokio::main]
     /// # async fn main() -> cot::Result<()> {
-    /// const OPERATION: Operation = Operation::create_model()
+    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
+    /// #     .table_name(Identifier::new("todoapp__my_model"))
+    /// #     .fields(&[
+    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+    /// #             .primary_key()
+    /// #             .auto(),
+    /// #     ])
+    /// #     .build();
+    /// const OPERATION: Operation = Operation::add_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .fields(&[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()])
+    ///     .field(Field::new(
+    ///         Identifier::new("name"),
+    ///         <String as DatabaseField>::TYPE,
+    ///     ))
     ///     .build();
     /// # let database = cot::db::Database::new("sqlite::memory:").await?;
+    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
     /// # OPERATION.forwards(&database).await?;
     /// # Ok(())
     /// # }
     /// ```
     #[must_use]
     pub const fn build(self) -> Operation {
-        Operation::new(OperationInner::CreateModel {
+        Operation::new(OperationInner::AddField {
             table_name: unwrap_builder_option!(self, table_name),
-            fields: unwrap_builder_option!(self, fields),
-            if_not_exists: self.if_not_exists,
+            field: unwrap_builder_option!(self, field),
         })
     }
 }
 
-/// A builder for adding a field to a model.
-///
-/// # Cot CLI Usage
+/// A builder for removing a field from a model.
 ///
 /// Typically, you shouldn't need to use this directly. Instead, in most
 /// cases, this can be automatically generated by the Cot CLI.
@@ -1125,15 +2600,17 @@ impl CreateModelBuilder {
 /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
 /// #             .primary_key()
 /// #             .auto(),
+/// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
 /// #     ])
 /// #     .build();
-/// const OPERATION: Operation = Operation::add_field()
+/// const OPERATION: Operation = Operation::remove_field()
 ///     .table_name(Identifier::new("todoapp__my_model"))
 ///     .field(Field::new(
 ///         Identifier::new("name"),
 ///         <String as DatabaseField>::TYPE,
 ///     ))
 ///     .build();
+///
 /// # let database = cot::db::Database::new("sqlite::memory:").await?;
 /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
 /// # OPERATION.forwards(&database).await?;
@@ -1141,18 +2618,18 @@ impl CreateModelBuilder {
 /// # }
 /// ```
 #[derive(Debug, Copy, Clone)]
-pub struct AddFieldBuilder {
+pub struct RemoveFieldBuilder {
     table_name: Option<Identifier>,
     field: Option<Field>,
 }
 
-impl Default for AddFieldBuilder {
+impl Default for RemoveFieldBuilder {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl AddFieldBuilder {
+impl RemoveFieldBuilder {
     #[must_use]
     const fn new() -> Self {
         Self {
@@ -1161,7 +2638,7 @@ impl AddFieldBuilder {
         }
     }
 
-    /// Sets the name of the table to add the field to.
+    /// Sets the name of the table to remove the field from.
     ///
     /// # Cot CLI Usage
     ///
@@ -1182,15 +2659,17 @@ impl AddFieldBuilder {
     /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
     /// #             .primary_key()
     /// #             .auto(),
+    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
     /// #     ])
     /// #     .build();
-    /// const OPERATION: Operation = Operation::add_field()
+    /// const OPERATION: Operation = Operation::remove_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
     ///     .field(Field::new(
     ///         Identifier::new("name"),
     ///         <String as DatabaseField>::TYPE,
     ///     ))
     ///     .build();
+    ///
     /// # let database = cot::db::Database::new("sqlite::memory:").await?;
     /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
     /// # OPERATION.forwards(&database).await?;
@@ -1203,7 +2682,7 @@ impl AddFieldBuilder {
         self
     }
 
-    /// Sets the field to add to the model.
+    /// Sets the field that is to be removed from the model.
     ///
     /// # Cot CLI Usage
     ///
@@ -1224,15 +2703,17 @@ impl AddFieldBuilder {
     /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
     /// #             .primary_key()
     /// #             .auto(),
+    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
     /// #     ])
     /// #     .build();
-    /// const OPERATION: Operation = Operation::add_field()
+    /// const OPERATION: Operation = Operation::remove_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
     ///     .field(Field::new(
     ///         Identifier::new("name"),
     ///         <String as DatabaseField>::TYPE,
     ///     ))
     ///     .build();
+    ///
     /// # let database = cot::db::Database::new("sqlite::memory:").await?;
     /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
     /// # OPERATION.forwards(&database).await?;
@@ -1266,15 +2747,17 @@ impl AddFieldBuilder {
     /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
     /// #             .primary_key()
     /// #             .auto(),
+    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
     /// #     ])
     /// #     .build();
-    /// const OPERATION: Operation = Operation::add_field()
+    /// const OPERATION: Operation = Operation::remove_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
     ///     .field(Field::new(
     ///         Identifier::new("name"),
     ///         <String as DatabaseField>::TYPE,
     ///     ))
     ///     .build();
+    ///
     /// # let database = cot::db::Database::new("sqlite::memory:").await?;
     /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
     /// # OPERATION.forwards(&database).await?;
@@ -1283,14 +2766,159 @@ impl AddFieldBuilder {
     /// ```
     #[must_use]
     pub const fn build(self) -> Operation {
-        Operation::new(OperationInner::AddField {
+        Operation::new(OperationInner::RemoveField {
             table_name: unwrap_builder_option!(self, table_name),
             field: unwrap_builder_option!(self, field),
         })
     }
 }
 
-/// A builder for removing a field from a model.
+/// A builder for renaming a field in a model.
+///
+/// Typically, you shouldn't need to use this directly. Instead, in most
+/// cases, this can be automatically generated by the Cot CLI.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::Identifier;
+/// use cot::db::migrations::Operation;
+///
+/// const OPERATION: Operation = Operation::rename_field()
+///     .table_name(Identifier::new("todoapp__my_model"))
+///     .old_name(Identifier::new("name"))
+///     .new_name(Identifier::new("title"))
+///     .build();
+/// ```
+#[expect(clippy::struct_field_names)] // mirrors the names of the builder methods
+#[derive(Debug, Copy, Clone)]
+pub struct RenameFieldBuilder {
+    table_name: Option<Identifier>,
+    old_name: Option<Identifier>,
+    new_name: Option<Identifier>,
+}
+
+impl Default for RenameFieldBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenameFieldBuilder {
+    #[must_use]
+    const fn new() -> Self {
+        Self {
+            table_name: None,
+            old_name: None,
+            new_name: None,
+        }
+    }
+
+    /// Sets the name of the table containing the field to rename.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Identifier;
+    /// use cot::db::migrations::Operation;
+    ///
+    /// const OPERATION: Operation = Operation::rename_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_name(Identifier::new("name"))
+    ///     .new_name(Identifier::new("title"))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub const fn table_name(mut self, table_name: Identifier) -> Self {
+        self.table_name = Some(table_name);
+        self
+    }
+
+    /// Sets the current name of the field.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Identifier;
+    /// use cot::db::migrations::Operation;
+    ///
+    /// const OPERATION: Operation = Operation::rename_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_name(Identifier::new("name"))
+    ///     .new_name(Identifier::new("title"))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub const fn old_name(mut self, old_name: Identifier) -> Self {
+        self.old_name = Some(old_name);
+        self
+    }
+
+    /// Sets the name the field should be renamed to.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Identifier;
+    /// use cot::db::migrations::Operation;
+    ///
+    /// const OPERATION: Operation = Operation::rename_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_name(Identifier::new("name"))
+    ///     .new_name(Identifier::new("title"))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub const fn new_name(mut self, new_name: Identifier) -> Self {
+        self.new_name = Some(new_name);
+        self
+    }
+
+    /// Builds the operation.
+    ///
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Identifier;
+    /// use cot::db::migrations::Operation;
+    ///
+    /// const OPERATION: Operation = Operation::rename_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_name(Identifier::new("name"))
+    ///     .new_name(Identifier::new("title"))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub const fn build(self) -> Operation {
+        Operation::new(OperationInner::RenameField {
+            table_name: unwrap_builder_option!(self, table_name),
+            old_name: unwrap_builder_option!(self, old_name),
+            new_name: unwrap_builder_option!(self, new_name),
+        })
+    }
+}
+
+/// A builder for changing the type or the nullability of a field in a model.
 ///
 /// Typically, you shouldn't need to use this directly. Instead, in most
 /// cases, this can be automatically generated by the Cot CLI.
@@ -1300,54 +2928,43 @@ impl AddFieldBuilder {
 /// ```
 /// use cot::db::migrations::{Field, Operation};
 /// use cot::db::{DatabaseField, Identifier};
-///
-/// # #[tokio::main]
-/// # async fn main() -> cot::Result<()> {
-/// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
-/// #     .table_name(Identifier::new("todoapp__my_model"))
-/// #     .fields(&[
-/// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
-/// #             .primary_key()
-/// #             .auto(),
-/// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
-/// #     ])
-/// #     .build();
-/// const OPERATION: Operation = Operation::remove_field()
+///
+/// const OPERATION: Operation = Operation::alter_field()
 ///     .table_name(Identifier::new("todoapp__my_model"))
-///     .field(Field::new(
-///         Identifier::new("name"),
-///         <String as DatabaseField>::TYPE,
+///     .old_field(Field::new(
+///         Identifier::new("count"),
+///         <i32 as DatabaseField>::TYPE,
+///     ))
+///     .new_field(Field::new(
+///         Identifier::new("count"),
+///         <i64 as DatabaseField>::TYPE,
 ///     ))
 ///     .build();
-///
-/// # let database = cot::db::Database::new("sqlite::memory:").await?;
-/// # CREATE_MODEL_OPERATION.forwards(&database).await?;
-/// # OPERATION.forwards(&database).await?;
-/// # Ok(())
-/// # }
 /// ```
 #[derive(Debug, Copy, Clone)]
-pub struct RemoveFieldBuilder {
+pub struct AlterFieldBuilder {
     table_name: Option<Identifier>,
-    field: Option<Field>,
+    old_field: Option<Field>,
+    new_field: Option<Field>,
 }
 
-impl Default for RemoveFieldBuilder {
+impl Default for AlterFieldBuilder {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl RemoveFieldBuilder {
+impl AlterFieldBuilder {
     #[must_use]
     const fn new() -> Self {
         Self {
             table_name: None,
-            field: None,
+            old_field: None,
+            new_field: None,
         }
     }
 
-    /// Sets the name of the table to remove the field from.
+    /// Sets the name of the table containing the field to change.
     ///
     /// # Cot CLI Usage
     ///
@@ -1360,30 +2977,17 @@ impl RemoveFieldBuilder {
     /// use cot::db::migrations::{Field, Operation};
     /// use cot::db::{DatabaseField, Identifier};
     ///
-    /// # #[tokio::main]
-    /// # async fn main() -> cot::Result<()> {
-    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
-    /// #     .table_name(Identifier::new("todoapp__my_model"))
-    /// #     .fields(&[
-    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
-    /// #             .primary_key()
-    /// #             .auto(),
-    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
-    /// #     ])
-    /// #     .build();
-    /// const OPERATION: Operation = Operation::remove_field()
+    /// const OPERATION: Operation = Operation::alter_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .field(Field::new(
-    ///         Identifier::new("name"),
-    ///         <String as DatabaseField>::TYPE,
+    ///     .old_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i32 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .new_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i64 as DatabaseField>::TYPE,
     ///     ))
     ///     .build();
-    ///
-    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
-    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
-    /// # OPERATION.forwards(&database).await?;
-    /// # Ok(())
-    /// # }
     /// ```
     #[must_use]
     pub const fn table_name(mut self, table_name: Identifier) -> Self {
@@ -1391,7 +2995,10 @@ impl RemoveFieldBuilder {
         self
     }
 
-    /// Sets the field that is to be removed from the model.
+    /// Sets the field as it is currently defined.
+    ///
+    /// This is used when the operation is run backwards, and to determine
+    /// whether the operation is destructive.
     ///
     /// # Cot CLI Usage
     ///
@@ -1404,34 +3011,52 @@ impl RemoveFieldBuilder {
     /// use cot::db::migrations::{Field, Operation};
     /// use cot::db::{DatabaseField, Identifier};
     ///
-    /// # #[tokio::main]
-    /// # async fn main() -> cot::Result<()> {
-    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
-    /// #     .table_name(Identifier::new("todoapp__my_model"))
-    /// #     .fields(&[
-    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
-    /// #             .primary_key()
-    /// #             .auto(),
-    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
-    /// #     ])
-    /// #     .build();
-    /// const OPERATION: Operation = Operation::remove_field()
+    /// const OPERATION: Operation = Operation::alter_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .field(Field::new(
-    ///         Identifier::new("name"),
-    ///         <String as DatabaseField>::TYPE,
+    ///     .old_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i32 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .new_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i64 as DatabaseField>::TYPE,
     ///     ))
     ///     .build();
+    /// ```
+    #[must_use]
+    pub const fn old_field(mut self, old_field: Field) -> Self {
+        self.old_field = Some(old_field);
+        self
+    }
+
+    /// Sets the field as it should be defined after running the operation.
     ///
-    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
-    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
-    /// # OPERATION.forwards(&database).await?;
-    /// # Ok(())
-    /// # }
+    /// # Cot CLI Usage
+    ///
+    /// Typically, you shouldn't need to use this directly. Instead, in most
+    /// cases, this can be automatically generated by the Cot CLI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::migrations::{Field, Operation};
+    /// use cot::db::{DatabaseField, Identifier};
+    ///
+    /// const OPERATION: Operation = Operation::alter_field()
+    ///     .table_name(Identifier::new("todoapp__my_model"))
+    ///     .old_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i32 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .new_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i64 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .build();
     /// ```
     #[must_use]
-    pub const fn field(mut self, field: Field) -> Self {
-        self.field = Some(field);
+    pub const fn new_field(mut self, new_field: Field) -> Self {
+        self.new_field = Some(new_field);
         self
     }
 
@@ -1448,36 +3073,24 @@ impl RemoveFieldBuilder {
     /// use cot::db::migrations::{Field, Operation};
     /// use cot::db::{DatabaseField, Identifier};
     ///
-    /// # #[tokio::main]
-    /// # async fn main() -> cot::Result<()> {
-    /// # const CREATE_MODEL_OPERATION: Operation = Operation::create_model()
-    /// #     .table_name(Identifier::new("todoapp__my_model"))
-    /// #     .fields(&[
-    /// #         Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
-    /// #             .primary_key()
-    /// #             .auto(),
-    /// #         Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
-    /// #     ])
-    /// #     .build();
-    /// const OPERATION: Operation = Operation::remove_field()
+    /// const OPERATION: Operation = Operation::alter_field()
     ///     .table_name(Identifier::new("todoapp__my_model"))
-    ///     .field(Field::new(
-    ///         Identifier::new("name"),
-    ///         <String as DatabaseField>::TYPE,
+    ///     .old_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i32 as DatabaseField>::TYPE,
+    ///     ))
+    ///     .new_field(Field::new(
+    ///         Identifier::new("count"),
+    ///         <i64 as DatabaseField>::TYPE,
     ///     ))
     ///     .build();
-    ///
-    /// # let database = cot::db::Database::new("sqlite::memory:").await?;
-    /// # CREATE_MODEL_OPERATION.forwards(&database).await?;
-    /// # OPERATION.forwards(&database).await?;
-    /// # Ok(())
-    /// # }
     /// ```
     #[must_use]
     pub const fn build(self) -> Operation {
-        Operation::new(OperationInner::RemoveField {
+        Operation::new(OperationInner::AlterField {
             table_name: unwrap_builder_option!(self, table_name),
-            field: unwrap_builder_option!(self, field),
+            old_field: unwrap_builder_option!(self, old_field),
+            new_field: unwrap_builder_option!(self, new_field),
         })
     }
 }
@@ -1667,6 +3280,96 @@ impl CustomBuilder {
     }
 }
 
+/// A builder for a batched migration operation.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::db::Result;
+/// use cot::db::migrations::{BatchContext, Operation, migration_op};
+///
+/// #[migration_op]
+/// async fn forwards(ctx: BatchContext<'_>) -> Result<u64> {
+///     // process at most `ctx.batch_size` rows
+///     Ok(0)
+/// }
+///
+/// #[migration_op]
+/// async fn backwards(ctx: BatchContext<'_>) -> Result<u64> {
+///     // undo the changes for at most `ctx.batch_size` rows
+///     Ok(0)
+/// }
+///
+/// const OPERATION: Operation = Operation::batched(forwards)
+///     .backwards(backwards)
+///     .batch_size(500)
+///     .pause(Duration::from_secs(1))
+///     .build();
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct BatchedBuilder {
+    forwards: BatchOperationFn,
+    backwards: Option<BatchOperationFn>,
+    batch_size: u64,
+    pause: Duration,
+}
+
+impl BatchedBuilder {
+    #[must_use]
+    const fn new(forwards: BatchOperationFn) -> Self {
+        Self {
+            forwards,
+            backwards: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            pause: Duration::ZERO,
+        }
+    }
+
+    /// Sets the backwards operation.
+    #[must_use]
+    pub const fn backwards(mut self, backwards: BatchOperationFn) -> Self {
+        self.backwards = Some(backwards);
+        self
+    }
+
+    /// Sets the maximum number of rows processed in a single batch.
+    ///
+    /// Defaults to [`DEFAULT_BATCH_SIZE`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the batch size is zero.
+    #[must_use]
+    pub const fn batch_size(mut self, batch_size: u64) -> Self {
+        assert!(batch_size > 0, "batch size must be greater than zero");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets the time to wait between the batches, giving the database some
+    /// room to serve other queries.
+    ///
+    /// Defaults to no pause.
+    #[must_use]
+    pub const fn pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    /// Builds the operation.
+    #[must_use]
+    pub const fn build(self) -> Operation {
+        Operation::new(OperationInner::Batched {
+            forwards: self.forwards,
+            backwards: self.backwards,
+            batch_size: self.batch_size,
+            pause: self.pause,
+        })
+    }
+}
+
 /// A trait for defining a migration.
 ///
 /// # Cot CLI Usage
@@ -2020,61 +3723,257 @@ const CREATE_APPLIED_MIGRATIONS_MIGRATION: Operation = Operation::create_model()
     .if_not_exists()
     .build();
 
-#[cfg(test)]
-mod tests {
-    use cot::test::TestDatabase;
-    use sea_query::ColumnSpec;
+#[cfg(test)]
+mod tests {
+    use cot::test::TestDatabase;
+    use sea_query::ColumnSpec;
+
+    use super::*;
+    use crate::db::{ColumnType, DatabaseField, Identifier};
+
+    struct TestMigration;
+
+    impl Migration for TestMigration {
+        const APP_NAME: &'static str = "testapp";
+        const MIGRATION_NAME: &'static str = "m_0001_initial";
+        const DEPENDENCIES: &'static [MigrationDependency] = &[];
+        const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+            .table_name(Identifier::new("testapp__test_model"))
+            .fields(&[
+                Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                    .primary_key()
+                    .auto(),
+                Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+            ])
+            .build()];
+    }
+
+    struct DummyMigration;
+
+    impl Migration for DummyMigration {
+        const APP_NAME: &'static str = "testapp";
+        const MIGRATION_NAME: &'static str = "m_0002_custom";
+        const DEPENDENCIES: &'static [MigrationDependency] = &[];
+        const OPERATIONS: &'static [Operation] = &[];
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_run(test_db: &mut TestDatabase) {
+        let engine = MigrationEngine::new([TestMigration]).unwrap();
+
+        let result = engine.run(&test_db.database()).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_multiple_migrations_run(test_db: &mut TestDatabase) {
+        #[expect(trivial_casts)] // cast to the correct trait object type
+        let engine = MigrationEngine::new([
+            &TestMigration as &SyncDynMigration,
+            &DummyMigration as &SyncDynMigration,
+        ])
+        .unwrap();
+
+        let result = engine.run(&test_db.database()).await;
+
+        assert!(result.is_ok());
+    }
+
+    struct AddFieldMigration;
+
+    impl Migration for AddFieldMigration {
+        const APP_NAME: &'static str = "testapp";
+        const MIGRATION_NAME: &'static str = "m_0002_add_field";
+        const DEPENDENCIES: &'static [MigrationDependency] =
+            &[MigrationDependency::migration("testapp", "m_0001_initial")];
+        const OPERATIONS: &'static [Operation] = &[Operation::add_field()
+            .table_name(Identifier::new("testapp__test_model"))
+            .field(Field::new(
+                Identifier::new("description"),
+                <String as DatabaseField>::TYPE,
+            ))
+            .build()];
+    }
+
+    struct RemoveFieldMigration;
+
+    impl Migration for RemoveFieldMigration {
+        const APP_NAME: &'static str = "testapp";
+        const MIGRATION_NAME: &'static str = "m_0002_remove_field";
+        const DEPENDENCIES: &'static [MigrationDependency] =
+            &[MigrationDependency::migration("testapp", "m_0001_initial")];
+        const OPERATIONS: &'static [Operation] = &[Operation::remove_field()
+            .table_name(Identifier::new("testapp__test_model"))
+            .field(Field::new(
+                Identifier::new("name"),
+                <String as DatabaseField>::TYPE,
+            ))
+            .build()];
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_refuses_destructive(test_db: &mut TestDatabase) {
+        #[expect(trivial_casts)] // cast to the correct trait object type
+        let engine = MigrationEngine::new([
+            &TestMigration as &SyncDynMigration,
+            &RemoveFieldMigration as &SyncDynMigration,
+        ])
+        .unwrap();
+
+        let result = engine.run(&test_db.database()).await;
+
+        assert!(matches!(
+            result.unwrap_err(),
+            crate::db::DatabaseError::MigrationError(MigrationEngineError::DestructiveMigration {
+                ref migration_name,
+                ..
+            }) if migration_name == "m_0002_remove_field"
+        ));
+        assert!(applied_migrations(test_db).await.is_empty());
+
+        engine
+            .allow_destructive(true)
+            .run(&test_db.database())
+            .await
+            .unwrap();
+        assert_eq!(
+            applied_migrations(test_db).await,
+            ["m_0001_initial", "m_0002_remove_field"]
+        );
+    }
+
+    async fn applied_migrations(test_db: &TestDatabase) -> Vec<String> {
+        AppliedMigration::objects()
+            .all(&test_db.database())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|migration| migration.name)
+            .collect()
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_revert(test_db: &mut TestDatabase) {
+        #[expect(trivial_casts)] // cast to the correct trait object type
+        let engine = MigrationEngine::new([
+            &TestMigration as &SyncDynMigration,
+            &AddFieldMigration as &SyncDynMigration,
+        ])
+        .unwrap();
+        engine.run(&test_db.database()).await.unwrap();
 
-    use super::*;
-    use crate::db::{ColumnType, DatabaseField, Identifier};
+        engine.revert(&test_db.database(), None).await.unwrap();
+        assert_eq!(applied_migrations(test_db).await, ["m_0001_initial"]);
 
-    struct TestMigration;
+        engine
+            .revert(&test_db.database(), Some("testapp:m_0001_initial"))
+            .await
+            .unwrap();
+        assert_eq!(applied_migrations(test_db).await, ["m_0001_initial"]);
 
-    impl Migration for TestMigration {
-        const APP_NAME: &'static str = "testapp";
-        const MIGRATION_NAME: &'static str = "m_0001_initial";
-        const DEPENDENCIES: &'static [MigrationDependency] = &[];
-        const OPERATIONS: &'static [Operation] = &[Operation::create_model()
-            .table_name(Identifier::new("testapp__test_model"))
-            .fields(&[
-                Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
-                    .primary_key()
-                    .auto(),
-                Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
-            ])
-            .build()];
+        engine.run(&test_db.database()).await.unwrap();
+        assert_eq!(
+            applied_migrations(test_db).await,
+            ["m_0001_initial", "m_0002_add_field"]
+        );
     }
 
-    struct DummyMigration;
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_status(test_db: &mut TestDatabase) {
+        #[expect(trivial_casts)] // cast to the correct trait object type
+        let engine = MigrationEngine::new([
+            &TestMigration as &SyncDynMigration,
+            &AddFieldMigration as &SyncDynMigration,
+        ])
+        .unwrap();
+        MigrationEngine::new([TestMigration])
+            .unwrap()
+            .run(&test_db.database())
+            .await
+            .unwrap();
 
-    impl Migration for DummyMigration {
-        const APP_NAME: &'static str = "testapp";
-        const MIGRATION_NAME: &'static str = "m_0002_custom";
-        const DEPENDENCIES: &'static [MigrationDependency] = &[];
-        const OPERATIONS: &'static [Operation] = &[];
+        let status = engine.status(&test_db.database()).await.unwrap();
+
+        let status: Vec<_> = status
+            .iter()
+            .map(|status| (status.app_name(), status.name(), status.is_applied()))
+            .collect();
+        assert_eq!(
+            status,
+            [
+                ("testapp", "m_0001_initial", true),
+                ("testapp", "m_0002_add_field", false),
+            ]
+        );
     }
 
     #[cot_macros::dbtest]
-    async fn test_migration_engine_run(test_db: &mut TestDatabase) {
-        let engine = MigrationEngine::new([TestMigration]).unwrap();
+    async fn test_migration_engine_revert_to(test_db: &mut TestDatabase) {
+        #[expect(trivial_casts)] // cast to the correct trait object type
+        let engine = MigrationEngine::new([
+            &TestMigration as &SyncDynMigration,
+            &AddFieldMigration as &SyncDynMigration,
+        ])
+        .unwrap();
+        engine.run(&test_db.database()).await.unwrap();
 
-        let result = engine.run(&test_db.database()).await;
+        engine
+            .revert(&test_db.database(), Some("m_0001_initial"))
+            .await
+            .unwrap();
+        assert_eq!(applied_migrations(test_db).await, ["m_0001_initial"]);
 
-        assert!(result.is_ok());
+        let result = engine
+            .revert(&test_db.database(), Some("m_0003_missing"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(crate::db::DatabaseError::MigrationError(
+                MigrationEngineError::MigrationNotFound(_)
+            ))
+        ));
     }
 
     #[cot_macros::dbtest]
-    async fn test_migration_engine_multiple_migrations_run(test_db: &mut TestDatabase) {
+    async fn test_migration_engine_revert_irreversible(test_db: &mut TestDatabase) {
+        #[migration_op]
+        async fn forwards(_ctx: MigrationContext<'_>) -> Result<()> {
+            Ok(())
+        }
+
+        struct IrreversibleMigration;
+
+        impl Migration for IrreversibleMigration {
+            const APP_NAME: &'static str = "testapp";
+            const MIGRATION_NAME: &'static str = "m_0002_custom";
+            const DEPENDENCIES: &'static [MigrationDependency] =
+                &[MigrationDependency::migration("testapp", "m_0001_initial")];
+            const OPERATIONS: &'static [Operation] = &[Operation::custom(forwards).build()];
+        }
+
         #[expect(trivial_casts)] // cast to the correct trait object type
         let engine = MigrationEngine::new([
             &TestMigration as &SyncDynMigration,
-            &DummyMigration as &SyncDynMigration,
+            &IrreversibleMigration as &SyncDynMigration,
         ])
         .unwrap();
+        engine.run(&test_db.database()).await.unwrap();
 
-        let result = engine.run(&test_db.database()).await;
+        let result = engine
+            .revert(&test_db.database(), Some("m_0001_initial"))
+            .await;
 
-        assert!(result.is_ok());
+        assert!(matches!(
+            result,
+            Err(crate::db::DatabaseError::MigrationError(
+                MigrationEngineError::IrreversibleMigration { .. }
+            ))
+        ));
+        assert_eq!(
+            applied_migrations(test_db).await,
+            ["m_0001_initial", "m_0002_custom"]
+        );
     }
 
     #[test]
@@ -2145,6 +4044,139 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_operation_is_destructive() {
+        const FIELDS: &[Field; 1] =
+            &[Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE).primary_key()];
+        let field = Field::new(Identifier::new("age"), <i32 as DatabaseField>::TYPE);
+        let table_name = Identifier::new("testapp__test_model");
+
+        assert!(
+            !Operation::create_model()
+                .table_name(table_name)
+                .fields(FIELDS)
+                .build()
+                .is_destructive()
+        );
+        assert!(
+            !Operation::add_field()
+                .table_name(table_name)
+                .field(field)
+                .build()
+                .is_destructive()
+        );
+        assert!(
+            Operation::remove_field()
+                .table_name(table_name)
+                .field(field)
+                .build()
+                .is_destructive()
+        );
+        assert!(
+            Operation::remove_model()
+                .table_name(table_name)
+                .fields(FIELDS)
+                .build()
+                .is_destructive()
+        );
+    }
+
+    #[test]
+    fn test_alter_field_is_destructive() {
+        fn alter_field(old_field: Field, new_field: Field) -> Operation {
+            Operation::alter_field()
+                .table_name(Identifier::new("testapp__test_model"))
+                .old_field(old_field)
+                .new_field(new_field)
+                .build()
+        }
+        fn field(ty: ColumnType) -> Field {
+            Field::new(Identifier::new("value"), ty)
+        }
+
+        assert!(
+            !alter_field(field(ColumnType::Integer), field(ColumnType::BigInteger))
+                .is_destructive()
+        );
+        assert!(
+            !alter_field(
+                field(ColumnType::UnsignedInteger),
+                field(ColumnType::BigInteger)
+            )
+            .is_destructive()
+        );
+        assert!(!alter_field(field(ColumnType::Float), field(ColumnType::Double)).is_destructive());
+        assert!(
+            !alter_field(
+                field(ColumnType::String(50)),
+                field(ColumnType::String(100))
+            )
+            .is_destructive()
+        );
+        assert!(
+            !alter_field(field(ColumnType::String(50)), field(ColumnType::Text)).is_destructive()
+        );
+        assert!(
+            !alter_field(field(ColumnType::Text), field(ColumnType::Text).null()).is_destructive()
+        );
+
+        assert!(
+            alter_field(field(ColumnType::BigInteger), field(ColumnType::Integer)).is_destructive()
+        );
+        assert!(
+            alter_field(
+                field(ColumnType::UnsignedInteger),
+                field(ColumnType::Integer)
+            )
+            .is_destructive()
+        );
+        assert!(
+            alter_field(
+                field(ColumnType::Integer),
+                field(ColumnType::UnsignedInteger)
+            )
+            .is_destructive()
+        );
+        assert!(alter_field(field(ColumnType::Double), field(ColumnType::Float)).is_destructive());
+        assert!(
+            alter_field(
+                field(ColumnType::String(100)),
+                field(ColumnType::String(50))
+            )
+            .is_destructive()
+        );
+        assert!(
+            alter_field(field(ColumnType::Text), field(ColumnType::String(100))).is_destructive()
+        );
+        assert!(alter_field(field(ColumnType::Text), field(ColumnType::Integer)).is_destructive());
+        assert!(
+            alter_field(field(ColumnType::Text).null(), field(ColumnType::Text)).is_destructive()
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+    )]
+    async fn operation_alter_field_sqlite() {
+        let test_db = TestDatabase::new_sqlite().await.unwrap();
+        let operation = Operation::alter_field()
+            .table_name(Identifier::new("testapp__test_model"))
+            .old_field(Field::new(Identifier::new("value"), ColumnType::Integer))
+            .new_field(Field::new(Identifier::new("value"), ColumnType::BigInteger))
+            .build();
+
+        let result = operation.forwards(&test_db.database()).await;
+
+        assert!(matches!(
+            result.unwrap_err(),
+            crate::db::DatabaseError::MigrationError(
+                MigrationEngineError::AlterFieldNotSupported { .. }
+            )
+        ));
+    }
+
     #[cot::test]
     #[cfg_attr(
         miri,
@@ -2342,6 +4374,91 @@ mod tests {
         }
     }
 
+    #[cot_macros::dbtest]
+    async fn test_batched_operation(test_db: &mut TestDatabase) {
+        #[migration_op]
+        async fn rename(ctx: BatchContext<'_>) -> Result<u64> {
+            let result = ctx
+                .db
+                .raw(&format!(
+                    "UPDATE testapp__test_model SET name = 'new' WHERE id IN \
+                     (SELECT id FROM (SELECT id FROM testapp__test_model \
+                     WHERE name = 'old' LIMIT {}) AS batch)",
+                    ctx.batch_size
+                ))
+                .await?;
+            Ok(result.rows_affected().0)
+        }
+
+        let database = test_db.database();
+        TestMigration::OPERATIONS[0]
+            .forwards(&database)
+            .await
+            .unwrap();
+        for _ in 0..5 {
+            database
+                .raw("INSERT INTO testapp__test_model (name) VALUES ('old')")
+                .await
+                .unwrap();
+        }
+
+        let operation = Operation::batched(rename)
+            .batch_size(2)
+            .build()
+            .lock_timeout(Duration::from_secs(5));
+        let mut batches = Vec::new();
+        operation
+            .run_forwards(&database, &mut |batch, rows, total_rows| {
+                batches.push((batch, rows, total_rows));
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(batches, [(1, 2, 2), (2, 2, 4), (3, 1, 5)]);
+        let result = database
+            .raw("UPDATE testapp__test_model SET name = 'new' WHERE name = 'old'")
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected().0, 0);
+        assert!(!operation.is_reversible());
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_operation_lock_timeout(test_db: &mut TestDatabase) {
+        let database = test_db.database();
+        TestMigration::OPERATIONS[0]
+            .forwards(&database)
+            .await
+            .unwrap();
+
+        let operation = AddFieldMigration::OPERATIONS[0].lock_timeout(Duration::from_secs(5));
+        operation.forwards(&database).await.unwrap();
+        operation.backwards(&database).await.unwrap();
+    }
+
+    #[test]
+    fn test_migration_progress_display() {
+        assert_eq!(
+            MigrationProgress::Applying {
+                app_name: "testapp",
+                migration_name: "m_0001_initial",
+            }
+            .to_string(),
+            "Applying migration m_0001_initial for app testapp"
+        );
+        assert_eq!(
+            MigrationProgress::BatchProcessed {
+                app_name: "testapp",
+                migration_name: "m_0001_initial",
+                batch: 3,
+                rows: 100,
+                total_rows: 300,
+            }
+            .to_string(),
+            "  batch 3: 100 row(s) processed, 300 in total"
+        );
+    }
+
     #[cot_macros::dbtest]
     async fn test_remove_field_operation_forwards(test_db: &mut TestDatabase) {
         const FIELDS: &[Field] = &[
@@ -2450,4 +4567,178 @@ mod tests {
             .table_name(Identifier::new("testapp__test_model"))
             .build();
     }
+
+    #[test]
+    fn test_operation_rename_field() {
+        let operation = Operation::rename_field()
+            .table_name(Identifier::new("testapp__test_model"))
+            .old_name(Identifier::new("name"))
+            .new_name(Identifier::new("title"))
+            .build();
+
+        assert!(!operation.is_destructive());
+        if let OperationInner::RenameField {
+            table_name,
+            old_name,
+            new_name,
+        } = operation.inner
+        {
+            assert_eq!(table_name.to_string(), "testapp__test_model");
+            assert_eq!(old_name.to_string(), "name");
+            assert_eq!(new_name.to_string(), "title");
+        } else {
+            panic!("Expected OperationInner::RenameField");
+        }
+    }
+
+    #[cot_macros::dbtest]
+    async fn test_rename_field_operation_forwards_backwards(test_db: &mut TestDatabase) {
+        const FIELDS: &[Field] = &[
+            Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        ];
+
+        Operation::create_model()
+            .table_name(Identifier::new("testapp__test_model"))
+            .fields(FIELDS)
+            .build()
+            .forwards(&test_db.database())
+            .await
+            .unwrap();
+
+        let rename_operation = Operation::rename_field()
+            .table_name(Identifier::new("testapp__test_model"))
+            .old_name(Identifier::new("name"))
+            .new_name(Identifier::new("title"))
+            .build();
+
+        rename_operation
+            .forwards(&test_db.database())
+            .await
+            .unwrap();
+        rename_operation
+            .backwards(&test_db.database())
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "`new_name` is required")]
+    fn test_rename_field_builder_missing_new_name() {
+        let _ = RenameFieldBuilder::new()
+            .table_name(Identifier::new("testapp__test_model"))
+            .old_name(Identifier::new("name"))
+            .build();
+    }
+
+    #[crate::db::model(table_name = "check_model")]
+    struct TestModel {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+    }
+
+    #[crate::db::model(table_name = "check_model")]
+    struct RenamedFieldModel {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        title: String,
+    }
+
+    #[crate::db::model(table_name = "other_model")]
+    struct UnknownModel {
+        #[model(primary_key)]
+        id: Auto<i32>,
+    }
+
+    struct CheckModelsMigration;
+
+    impl Migration for CheckModelsMigration {
+        const APP_NAME: &'static str = "testapp";
+        const MIGRATION_NAME: &'static str = "m_0001_initial";
+        const DEPENDENCIES: &'static [MigrationDependency] = &[];
+        const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+            .table_name(TestModel::TABLE_NAME)
+            .fields(&[
+                Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                    .primary_key()
+                    .auto(),
+                Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+            ])
+            .build()];
+    }
+
+    #[test]
+    fn test_check_models_no_drift() {
+        let engine = MigrationEngine::new([CheckModelsMigration]).unwrap();
+
+        let drift = engine.check_models(&[ModelSchema::of::<TestModel>()]);
+
+        assert_eq!(drift, vec![]);
+    }
+
+    #[test]
+    fn test_check_models_missing_table() {
+        let engine = MigrationEngine::new([CheckModelsMigration]).unwrap();
+
+        let drift = engine.check_models(&[ModelSchema::of::<UnknownModel>()]);
+
+        assert_eq!(
+            drift,
+            vec![SchemaDrift::MissingTable {
+                table: UnknownModel::TABLE_NAME.as_str().to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_models_column_drift() {
+        let engine = MigrationEngine::new([CheckModelsMigration]).unwrap();
+
+        let drift = engine.check_models(&[ModelSchema::of::<RenamedFieldModel>()]);
+
+        assert_eq!(
+            drift,
+            vec![
+                SchemaDrift::MissingColumn {
+                    table: TestModel::TABLE_NAME.as_str().to_owned(),
+                    column: "title".to_owned(),
+                },
+                SchemaDrift::UnknownColumn {
+                    table: TestModel::TABLE_NAME.as_str().to_owned(),
+                    column: "name".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_models_after_rename() {
+        struct RenameMigration;
+
+        impl Migration for RenameMigration {
+            const APP_NAME: &'static str = "testapp";
+            const MIGRATION_NAME: &'static str = "m_0002_rename";
+            const DEPENDENCIES: &'static [MigrationDependency] =
+                &[MigrationDependency::migration("testapp", "m_0001_initial")];
+            const OPERATIONS: &'static [Operation] = &[Operation::rename_field()
+                .table_name(TestModel::TABLE_NAME)
+                .old_name(Identifier::new("name"))
+                .new_name(Identifier::new("title"))
+                .build()];
+        }
+
+        #[expect(trivial_casts)] // cast to the correct trait object type
+        let engine = MigrationEngine::new([
+            &CheckModelsMigration as &SyncDynMigration,
+            &RenameMigration as &SyncDynMigration,
+        ])
+        .unwrap();
+
+        let drift = engine.check_models(&[ModelSchema::of::<RenamedFieldModel>()]);
+
+        assert_eq!(drift, vec![]);
+    }
 }
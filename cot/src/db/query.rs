@@ -34,6 +34,7 @@ pub struct Query<T> {
     filter: Option<Expr>,
     limit: Option<u64>,
     offset: Option<u64>,
+    order_by_primary_key: bool,
     phantom_data: PhantomData<fn() -> T>,
 }
 
@@ -44,6 +45,7 @@ impl<T> Debug for Query<T> {
             .field("filter", &self.filter)
             .field("limit", &self.limit)
             .field("offset", &self.offset)
+            .field("order_by_primary_key", &self.order_by_primary_key)
             .field("phantom_data", &self.phantom_data)
             .finish()
     }
@@ -56,6 +58,7 @@ impl<T> Clone for Query<T> {
             filter: self.filter.clone(),
             limit: self.limit,
             offset: self.offset,
+            order_by_primary_key: self.order_by_primary_key,
             phantom_data: PhantomData,
         }
     }
@@ -99,6 +102,7 @@ impl<T: Model> Query<T> {
             filter: None,
             limit: None,
             offset: None,
+            order_by_primary_key: false,
             phantom_data: PhantomData,
         }
     }
@@ -172,6 +176,25 @@ impl<T: Model> Query<T> {
         self
     }
 
+    /// Adds a filter expression to the query, combining it with the existing
+    /// filter (if any) using `AND`.
+    pub(crate) fn and_filter(&mut self, filter: Expr) -> &mut Self {
+        self.filter = Some(match self.filter.take() {
+            Some(existing) => Expr::and(existing, filter),
+            None => filter,
+        });
+        self
+    }
+
+    /// Sorts the results by the primary key in ascending order.
+    ///
+    /// This makes the results stable between queries, which is required for
+    /// pagination.
+    pub(crate) fn order_by_primary_key(&mut self) -> &mut Self {
+        self.order_by_primary_key = true;
+        self
+    }
+
     /// Execute the query and return all results.
     ///
     /// # Errors
@@ -220,6 +243,40 @@ impl<T: Model> Query<T> {
         db.exists(self).await
     }
 
+    /// Cache the results of the query for the given amount of time.
+    ///
+    /// The cached results are invalidated when an instance of the model is
+    /// inserted, updated, or deleted. See the [`db::cache`](crate::db::cache)
+    /// module documentation for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::Timeout;
+    /// use cot::db::{Model, model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[model]
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Category {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// let query = Category::objects().cached(Timeout::After(Duration::from_secs(60)));
+    /// ```
+    #[cfg(feature = "cache")]
+    #[must_use]
+    pub fn cached(&self, expiry: crate::config::Timeout) -> db::cache::CachedQuery<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Sync,
+    {
+        db::cache::CachedQuery::new(self.clone(), expiry)
+    }
+
     /// Delete all rows that match the query.
     ///
     /// # Errors
@@ -249,6 +306,12 @@ impl<T: Model> Query<T> {
             statement.offset(offset);
         }
     }
+
+    pub(super) fn add_order_to_statement(&self, statement: &mut sea_query::SelectStatement) {
+        if self.order_by_primary_key {
+            statement.order_by(T::PRIMARY_KEY_NAME, sea_query::Order::Asc);
+        }
+    }
 }
 
 /// An expression that can be used to filter, update, or delete rows.
@@ -0,0 +1,125 @@
+//! Read-only mode of the database.
+//!
+//! When the read-only mode is enabled, the ORM rejects all the writes with
+//! [`DatabaseError::ReadOnly`], except for the writes to the tables that are
+//! explicitly allowed in the configuration. This is useful during failovers
+//! and maintenance windows, when the database (or its replica the application
+//! is connected to) can't accept writes.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::DatabaseConfig;
+use crate::db::{Database, DatabaseError, Result};
+
+/// The read-only state of the database, shared between all the clones of a
+/// [`Database`] instance.
+#[derive(Debug, Default)]
+pub(super) struct ReadOnlyMode {
+    enabled: AtomicBool,
+    allowed_tables: Vec<String>,
+}
+
+impl ReadOnlyMode {
+    pub(super) fn new(config: &DatabaseConfig) -> Self {
+        Self {
+            enabled: AtomicBool::new(config.read_only),
+            allowed_tables: config.read_only_allowed_tables.clone(),
+        }
+    }
+
+    fn allows_write(&self, table: &str) -> bool {
+        !self.enabled.load(Ordering::Relaxed)
+            || self.allowed_tables.iter().any(|allowed| allowed == table)
+    }
+}
+
+impl Database {
+    /// Enables or disables the read-only mode of the database.
+    ///
+    /// In read-only mode, inserts, updates, and deletes made through the ORM
+    /// fail with [`DatabaseError::ReadOnly`], which is converted into a
+    /// `503 Service Unavailable` response when returned from a request
+    /// handler. Writes to the tables listed in
+    /// [`DatabaseConfig::read_only_allowed_tables`] are still allowed.
+    ///
+    /// The mode is shared between all the clones of this database instance,
+    /// so it can be toggled at runtime, for instance from an admin view or
+    /// when a failover is detected. Raw queries and migrations are not
+    /// affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// db.set_read_only(true);
+    /// assert!(db.is_read_only());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.enabled.store(read_only, Ordering::Relaxed);
+    }
+
+    /// Returns whether the database is in read-only mode.
+    ///
+    /// See [`Self::set_read_only`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// assert!(!db.is_read_only());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.enabled.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn check_writable(&self, table: &str) -> Result<()> {
+        if self.read_only.allows_write(table) {
+            Ok(())
+        } else {
+            Err(DatabaseError::ReadOnly {
+                table: table.to_owned(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_allows_all_writes() {
+        let mode = ReadOnlyMode::default();
+
+        assert!(mode.allows_write("app__post"));
+    }
+
+    #[test]
+    fn enabled_rejects_writes() {
+        let config = DatabaseConfig::builder()
+            .url("sqlite::memory:")
+            .read_only(true)
+            .read_only_allowed_tables(vec!["cot__session".to_owned()])
+            .build();
+        let mode = ReadOnlyMode::new(&config);
+
+        assert!(!mode.allows_write("app__post"));
+        assert!(mode.allows_write("cot__session"));
+
+        mode.enabled.store(false, Ordering::Relaxed);
+        assert!(mode.allows_write("app__post"));
+    }
+}
@@ -1,4 +1,9 @@
-use crate::db::{DatabaseBackend, DatabaseError, Model, Result};
+use std::marker::PhantomData;
+
+use crate::db::query::{Expr, Query};
+use crate::db::{
+    DatabaseBackend, DatabaseError, DbFieldValue, Identifier, Model, Result, ToDbFieldValue,
+};
 
 /// A foreign key to another model.
 ///
@@ -197,6 +202,279 @@ impl From<ForeignKeyOnUpdatePolicy> for sea_query::ForeignKeyAction {
     }
 }
 
+/// A many-to-many relation to another model.
+///
+/// The relation is not stored in the model's table. Instead, the `#[model]`
+/// macro defines a separate model for the join table, and the migration
+/// generator creates the table for it. The join table is named after the
+/// model and the field, e.g. `post_tags` for the `tags` field of the `Post`
+/// model.
+///
+/// The related model instances are accessed through a method with the same
+/// name as the field that the `#[model]` macro generates, which returns a
+/// [`ManyToManyRelation`].
+///
+/// Note that the join table references both models with the `RESTRICT`
+/// policy, so the relations of a model instance have to be removed with
+/// [`ManyToManyRelation::clear`] before the instance can be deleted.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, Database, ManyToMany, Model, model};
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+///     tags: ManyToMany<Tag>,
+/// }
+///
+/// #[model]
+/// struct Tag {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     name: String,
+/// }
+///
+/// async fn tag_post(db: &Database, post: &Post, tag: &Tag) -> cot::Result<Vec<Tag>> {
+///     post.tags().add(db, tag).await?;
+///
+///     Ok(post.tags().all(db).await?)
+/// }
+/// ```
+pub struct ManyToMany<T> {
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Model> ManyToMany<T> {
+    /// Creates a new many-to-many relation field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, ManyToMany, model};
+    ///
+    /// #[model]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     tags: ManyToMany<Tag>,
+    /// }
+    ///
+    /// #[model]
+    /// struct Tag {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    /// }
+    ///
+    /// let post = Post {
+    ///     id: Auto::auto(),
+    ///     tags: ManyToMany::new(),
+    /// };
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Model> Default for ManyToMany<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::fmt::Debug for ManyToMany<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManyToMany").finish_non_exhaustive()
+    }
+}
+
+impl<T> Clone for ManyToMany<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ManyToMany<T> {}
+
+impl<T> PartialEq for ManyToMany<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for ManyToMany<T> {}
+
+/// A model representing the join table of a [`ManyToMany`] relation.
+///
+/// This trait is implemented by the `#[model]` macro for the join models it
+/// defines for the [`ManyToMany`] fields, and typically shouldn't be
+/// implemented manually.
+pub trait ManyToManyJoin: Model + Sync {
+    /// The model the relation is defined in.
+    type Source: Model + Sync;
+    /// The related model.
+    type Target: Model + Sync;
+
+    /// The name of the column referencing the source model.
+    const SOURCE_COLUMN: Identifier;
+    /// The name of the column referencing the target model.
+    const TARGET_COLUMN: Identifier;
+
+    /// Creates a new join model instance linking the given model instances.
+    fn new(source: ForeignKey<Self::Source>, target: ForeignKey<Self::Target>) -> Self;
+
+    /// Returns the reference to the target model instance.
+    fn target(&self) -> &ForeignKey<Self::Target>;
+}
+
+/// The related model instances of a [`ManyToMany`] field of a model instance.
+///
+/// This is returned by the method the `#[model]` macro generates for each
+/// [`ManyToMany`] field. See the [`ManyToMany`] documentation for an
+/// example.
+pub struct ManyToManyRelation<'a, J: ManyToManyJoin> {
+    source: &'a J::Source,
+}
+
+impl<J: ManyToManyJoin> std::fmt::Debug for ManyToManyRelation<'_, J> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManyToManyRelation")
+            .field("join_table", &J::TABLE_NAME)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, J: ManyToManyJoin> ManyToManyRelation<'a, J> {
+    /// Creates a new relation for the given source model instance.
+    ///
+    /// This is used by the `#[model]` macro and typically shouldn't be called
+    /// directly.
+    #[must_use]
+    pub fn new(source: &'a J::Source) -> Self {
+        Self { source }
+    }
+
+    /// Returns all the model instances related to the source model instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if the source model instance
+    /// has not been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn all<DB: DatabaseBackend>(&self, db: &DB) -> Result<Vec<J::Target>> {
+        let joins = Query::<J>::new()
+            .filter(self.source_filter()?)
+            .all(db)
+            .await?;
+
+        let target_filter = joins
+            .iter()
+            .map(|join| -> Result<Expr> {
+                Ok(Expr::eq(
+                    Expr::field(<J::Target as Model>::PRIMARY_KEY_NAME),
+                    primary_key_expr(join.target().primary_key())?,
+                ))
+            })
+            .reduce(|lhs, rhs| Ok(Expr::or(lhs?, rhs?)));
+        let Some(target_filter) = target_filter else {
+            return Ok(Vec::new());
+        };
+
+        Query::<J::Target>::new()
+            .filter(target_filter?)
+            .all(db)
+            .await
+    }
+
+    /// Adds the given model instance to the relation.
+    ///
+    /// Adding an instance that is already related does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if either of the model
+    /// instances has not been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn add<DB: DatabaseBackend>(&self, db: &DB, target: &J::Target) -> Result<()> {
+        let exists = Query::<J>::new()
+            .filter(self.target_filter(target)?)
+            .exists(db)
+            .await?;
+        if exists {
+            return Ok(());
+        }
+
+        let mut join = J::new(ForeignKey::from(self.source), ForeignKey::from(target));
+        db.insert(&mut join).await
+    }
+
+    /// Removes the given model instance from the relation.
+    ///
+    /// Removing an instance that is not related does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if either of the model
+    /// instances has not been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn remove<DB: DatabaseBackend>(&self, db: &DB, target: &J::Target) -> Result<()> {
+        Query::<J>::new()
+            .filter(self.target_filter(target)?)
+            .delete(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Removes all the model instances from the relation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::UnsavedModel`] if the source model instance
+    /// has not been saved to the database.
+    ///
+    /// Returns an error if there was a problem communicating with the database.
+    pub async fn clear<DB: DatabaseBackend>(&self, db: &DB) -> Result<()> {
+        Query::<J>::new()
+            .filter(self.source_filter()?)
+            .delete(db)
+            .await?;
+        Ok(())
+    }
+
+    fn source_filter(&self) -> Result<Expr> {
+        Ok(Expr::eq(
+            Expr::field(J::SOURCE_COLUMN),
+            primary_key_expr(self.source.primary_key())?,
+        ))
+    }
+
+    fn target_filter(&self, target: &J::Target) -> Result<Expr> {
+        Ok(Expr::and(
+            self.source_filter()?,
+            Expr::eq(
+                Expr::field(J::TARGET_COLUMN),
+                primary_key_expr(target.primary_key())?,
+            ),
+        ))
+    }
+}
+
+fn primary_key_expr<T: ToDbFieldValue>(primary_key: &T) -> Result<Expr> {
+    match primary_key.to_db_field_value() {
+        DbFieldValue::Value(value) => Ok(Expr::Value(value)),
+        DbFieldValue::Auto => Err(DatabaseError::UnsavedModel),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,4 +541,18 @@ mod tests {
 
         assert_eq!(fk.primary_key(), &Auto::fixed(1));
     }
+
+    #[test]
+    fn primary_key_expr_unsaved() {
+        let error = primary_key_expr(&Auto::<i32>::auto()).unwrap_err();
+
+        assert!(matches!(error, DatabaseError::UnsavedModel));
+    }
+
+    #[test]
+    fn primary_key_expr_fixed() {
+        let expr = primary_key_expr(&Auto::fixed(1)).unwrap();
+
+        assert_eq!(expr, Expr::Value(1.into()));
+    }
 }
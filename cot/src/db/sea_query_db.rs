@@ -1,7 +1,13 @@
+use std::time::Instant;
+
+use tracing::{Instrument, Span, debug, debug_span, field};
+
 /// Implements the database backend for a specific engine using `SeaQuery`.
 ///
 /// Note that this macro doesn't implement certain engine-specific methods, and
 /// they need to be implemented in a separate `impl` block. These methods are:
+/// * `init`
+/// * `statement_timeout_sql`
 /// * `prepare_values`
 /// * `sea_query_column_type_for`
 macro_rules! impl_sea_query_db_backend {
@@ -11,17 +17,87 @@ macro_rules! impl_sea_query_db_backend {
         #[derive(Debug)]
         pub(super) struct $db_name {
             db_connection: $pool_ty,
+            transaction:
+                Option<tokio::sync::Mutex<Option<sqlx::Transaction<'static, $sqlx_db_ty>>>>,
         }
 
         impl $db_name {
-            pub(super) async fn new(url: &str) -> crate::db::Result<Self> {
-                let db_connection = <$pool_ty>::connect(url).await?;
+            pub(super) async fn new(
+                url: &str,
+                config: &crate::config::DatabaseConfig,
+            ) -> crate::db::Result<Self> {
+                let mut options = sqlx::pool::PoolOptions::<$sqlx_db_ty>::new();
+                if let Some(max_connections) = config.max_connections {
+                    options = options.max_connections(max_connections);
+                }
+                if let Some(min_connections) = config.min_connections {
+                    options = options.min_connections(min_connections);
+                }
+                if let Some(acquire_timeout) = config.acquire_timeout {
+                    options = options.acquire_timeout(acquire_timeout);
+                }
+                if let Some(sql) = config
+                    .statement_timeout
+                    .and_then(Self::statement_timeout_sql)
+                {
+                    options = options.after_connect(move |connection, _metadata| {
+                        let sql = sql.clone();
+                        Box::pin(async move {
+                            sqlx::Executor::execute(connection, sql.as_str()).await?;
+                            Ok(())
+                        })
+                    });
+                }
+                let db_connection = options.connect(url).await?;
 
-                let db = Self { db_connection };
+                let db = Self {
+                    db_connection,
+                    transaction: None,
+                };
                 db.init().await?;
                 Ok(db)
             }
 
+            pub(super) fn in_transaction(&self) -> bool {
+                self.transaction.is_some()
+            }
+
+            pub(super) async fn begin(&self) -> crate::db::Result<Self> {
+                let transaction = self.db_connection.begin().await?;
+                tracing::debug!("Transaction started");
+
+                Ok(Self {
+                    db_connection: self.db_connection.clone(),
+                    transaction: Some(tokio::sync::Mutex::new(Some(transaction))),
+                })
+            }
+
+            pub(super) async fn commit(&self) -> crate::db::Result<()> {
+                self.take_transaction().await?.commit().await?;
+                tracing::debug!("Transaction committed");
+                Ok(())
+            }
+
+            pub(super) async fn rollback(&self) -> crate::db::Result<()> {
+                self.take_transaction().await?.rollback().await?;
+                tracing::debug!("Transaction rolled back");
+                Ok(())
+            }
+
+            async fn take_transaction(
+                &self,
+            ) -> crate::db::Result<sqlx::Transaction<'static, $sqlx_db_ty>> {
+                let transaction = self
+                    .transaction
+                    .as_ref()
+                    .expect("not a transactional database handle");
+                transaction
+                    .lock()
+                    .await
+                    .take()
+                    .ok_or(crate::db::DatabaseError::TransactionFinished)
+            }
+
             pub(super) async fn close(&self) -> crate::db::Result<()> {
                 self.db_connection.close().await;
                 Ok(())
@@ -33,10 +109,28 @@ macro_rules! impl_sea_query_db_backend {
             ) -> crate::db::Result<Option<$row_name>> {
                 let (sql, values) = Self::build_sql(statement);
 
-                let row = Self::sqlx_query_with(&sql, values)
-                    .fetch_optional(&self.db_connection)
-                    .await
-                    .map_err(|err| crate::db::sea_query_db::map_sqlx_error(err))?;
+                let query = Self::sqlx_query_with(&sql, values);
+                let count_rows = |row: &Option<_>| u64::from(row.is_some());
+
+                let row = if let Some(transaction) = &self.transaction {
+                    let mut transaction = transaction.lock().await;
+                    let connection = transaction
+                        .as_deref_mut()
+                        .ok_or(crate::db::DatabaseError::TransactionFinished)?;
+                    crate::db::sea_query_db::instrument_query(
+                        &sql,
+                        query.fetch_optional(connection),
+                        count_rows,
+                    )
+                    .await?
+                } else {
+                    crate::db::sea_query_db::instrument_query(
+                        &sql,
+                        query.fetch_optional(&self.db_connection),
+                        count_rows,
+                    )
+                    .await?
+                };
                 Ok(row.map($row_name::new))
             }
 
@@ -46,12 +140,29 @@ macro_rules! impl_sea_query_db_backend {
             ) -> crate::db::Result<Vec<$row_name>> {
                 let (sql, values) = Self::build_sql(statement);
 
-                let result = Self::sqlx_query_with(&sql, values)
-                    .fetch_all(&self.db_connection)
+                let query = Self::sqlx_query_with(&sql, values);
+                let count_rows = |rows: &Vec<_>| rows.len() as u64;
+
+                let rows = if let Some(transaction) = &self.transaction {
+                    let mut transaction = transaction.lock().await;
+                    let connection = transaction
+                        .as_deref_mut()
+                        .ok_or(crate::db::DatabaseError::TransactionFinished)?;
+                    crate::db::sea_query_db::instrument_query(
+                        &sql,
+                        query.fetch_all(connection),
+                        count_rows,
+                    )
+                    .await?
+                } else {
+                    crate::db::sea_query_db::instrument_query(
+                        &sql,
+                        query.fetch_all(&self.db_connection),
+                        count_rows,
+                    )
                     .await?
-                    .into_iter()
-                    .map($row_name::new)
-                    .collect();
+                };
+                let result = rows.into_iter().map($row_name::new).collect();
                 Ok(result)
             }
 
@@ -62,7 +173,7 @@ macro_rules! impl_sea_query_db_backend {
                 let (sql, mut values) = Self::build_sql(statement);
                 Self::prepare_values(&mut values);
 
-                self.execute_sqlx(Self::sqlx_query_with(&sql, values)).await
+                self.execute_sqlx(&sql, Self::sqlx_query_with(&sql, values)).await
             }
 
             pub(super) async fn execute_schema<T: sea_query::SchemaStatementBuilder>(
@@ -72,7 +183,7 @@ macro_rules! impl_sea_query_db_backend {
                 let sql = statement.build($query_builder);
                 tracing::debug!("Schema modification: {}", sql);
 
-                self.execute_sqlx(sqlx::query(&sql)).await
+                self.execute_sqlx(&sql, sqlx::query(&sql)).await
             }
 
             pub(super) async fn raw_with(
@@ -80,26 +191,44 @@ macro_rules! impl_sea_query_db_backend {
                 sql: &str,
                 values: sea_query_binder::SqlxValues,
             ) -> crate::db::Result<crate::db::StatementResult> {
-                self.execute_sqlx(Self::sqlx_query_with(sql, values)).await
+                self.execute_sqlx(sql, Self::sqlx_query_with(sql, values)).await
             }
 
             async fn execute_sqlx<'a, A>(
                 &self,
+                sql: &str,
                 sqlx_statement: sqlx::query::Query<'a, $sqlx_db_ty, A>,
             ) -> crate::db::Result<crate::db::StatementResult>
             where
                 A: 'a + sqlx::IntoArguments<'a, $sqlx_db_ty>,
             {
-                let result = sqlx_statement
-                    .execute(&self.db_connection)
-                    .await
-                    .map_err(|err| crate::db::sea_query_db::map_sqlx_error(err))?;
+                let count_rows =
+                    |result: &<$sqlx_db_ty as sqlx::Database>::QueryResult| result.rows_affected();
+
+                let result = if let Some(transaction) = &self.transaction {
+                    let mut transaction = transaction.lock().await;
+                    let connection = transaction
+                        .as_deref_mut()
+                        .ok_or(crate::db::DatabaseError::TransactionFinished)?;
+                    crate::db::sea_query_db::instrument_query(
+                        sql,
+                        sqlx_statement.execute(connection),
+                        count_rows,
+                    )
+                    .await?
+                } else {
+                    crate::db::sea_query_db::instrument_query(
+                        sql,
+                        sqlx_statement.execute(&self.db_connection),
+                        count_rows,
+                    )
+                    .await?
+                };
                 let result = crate::db::StatementResult {
                     rows_affected: crate::db::RowsNum(result.rows_affected()),
                     last_inserted_row_id: Self::last_inserted_row_id_for(&result),
                 };
 
-                tracing::debug!("Rows affected: {}", result.rows_affected.0);
                 Ok(result)
             }
 
@@ -174,15 +303,95 @@ macro_rules! impl_sea_query_db_backend {
     };
 }
 
-pub(crate) fn map_sqlx_error(err: sqlx::Error) -> crate::db::DatabaseError {
-    if err
-        .as_database_error()
-        .is_some_and(sqlx::error::DatabaseError::is_unique_violation)
-    {
-        return crate::db::DatabaseError::UniqueViolation;
+pub(super) use impl_sea_query_db_backend;
+
+/// Runs a database query inside a `db_query` tracing span.
+///
+/// The span contains the operation (such as `SELECT` or `INSERT`) and the SQL
+/// statement, without the bound values. Once the query finishes, the number of
+/// rows returned or affected is recorded in the span, and an event with the
+/// time spent executing the query is emitted. Since the span is created as a
+/// child of the current span, the queries made while handling a request are
+/// nested under the span of that request.
+pub(super) async fn instrument_query<T, E>(
+    sql: &str,
+    query: impl Future<Output = Result<T, E>>,
+    count_rows: impl FnOnce(&T) -> u64,
+) -> Result<T, E>
+where
+    E: std::fmt::Display,
+{
+    let span = debug_span!(
+        "db_query",
+        db.operation = statement_operation(sql),
+        db.statement = sql,
+        db.rows = field::Empty,
+    );
+    async move {
+        let start = Instant::now();
+        let result = query.await;
+
+        match &result {
+            Ok(value) => {
+                let rows = count_rows(value);
+                Span::current().record("db.rows", rows);
+                debug!(rows, elapsed = ?start.elapsed(), "Query finished");
+            }
+            Err(error) => debug!(%error, elapsed = ?start.elapsed(), "Query failed"),
+        }
+        result
     }
+    .instrument(span)
+    .await
+}
 
-    crate::db::DatabaseError::from(err)
+/// Returns the operation of the SQL statement, which is its first keyword.
+fn statement_operation(sql: &str) -> &str {
+    sql.split_whitespace().next().unwrap_or_default()
 }
 
-pub(super) use impl_sea_query_db_backend;
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    #[test]
+    fn statement_operation_first_keyword() {
+        assert_eq!(statement_operation("SELECT \"id\" FROM \"test\""), "SELECT");
+        assert_eq!(statement_operation("  INSERT INTO \"test\""), "INSERT");
+        assert_eq!(statement_operation(""), "");
+    }
+
+    #[cot::test]
+    #[traced_test]
+    async fn instrument_query_records_rows() {
+        let result: Result<Vec<i32>, std::fmt::Error> = instrument_query(
+            "SELECT \"id\" FROM \"test\"",
+            async { Ok(vec![1, 2, 3]) },
+            |rows| rows.len() as u64,
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), vec![1, 2, 3]);
+        assert!(logs_contain("db_query"));
+        assert!(logs_contain("db.operation=\"SELECT\""));
+        assert!(logs_contain("rows=3"));
+        assert!(logs_contain("Query finished"));
+    }
+
+    #[cot::test]
+    #[traced_test]
+    async fn instrument_query_logs_failure() {
+        let result: Result<(), std::fmt::Error> = instrument_query(
+            "DELETE FROM \"test\"",
+            async { Err(std::fmt::Error) },
+            |()| 0,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(logs_contain("db.operation=\"DELETE\""));
+        assert!(logs_contain("Query failed"));
+    }
+}
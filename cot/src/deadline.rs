@@ -0,0 +1,306 @@
+//! Request-scoped deadlines.
+//!
+//! A deadline limits how long the processing of a request can take, so that a
+//! single slow downstream service (such as a database or an external API)
+//! can't hold the request for minutes. The deadline is set by the
+//! [`TimeoutMiddleware`](crate::middleware::TimeoutMiddleware) and can be
+//! obtained either with the [`Deadline`] extractor or with
+//! [`Deadline::current`].
+//!
+//! The deadline is respected by:
+//! * the [`Database`](crate::db::Database) queries, which fail with
+//!   [`DatabaseError::DeadlineExceeded`](crate::db::DatabaseError::DeadlineExceeded)
+//!   after the deadline passes,
+//! * the outbound calls wrapped with [`Deadline::run`], such as the HTTP
+//!   requests made to other services.
+
+use std::future::Future;
+use std::time::Duration;
+
+use cot_core::error::impl_into_cot_error;
+use thiserror::Error;
+use tokio::time::Instant;
+
+use crate::request::RequestHead;
+use crate::request::extractors::FromRequestHead;
+
+tokio::task_local! {
+    static CURRENT_DEADLINE: Deadline;
+}
+
+/// The point in time by which the processing of a request should be
+/// finished.
+///
+/// A deadline can also be unset (see [`Deadline::none`]), in which case it
+/// never expires.
+///
+/// # Examples
+///
+/// ```
+/// use cot::deadline::Deadline;
+/// use cot::html::Html;
+///
+/// async fn call_downstream() -> String {
+///     String::from("response from a downstream service")
+/// }
+///
+/// async fn index(deadline: Deadline) -> cot::Result<Html> {
+///     let response = deadline.run(call_downstream()).await?;
+///     Ok(Html::new(response))
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Deadline {
+    instant: Option<Instant>,
+}
+
+impl Deadline {
+    /// Creates a deadline that expires at the given point in time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::deadline::Deadline;
+    /// use tokio::time::Instant;
+    ///
+    /// let deadline = Deadline::at(Instant::now());
+    /// assert!(deadline.is_expired());
+    /// ```
+    #[must_use]
+    pub const fn at(instant: Instant) -> Self {
+        Self {
+            instant: Some(instant),
+        }
+    }
+
+    /// Creates a deadline that expires after the given duration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::deadline::Deadline;
+    ///
+    /// let deadline = Deadline::after(Duration::from_secs(30));
+    /// assert!(!deadline.is_expired());
+    /// ```
+    #[must_use]
+    pub fn after(duration: Duration) -> Self {
+        Self::at(Instant::now() + duration)
+    }
+
+    /// Creates a deadline that never expires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::deadline::Deadline;
+    ///
+    /// let deadline = Deadline::none();
+    /// assert_eq!(deadline.remaining(), None);
+    /// ```
+    #[must_use]
+    pub const fn none() -> Self {
+        Self { instant: None }
+    }
+
+    /// Returns the deadline of the request that is currently being handled.
+    ///
+    /// This returns [`Deadline::none`] if the code is not run as part of a
+    /// request handler, or if the request doesn't have a deadline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::deadline::Deadline;
+    ///
+    /// assert_eq!(Deadline::current(), Deadline::none());
+    /// ```
+    #[must_use]
+    pub fn current() -> Self {
+        CURRENT_DEADLINE
+            .try_with(|deadline| *deadline)
+            .unwrap_or_else(|_| Self::none())
+    }
+
+    /// Returns the point in time when the deadline expires, or `None` if it
+    /// never expires.
+    #[must_use]
+    pub const fn instant(&self) -> Option<Instant> {
+        self.instant
+    }
+
+    /// Returns the time left until the deadline expires, or `None` if it
+    /// never expires.
+    ///
+    /// This is useful for passing the deadline to the clients that support
+    /// timeouts, such as HTTP clients.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::deadline::Deadline;
+    ///
+    /// let deadline = Deadline::after(Duration::from_secs(30));
+    /// assert!(deadline.remaining().unwrap() <= Duration::from_secs(30));
+    /// ```
+    #[must_use]
+    pub fn remaining(&self) -> Option<Duration> {
+        self.instant
+            .map(|instant| instant.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns `true` if the deadline has already expired.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.instant
+            .is_some_and(|instant| instant <= Instant::now())
+    }
+
+    /// Returns the earlier of the two deadlines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::deadline::Deadline;
+    ///
+    /// let deadline = Deadline::after(Duration::from_secs(5));
+    /// assert_eq!(deadline.min(Deadline::none()), deadline);
+    /// ```
+    #[must_use]
+    pub fn min(self, other: Self) -> Self {
+        match (self.instant, other.instant) {
+            (Some(this), Some(other)) => Self::at(this.min(other)),
+            (Some(_), None) => self,
+            (None, _) => other,
+        }
+    }
+
+    /// Runs the given future, failing if it doesn't finish before the
+    /// deadline.
+    ///
+    /// If the deadline has already expired, the future is not run at all.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeadlineExceeded`] if the deadline expires before the future
+    /// finishes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::deadline::Deadline;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let deadline = Deadline::after(Duration::from_millis(10));
+    /// let result = deadline.run(std::future::pending::<()>()).await;
+    /// assert!(result.is_err());
+    /// # }
+    /// ```
+    pub async fn run<F: Future>(self, future: F) -> Result<F::Output, DeadlineExceeded> {
+        if self.is_expired() {
+            return Err(DeadlineExceeded);
+        }
+
+        match self.instant {
+            Some(instant) => tokio::time::timeout_at(instant, future)
+                .await
+                .map_err(|_| DeadlineExceeded),
+            None => Ok(future.await),
+        }
+    }
+
+    /// Runs the given future with this deadline set as the
+    /// [current](Self::current) one.
+    ///
+    /// If there already is a current deadline, the earlier of the two is
+    /// used.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_DEADLINE
+            .scope(self.min(Self::current()), future)
+            .await
+    }
+}
+
+impl Default for Deadline {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl FromRequestHead for Deadline {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        Ok(head.extensions.get::<Self>().copied().unwrap_or_default())
+    }
+}
+
+/// An error returned when a request deadline expires.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[error("the request deadline has been exceeded")]
+pub struct DeadlineExceeded;
+impl_into_cot_error!(DeadlineExceeded, GATEWAY_TIMEOUT);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    #[cot::test]
+    async fn run_before_deadline() {
+        let deadline = Deadline::after(Duration::from_secs(60));
+
+        assert_eq!(deadline.run(async { 42 }).await, Ok(42));
+    }
+
+    #[cot::test]
+    async fn run_after_deadline() {
+        let deadline = Deadline::after(Duration::from_millis(1));
+
+        let result = deadline.run(std::future::pending::<()>()).await;
+
+        assert_eq!(result, Err(DeadlineExceeded));
+    }
+
+    #[cot::test]
+    async fn none_never_expires() {
+        let deadline = Deadline::none();
+
+        assert!(!deadline.is_expired());
+        assert_eq!(deadline.remaining(), None);
+        assert_eq!(deadline.run(async { 42 }).await, Ok(42));
+    }
+
+    #[cot::test]
+    async fn scope_sets_current() {
+        let outer = Deadline::after(Duration::from_secs(10));
+        let inner = Deadline::after(Duration::from_secs(60));
+
+        assert_eq!(Deadline::current(), Deadline::none());
+        outer
+            .scope(async move {
+                assert_eq!(Deadline::current(), outer);
+                inner
+                    .scope(async move { assert_eq!(Deadline::current(), outer) })
+                    .await;
+            })
+            .await;
+    }
+
+    #[cot::test]
+    async fn from_request_head() {
+        let deadline = Deadline::after(Duration::from_secs(10));
+        let mut request = TestRequestBuilder::get("/").build();
+        request.extensions_mut().insert(deadline);
+        let (head, _body) = request.into_parts();
+
+        assert_eq!(Deadline::from_request_head(&head).await.unwrap(), deadline);
+    }
+}
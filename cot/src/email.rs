@@ -24,7 +24,13 @@
 //! email.send(message).await?;
 //! # Ok(()) }
 //! ```
+//!
+//! When the `tasks` feature is enabled, the emails can also be sent in the
+//! background, with the failed deliveries retried later; see
+//! [`EmailQueueConfig`](crate::config::EmailQueueConfig) for details.
 
+#[cfg(feature = "tasks")]
+pub(crate) mod queue;
 pub mod transport;
 
 use std::error::Error as StdError;
@@ -35,11 +41,15 @@ use cot::email::transport::smtp::Smtp;
 use cot_core::error::impl_into_cot_error;
 use derive_builder::Builder;
 use derive_more::with_trait::Debug;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use transport::{BoxedTransport, Transport};
 
 use crate::email::transport::TransportError;
 use crate::email::transport::console::Console;
+#[cfg(feature = "tasks")]
+use crate::tasks::{Tasks, TasksError};
+
 const ERROR_PREFIX: &str = "email message build error:";
 
 /// Represents errors that can occur when sending an email.
@@ -49,6 +59,10 @@ pub enum EmailError {
     /// An error occurred in the transport layer while sending the email.
     #[error(transparent)]
     Transport(TransportError),
+    /// An error occurred while adding the email to the background queue.
+    #[cfg(feature = "tasks")]
+    #[error(transparent)]
+    Queue(TasksError),
 }
 
 impl_into_cot_error!(EmailError);
@@ -57,7 +71,7 @@ impl_into_cot_error!(EmailError);
 pub type EmailResult<T> = Result<T, EmailError>;
 
 /// Raw attachment data to be embedded into an email.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttachmentData {
     /// The filename to display for the attachment.
     pub filename: String,
@@ -67,11 +81,66 @@ pub struct AttachmentData {
     pub data: Vec<u8>,
 }
 
+/// Raw data of a file embedded inline in the HTML body of an email.
+///
+/// Inline attachments are referenced from the HTML body by their content ID,
+/// e.g. `<img src="cid:logo">` for an attachment with the `logo` content ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InlineAttachmentData {
+    /// The content ID used to reference the attachment from the HTML body.
+    pub content_id: String,
+    /// The MIME content type of the attachment (e.g., `image/png`).
+    pub content_type: String,
+    /// The raw bytes of the attachment.
+    pub data: Vec<u8>,
+}
+
+/// Headers that are set by the email message itself and cannot be overridden
+/// with [`EmailMessageBuilder::header`].
+const RESERVED_HEADERS: &[&str] = &[
+    "from",
+    "to",
+    "cc",
+    "bcc",
+    "reply-to",
+    "subject",
+    "date",
+    "mime-version",
+    "content-type",
+    "content-transfer-encoding",
+];
+
 /// A high-level email message representation.
 ///
 /// This struct encapsulates the components of an email, including
 /// subject, body, sender, recipients, and attachments.
-#[derive(Debug, Clone, Builder)]
+///
+/// Besides the plain text body, a message can have an HTML body, which is sent
+/// as an alternative to the plain text version. Images referenced from the HTML
+/// body can be embedded in the message as inline attachments.
+///
+/// # Examples
+///
+/// ```
+/// use cot::common_types::Email;
+/// use cot::email::{EmailMessage, InlineAttachmentData};
+///
+/// let message = EmailMessage::builder()
+///     .from(Email::try_from("no-reply@example.com").unwrap())
+///     .to(vec![Email::try_from("user@example.com").unwrap()])
+///     .subject("Greetings")
+///     .body("Hello from cot!")
+///     .html(r#"<p>Hello from <img src="cid:logo" alt="cot">!</p>"#)
+///     .inline_attachments(vec![InlineAttachmentData {
+///         content_id: "logo".to_owned(),
+///         content_type: "image/png".to_owned(),
+///         data: vec![0x89, b'P', b'N', b'G'],
+///     }])
+///     .header("X-Campaign", "welcome")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Builder, Serialize, Deserialize)]
 #[builder(build_fn(skip))]
 pub struct EmailMessage {
     /// The subject of the email.
@@ -80,6 +149,9 @@ pub struct EmailMessage {
     /// The body content of the email.
     #[builder(setter(into))]
     body: String,
+    /// The HTML body content of the email.
+    #[builder(setter(into, strip_option))]
+    html: Option<String>,
     /// The sender's email address.
     from: crate::common_types::Email,
     /// The primary recipients of the email.
@@ -92,6 +164,10 @@ pub struct EmailMessage {
     reply_to: Vec<crate::common_types::Email>,
     /// Attachments to include with the email.
     attachments: Vec<AttachmentData>,
+    /// Attachments embedded inline in the HTML body of the email.
+    inline_attachments: Vec<InlineAttachmentData>,
+    /// Custom headers to include with the email.
+    headers: Vec<(String, String)>,
 }
 
 impl EmailMessage {
@@ -118,12 +194,38 @@ impl EmailMessage {
 }
 
 impl EmailMessageBuilder {
+    /// Add a custom header to the email.
+    ///
+    /// Headers set by the message itself, such as `From`, `Subject` or
+    /// `Content-Type`, cannot be overridden; trying to do so makes
+    /// [`build`](Self::build) return an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::common_types::Email;
+    /// use cot::email::EmailMessage;
+    ///
+    /// let message = EmailMessage::builder()
+    ///     .from(Email::try_from("no-reply@example.com").unwrap())
+    ///     .header("X-Priority", "1")
+    ///     .header("List-Unsubscribe", "<https://example.com/unsubscribe>")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn header<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) -> &mut Self {
+        self.headers
+            .get_or_insert_with(Vec::new)
+            .push((name.into(), value.into()));
+        self
+    }
+
     /// Build the [`EmailMessage`], ensuring required fields are set.
     ///
     /// # Errors
     ///
     /// This method returns an [`EmailMessageError`] if required fields are
-    /// missing.
+    /// missing or if any of the custom headers is invalid.
     ///
     /// # Examples
     ///
@@ -147,26 +249,48 @@ impl EmailMessageBuilder {
 
         let subject = self.subject.clone().unwrap_or_default();
         let body = self.body.clone().unwrap_or_default();
+        let html = self.html.clone().flatten();
 
         let to = self.to.clone().unwrap_or_default();
         let cc = self.cc.clone().unwrap_or_default();
         let bcc = self.bcc.clone().unwrap_or_default();
         let reply_to = self.reply_to.clone().unwrap_or_default();
         let attachments = self.attachments.clone().unwrap_or_default();
+        let inline_attachments = self.inline_attachments.clone().unwrap_or_default();
+
+        let headers = self.headers.clone().unwrap_or_default();
+        for (name, _) in &headers {
+            if !is_valid_header_name(name) {
+                return Err(EmailMessageError::InvalidHeader(name.clone()));
+            }
+        }
 
         Ok(EmailMessage {
             subject,
             body,
+            html,
             from,
             to,
             cc,
             bcc,
             reply_to,
             attachments,
+            inline_attachments,
+            headers,
         })
     }
 }
 
+fn is_valid_header_name(name: &str) -> bool {
+    // RFC 5322, section 2.2: printable US-ASCII characters, except colon
+    !name.is_empty()
+        && name.len() <= 76
+        && name.bytes().all(|b| b.is_ascii_graphic() && b != b':')
+        && !RESERVED_HEADERS
+            .iter()
+            .any(|reserved| name.eq_ignore_ascii_case(reserved))
+}
+
 /// Errors that can occur while building an email message.
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -180,6 +304,9 @@ pub enum EmailMessageError {
     /// A required field is missing in the email message.
     #[error("{ERROR_PREFIX} The `{0}` field is required but was not set")]
     MissingField(String),
+    /// A custom header has an invalid or reserved name.
+    #[error("{ERROR_PREFIX} invalid or reserved header name: `{0}`")]
+    InvalidHeader(String),
 }
 
 impl_into_cot_error!(EmailMessageError);
@@ -187,7 +314,9 @@ impl_into_cot_error!(EmailMessageError);
 #[derive(Debug)]
 struct EmailImpl {
     #[debug("..")]
-    transport: Box<dyn BoxedTransport>,
+    transport: Arc<dyn BoxedTransport>,
+    #[cfg(feature = "tasks")]
+    queue: Option<Tasks>,
 }
 
 /// A high-level email interface for sending emails.
@@ -232,16 +361,62 @@ impl Email {
     /// let email = Email::new(Console::new());
     /// ```
     pub fn new(transport: impl Transport) -> Self {
-        let transport: Box<dyn BoxedTransport> = Box::new(transport);
+        let transport: Arc<dyn BoxedTransport> = Arc::new(transport);
+        Self {
+            inner: Arc::new(EmailImpl {
+                transport,
+                #[cfg(feature = "tasks")]
+                queue: None,
+            }),
+        }
+    }
+
+    /// Returns an email sender that sends the emails in the background.
+    ///
+    /// The messages passed to [`send`](Self::send) and
+    /// [`send_multiple`](Self::send_multiple) of the returned sender are
+    /// added to the given task queue and sent by the
+    /// [`TaskWorker`](crate::tasks::TaskWorker) using the same transport as
+    /// this sender. The messages that fail to be sent are retried with an
+    /// exponential backoff, as configured in
+    /// [`EmailConfig::queue`](crate::config::EmailConfig::queue).
+    ///
+    /// This is done automatically for the project's email sender when
+    /// [`EmailQueueConfig::enabled`](crate::config::EmailQueueConfig::enabled)
+    /// is set, so this typically doesn't need to be called directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::email::Email;
+    /// use cot::email::transport::console::Console;
+    /// use cot::tasks::Tasks;
+    /// use cot::tasks::queue::memory::Memory;
+    ///
+    /// let email = Email::new(Console::new()).with_queue(Tasks::new(Memory::new()));
+    /// ```
+    #[cfg(feature = "tasks")]
+    #[must_use]
+    pub fn with_queue(&self, tasks: Tasks) -> Self {
         Self {
-            inner: Arc::new(EmailImpl { transport }),
+            inner: Arc::new(EmailImpl {
+                transport: Arc::clone(&self.inner.transport),
+                queue: Some(tasks),
+            }),
         }
     }
+
     /// Send a single [`EmailMessage`]
     ///
+    /// If the sender uses a background queue (see
+    /// [`with_queue`](Self::with_queue)), the message is only added to the
+    /// queue.
+    ///
     /// # Errors
     ///
-    /// Returns an [`EmailError::Transport`] error if sending the email fails.
+    /// Returns an [`EmailError::Transport`] error if sending the email fails,
+    /// or an [`EmailError::Queue`] error if the email could not be added to
+    /// the background queue.
     ///
     /// # Examples
     ///
@@ -264,18 +439,29 @@ impl Email {
     /// # }
     /// ```
     pub async fn send(&self, message: EmailMessage) -> EmailResult<()> {
-        self.inner
-            .transport
-            .send(&[message])
-            .await
-            .map_err(EmailError::Transport)
+        #[cfg(feature = "tasks")]
+        if let Some(tasks) = &self.inner.queue {
+            return tasks
+                .enqueue(queue::SendEmail::new(message))
+                .await
+                .map_err(EmailError::Queue);
+        }
+
+        self.deliver(&[message]).await
     }
 
     /// Send multiple emails in sequence.
     ///
+    /// If the sender uses a background queue (see
+    /// [`with_queue`](Self::with_queue)), each message is added to the queue
+    /// separately, so that a failure to send one of them doesn't cause the
+    /// others to be sent again.
+    ///
     /// # Errors
     ///
-    /// Returns an [`EmailError::Transport`] if sending any of the emails fails.
+    /// Returns an [`EmailError::Transport`] if sending any of the emails fails,
+    /// or an [`EmailError::Queue`] error if any of the emails could not be
+    /// added to the background queue.
     ///
     /// # Examples
     ///
@@ -305,6 +491,23 @@ impl Email {
     /// # }
     /// ```
     pub async fn send_multiple(&self, messages: &[EmailMessage]) -> EmailResult<()> {
+        #[cfg(feature = "tasks")]
+        if let Some(tasks) = &self.inner.queue {
+            for message in messages {
+                tasks
+                    .enqueue(queue::SendEmail::new(message.clone()))
+                    .await
+                    .map_err(EmailError::Queue)?;
+            }
+            return Ok(());
+        }
+
+        self.deliver(messages).await
+    }
+
+    /// Sends the messages using the transport right away, bypassing the
+    /// background queue.
+    async fn deliver(&self, messages: &[EmailMessage]) -> EmailResult<()> {
         self.inner
             .transport
             .send(messages)
@@ -344,8 +547,14 @@ impl Email {
                     Self::new(console)
                 }
 
-                EmailTransportTypeConfig::Smtp { url, mechanism } => {
-                    let smtp = Smtp::new(url, *mechanism).map_err(EmailError::Transport)?;
+                EmailTransportTypeConfig::Smtp {
+                    url,
+                    mechanism,
+                    timeout,
+                    pool,
+                } => {
+                    let smtp = Smtp::with_config(url, *mechanism, *timeout, pool)
+                        .map_err(EmailError::Transport)?;
                     Self::new(smtp)
                 }
             }
@@ -357,7 +566,7 @@ impl Email {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{EmailTransportConfig, EmailUrl};
+    use crate::config::{EmailTransportConfig, EmailUrl, SmtpPoolConfig};
     use crate::email::transport::smtp::Mechanism;
 
     #[cot::test]
@@ -387,30 +596,69 @@ mod tests {
         assert!(msg.bcc.is_empty());
         assert!(msg.reply_to.is_empty());
         assert!(msg.attachments.is_empty());
+        assert!(msg.html.is_none());
+        assert!(msg.inline_attachments.is_empty());
+        assert!(msg.headers.is_empty());
+    }
+
+    #[cot::test]
+    async fn builder_collects_custom_headers() {
+        let msg = EmailMessage::builder()
+            .from(crate::common_types::Email::new("sender@example.com").unwrap())
+            .header("X-Priority", "1")
+            .header("X-Campaign", "welcome")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            msg.headers,
+            vec![
+                ("X-Priority".to_owned(), "1".to_owned()),
+                ("X-Campaign".to_owned(), "welcome".to_owned()),
+            ]
+        );
+    }
+
+    #[cot::test]
+    async fn builder_errors_on_invalid_header_name() {
+        for name in ["", "X Priority", "X-Priority:", "Subject", "content-type"] {
+            let err = EmailMessage::builder()
+                .from(crate::common_types::Email::new("sender@example.com").unwrap())
+                .header(name, "value")
+                .build()
+                .unwrap_err();
+
+            assert_eq!(
+                err.to_string(),
+                format!("email message build error: invalid or reserved header name: `{name}`")
+            );
+        }
     }
 
     #[cot::test]
     async fn from_config_console_builds() {
         use crate::config::{EmailConfig, EmailTransportTypeConfig};
-        let cfg = EmailConfig {
-            transport: EmailTransportConfig {
+        let cfg = EmailConfig::builder()
+            .transport(EmailTransportConfig {
                 transport_type: EmailTransportTypeConfig::Console,
-            },
-        };
+            })
+            .build();
         let email = Email::from_config(&cfg);
         assert!(email.is_ok());
     }
 
     #[cot::test]
     async fn from_config_smtp_builds() {
-        let cfg = EmailConfig {
-            transport: EmailTransportConfig {
+        let cfg = EmailConfig::builder()
+            .transport(EmailTransportConfig {
                 transport_type: EmailTransportTypeConfig::Smtp {
                     url: EmailUrl::from("smtp://localhost:1025"),
                     mechanism: Mechanism::Plain,
+                    timeout: None,
+                    pool: SmtpPoolConfig::default(),
                 },
-            },
-        };
+            })
+            .build();
         let email = Email::from_config(&cfg);
         assert!(email.is_ok());
     }
@@ -0,0 +1,179 @@
+//! Sending emails in the background.
+//!
+//! When the email queue is enabled (see
+//! [`EmailQueueConfig`](crate::config::EmailQueueConfig)), the messages are
+//! wrapped in a [`SendEmail`] task and sent by the
+//! [`TaskWorker`](crate::tasks::TaskWorker). The messages that fail to be sent
+//! are enqueued again with an exponentially growing delay.
+
+use std::slice;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::ProjectContext;
+use crate::config::EmailQueueConfig;
+use crate::email::EmailMessage;
+use crate::tasks::Task;
+
+/// The delay before the first retry if not configured otherwise.
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(10);
+/// The maximum delay between two retries if not configured otherwise.
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// A background task that sends a single email message.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SendEmail {
+    message: EmailMessage,
+    attempt: u32,
+}
+
+impl SendEmail {
+    pub(crate) fn new(message: EmailMessage) -> Self {
+        Self {
+            message,
+            attempt: 0,
+        }
+    }
+}
+
+impl Task for SendEmail {
+    const NAME: &'static str = "cot_send_email";
+
+    async fn run(self, context: &ProjectContext) -> crate::Result<()> {
+        let Err(error) = context
+            .email()
+            .deliver(slice::from_ref(&self.message))
+            .await
+        else {
+            return Ok(());
+        };
+
+        let config = &context.config().email.queue;
+        if self.attempt >= config.max_retries {
+            return Err(error.into());
+        }
+
+        let delay = backoff(config, self.attempt);
+        warn!(
+            attempt = self.attempt + 1,
+            ?delay,
+            "Failed to send an email, retrying later: {error}"
+        );
+        context
+            .tasks()
+            .enqueue_in(
+                Self {
+                    message: self.message,
+                    attempt: self.attempt + 1,
+                },
+                delay,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Returns the delay before retrying to send a message after the given
+/// (zero-based) attempt has failed.
+fn backoff(config: &EmailQueueConfig, attempt: u32) -> Duration {
+    let initial = config.initial_backoff.unwrap_or(DEFAULT_INITIAL_BACKOFF);
+    let max = config.max_backoff.unwrap_or(DEFAULT_MAX_BACKOFF);
+
+    2u32.checked_pow(attempt)
+        .and_then(|factor| initial.checked_mul(factor))
+        .unwrap_or(Duration::MAX)
+        .min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::Project;
+    use crate::common_types::Email;
+    use crate::config::{
+        EmailConfig, EmailTransportConfig, EmailTransportTypeConfig, EmailUrl, ProjectConfig,
+        SmtpPoolConfig,
+    };
+    use crate::email::transport::smtp::Mechanism;
+    use crate::project::Bootstrapper;
+    use crate::tasks::TaskWorker;
+
+    #[test]
+    fn backoff_defaults() {
+        let config = EmailQueueConfig::default();
+
+        assert_eq!(backoff(&config, 0), Duration::from_secs(10));
+        assert_eq!(backoff(&config, 1), Duration::from_secs(20));
+        assert_eq!(backoff(&config, 2), Duration::from_secs(40));
+        assert_eq!(backoff(&config, 20), Duration::from_secs(60 * 60));
+        assert_eq!(backoff(&config, u32::MAX), Duration::from_secs(60 * 60));
+    }
+
+    #[test]
+    fn backoff_configured() {
+        let config = EmailQueueConfig::builder()
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(5))
+            .build();
+
+        assert_eq!(backoff(&config, 0), Duration::from_secs(1));
+        assert_eq!(backoff(&config, 2), Duration::from_secs(4));
+        assert_eq!(backoff(&config, 3), Duration::from_secs(5));
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore = "unsupported operation: socket")]
+    async fn failed_email_is_retried() {
+        struct TestProject;
+        impl Project for TestProject {}
+
+        let config = ProjectConfig::builder()
+            .email(
+                EmailConfig::builder()
+                    .transport(
+                        EmailTransportConfig::builder()
+                            .transport_type(EmailTransportTypeConfig::Smtp {
+                                url: EmailUrl::from("smtp://localhost:1"),
+                                mechanism: Mechanism::Plain,
+                                timeout: Some(Duration::from_secs(1)),
+                                pool: SmtpPoolConfig::default(),
+                            })
+                            .build(),
+                    )
+                    .queue(
+                        EmailQueueConfig::builder()
+                            .enabled(true)
+                            .max_retries(2)
+                            .initial_backoff(Duration::ZERO)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+        let project = Bootstrapper::new(TestProject)
+            .with_config(config)
+            .boot()
+            .await
+            .unwrap()
+            .finish();
+        let context: Arc<ProjectContext> = Arc::new(project.context);
+
+        let message = EmailMessage::builder()
+            .from(Email::new("from@example.com").unwrap())
+            .to(vec![Email::new("to@example.com").unwrap()])
+            .subject("Hello")
+            .build()
+            .unwrap();
+        context.email().send(message).await.unwrap();
+
+        let worker = TaskWorker::new(Arc::clone(&context), project.task_registry);
+        // the first attempt and two retries
+        assert_eq!(worker.run_pending().await.unwrap(), 3);
+        assert_eq!(worker.run_pending().await.unwrap(), 0);
+    }
+}
@@ -133,6 +133,9 @@ impl fmt::Display for EmailMessage {
                 &self.subject
             }
         )?;
+        for (name, value) in &self.headers {
+            writeln!(f, "{name}: {value}")?;
+        }
         writeln!(
             f,
             "────────────────────────────────────────────────────────"
@@ -142,6 +145,13 @@ impl fmt::Display for EmailMessage {
         } else {
             writeln!(f, "{}", self.body.trim_end())?;
         }
+        if let Some(html) = &self.html {
+            writeln!(
+                f,
+                "───────────────────────── HTML ─────────────────────────"
+            )?;
+            writeln!(f, "{}", html.trim_end())?;
+        }
         writeln!(
             f,
             "────────────────────────────────────────────────────────"
@@ -160,6 +170,18 @@ impl fmt::Display for EmailMessage {
                 )?;
             }
         }
+        if !self.inline_attachments.is_empty() {
+            writeln!(f, "Inline attachments ({}):", self.inline_attachments.len())?;
+            for a in &self.inline_attachments {
+                writeln!(
+                    f,
+                    "  - cid:{} ({} bytes, {})",
+                    a.content_id,
+                    a.data.len(),
+                    a.content_type
+                )?;
+            }
+        }
         writeln!(
             f,
             "════════════════════════════════════════════════════════════════"
@@ -172,7 +194,7 @@ impl fmt::Display for EmailMessage {
 mod tests {
     use super::*;
     use crate::common_types::Email as Addr;
-    use crate::email::{AttachmentData, Email};
+    use crate::email::{AttachmentData, Email, InlineAttachmentData};
 
     #[cot::test]
     async fn console_error_to_transport_error() {
@@ -200,7 +222,14 @@ mod tests {
             .bcc(vec![Addr::new("bcc@example.com").unwrap()])
             .reply_to(vec![Addr::new("reply@example.com").unwrap()])
             .subject("Subject Line")
+            .header("X-Campaign", "welcome")
             .body("Hello body\n")
+            .html("<p>Hello body</p>\n")
+            .inline_attachments(vec![InlineAttachmentData {
+                content_id: "logo".into(),
+                content_type: "image/png".into(),
+                data: vec![0u8; 4],
+            }])
             .attachments(vec![
                 AttachmentData {
                     filename: "a.txt".into(),
@@ -234,11 +263,17 @@ mod tests {
         assert!(rendered.contains("Bcc     : bcc@example.com"));
         assert!(rendered.contains("Reply-To: reply@example.com"));
 
+        assert!(rendered.contains("X-Campaign: welcome"));
+
         assert!(rendered.contains("Hello body"));
+        assert!(rendered.contains("HTML"));
+        assert!(rendered.contains("<p>Hello body</p>"));
 
         assert!(rendered.contains("Attachments (2):"));
         assert!(rendered.contains("  - a.txt (3 bytes, text/plain)"));
         assert!(rendered.contains("  - b.pdf (10 bytes, application/pdf)"));
+        assert!(rendered.contains("Inline attachments (1):"));
+        assert!(rendered.contains("  - cid:logo (4 bytes, image/png)"));
 
         assert!(
             rendered.contains("════════════════════════════════════════════════════════════════")
@@ -273,5 +308,7 @@ mod tests {
 
         assert!(rendered.contains("<empty>"));
         assert!(rendered.contains("Attachments: -"));
+        assert!(!rendered.contains("HTML"));
+        assert!(!rendered.contains("Inline attachments"));
     }
 }
@@ -5,7 +5,10 @@
 //! including 404 Not Found errors, uncaught panics, and custom error pages.
 
 pub mod handler;
+#[cfg(feature = "db")]
+pub mod log;
 mod not_found;
+pub mod stream;
 
 #[doc(inline)]
 pub use cot_core::error::{MethodNotAllowed, UncaughtPanic};
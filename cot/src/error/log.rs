@@ -0,0 +1,564 @@
+//! Database-backed log of the errors returned by the request handlers.
+//!
+//! This module provides a model for storing recent unhandled errors in the
+//! database, a middleware that records them, and an app that registers the
+//! model and its migrations. The recorded errors can be browsed in the admin
+//! panel, which gives small deployments basic error visibility without having
+//! to set up an external error tracking service.
+pub mod migrations;
+
+use std::task::{Context, Poll};
+
+use cot::db::migrations::{ModelSchema, SyncDynMigration};
+use futures_core::future::BoxFuture;
+use http::header;
+use tower::Service;
+use tracing::warn;
+
+use crate::auth::Auth;
+use crate::db::query::{Expr, Query};
+use crate::db::{Auto, Database, Model, model};
+use crate::error::handler::RequestOuterError;
+use crate::request::{Request, RequestExt};
+use crate::response::Response;
+use crate::{App, Error};
+
+/// The default number of errors kept in the database by
+/// [`ErrorLogMiddleware`].
+pub const DEFAULT_CAPACITY: u32 = 100;
+
+/// Headers that are not stored in the error log, as they contain credentials.
+const REDACTED_HEADERS: [header::HeaderName; 4] = [
+    header::AUTHORIZATION,
+    header::COOKIE,
+    header::PROXY_AUTHORIZATION,
+    header::SET_COOKIE,
+];
+
+/// An error stored in the database by [`ErrorLogMiddleware`].
+#[derive(Debug, Clone)]
+#[model]
+pub struct ErrorLogEntry {
+    #[model(primary_key)]
+    pub(crate) id: Auto<i32>,
+    pub(crate) created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub(crate) status_code: i32,
+    pub(crate) message: String,
+    pub(crate) stack: String,
+    pub(crate) method: String,
+    pub(crate) uri: String,
+    pub(crate) headers: String,
+    pub(crate) user: Option<String>,
+}
+
+impl ErrorLogEntry {
+    fn from_request(request: &Request, error: &Error) -> Self {
+        let stack = error
+            .backtrace()
+            .frames()
+            .iter()
+            .map(|frame| format!("{} at {}", frame.symbol_name(), frame.location()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = if REDACTED_HEADERS.contains(name) {
+                    "[redacted]"
+                } else {
+                    value.to_str().unwrap_or("[binary]")
+                };
+                format!("{name}: {value}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let user = request.extensions().get::<Auth>().and_then(|auth| {
+            let user = auth.user();
+            user.username()
+                .map(std::borrow::Cow::into_owned)
+                .or_else(|| user.id().map(|id| id.to_string()))
+        });
+
+        Self {
+            id: Auto::auto(),
+            created_at: chrono::Utc::now().fixed_offset(),
+            status_code: i32::from(error.status_code().as_u16()),
+            message: error.to_string(),
+            stack,
+            method: request.method().to_string(),
+            uri: request.uri().to_string(),
+            headers,
+            user,
+        }
+    }
+
+    /// Returns the time the error occurred at.
+    #[must_use]
+    pub fn created_at(&self) -> chrono::DateTime<chrono::FixedOffset> {
+        self.created_at
+    }
+
+    /// Returns the HTTP status code of the error.
+    #[must_use]
+    pub fn status_code(&self) -> i32 {
+        self.status_code
+    }
+
+    /// Returns the error message.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the stack trace captured when the error was created, one frame
+    /// per line.
+    #[must_use]
+    pub fn stack(&self) -> &str {
+        &self.stack
+    }
+
+    /// Returns the HTTP method of the request that caused the error.
+    #[must_use]
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns the URI of the request that caused the error.
+    #[must_use]
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns the headers of the request that caused the error, one header
+    /// per line. Headers containing credentials are redacted.
+    #[must_use]
+    pub fn headers(&self) -> &str {
+        &self.headers
+    }
+
+    /// Returns the user that made the request, if they were authenticated and
+    /// [`AuthMiddleware`](crate::middleware::AuthMiddleware) was active for
+    /// the error handler.
+    #[must_use]
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// Returns the recently logged errors, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database query fails.
+    pub async fn recent(database: &Database) -> cot::Result<Vec<Self>> {
+        let mut entries = Self::objects().all(database).await?;
+        // the table is capped, so sorting in memory is fine
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.id.unwrap()));
+        Ok(entries)
+    }
+}
+
+async fn record_error(
+    database: &Database,
+    mut entry: ErrorLogEntry,
+    capacity: u32,
+) -> cot::Result<()> {
+    database.insert(&mut entry).await?;
+
+    if let Auto::Fixed(id) = entry.id {
+        let oldest_kept = id.saturating_sub(capacity.try_into().unwrap_or(i32::MAX));
+        Query::<ErrorLogEntry>::new()
+            .filter(Expr::lte(Expr::field("id"), Expr::value(oldest_kept)))
+            .delete(database)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// A middleware that stores the errors in the database.
+///
+/// This middleware should be added to the error handler using
+/// [`RootHandlerBuilder::error_handler_middleware`](crate::project::RootHandlerBuilder::error_handler_middleware).
+/// Every server error (including the panics) passed to the error handler is
+/// then stored as an [`ErrorLogEntry`], along with the request head and the
+/// user that made the request. Client errors, such as `404 Not Found`, are not
+/// stored by default, as any client can cause them and they would quickly push
+/// the server errors out of the log; see
+/// [`record_client_errors`](Self::record_client_errors). Only the most recent
+/// errors are kept; see [`capacity`](Self::capacity).
+///
+/// The errors are not recorded in debug mode, since they are displayed on the
+/// error page anyway. [`ErrorLogApp`] needs to be registered for the database
+/// table to be created.
+///
+/// # Examples
+///
+/// ```
+/// use cot::Project;
+/// use cot::error::log::ErrorLogMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         _context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .error_handler_middleware(ErrorLogMiddleware::new())
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct ErrorLogMiddleware {
+    capacity: u32,
+    record_client_errors: bool,
+}
+
+impl ErrorLogMiddleware {
+    /// Creates a new [`ErrorLogMiddleware`] keeping [`DEFAULT_CAPACITY`]
+    /// errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::error::log::ErrorLogMiddleware;
+    ///
+    /// let middleware = ErrorLogMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            record_client_errors: false,
+        }
+    }
+
+    /// Sets the maximum number of errors kept in the database. When a new
+    /// error is recorded, the oldest ones beyond this number are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::error::log::ErrorLogMiddleware;
+    ///
+    /// let middleware = ErrorLogMiddleware::new().capacity(500);
+    /// ```
+    #[must_use]
+    pub fn capacity(mut self, capacity: u32) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets whether the client errors (`4xx` status codes) are stored as well.
+    /// By default, only the server errors are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::error::log::ErrorLogMiddleware;
+    ///
+    /// let middleware = ErrorLogMiddleware::new().record_client_errors(true);
+    /// ```
+    #[must_use]
+    pub fn record_client_errors(mut self, record_client_errors: bool) -> Self {
+        self.record_client_errors = record_client_errors;
+        self
+    }
+}
+
+impl Default for ErrorLogMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for ErrorLogMiddleware {
+    type Service = ErrorLogService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ErrorLogService {
+            inner,
+            capacity: self.capacity,
+            record_client_errors: self.record_client_errors,
+        }
+    }
+}
+
+/// Service that stores the errors in the database.
+///
+/// Used by [`ErrorLogMiddleware`].
+#[derive(Debug, Clone)]
+pub struct ErrorLogService<S> {
+    inner: S,
+    capacity: u32,
+    record_client_errors: bool,
+}
+
+impl<S> Service<Request> for ErrorLogService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let record = if request.project_config().debug {
+            None
+        } else {
+            let record_client_errors = self.record_client_errors;
+            let error = request
+                .extensions()
+                .get::<RequestOuterError>()
+                .filter(|error| {
+                    let status_code = error.status_code();
+                    status_code.is_server_error()
+                        || (record_client_errors && status_code.is_client_error())
+                });
+            let database = request.context().try_database();
+            error.zip(database).map(|(error, database)| {
+                (
+                    ErrorLogEntry::from_request(&request, error),
+                    database.clone(),
+                )
+            })
+        };
+        let capacity = self.capacity;
+        let response = self.inner.call(request);
+
+        Box::pin(async move {
+            if let Some((entry, database)) = record
+                && let Err(error) = record_error(&database, entry, capacity).await
+            {
+                warn!(?error, "failed to store the error in the error log");
+            }
+
+            response.await
+        })
+    }
+}
+
+/// An app that stores the errors logged by [`ErrorLogMiddleware`] in the
+/// database.
+///
+/// This app registers the error log model and its migrations. The logged
+/// errors can be browsed in the admin panel.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cot::config::{DatabaseConfig, ProjectConfig};
+/// use cot::error::log::{ErrorLogApp, ErrorLogMiddleware};
+/// use cot::project::{MiddlewareContext, RegisterAppsContext, RootHandler, RootHandlerBuilder};
+/// use cot::{AppBuilder, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn config(&self, config_name: &str) -> cot::Result<ProjectConfig> {
+///         Ok(ProjectConfig::builder()
+///             .database(DatabaseConfig::builder().url("sqlite::memory:").build())
+///             .build())
+///     }
+///
+///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+///         apps.register(ErrorLogApp::new());
+///     }
+///
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         _context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .error_handler_middleware(ErrorLogMiddleware::new())
+///             .build()
+///     }
+/// }
+///
+/// #[cot::main]
+/// fn main() -> impl Project {
+///     MyProject
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
+pub struct ErrorLogApp;
+
+impl ErrorLogApp {
+    /// Create a new instance of the error log app.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::error::log::ErrorLogApp;
+    /// let app = ErrorLogApp::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for ErrorLogApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl App for ErrorLogApp {
+    fn name(&self) -> &'static str {
+        "cot_error_log"
+    }
+
+    fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
+        cot::db::migrations::wrap_migrations(migrations::MIGRATIONS)
+    }
+
+    fn models(&self) -> Vec<ModelSchema> {
+        vec![ModelSchema::of::<ErrorLogEntry>()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::App;
+    use crate::StatusCode;
+
+    #[test]
+    fn error_log_app_basic_behavior() {
+        let app = ErrorLogApp::default();
+
+        assert_eq!(app.name(), "cot_error_log");
+        assert!(!app.migrations().is_empty());
+    }
+
+    #[test]
+    fn error_log_app_models_match_migrations() {
+        let app = ErrorLogApp::new();
+
+        let engine = crate::db::migrations::MigrationEngine::new(app.migrations()).unwrap();
+
+        assert_eq!(engine.check_models(&app.models()), vec![]);
+    }
+
+    #[test]
+    fn error_log_entry_redacts_credentials() {
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/checkout/?step=2")
+            .header(header::COOKIE, "sessionid=secret")
+            .header(header::USER_AGENT, "test-agent")
+            .body(crate::Body::empty())
+            .unwrap();
+        let error = Error::with_status("payment failed", StatusCode::BAD_GATEWAY);
+
+        let entry = ErrorLogEntry::from_request(&request, &error);
+
+        assert_eq!(entry.method(), "POST");
+        assert_eq!(entry.uri(), "/checkout/?step=2");
+        assert_eq!(entry.status_code(), 502);
+        assert_eq!(entry.message(), "payment failed");
+        assert_eq!(
+            entry.headers(),
+            "cookie: [redacted]\nuser-agent: test-agent"
+        );
+        assert_eq!(entry.user(), None);
+    }
+
+    async fn call_with_error(middleware: ErrorLogMiddleware, database: Database, error: Error) {
+        use tower::{Layer, ServiceExt};
+
+        let service = middleware.layer(tower::service_fn(|_request: Request| async {
+            Ok::<_, Error>(Response::new(crate::Body::empty()))
+        }));
+        let mut request = crate::test::TestRequestBuilder::get("/missing/")
+            .config(crate::config::ProjectConfig::builder().debug(false).build())
+            .database(database)
+            .build();
+        request
+            .extensions_mut()
+            .insert(RequestOuterError::new(error));
+
+        service.oneshot(request).await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn middleware_records_only_server_errors() {
+        let mut test_db = crate::test::TestDatabase::new_sqlite().await.unwrap();
+        test_db.add_migrations(ErrorLogApp::new().migrations());
+        test_db.run_migrations().await;
+        let database = test_db.database();
+
+        call_with_error(
+            ErrorLogMiddleware::new(),
+            database.clone(),
+            Error::from(crate::error::NotFound::new()),
+        )
+        .await;
+        assert!(ErrorLogEntry::recent(&database).await.unwrap().is_empty());
+
+        call_with_error(
+            ErrorLogMiddleware::new(),
+            database.clone(),
+            Error::internal("server error"),
+        )
+        .await;
+        let entries = ErrorLogEntry::recent(&database).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status_code(), 500);
+
+        call_with_error(
+            ErrorLogMiddleware::new().record_client_errors(true),
+            database.clone(),
+            Error::from(crate::error::NotFound::new()),
+        )
+        .await;
+        let entries = ErrorLogEntry::recent(&database).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].status_code(), 404);
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn record_error_removes_oldest_entries() {
+        let mut test_db = crate::test::TestDatabase::new_sqlite().await.unwrap();
+        test_db.add_migrations(ErrorLogApp::new().migrations());
+        test_db.run_migrations().await;
+        let database = test_db.database();
+
+        for message in ["first", "second", "third"] {
+            let request = http::Request::builder().body(crate::Body::empty()).unwrap();
+            let entry = ErrorLogEntry::from_request(&request, &Error::internal(message));
+            record_error(&database, entry, 2).await.unwrap();
+        }
+
+        let messages: Vec<_> = ErrorLogEntry::recent(&database)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(messages, ["third", "second"]);
+
+        test_db.cleanup().await.unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+//! List of migrations for the current app.
+//!
+//! Generated by cot CLI 0.5.0 on 2026-10-16 10:12:31+00:00
+
+pub mod m_0001_initial;
+/// The list of migrations for current app.
+pub const MIGRATIONS: &[&::cot::db::migrations::SyncDynMigration] = &[&m_0001_initial::Migration];
@@ -0,0 +1,303 @@
+//! Handling of the errors that occur while streaming a response body.
+//!
+//! The headers of a streaming response (see
+//! [`Body::streaming`](crate::Body::streaming)) are sent to the client before
+//! its body is produced. Because of that, if the stream returns an error or
+//! panics, it's no longer possible to respond with an error page. Instead, Cot:
+//! * logs the error along with the request method and URI,
+//! * passes it to the [`StreamErrorHook`] returned by
+//!   [`Project::stream_error_hook`](crate::Project::stream_error_hook), if
+//!   any,
+//! * finishes the response according to the
+//!   [`StreamErrorPolicy`](crate::config::StreamErrorPolicy).
+
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use derive_more::with_trait::Debug;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Uri};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use tracing::error;
+
+use crate::Error;
+use crate::config::StreamErrorPolicy;
+use crate::error::UncaughtPanic;
+
+/// The name of the trailer sent when a response body fails with the
+/// [`StreamErrorPolicy::Trailer`] policy.
+pub const STREAM_ERROR_TRAILER: HeaderName = HeaderName::from_static("cot-stream-error");
+
+/// An error that occurred while streaming a response body.
+///
+/// This is passed to the [`StreamErrorHook`].
+#[derive(Debug)]
+pub struct StreamError {
+    method: Method,
+    uri: Uri,
+    error: Error,
+}
+
+impl StreamError {
+    /// Returns the method of the request whose response failed.
+    #[must_use]
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the URI of the request whose response failed.
+    #[must_use]
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// Returns the error returned by the stream.
+    ///
+    /// If the stream panicked, this is an [`UncaughtPanic`] error.
+    #[must_use]
+    pub fn error(&self) -> &Error {
+        self.error.inner()
+    }
+}
+
+/// A hook that is called when an error occurs while streaming a response
+/// body.
+///
+/// This can be used to report such errors to an error tracking service. The
+/// trait is implemented for all functions that take a [`StreamError`].
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use cot::Project;
+/// use cot::error::stream::{StreamError, StreamErrorHook};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn stream_error_hook(&self) -> Option<Arc<dyn StreamErrorHook>> {
+///         Some(Arc::new(|error: &StreamError| {
+///             eprintln!("{} {} failed: {}", error.method(), error.uri(), error.error());
+///         }))
+///     }
+/// }
+/// ```
+pub trait StreamErrorHook: Send + Sync {
+    /// Called when an error occurs while streaming a response body.
+    fn on_stream_error(&self, error: &StreamError);
+}
+
+impl<F> StreamErrorHook for F
+where
+    F: Fn(&StreamError) + Send + Sync,
+{
+    fn on_stream_error(&self, error: &StreamError) {
+        self(error);
+    }
+}
+
+/// Wraps the response bodies so that the errors that occur while streaming
+/// them are handled according to the [`StreamErrorPolicy`].
+#[derive(Debug, Clone)]
+pub(crate) struct StreamErrorGuard {
+    policy: StreamErrorPolicy,
+    #[debug("..")]
+    hook: Option<Arc<dyn StreamErrorHook>>,
+}
+
+impl StreamErrorGuard {
+    pub(crate) fn new(policy: StreamErrorPolicy, hook: Option<Arc<dyn StreamErrorHook>>) -> Self {
+        Self { policy, hook }
+    }
+
+    pub(crate) fn wrap<B>(&self, method: Method, uri: Uri, body: B) -> GuardedBody<B>
+    where
+        B: HttpBody,
+    {
+        GuardedBody {
+            inner: body,
+            guard: self.clone(),
+            method,
+            uri,
+            failed: false,
+        }
+    }
+
+    fn report(&self, stream_error: &StreamError) {
+        error!(
+            method = %stream_error.method,
+            uri = %stream_error.uri,
+            error = %stream_error.error,
+            "Error while streaming the response body"
+        );
+
+        if let Some(hook) = &self.hook {
+            hook.on_stream_error(stream_error);
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A response body guarded by the [`StreamErrorGuard`].
+    #[derive(Debug)]
+    pub(crate) struct GuardedBody<B> {
+        #[pin]
+        inner: B,
+        guard: StreamErrorGuard,
+        method: Method,
+        uri: Uri,
+        failed: bool,
+    }
+}
+
+impl<B> HttpBody for GuardedBody<B>
+where
+    B: HttpBody<Data = Bytes>,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        if *this.failed {
+            return Poll::Ready(None);
+        }
+
+        let inner = this.inner;
+        let error = match std::panic::catch_unwind(AssertUnwindSafe(|| inner.poll_frame(cx))) {
+            Ok(Poll::Ready(Some(Err(error)))) => Error::wrap(error),
+            Ok(result) => return result.map_err(Error::wrap),
+            Err(payload) => Error::from(UncaughtPanic::new(payload)),
+        };
+        *this.failed = true;
+
+        let stream_error = StreamError {
+            method: this.method.clone(),
+            uri: this.uri.clone(),
+            error,
+        };
+        this.guard.report(&stream_error);
+
+        match this.guard.policy {
+            StreamErrorPolicy::Abort => Poll::Ready(Some(Err(stream_error.error))),
+            StreamErrorPolicy::Trailer => {
+                let mut trailers = HeaderMap::new();
+                trailers.insert(
+                    STREAM_ERROR_TRAILER,
+                    HeaderValue::from(stream_error.error.status_code().as_u16()),
+                );
+                Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.failed || self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures_util::stream;
+    use http_body_util::BodyExt;
+
+    use super::*;
+    use crate::Body;
+
+    fn failing_body() -> Body {
+        Body::streaming(stream::iter([
+            Ok(Bytes::from("hello")),
+            Err(Error::internal("stream failed")),
+            Ok(Bytes::from("unreachable")),
+        ]))
+    }
+
+    fn panicking_body() -> Body {
+        Body::streaming(stream::poll_fn(|_| -> Poll<Option<crate::Result<Bytes>>> {
+            panic!("stream panicked")
+        }))
+    }
+
+    fn guard_body(policy: StreamErrorPolicy, body: Body) -> GuardedBody<Body> {
+        StreamErrorGuard::new(policy, None).wrap(Method::GET, Uri::from_static("/events/"), body)
+    }
+
+    #[cot::test]
+    async fn passes_successful_body_through() {
+        let body = guard_body(StreamErrorPolicy::Abort, Body::fixed("hello"));
+
+        let collected = body.collect().await.unwrap();
+
+        assert_eq!(collected.to_bytes(), "hello");
+    }
+
+    #[cot::test]
+    async fn abort_returns_error() {
+        let mut body = guard_body(StreamErrorPolicy::Abort, failing_body());
+
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!(frame.into_data().unwrap(), "hello");
+        let error = body.frame().await.unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "stream failed");
+        assert!(body.frame().await.is_none());
+        assert!(body.is_end_stream());
+    }
+
+    #[cot::test]
+    async fn trailer_ends_body_with_trailer() {
+        let body = guard_body(StreamErrorPolicy::Trailer, failing_body());
+
+        let collected = body.collect().await.unwrap();
+
+        assert_eq!(
+            collected.trailers().unwrap()[STREAM_ERROR_TRAILER],
+            HeaderValue::from_static("500")
+        );
+        assert_eq!(collected.to_bytes(), "hello");
+    }
+
+    #[cot::test]
+    async fn catches_panic() {
+        let mut body = guard_body(StreamErrorPolicy::Abort, panicking_body());
+
+        let error = body.frame().await.unwrap().unwrap_err();
+
+        assert!(error.inner().is::<UncaughtPanic>());
+    }
+
+    #[cot::test]
+    async fn calls_hook() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let hook_reported = Arc::clone(&reported);
+        let hook = move |error: &StreamError| {
+            hook_reported.lock().unwrap().push(format!(
+                "{} {}: {}",
+                error.method(),
+                error.uri(),
+                error.error()
+            ));
+        };
+        let guard = StreamErrorGuard::new(StreamErrorPolicy::Trailer, Some(Arc::new(hook)));
+        let body = guard.wrap(Method::GET, Uri::from_static("/events/"), failing_body());
+
+        body.collect().await.unwrap();
+
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec!["GET /events/: stream failed".to_owned()]
+        );
+    }
+}
@@ -23,6 +23,7 @@
 mod field_value;
 /// Built-in form fields that can be used in a form.
 pub mod fields;
+mod formset;
 
 use std::borrow::Cow;
 use std::fmt::Display;
@@ -51,6 +52,88 @@ use cot_core::headers::{MULTIPART_FORM_CONTENT_TYPE, URLENCODED_FORM_CONTENT_TYP
 /// Note that even if the form is not rendered in a template, you will still be
 /// able to render the fields individually.
 ///
+/// # Field attributes
+///
+/// The following attributes can be used on the fields inside `#[form(...)]`:
+///
+/// * `opts(...)` sets the custom options of the field type, such as
+///   `opts(max_length = 100)`. Options that have an HTML5 validation
+///   counterpart (`min_length`, `max_length`, `min`, `max`, `step`, `pattern`,
+///   `accept`) are rendered as the matching attribute and enforced again when
+///   the form is validated on the server,
+/// * `help_text = "..."` sets the help text displayed next to the field,
+/// * `widget = ...` renders the field as a different [`Widget`], such as
+///   `widget = Textarea`,
+/// * `attrs(...)` adds HTML attributes to the rendered element, such as
+///   `attrs(class = "input")`. Underscores in the attribute names are
+///   replaced with dashes, so `aria_label` becomes `aria-label`,
+/// * `min_from = other` and `max_from = other` require the value to be not
+///   less (or not greater) than the value of the field `other`. This is
+///   checked on the server once both fields are valid, and a violation is
+///   reported as an error of the annotated field. The fields have to
+///   implement [`FormFieldBound`], which is the case for the date and time
+///   types,
+/// * `validate_with = "path::to::fn"` runs a custom validator on the cleaned
+///   value of the field. The function is called as `validator(&value)` and
+///   should return `Result<(), FormFieldValidationError>`; an error is
+///   reported as an error of the annotated field.
+///
+/// ```
+/// use cot::form::Form;
+///
+/// #[derive(Form)]
+/// struct CommentForm {
+///     #[form(opts(max_length = 100), attrs(class = "input"))]
+///     author: String,
+///     #[form(widget = Textarea, help_text = "Markdown is supported.")]
+///     content: String,
+/// }
+/// ```
+///
+/// Use [`FormContext::field`] to render the label, the widget, the help text,
+/// and the errors of a field separately in templates.
+///
+/// # Form attributes
+///
+/// Validation that involves more than one field can be done with
+/// `#[form(validate = "path::to::fn")]` on the struct. The function is called
+/// as `validate(&form, &mut context)` once all the fields are valid, and can
+/// attach errors to any field (or the form as a whole) with
+/// [`FormContext::add_error`]. If it adds any error, the form is reported as
+/// invalid.
+///
+/// ```
+/// use cot::form::{Form, FormContext, FormErrorTarget, FormFieldValidationError};
+///
+/// #[derive(Form)]
+/// #[form(validate = "check_passwords_match")]
+/// struct SignupForm {
+///     #[form(validate_with = "check_username")]
+///     username: String,
+///     password: String,
+///     password_confirmation: String,
+/// }
+///
+/// fn check_username(username: &str) -> Result<(), FormFieldValidationError> {
+///     if username.chars().all(char::is_alphanumeric) {
+///         Ok(())
+///     } else {
+///         Err(FormFieldValidationError::from_static(
+///             "Only letters and digits are allowed.",
+///         ))
+///     }
+/// }
+///
+/// fn check_passwords_match(form: &SignupForm, context: &mut impl FormContext) {
+///     if form.password != form.password_confirmation {
+///         context.add_error(
+///             FormErrorTarget::Field("password_confirmation"),
+///             FormFieldValidationError::from_static("The passwords do not match."),
+///         );
+///     }
+/// }
+/// ```
+///
 /// # Safety
 ///
 /// The implementation of [`Display`] for the form context that this derive
@@ -58,11 +141,70 @@ use cot_core::headers::{MULTIPART_FORM_CONTENT_TYPE, URLENCODED_FORM_CONTENT_TYP
 /// fields. If the form fields are not safe to render as HTML, the form context
 /// will not be safe to render as HTML either.
 pub use cot_macros::Form;
+/// Derive a form for a database model.
+///
+/// This macro generates a `{Model}Form` struct with the fields of the model,
+/// along with a [`Form`] implementation for it, so that the fields don't have
+/// to be repeated in a separate struct. The primary key is not part of the
+/// form.
+///
+/// The fields can be chosen with `#[model_form(fields(...))]`, which also
+/// determines their order, or skipped with `#[model_form(exclude(...))]`. The
+/// `#[form(...)]` attributes on the model fields (see [`Form`](derive@Form))
+/// are copied to the form.
+///
+/// The generated form has the following methods:
+///
+/// * `save(db)` creates a new model instance from the form data and inserts it
+///   into the database. The fields that are not part of the form are set to
+///   their [`Default`] values, so this is only available if they implement
+///   [`Default`],
+/// * `save_to(model, db)` copies the form data into an existing model instance
+///   and updates it in the database,
+/// * `apply_to(model)` copies the form data into an existing model instance
+///   without saving it.
+///
+/// The model can also be converted into the form (and, if the fields that are
+/// not part of the form implement [`Default`], the other way around) with
+/// [`From`], which makes the form usable with the [generic
+/// views](crate::views).
+///
+/// ```
+/// use cot::db::{Auto, Database, model};
+/// use cot::form::{Form, ModelForm};
+/// use cot::request::Request;
+///
+/// #[derive(Debug, ModelForm)]
+/// #[model_form(fields(title, content))]
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     #[form(opts(max_length = 100))]
+///     title: String,
+///     #[form(widget = Textarea)]
+///     content: String,
+///     views: i64,
+/// }
+///
+/// async fn add_post(request: &mut Request, db: &Database) -> cot::Result<()> {
+///     let form = PostForm::from_request(request).await?.unwrap();
+///     let post = form.save(db).await?;
+///     assert_eq!(post.views, 0);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "db")]
+pub use cot_macros::ModelForm;
 use derive_more::with_trait::Debug;
 pub use field_value::{FormFieldValue, FormFieldValueError};
+pub use formset::{DEFAULT_MAX_FORMS, FormSet, FormSetContext, FormSetForm, FormSetResult};
+use http::StatusCode;
 use http_body_util::BodyExt;
 use thiserror::Error;
 
+use crate::config::FormsConfig;
+use crate::html::{Html, HtmlTag};
 use crate::request::{Request, RequestExt};
 
 const ERROR_PREFIX: &str = "failed to process a form:";
@@ -84,11 +226,48 @@ pub enum FormError {
     #[non_exhaustive]
     MultipartError {
         /// The underlying error that occurred during multipart form processing.
-        #[from]
+        #[source]
         error: FormFieldValueError,
     },
+    /// A field of a multipart form exceeded the
+    /// [`max_field_size`](crate::config::FormsConfig::max_field_size) limit.
+    #[error("{ERROR_PREFIX} field `{field}` exceeded the size limit of {limit} bytes")]
+    #[non_exhaustive]
+    FieldTooLarge {
+        /// The name of the field.
+        field: String,
+        /// The size limit of the field, in bytes.
+        limit: u64,
+    },
+    /// The body of a multipart form exceeded the
+    /// [`max_body_size`](crate::config::FormsConfig::max_body_size) limit.
+    #[error("{ERROR_PREFIX} form data exceeded the size limit of {limit} bytes")]
+    #[non_exhaustive]
+    BodyTooLarge {
+        /// The size limit of the form data, in bytes.
+        limit: u64,
+    },
+}
+
+impl From<FormFieldValueError> for FormError {
+    fn from(error: FormFieldValueError) -> Self {
+        error
+            .size_limit_error()
+            .unwrap_or(FormError::MultipartError { error })
+    }
+}
+
+impl From<FormError> for crate::Error {
+    fn from(error: FormError) -> Self {
+        let status_code = match error {
+            FormError::FieldTooLarge { .. } | FormError::BodyTooLarge { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            _ => StatusCode::BAD_REQUEST,
+        };
+        crate::Error::with_status(error, status_code)
+    }
 }
-impl_into_cot_error!(FormError, BAD_REQUEST);
 
 /// The result of validating a form.
 ///
@@ -158,6 +337,24 @@ pub enum FormFieldValidationError {
         /// The maximum permitted value.
         max_value: String,
     },
+    /// The field value does not match the required pattern.
+    #[error("This does not match the required pattern.")]
+    PatternMismatch {
+        /// The pattern the value was expected to match.
+        pattern: String,
+    },
+    /// The field value does not fall on one of the allowed steps.
+    #[error("This value is not one of the allowed steps.")]
+    StepMismatch,
+    /// The uploaded file is not of an accepted type.
+    #[error("This file type is not accepted; expected one of: {accept}.")]
+    FileTypeNotAccepted {
+        /// The comma-separated list of accepted file types.
+        accept: String,
+    },
+    /// The uploaded file is not a valid image.
+    #[error("Upload a valid image. The file is either not an image or a corrupted image.")]
+    InvalidImage,
     /// The field value is an ambiguous datetime.
     #[error("The datetime value `{datetime}` is ambiguous.")]
     AmbiguousDateTime {
@@ -184,6 +381,14 @@ pub enum FormFieldValidationError {
     /// Custom error with a given message.
     #[error("{0}")]
     Custom(Cow<'static, str>),
+    /// Custom error with a given message and a machine-readable code.
+    #[error("{message}")]
+    CustomWithCode {
+        /// The machine-readable code of the error.
+        code: &'static str,
+        /// The human-readable message of the error.
+        message: Cow<'static, str>,
+    },
 }
 
 impl FormFieldValidationError {
@@ -208,6 +413,24 @@ impl FormFieldValidationError {
         FormFieldValidationError::MinimumLengthNotMet { min_length }
     }
 
+    /// Creates a new `FormFieldValidationError` for a field value that does
+    /// not match the required pattern.
+    #[must_use]
+    pub fn pattern_mismatch<T: Into<String>>(pattern: T) -> Self {
+        FormFieldValidationError::PatternMismatch {
+            pattern: pattern.into(),
+        }
+    }
+
+    /// Creates a new `FormFieldValidationError` for an uploaded file whose
+    /// type is not in the list of accepted types.
+    #[must_use]
+    pub fn file_type_not_accepted(accept: &[String]) -> Self {
+        FormFieldValidationError::FileTypeNotAccepted {
+            accept: accept.join(","),
+        }
+    }
+
     /// Creates a new `FormFieldValidatorError`for a field value below the
     /// permitted minimum value.
     #[must_use]
@@ -250,6 +473,236 @@ impl FormFieldValidationError {
     pub const fn from_static(message: &'static str) -> Self {
         Self::Custom(Cow::Borrowed(message))
     }
+
+    /// Creates a new custom `FormFieldValidationError` with a given
+    /// machine-readable code.
+    ///
+    /// The code is returned by [`Self::code`] and used to build
+    /// [`Self::translation_key`], so it should be a stable `snake_case`
+    /// identifier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::FormFieldValidationError;
+    ///
+    /// let error = FormFieldValidationError::with_code("passwords_mismatch", "Passwords differ.");
+    /// assert_eq!(error.code(), "passwords_mismatch");
+    /// assert_eq!(error.translation_key(), "form.errors.passwords_mismatch");
+    /// assert_eq!(error.to_string(), "Passwords differ.");
+    /// ```
+    #[must_use]
+    pub fn with_code<T: Into<Cow<'static, str>>>(code: &'static str, message: T) -> Self {
+        Self::CustomWithCode {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the machine-readable code of the error.
+    ///
+    /// Unlike the [`Display`] output, the code does not depend on the values
+    /// involved and is guaranteed to stay the same between releases, so it can
+    /// be used to look up translations or to report the error in an API
+    /// response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::FormFieldValidationError;
+    ///
+    /// assert_eq!(FormFieldValidationError::Required.code(), "required");
+    /// assert_eq!(
+    ///     FormFieldValidationError::maximum_length_exceeded(10).code(),
+    ///     "max_length"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Required => "required",
+            Self::MaximumLengthExceeded { .. } => "max_length",
+            Self::MinimumLengthNotMet { .. } => "min_length",
+            Self::MinimumValueNotMet { .. } => "min_value",
+            Self::MaximumValueExceeded { .. } => "max_value",
+            Self::PatternMismatch { .. } => "pattern",
+            Self::StepMismatch => "step",
+            Self::FileTypeNotAccepted { .. } => "file_type",
+            Self::InvalidImage => "invalid_image",
+            Self::AmbiguousDateTime { .. } => "ambiguous_datetime",
+            Self::NonExistentLocalDateTime { .. } => "nonexistent_datetime",
+            Self::BooleanRequiredToBeTrue => "must_be_true",
+            Self::InvalidValue(_) => "invalid",
+            Self::FormFieldValueError(_) => "value_error",
+            Self::Custom(_) => "custom",
+            Self::CustomWithCode { code, .. } => code,
+        }
+    }
+
+    /// Returns the key used to look up the translated message of the error.
+    ///
+    /// The key is the [`Self::code`] prefixed with `form.errors.`; the values
+    /// to interpolate into the translated message are returned by
+    /// [`Self::params`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::FormFieldValidationError;
+    ///
+    /// assert_eq!(
+    ///     FormFieldValidationError::Required.translation_key(),
+    ///     "form.errors.required"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn translation_key(&self) -> String {
+        format!("form.errors.{}", self.code())
+    }
+
+    /// Returns the named parameters of the error.
+    ///
+    /// These are the values that are embedded in the English message, such as
+    /// the maximum length for [`Self::MaximumLengthExceeded`], so that a
+    /// translated message can include them, too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::FormFieldValidationError;
+    ///
+    /// let error = FormFieldValidationError::maximum_length_exceeded(10);
+    /// assert_eq!(error.params(), vec![("max_length", "10".to_owned())]);
+    /// ```
+    #[must_use]
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::MaximumLengthExceeded { max_length } => {
+                vec![("max_length", max_length.to_string())]
+            }
+            Self::MinimumLengthNotMet { min_length } => {
+                vec![("min_length", min_length.to_string())]
+            }
+            Self::MinimumValueNotMet { min_value } => vec![("min_value", min_value.clone())],
+            Self::MaximumValueExceeded { max_value } => vec![("max_value", max_value.clone())],
+            Self::PatternMismatch { pattern } => vec![("pattern", pattern.clone())],
+            Self::FileTypeNotAccepted { accept } => vec![("accept", accept.clone())],
+            Self::AmbiguousDateTime { datetime } => vec![("datetime", datetime.to_string())],
+            Self::NonExistentLocalDateTime { datetime, timezone } => vec![
+                ("datetime", datetime.to_string()),
+                ("timezone", timezone.to_string()),
+            ],
+            Self::InvalidValue(value) => vec![("value", value.clone())],
+            Self::Required
+            | Self::StepMismatch
+            | Self::InvalidImage
+            | Self::BooleanRequiredToBeTrue
+            | Self::FormFieldValueError(_)
+            | Self::Custom(_)
+            | Self::CustomWithCode { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Serializes the error as an object with its `code`, English `message`, and
+/// `params`, which is suitable for use in API error responses.
+impl serde::Serialize for FormFieldValidationError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let params: std::collections::BTreeMap<_, _> = self.params().into_iter().collect();
+
+        let mut state = serializer.serialize_struct("FormFieldValidationError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("params", &params)?;
+        state.end()
+    }
+}
+
+/// A form value that can be compared with the value of another field.
+///
+/// This is used by the `min_from` and `max_from` attributes of the
+/// [`Form`](derive@Form) derive macro to validate constraints between fields,
+/// such as an end date that must not be before the start date.
+///
+/// Timezone-aware values ([`chrono::DateTime`]) are compared as points in
+/// time, so the constraint holds even if the fields were submitted in
+/// different timezones.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use cot::form::{Form, FormFieldBound};
+///
+/// #[derive(Form)]
+/// struct BookingForm {
+///     start: NaiveDate,
+///     #[form(min_from = start)]
+///     end: NaiveDate,
+///     #[form(max_from = end)]
+///     check_in: Option<NaiveDate>,
+/// }
+///
+/// let start = NaiveDate::from_ymd_opt(2025, 5, 27).unwrap();
+/// let end = NaiveDate::from_ymd_opt(2025, 5, 26).unwrap();
+/// assert!(end.check_min(&start).is_err());
+/// assert!(None::<NaiveDate>.check_min(&start).is_ok());
+/// ```
+pub trait FormFieldBound {
+    /// The type the values are compared as.
+    type Bound: PartialOrd + Display;
+
+    /// Returns the value to compare, or `None` if there is nothing to compare,
+    /// such as for an empty optional field.
+    fn as_bound(&self) -> Option<&Self::Bound>;
+
+    /// Checks that this value is not less than `min`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FormFieldValidationError::MinimumValueNotMet`] if the value is
+    /// less than `min`.
+    fn check_min<T: FormFieldBound<Bound = Self::Bound>>(
+        &self,
+        min: &T,
+    ) -> Result<(), FormFieldValidationError> {
+        if let (Some(value), Some(min)) = (self.as_bound(), min.as_bound())
+            && value < min
+        {
+            return Err(FormFieldValidationError::minimum_value_not_met(min));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that this value is not greater than `max`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FormFieldValidationError::MaximumValueExceeded`] if the value
+    /// is greater than `max`.
+    fn check_max<T: FormFieldBound<Bound = Self::Bound>>(
+        &self,
+        max: &T,
+    ) -> Result<(), FormFieldValidationError> {
+        if let (Some(value), Some(max)) = (self.as_bound(), max.as_bound())
+            && value > max
+        {
+            return Err(FormFieldValidationError::maximum_value_exceeded(max));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: FormFieldBound> FormFieldBound for Option<T> {
+    type Bound = T::Bound;
+
+    fn as_bound(&self) -> Option<&Self::Bound> {
+        self.as_ref().and_then(FormFieldBound::as_bound)
+    }
 }
 
 /// An enum indicating the target of a form validation error.
@@ -295,12 +748,22 @@ pub trait Form: Sized {
 
     /// Creates a form struct from a request.
     ///
+    /// This is typically implemented by building the context with
+    /// [`Self::build_context`] and validating it with [`Self::from_context`].
+    ///
     /// # Errors
     ///
     /// This method should return an error if the form data could not be read
     /// from the request.
     async fn from_request(request: &mut Request) -> Result<FormResult<Self>, FormError>;
 
+    /// Creates a form struct from a context filled with the user's input.
+    ///
+    /// The values of the fields are converted into the final types and
+    /// validated. If any of them is invalid, the errors are added to the
+    /// context and `None` is returned.
+    async fn from_context(context: &mut Self::Context) -> Option<Self>;
+
     /// Creates the context for the form from `self`.
     ///
     /// This is useful for pre-populating forms with objects created in the code
@@ -324,6 +787,15 @@ pub trait Form: Sized {
 
         while let Some((field_id, value)) = form_data.next_value().await? {
             if let Err(err) = context.set_value(&field_id, value).await {
+                let size_error = match &err {
+                    FormFieldValidationError::FormFieldValueError(error) => {
+                        error.size_limit_error()
+                    }
+                    _ => None,
+                };
+                if let Some(size_error) = size_error {
+                    return Err(size_error);
+                }
                 context.add_error(FormErrorTarget::Field(&field_id), err);
             }
         }
@@ -334,9 +806,23 @@ pub trait Form: Sized {
 
 async fn form_data(request: &mut Request) -> Result<FormData<'_>, FormError> {
     let form_data = if content_type_str(request).starts_with(MULTIPART_FORM_CONTENT_TYPE) {
-        let multipart = multipart_form_data(request)?;
-
-        FormData::Multipart { inner: multipart }
+        #[cfg(feature = "storage")]
+        let storage = request
+            .extensions()
+            .get::<std::sync::Arc<crate::ProjectContext>>()
+            .map(|context| context.storage().clone());
+        let config = request
+            .extensions()
+            .get::<std::sync::Arc<crate::ProjectContext>>()
+            .map(|context| context.config().forms.clone())
+            .unwrap_or_default();
+        let multipart = multipart_form_data(request, &config)?;
+
+        FormData::Multipart {
+            inner: multipart,
+            #[cfg(feature = "storage")]
+            storage,
+        }
     } else {
         let form_data_bytes = urlencoded_form_data(request).await?;
 
@@ -345,13 +831,27 @@ async fn form_data(request: &mut Request) -> Result<FormData<'_>, FormError> {
     Ok(form_data)
 }
 
-fn multipart_form_data(request: &mut Request) -> Result<multer::Multipart<'_>, FormError> {
+fn multipart_form_data<'a>(
+    request: &mut Request,
+    config: &FormsConfig,
+) -> Result<multer::Multipart<'a>, FormError> {
     let content_type = content_type_str(request);
 
     let boundary =
         multer::parse_boundary(content_type).map_err(FormFieldValueError::from_multer)?;
+    let mut size_limit = multer::SizeLimit::new();
+    if let Some(max_field_size) = config.max_field_size {
+        size_limit = size_limit.per_field(max_field_size);
+    }
+    if let Some(max_body_size) = config.max_body_size {
+        size_limit = size_limit.whole_stream(max_body_size);
+    }
     let body = std::mem::take(request.body_mut());
-    let multipart = multer::Multipart::new(body.into_data_stream(), boundary);
+    let multipart = multer::Multipart::with_constraints(
+        body.into_data_stream(),
+        boundary,
+        multer::Constraints::new().size_limit(size_limit),
+    );
 
     Ok(multipart)
 }
@@ -407,6 +907,8 @@ enum FormData<'a> {
     },
     Multipart {
         inner: multer::Multipart<'a>,
+        #[cfg(feature = "storage")]
+        storage: Option<crate::storage::FileStorage>,
     },
 }
 
@@ -438,7 +940,11 @@ impl<'a> FormData<'a> {
             FormData::Form { inner, .. } => Ok(inner
                 .next()
                 .map(|(key, value)| (key.into_owned(), FormFieldValue::new_text(value)))),
-            FormData::Multipart { inner } => {
+            FormData::Multipart {
+                inner,
+                #[cfg(feature = "storage")]
+                storage,
+            } => {
                 let next_field = inner.next_field().await;
 
                 match next_field {
@@ -448,6 +954,8 @@ impl<'a> FormData<'a> {
                             .ok_or_else(FormFieldValueError::no_name)?
                             .to_owned();
                         let value = FormFieldValue::new_multipart(field);
+                        #[cfg(feature = "storage")]
+                        let value = value.with_storage(storage.clone());
 
                         Ok(Some((name, value)))
                     }
@@ -476,6 +984,20 @@ pub trait FormContext: Debug {
     where
         Self: Sized;
 
+    /// Creates a new form context without any initial form data, with the
+    /// HTML names and IDs of the fields prefixed with `prefix` and a dash.
+    ///
+    /// This allows rendering multiple forms of the same type inside a single
+    /// HTML form, as done by [`FormSet`]. The fields are still identified by
+    /// their unprefixed IDs in the other methods of this trait.
+    fn with_prefix(prefix: &str) -> Self
+    where
+        Self: Sized;
+
+    /// Returns the prefix of the field names, if the context was created with
+    /// [`Self::with_prefix`].
+    fn prefix(&self) -> Option<&str>;
+
     /// Returns an iterator over the fields in the form.
     fn fields(&self) -> Box<dyn DoubleEndedIterator<Item = &dyn DynFormField> + '_>;
 
@@ -505,6 +1027,35 @@ pub trait FormContext: Debug {
 
     /// Returns whether the form context has any validation errors.
     fn has_errors(&self) -> bool;
+
+    /// Returns the field with the given ID, bound to its validation errors,
+    /// so that its label, widget, help text, and errors can be rendered
+    /// individually.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the form doesn't have a field with the given ID.
+    fn field(&self, field_id: &str) -> BoundField<'_> {
+        let field = self
+            .fields()
+            .find(|field| {
+                crate::__private::strip_form_prefix(self.prefix(), field.dyn_id()) == field_id
+            })
+            .unwrap_or_else(|| panic!("Unknown field name passed to field: `{field_id}`"));
+
+        BoundField::new(field, self.errors_for(FormErrorTarget::Field(field_id)))
+    }
+
+    /// Returns an iterator over the fields in the form, bound to their
+    /// validation errors.
+    fn bound_fields(&self) -> Box<dyn DoubleEndedIterator<Item = BoundField<'_>> + '_> {
+        Box::new(self.fields().map(|field| {
+            BoundField::new(
+                field,
+                self.errors_for(FormErrorTarget::Field(field.dyn_id())),
+            )
+        }))
+    }
 }
 
 /// Generic options valid for all types of form fields.
@@ -519,6 +1070,208 @@ pub struct FormFieldOptions {
     /// fields are required. If you want to make a field optional, just use
     /// [`Option`] in the struct definition.
     pub required: bool,
+    /// Help text describing the form field, available in templates through
+    /// [`BoundField::help_text`].
+    pub help_text: Option<String>,
+    /// The widget to render the field as, instead of the default HTML element
+    /// of the field type.
+    pub widget: Option<Widget>,
+    /// Additional HTML attributes of the rendered element. These take
+    /// precedence over the attributes set by the field type.
+    pub attrs: Vec<(String, String)>,
+}
+
+impl FormFieldOptions {
+    /// Renders the HTML element of a form field, applying the
+    /// [`Self::widget`] and [`Self::attrs`] overrides.
+    ///
+    /// This is meant to be used in the [`Display`] implementations of the
+    /// form fields, which build the default element of the field and pass
+    /// it here instead of rendering it directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{FormFieldOptions, Widget};
+    /// use cot::html::HtmlTag;
+    ///
+    /// let options = FormFieldOptions {
+    ///     id: "bio".to_owned(),
+    ///     name: "Bio".to_owned(),
+    ///     required: false,
+    ///     help_text: None,
+    ///     widget: Some(Widget::Textarea),
+    ///     attrs: vec![("class".to_owned(), "input".to_owned())],
+    /// };
+    ///
+    /// let mut tag = HtmlTag::input("text");
+    /// tag.attr("name", "bio").attr("id", "bio").attr("value", "Hello");
+    /// assert_eq!(
+    ///     options.render_widget(tag).as_str(),
+    ///     "<textarea name=\"bio\" id=\"bio\" class=\"input\">Hello</textarea>"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn render_widget(&self, tag: HtmlTag) -> Html {
+        const COPIED_ATTRS: [&str; 5] = ["name", "id", "maxlength", "minlength", "placeholder"];
+
+        let mut tag = match self.widget {
+            None => tag,
+            Some(Widget::Textarea) => {
+                let mut textarea = HtmlTag::new("textarea");
+                for key in COPIED_ATTRS {
+                    if let Some(value) = tag.get_attr(key) {
+                        textarea.attr(key, value);
+                    }
+                }
+                if tag.has_bool_attr("required") {
+                    textarea.bool_attr("required");
+                }
+                // always push the content, even if empty, so that the element
+                // is not rendered as self-closing
+                textarea.push_str(tag.get_attr("value").unwrap_or_default());
+                textarea
+            }
+            Some(Widget::Hidden) => {
+                let mut hidden = HtmlTag::input("hidden");
+                for key in ["name", "id", "value"] {
+                    if let Some(value) = tag.get_attr(key) {
+                        hidden.attr(key, value);
+                    }
+                }
+                hidden
+            }
+        };
+
+        for (key, value) in &self.attrs {
+            tag.set_attr(key, value);
+        }
+        tag.render()
+    }
+}
+
+/// A widget overriding the HTML element a form field is rendered as.
+///
+/// The widgets are meant for the fields rendered as single-value `<input>`
+/// elements, such as the text, email, or number fields. The value of the
+/// field, as well as its `name`, `id`, and basic validation attributes, are
+/// carried over to the new element.
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::Form;
+///
+/// #[derive(Form)]
+/// struct CommentForm {
+///     #[form(widget = Textarea, attrs(rows = "5"))]
+///     content: String,
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Widget {
+    /// A multi-line `<textarea>` element.
+    Textarea,
+    /// An `<input type="hidden">` element.
+    Hidden,
+}
+
+/// A form field together with its validation errors, allowing to render the
+/// parts of the field individually in templates.
+///
+/// This is returned by [`FormContext::field`] and
+/// [`FormContext::bound_fields`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::{Form, FormContext};
+///
+/// #[derive(Form)]
+/// struct ContactForm {
+///     #[form(help_text = "We will never share your email.")]
+///     email: String,
+/// }
+///
+/// let context = <ContactForm as Form>::Context::new();
+/// let field = context.field("email");
+///
+/// assert_eq!(field.label().as_str(), "<label for=\"email\">Email</label>");
+/// assert_eq!(field.help_text(), Some("We will never share your email."));
+/// assert!(field.errors().is_empty());
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct BoundField<'a> {
+    #[debug("..")]
+    field: &'a dyn DynFormField,
+    errors: &'a [FormFieldValidationError],
+}
+
+impl<'a> BoundField<'a> {
+    /// Creates a new bound field from a form field and its errors.
+    #[must_use]
+    pub fn new(field: &'a dyn DynFormField, errors: &'a [FormFieldValidationError]) -> Self {
+        Self { field, errors }
+    }
+
+    /// Returns the underlying form field.
+    #[must_use]
+    pub fn field(&self) -> &'a dyn DynFormField {
+        self.field
+    }
+
+    /// Returns the HTML ID of the form field.
+    #[must_use]
+    pub fn id(&self) -> &'a str {
+        self.field.dyn_id()
+    }
+
+    /// Returns the display name of the form field.
+    #[must_use]
+    pub fn name(&self) -> &'a str {
+        &self.field.dyn_options().name
+    }
+
+    /// Returns whether the form field is required.
+    #[must_use]
+    pub fn is_required(&self) -> bool {
+        self.field.dyn_options().required
+    }
+
+    /// Returns the help text of the form field, if any.
+    #[must_use]
+    pub fn help_text(&self) -> Option<&'a str> {
+        self.field.dyn_options().help_text.as_deref()
+    }
+
+    /// Renders the `<label>` element of the form field.
+    #[must_use]
+    pub fn label(&self) -> Html {
+        HtmlTag::new("label")
+            .attr("for", self.id())
+            .push_str(self.name())
+            .render()
+    }
+
+    /// Renders the HTML element of the form field (e.g., an `<input>` or a
+    /// `<select>`), taking the widget overrides into account.
+    #[must_use]
+    pub fn widget(&self) -> Html {
+        Html::new(self.field.to_string())
+    }
+
+    /// Returns the validation errors of the form field.
+    #[must_use]
+    pub fn errors(&self) -> &'a [FormFieldValidationError] {
+        self.errors
+    }
+
+    /// Returns whether the form field has any validation errors.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
 }
 
 /// A form field.
@@ -663,6 +1416,33 @@ mod tests {
     use super::*;
     use crate::Body;
 
+    #[test]
+    fn form_field_validation_error_codes() {
+        let error = FormFieldValidationError::minimum_value_not_met(5);
+        assert_eq!(error.code(), "min_value");
+        assert_eq!(error.translation_key(), "form.errors.min_value");
+        assert_eq!(error.params(), vec![("min_value", "5".to_owned())]);
+
+        let error = FormFieldValidationError::from_static("Something went wrong.");
+        assert_eq!(error.code(), "custom");
+        assert!(error.params().is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn form_field_validation_error_serialize() {
+        let error = FormFieldValidationError::maximum_length_exceeded(10);
+
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "max_length",
+                "message": "This exceeds the maximum length of 10.",
+                "params": {"max_length": "10"},
+            })
+        );
+    }
+
     #[cot::test]
     async fn urlencoded_form_data_extract_get_empty() {
         let mut request = http::Request::builder()
@@ -820,4 +1600,81 @@ mod tests {
             panic!("Expected RequestError");
         }
     }
+
+    fn limited_multipart_request(config: FormsConfig) -> Request {
+        let boundary = "boundary";
+        let body = format!(
+            "--{boundary}\r\n\
+            Content-Disposition: form-data; name=\"hello\"\r\n\
+            \r\n\
+            world\r\n\
+            --{boundary}\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"test.txt\"\r\n\
+            Content-Type: text/plain\r\n\
+            \r\n\
+            file content that is too long\r\n\
+            --{boundary}--\r\n"
+        );
+
+        let mut request = crate::test::TestRequestBuilder::post("/")
+            .config(
+                crate::config::ProjectConfig::builder()
+                    .forms(config)
+                    .build(),
+            )
+            .build();
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            format!("{MULTIPART_FORM_CONTENT_TYPE}; boundary={boundary}")
+                .parse()
+                .unwrap(),
+        );
+        *request.body_mut() = Body::fixed(body);
+        request
+    }
+
+    #[cot::test]
+    async fn form_data_multipart_field_too_large() {
+        let mut request =
+            limited_multipart_request(FormsConfig::builder().max_field_size(16).build());
+
+        let mut form_data = form_data(&mut request).await.unwrap();
+        let (_, hello) = form_data.next_value().await.unwrap().unwrap();
+        assert_eq!(hello.into_text().await.unwrap(), "world");
+        let (_, file) = form_data.next_value().await.unwrap().unwrap();
+        let error = FormError::from(file.into_bytes().await.unwrap_err());
+
+        assert!(
+            matches!(&error, FormError::FieldTooLarge { field, limit: 16 } if field == "file"),
+            "{error:?}"
+        );
+        assert_eq!(
+            crate::Error::from(error).status_code(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[cot::test]
+    async fn form_data_multipart_body_too_large() {
+        let mut request =
+            limited_multipart_request(FormsConfig::builder().max_body_size(64).build());
+
+        let mut form_data = form_data(&mut request).await.unwrap();
+        let error = loop {
+            match form_data.next_value().await {
+                Ok(Some((_, value))) => {
+                    if let Err(error) = value.into_bytes().await {
+                        break FormError::from(error);
+                    }
+                }
+                Ok(None) => panic!("Expected the body size limit to be exceeded"),
+                Err(error) => break FormError::from(error),
+            }
+        };
+
+        assert!(
+            matches!(error, FormError::BodyTooLarge { limit: 64 }),
+            "{error:?}"
+        );
+    }
 }
@@ -5,6 +5,10 @@ use bytes::Bytes;
 use cot_core::error::impl_into_cot_error;
 use thiserror::Error;
 
+use crate::form::FormError;
+#[cfg(feature = "storage")]
+use crate::storage::{ByteStream, FileStorage, StorageError};
+
 /// A value from a form field.
 ///
 /// This type represents a value from a form field, which can be either a text
@@ -45,7 +49,54 @@ impl<'a> FormFieldValue<'a> {
     #[must_use]
     pub(crate) fn new_multipart(field: multer::Field<'a>) -> Self {
         Self {
-            inner: FormFieldValueImpl::Multipart(Box::new(MultipartField { inner: field })),
+            inner: FormFieldValueImpl::Multipart(Box::new(MultipartField {
+                inner: field,
+                #[cfg(feature = "storage")]
+                storage: None,
+            })),
+        }
+    }
+
+    /// Sets the file storage the uploaded file can be saved to. Has no effect
+    /// on text fields.
+    #[cfg(feature = "storage")]
+    #[must_use]
+    pub(crate) fn with_storage(mut self, storage: Option<FileStorage>) -> Self {
+        if let FormFieldValueImpl::Multipart(multipart) = &mut self.inner {
+            multipart.storage = storage;
+        }
+        self
+    }
+
+    /// Returns the file storage of the project, if this is a multipart field
+    /// of a request handled by a project.
+    #[cfg(feature = "storage")]
+    #[must_use]
+    pub(crate) fn storage(&self) -> Option<&FileStorage> {
+        match &self.inner {
+            FormFieldValueImpl::Text(_) => None,
+            FormFieldValueImpl::Multipart(multipart) => multipart.storage.as_ref(),
+        }
+    }
+
+    /// Converts the field value into a stream of chunks, so that its contents
+    /// can be processed without reading them into memory at once.
+    #[cfg(feature = "storage")]
+    #[must_use]
+    pub(crate) fn into_stream(self) -> ByteStream<'a> {
+        use futures_util::StreamExt;
+
+        match self.inner {
+            FormFieldValueImpl::Text(text) => {
+                Box::pin(futures_util::stream::once(
+                    async move { Ok(Bytes::from(text)) },
+                ))
+            }
+            FormFieldValueImpl::Multipart(multipart) => Box::pin(
+                multipart
+                    .inner
+                    .map(|chunk| chunk.map_err(std::io::Error::other)),
+            ),
         }
     }
 
@@ -167,6 +218,8 @@ enum FormFieldValueImpl<'a> {
 #[derive(Debug)]
 struct MultipartField<'a> {
     inner: multer::Field<'a>,
+    #[cfg(feature = "storage")]
+    storage: Option<FileStorage>,
 }
 
 /// An error that can occur when processing a form field value.
@@ -203,6 +256,12 @@ enum FormFieldValueErrorImpl {
     NoName,
     #[error("file field requires the form to be sent as `multipart/form-data`")]
     MultipartRequired,
+    #[cfg(feature = "storage")]
+    #[error("file storage is not available for this request")]
+    StorageUnavailable,
+    #[cfg(feature = "storage")]
+    #[error("failed to save the uploaded file: {0}")]
+    Storage(String),
 }
 
 impl FormFieldValueError {
@@ -223,6 +282,68 @@ impl FormFieldValueError {
             inner: FormFieldValueErrorImpl::MultipartRequired,
         }
     }
+
+    #[cfg(feature = "storage")]
+    pub(crate) fn storage_unavailable() -> Self {
+        Self {
+            inner: FormFieldValueErrorImpl::StorageUnavailable,
+        }
+    }
+
+    #[cfg(feature = "storage")]
+    pub(crate) fn from_storage(error: &StorageError) -> Self {
+        // exceeding the size limits is reported as a read error by the storage
+        // backends; keep the original error so it can be reported as such
+        let size_error = match error {
+            StorageError::Read(error) => error
+                .get_ref()
+                .and_then(|error| error.downcast_ref::<multer::Error>())
+                .and_then(copy_size_limit_error),
+            _ => None,
+        };
+        if let Some(size_error) = size_error {
+            return Self::from_multer(size_error);
+        }
+
+        Self {
+            inner: FormFieldValueErrorImpl::Storage(error.to_string()),
+        }
+    }
+
+    /// Returns the [`FormError`] to report if this error was caused by
+    /// exceeding one of the size limits configured in
+    /// [`FormsConfig`](crate::config::FormsConfig).
+    pub(crate) fn size_limit_error(&self) -> Option<FormError> {
+        match &self.inner {
+            FormFieldValueErrorImpl::Multer(multer::Error::FieldSizeExceeded {
+                limit,
+                field_name,
+            }) => Some(FormError::FieldTooLarge {
+                field: field_name.clone().unwrap_or_default(),
+                limit: *limit,
+            }),
+            FormFieldValueErrorImpl::Multer(multer::Error::StreamSizeExceeded { limit }) => {
+                Some(FormError::BodyTooLarge { limit: *limit })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "storage")]
+fn copy_size_limit_error(error: &multer::Error) -> Option<multer::Error> {
+    match error {
+        multer::Error::FieldSizeExceeded { limit, field_name } => {
+            Some(multer::Error::FieldSizeExceeded {
+                limit: *limit,
+                field_name: field_name.clone(),
+            })
+        }
+        multer::Error::StreamSizeExceeded { limit } => {
+            Some(multer::Error::StreamSizeExceeded { limit: *limit })
+        }
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,13 @@
 mod attrs;
 mod chrono;
 mod files;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "db")]
+mod model_choice;
 mod select;
+#[cfg(feature = "storage")]
+mod storage;
 
 use std::fmt::{Debug, Display, Formatter};
 use std::num::{
@@ -16,11 +22,19 @@ pub use chrono::{
     DateTimeWithTimezoneFieldOptions, TimeField, TimeFieldOptions,
 };
 pub use files::{FileField, FileFieldOptions, InMemoryUploadedFile};
-pub(crate) use select::check_required_multiple;
+#[cfg(feature = "json")]
+pub use json::{JsonField, JsonFieldOptions};
+#[cfg(feature = "db")]
+pub use model_choice::{
+    ModelChoice, ModelChoiceField, ModelChoiceFieldOptions, ModelMultipleChoiceField,
+    ModelMultipleChoiceFieldOptions,
+};
 pub use select::{
     SelectAsFormField, SelectChoice, SelectField, SelectFieldOptions, SelectMultipleField,
     SelectMultipleFieldOptions,
 };
+#[cfg(feature = "storage")]
+pub use storage::{ImageField, ImageFieldOptions, StorageFileField, StorageFileFieldOptions};
 
 use crate::auth::PasswordHash;
 use crate::common_types::{Email, Password, Url};
@@ -78,6 +92,17 @@ pub struct StringFieldOptions {
     /// The maximum length of the field. Used to set the `maxlength` attribute
     /// in the HTML input element.
     pub max_length: Option<u32>,
+    /// The minimum length of the field. Used to set the `minlength` attribute
+    /// in the HTML input element.
+    pub min_length: Option<u32>,
+    /// A regular expression the whole value has to match. Used to set the
+    /// `pattern` attribute in the HTML input element.
+    ///
+    /// Like the HTML attribute, the pattern is implicitly anchored at both
+    /// ends, so `[a-z]+` only accepts values consisting entirely of lowercase
+    /// letters. Empty values of optional fields are not checked against the
+    /// pattern.
+    pub pattern: Option<&'static str>,
 }
 
 impl Display for StringField {
@@ -91,29 +116,61 @@ impl Display for StringField {
         if let Some(max_length) = self.custom_options.max_length {
             tag.attr("maxlength", max_length.to_string());
         }
+        if let Some(min_length) = self.custom_options.min_length {
+            tag.attr("minlength", min_length.to_string());
+        }
+        if let Some(pattern) = self.custom_options.pattern {
+            tag.attr("pattern", pattern);
+        }
         if let Some(value) = &self.value {
             tag.attr("value", value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
 impl HtmlSafe for StringField {}
 
-impl AsFormField for String {
-    type Type = StringField;
-
-    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
-        let value = check_required(field)?;
-
-        if let Some(max_length) = field.custom_options.max_length
+impl StringFieldOptions {
+    fn validate(&self, value: &str) -> Result<(), FormFieldValidationError> {
+        if let Some(max_length) = self.max_length
             && value.len() > max_length as usize
         {
             return Err(FormFieldValidationError::maximum_length_exceeded(
                 max_length,
             ));
         }
+
+        if let Some(min_length) = self.min_length
+            && value.len() < min_length as usize
+        {
+            return Err(FormFieldValidationError::minimum_length_not_met(min_length));
+        }
+
+        if let Some(pattern) = self.pattern {
+            // mirror the HTML `pattern` attribute, which must match the whole value
+            let regex = regex::Regex::new(&format!("^(?:{pattern})$")).map_err(|error| {
+                FormFieldValidationError::from_string(format!(
+                    "invalid pattern `{pattern}`: {error}"
+                ))
+            })?;
+            if !regex.is_match(value) {
+                return Err(FormFieldValidationError::pattern_mismatch(pattern));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AsFormField for String {
+    type Type = StringField;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        let value = check_required(field)?;
+
+        field.custom_options.validate(value)?;
         Ok(value.to_owned())
     }
 
@@ -132,6 +189,7 @@ impl<const LEN: u32> AsFormField for LimitedString<LEN> {
         if value.len() > LEN as usize {
             return Err(FormFieldValidationError::maximum_length_exceeded(LEN));
         }
+        field.custom_options.validate(value)?;
         Ok(LimitedString::new(value.to_owned()).expect("length has already been checked"))
     }
 
@@ -148,6 +206,9 @@ pub struct PasswordFieldOptions {
     /// The maximum length of the field. Used to set the `maxlength` attribute
     /// in the HTML input element.
     pub max_length: Option<u32>,
+    /// The minimum length of the field. Used to set the `minlength` attribute
+    /// in the HTML input element.
+    pub min_length: Option<u32>,
 }
 
 impl Display for PasswordField {
@@ -161,22 +222,21 @@ impl Display for PasswordField {
         if let Some(max_length) = self.custom_options.max_length {
             tag.attr("maxlength", max_length.to_string());
         }
+        if let Some(min_length) = self.custom_options.min_length {
+            tag.attr("minlength", min_length.to_string());
+        }
         // we don't set the value attribute for password fields
         // to avoid leaking the password in the HTML
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
 impl HtmlSafe for PasswordField {}
 
-impl AsFormField for Password {
-    type Type = PasswordField;
-
-    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
-        let value = check_required(field)?;
-
-        if let Some(max_length) = field.custom_options.max_length
+impl PasswordFieldOptions {
+    fn validate(&self, value: &str) -> Result<(), FormFieldValidationError> {
+        if let Some(max_length) = self.max_length
             && value.len() > max_length as usize
         {
             return Err(FormFieldValidationError::maximum_length_exceeded(
@@ -184,6 +244,23 @@ impl AsFormField for Password {
             ));
         }
 
+        if let Some(min_length) = self.min_length
+            && value.len() < min_length as usize
+        {
+            return Err(FormFieldValidationError::minimum_length_not_met(min_length));
+        }
+
+        Ok(())
+    }
+}
+
+impl AsFormField for Password {
+    type Type = PasswordField;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        let value = check_required(field)?;
+
+        field.custom_options.validate(value)?;
         Ok(Password::new(value))
     }
 
@@ -198,14 +275,7 @@ impl AsFormField for PasswordHash {
     fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
         let value = check_required(field)?;
 
-        if let Some(max_length) = field.custom_options.max_length
-            && value.len() > max_length as usize
-        {
-            return Err(FormFieldValidationError::maximum_length_exceeded(
-                max_length,
-            ));
-        }
-
+        field.custom_options.validate(value)?;
         Ok(PasswordHash::from_password(&Password::new(value)))
     }
 
@@ -246,7 +316,7 @@ impl Display for EmailField {
             tag.attr("value", value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -330,7 +400,7 @@ impl<T: Integer> Display for IntegerField<T> {
             tag.attr("value", value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -480,7 +550,7 @@ impl Display for BoolField {
 
         if self.custom_options.must_be_true.unwrap_or(false) {
             bool_input.bool_attr("required");
-            return write!(f, "{}", bool_input.render());
+            return write!(f, "{}", self.options.render_widget(bool_input));
         }
 
         if let Some(value) = &self.value
@@ -496,7 +566,7 @@ impl Display for BoolField {
         hidden_input.attr("value", "0");
         let hidden = hidden_input.render();
 
-        let checkbox = bool_input.render();
+        let checkbox = self.options.render_widget(bool_input);
         write!(f, "{}{}", hidden.as_str(), checkbox.as_str())
     }
 }
@@ -688,7 +758,7 @@ impl<T: Float> Display for FloatField<T> {
             tag.attr("value", value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -794,7 +864,7 @@ impl Display for UrlField {
             tag.attr("value", value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -829,9 +899,14 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             StringFieldOptions {
                 max_length: Some(10),
+                min_length: None,
+                pattern: None,
             },
         );
         let html = field.to_string();
@@ -847,9 +922,14 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             StringFieldOptions {
                 max_length: Some(10),
+                min_length: None,
+                pattern: None,
             },
         );
         field
@@ -867,9 +947,14 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             StringFieldOptions {
                 max_length: Some(10),
+                min_length: None,
+                pattern: None,
             },
         );
         field.set_value(FormFieldValue::new_text("")).await.unwrap();
@@ -884,9 +969,13 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             PasswordFieldOptions {
                 max_length: Some(10),
+                min_length: None,
             },
         );
         let html = field.to_string();
@@ -901,9 +990,13 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             PasswordFieldOptions {
                 max_length: Some(10),
+                min_length: None,
             },
         );
         field
@@ -914,6 +1007,99 @@ mod tests {
         assert_eq!(value.as_str(), "password");
     }
 
+    #[test]
+    fn string_field_render_validation_attributes() {
+        let field = StringField::with_options(
+            FormFieldOptions {
+                id: "test".to_owned(),
+                name: "test".to_owned(),
+                required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
+            },
+            StringFieldOptions {
+                max_length: Some(10),
+                min_length: Some(3),
+                pattern: Some("[a-z]+"),
+            },
+        );
+        let html = field.to_string();
+        assert!(html.contains("minlength=\"3\""));
+        assert!(html.contains("pattern=\"[a-z]+\""));
+    }
+
+    #[cot::test]
+    async fn string_field_clean_validation_attributes() {
+        let mut field = StringField::with_options(
+            FormFieldOptions {
+                id: "test".to_owned(),
+                name: "test".to_owned(),
+                required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
+            },
+            StringFieldOptions {
+                max_length: Some(10),
+                min_length: Some(3),
+                pattern: Some("[a-z]+"),
+            },
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("ab"))
+            .await
+            .unwrap();
+        assert_eq!(
+            String::clean_value(&field),
+            Err(FormFieldValidationError::minimum_length_not_met(3))
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("abc1"))
+            .await
+            .unwrap();
+        assert_eq!(
+            String::clean_value(&field),
+            Err(FormFieldValidationError::pattern_mismatch("[a-z]+"))
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("abcd"))
+            .await
+            .unwrap();
+        assert_eq!(String::clean_value(&field).unwrap(), "abcd");
+    }
+
+    #[cot::test]
+    async fn password_field_clean_min_length() {
+        let mut field = PasswordField::with_options(
+            FormFieldOptions {
+                id: "test".to_owned(),
+                name: "test".to_owned(),
+                required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
+            },
+            PasswordFieldOptions {
+                max_length: None,
+                min_length: Some(8),
+            },
+        );
+        assert!(field.to_string().contains("minlength=\"8\""));
+
+        field
+            .set_value(FormFieldValue::new_text("short"))
+            .await
+            .unwrap();
+        assert_eq!(
+            Password::clean_value(&field).map(|password| password.as_str().to_owned()),
+            Err(FormFieldValidationError::minimum_length_not_met(8))
+        );
+    }
+
     #[test]
     fn email_field_render() {
         let field = EmailField::with_options(
@@ -921,6 +1107,9 @@ mod tests {
                 id: "test_id".to_owned(),
                 name: "test_name".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             EmailFieldOptions {
                 min_length: Some(10),
@@ -944,6 +1133,9 @@ mod tests {
                 id: "email_test".to_owned(),
                 name: "email_test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             EmailFieldOptions {
                 min_length: Some(10),
@@ -967,6 +1159,9 @@ mod tests {
                 id: "email_test".to_owned(),
                 name: "email_test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             EmailFieldOptions {
                 min_length: Some(10),
@@ -990,6 +1185,9 @@ mod tests {
                 id: "email_test".to_owned(),
                 name: "email_test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             EmailFieldOptions {
                 min_length: Some(5),
@@ -1016,6 +1214,9 @@ mod tests {
                 id: "email_test".to_owned(),
                 name: "email_test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             EmailFieldOptions {
                 min_length: Some(5),
@@ -1042,6 +1243,9 @@ mod tests {
                 id: "email_test".to_owned(),
                 name: "email_test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             EmailFieldOptions {
                 min_length: Some(50),
@@ -1069,6 +1273,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             IntegerFieldOptions {
                 min: Some(1),
@@ -1089,6 +1296,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             IntegerFieldOptions {
                 min: Some(1),
@@ -1110,6 +1320,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             IntegerFieldOptions {
                 min: Some(10),
@@ -1134,6 +1347,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             IntegerFieldOptions {
                 min: Some(10),
@@ -1158,6 +1374,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             BoolFieldOptions {
                 must_be_true: Some(false),
@@ -1176,6 +1395,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             BoolFieldOptions {
                 must_be_true: Some(true),
@@ -1194,6 +1416,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             BoolFieldOptions {
                 must_be_true: Some(true),
@@ -1214,6 +1439,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FloatFieldOptions {
                 min: Some(1.5),
@@ -1235,6 +1463,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FloatFieldOptions {
                 min: Some(1.0),
@@ -1256,6 +1487,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FloatFieldOptions {
                 min: Some(5.0),
@@ -1280,6 +1514,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FloatFieldOptions {
                 min: Some(5.0),
@@ -1304,6 +1541,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FloatFieldOptions {
                 min: Some(1.0),
@@ -1334,6 +1574,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FloatFieldOptions {
                 min: Some(1.0),
@@ -1352,6 +1595,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             UrlFieldOptions,
         );
@@ -1373,6 +1619,9 @@ mod tests {
                 id: "id_url".to_owned(),
                 name: "url".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             UrlFieldOptions,
         );
@@ -1393,6 +1642,9 @@ mod tests {
                 id: "id_url".to_owned(),
                 name: "url".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             UrlFieldOptions,
         );
@@ -11,15 +11,13 @@ use cot::form::fields::impl_form_field;
 use cot::html::HtmlTag;
 
 use crate::form::fields::{SelectChoice, SelectField, Step, check_required};
-use crate::form::{AsFormField, FormFieldValidationError};
+use crate::form::{AsFormField, FormFieldBound, FormFieldValidationError};
 
 impl AsFormField for Weekday {
     type Type = SelectField<Self>;
 
     fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
-        let value = check_required(field)?;
-
-        Self::from_str(value)
+        field.clean_choice()
     }
 
     fn to_field_value(&self) -> String {
@@ -110,6 +108,39 @@ fn parse_time_with_fallback(value: &str) -> Result<NaiveTime, ParseError> {
         .or_else(|_| NaiveTime::parse_from_str(value, BROWSER_TIME_WITHOUT_SEC_FMT))
 }
 
+macro_rules! impl_form_field_bound {
+    ($type:ty) => {
+        impl FormFieldBound for $type {
+            type Bound = Self;
+
+            fn as_bound(&self) -> Option<&Self::Bound> {
+                Some(self)
+            }
+        }
+    };
+}
+
+impl_form_field_bound!(NaiveDateTime);
+impl_form_field_bound!(DateTime<FixedOffset>);
+impl_form_field_bound!(NaiveTime);
+impl_form_field_bound!(NaiveDate);
+
+/// Checks that `offset` (the distance between the value and the step base) is
+/// a whole multiple of `step`, mirroring the browser's step validation.
+fn check_step(
+    offset: Duration,
+    step: Option<&Step<Duration>>,
+) -> Result<(), FormFieldValidationError> {
+    if let Some(Step::Value(step)) = step
+        && step.num_milliseconds() > 0
+        && offset.num_milliseconds() % step.num_milliseconds() != 0
+    {
+        return Err(FormFieldValidationError::StepMismatch);
+    }
+
+    Ok(())
+}
+
 impl_form_field!(DateTimeField, DateTimeFieldOptions, "a datetime");
 
 /// Custom options for [`DateTimeField`]
@@ -138,6 +169,9 @@ impl_form_field!(DateTimeField, DateTimeFieldOptions, "a datetime");
 ///         id: "event_time".into(),
 ///         name: "event_time".into(),
 ///         required: true,
+///         help_text: None,
+///         widget: None,
+///         attrs: Vec::new(),
 ///     },
 ///     options,
 /// );
@@ -203,7 +237,7 @@ impl Display for DateTimeField {
             tag.attr("step", step_value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -230,6 +264,9 @@ impl AsFormField for NaiveDateTime {
             return Err(FormFieldValidationError::maximum_value_exceeded(max));
         }
 
+        let base = opts.min.unwrap_or(DateTime::UNIX_EPOCH.naive_utc());
+        check_step(date_time - base, opts.step.as_ref())?;
+
         Ok(date_time)
     }
 
@@ -248,7 +285,7 @@ impl_form_field!(
 
 impl From<ParseError> for FormFieldValidationError {
     fn from(error: ParseError) -> Self {
-        FormFieldValidationError::from_string(error.to_string())
+        FormFieldValidationError::with_code("invalid_format", error.to_string())
     }
 }
 
@@ -286,6 +323,9 @@ impl From<ParseError> for FormFieldValidationError {
 ///         id: "dt".into(),
 ///         name: "dt".into(),
 ///         required: true,
+///         help_text: None,
+///         widget: None,
+///         attrs: Vec::new(),
 ///     },
 ///     options,
 /// );
@@ -387,7 +427,7 @@ impl Display for DateTimeWithTimezoneField {
             tag.attr("step", step_value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -436,6 +476,12 @@ impl AsFormField for DateTime<FixedOffset> {
             return Err(FormFieldValidationError::maximum_value_exceeded(max));
         }
 
+        // the browser steps through the local wall-clock time
+        let base = opts
+            .min
+            .map_or(DateTime::UNIX_EPOCH.naive_utc(), |min| min.naive_local());
+        check_step(naive - base, opts.step.as_ref())?;
+
         Ok(date_time)
     }
 
@@ -471,6 +517,9 @@ impl_form_field!(TimeField, TimeFieldOptions, "a time");
 ///         id: "event_time".into(),
 ///         name: "event_time".into(),
 ///         required: true,
+///         help_text: None,
+///         widget: None,
+///         attrs: Vec::new(),
 ///     },
 ///     options,
 /// );
@@ -529,7 +578,7 @@ impl Display for TimeField {
             tag.attr("step", step_value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -556,6 +605,9 @@ impl AsFormField for NaiveTime {
             return Err(FormFieldValidationError::maximum_value_exceeded(max));
         }
 
+        let base = opts.min.unwrap_or(NaiveTime::MIN);
+        check_step(time - base, opts.step.as_ref())?;
+
         Ok(time)
     }
 
@@ -592,6 +644,9 @@ impl_form_field!(DateField, DateFieldOptions, "a date");
 ///         id: "event_time".into(),
 ///         name: "event_time".into(),
 ///         required: true,
+///         help_text: None,
+///         widget: None,
+///         attrs: Vec::new(),
 ///     },
 ///     options,
 /// );
@@ -650,7 +705,7 @@ impl Display for DateField {
             tag.attr("step", step_value);
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -662,8 +717,7 @@ impl AsFormField for NaiveDate {
         Self: Sized,
     {
         let value = check_required(field)?;
-        let date = NaiveDate::parse_from_str(value, BROWSER_DATE_FMT)
-            .map_err(|err| FormFieldValidationError::from_string(err.to_string()))?;
+        let date = NaiveDate::parse_from_str(value, BROWSER_DATE_FMT)?;
         let opts = &field.custom_options;
 
         if let Some(min) = &opts.min
@@ -678,6 +732,9 @@ impl AsFormField for NaiveDate {
             return Err(FormFieldValidationError::maximum_value_exceeded(max));
         }
 
+        let base = opts.min.unwrap_or(DateTime::UNIX_EPOCH.date_naive());
+        check_step(date - base, opts.step.as_ref())?;
+
         Ok(date)
     }
 
@@ -776,6 +833,9 @@ mod tests {
                 id: "weekday".to_owned(),
                 name: "weekday".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -796,6 +856,9 @@ mod tests {
                 id: "weekday".to_owned(),
                 name: "weekday".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -821,6 +884,9 @@ mod tests {
                 id: "weekday".to_owned(),
                 name: "weekday".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -845,6 +911,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -876,6 +945,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -891,6 +963,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -926,6 +1001,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -952,6 +1030,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -978,6 +1059,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -999,6 +1083,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -1029,6 +1116,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -1069,6 +1159,9 @@ mod tests {
                 id: "weekday".to_owned(),
                 name: "weekday".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -1100,6 +1193,9 @@ mod tests {
                 id: "weekdays".to_owned(),
                 name: "weekdays".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -1121,6 +1217,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeFieldOptions {
                 min: Some(
@@ -1152,6 +1251,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeFieldOptions {
                 min: Some(
@@ -1174,6 +1276,44 @@ mod tests {
         }
     }
 
+    #[cot::test]
+    async fn datetime_field_clean_step_mismatch() {
+        let mut field = DateTimeField::with_options(
+            FormFieldOptions {
+                id: "dt".into(),
+                name: "dt".into(),
+                required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
+            },
+            DateTimeFieldOptions {
+                min: Some(
+                    NaiveDateTime::parse_from_str("2025-05-27T09:00:00", "%Y-%m-%dT%H:%M:%S")
+                        .unwrap(),
+                ),
+                max: None,
+                readonly: None,
+                step: Some(Step::Value(Duration::minutes(15))),
+            },
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("2025-05-27T09:45"))
+            .await
+            .unwrap();
+        assert!(NaiveDateTime::clean_value(&field).is_ok());
+
+        field
+            .set_value(FormFieldValue::new_text("2025-05-27T09:50"))
+            .await
+            .unwrap();
+        assert_eq!(
+            NaiveDateTime::clean_value(&field),
+            Err(FormFieldValidationError::StepMismatch)
+        );
+    }
+
     #[cot::test]
     async fn datetime_field_clean_below_min() {
         let mut field = DateTimeField::with_options(
@@ -1181,6 +1321,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeFieldOptions {
                 min: Some(
@@ -1209,6 +1352,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeFieldOptions {
                 min: None,
@@ -1237,6 +1383,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: Some(
@@ -1267,6 +1416,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1294,6 +1446,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1321,6 +1476,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1348,6 +1506,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1375,6 +1536,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1404,6 +1568,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1435,6 +1602,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: Some(min_dt),
@@ -1465,6 +1635,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1493,6 +1666,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1519,6 +1695,9 @@ mod tests {
                 id: "dt".into(),
                 name: "dt".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateTimeWithTimezoneFieldOptions {
                 min: None,
@@ -1542,6 +1721,9 @@ mod tests {
                 id: "time".into(),
                 name: "time".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             TimeFieldOptions {
                 min: Some(NaiveTime::parse_from_str("09:00:00", "%H:%M:%S").unwrap()),
@@ -1566,6 +1748,9 @@ mod tests {
                 id: "t".into(),
                 name: "t".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             TimeFieldOptions {
                 min: Some(NaiveTime::parse_from_str("09:00:00", "%H:%M:%S").unwrap()),
@@ -1589,6 +1774,9 @@ mod tests {
                 id: "t".into(),
                 name: "t".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             TimeFieldOptions {
                 min: Some(NaiveTime::parse_from_str("09:00:00", "%H:%M:%S").unwrap()),
@@ -1617,6 +1805,9 @@ mod tests {
                 id: "t".into(),
                 name: "t".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             TimeFieldOptions {
                 min: None,
@@ -1646,6 +1837,9 @@ mod tests {
                 id: "d".into(),
                 name: "d".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateFieldOptions {
                 min: Some(NaiveDate::parse_from_str("2025-05-27", "%Y-%m-%d").unwrap()),
@@ -1668,6 +1862,9 @@ mod tests {
                 id: "d".into(),
                 name: "d".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateFieldOptions {
                 min: Some(NaiveDate::parse_from_str("2025-05-27", "%Y-%m-%d").unwrap()),
@@ -1691,6 +1888,9 @@ mod tests {
                 id: "d".into(),
                 name: "d".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateFieldOptions {
                 min: Some(NaiveDate::parse_from_str("2025-05-27", "%Y-%m-%d").unwrap()),
@@ -1717,6 +1917,9 @@ mod tests {
                 id: "d".into(),
                 name: "d".into(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             DateFieldOptions {
                 min: None,
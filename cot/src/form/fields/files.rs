@@ -62,10 +62,55 @@ pub struct FileFieldOptions {
     /// - `".pdf"` - Accepts PDF files
     /// - `"application/pdf"` - Accepts PDF files by MIME type
     ///
+    /// The same list is checked when the form is validated: an uploaded file
+    /// is accepted if its filename has one of the listed extensions or its
+    /// content type matches one of the listed MIME types.
+    ///
     /// [`accept` attribute]: https://developer.mozilla.org/en-US/docs/Web/HTML/Reference/Elements/input/file#limiting_accepted_file_types
     pub accept: Option<Vec<String>>,
 }
 
+impl FileFieldOptions {
+    fn accepts(&self, filename: Option<&str>, content_type: Option<&str>) -> bool {
+        accepts(self.accept.as_deref(), filename, content_type)
+    }
+}
+
+/// Checks whether a file with the given filename and content type matches
+/// one of the accepted file types. Every file is accepted if the list is
+/// `None`.
+pub(super) fn accepts(
+    accept: Option<&[String]>,
+    filename: Option<&str>,
+    content_type: Option<&str>,
+) -> bool {
+    let Some(accept) = accept else {
+        return true;
+    };
+
+    let content_type = content_type
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|content_type| content_type.trim().to_ascii_lowercase());
+    let filename = filename.map(str::to_ascii_lowercase);
+
+    accept.iter().any(|accepted| {
+        let accepted = accepted.trim().to_ascii_lowercase();
+        if accepted.starts_with('.') {
+            filename
+                .as_deref()
+                .is_some_and(|filename| filename.ends_with(&accepted))
+        } else if let Some(prefix) = accepted.strip_suffix("/*") {
+            content_type.as_deref().is_some_and(|content_type| {
+                content_type
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+        } else {
+            content_type.as_deref() == Some(accepted.as_str())
+        }
+    })
+}
+
 impl Display for FileField {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut tag = HtmlTag::input("file");
@@ -78,7 +123,7 @@ impl Display for FileField {
             tag.attr("accept", accept.join(","));
         }
 
-        write!(f, "{}", tag.render())
+        write!(f, "{}", self.options.render_widget(tag))
     }
 }
 
@@ -110,6 +155,15 @@ impl AsFormField for InMemoryUploadedFile {
             Err(FormFieldValidationError::Required)
         }?;
 
+        if !field
+            .custom_options
+            .accepts(field.filename.as_deref(), field.content_type.as_deref())
+        {
+            return Err(FormFieldValidationError::file_type_not_accepted(
+                field.custom_options.accept.as_deref().unwrap_or_default(),
+            ));
+        }
+
         Ok(Self {
             filename: field.filename.clone(),
             content_type: field.content_type.clone(),
@@ -158,6 +212,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FileFieldOptions {
                 accept: Some(vec!["image/*".to_string(), ".pdf".to_string()]),
@@ -178,6 +235,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FileFieldOptions { accept: None },
         );
@@ -196,6 +256,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FileFieldOptions { accept: None },
         );
@@ -224,6 +287,19 @@ mod tests {
         assert_eq!(value.content(), &bytes::Bytes::from("test content"));
     }
 
+    #[test]
+    fn file_field_options_accepts() {
+        let options = FileFieldOptions {
+            accept: Some(vec!["image/*".to_string(), ".pdf".to_string()]),
+        };
+
+        assert!(options.accepts(Some("photo.jpg"), Some("image/jpeg")));
+        assert!(options.accepts(Some("Report.PDF"), Some("application/octet-stream")));
+        assert!(!options.accepts(Some("test.txt"), Some("text/plain")));
+        assert!(!options.accepts(None, Some("imagex/png")));
+        assert!(FileFieldOptions { accept: None }.accepts(None, None));
+    }
+
     #[cot::test]
     async fn file_field_clean_required() {
         let field = FileField::with_options(
@@ -231,6 +307,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             FileFieldOptions { accept: None },
         );
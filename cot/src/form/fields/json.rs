@@ -0,0 +1,136 @@
+use std::fmt::{Display, Formatter};
+
+use askama::filters::HtmlSafe;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::form::fields::{check_required, impl_form_field};
+use crate::form::{AsFormField, FormField, FormFieldValidationError};
+use crate::html::HtmlTag;
+use crate::json::Json;
+
+impl_form_field!(JsonField, JsonFieldOptions, "a JSON value");
+
+/// Custom options for a [`JsonField`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct JsonFieldOptions {
+    /// If `true`, the value is displayed, but cannot be edited. Used to set
+    /// the `readonly` attribute in the HTML textarea element.
+    pub readonly: Option<bool>,
+}
+
+impl Display for JsonField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut tag = HtmlTag::new("textarea");
+        tag.attr("name", self.id());
+        tag.attr("id", self.id());
+        tag.attr("class", "json-editor");
+        tag.attr("spellcheck", "false");
+        if self.options.required {
+            tag.bool_attr("required");
+        }
+        if let Some(readonly) = self.custom_options.readonly
+            && readonly
+        {
+            tag.bool_attr("readonly");
+        }
+        // the submitted text is rendered back as-is, so that invalid JSON can be
+        // fixed by the user instead of being lost
+        tag.push_str(self.value.as_deref().unwrap_or_default());
+
+        write!(f, "{}", self.options.render_widget(tag))
+    }
+}
+
+impl HtmlSafe for JsonField {}
+
+impl<T: Serialize + DeserializeOwned> AsFormField for Json<T> {
+    type Type = JsonField;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        let value = check_required(field)?;
+
+        let data = serde_json::from_str(value).map_err(|error| {
+            FormFieldValidationError::with_code("invalid_json", format!("Invalid JSON: {error}"))
+        })?;
+        Ok(Json(data))
+    }
+
+    fn to_field_value(&self) -> String {
+        serde_json::to_string_pretty(&self.0).expect("JSON values should always serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::form::{FormFieldOptions, FormFieldValue};
+
+    fn json_field() -> JsonField {
+        JsonField::with_options(
+            FormFieldOptions {
+                id: "data".to_owned(),
+                name: "data".to_owned(),
+                required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
+            },
+            JsonFieldOptions::default(),
+        )
+    }
+
+    #[cot::test]
+    async fn json_field_render_keeps_submitted_value() {
+        let mut field = json_field();
+        field
+            .set_value(FormFieldValue::new_text("{\"a\": <1>}"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            field.to_string(),
+            "<textarea name=\"data\" id=\"data\" class=\"json-editor\" spellcheck=\"false\" \
+            required>{&#34;a&#34;: &#60;1&#62;}</textarea>"
+        );
+    }
+
+    #[cot::test]
+    async fn json_field_clean_value() {
+        let mut field = json_field();
+        field
+            .set_value(FormFieldValue::new_text("{\"a\": 1, \"b\": 2}"))
+            .await
+            .unwrap();
+
+        let Json(value) = Json::<BTreeMap<String, i32>>::clean_value(&field).unwrap();
+        assert_eq!(
+            value,
+            BTreeMap::from([("a".to_owned(), 1), ("b".to_owned(), 2)])
+        );
+    }
+
+    #[cot::test]
+    async fn json_field_clean_invalid() {
+        let mut field = json_field();
+        field
+            .set_value(FormFieldValue::new_text("{\"a\": "))
+            .await
+            .unwrap();
+
+        let error = Json::<serde_json::Value>::clean_value(&field).unwrap_err();
+        assert_eq!(error.code(), "invalid_json");
+    }
+
+    #[test]
+    fn json_to_field_value_is_pretty() {
+        let value = Json(serde_json::json!({"a": [1, 2]}));
+
+        assert_eq!(
+            value.to_field_value(),
+            "{\n  \"a\": [\n    1,\n    2\n  ]\n}"
+        );
+    }
+}
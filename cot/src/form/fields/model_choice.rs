@@ -0,0 +1,603 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Deref;
+
+use askama::filters::HtmlSafe;
+use indexmap::IndexSet;
+
+use crate::db::query::Query;
+use crate::db::{self, DatabaseBackend, ForeignKey, Model};
+use crate::form::fields::select::{SelectChoice, render_select};
+use crate::form::{
+    AsFormField, FormField, FormFieldOptions, FormFieldValidationError, FormFieldValue,
+    FormFieldValueError,
+};
+
+/// A model instance selected in a [`ModelChoiceField`] or a
+/// [`ModelMultipleChoiceField`].
+///
+/// Use `ModelChoice<T>` as the type of a form field to render it as a dropdown
+/// list of the instances of model `T`, or `Vec<ModelChoice<T>>` to allow
+/// selecting multiple instances. The selected instance can be converted into a
+/// [`ForeignKey`] with [`From`].
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::{Display, Formatter};
+///
+/// use cot::db::{Auto, Database, ForeignKey, model};
+/// use cot::form::fields::ModelChoice;
+/// use cot::form::{Form, FormResult};
+/// use cot::request::Request;
+///
+/// #[derive(Debug, Clone)]
+/// #[model]
+/// struct Category {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     name: String,
+/// }
+///
+/// impl Display for Category {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "{}", self.name)
+///     }
+/// }
+///
+/// #[derive(Form)]
+/// struct ArticleForm {
+///     title: String,
+///     category: ModelChoice<Category>,
+/// }
+///
+/// async fn add_article(request: &mut Request, db: &Database) -> cot::Result<()> {
+///     let mut context = ArticleForm::build_context(request).await?;
+///     // the choices have to be loaded before the form is validated or rendered
+///     context.category.load_choices(db).await?;
+///
+///     if let Some(form) = ArticleForm::from_context(&mut context).await {
+///         let category: ForeignKey<Category> = form.category.into();
+///         // ...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelChoice<T>(T);
+
+impl<T> ModelChoice<T> {
+    /// Creates a new `ModelChoice` for the given model instance.
+    #[must_use]
+    pub const fn new(model: T) -> Self {
+        Self(model)
+    }
+
+    /// Returns the selected model instance.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ModelChoice<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Model> From<ModelChoice<T>> for ForeignKey<T> {
+    fn from(choice: ModelChoice<T>) -> Self {
+        ForeignKey::Model(Box::new(choice.0))
+    }
+}
+
+/// Custom options for a [`ModelChoiceField`].
+#[derive(Debug, Clone)]
+pub struct ModelChoiceFieldOptions<T> {
+    /// The query used to load the available choices. If not set, all the
+    /// instances of the model are available.
+    pub query: Option<Query<T>>,
+    /// Custom text for the empty option when the field is not required.
+    /// If not set, "—" will be used as the default empty option text.
+    pub none_option: Option<String>,
+}
+
+impl<T> Default for ModelChoiceFieldOptions<T> {
+    fn default() -> Self {
+        Self {
+            query: None,
+            none_option: None,
+        }
+    }
+}
+
+/// A form field for selecting a model instance from a dropdown list.
+///
+/// The available choices are loaded from the database with
+/// [`Self::load_choices`], which has to be called before the field is
+/// rendered or validated; until then, no choice is available. The options are
+/// identified by the primary keys of the instances and labeled using their
+/// [`Display`] implementation.
+///
+/// See [`ModelChoice`] for an example.
+#[derive(Debug)]
+pub struct ModelChoiceField<T> {
+    options: FormFieldOptions,
+    custom_options: ModelChoiceFieldOptions<T>,
+    choices: Vec<T>,
+    value: Option<String>,
+}
+
+impl<T: Model + Send> FormField for ModelChoiceField<T>
+where
+    T: Display,
+    T::PrimaryKey: AsFormField,
+{
+    type CustomOptions = ModelChoiceFieldOptions<T>;
+
+    fn with_options(options: FormFieldOptions, custom_options: Self::CustomOptions) -> Self {
+        Self {
+            options,
+            custom_options,
+            choices: Vec::new(),
+            value: None,
+        }
+    }
+
+    fn options(&self) -> &FormFieldOptions {
+        &self.options
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    async fn set_value(&mut self, field: FormFieldValue<'_>) -> Result<(), FormFieldValueError> {
+        self.value = Some(field.into_text().await?);
+        Ok(())
+    }
+}
+
+impl<T: Model> ModelChoiceField<T> {
+    /// Loads the available choices from the database, using
+    /// [`ModelChoiceFieldOptions::query`] if it's set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the choices could not be loaded from the database.
+    pub async fn load_choices<DB: DatabaseBackend>(&mut self, db: &DB) -> db::Result<()> {
+        self.choices = load_choices(self.custom_options.query.as_ref(), db).await?;
+        Ok(())
+    }
+
+    /// Returns the loaded choices.
+    #[must_use]
+    pub fn choices(&self) -> &[T] {
+        &self.choices
+    }
+}
+
+impl<T: Model + Clone> ModelChoiceField<T>
+where
+    T::PrimaryKey: AsFormField,
+{
+    /// Returns the model instance whose primary key has been submitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FormFieldValidationError::Required`] if no value has been
+    /// submitted, and [`FormFieldValidationError::InvalidValue`] if the value
+    /// is not the primary key of one of the loaded choices.
+    pub fn clean_choice(&self) -> Result<ModelChoice<T>, FormFieldValidationError> {
+        match self.value.as_deref() {
+            None | Some("") => Err(FormFieldValidationError::Required),
+            Some(value) => find_choice(&self.choices, value),
+        }
+    }
+}
+
+impl<T: Model + Display> Display for ModelChoiceField<T>
+where
+    T::PrimaryKey: AsFormField,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const DEFAULT_NONE_OPTION: &str = "—";
+
+        let value = self.value.iter().cloned().collect();
+        let none_option = if let Some(none_option) = &self.custom_options.none_option {
+            Some(none_option.as_str())
+        } else if self.options.required {
+            None
+        } else {
+            Some(DEFAULT_NONE_OPTION)
+        };
+        render_select(
+            f,
+            &self.options,
+            false,
+            none_option,
+            None,
+            Some(&model_options(&self.choices)),
+            &value,
+        )
+    }
+}
+
+impl<T: Model + Display> HtmlSafe for ModelChoiceField<T> where T::PrimaryKey: AsFormField {}
+
+impl<T> AsFormField for ModelChoice<T>
+where
+    T: Model + Clone + Display + Send,
+    T::PrimaryKey: AsFormField,
+{
+    type Type = ModelChoiceField<T>;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        field.clean_choice()
+    }
+
+    fn to_field_value(&self) -> String {
+        self.0.primary_key().to_field_value()
+    }
+}
+
+/// Custom options for a [`ModelMultipleChoiceField`].
+#[derive(Debug, Clone)]
+pub struct ModelMultipleChoiceFieldOptions<T> {
+    /// The query used to load the available choices. If not set, all the
+    /// instances of the model are available.
+    pub query: Option<Query<T>>,
+    /// The number of visible options in the select box.
+    /// Sets the [`size`] attribute on the HTML select element.
+    ///
+    /// [`size`]: https://developer.mozilla.org/en-US/docs/Web/HTML/Reference/Attributes/size
+    pub size: Option<u32>,
+}
+
+impl<T> Default for ModelMultipleChoiceFieldOptions<T> {
+    fn default() -> Self {
+        Self {
+            query: None,
+            size: None,
+        }
+    }
+}
+
+/// A form field for selecting multiple model instances from a list.
+///
+/// This is the multiple-choice counterpart of [`ModelChoiceField`], used for
+/// `Vec<ModelChoice<T>>` form fields. Like there, the choices have to be
+/// loaded with [`Self::load_choices`] before the field is rendered or
+/// validated.
+#[derive(Debug)]
+pub struct ModelMultipleChoiceField<T> {
+    options: FormFieldOptions,
+    custom_options: ModelMultipleChoiceFieldOptions<T>,
+    choices: Vec<T>,
+    value: IndexSet<String>,
+}
+
+impl<T: Model + Send> FormField for ModelMultipleChoiceField<T>
+where
+    T: Display,
+    T::PrimaryKey: AsFormField,
+{
+    type CustomOptions = ModelMultipleChoiceFieldOptions<T>;
+
+    fn with_options(options: FormFieldOptions, custom_options: Self::CustomOptions) -> Self {
+        Self {
+            options,
+            custom_options,
+            choices: Vec::new(),
+            value: IndexSet::new(),
+        }
+    }
+
+    fn options(&self) -> &FormFieldOptions {
+        &self.options
+    }
+
+    fn value(&self) -> Option<&str> {
+        None
+    }
+
+    async fn set_value(&mut self, field: FormFieldValue<'_>) -> Result<(), FormFieldValueError> {
+        self.value.insert(field.into_text().await?);
+        Ok(())
+    }
+}
+
+impl<T: Model> ModelMultipleChoiceField<T> {
+    /// Loads the available choices from the database, using
+    /// [`ModelMultipleChoiceFieldOptions::query`] if it's set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the choices could not be loaded from the database.
+    pub async fn load_choices<DB: DatabaseBackend>(&mut self, db: &DB) -> db::Result<()> {
+        self.choices = load_choices(self.custom_options.query.as_ref(), db).await?;
+        Ok(())
+    }
+
+    /// Returns the loaded choices.
+    #[must_use]
+    pub fn choices(&self) -> &[T] {
+        &self.choices
+    }
+
+    /// Returns an iterator over the submitted primary keys.
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.value.iter().map(AsRef::as_ref)
+    }
+}
+
+impl<T: Model + Clone> ModelMultipleChoiceField<T>
+where
+    T::PrimaryKey: AsFormField,
+{
+    /// Returns the model instances whose primary keys have been submitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FormFieldValidationError::Required`] if no value has been
+    /// submitted, and [`FormFieldValidationError::InvalidValue`] if any of the
+    /// values is not the primary key of one of the loaded choices.
+    pub fn clean_choices<C: FromIterator<ModelChoice<T>>>(
+        &self,
+    ) -> Result<C, FormFieldValidationError> {
+        if self.value.is_empty() {
+            return Err(FormFieldValidationError::Required);
+        }
+
+        self.value
+            .iter()
+            .map(|value| find_choice(&self.choices, value))
+            .collect()
+    }
+}
+
+impl<T: Model + Display> Display for ModelMultipleChoiceField<T>
+where
+    T::PrimaryKey: AsFormField,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        render_select(
+            f,
+            &self.options,
+            true,
+            None,
+            self.custom_options.size,
+            Some(&model_options(&self.choices)),
+            &self.value,
+        )
+    }
+}
+
+impl<T: Model + Display> HtmlSafe for ModelMultipleChoiceField<T> where T::PrimaryKey: AsFormField {}
+
+impl<T> AsFormField for Vec<ModelChoice<T>>
+where
+    T: Model + Clone + Display + Send,
+    T::PrimaryKey: AsFormField,
+{
+    type Type = ModelMultipleChoiceField<T>;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        field.clean_choices()
+    }
+
+    fn to_field_value(&self) -> String {
+        String::new()
+    }
+}
+
+async fn load_choices<T: Model, DB: DatabaseBackend>(
+    query: Option<&Query<T>>,
+    db: &DB,
+) -> db::Result<Vec<T>> {
+    match query {
+        Some(query) => query.all(db).await,
+        None => T::objects().all(db).await,
+    }
+}
+
+fn find_choice<T: Model + Clone>(
+    choices: &[T],
+    value: &str,
+) -> Result<ModelChoice<T>, FormFieldValidationError>
+where
+    T::PrimaryKey: AsFormField,
+{
+    choices
+        .iter()
+        .find(|choice| choice.primary_key().to_field_value() == value)
+        .map(|choice| ModelChoice(choice.clone()))
+        .ok_or_else(|| FormFieldValidationError::invalid_value(value))
+}
+
+/// An option of a model choice select box, identified by the primary key of
+/// the model instance.
+struct ModelOption {
+    id: String,
+    label: String,
+}
+
+impl SelectChoice for ModelOption {
+    fn from_str(s: &str) -> Result<Self, FormFieldValidationError> {
+        Err(FormFieldValidationError::invalid_value(s))
+    }
+
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn to_string(&self) -> String {
+        self.label.clone()
+    }
+}
+
+fn model_options<T: Model + Display>(choices: &[T]) -> Vec<ModelOption>
+where
+    T::PrimaryKey: AsFormField,
+{
+    choices
+        .iter()
+        .map(|choice| ModelOption {
+            id: choice.primary_key().to_field_value(),
+            label: choice.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use cot_macros::model;
+
+    use super::*;
+    use crate::db::Auto;
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Category {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+    }
+
+    impl Display for Category {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.name)
+        }
+    }
+
+    fn categories() -> Vec<Category> {
+        vec![
+            Category {
+                id: Auto::fixed(1),
+                name: "News".to_owned(),
+            },
+            Category {
+                id: Auto::fixed(2),
+                name: "Sports".to_owned(),
+            },
+        ]
+    }
+
+    fn field_options(required: bool) -> FormFieldOptions {
+        FormFieldOptions {
+            id: "category".to_owned(),
+            name: "Category".to_owned(),
+            required,
+            help_text: None,
+            widget: None,
+            attrs: Vec::new(),
+        }
+    }
+
+    #[cot::test]
+    async fn model_choice_field_render() {
+        let mut field = ModelChoiceField::<Category>::with_options(
+            field_options(false),
+            ModelChoiceFieldOptions::default(),
+        );
+        field.choices = categories();
+        field
+            .set_value(FormFieldValue::new_text("2"))
+            .await
+            .unwrap();
+        let html = field.to_string();
+
+        assert!(html.contains(r#"<select name="category" id="category">"#));
+        assert!(html.contains(r#"<option value="">—</option>"#));
+        assert!(html.contains(r#"<option value="1">News</option>"#));
+        assert!(html.contains(r#"<option value="2" selected>Sports</option>"#));
+    }
+
+    #[cot::test]
+    async fn model_choice_field_clean() {
+        let mut field = ModelChoiceField::<Category>::with_options(
+            field_options(true),
+            ModelChoiceFieldOptions::default(),
+        );
+        assert_eq!(
+            ModelChoice::<Category>::clean_value(&field),
+            Err(FormFieldValidationError::Required)
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("2"))
+            .await
+            .unwrap();
+        // no choices have been loaded
+        assert_eq!(
+            ModelChoice::<Category>::clean_value(&field),
+            Err(FormFieldValidationError::invalid_value("2"))
+        );
+
+        field.choices = categories();
+        let choice = ModelChoice::<Category>::clean_value(&field).unwrap();
+        assert_eq!(choice.name, "Sports");
+        assert_eq!(choice.to_field_value(), "2");
+        let foreign_key = ForeignKey::from(choice);
+        assert_eq!(foreign_key.primary_key(), &Auto::fixed(2));
+    }
+
+    #[cot::test]
+    async fn model_choice_field_clean_optional() {
+        let field = ModelChoiceField::<Category>::with_options(
+            field_options(false),
+            ModelChoiceFieldOptions::default(),
+        );
+
+        assert_eq!(
+            Option::<ModelChoice<Category>>::clean_value(&field),
+            Ok(None)
+        );
+    }
+
+    #[cot::test]
+    async fn model_multiple_choice_field() {
+        let mut field = ModelMultipleChoiceField::<Category>::with_options(
+            field_options(true),
+            ModelMultipleChoiceFieldOptions::default(),
+        );
+        field.choices = categories();
+        assert_eq!(
+            Vec::<ModelChoice<Category>>::clean_value(&field),
+            Err(FormFieldValidationError::Required)
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("1"))
+            .await
+            .unwrap();
+        field
+            .set_value(FormFieldValue::new_text("2"))
+            .await
+            .unwrap();
+        let html = field.to_string();
+        assert!(html.contains("multiple"));
+        assert!(html.contains(r#"<option value="1" selected>News</option>"#));
+        assert!(html.contains(r#"<option value="2" selected>Sports</option>"#));
+
+        let choices = Vec::<ModelChoice<Category>>::clean_value(&field).unwrap();
+        assert_eq!(
+            choices,
+            categories()
+                .into_iter()
+                .map(ModelChoice::new)
+                .collect::<Vec<_>>()
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("3"))
+            .await
+            .unwrap();
+        assert_eq!(
+            Vec::<ModelChoice<Category>>::clean_value(&field),
+            Err(FormFieldValidationError::invalid_value("3"))
+        );
+    }
+}
@@ -89,6 +89,27 @@ pub use cot_macros::SelectAsFormField;
 /// assert_eq!(Priority::High.to_string(), "High Priority");
 /// ```
 ///
+/// ## `group`
+///
+/// Put the variant in an option group; consecutive variants with the same
+/// group are rendered inside a single `<optgroup>` element.
+///
+/// ```
+/// use cot::form::fields::SelectChoice;
+///
+/// #[derive(SelectChoice, Debug, PartialEq)]
+/// enum Food {
+///     #[select_choice(group = "Fruits")]
+///     Apple,
+///     #[select_choice(group = "Fruits")]
+///     Banana,
+///     #[select_choice(group = "Vegetables")]
+///     Carrot,
+/// }
+///
+/// assert_eq!(Food::Banana.group().as_deref(), Some("Fruits"));
+/// ```
+///
 /// # Error Cases
 ///
 /// The macro will fail to compile if:
@@ -149,8 +170,7 @@ macro_rules! impl_as_form_field_mult_collection {
             fn clean_value(
                 field: &Self::Type,
             ) -> Result<Self, crate::form::FormFieldValidationError> {
-                let values = crate::form::fields::check_required_multiple(field)?;
-                values.iter().map(|id| <$element>::from_str(id)).collect()
+                field.clean_choices()
             }
 
             fn to_field_value(&self) -> String {
@@ -167,8 +187,7 @@ macro_rules! impl_as_form_field_mult_collection {
             fn clean_value(
                 field: &Self::Type,
             ) -> Result<Self, crate::form::FormFieldValidationError> {
-                let values = crate::form::fields::check_required_multiple(field)?;
-                values.iter().map(|id| <$element>::from_str(id)).collect()
+                field.clean_choices()
             }
 
             fn to_field_value(&self) -> String {
@@ -205,6 +224,95 @@ impl<T> Default for SelectFieldOptions<T> {
     }
 }
 
+impl<T: SelectChoice + Send> SelectField<T> {
+    /// Replaces the list of available choices.
+    ///
+    /// This is useful when the choices are only known at runtime, such as
+    /// when they are loaded from the database in the view before rendering
+    /// the form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::fields::{SelectChoice, SelectField, SelectFieldOptions};
+    /// use cot::form::{FormField, FormFieldOptions, FormFieldValidationError};
+    ///
+    /// struct Category {
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// impl SelectChoice for Category {
+    ///     fn from_str(s: &str) -> Result<Self, FormFieldValidationError> {
+    ///         let id = s
+    ///             .parse()
+    ///             .map_err(|_| FormFieldValidationError::invalid_value(s))?;
+    ///         Ok(Self {
+    ///             id,
+    ///             name: String::new(),
+    ///         })
+    ///     }
+    ///
+    ///     fn id(&self) -> String {
+    ///         self.id.to_string()
+    ///     }
+    ///
+    ///     fn to_string(&self) -> String {
+    ///         self.name.clone()
+    ///     }
+    /// }
+    ///
+    /// let mut field = SelectField::<Category>::with_options(
+    ///     FormFieldOptions {
+    ///         id: "category".to_owned(),
+    ///         name: "Category".to_owned(),
+    ///         required: true,
+    ///         help_text: None,
+    ///         widget: None,
+    ///         attrs: Vec::new(),
+    ///     },
+    ///     SelectFieldOptions::default(),
+    /// );
+    /// // e.g. the result of `Category::objects().all(&db).await?`
+    /// field.set_choices(vec![Category {
+    ///     id: 1,
+    ///     name: "News".to_owned(),
+    /// }]);
+    ///
+    /// assert!(field.to_string().contains(r#"<option value="1">News</option>"#));
+    /// ```
+    pub fn set_choices(&mut self, choices: Vec<T>) {
+        self.custom_options.choices = Some(choices);
+    }
+
+    /// Returns the explicitly set list of choices, if any.
+    ///
+    /// If this returns `None`, [`SelectChoice::default_choices`] are used.
+    #[must_use]
+    pub fn choices(&self) -> Option<&[T]> {
+        self.custom_options.choices.as_deref()
+    }
+
+    /// Converts the submitted value into a choice.
+    ///
+    /// If the choices have been set explicitly, either through
+    /// [`SelectFieldOptions::choices`] or [`Self::set_choices`], the submitted
+    /// ID must be one of them; otherwise it is only checked by
+    /// [`SelectChoice::from_str`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FormFieldValidationError::Required`] if no value has been
+    /// submitted, and [`FormFieldValidationError::InvalidValue`] if the value
+    /// is not one of the available choices.
+    pub fn clean_choice(&self) -> Result<T, FormFieldValidationError> {
+        let value = crate::form::fields::check_required(self)?;
+
+        check_available_choice(self.choices(), value)?;
+        T::from_str(value)
+    }
+}
+
 impl<T: SelectChoice + Send> Display for SelectField<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         const DEFAULT_NONE_OPTION: &str = "—";
@@ -224,11 +332,11 @@ impl<T: SelectChoice + Send> Display for SelectField<T> {
         };
         render_select(
             f,
-            self,
+            &self.options,
             false,
             none_option,
             None,
-            self.custom_options.choices.as_ref(),
+            self.choices(),
             &value,
         )
     }
@@ -304,15 +412,69 @@ impl<T> Default for SelectMultipleFieldOptions<T> {
     }
 }
 
+impl<T: SelectChoice + Send> SelectMultipleField<T> {
+    /// Replaces the list of available choices.
+    ///
+    /// This is useful when the choices are only known at runtime, such as
+    /// when they are loaded from the database in the view before rendering
+    /// the form. See [`SelectField::set_choices`] for an example.
+    pub fn set_choices(&mut self, choices: Vec<T>) {
+        self.custom_options.choices = Some(choices);
+    }
+
+    /// Returns the explicitly set list of choices, if any.
+    ///
+    /// If this returns `None`, [`SelectChoice::default_choices`] are used.
+    #[must_use]
+    pub fn choices(&self) -> Option<&[T]> {
+        self.custom_options.choices.as_deref()
+    }
+
+    /// Converts the submitted values into a collection of choices.
+    ///
+    /// Like [`SelectField::clean_choice`], every submitted ID must be one of
+    /// the explicitly set choices, if there are any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FormFieldValidationError::Required`] if no value has been
+    /// submitted, and [`FormFieldValidationError::InvalidValue`] if any of the
+    /// values is not one of the available choices.
+    pub fn clean_choices<C: FromIterator<T>>(&self) -> Result<C, FormFieldValidationError> {
+        let values = check_required_multiple(self)?;
+
+        values
+            .iter()
+            .map(|id| {
+                check_available_choice(self.choices(), id)?;
+                T::from_str(id)
+            })
+            .collect()
+    }
+}
+
+fn check_available_choice<T: SelectChoice>(
+    choices: Option<&[T]>,
+    id: &str,
+) -> Result<(), FormFieldValidationError> {
+    if let Some(choices) = choices
+        && !choices.iter().any(|choice| choice.id() == id)
+    {
+        return Err(FormFieldValidationError::invalid_value(id));
+    }
+
+    Ok(())
+}
+
 impl<T: SelectChoice + Send> Display for SelectMultipleField<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         render_select(
             f,
-            self,
+            &self.options,
             true,
             None,
             self.custom_options.size,
-            self.custom_options.choices.as_ref(),
+            self.choices(),
             &self.value,
         )
     }
@@ -320,22 +482,22 @@ impl<T: SelectChoice + Send> Display for SelectMultipleField<T> {
 
 impl<T: SelectChoice + Send> HtmlSafe for SelectMultipleField<T> {}
 
-fn render_select<T: FormField, S: SelectChoice>(
+pub(super) fn render_select<S: SelectChoice>(
     f: &mut Formatter<'_>,
-    field: &T,
+    options: &FormFieldOptions,
     multiple: bool,
     empty_option: Option<&str>,
     size: Option<u32>,
-    choices: Option<&Vec<S>>,
+    choices: Option<&[S]>,
     selected: &IndexSet<String>,
 ) -> std::fmt::Result {
     let mut tag: HtmlTag = HtmlTag::new("select");
-    tag.attr("name", field.id());
-    tag.attr("id", field.id());
+    tag.attr("name", &options.id);
+    tag.attr("id", &options.id);
     if multiple {
         tag.bool_attr("multiple");
     }
-    if field.options().required {
+    if options.required {
         tag.bool_attr("required");
     }
 
@@ -356,6 +518,9 @@ fn render_select<T: FormField, S: SelectChoice>(
     } else {
         &S::default_choices()
     };
+    // consecutive choices from the same group are rendered in a single
+    // `<optgroup>`
+    let mut current_group: Option<(String, HtmlTag)> = None;
     for choice in choices {
         let mut child = HtmlTag::new("option");
         child
@@ -364,10 +529,30 @@ fn render_select<T: FormField, S: SelectChoice>(
         if selected.contains(&choice.id()) {
             child.bool_attr("selected");
         }
-        tag.push_tag(child);
+
+        let group = choice.group();
+        if current_group.as_ref().map(|(label, _)| label) != group.as_ref() {
+            if let Some((_, optgroup)) = current_group.take() {
+                tag.push_tag(optgroup);
+            }
+            if let Some(label) = group {
+                let mut optgroup = HtmlTag::new("optgroup");
+                optgroup.attr("label", &label);
+                current_group = Some((label, optgroup));
+            }
+        }
+
+        if let Some((_, optgroup)) = &mut current_group {
+            optgroup.push_tag(child);
+        } else {
+            tag.push_tag(child);
+        }
+    }
+    if let Some((_, optgroup)) = current_group {
+        tag.push_tag(optgroup);
     }
 
-    write!(f, "{}", tag.render())
+    write!(f, "{}", options.render_widget(tag))
 }
 
 pub(crate) fn check_required_multiple<T>(
@@ -619,6 +804,48 @@ pub trait SelectChoice {
     /// assert_eq!(Status::Active.to_string(), "Currently Active");
     /// ```
     fn to_string(&self) -> String;
+
+    /// Returns the label of the option group this choice belongs to.
+    ///
+    /// Consecutive choices with the same group are rendered inside a single
+    /// `<optgroup>` element. The default implementation returns `None`, which
+    /// renders the choice outside of any group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::fields::SelectChoice;
+    ///
+    /// #[derive(Debug)]
+    /// enum Fruit {
+    ///     Apple,
+    ///     Carrot,
+    /// }
+    ///
+    /// impl SelectChoice for Fruit {
+    ///     fn group(&self) -> Option<String> {
+    ///         match self {
+    ///             Self::Apple => Some("Fruits".to_string()),
+    ///             Self::Carrot => Some("Vegetables".to_string()),
+    ///         }
+    ///     }
+    /// #
+    /// #     fn from_str(_: &str) -> Result<Self, cot::form::FormFieldValidationError> {
+    /// #         unimplemented!()
+    /// #     }
+    /// #     fn id(&self) -> String {
+    /// #         unimplemented!()
+    /// #     }
+    /// #     fn to_string(&self) -> String {
+    /// #         unimplemented!()
+    /// #     }
+    /// }
+    ///
+    /// assert_eq!(Fruit::Carrot.group().as_deref(), Some("Vegetables"));
+    /// ```
+    fn group(&self) -> Option<String> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -675,6 +902,9 @@ mod tests {
                 id: "test_select".to_owned(),
                 name: "test_select".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -700,6 +930,9 @@ mod tests {
                 id: "test_select".to_owned(),
                 name: "test_select".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -716,6 +949,9 @@ mod tests {
                 id: "test_select".to_owned(),
                 name: "test_select".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions {
                 choices: None,
@@ -735,6 +971,9 @@ mod tests {
                 id: "test_select".to_owned(),
                 name: "test_select".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions {
                 choices: Some(vec![TestChoice::Option1, TestChoice::Option3]),
@@ -755,6 +994,9 @@ mod tests {
                 id: "test_select".to_owned(),
                 name: "test_select".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -775,6 +1017,9 @@ mod tests {
                 id: "test_multi".to_owned(),
                 name: "test_multi".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -797,6 +1042,9 @@ mod tests {
                 id: "test_multi".to_owned(),
                 name: "test_multi".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions {
                 choices: None,
@@ -815,6 +1063,9 @@ mod tests {
                 id: "test_multi".to_owned(),
                 name: "test_multi".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -830,6 +1081,9 @@ mod tests {
                 id: "test_multi".to_owned(),
                 name: "test_multi".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -854,6 +1108,135 @@ mod tests {
         assert!(values.contains(&"opt3"));
     }
 
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum GroupedChoice {
+        Apple,
+        Banana,
+        Carrot,
+        Other,
+    }
+
+    impl SelectChoice for GroupedChoice {
+        fn default_choices() -> Vec<Self> {
+            vec![Self::Apple, Self::Banana, Self::Carrot, Self::Other]
+        }
+
+        fn from_str(s: &str) -> Result<Self, FormFieldValidationError> {
+            Self::default_choices()
+                .into_iter()
+                .find(|choice| choice.id() == s)
+                .ok_or_else(|| FormFieldValidationError::invalid_value(s))
+        }
+
+        fn id(&self) -> String {
+            format!("{self:?}").to_lowercase()
+        }
+
+        fn to_string(&self) -> String {
+            format!("{self:?}")
+        }
+
+        fn group(&self) -> Option<String> {
+            match self {
+                Self::Apple | Self::Banana => Some("Fruits".to_string()),
+                Self::Carrot => Some("Vegetables".to_string()),
+                Self::Other => None,
+            }
+        }
+    }
+
+    #[test]
+    fn select_field_render_groups() {
+        let field = SelectField::<GroupedChoice>::with_options(
+            FormFieldOptions {
+                id: "food".to_owned(),
+                name: "food".to_owned(),
+                required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
+            },
+            SelectFieldOptions::default(),
+        );
+        let html = field.to_string();
+
+        assert_eq!(
+            html,
+            "<select name=\"food\" id=\"food\" required>\
+            <optgroup label=\"Fruits\">\
+            <option value=\"apple\">Apple</option>\
+            <option value=\"banana\">Banana</option>\
+            </optgroup>\
+            <optgroup label=\"Vegetables\">\
+            <option value=\"carrot\">Carrot</option>\
+            </optgroup>\
+            <option value=\"other\">Other</option>\
+            </select>"
+        );
+    }
+
+    #[cot::test]
+    async fn select_field_clean_choice_restricted() {
+        let mut field = SelectField::<TestChoice>::with_options(
+            FormFieldOptions {
+                id: "test_select".to_owned(),
+                name: "test_select".to_owned(),
+                required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
+            },
+            SelectFieldOptions::default(),
+        );
+        field.set_choices(vec![TestChoice::Option1, TestChoice::Option3]);
+
+        field
+            .set_value(FormFieldValue::new_text("opt2"))
+            .await
+            .unwrap();
+        assert_eq!(
+            field.clean_choice(),
+            Err(FormFieldValidationError::invalid_value("opt2"))
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("opt3"))
+            .await
+            .unwrap();
+        assert_eq!(field.clean_choice(), Ok(TestChoice::Option3));
+    }
+
+    #[cot::test]
+    async fn select_multiple_field_clean_choices_restricted() {
+        let mut field = SelectMultipleField::<TestChoice>::with_options(
+            FormFieldOptions {
+                id: "test_multi".to_owned(),
+                name: "test_multi".to_owned(),
+                required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
+            },
+            SelectMultipleFieldOptions::default(),
+        );
+        field.set_choices(vec![TestChoice::Option1]);
+
+        field
+            .set_value(FormFieldValue::new_text("opt1"))
+            .await
+            .unwrap();
+        assert_eq!(
+            Vec::<TestChoice>::clean_value(&field),
+            Ok(vec![TestChoice::Option1])
+        );
+
+        field
+            .set_value(FormFieldValue::new_text("opt2"))
+            .await
+            .unwrap();
+        assert!(Vec::<TestChoice>::clean_value(&field).is_err());
+    }
+
     #[test]
     fn select_choice_default_choices() {
         let choices = TestChoice::default_choices();
@@ -881,6 +1264,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -896,6 +1282,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -919,6 +1308,9 @@ mod tests {
                 id: "test".to_owned(),
                 name: "test".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -952,6 +1344,9 @@ mod tests {
                 id: "choices".to_owned(),
                 name: "choices".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -976,6 +1371,9 @@ mod tests {
                 id: "choices".to_owned(),
                 name: "choices".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -991,6 +1389,9 @@ mod tests {
                 id: "choices".to_owned(),
                 name: "choices".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -1024,6 +1425,9 @@ mod tests {
                 id: "choices".to_owned(),
                 name: "choices".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -1049,6 +1453,9 @@ mod tests {
                 id: "choices".to_owned(),
                 name: "choices".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -1070,6 +1477,9 @@ mod tests {
                 id: "choices".to_owned(),
                 name: "choices".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -1100,6 +1510,9 @@ mod tests {
                 id: "choices".to_owned(),
                 name: "choices".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectMultipleFieldOptions::default(),
         );
@@ -1141,6 +1554,9 @@ mod tests {
                 id: "status".to_owned(),
                 name: "status".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -1164,6 +1580,9 @@ mod tests {
                 id: "status".to_owned(),
                 name: "status".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -1184,6 +1603,9 @@ mod tests {
                 id: "status".to_owned(),
                 name: "status".to_owned(),
                 required: true,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -1201,6 +1623,9 @@ mod tests {
                 id: "status".to_owned(),
                 name: "status".to_owned(),
                 required: false,
+                help_text: None,
+                widget: None,
+                attrs: Vec::new(),
             },
             SelectFieldOptions::default(),
         );
@@ -0,0 +1,672 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use askama::filters::HtmlSafe;
+use bytes::{Bytes, BytesMut};
+use futures_util::StreamExt;
+use futures_util::stream;
+
+use super::files::accepts;
+use crate::form::{
+    AsFormField, FormField, FormFieldOptions, FormFieldValidationError, FormFieldValue,
+    FormFieldValueError,
+};
+use crate::html::HtmlTag;
+use crate::storage::{StorageError, StoredFile, StoredImage};
+
+/// The number of bytes read from the beginning of an upload before it's
+/// saved, used to detect empty uploads and to recognize image formats.
+const HEAD_SIZE: usize = 12;
+
+/// The filename used for uploads that don't have a usable filename.
+const DEFAULT_FILENAME: &str = "upload";
+
+/// A form field for a file that is saved to the project's
+/// [`FileStorage`](crate::storage::FileStorage) as it's being uploaded.
+///
+/// Unlike [`FileField`](super::FileField), this field never keeps the whole
+/// file in memory, which makes it suitable for large uploads.
+///
+/// When the form is created from an existing object, the name of the current
+/// file is displayed next to the input. Note that a file still has to be
+/// uploaded when the form is submitted; the current file is not kept.
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::Form;
+/// use cot::storage::StoredFile;
+///
+/// #[derive(Form)]
+/// struct UploadForm {
+///     #[form(opts(upload_to = "documents".to_owned()))]
+///     document: StoredFile,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct StorageFileField {
+    options: FormFieldOptions,
+    custom_options: StorageFileFieldOptions,
+    upload: Option<Upload>,
+}
+
+impl FormField for StorageFileField {
+    type CustomOptions = StorageFileFieldOptions;
+
+    fn with_options(options: FormFieldOptions, custom_options: Self::CustomOptions) -> Self {
+        Self {
+            options,
+            custom_options,
+            upload: None,
+        }
+    }
+
+    fn options(&self) -> &FormFieldOptions {
+        &self.options
+    }
+
+    fn value(&self) -> Option<&str> {
+        None
+    }
+
+    async fn set_value(&mut self, field: FormFieldValue<'_>) -> Result<(), FormFieldValueError> {
+        self.upload = Some(
+            upload(
+                field,
+                self.custom_options.accept.as_deref(),
+                self.custom_options.upload_to.as_deref(),
+                |_| true,
+            )
+            .await?,
+        );
+        Ok(())
+    }
+}
+
+/// Custom options for a [`StorageFileField`].
+#[derive(Debug, Default, Clone)]
+pub struct StorageFileFieldOptions {
+    /// The accepted file types. Used to set the [`accept` attribute] in the
+    /// HTML input element and checked when the form is validated, the same
+    /// way as [`FileFieldOptions::accept`](super::FileFieldOptions::accept).
+    ///
+    /// Files that are not accepted are not saved to the storage.
+    ///
+    /// [`accept` attribute]: https://developer.mozilla.org/en-US/docs/Web/HTML/Reference/Elements/input/file#limiting_accepted_file_types
+    pub accept: Option<Vec<String>>,
+    /// The directory in the storage the uploaded files are saved to, such as
+    /// `"avatars"`. If `None`, the files are saved to the root of the
+    /// storage.
+    pub upload_to: Option<String>,
+}
+
+impl Display for StorageFileField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        render(
+            f,
+            &self.options,
+            self.custom_options
+                .accept
+                .as_ref()
+                .map(|accept| accept.join(",")),
+            self.upload.as_ref().and_then(Upload::current),
+        )
+    }
+}
+
+impl HtmlSafe for StorageFileField {}
+
+/// A form field for an image that is saved to the project's
+/// [`FileStorage`](crate::storage::FileStorage) as it's being uploaded.
+///
+/// This works like [`StorageFileField`], but additionally checks that the
+/// uploaded file is a PNG, JPEG, GIF, WebP, or BMP image by looking at its
+/// contents. Files that are not images are not saved to the storage.
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::Form;
+/// use cot::storage::StoredImage;
+///
+/// #[derive(Form)]
+/// struct ProfileForm {
+///     #[form(opts(upload_to = "avatars".to_owned()))]
+///     avatar: Option<StoredImage>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ImageField {
+    options: FormFieldOptions,
+    custom_options: ImageFieldOptions,
+    upload: Option<Upload>,
+}
+
+impl FormField for ImageField {
+    type CustomOptions = ImageFieldOptions;
+
+    fn with_options(options: FormFieldOptions, custom_options: Self::CustomOptions) -> Self {
+        Self {
+            options,
+            custom_options,
+            upload: None,
+        }
+    }
+
+    fn options(&self) -> &FormFieldOptions {
+        &self.options
+    }
+
+    fn value(&self) -> Option<&str> {
+        None
+    }
+
+    async fn set_value(&mut self, field: FormFieldValue<'_>) -> Result<(), FormFieldValueError> {
+        self.upload = Some(
+            upload(
+                field,
+                self.custom_options.accept.as_deref(),
+                self.custom_options.upload_to.as_deref(),
+                is_image,
+            )
+            .await?,
+        );
+        Ok(())
+    }
+}
+
+/// Custom options for an [`ImageField`].
+#[derive(Debug, Default, Clone)]
+pub struct ImageFieldOptions {
+    /// The accepted file types. Used to set the [`accept` attribute] in the
+    /// HTML input element and checked when the form is validated. If `None`,
+    /// the `accept` attribute is set to `image/*`.
+    ///
+    /// [`accept` attribute]: https://developer.mozilla.org/en-US/docs/Web/HTML/Reference/Elements/input/file#limiting_accepted_file_types
+    pub accept: Option<Vec<String>>,
+    /// The directory in the storage the uploaded images are saved to, such as
+    /// `"avatars"`. If `None`, the images are saved to the root of the
+    /// storage.
+    pub upload_to: Option<String>,
+}
+
+impl Display for ImageField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let accept = self
+            .custom_options
+            .accept
+            .as_ref()
+            .map_or_else(|| "image/*".to_owned(), |accept| accept.join(","));
+        render(
+            f,
+            &self.options,
+            Some(accept),
+            self.upload.as_ref().and_then(Upload::current),
+        )
+    }
+}
+
+impl HtmlSafe for ImageField {}
+
+impl AsFormField for StoredFile {
+    type Type = StorageFileField;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        clean_upload(
+            field.upload.as_ref(),
+            field.custom_options.accept.as_deref(),
+        )
+    }
+
+    fn to_field_value(&self) -> String {
+        serde_json::to_string(self).expect("serializing a stored file reference never fails")
+    }
+}
+
+impl AsFormField for StoredImage {
+    type Type = ImageField;
+
+    fn clean_value(field: &Self::Type) -> Result<Self, FormFieldValidationError> {
+        clean_upload(
+            field.upload.as_ref(),
+            field.custom_options.accept.as_deref(),
+        )
+        .map(StoredImage::new)
+    }
+
+    fn to_field_value(&self) -> String {
+        self.as_file().to_field_value()
+    }
+}
+
+#[derive(Debug)]
+enum Upload {
+    Empty,
+    /// The file that is currently stored, set when the form is created from
+    /// an existing object. It's only displayed next to the input; a new file
+    /// still has to be uploaded when the form is submitted.
+    Current(StoredFile),
+    NotAccepted,
+    InvalidImage,
+    Stored(StoredFile),
+}
+
+impl Upload {
+    fn current(&self) -> Option<&StoredFile> {
+        match self {
+            Upload::Current(file) => Some(file),
+            _ => None,
+        }
+    }
+}
+
+fn clean_upload(
+    upload: Option<&Upload>,
+    accept: Option<&[String]>,
+) -> Result<StoredFile, FormFieldValidationError> {
+    match upload {
+        None | Some(Upload::Empty | Upload::Current(_)) => Err(FormFieldValidationError::Required),
+        Some(Upload::NotAccepted) => Err(FormFieldValidationError::file_type_not_accepted(
+            accept.unwrap_or_default(),
+        )),
+        Some(Upload::InvalidImage) => Err(FormFieldValidationError::InvalidImage),
+        Some(Upload::Stored(file)) => Ok(file.clone()),
+    }
+}
+
+async fn upload(
+    field: FormFieldValue<'_>,
+    accept: Option<&[String]>,
+    upload_to: Option<&str>,
+    is_valid: fn(&[u8]) -> bool,
+) -> Result<Upload, FormFieldValueError> {
+    if !field.is_multipart() {
+        let text = field.into_text().await?;
+        if text.is_empty() {
+            return Ok(Upload::Empty);
+        }
+        return serde_json::from_str(&text)
+            .map(Upload::Current)
+            .map_err(|_| FormFieldValueError::multipart_required());
+    }
+
+    let filename = field.filename().map(ToOwned::to_owned);
+    let content_type = field.content_type().map(ToOwned::to_owned);
+    let storage = field.storage().cloned();
+
+    let mut content = field.into_stream();
+    let mut head = BytesMut::new();
+    while head.len() < HEAD_SIZE {
+        match content.next().await {
+            Some(chunk) => {
+                head.extend_from_slice(&chunk.map_err(|error| {
+                    FormFieldValueError::from_storage(&StorageError::Read(error))
+                })?);
+            }
+            None => break,
+        }
+    }
+
+    if head.is_empty() {
+        return Ok(Upload::Empty);
+    }
+    if !accepts(accept, filename.as_deref(), content_type.as_deref()) {
+        return Ok(Upload::NotAccepted);
+    }
+    if !is_valid(&head) {
+        return Ok(Upload::InvalidImage);
+    }
+
+    let storage = storage.ok_or_else(FormFieldValueError::storage_unavailable)?;
+    let name = storage_name(upload_to, filename.as_deref());
+    let size = Arc::new(AtomicU64::new(0));
+    let content = {
+        let size = Arc::clone(&size);
+        let head: Bytes = head.freeze();
+        Box::pin(
+            stream::once(async move { Ok(head) })
+                .chain(content)
+                .inspect(move |chunk| {
+                    if let Ok(chunk) = chunk {
+                        size.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                    }
+                }),
+        )
+    };
+    let name = storage
+        .save(&name, content)
+        .await
+        .map_err(|error| FormFieldValueError::from_storage(&error))?;
+
+    Ok(Upload::Stored(StoredFile::uploaded(
+        name,
+        filename,
+        content_type,
+        size.load(Ordering::Relaxed),
+    )))
+}
+
+/// Builds the name the file is saved under in the storage from the filename
+/// sent by the browser, which can't be trusted to be a valid (or safe) name.
+fn storage_name(upload_to: Option<&str>, filename: Option<&str>) -> String {
+    let filename = filename
+        .and_then(|filename| filename.rsplit(['/', '\\']).next())
+        .unwrap_or_default();
+    let filename: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let filename = filename.trim_start_matches('.');
+    let filename = if filename.is_empty() {
+        DEFAULT_FILENAME
+    } else {
+        filename
+    };
+
+    match upload_to
+        .map(|upload_to| upload_to.trim_matches('/'))
+        .filter(|upload_to| !upload_to.is_empty())
+    {
+        Some(upload_to) => format!("{upload_to}/{filename}"),
+        None => filename.to_owned(),
+    }
+}
+
+/// Checks whether the given bytes look like the beginning of a PNG, JPEG,
+/// GIF, WebP, or BMP image.
+fn is_image(head: &[u8]) -> bool {
+    head.starts_with(b"\x89PNG\r\n\x1a\n")
+        || head.starts_with(b"\xff\xd8\xff")
+        || head.starts_with(b"GIF87a")
+        || head.starts_with(b"GIF89a")
+        || (head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP"))
+        || head.starts_with(b"BM")
+}
+
+fn render(
+    f: &mut Formatter<'_>,
+    options: &FormFieldOptions,
+    accept: Option<String>,
+    current: Option<&StoredFile>,
+) -> std::fmt::Result {
+    if let Some(current) = current {
+        let mut name = HtmlTag::new("code");
+        name.push_str(current.name());
+        let mut paragraph = HtmlTag::new("p");
+        paragraph.attr("class", "current-file");
+        paragraph.push_str("Currently: ");
+        paragraph.push_tag(name);
+        write!(f, "{}", paragraph.render())?;
+    }
+
+    let mut tag = HtmlTag::input("file");
+    tag.attr("name", &options.id);
+    tag.attr("id", &options.id);
+    if options.required {
+        tag.bool_attr("required");
+    }
+    if let Some(accept) = accept {
+        tag.attr("accept", accept);
+    }
+
+    write!(f, "{}", options.render_widget(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream::once;
+    use multer::Multipart;
+
+    use super::*;
+    use crate::storage::FileStorage;
+    use crate::storage::memory::Memory;
+
+    fn options() -> FormFieldOptions {
+        FormFieldOptions {
+            id: "test".to_owned(),
+            name: "test".to_owned(),
+            required: true,
+            help_text: None,
+            widget: None,
+            attrs: Vec::new(),
+        }
+    }
+
+    async fn set_file<F: FormField>(
+        field: &mut F,
+        storage: Option<FileStorage>,
+        filename: &str,
+        content_type: &str,
+        content: &'static [u8],
+    ) -> Result<(), FormFieldValueError> {
+        let boundary = "boundary";
+        let mut body = format!(
+            "--{boundary}\r\n\
+            Content-Disposition: form-data; name=\"test\"; filename=\"{filename}\"\r\n\
+            Content-Type: {content_type}\r\n\
+            \r\n"
+        )
+        .into_bytes();
+        body.extend_from_slice(content);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        let stream = once(async move { Ok::<_, std::io::Error>(Bytes::from(body)) });
+        let mut multipart = Multipart::new(stream, boundary);
+        let value = multipart.next_field().await.unwrap().unwrap();
+
+        field
+            .set_value(FormFieldValue::new_multipart(value).with_storage(storage))
+            .await
+    }
+
+    #[test]
+    fn storage_file_field_render() {
+        let field = StorageFileField::with_options(
+            options(),
+            StorageFileFieldOptions {
+                accept: Some(vec![".pdf".to_owned()]),
+                upload_to: None,
+            },
+        );
+
+        let html = field.to_string();
+
+        assert!(html.contains("type=\"file\""));
+        assert!(html.contains("required"));
+        assert!(html.contains("accept=\".pdf\""));
+    }
+
+    #[test]
+    fn image_field_render_default_accept() {
+        let field = ImageField::with_options(options(), ImageFieldOptions::default());
+
+        assert!(field.to_string().contains("accept=\"image/*\""));
+    }
+
+    #[cot::test]
+    async fn storage_file_field_saves_to_storage() {
+        let storage = FileStorage::new(Memory::new());
+        let mut field = StorageFileField::with_options(
+            options(),
+            StorageFileFieldOptions {
+                accept: None,
+                upload_to: Some("documents/".to_owned()),
+            },
+        );
+
+        set_file(
+            &mut field,
+            Some(storage.clone()),
+            "../../etc/my report.txt",
+            "text/plain",
+            b"test content",
+        )
+        .await
+        .unwrap();
+        let value = StoredFile::clean_value(&field).unwrap();
+
+        assert_eq!(value.name(), "documents/my_report.txt");
+        assert_eq!(value.url(&storage), "/media/documents/my_report.txt");
+        assert_eq!(value.size(), Some(12));
+        assert_eq!(value.filename(), Some("../../etc/my report.txt"));
+        assert_eq!(value.content_type(), Some("text/plain"));
+        assert_eq!(storage.open(value.name()).await.unwrap(), "test content");
+    }
+
+    #[cot::test]
+    async fn storage_file_field_empty_upload() {
+        let storage = FileStorage::new(Memory::new());
+        let mut field =
+            StorageFileField::with_options(options(), StorageFileFieldOptions::default());
+
+        set_file(
+            &mut field,
+            Some(storage.clone()),
+            "",
+            "application/octet-stream",
+            b"",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            StoredFile::clean_value(&field),
+            Err(FormFieldValidationError::Required)
+        );
+        assert!(!storage.exists(DEFAULT_FILENAME).await.unwrap());
+    }
+
+    #[cot::test]
+    async fn storage_file_field_not_accepted() {
+        let storage = FileStorage::new(Memory::new());
+        let mut field = StorageFileField::with_options(
+            options(),
+            StorageFileFieldOptions {
+                accept: Some(vec![".pdf".to_owned()]),
+                upload_to: None,
+            },
+        );
+
+        set_file(
+            &mut field,
+            Some(storage.clone()),
+            "test.txt",
+            "text/plain",
+            b"test content",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            StoredFile::clean_value(&field),
+            Err(FormFieldValidationError::file_type_not_accepted(&[
+                ".pdf".to_owned()
+            ]))
+        );
+        assert!(!storage.exists("test.txt").await.unwrap());
+    }
+
+    #[cot::test]
+    async fn storage_file_field_without_storage() {
+        let mut field =
+            StorageFileField::with_options(options(), StorageFileFieldOptions::default());
+
+        let result = set_file(&mut field, None, "test.txt", "text/plain", b"test content").await;
+
+        assert_eq!(result, Err(FormFieldValueError::storage_unavailable()));
+    }
+
+    #[cot::test]
+    async fn image_field_accepts_image() {
+        let storage = FileStorage::new(Memory::new());
+        let mut field = ImageField::with_options(options(), ImageFieldOptions::default());
+
+        set_file(
+            &mut field,
+            Some(storage.clone()),
+            "pixel.png",
+            "image/png",
+            b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR",
+        )
+        .await
+        .unwrap();
+        let value = StoredImage::clean_value(&field).unwrap();
+
+        assert_eq!(value.name(), "pixel.png");
+        assert!(storage.exists("pixel.png").await.unwrap());
+    }
+
+    #[cot::test]
+    async fn image_field_rejects_non_image() {
+        let storage = FileStorage::new(Memory::new());
+        let mut field = ImageField::with_options(options(), ImageFieldOptions::default());
+
+        set_file(
+            &mut field,
+            Some(storage.clone()),
+            "fake.png",
+            "image/png",
+            b"definitely not a PNG",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            StoredImage::clean_value(&field),
+            Err(FormFieldValidationError::InvalidImage)
+        );
+        assert!(!storage.exists("fake.png").await.unwrap());
+    }
+
+    #[cot::test]
+    async fn storage_file_field_current_file() {
+        let file = StoredFile::new("documents/report.pdf");
+        let mut field =
+            StorageFileField::with_options(options(), StorageFileFieldOptions::default());
+
+        field
+            .set_value(FormFieldValue::new_text(file.to_field_value()))
+            .await
+            .unwrap();
+
+        assert!(
+            field
+                .to_string()
+                .contains("Currently: <code>documents/report.pdf</code>")
+        );
+        assert_eq!(
+            StoredFile::clean_value(&field),
+            Err(FormFieldValidationError::Required)
+        );
+    }
+
+    #[test]
+    fn storage_name_sanitizes_filename() {
+        assert_eq!(storage_name(None, Some("photo.jpg")), "photo.jpg");
+        assert_eq!(
+            storage_name(Some("/avatars/"), Some("C:\\fakepath\\me.png")),
+            "avatars/me.png"
+        );
+        assert_eq!(storage_name(None, Some("..")), DEFAULT_FILENAME);
+        assert_eq!(storage_name(None, Some(".htaccess")), "htaccess");
+        assert_eq!(storage_name(Some(""), None), DEFAULT_FILENAME);
+    }
+
+    #[test]
+    fn is_image_formats() {
+        assert!(is_image(b"\x89PNG\r\n\x1a\n\0\0\0\r"));
+        assert!(is_image(b"\xff\xd8\xff\xe0\0\x10JFIF"));
+        assert!(is_image(b"GIF89a\x01\0\x01\0"));
+        assert!(is_image(b"RIFF\x24\0\0\0WEBPVP8 "));
+        assert!(is_image(b"BM\x3a\0\0\0"));
+        assert!(!is_image(b"RIFF\x24\0\0\0WAVEfmt "));
+        assert!(!is_image(b"%PDF-1.7"));
+    }
+}
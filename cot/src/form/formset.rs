@@ -0,0 +1,870 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use crate::form::{
+    Form, FormContext, FormError, FormErrorTarget, FormFieldValidationError, FormFieldValue,
+    form_data,
+};
+use crate::html::{Html, HtmlTag};
+use crate::request::Request;
+
+/// The default maximum number of forms in a [`FormSet`].
+///
+/// The number of forms that are processed is additionally limited to the
+/// maximum plus this value, so that a client can't make the server do an
+/// arbitrary amount of work by sending a huge number of forms.
+pub const DEFAULT_MAX_FORMS: usize = 1000;
+
+const TOTAL_FORMS: &str = "TOTAL_FORMS";
+const INITIAL_FORMS: &str = "INITIAL_FORMS";
+const MIN_NUM_FORMS: &str = "MIN_NUM_FORMS";
+const MAX_NUM_FORMS: &str = "MAX_NUM_FORMS";
+const DELETE: &str = "DELETE";
+
+/// A set of forms of the same type, submitted together in a single request.
+///
+/// This allows editing a dynamic list of objects, such as the items of an
+/// order, on a single page. The fields of each form are prefixed with the
+/// prefix of the form set and the index of the form, e.g. `form-0-name`,
+/// `form-1-name`, etc. The number of submitted forms is sent in the
+/// *management form*, which has to be rendered inside the HTML form along with
+/// the forms (see [`FormSetContext::management_form`]).
+///
+/// The forms can be either *initial* forms, pre-populated with existing
+/// objects, or *extra* forms, which are empty. Extra forms that were left empty
+/// by the user are ignored. If deleting is enabled, each form also has a
+/// checkbox marking it for deletion.
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::{Form, FormSet, FormSetResult};
+/// use cot::html::Html;
+/// use cot::request::Request;
+///
+/// #[derive(Form)]
+/// struct ItemForm {
+///     name: String,
+///     quantity: u32,
+/// }
+///
+/// async fn edit_items(mut request: Request) -> cot::Result<Html> {
+///     let formset = FormSet::<ItemForm>::new().with_extra_forms(3);
+///
+///     match formset.forms_from_request(&mut request).await? {
+///         FormSetResult::Ok(forms) => {
+///             for entry in forms {
+///                 if !entry.should_delete() {
+///                     let item = entry.into_form();
+///                     // save the item...
+///                 }
+///             }
+///             Ok(Html::new("saved"))
+///         }
+///         FormSetResult::ValidationError(context) => {
+///             // render the forms again, along with the errors...
+///             Ok(Html::new(format!("{} errors", context.errors().len())))
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FormSet<F> {
+    prefix: String,
+    extra_forms: usize,
+    min_forms: usize,
+    max_forms: usize,
+    can_delete: bool,
+    phantom_data: PhantomData<fn() -> F>,
+}
+
+impl<F> Clone for FormSet<F> {
+    fn clone(&self) -> Self {
+        Self {
+            prefix: self.prefix.clone(),
+            extra_forms: self.extra_forms,
+            min_forms: self.min_forms,
+            max_forms: self.max_forms,
+            can_delete: self.can_delete,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<F: Form> Default for FormSet<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Form> FormSet<F> {
+    /// Creates a new form set with the `form` prefix, one extra form, no
+    /// minimum number of forms, [`DEFAULT_MAX_FORMS`] as the maximum number of
+    /// forms, and deleting disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormSet};
+    ///
+    /// #[derive(Form)]
+    /// struct ItemForm {
+    ///     name: String,
+    /// }
+    ///
+    /// let formset = FormSet::<ItemForm>::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            prefix: "form".to_owned(),
+            extra_forms: 1,
+            min_forms: 0,
+            max_forms: DEFAULT_MAX_FORMS,
+            can_delete: false,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Sets the prefix of the field names.
+    ///
+    /// This needs to be set to different values if there are multiple form
+    /// sets inside a single HTML form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormSet};
+    ///
+    /// #[derive(Form)]
+    /// struct ItemForm {
+    ///     name: String,
+    /// }
+    ///
+    /// let formset = FormSet::<ItemForm>::new().with_prefix("items");
+    /// let context = formset.context();
+    /// assert_eq!(context.prefix(), "items");
+    /// ```
+    #[must_use]
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the number of empty forms displayed after the initial ones.
+    #[must_use]
+    pub fn with_extra_forms(mut self, extra_forms: usize) -> Self {
+        self.extra_forms = extra_forms;
+        self
+    }
+
+    /// Sets the minimum number of forms that have to be submitted, not counting
+    /// empty extra forms and the forms marked for deletion.
+    #[must_use]
+    pub fn with_min_forms(mut self, min_forms: usize) -> Self {
+        self.min_forms = min_forms;
+        self
+    }
+
+    /// Sets the maximum number of forms that can be submitted, not counting
+    /// empty extra forms and the forms marked for deletion.
+    ///
+    /// The number of extra forms displayed is limited so that the total number
+    /// of forms doesn't exceed this value.
+    #[must_use]
+    pub fn with_max_forms(mut self, max_forms: usize) -> Self {
+        self.max_forms = max_forms;
+        self
+    }
+
+    /// Sets whether the forms can be marked for deletion.
+    ///
+    /// If enabled, each form is accompanied by a `DELETE` checkbox (see
+    /// [`FormSetContext::delete_field`]), and the forms that were marked are
+    /// reported by [`FormSetForm::should_delete`].
+    #[must_use]
+    pub fn with_can_delete(mut self, can_delete: bool) -> Self {
+        self.can_delete = can_delete;
+        self
+    }
+
+    /// Creates the context for a form set without any initial forms.
+    #[must_use]
+    pub fn context(&self) -> FormSetContext<F::Context> {
+        self.build_set_context(Vec::new(), self.extra_forms.min(self.max_forms))
+    }
+
+    /// Creates the context for a form set with initial forms pre-populated
+    /// with the given form values, e.g. converted from the objects obtained
+    /// from the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormSet};
+    ///
+    /// #[derive(Form)]
+    /// struct ItemForm {
+    ///     name: String,
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let items = [ItemForm {
+    ///     name: "Apple".to_owned(),
+    /// }];
+    /// let context = FormSet::<ItemForm>::new().context_from_forms(&items).await;
+    ///
+    /// assert_eq!(context.initial_forms(), 1);
+    /// assert_eq!(context.total_forms(), 2);
+    /// # }
+    /// ```
+    pub async fn context_from_forms(&self, forms: &[F]) -> FormSetContext<F::Context>
+    where
+        F: Sync,
+    {
+        let mut contexts = Vec::with_capacity(forms.len());
+        for (index, form) in forms.iter().enumerate() {
+            let values: Vec<_> = form
+                .to_context()
+                .await
+                .fields()
+                .filter_map(|field| {
+                    Some((field.dyn_id().to_owned(), field.dyn_value()?.to_owned()))
+                })
+                .collect();
+            let mut context = F::Context::with_prefix(&self.form_prefix(index));
+            for (field_id, value) in values {
+                let value = FormFieldValue::new_text(value);
+                if let Err(err) = context.set_value(&field_id, value).await {
+                    context.add_error(FormErrorTarget::Field(&field_id), err);
+                }
+            }
+            contexts.push(context);
+        }
+
+        let extra_forms = self
+            .extra_forms
+            .min(self.max_forms.saturating_sub(forms.len()));
+        self.build_set_context(contexts, extra_forms)
+    }
+
+    /// Creates the forms from a request and validates them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the form data could not be read from the request.
+    /// Invalid forms and a missing or invalid management form don't result in
+    /// an error, but in [`FormSetResult::ValidationError`].
+    pub async fn forms_from_request(
+        &self,
+        request: &mut Request,
+    ) -> Result<FormSetResult<F>, FormError> {
+        let SubmittedForms {
+            total_forms,
+            initial_forms,
+            mut contexts,
+            deleted,
+        } = self.read_form_data(request).await?;
+
+        let (Some(total_forms), Some(initial_forms)) = (total_forms, initial_forms) else {
+            let mut context = self.build_set_context(Vec::new(), 0);
+            context.add_error(FormFieldValidationError::with_code(
+                "missing_management_form",
+                "The management form data is missing or has been tampered with.",
+            ));
+            return Ok(FormSetResult::ValidationError(context));
+        };
+        let total_forms = total_forms.min(self.absolute_max_forms());
+        let initial_forms = initial_forms.min(total_forms);
+
+        let mut forms = Vec::new();
+        let mut form_contexts = Vec::with_capacity(total_forms);
+        let mut has_invalid_forms = false;
+        for index in 0..total_forms {
+            let mut context = contexts
+                .remove(&index)
+                .unwrap_or_else(|| F::Context::with_prefix(&self.form_prefix(index)));
+            let is_initial = index < initial_forms;
+            let should_delete = deleted.contains(&index);
+            let has_changed = context
+                .fields()
+                .any(|field| field.dyn_value().is_some_and(|value| !value.is_empty()));
+
+            if is_initial || (has_changed && !should_delete) {
+                match F::from_context(&mut context).await {
+                    Some(form) => forms.push(FormSetForm {
+                        form,
+                        index,
+                        is_initial,
+                        should_delete,
+                    }),
+                    None => has_invalid_forms = true,
+                }
+            }
+            form_contexts.push(context);
+        }
+
+        let mut context = self.build_set_context(form_contexts, 0);
+        context.initial_forms = initial_forms;
+        context.deleted = deleted;
+
+        let form_count = forms.iter().filter(|form| !form.should_delete).count();
+        if form_count < self.min_forms {
+            context.add_error(FormFieldValidationError::with_code(
+                "too_few_forms",
+                format!("Please submit at least {} forms.", self.min_forms),
+            ));
+        }
+        if form_count > self.max_forms {
+            context.add_error(FormFieldValidationError::with_code(
+                "too_many_forms",
+                format!("Please submit at most {} forms.", self.max_forms),
+            ));
+        }
+
+        if has_invalid_forms || !context.errors.is_empty() {
+            Ok(FormSetResult::ValidationError(context))
+        } else {
+            Ok(FormSetResult::Ok(forms))
+        }
+    }
+
+    async fn read_form_data(
+        &self,
+        request: &mut Request,
+    ) -> Result<SubmittedForms<F::Context>, FormError> {
+        let mut submitted = SubmittedForms {
+            total_forms: None,
+            initial_forms: None,
+            contexts: BTreeMap::new(),
+            deleted: Vec::new(),
+        };
+
+        let mut form_data = form_data(request).await?;
+        while let Some((name, value)) = form_data.next_value().await? {
+            let Some(name) = name
+                .strip_prefix(&self.prefix)
+                .and_then(|name| name.strip_prefix('-'))
+            else {
+                continue;
+            };
+
+            if name == TOTAL_FORMS || name == INITIAL_FORMS {
+                let count = value.into_text().await?.trim().parse::<usize>().ok();
+                if name == TOTAL_FORMS {
+                    submitted.total_forms = count;
+                } else {
+                    submitted.initial_forms = count;
+                }
+                continue;
+            }
+
+            let Some((index, field_id)) = name
+                .split_once('-')
+                .and_then(|(index, field_id)| Some((index.parse::<usize>().ok()?, field_id)))
+            else {
+                continue;
+            };
+            if index >= self.absolute_max_forms() {
+                continue;
+            }
+
+            if field_id == DELETE {
+                let value = value.into_text().await?;
+                if self.can_delete && matches!(value.as_str(), "on" | "true" | "1") {
+                    submitted.deleted.push(index);
+                }
+                continue;
+            }
+
+            let context = submitted
+                .contexts
+                .entry(index)
+                .or_insert_with(|| F::Context::with_prefix(&self.form_prefix(index)));
+            if let Err(err) = context.set_value(field_id, value).await {
+                let size_error = match &err {
+                    FormFieldValidationError::FormFieldValueError(error) => {
+                        error.size_limit_error()
+                    }
+                    _ => None,
+                };
+                if let Some(size_error) = size_error {
+                    return Err(size_error);
+                }
+                context.add_error(FormErrorTarget::Field(field_id), err);
+            }
+        }
+
+        Ok(submitted)
+    }
+
+    fn absolute_max_forms(&self) -> usize {
+        self.max_forms.saturating_add(DEFAULT_MAX_FORMS)
+    }
+
+    fn form_prefix(&self, index: usize) -> String {
+        format!("{}-{index}", self.prefix)
+    }
+
+    fn build_set_context(
+        &self,
+        mut forms: Vec<F::Context>,
+        extra_forms: usize,
+    ) -> FormSetContext<F::Context> {
+        let initial_forms = forms.len();
+        forms.extend(
+            (initial_forms..initial_forms + extra_forms)
+                .map(|index| F::Context::with_prefix(&self.form_prefix(index))),
+        );
+
+        FormSetContext {
+            prefix: self.prefix.clone(),
+            forms,
+            initial_forms,
+            min_forms: self.min_forms,
+            max_forms: self.max_forms,
+            can_delete: self.can_delete,
+            deleted: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+struct SubmittedForms<C> {
+    total_forms: Option<usize>,
+    initial_forms: Option<usize>,
+    contexts: BTreeMap<usize, C>,
+    deleted: Vec<usize>,
+}
+
+/// The result of validating a [`FormSet`].
+#[must_use]
+#[derive(Debug)]
+pub enum FormSetResult<F: Form> {
+    /// All the forms are valid. Contains the submitted forms, excluding the
+    /// extra forms that were left empty or marked for deletion.
+    Ok(Vec<FormSetForm<F>>),
+    /// Some of the forms, or the form set as a whole, are invalid.
+    ValidationError(FormSetContext<F::Context>),
+}
+
+impl<F: Form> FormSetResult<F> {
+    /// Unwraps the form set result, panicking if the validation failed.
+    ///
+    /// This should only be used in tests or when the validation is guaranteed
+    /// to pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the validation failed.
+    #[must_use]
+    #[track_caller]
+    pub fn unwrap(self) -> Vec<FormSetForm<F>> {
+        match self {
+            Self::Ok(forms) => forms,
+            Self::ValidationError(context) => {
+                panic!("Form set validation failed: {context:?}")
+            }
+        }
+    }
+}
+
+/// A single valid form submitted in a [`FormSet`].
+#[derive(Debug, Clone)]
+pub struct FormSetForm<F> {
+    form: F,
+    index: usize,
+    is_initial: bool,
+    should_delete: bool,
+}
+
+impl<F> FormSetForm<F> {
+    /// Returns the form.
+    #[must_use]
+    pub fn form(&self) -> &F {
+        &self.form
+    }
+
+    /// Returns the form, consuming `self`.
+    #[must_use]
+    pub fn into_form(self) -> F {
+        self.form
+    }
+
+    /// Returns the index of the form in the form set.
+    ///
+    /// For initial forms, this is the index of the value the form was
+    /// pre-populated with in [`FormSet::context_from_forms`].
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns whether the form is one of the initial forms, i.e. it edits an
+    /// existing object, rather than creating a new one.
+    #[must_use]
+    pub fn is_initial(&self) -> bool {
+        self.is_initial
+    }
+
+    /// Returns whether the form was marked for deletion.
+    #[must_use]
+    pub fn should_delete(&self) -> bool {
+        self.should_delete
+    }
+}
+
+/// The context of a [`FormSet`], used to render the forms in a template.
+///
+/// # Examples
+///
+/// ```
+/// use cot::Template;
+/// use cot::form::{Form, FormSet, FormSetContext};
+///
+/// #[derive(Form)]
+/// struct ItemForm {
+///     name: String,
+/// }
+///
+/// #[derive(Template)]
+/// #[template(
+///     source = r#"<form method="post">
+/// {{ formset.management_form()|safe }}
+/// {%- for form in formset.forms() %}
+///     {{ form }}
+/// {%- endfor %}
+/// </form>"#,
+///     ext = "html"
+/// )]
+/// struct ItemsTemplate {
+///     formset: FormSetContext<<ItemForm as Form>::Context>,
+/// }
+///
+/// let template = ItemsTemplate {
+///     formset: FormSet::<ItemForm>::new().context(),
+/// };
+/// let html = template.render().unwrap();
+/// assert!(html.contains(r#"name="form-TOTAL_FORMS""#));
+/// assert!(html.contains(r#"name="form-0-name""#));
+/// ```
+#[derive(Debug)]
+pub struct FormSetContext<C> {
+    prefix: String,
+    forms: Vec<C>,
+    initial_forms: usize,
+    min_forms: usize,
+    max_forms: usize,
+    can_delete: bool,
+    deleted: Vec<usize>,
+    errors: Vec<FormFieldValidationError>,
+}
+
+impl<C: FormContext> FormSetContext<C> {
+    /// Returns the prefix of the field names.
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the contexts of the forms.
+    #[must_use]
+    pub fn forms(&self) -> &[C] {
+        &self.forms
+    }
+
+    /// Returns the total number of forms.
+    #[must_use]
+    pub fn total_forms(&self) -> usize {
+        self.forms.len()
+    }
+
+    /// Returns the number of initial forms, i.e. the forms pre-populated with
+    /// existing objects.
+    #[must_use]
+    pub fn initial_forms(&self) -> usize {
+        self.initial_forms
+    }
+
+    /// Returns the errors of the form set as a whole, such as submitting too
+    /// few or too many forms.
+    ///
+    /// The errors of the individual forms are stored in their contexts.
+    #[must_use]
+    pub fn errors(&self) -> &[FormFieldValidationError] {
+        &self.errors
+    }
+
+    /// Adds an error of the form set as a whole.
+    pub fn add_error(&mut self, error: FormFieldValidationError) {
+        self.errors.push(error);
+    }
+
+    /// Returns whether the form set, or any of its forms, has errors.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty() || self.forms.iter().any(FormContext::has_errors)
+    }
+
+    /// Returns whether the form with the given index was marked for deletion.
+    #[must_use]
+    pub fn is_deleted(&self, index: usize) -> bool {
+        self.deleted.contains(&index)
+    }
+
+    /// Returns a new empty form context with the `__prefix__` placeholder in
+    /// place of the form index.
+    ///
+    /// This is useful for adding forms dynamically with JavaScript, by
+    /// replacing the placeholder with the next index and incrementing the
+    /// `TOTAL_FORMS` field of the management form.
+    #[must_use]
+    pub fn empty_form(&self) -> C {
+        C::with_prefix(&format!("{}-__prefix__", self.prefix))
+    }
+
+    /// Renders the hidden fields of the management form, which contain the
+    /// number of the forms. This has to be rendered inside the HTML form
+    /// along with the forms.
+    #[must_use]
+    pub fn management_form(&self) -> Html {
+        let fields = [
+            (TOTAL_FORMS, self.total_forms()),
+            (INITIAL_FORMS, self.initial_forms),
+            (MIN_NUM_FORMS, self.min_forms),
+            (MAX_NUM_FORMS, self.max_forms),
+        ];
+
+        let html: String = fields
+            .into_iter()
+            .map(|(name, value)| {
+                let id = format!("{}-{name}", self.prefix);
+                let mut tag = HtmlTag::input("hidden");
+                tag.attr("name", &id)
+                    .attr("id", &id)
+                    .attr("value", value.to_string());
+                tag.render().as_str().to_owned()
+            })
+            .collect();
+        Html::new(html)
+    }
+
+    /// Renders the checkbox marking the form with the given index for
+    /// deletion, or returns `None` if deleting is disabled.
+    #[must_use]
+    pub fn delete_field(&self, index: usize) -> Option<Html> {
+        if !self.can_delete {
+            return None;
+        }
+
+        let id = format!("{}-{index}-{DELETE}", self.prefix);
+        let mut tag = HtmlTag::input("checkbox");
+        tag.attr("name", &id).attr("id", &id);
+        if self.is_deleted(index) {
+            tag.bool_attr("checked");
+        }
+        Some(tag.render())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::form::FormField;
+    use crate::test::TestRequestBuilder;
+
+    #[derive(Debug, PartialEq, Form)]
+    struct ItemForm {
+        name: String,
+        quantity: u32,
+    }
+
+    fn formset() -> FormSet<ItemForm> {
+        FormSet::new().with_prefix("items").with_extra_forms(2)
+    }
+
+    async fn submit(formset: &FormSet<ItemForm>, data: &[(&str, &str)]) -> FormSetResult<ItemForm> {
+        let mut request = TestRequestBuilder::post("/").form_data(data).build();
+        formset.forms_from_request(&mut request).await.unwrap()
+    }
+
+    #[test]
+    fn context_empty() {
+        let context = formset().context();
+
+        assert_eq!(context.total_forms(), 2);
+        assert_eq!(context.initial_forms(), 0);
+        assert_eq!(context.forms()[1].name.id(), "items-1-name");
+        assert_eq!(context.forms()[1].prefix(), Some("items-1"));
+        assert!(!context.has_errors());
+        assert_eq!(context.delete_field(0), None);
+    }
+
+    #[cot::test]
+    async fn context_from_forms() {
+        let items = [ItemForm {
+            name: "Apple".to_owned(),
+            quantity: 3,
+        }];
+
+        let context = formset().with_max_forms(2).context_from_forms(&items).await;
+
+        assert_eq!(context.total_forms(), 2);
+        assert_eq!(context.initial_forms(), 1);
+        assert_eq!(context.forms()[0].name.value(), Some("Apple"));
+        assert_eq!(context.forms()[0].quantity.value(), Some("3"));
+        assert_eq!(context.forms()[1].name.value(), None);
+    }
+
+    #[test]
+    fn management_form() {
+        let context = formset().with_min_forms(1).with_max_forms(5).context();
+
+        assert_eq!(
+            context.management_form().as_str(),
+            "<input type=\"hidden\" name=\"items-TOTAL_FORMS\" id=\"items-TOTAL_FORMS\" value=\"2\"/>\
+            <input type=\"hidden\" name=\"items-INITIAL_FORMS\" id=\"items-INITIAL_FORMS\" value=\"0\"/>\
+            <input type=\"hidden\" name=\"items-MIN_NUM_FORMS\" id=\"items-MIN_NUM_FORMS\" value=\"1\"/>\
+            <input type=\"hidden\" name=\"items-MAX_NUM_FORMS\" id=\"items-MAX_NUM_FORMS\" value=\"5\"/>"
+        );
+    }
+
+    #[test]
+    fn delete_field() {
+        let context = formset().with_can_delete(true).context();
+
+        assert_eq!(
+            context.delete_field(1).unwrap().as_str(),
+            "<input type=\"checkbox\" name=\"items-1-DELETE\" id=\"items-1-DELETE\"/>"
+        );
+    }
+
+    #[test]
+    fn empty_form() {
+        let context = formset().context();
+
+        assert_eq!(context.empty_form().name.id(), "items-__prefix__-name");
+    }
+
+    #[cot::test]
+    async fn from_request() {
+        let forms = submit(
+            &formset(),
+            &[
+                ("items-TOTAL_FORMS", "3"),
+                ("items-INITIAL_FORMS", "1"),
+                ("items-0-name", "Apple"),
+                ("items-0-quantity", "3"),
+                ("items-1-name", "Banana"),
+                ("items-1-quantity", "5"),
+                ("items-2-name", ""),
+                ("items-2-quantity", ""),
+            ],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(forms.len(), 2);
+        assert_eq!(forms[0].index(), 0);
+        assert!(forms[0].is_initial());
+        assert_eq!(
+            forms[0].form(),
+            &ItemForm {
+                name: "Apple".to_owned(),
+                quantity: 3
+            }
+        );
+        assert_eq!(forms[1].index(), 1);
+        assert!(!forms[1].is_initial());
+        assert_eq!(forms[1].form().name, "Banana");
+    }
+
+    #[cot::test]
+    async fn from_request_invalid_form() {
+        let result = submit(
+            &formset(),
+            &[
+                ("items-TOTAL_FORMS", "2"),
+                ("items-INITIAL_FORMS", "0"),
+                ("items-0-name", "Apple"),
+                ("items-0-quantity", "3"),
+                ("items-1-name", "Banana"),
+                ("items-1-quantity", "many"),
+            ],
+        )
+        .await;
+
+        let FormSetResult::ValidationError(context) = result else {
+            panic!("expected a validation error");
+        };
+        assert!(context.has_errors());
+        assert!(context.errors().is_empty());
+        assert!(!context.forms()[0].has_errors());
+        assert_eq!(context.forms()[1].name.value(), Some("Banana"));
+        assert!(
+            !context.forms()[1]
+                .errors_for(FormErrorTarget::Field("quantity"))
+                .is_empty()
+        );
+        assert!(!context.forms()[1].field("quantity").errors().is_empty());
+    }
+
+    #[cot::test]
+    async fn from_request_delete() {
+        let forms = submit(
+            &formset().with_can_delete(true),
+            &[
+                ("items-TOTAL_FORMS", "2"),
+                ("items-INITIAL_FORMS", "1"),
+                ("items-0-name", "Apple"),
+                ("items-0-quantity", "3"),
+                ("items-0-DELETE", "on"),
+                ("items-1-name", "Banana"),
+                ("items-1-quantity", "invalid"),
+                ("items-1-DELETE", "on"),
+            ],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(forms.len(), 1);
+        assert!(forms[0].should_delete());
+        assert_eq!(forms[0].form().name, "Apple");
+    }
+
+    #[cot::test]
+    async fn from_request_min_max_forms() {
+        let data = [
+            ("items-TOTAL_FORMS", "2"),
+            ("items-INITIAL_FORMS", "0"),
+            ("items-0-name", "Apple"),
+            ("items-0-quantity", "3"),
+        ];
+
+        for formset in [formset().with_min_forms(2), formset().with_max_forms(0)] {
+            let result = submit(&formset, &data).await;
+
+            let FormSetResult::ValidationError(context) = result else {
+                panic!("expected a validation error");
+            };
+            assert_eq!(context.errors().len(), 1);
+        }
+    }
+
+    #[cot::test]
+    async fn from_request_missing_management_form() {
+        let result = submit(&formset(), &[("items-0-name", "Apple")]).await;
+
+        let FormSetResult::ValidationError(context) = result else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(
+            context.errors(),
+            [FormFieldValidationError::with_code(
+                "missing_management_form",
+                "The management form data is missing or has been tampered with."
+            )]
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! Exporting and anonymizing the personal data of the users.
+//!
+//! This module helps with handling the data subject requests, such as the
+//! ones defined by the GDPR. The models that store the personal data of the
+//! users implement [`Exportable`] and/or [`Anonymizable`], and are registered
+//! by their apps in [`App::personal_data_models`](crate::App::personal_data_models).
+//! Then:
+//! * [`export_user`] collects the data of all the registered models that
+//!   belongs to a user into a single JSON document,
+//! * [`anonymize_user`] scrubs the personal data of a user from all the
+//!   registered models.
+//!
+//! Both are also available as the `gdpr export-user <id>` and
+//! `gdpr anonymize-user <id>` CLI commands.
+//!
+//! The rows belonging to a user are found using the column referencing the
+//! user, typically a [`ForeignKey`](crate::db::ForeignKey) to the user model,
+//! given by [`PersonalData::USER_FIELD`]. Cot doesn't manage the files
+//! uploaded by the users, so if a model references any files, its
+//! [`Exportable::export`] and [`Anonymizable::anonymize`] implementations are
+//! responsible for including them in the export or removing them.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::db::{Auto, ForeignKey, Identifier, model};
+//! use cot::gdpr::{Anonymizable, Exportable, PersonalData, PersonalDataModel};
+//! use cot::auth::db::DatabaseUser;
+//! use cot::App;
+//!
+//! #[model]
+//! struct Address {
+//!     #[model(primary_key)]
+//!     id: Auto<i64>,
+//!     user: ForeignKey<DatabaseUser>,
+//!     street: String,
+//! }
+//!
+//! impl PersonalData for Address {
+//!     const USER_FIELD: Identifier = Identifier::new("user");
+//! }
+//!
+//! impl Exportable for Address {
+//!     async fn export(&self) -> cot::Result<serde_json::Value> {
+//!         Ok(serde_json::json!({ "street": self.street }))
+//!     }
+//! }
+//!
+//! impl Anonymizable for Address {
+//!     async fn anonymize(&mut self) -> cot::Result<()> {
+//!         self.street = String::new();
+//!         Ok(())
+//!     }
+//! }
+//!
+//! struct AddressBookApp;
+//! impl App for AddressBookApp {
+//!     fn name(&self) -> &str {
+//!         "address_book"
+//!     }
+//!
+//!     fn personal_data_models(&self) -> Vec<PersonalDataModel> {
+//!         vec![PersonalDataModel::new::<Address>()]
+//!     }
+//! }
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+
+use cot_core::error::impl_into_cot_error;
+use derive_more::with_trait::Debug;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::App;
+use crate::auth::UserId;
+use crate::db::query::{Expr, Query};
+use crate::db::{Database, Identifier, Model};
+
+const ERROR_PREFIX: &str = "personal data error:";
+
+/// Errors that can occur while exporting or anonymizing personal data.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum GdprError {
+    /// The project has no database configured.
+    #[error("{ERROR_PREFIX} the project has no database configured")]
+    DatabaseNotConfigured,
+    /// The export could not be written to a file.
+    #[error("{ERROR_PREFIX} failed to write the export: {0}")]
+    WriteExport(#[source] std::io::Error),
+}
+
+impl_into_cot_error!(GdprError);
+
+/// A model that stores data belonging to a user.
+///
+/// This is the common part of [`Exportable`] and [`Anonymizable`].
+pub trait PersonalData: Model + Send + Sync + 'static {
+    /// The column that references the user the row belongs to.
+    ///
+    /// This is typically a [`ForeignKey`](crate::db::ForeignKey) to the user
+    /// model, but any column storing the [`UserId`] works.
+    const USER_FIELD: Identifier;
+}
+
+/// A model whose data can be exported for the user it belongs to.
+pub trait Exportable: PersonalData {
+    /// Returns the data of this row that should be included in the export.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the data could not be exported,
+    /// which aborts the whole export.
+    fn export(&self) -> impl Future<Output = crate::Result<Value>> + Send;
+}
+
+/// A model whose personal data can be scrubbed.
+pub trait Anonymizable: PersonalData {
+    /// Removes the personal data from this row.
+    ///
+    /// The row is saved to the database afterwards. The row itself is kept,
+    /// so that the other data referencing it (e.g. orders, statistics) stays
+    /// consistent.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the data could not be anonymized,
+    /// which aborts the anonymization of the remaining rows.
+    fn anonymize(&mut self) -> impl Future<Output = crate::Result<()>> + Send;
+}
+
+type ExportFn = for<'a> fn(
+    &'a Database,
+    &'a UserId,
+) -> Pin<Box<dyn Future<Output = crate::Result<Vec<Value>>> + Send + 'a>>;
+type AnonymizeFn = for<'a> fn(
+    &'a Database,
+    &'a UserId,
+) -> Pin<Box<dyn Future<Output = crate::Result<usize>> + Send + 'a>>;
+
+/// A model registered for exporting and anonymizing the personal data.
+///
+/// It is returned by
+/// [`App::personal_data_models`](crate::App::personal_data_models).
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, Identifier, model};
+/// use cot::gdpr::{Exportable, PersonalData, PersonalDataModel};
+///
+/// #[model]
+/// struct LoginEvent {
+///     #[model(primary_key)]
+///     id: Auto<i64>,
+///     user_id: i64,
+///     ip_address: String,
+/// }
+///
+/// impl PersonalData for LoginEvent {
+///     const USER_FIELD: Identifier = Identifier::new("user_id");
+/// }
+///
+/// impl Exportable for LoginEvent {
+///     async fn export(&self) -> cot::Result<serde_json::Value> {
+///         Ok(serde_json::json!({ "ip_address": self.ip_address }))
+///     }
+/// }
+///
+/// let model = PersonalDataModel::exportable::<LoginEvent>();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PersonalDataModel {
+    name: String,
+    #[debug("..")]
+    export: Option<ExportFn>,
+    #[debug("..")]
+    anonymize: Option<AnonymizeFn>,
+}
+
+impl PersonalDataModel {
+    /// Registers a model that can be both exported and anonymized.
+    ///
+    /// # Examples
+    ///
+    /// See the [module documentation](self).
+    #[must_use]
+    pub fn new<T: Exportable + Anonymizable>() -> Self {
+        Self {
+            name: T::TABLE_NAME.as_str().to_owned(),
+            export: Some(export_rows::<T>),
+            anonymize: Some(anonymize_rows::<T>),
+        }
+    }
+
+    /// Registers a model that can only be exported.
+    ///
+    /// # Examples
+    ///
+    /// See [`PersonalDataModel`].
+    #[must_use]
+    pub fn exportable<T: Exportable>() -> Self {
+        Self {
+            name: T::TABLE_NAME.as_str().to_owned(),
+            export: Some(export_rows::<T>),
+            anonymize: None,
+        }
+    }
+
+    /// Registers a model that can only be anonymized.
+    ///
+    /// This is useful for the data that shouldn't be included in the export,
+    /// such as the internal notes about the user.
+    #[must_use]
+    pub fn anonymizable<T: Anonymizable>() -> Self {
+        Self {
+            name: T::TABLE_NAME.as_str().to_owned(),
+            export: None,
+            anonymize: Some(anonymize_rows::<T>),
+        }
+    }
+
+    /// Returns the name of the model, which is the name of its table.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn user_query<T: PersonalData>(user_id: &UserId) -> Query<T> {
+    let user_id = match user_id {
+        UserId::Int(id) => Expr::value(*id),
+        UserId::String(id) => Expr::value(id.clone()),
+    };
+
+    let mut query = Query::new();
+    query.filter(Expr::eq(Expr::field(T::USER_FIELD), user_id));
+    query
+}
+
+fn export_rows<'a, T: Exportable>(
+    database: &'a Database,
+    user_id: &'a UserId,
+) -> Pin<Box<dyn Future<Output = crate::Result<Vec<Value>>> + Send + 'a>> {
+    Box::pin(async move {
+        let rows = user_query::<T>(user_id).all(database).await?;
+
+        let mut values = Vec::with_capacity(rows.len());
+        for row in &rows {
+            values.push(row.export().await?);
+        }
+        Ok(values)
+    })
+}
+
+fn anonymize_rows<'a, T: Anonymizable>(
+    database: &'a Database,
+    user_id: &'a UserId,
+) -> Pin<Box<dyn Future<Output = crate::Result<usize>> + Send + 'a>> {
+    Box::pin(async move {
+        let rows = user_query::<T>(user_id).all(database).await?;
+
+        let count = rows.len();
+        for mut row in rows {
+            row.anonymize().await?;
+            database.update(&mut row).await?;
+        }
+        Ok(count)
+    })
+}
+
+/// Collects the personal data of the given user from the models registered
+/// by the given apps.
+///
+/// The result is a JSON object with the ID of the user and the exported rows
+/// of each model, keyed by the model name.
+///
+/// # Errors
+///
+/// Returns an error if the data could not be retrieved from the database or
+/// any of the rows could not be exported.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::UserId;
+/// use cot::db::Database;
+/// use cot::gdpr::export_user;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let database = Database::new("sqlite::memory:").await?;
+///
+/// let export = export_user(&database, &[], &UserId::Int(1)).await?;
+/// assert_eq!(export["user_id"], 1);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn export_user(
+    database: &Database,
+    apps: &[Box<dyn App>],
+    user_id: &UserId,
+) -> crate::Result<Value> {
+    let mut models = Map::new();
+    for model in apps.iter().flat_map(|app| app.personal_data_models()) {
+        if let Some(export) = model.export {
+            let rows = export(database, user_id).await?;
+            models.insert(model.name, Value::Array(rows));
+        }
+    }
+
+    let user_id = match user_id {
+        UserId::Int(id) => Value::from(*id),
+        UserId::String(id) => Value::from(id.clone()),
+    };
+    Ok(serde_json::json!({
+        "user_id": user_id,
+        "models": models,
+    }))
+}
+
+/// Scrubs the personal data of the given user from the models registered by
+/// the given apps, and returns the number of anonymized rows.
+///
+/// # Errors
+///
+/// Returns an error if the data could not be retrieved from or saved to the
+/// database, or any of the rows could not be anonymized. The rows that have
+/// been anonymized before the error occurred stay anonymized.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::UserId;
+/// use cot::db::Database;
+/// use cot::gdpr::anonymize_user;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let database = Database::new("sqlite::memory:").await?;
+///
+/// let count = anonymize_user(&database, &[], &UserId::Int(1)).await?;
+/// assert_eq!(count, 0);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn anonymize_user(
+    database: &Database,
+    apps: &[Box<dyn App>],
+    user_id: &UserId,
+) -> crate::Result<usize> {
+    let mut count = 0;
+    for model in apps.iter().flat_map(|app| app.personal_data_models()) {
+        if let Some(anonymize) = model.anonymize {
+            count += anonymize(database, user_id).await?;
+        }
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::{Field, Operation};
+    use crate::db::{Auto, DatabaseField, model};
+    use crate::test::TestDatabase;
+
+    #[model]
+    struct Profile {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        user_id: i64,
+        email: String,
+    }
+
+    impl PersonalData for Profile {
+        const USER_FIELD: Identifier = Identifier::new("user_id");
+    }
+
+    impl Exportable for Profile {
+        async fn export(&self) -> crate::Result<Value> {
+            Ok(serde_json::json!({ "email": self.email }))
+        }
+    }
+
+    impl Anonymizable for Profile {
+        async fn anonymize(&mut self) -> crate::Result<()> {
+            self.email = String::new();
+            Ok(())
+        }
+    }
+
+    struct ProfileApp;
+
+    impl App for ProfileApp {
+        fn name(&self) -> &'static str {
+            "profile"
+        }
+
+        fn personal_data_models(&self) -> Vec<PersonalDataModel> {
+            vec![PersonalDataModel::new::<Profile>()]
+        }
+    }
+
+    const CREATE_PROFILE: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__profile"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("user_id"), <i64 as DatabaseField>::TYPE),
+            Field::new(Identifier::new("email"), <String as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    async fn insert_profiles(database: &Database) {
+        CREATE_PROFILE.forwards(database).await.unwrap();
+        for (user_id, email) in [(1, "alice@example.com"), (2, "bob@example.com")] {
+            let mut profile = Profile {
+                id: Auto::auto(),
+                user_id,
+                email: email.to_owned(),
+            };
+            database.insert(&mut profile).await.unwrap();
+        }
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn export_user_data() {
+        let test_db = TestDatabase::new_sqlite().await.unwrap();
+        insert_profiles(&test_db.database()).await;
+        let apps: Vec<Box<dyn App>> = vec![Box::new(ProfileApp)];
+
+        let export = export_user(&test_db.database(), &apps, &UserId::Int(1))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            export,
+            serde_json::json!({
+                "user_id": 1,
+                "models": { "cot__profile": [{ "email": "alice@example.com" }] },
+            })
+        );
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn anonymize_user_data() {
+        let test_db = TestDatabase::new_sqlite().await.unwrap();
+        let database = test_db.database();
+        insert_profiles(&database).await;
+        let apps: Vec<Box<dyn App>> = vec![Box::new(ProfileApp)];
+
+        let count = anonymize_user(&database, &apps, &UserId::Int(1))
+            .await
+            .unwrap();
+
+        assert_eq!(count, 1);
+        let mut emails: Vec<_> = Profile::objects()
+            .all(&database)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|profile| profile.email)
+            .collect();
+        emails.sort();
+        assert_eq!(emails, vec![String::new(), "bob@example.com".to_owned()]);
+
+        test_db.cleanup().await.unwrap();
+    }
+}
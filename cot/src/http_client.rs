@@ -0,0 +1,629 @@
+//! Outbound HTTP client.
+//!
+//! This module provides [`HttpClient`], an HTTP client for calling other
+//! services from the request handlers. The client is created from the
+//! [`HttpClientConfig`] when the project is bootstrapped and is available as
+//! [`ProjectContext::http_client`](crate::ProjectContext::http_client) and as
+//! an extractor.
+//!
+//! When obtained with the extractor, the client:
+//! * copies the tracing headers (such as `traceparent`) of the request being
+//!   handled to the outbound requests (see
+//!   [`HttpClientConfig::propagate_headers`]),
+//! * respects the request [`Deadline`], failing with
+//!   [`HttpClientError::DeadlineExceeded`] once it expires.
+//!
+//! The client is based on [`reqwest`] with no TLS backend enabled, so only
+//! plain HTTP URLs are supported out of the box. To call HTTPS services,
+//! enable one of the TLS features of `reqwest` (e.g. `rustls`) in your
+//! project.
+//!
+//! In tests, the client can be replaced with a
+//! [`MockHttpClient`](crate::test::MockHttpClient).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use cot::html::Html;
+//! use cot::http_client::HttpClient;
+//!
+//! async fn index(http_client: HttpClient) -> cot::Result<Html> {
+//!     let response = http_client
+//!         .get("http://inventory.internal/items/")
+//!         .send()
+//!         .await?;
+//!     let items = response.text().await.map_err(cot::Error::internal)?;
+//!
+//!     Ok(Html::new(items))
+//! }
+//! ```
+
+use std::sync::Arc;
+use std::time::Instant;
+
+#[cfg(feature = "test")]
+use bytes::Bytes;
+use derive_more::with_trait::Debug;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use reqwest::IntoUrl;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{Instrument, debug, debug_span};
+
+use crate::config::HttpClientConfig;
+use crate::deadline::Deadline;
+use crate::request::RequestHead;
+
+const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+const TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+
+/// The handler used instead of sending the requests by a mocked client.
+#[cfg(feature = "test")]
+pub(crate) type MockHandler = dyn Fn(http::Request<Bytes>) -> http::Response<Bytes> + Send + Sync;
+
+/// An error that can occur when using the [`HttpClient`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum HttpClientError {
+    /// The client could not be created from the configuration.
+    #[error("failed to create the HTTP client: {0}")]
+    Config(String),
+    /// The request could not be sent or the response could not be received.
+    #[error("HTTP request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    /// The request deadline expired before the response was received.
+    #[error("the request deadline expired before the HTTP response was received")]
+    DeadlineExceeded,
+}
+
+impl From<HttpClientError> for crate::Error {
+    fn from(error: HttpClientError) -> Self {
+        let status_code = match error {
+            HttpClientError::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        crate::Error::with_status(error, status_code)
+    }
+}
+
+/// A convenience alias for results returned by the HTTP client.
+pub type HttpClientResult<T> = Result<T, HttpClientError>;
+
+/// An HTTP client for calling other services.
+///
+/// Cloning the client is cheap, as the connection pool is shared between the
+/// clones.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cot::http_client::HttpClient;
+///
+/// # async fn run() -> cot::Result<()> {
+/// let http_client = HttpClient::new();
+/// let response = http_client.get("http://example.com/").send().await?;
+/// println!("{}", response.status());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    propagate_headers: Arc<[HeaderName]>,
+    headers: HeaderMap,
+    #[cfg(feature = "test")]
+    #[debug("{}", if mock.is_some() { "Some(..)" } else { "None" })]
+    mock: Option<Arc<MockHandler>>,
+}
+
+impl HttpClient {
+    /// Creates a new HTTP client with the default configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the underlying client could not be initialized. See
+    /// [`reqwest::Client::new`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http_client::HttpClient;
+    ///
+    /// let http_client = HttpClient::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_config(&HttpClientConfig::default())
+            .expect("default HTTP client configuration should be valid")
+    }
+
+    /// Creates a new HTTP client from the given configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HttpClientError::Config`] if the proxy URL, the user agent,
+    /// or any of the propagated header names is invalid, or if the
+    /// underlying client could not be initialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::HttpClientConfig;
+    /// use cot::http_client::HttpClient;
+    ///
+    /// let config = HttpClientConfig::builder()
+    ///     .timeout(Duration::from_secs(10))
+    ///     .build();
+    /// let http_client = HttpClient::from_config(&config)?;
+    /// # Ok::<(), cot::http_client::HttpClientError>(())
+    /// ```
+    pub fn from_config(config: &HttpClientConfig) -> HttpClientResult<Self> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = &config.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|error| HttpClientError::Config(format!("invalid proxy: {error}")))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(user_agent) = &config.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        let client = builder
+            .build()
+            .map_err(|error| HttpClientError::Config(error.to_string()))?;
+
+        let propagate_headers = config
+            .propagate_headers
+            .iter()
+            .map(|name| {
+                HeaderName::try_from(name).map_err(|_| {
+                    HttpClientError::Config(format!("invalid propagated header name: {name}"))
+                })
+            })
+            .collect::<HttpClientResult<_>>()?;
+
+        Ok(Self {
+            client,
+            propagate_headers,
+            headers: HeaderMap::new(),
+            #[cfg(feature = "test")]
+            mock: None,
+        })
+    }
+
+    /// Returns a client that sends the propagated headers of the given
+    /// request with every outbound request.
+    ///
+    /// The headers to propagate are configured with
+    /// [`HttpClientConfig::propagate_headers`]. This is done automatically
+    /// when the client is obtained with the extractor.
+    ///
+    /// With the `opentelemetry` feature enabled, the `traceparent` and
+    /// `tracestate` headers are not copied from the request; instead, they are
+    /// set to the context of the current span, so that the downstream services
+    /// see the outbound request as a child of it. The headers are only copied
+    /// if the current span is not recorded by OpenTelemetry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::http_client::HttpClient;
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     let (head, _body) = request.into_parts();
+    ///     let http_client = head.context().http_client().with_headers_from(&head);
+    ///     // ...
+    /// #    unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_headers_from(&self, head: &RequestHead) -> Self {
+        let mut client = self.clone();
+        #[cfg(feature = "opentelemetry")]
+        let injected = self.propagate_headers.contains(&TRACEPARENT)
+            && crate::telemetry::inject_current_context(&mut client.headers);
+        #[cfg(not(feature = "opentelemetry"))]
+        let injected = false;
+
+        for name in self.propagate_headers.iter() {
+            if injected && (*name == TRACEPARENT || *name == TRACESTATE) {
+                continue;
+            }
+            for value in head.headers.get_all(name) {
+                client.headers.append(name.clone(), value.clone());
+            }
+        }
+        client
+    }
+
+    #[cfg(feature = "test")]
+    pub(crate) fn with_mock(mut self, mock: Arc<MockHandler>) -> Self {
+        self.mock = Some(mock);
+        self
+    }
+
+    /// Starts building a request with the given method and URL.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::http_client::HttpClient;
+    /// use http::Method;
+    ///
+    /// # async fn run() -> cot::Result<()> {
+    /// let response = HttpClient::new()
+    ///     .request(Method::OPTIONS, "http://example.com/")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> HttpRequestBuilder {
+        HttpRequestBuilder {
+            client: self.clone(),
+            inner: self.client.request(method, url),
+        }
+    }
+
+    /// Starts building a `GET` request to the given URL.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::http_client::HttpClient;
+    ///
+    /// # async fn run() -> cot::Result<()> {
+    /// let response = HttpClient::new().get("http://example.com/").send().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get<U: IntoUrl>(&self, url: U) -> HttpRequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    /// Starts building a `POST` request to the given URL.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::http_client::HttpClient;
+    ///
+    /// # async fn run() -> cot::Result<()> {
+    /// let response = HttpClient::new()
+    ///     .post("http://example.com/items/")
+    ///     .json(&["apple", "banana"])
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn post<U: IntoUrl>(&self, url: U) -> HttpRequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Starts building a `PUT` request to the given URL.
+    pub fn put<U: IntoUrl>(&self, url: U) -> HttpRequestBuilder {
+        self.request(Method::PUT, url)
+    }
+
+    /// Starts building a `PATCH` request to the given URL.
+    pub fn patch<U: IntoUrl>(&self, url: U) -> HttpRequestBuilder {
+        self.request(Method::PATCH, url)
+    }
+
+    /// Starts building a `DELETE` request to the given URL.
+    pub fn delete<U: IntoUrl>(&self, url: U) -> HttpRequestBuilder {
+        self.request(Method::DELETE, url)
+    }
+
+    async fn execute(&self, request: reqwest::Request) -> HttpClientResult<reqwest::Response> {
+        #[cfg(feature = "test")]
+        if let Some(mock) = &self.mock {
+            return Ok(reqwest::Response::from(mock(into_http_request(&request))));
+        }
+
+        Ok(self.client.execute(request).await?)
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "test")]
+fn into_http_request(request: &reqwest::Request) -> http::Request<Bytes> {
+    let body = request
+        .body()
+        .and_then(reqwest::Body::as_bytes)
+        .map(Bytes::copy_from_slice)
+        .unwrap_or_default();
+
+    let mut http_request = http::Request::new(body);
+    *http_request.method_mut() = request.method().clone();
+    *http_request.uri_mut() = request
+        .url()
+        .as_str()
+        .parse()
+        .expect("reqwest URL should be a valid URI");
+    *http_request.headers_mut() = request.headers().clone();
+    http_request
+}
+
+/// A builder for an outbound HTTP request.
+///
+/// This is created with [`HttpClient::request`] or one of the shorthand
+/// methods, like [`HttpClient::get`].
+#[derive(Debug)]
+#[must_use = "the request is not sent until `send` is called"]
+pub struct HttpRequestBuilder {
+    client: HttpClient,
+    inner: reqwest::RequestBuilder,
+}
+
+impl HttpRequestBuilder {
+    /// Adds a header to the request.
+    ///
+    /// Setting any of the propagated headers overrides the value copied from
+    /// the request being handled.
+    pub fn header<K, V>(self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        self.map(|inner| inner.header(key, value))
+    }
+
+    /// Adds the headers to the request.
+    pub fn headers(self, headers: HeaderMap) -> Self {
+        self.map(|inner| inner.headers(headers))
+    }
+
+    /// Adds a bearer token `Authorization` header to the request.
+    pub fn bearer_auth<T: std::fmt::Display>(self, token: T) -> Self {
+        self.map(|inner| inner.bearer_auth(token))
+    }
+
+    /// Appends the given parameters to the query string of the URL.
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        self.map(|inner| inner.query(query))
+    }
+
+    /// Sets the request body.
+    pub fn body<T: Into<reqwest::Body>>(self, body: T) -> Self {
+        self.map(|inner| inner.body(body))
+    }
+
+    /// Sets the request body to the URL-encoded form data.
+    pub fn form<T: Serialize + ?Sized>(self, form: &T) -> Self {
+        self.map(|inner| inner.form(form))
+    }
+
+    /// Sets the request body to the JSON representation of the given value.
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        self.map(|inner| inner.json(json))
+    }
+
+    /// Sets the timeout of this request, overriding
+    /// [`HttpClientConfig::timeout`].
+    ///
+    /// The request deadline, if any, still applies.
+    pub fn timeout(self, timeout: std::time::Duration) -> Self {
+        self.map(|inner| inner.timeout(timeout))
+    }
+
+    fn map(self, f: impl FnOnce(reqwest::RequestBuilder) -> reqwest::RequestBuilder) -> Self {
+        Self {
+            client: self.client,
+            inner: f(self.inner),
+        }
+    }
+
+    /// Sends the request and waits for the response headers.
+    ///
+    /// Note that a response with an error status code (such as
+    /// `404 Not Found`) is not considered an error; use
+    /// [`reqwest::Response::error_for_status`] to turn it into one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HttpClientError::Request`] if the request is invalid, could
+    /// not be sent, or timed out.
+    ///
+    /// Returns [`HttpClientError::DeadlineExceeded`] if the request
+    /// [`Deadline`] expires before the response is received.
+    pub async fn send(self) -> HttpClientResult<reqwest::Response> {
+        let mut request = self.inner.build()?;
+        for name in self.client.headers.keys() {
+            if !request.headers().contains_key(name) {
+                for value in self.client.headers.get_all(name) {
+                    request.headers_mut().append(name.clone(), value.clone());
+                }
+            }
+        }
+
+        let span = debug_span!(
+            "http_client_request",
+            method = %request.method(),
+            url = %request.url(),
+        );
+        async move {
+            let start = Instant::now();
+            let response = match Deadline::current().run(self.client.execute(request)).await {
+                Ok(response) => response,
+                Err(_) => Err(HttpClientError::DeadlineExceeded),
+            };
+
+            match &response {
+                Ok(response) => debug!(
+                    status = %response.status(),
+                    elapsed = ?start.elapsed(),
+                    "HTTP request finished"
+                ),
+                Err(error) => debug!(%error, elapsed = ?start.elapsed(), "HTTP request failed"),
+            }
+            response
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+    use super::*;
+    use crate::test::{MockHttpClient, TestRequestBuilder};
+
+    fn ok_client() -> MockHttpClient {
+        MockHttpClient::new(|_request| http::Response::new(Bytes::from("ok")))
+    }
+
+    #[test]
+    fn from_config_invalid_proxy() {
+        let config = HttpClientConfig::builder().proxy("not a url").build();
+
+        let error = HttpClient::from_config(&config).unwrap_err();
+
+        assert!(matches!(error, HttpClientError::Config(_)));
+    }
+
+    #[test]
+    fn from_config_invalid_header_name() {
+        let config = HttpClientConfig::builder()
+            .propagate_headers(vec!["invalid header".to_owned()])
+            .build();
+
+        let error = HttpClient::from_config(&config).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "failed to create the HTTP client: invalid propagated header name: invalid header"
+        );
+    }
+
+    #[cot::test]
+    async fn send_mocked() {
+        let mock = ok_client();
+
+        let response = mock
+            .client()
+            .post("http://example.com/items/")
+            .query(&[("page", "2")])
+            .json(&["apple"])
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "ok");
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method(), Method::POST);
+        assert_eq!(requests[0].uri(), "http://example.com/items/?page=2");
+        assert_eq!(requests[0].body(), r#"["apple"]"#);
+    }
+
+    #[cot::test]
+    async fn propagates_headers() {
+        let mock = ok_client();
+        let mut request = TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert("traceparent", HeaderValue::from_static("00-trace-01"));
+        request
+            .headers_mut()
+            .insert("x-request-id", HeaderValue::from_static("incoming"));
+        request
+            .headers_mut()
+            .insert("authorization", HeaderValue::from_static("secret"));
+        let (head, _body) = request.into_parts();
+
+        mock.client()
+            .with_headers_from(&head)
+            .get("http://example.com/")
+            .header("x-request-id", "overridden")
+            .send()
+            .await
+            .unwrap();
+
+        let headers = mock.requests()[0].headers().clone();
+        assert_eq!(headers["traceparent"], "00-trace-01");
+        assert_eq!(headers["x-request-id"], "overridden");
+        assert!(!headers.contains_key("authorization"));
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[cot::test]
+    async fn propagates_current_trace_context() {
+        use opentelemetry::trace::TracerProvider as _;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let mock = ok_client();
+        let incoming = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut request = TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert("traceparent", HeaderValue::from_static(incoming));
+        request
+            .headers_mut()
+            .insert("tracestate", HeaderValue::from_static("vendor=value"));
+        let (head, _body) = request.into_parts();
+        let tracer = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+            .build()
+            .tracer("test");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let span = tracing::info_span!("request", otel.kind = tracing::field::Empty);
+        crate::telemetry::start_request_span(&span, &head.headers);
+
+        async {
+            mock.client()
+                .with_headers_from(&head)
+                .get("http://example.com/")
+                .send()
+                .await
+                .unwrap();
+        }
+        .instrument(span)
+        .await;
+
+        let headers = mock.requests()[0].headers().clone();
+        let traceparent = headers["traceparent"].to_str().unwrap();
+        assert!(traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+        assert_ne!(traceparent, incoming);
+        assert_eq!(headers["tracestate"], "vendor=value");
+        assert_eq!(headers.get_all("traceparent").iter().count(), 1);
+    }
+
+    #[cot::test]
+    async fn respects_deadline() {
+        let mock = ok_client();
+
+        let result = Deadline::at(tokio::time::Instant::now())
+            .scope(mock.client().get("http://example.com/").send())
+            .await;
+
+        assert!(matches!(result, Err(HttpClientError::DeadlineExceeded)));
+        assert!(mock.requests().is_empty());
+    }
+
+    #[test]
+    fn deadline_exceeded_into_cot_error() {
+        let error = crate::Error::from(HttpClientError::DeadlineExceeded);
+
+        assert_eq!(error.status_code(), StatusCode::GATEWAY_TIMEOUT);
+    }
+}
@@ -66,21 +66,48 @@ pub mod auth;
 pub mod cli;
 pub mod common_types;
 pub mod config;
+pub mod deadline;
 #[cfg(feature = "email")]
 pub mod email;
 mod error_page;
+#[cfg(feature = "gdpr")]
+pub mod gdpr;
+#[cfg(feature = "http-client")]
+pub mod http_client;
+pub mod messages;
 pub mod middleware;
+pub mod nav;
+pub mod notify;
 #[cfg(feature = "openapi")]
 pub mod openapi;
+#[cfg(feature = "db")]
+pub mod pagination;
 pub mod project;
 pub mod request;
+pub mod response;
 pub mod router;
+pub mod serialize;
 mod serializers;
 pub mod session;
+pub mod shutdown;
+#[cfg(feature = "db")]
+pub mod site_settings;
 pub mod static_files;
+#[cfg(feature = "storage")]
+pub mod storage;
+#[cfg(feature = "tasks")]
+pub mod tasks;
+#[cfg(feature = "opentelemetry")]
+pub mod telemetry;
 #[cfg(feature = "test")]
 pub mod test;
 pub(crate) mod utils;
+pub mod validation;
+#[cfg(feature = "db")]
+pub mod views;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+pub mod workflow;
 
 #[cfg(feature = "openapi")]
 pub use aide;
@@ -117,7 +144,7 @@ pub use cot_core::handler::RequestHandler;
 #[doc(inline)]
 pub use cot_core::json;
 #[doc(inline)]
-pub use cot_core::{Body, Method, Result, StatusCode, error::Error, html, response};
+pub use cot_core::{Body, Method, Result, StatusCode, error::Error, html};
 /// An attribute macro that defines an end-to-end test function for a
 /// Cot-powered app.
 ///
@@ -181,8 +208,12 @@ pub use cot_macros::e2e_test;
 /// ```
 pub use cot_macros::main;
 pub use cot_macros::test;
+#[cfg(feature = "xlsx")]
+pub use rust_xlsxwriter;
 #[cfg(feature = "openapi")]
 pub use schemars;
+#[cfg(feature = "tls")]
+pub use tokio_rustls::rustls;
 pub use {bytes, http};
 
 pub use crate::__private::askama::{Template, filter_fn};
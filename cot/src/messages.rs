@@ -0,0 +1,504 @@
+//! One-time notification messages ("flash messages").
+//!
+//! Messages are short notifications, such as "The post has been saved", that
+//! are stored in the [session](crate::session) when an action is performed and
+//! displayed to the user on the next rendered page, typically after a
+//! redirect. Each message is displayed only once: reading the pending
+//! messages removes them from the session.
+//!
+//! Messages are added using the [`Messages`] handle, available through
+//! [`RequestExt::messages`](crate::request::RequestExt::messages) or as an
+//! extractor, and read for rendering using the [`FlashMessages`] extractor.
+//! Both require the [`SessionMiddleware`](crate::middleware::SessionMiddleware)
+//! to be enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::html::Html;
+//! use cot::messages::FlashMessages;
+//! use cot::request::{Request, RequestExt};
+//! use cot::response::Response;
+//! use cot::{Template, reverse_redirect};
+//!
+//! async fn save(request: Request) -> cot::Result<Response> {
+//!     // ... save the object
+//!     request.messages().success("Saved!").await?;
+//!     Ok(reverse_redirect!(request, "index")?)
+//! }
+//!
+//! #[derive(Debug, Template)]
+//! #[template(
+//!     source = r#"
+//! {%- for message in messages -%}
+//!     <div class="message {{ message.level() }}">{{ message }}</div>
+//! {%- endfor -%}"#,
+//!     ext = "html"
+//! )]
+//! struct IndexTemplate {
+//!     messages: FlashMessages,
+//! }
+//!
+//! async fn index(messages: FlashMessages) -> cot::Result<Html> {
+//!     Ok(Html::new(IndexTemplate { messages }.render()?))
+//! }
+//! ```
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::request::RequestHead;
+use crate::request::extractors::FromRequestHead;
+use crate::session::Session;
+
+const MESSAGES_SESSION_KEY: &str = "__cot_messages";
+
+/// The level of a [`Message`], describing its importance.
+///
+/// The level is typically used to style the message when it's displayed. Its
+/// [`Display`] implementation returns a lowercase name, such as `success`,
+/// which is suitable to be used as a CSS class.
+///
+/// # Examples
+///
+/// ```
+/// use cot::messages::Level;
+///
+/// assert_eq!(Level::Success.to_string(), "success");
+/// assert!(Level::Error > Level::Info);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Level {
+    /// Development-related information.
+    Debug,
+    /// Informational message for the user.
+    Info,
+    /// An action was performed successfully.
+    Success,
+    /// Something the user should be aware of, but not an error.
+    Warning,
+    /// An action was not performed successfully.
+    Error,
+}
+
+impl Level {
+    /// Returns the lowercase name of the level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::Level;
+    ///
+    /// assert_eq!(Level::Warning.as_str(), "warning");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Success => "success",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single message stored in the session.
+///
+/// The [`Display`] implementation returns the text of the message.
+///
+/// # Examples
+///
+/// ```
+/// use cot::messages::{Level, Message};
+///
+/// let message = Message::new(Level::Success, "Saved!");
+/// assert_eq!(message.level(), Level::Success);
+/// assert_eq!(message.to_string(), "Saved!");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Message {
+    level: Level,
+    text: String,
+}
+
+impl Message {
+    /// Creates a new message with the given level and text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::{Level, Message};
+    ///
+    /// let message = Message::new(Level::Info, "Welcome back!");
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(level: Level, text: T) -> Self {
+        Self {
+            level,
+            text: text.into(),
+        }
+    }
+
+    /// Returns the level of the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::{Level, Message};
+    ///
+    /// let message = Message::new(Level::Warning, "Your password expires soon");
+    /// assert_eq!(message.level(), Level::Warning);
+    /// ```
+    #[must_use]
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the text of the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::{Level, Message};
+    ///
+    /// let message = Message::new(Level::Warning, "Your password expires soon");
+    /// assert_eq!(message.text(), "Your password expires soon");
+    /// ```
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// A handle used to add messages to be displayed to the user.
+///
+/// The messages are stored in the session until they are read, typically
+/// using the [`FlashMessages`] extractor when the next page is rendered.
+///
+/// This is usually obtained using
+/// [`RequestExt::messages`](crate::request::RequestExt::messages), but it can
+/// also be used as an extractor.
+///
+/// # Examples
+///
+/// ```
+/// use cot::messages::Messages;
+/// use cot::response::Response;
+///
+/// async fn handler(messages: Messages) -> cot::Result<Response> {
+///     messages.info("You have 3 new notifications").await?;
+///     # unimplemented!()
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Messages {
+    session: Session,
+}
+
+impl Messages {
+    /// Returns the messages handle for the given session.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::Messages;
+    /// use cot::session::Session;
+    ///
+    /// async fn handler(session: Session) -> cot::Result<()> {
+    ///     Messages::new(session).success("Saved!").await
+    /// }
+    /// ```
+    #[must_use]
+    pub fn new(session: Session) -> Self {
+        Self { session }
+    }
+
+    /// Adds a message with the given level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be stored in the session.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::{Level, Messages};
+    ///
+    /// async fn handler(messages: Messages) -> cot::Result<()> {
+    ///     messages.add(Level::Warning, "The file was truncated").await
+    /// }
+    /// ```
+    pub async fn add<T: Into<String>>(&self, level: Level, text: T) -> crate::Result<()> {
+        let mut messages = self.pending().await?;
+        messages.push(Message::new(level, text));
+        self.session.insert(MESSAGES_SESSION_KEY, messages).await?;
+        Ok(())
+    }
+
+    /// Adds a message with the [`Level::Debug`] level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be stored in the session.
+    pub async fn debug<T: Into<String>>(&self, text: T) -> crate::Result<()> {
+        self.add(Level::Debug, text).await
+    }
+
+    /// Adds a message with the [`Level::Info`] level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be stored in the session.
+    pub async fn info<T: Into<String>>(&self, text: T) -> crate::Result<()> {
+        self.add(Level::Info, text).await
+    }
+
+    /// Adds a message with the [`Level::Success`] level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be stored in the session.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    ///
+    /// async fn handler(request: Request) -> cot::Result<()> {
+    ///     request.messages().success("Saved!").await
+    /// }
+    /// ```
+    pub async fn success<T: Into<String>>(&self, text: T) -> crate::Result<()> {
+        self.add(Level::Success, text).await
+    }
+
+    /// Adds a message with the [`Level::Warning`] level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be stored in the session.
+    pub async fn warning<T: Into<String>>(&self, text: T) -> crate::Result<()> {
+        self.add(Level::Warning, text).await
+    }
+
+    /// Adds a message with the [`Level::Error`] level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be stored in the session.
+    pub async fn error<T: Into<String>>(&self, text: T) -> crate::Result<()> {
+        self.add(Level::Error, text).await
+    }
+
+    /// Removes all the pending messages from the session and returns them,
+    /// in the order they were added.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be read from the session.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::Messages;
+    ///
+    /// async fn handler(messages: Messages) -> cot::Result<()> {
+    ///     for message in messages.take().await? {
+    ///         println!("{}: {message}", message.level());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn take(&self) -> crate::Result<Vec<Message>> {
+        Ok(self
+            .session
+            .remove::<Vec<Message>>(MESSAGES_SESSION_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn pending(&self) -> crate::Result<Vec<Message>> {
+        Ok(self
+            .session
+            .get::<Vec<Message>>(MESSAGES_SESSION_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+}
+
+impl FromRequestHead for Messages {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        Ok(Self::new(
+            Session::from_extensions(&head.extensions).clone(),
+        ))
+    }
+}
+
+/// An extractor that removes the pending messages from the session, so that
+/// they can be rendered.
+///
+/// Since the messages are removed when the extractor is run, it should only be
+/// used by the handlers that actually display them, such as the ones
+/// rendering HTML pages. It can be iterated over directly in the templates.
+///
+/// # Examples
+///
+/// ```
+/// use cot::html::Html;
+/// use cot::messages::FlashMessages;
+///
+/// async fn index(messages: FlashMessages) -> cot::Result<Html> {
+///     let mut html = String::new();
+///     for message in &messages {
+///         html.push_str(&format!("<p class=\"{}\">{message}</p>", message.level()));
+///     }
+///     Ok(Html::new(html))
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlashMessages(Vec<Message>);
+
+impl FlashMessages {
+    /// Returns whether there are no messages to display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::FlashMessages;
+    ///
+    /// assert!(FlashMessages::default().is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::FlashMessages;
+    ///
+    /// assert_eq!(FlashMessages::default().len(), 0);
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns an iterator over the messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::FlashMessages;
+    ///
+    /// assert_eq!(FlashMessages::default().iter().count(), 0);
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, Message> {
+        self.0.iter()
+    }
+
+    /// Returns the messages as a vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::messages::FlashMessages;
+    ///
+    /// assert!(FlashMessages::default().into_vec().is_empty());
+    /// ```
+    #[must_use]
+    pub fn into_vec(self) -> Vec<Message> {
+        self.0
+    }
+}
+
+impl<'a> IntoIterator for &'a FlashMessages {
+    type Item = &'a Message;
+    type IntoIter = std::slice::Iter<'a, Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl IntoIterator for FlashMessages {
+    type Item = Message;
+    type IntoIter = std::vec::IntoIter<Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl FromRequestHead for FlashMessages {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        let messages = Messages::from_request_head(head).await?;
+        Ok(Self(messages.take().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::RequestExt;
+    use crate::test::TestRequestBuilder;
+
+    #[cot::test]
+    async fn add_and_take() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let messages = request.messages();
+
+        messages.info("first").await.unwrap();
+        messages.error("second").await.unwrap();
+
+        assert_eq!(
+            messages.take().await.unwrap(),
+            vec![
+                Message::new(Level::Info, "first"),
+                Message::new(Level::Error, "second"),
+            ]
+        );
+        assert!(messages.take().await.unwrap().is_empty());
+    }
+
+    #[cot::test]
+    async fn flash_messages_drains() {
+        let mut request = TestRequestBuilder::get("/").with_session().build();
+        request.messages().success("Saved!").await.unwrap();
+
+        let flash: FlashMessages = request.extract_from_head().await.unwrap();
+        assert_eq!(flash.len(), 1);
+        let message = flash.iter().next().unwrap();
+        assert_eq!(message.level(), Level::Success);
+        assert_eq!(message.text(), "Saved!");
+
+        let flash: FlashMessages = request.extract_from_head().await.unwrap();
+        assert!(flash.is_empty());
+    }
+
+    #[test]
+    fn level_display() {
+        assert_eq!(Level::Debug.to_string(), "debug");
+        assert_eq!(Level::Error.to_string(), "error");
+        assert!(Level::Warning > Level::Success);
+    }
+}
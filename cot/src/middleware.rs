@@ -15,23 +15,33 @@ use tower_sessions::service::PlaintextCookie;
 use tower_sessions::{SessionManagerLayer, SessionStore};
 
 use crate::Error;
-#[cfg(feature = "cache")]
-use crate::config::CacheType;
-use crate::config::{Expiry, SameSite, SessionStoreTypeConfig};
+#[cfg(feature = "json")]
+use crate::config::SessionStoreTypeConfig;
+use crate::config::{Expiry, SameSite};
 use crate::project::MiddlewareContext;
 use crate::request::Request;
 use crate::response::Response;
-use crate::session::store::SessionStoreWrapper;
-#[cfg(all(feature = "db", feature = "json"))]
-use crate::session::store::db::DbStore;
 #[cfg(feature = "json")]
-use crate::session::store::file::FileStore;
+use crate::session::store::cookie::{CookieSessionService, CookieStore};
 use crate::session::store::memory::MemoryStore;
-#[cfg(feature = "redis")]
-use crate::session::store::redis::RedisStore;
+use crate::session::store::{SessionStoreManager, SessionStoreWrapper, session_store_from_config};
 
+mod allowed_hosts;
+mod canary;
+mod cors;
+mod etag;
 #[cfg(feature = "live-reload")]
 mod live_reload;
+mod normalize_path;
+pub(crate) mod ordering;
+mod secure_redirect;
+mod timeout;
+mod upload_progress;
+
+pub use allowed_hosts::{AllowedHostsMiddleware, AllowedHostsService};
+pub use canary::{CanaryMiddleware, CanaryService};
+pub(crate) use cors::validate_config as validate_cors_config;
+pub use cors::{CorsMiddleware, CorsService};
 
 /// Middleware that converts any error type to [`Error`].
 ///
@@ -96,30 +106,75 @@ pub use cot_core::middleware::IntoCotErrorLayer;
 pub use cot_core::middleware::IntoCotResponseLayer;
 #[doc(inline)]
 pub use cot_core::middleware::{IntoCotError, IntoCotResponse};
+pub use etag::{ETagMiddleware, ETagService};
 #[cfg(feature = "live-reload")]
 pub use live_reload::LiveReloadMiddleware;
+pub use normalize_path::{NormalizePathMiddleware, NormalizePathService};
+pub use ordering::{MiddlewareMetadata, MiddlewareRequirement};
+pub use secure_redirect::{SecureRedirectMiddleware, SecureRedirectService};
+pub use timeout::{TimeoutMiddleware, TimeoutService};
+pub use upload_progress::{
+    UPLOAD_ID_PARAM, UploadProgress, UploadProgressMiddleware, UploadProgressService,
+    UploadProgressTracker,
+};
 
 type DynamicSessionStore = SessionManagerLayer<SessionStoreWrapper, PlaintextCookie>;
 
+/// The default name of the session cookie used by `tower_sessions`.
+#[cfg(feature = "json")]
+const DEFAULT_SESSION_COOKIE_NAME: &str = "id";
+
+#[cfg(feature = "json")]
+type SessionCookieService<S> = CookieSessionService<S>;
+#[cfg(not(feature = "json"))]
+type SessionCookieService<S> = S;
+
 /// A middleware that provides session management.
 ///
 /// By default, it uses an in-memory store for session data.
 #[derive(Debug, Clone)]
 pub struct SessionMiddleware {
     inner: DynamicSessionStore,
+    store_manager: Option<SessionStoreManager>,
+    /// The store used when the sessions are kept in signed cookies.
+    #[cfg(feature = "json")]
+    cookie_store: Option<CookieStore>,
+    #[cfg(feature = "json")]
+    cookie_name: Cow<'static, str>,
 }
 
 impl SessionMiddleware {
     /// Creates a new instance of [`SessionMiddleware`].
+    ///
+    /// If the store is a
+    /// [`CookieStore`](crate::session::store::cookie::CookieStore), the
+    /// sessions are kept in signed cookies instead of being stored on the
+    /// server side.
     #[must_use]
     pub fn new<S: SessionStore + Send + Sync + 'static>(store: S) -> Self {
+        #[cfg(feature = "json")]
+        let cookie_store = {
+            let store: &dyn std::any::Any = &store;
+            store.downcast_ref::<CookieStore>().cloned()
+        };
         let layer = SessionManagerLayer::new(SessionStoreWrapper::new(Arc::new(store)));
-        SessionMiddleware { inner: layer }
+        SessionMiddleware {
+            inner: layer,
+            store_manager: None,
+            #[cfg(feature = "json")]
+            cookie_store,
+            #[cfg(feature = "json")]
+            cookie_name: Cow::Borrowed(DEFAULT_SESSION_COOKIE_NAME),
+        }
     }
 
     /// Creates a new instance of [`SessionMiddleware`] from the application
     /// context.
     ///
+    /// The session store is created based on the project configuration. The
+    /// middleware also makes the [`SessionStoreManager`] available to the
+    /// request handlers, which allows them to list and remove the sessions.
+    ///
     /// # Examples
     ///
     /// ```
@@ -147,18 +202,31 @@ impl SessionMiddleware {
     #[must_use]
     pub fn from_context(context: &MiddlewareContext) -> Self {
         let session_cfg = &context.config().middlewares.session;
-        let store_type = session_cfg.store.store_type.clone();
-        let boxed_store = Self::config_to_session_store(store_type, context);
-        let arc_store = Arc::from(boxed_store);
-        let layer = SessionManagerLayer::new(SessionStoreWrapper::new(arc_store));
-        let mut middleware = SessionMiddleware { inner: layer }
-            .secure(session_cfg.secure)
-            .path(session_cfg.path.clone())
-            .name(session_cfg.name.clone())
-            .http_only(session_cfg.http_only)
-            .always_save(session_cfg.always_save)
-            .same_site(session_cfg.same_site)
-            .expiry(session_cfg.expiry);
+        let store = session_store_from_config(&session_cfg.store.store_type, context);
+        let store_manager = SessionStoreManager::new(Arc::clone(&store));
+        let layer = SessionManagerLayer::new(SessionStoreWrapper::new(store));
+        #[cfg(feature = "json")]
+        let cookie_store = match session_cfg.store.store_type {
+            SessionStoreTypeConfig::Cookie { max_size } => {
+                Some(CookieStore::from_project_config(context.config(), max_size))
+            }
+            _ => None,
+        };
+        let mut middleware = SessionMiddleware {
+            inner: layer,
+            store_manager: Some(store_manager),
+            #[cfg(feature = "json")]
+            cookie_store,
+            #[cfg(feature = "json")]
+            cookie_name: Cow::Borrowed(DEFAULT_SESSION_COOKIE_NAME),
+        }
+        .secure(session_cfg.secure)
+        .path(session_cfg.path.clone())
+        .name(session_cfg.name.clone())
+        .http_only(session_cfg.http_only)
+        .always_save(session_cfg.always_save)
+        .same_site(session_cfg.same_site)
+        .expiry(session_cfg.expiry);
 
         if let Some(domain) = session_cfg.domain.as_ref() {
             middleware = middleware.domain(domain.clone());
@@ -179,8 +247,10 @@ impl SessionMiddleware {
     /// ```
     #[must_use]
     pub fn secure(self, secure: bool) -> Self {
-        let layer = self.inner.with_secure(secure);
-        SessionMiddleware { inner: layer }
+        Self {
+            inner: self.inner.with_secure(secure),
+            ..self
+        }
     }
 
     /// Enables or disables the `HttpOnly` flag on the session cookie.
@@ -200,6 +270,7 @@ impl SessionMiddleware {
     pub fn http_only(self, http_only: bool) -> Self {
         Self {
             inner: self.inner.with_http_only(http_only),
+            ..self
         }
     }
 
@@ -218,6 +289,7 @@ impl SessionMiddleware {
     pub fn domain<D: Into<Cow<'static, str>>>(self, domain: D) -> Self {
         Self {
             inner: self.inner.with_domain(domain),
+            ..self
         }
     }
 
@@ -237,6 +309,7 @@ impl SessionMiddleware {
     pub fn same_site(self, same_site: SameSite) -> Self {
         Self {
             inner: self.inner.with_same_site(same_site.into()),
+            ..self
         }
     }
 
@@ -253,8 +326,12 @@ impl SessionMiddleware {
     /// ```
     #[must_use]
     pub fn name<N: Into<Cow<'static, str>>>(self, name: N) -> Self {
+        let name = name.into();
         Self {
-            inner: self.inner.with_name(name.into()),
+            #[cfg(feature = "json")]
+            cookie_name: name.clone(),
+            inner: self.inner.with_name(name),
+            ..self
         }
     }
 
@@ -273,6 +350,7 @@ impl SessionMiddleware {
     pub fn path<P: Into<Cow<'static, str>>>(self, path: P) -> Self {
         Self {
             inner: self.inner.with_path(path.into()),
+            ..self
         }
     }
 
@@ -293,6 +371,7 @@ impl SessionMiddleware {
     pub fn always_save(self, always_save: bool) -> Self {
         Self {
             inner: self.inner.with_always_save(always_save),
+            ..self
         }
     }
 
@@ -315,37 +394,7 @@ impl SessionMiddleware {
     pub fn expiry(self, expiry: Expiry) -> Self {
         Self {
             inner: self.inner.with_expiry(expiry.into()),
-        }
-    }
-
-    /// Convert a [`SessionStoreTypeConfig`] variant into a valid
-    /// [`SessionStore`]
-    fn config_to_session_store(
-        config: SessionStoreTypeConfig,
-        context: &MiddlewareContext,
-    ) -> Box<dyn SessionStore + Send + Sync> {
-        match config {
-            SessionStoreTypeConfig::Memory => Box::new(MemoryStore::new()),
-            #[cfg(feature = "json")]
-            SessionStoreTypeConfig::File { path } => Box::new(
-                FileStore::new(path)
-                    .unwrap_or_else(|err| panic!("could not create File store: {err}")),
-            ),
-            #[cfg(feature = "cache")]
-            SessionStoreTypeConfig::Cache { ref uri } => {
-                let cache_type = CacheType::try_from(uri.clone())
-                    .unwrap_or_else(|e| panic!("could not convert cache URI `{uri}`: {e}"));
-                match cache_type {
-                    #[cfg(feature = "redis")]
-                    CacheType::Redis => {
-                        Box::new(RedisStore::new(uri).unwrap_or_else(|e| {
-                            panic!("could not connect to Redis at `{uri}`: {e}")
-                        }))
-                    }
-                }
-            }
-            #[cfg(all(feature = "db", feature = "json"))]
-            SessionStoreTypeConfig::Database => Box::new(DbStore::new(context.database().clone())),
+            ..self
         }
     }
 }
@@ -358,15 +407,24 @@ impl Default for SessionMiddleware {
 }
 
 impl<S> tower::Layer<S> for SessionMiddleware {
-    type Service = <DynamicSessionStore as tower::Layer<
-        <SessionWrapperLayer as tower::Layer<S>>::Service,
-    >>::Service;
+    type Service =
+        SessionCookieService<
+            <DynamicSessionStore as tower::Layer<
+                <SessionWrapperLayer as tower::Layer<S>>::Service,
+            >>::Service,
+        >;
 
     fn layer(&self, inner: S) -> Self::Service {
-        let session_wrapper_layer = SessionWrapperLayer::new();
+        let session_wrapper_layer = SessionWrapperLayer {
+            store_manager: self.store_manager.clone(),
+        };
         let layers = (&self.inner, session_wrapper_layer);
+        let service = layers.layer(inner);
 
-        layers.layer(inner)
+        #[cfg(feature = "json")]
+        let service =
+            CookieSessionService::new(service, self.cookie_store.clone(), self.cookie_name.clone());
+        service
     }
 }
 
@@ -376,8 +434,10 @@ impl<S> tower::Layer<S> for SessionMiddleware {
 /// This is only useful inside [`SessionMiddleware`] to expose session object as
 /// [`crate::session::Session`] to the request handlers. This shouldn't be
 /// useful on its own.
-#[derive(Debug, Copy, Clone)]
-pub struct SessionWrapperLayer;
+#[derive(Debug, Clone)]
+pub struct SessionWrapperLayer {
+    store_manager: Option<SessionStoreManager>,
+}
 
 impl SessionWrapperLayer {
     /// Create a new [`SessionWrapperLayer`].
@@ -391,7 +451,9 @@ impl SessionWrapperLayer {
     /// ```
     #[must_use]
     pub fn new() -> Self {
-        Self {}
+        Self {
+            store_manager: None,
+        }
     }
 }
 
@@ -405,7 +467,10 @@ impl<S> tower::Layer<S> for SessionWrapperLayer {
     type Service = SessionWrapper<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        SessionWrapper { inner }
+        SessionWrapper {
+            inner,
+            store_manager: self.store_manager.clone(),
+        }
     }
 }
 
@@ -416,6 +481,7 @@ impl<S> tower::Layer<S> for SessionWrapperLayer {
 #[derive(Debug, Clone)]
 pub struct SessionWrapper<S> {
     inner: S,
+    store_manager: Option<SessionStoreManager>,
 }
 
 impl<ReqBody, ResBody, S> Service<http::Request<ReqBody>> for SessionWrapper<S>
@@ -440,6 +506,9 @@ where
             .expect("session extension must be present");
         let session_wrapped = crate::session::Session::new(session);
         req.extensions_mut().insert(session_wrapped);
+        if let Some(store_manager) = &self.store_manager {
+            req.extensions_mut().insert(store_manager.clone());
+        }
 
         self.inner.call(req)
     }
@@ -451,10 +520,14 @@ where
 /// user to the request extensions. This adds the [`crate::auth::Auth`] object
 /// to the request which can be accessed by the request handlers.
 ///
+/// The authenticated user is stored in the session, so this middleware
+/// requires [`SessionMiddleware`] to be added after it. Otherwise, booting
+/// the project fails.
+///
 /// # Examples
 ///
 /// ```
-/// use cot::middleware::AuthMiddleware;
+/// use cot::middleware::{AuthMiddleware, SessionMiddleware};
 /// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
 /// use cot::{Project, ProjectContext};
 ///
@@ -465,7 +538,10 @@ where
 ///         handler: RootHandlerBuilder,
 ///         context: &MiddlewareContext,
 ///     ) -> RootHandler {
-///         handler.middleware(AuthMiddleware::new()).build()
+///         handler
+///             .middleware(AuthMiddleware::new())
+///             .middleware(SessionMiddleware::from_context(context))
+///             .build()
 ///     }
 /// }
 /// ```
@@ -639,6 +715,107 @@ mod tests {
         assert!(!cookie_value.contains("Secure;"));
     }
 
+    async fn call_counter_service<S>(svc: &mut S, cookie: Option<&str>) -> Response
+    where
+        S: Service<Request<Body>, Response = Response, Error = Error>,
+    {
+        let mut request = TestRequestBuilder::get("/").build();
+        if let Some(cookie) = cookie {
+            request
+                .headers_mut()
+                .insert(http::header::COOKIE, cookie.parse().unwrap());
+        }
+        svc.ready().await.unwrap().call(request).await.unwrap()
+    }
+
+    fn counter_service() -> impl Service<
+        Request<Body>,
+        Response = Response,
+        Error = Error,
+        Future = impl Future<Output = Result<Response, Error>> + Send,
+    > + Clone
+    + Send
+    + 'static {
+        tower::service_fn(|req: Request<Body>| async move {
+            let session = req.extensions().get::<Session>().unwrap();
+            let counter: u32 = session.get("counter").await.unwrap().unwrap_or_default();
+            session.insert("counter", counter + 1).await.unwrap();
+
+            Ok::<_, Error>(Response::new(Body::fixed(counter.to_string())))
+        })
+    }
+
+    fn set_cookie(response: &Response) -> String {
+        let set_cookie = response
+            .headers()
+            .get("set-cookie")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        set_cookie.split(';').next().unwrap().to_owned()
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn session_middleware_cookie_store() {
+        let store = CookieStore::new(crate::config::SecretKey::new(b"secret"));
+        let mut svc = SessionMiddleware::new(store).layer(counter_service());
+
+        let response = call_counter_service(&mut svc, None).await;
+        let cookie = set_cookie(&response);
+        assert!(cookie.starts_with("id="));
+        assert!(cookie.contains('.'));
+
+        let response = call_counter_service(&mut svc, Some(&cookie)).await;
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "1");
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn session_middleware_cookie_store_rejects_tampered_cookie() {
+        let store = CookieStore::new(crate::config::SecretKey::new(b"secret"));
+        let mut svc = SessionMiddleware::new(store).layer(counter_service());
+
+        let response = call_counter_service(&mut svc, None).await;
+        let cookie = format!("{}x", set_cookie(&response));
+
+        let response = call_counter_service(&mut svc, Some(&cookie)).await;
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "0");
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn session_middleware_cookie_store_fallback_key() {
+        let old_store = CookieStore::new(crate::config::SecretKey::new(b"old"));
+        let mut old_svc = SessionMiddleware::new(old_store).layer(counter_service());
+        let store = CookieStore::new(crate::config::SecretKey::new(b"new"))
+            .with_fallback_keys([crate::config::SecretKey::new(b"old")]);
+        let mut svc = SessionMiddleware::new(store).layer(counter_service());
+
+        let response = call_counter_service(&mut old_svc, None).await;
+        let cookie = set_cookie(&response);
+
+        let response = call_counter_service(&mut svc, Some(&cookie)).await;
+        let new_cookie = set_cookie(&response);
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "1");
+
+        // the re-signed cookie is no longer accepted with the old key only
+        let response = call_counter_service(&mut old_svc, Some(&new_cookie)).await;
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "0");
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn session_middleware_cookie_store_too_large() {
+        let store = CookieStore::new(crate::config::SecretKey::new(b"secret")).with_max_size(16);
+        let mut svc = SessionMiddleware::new(store).layer(counter_service());
+
+        let response = call_counter_service(&mut svc, None).await;
+
+        assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!response.headers().contains_key("set-cookie"));
+    }
+
     #[cot::test]
     async fn auth_middleware_adds_auth() {
         let svc = tower::service_fn(|req: Request<Body>| async move {
@@ -788,6 +965,29 @@ mod tests {
         create_svc_and_call_with_req(context).await;
     }
 
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn session_middleware_cookie_config_to_session_store() {
+        let config = create_project_config(SessionStoreTypeConfig::Cookie { max_size: 4000 });
+
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(config)
+            .with_apps()
+            .with_database()
+            .await
+            .expect("bootstrap failed")
+            .with_cache()
+            .await
+            .expect("bootstrap failed");
+        let context = bootstrapper.context();
+
+        let mut svc = SessionMiddleware::from_context(context).layer(counter_service());
+        let response = call_counter_service(&mut svc, None).await;
+        let cookie = set_cookie(&response);
+        let response = call_counter_service(&mut svc, Some(&cookie)).await;
+        assert_eq!(response.into_body().into_bytes().await.unwrap(), "1");
+    }
+
     #[cfg(all(feature = "cache", feature = "redis"))]
     #[cot::test]
     #[ignore = "requires external Redis service"]
@@ -810,6 +1010,30 @@ mod tests {
         create_svc_and_call_with_req(context).await;
     }
 
+    #[cfg(feature = "redis")]
+    #[cot::test]
+    #[ignore = "requires external Redis service"]
+    async fn session_middleware_redis_store_config_to_session_store() {
+        let redis_url =
+            env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let config = create_project_config(SessionStoreTypeConfig::Redis {
+            url: CacheUrl::from(redis_url),
+            key_prefix: "cot-test:session:".to_string(),
+        });
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(config)
+            .with_apps()
+            .with_database()
+            .await
+            .expect("bootstrap failed")
+            .with_cache()
+            .await
+            .expect("bootstrap failed");
+        let context = bootstrapper.context();
+
+        create_svc_and_call_with_req(context).await;
+    }
+
     #[cfg(all(feature = "db", feature = "json"))]
     #[cot::test]
     #[cfg_attr(
@@ -0,0 +1,377 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use cot_core::error::impl_into_cot_error;
+use futures_core::future::BoxFuture;
+use http::header;
+use thiserror::Error;
+use tower::Service;
+use tracing::warn;
+
+use crate::Error;
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::Response;
+use crate::router::request_host;
+
+/// A middleware that rejects requests with an unexpected `Host` header.
+///
+/// Cot uses the `Host` header to generate absolute URLs, for instance in
+/// password reset links sent via email. If an attacker can make the server
+/// process a request with a forged `Host` header, the generated links could
+/// point to a website controlled by the attacker. This middleware prevents
+/// that by responding with `400 Bad Request` to the requests with a host that
+/// is not explicitly allowed.
+///
+/// The allowed hosts use the same format as
+/// [`ProjectConfig::allowed_hosts`](crate::config::ProjectConfig::allowed_hosts):
+/// * `example.com` matches only `example.com`,
+/// * `.example.com` matches `example.com` and all its subdomains,
+/// * `*` matches any host.
+///
+/// If the list of allowed hosts is empty, all requests are rejected, unless the
+/// middleware is created with [`AllowedHostsMiddleware::from_context`] in
+/// [debug mode](crate::config::ProjectConfig::debug).
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::AllowedHostsMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+/// use cot::{Project, ProjectContext};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(AllowedHostsMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AllowedHostsMiddleware {
+    /// The allowed host patterns, or `None` if all hosts are allowed.
+    allowed_hosts: Option<Arc<[String]>>,
+}
+
+impl AllowedHostsMiddleware {
+    /// Creates a new [`AllowedHostsMiddleware`] allowing the given hosts.
+    ///
+    /// If the list of hosts is empty, all requests are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::AllowedHostsMiddleware;
+    ///
+    /// let middleware = AllowedHostsMiddleware::new(["example.com", ".example.org"]);
+    /// ```
+    #[must_use]
+    pub fn new<I, T>(allowed_hosts: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            allowed_hosts: Some(
+                allowed_hosts
+                    .into_iter()
+                    .map(|host| host.into().to_ascii_lowercase())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Creates a new [`AllowedHostsMiddleware`] using the hosts from the
+    /// [`ProjectConfig::allowed_hosts`](crate::config::ProjectConfig::allowed_hosts)
+    /// setting.
+    ///
+    /// If the setting is empty, all hosts are allowed in
+    /// [debug mode](crate::config::ProjectConfig::debug), so that the project
+    /// can be accessed under any address during development. Otherwise, all
+    /// requests are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::AllowedHostsMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+    /// use cot::{Project, ProjectContext};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> RootHandler {
+    ///         handler
+    ///             .middleware(AllowedHostsMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        let config = context.config();
+        if config.allowed_hosts.is_empty() {
+            if config.debug {
+                return Self {
+                    allowed_hosts: None,
+                };
+            }
+            warn!(
+                "`allowed_hosts` is empty and debug mode is disabled, so all the requests \
+                will be rejected; set it to the hosts the project is served from"
+            );
+        }
+
+        Self::new(config.allowed_hosts.iter().cloned())
+    }
+
+    fn is_allowed(&self, request: &Request) -> bool {
+        let Some(allowed_hosts) = &self.allowed_hosts else {
+            return true;
+        };
+
+        let Some(host) = request_host(request.headers(), request.uri()) else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+        let host = host.strip_suffix('.').unwrap_or(&host);
+
+        allowed_hosts
+            .iter()
+            .any(|pattern| host_matches(host, pattern))
+    }
+}
+
+fn host_matches(host: &str, pattern: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+
+    match pattern.strip_prefix('.') {
+        Some(domain) => {
+            host == domain
+                || host
+                    .strip_suffix(domain)
+                    .is_some_and(|subdomain| subdomain.ends_with('.'))
+        }
+        None => host == pattern,
+    }
+}
+
+impl<S> tower::Layer<S> for AllowedHostsMiddleware {
+    type Service = AllowedHostsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AllowedHostsService {
+            inner,
+            middleware: self.clone(),
+        }
+    }
+}
+
+/// Service that rejects requests with an unexpected `Host` header.
+///
+/// Used by [`AllowedHostsMiddleware`].
+#[derive(Debug, Clone)]
+pub struct AllowedHostsService<S> {
+    inner: S,
+    middleware: AllowedHostsMiddleware,
+}
+
+impl<S> Service<Request> for AllowedHostsService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if self.middleware.is_allowed(&req) {
+            Box::pin(self.inner.call(req))
+        } else {
+            let host = req
+                .headers()
+                .get(header::HOST)
+                .map(|host| String::from_utf8_lossy(host.as_bytes()).into_owned());
+            Box::pin(async move { Err(DisallowedHost(host).into()) })
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("host not allowed: {}", .0.as_deref().unwrap_or("<none>"))]
+struct DisallowedHost(Option<String>);
+impl_into_cot_error!(DisallowedHost, BAD_REQUEST);
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderValue, StatusCode};
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Project;
+    use crate::config::ProjectConfig;
+    use crate::project::Bootstrapper;
+    use crate::test::TestRequestBuilder;
+
+    async fn call(middleware: &AllowedHostsMiddleware, host: Option<&'static str>) -> StatusCode {
+        let service = middleware.layer(tower::service_fn(|_request: Request| async {
+            Ok::<_, Error>(Response::new(crate::Body::empty()))
+        }));
+
+        let mut request = TestRequestBuilder::get("/").build();
+        if let Some(host) = host {
+            request
+                .headers_mut()
+                .insert(header::HOST, HeaderValue::from_static(host));
+        }
+
+        match service.oneshot(request).await {
+            Ok(response) => response.status(),
+            Err(error) => error.status_code(),
+        }
+    }
+
+    #[cot::test]
+    async fn empty_rejects_all() {
+        let middleware = AllowedHostsMiddleware::new(Vec::<String>::new());
+
+        assert_eq!(
+            call(&middleware, Some("example.com")).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(call(&middleware, None).await, StatusCode::BAD_REQUEST);
+    }
+
+    async fn middleware_from_config(config: ProjectConfig) -> AllowedHostsMiddleware {
+        struct TestProject;
+        impl Project for TestProject {}
+
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(config)
+            .with_apps()
+            .with_database()
+            .await
+            .unwrap()
+            .with_cache()
+            .await
+            .unwrap();
+
+        AllowedHostsMiddleware::from_context(bootstrapper.context())
+    }
+
+    #[cot::test]
+    async fn from_context_empty_debug_allows_all() {
+        let middleware = middleware_from_config(ProjectConfig::builder().debug(true).build()).await;
+
+        assert_eq!(call(&middleware, Some("evil.com")).await, StatusCode::OK);
+        assert_eq!(call(&middleware, None).await, StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn from_context_empty_rejects_all() {
+        let middleware =
+            middleware_from_config(ProjectConfig::builder().debug(false).build()).await;
+
+        assert_eq!(
+            call(&middleware, Some("example.com")).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[cot::test]
+    async fn from_context_allowed_hosts() {
+        let middleware = middleware_from_config(
+            ProjectConfig::builder()
+                .debug(true)
+                .allowed_hosts(vec!["example.com".to_owned()])
+                .build(),
+        )
+        .await;
+
+        assert_eq!(call(&middleware, Some("example.com")).await, StatusCode::OK);
+        assert_eq!(
+            call(&middleware, Some("evil.com")).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[cot::test]
+    async fn exact_host() {
+        let middleware = AllowedHostsMiddleware::new(["example.com"]);
+
+        assert_eq!(call(&middleware, Some("example.com")).await, StatusCode::OK);
+        assert_eq!(
+            call(&middleware, Some("EXAMPLE.com:8000")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            call(&middleware, Some("www.example.com")).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            call(&middleware, Some("evil.com")).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(call(&middleware, None).await, StatusCode::BAD_REQUEST);
+    }
+
+    #[cot::test]
+    async fn subdomains() {
+        let middleware = AllowedHostsMiddleware::new([".example.com"]);
+
+        assert_eq!(call(&middleware, Some("example.com")).await, StatusCode::OK);
+        assert_eq!(
+            call(&middleware, Some("www.example.com")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            call(&middleware, Some("badexample.com")).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[cot::test]
+    async fn wildcard() {
+        let middleware = AllowedHostsMiddleware::new(["*"]);
+
+        assert_eq!(
+            call(&middleware, Some("anything.org")).await,
+            StatusCode::OK
+        );
+    }
+
+    #[cot::test]
+    async fn ipv6() {
+        let middleware = AllowedHostsMiddleware::new(["[::1]"]);
+
+        assert_eq!(call(&middleware, Some("[::1]:8000")).await, StatusCode::OK);
+        assert_eq!(
+            call(&middleware, Some("[::2]")).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn host_matches_patterns() {
+        assert!(host_matches("example.com", "example.com"));
+        assert!(host_matches("a.b.example.com", ".example.com"));
+        assert!(!host_matches("example.com", "www.example.com"));
+        assert!(!host_matches("notexample.com", ".example.com"));
+    }
+}
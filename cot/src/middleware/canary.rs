@@ -0,0 +1,340 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::{HeaderName, HeaderValue, header};
+use tower::Service;
+
+use crate::Error;
+use crate::request::Request;
+use crate::response::Response;
+use crate::router::{Router, request_host};
+
+/// A middleware that routes some of the requests to an alternate set of
+/// routes.
+///
+/// This is useful for gradual rollouts of rewritten endpoints: the new
+/// versions of the endpoints are put in a separate [`Router`], and the
+/// middleware sends a configurable share of the requests to it. The
+/// requests whose path is not handled by the canary router, as well as the
+/// requests that were not selected, are passed to the main handler as usual.
+///
+/// A request is routed to the canary router if any of the following is true:
+/// * it has a header set with [`header`](Self::header),
+/// * it has a cookie set with [`cookie`](Self::cookie),
+/// * it was randomly selected based on the [`percentage`](Self::percentage).
+///
+/// Note that the random selection is made independently for each request, so
+/// a single user can be served by both versions of an endpoint. If that's
+/// undesirable, use a cookie to select the users that should see the new
+/// version.
+///
+/// # Examples
+///
+/// ```
+/// use cot::html::Html;
+/// use cot::middleware::CanaryMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+/// use cot::router::{Route, Router};
+/// use cot::{Project, ProjectContext};
+///
+/// async fn new_checkout() -> Html {
+///     Html::new("the new checkout page")
+/// }
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         let canary_router = Router::with_urls([Route::with_handler("/checkout/", new_checkout)]);
+///
+///         handler
+///             .middleware(
+///                 CanaryMiddleware::new(canary_router)
+///                     .percentage(10)
+///                     .header("x-canary", "always"),
+///             )
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CanaryMiddleware {
+    config: Arc<CanaryConfig>,
+}
+
+#[derive(Debug)]
+struct CanaryConfig {
+    router: Router,
+    percentage: u8,
+    header: Option<(HeaderName, HeaderValue)>,
+    cookie: Option<(String, String)>,
+}
+
+impl CanaryMiddleware {
+    /// Creates a new [`CanaryMiddleware`] that routes requests to the given
+    /// router.
+    ///
+    /// By default, no requests are routed to the canary router; use
+    /// [`percentage`](Self::percentage), [`header`](Self::header), or
+    /// [`cookie`](Self::cookie) to select the requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CanaryMiddleware;
+    /// use cot::router::Router;
+    ///
+    /// let middleware = CanaryMiddleware::new(Router::empty());
+    /// ```
+    #[must_use]
+    pub fn new(router: Router) -> Self {
+        Self {
+            config: Arc::new(CanaryConfig {
+                router,
+                percentage: 0,
+                header: None,
+                cookie: None,
+            }),
+        }
+    }
+
+    /// Sets the percentage of requests that should be routed to the canary
+    /// router.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the percentage is greater than 100.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CanaryMiddleware;
+    /// use cot::router::Router;
+    ///
+    /// let middleware = CanaryMiddleware::new(Router::empty()).percentage(25);
+    /// ```
+    #[must_use]
+    pub fn percentage(mut self, percentage: u8) -> Self {
+        assert!(
+            percentage <= 100,
+            "canary percentage must be between 0 and 100, got {percentage}"
+        );
+        self.config_mut().percentage = percentage;
+        self
+    }
+
+    /// Routes the requests that have the given header with the given value to
+    /// the canary router.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the header name or value is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CanaryMiddleware;
+    /// use cot::router::Router;
+    ///
+    /// let middleware = CanaryMiddleware::new(Router::empty()).header("x-canary", "1");
+    /// ```
+    #[must_use]
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::try_from(name).expect("invalid canary header name");
+        let value = HeaderValue::try_from(value).expect("invalid canary header value");
+        self.config_mut().header = Some((name, value));
+        self
+    }
+
+    /// Routes the requests that have the given cookie with the given value to
+    /// the canary router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CanaryMiddleware;
+    /// use cot::router::Router;
+    ///
+    /// let middleware = CanaryMiddleware::new(Router::empty()).cookie("beta", "yes");
+    /// ```
+    #[must_use]
+    pub fn cookie<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.config_mut().cookie = Some((name.into(), value.into()));
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut CanaryConfig {
+        Arc::get_mut(&mut self.config)
+            .expect("CanaryMiddleware should not be configured after being cloned")
+    }
+}
+
+impl CanaryConfig {
+    fn should_route(&self, request: &Request) -> bool {
+        if !self.router.has_handler(
+            request_host(request.headers(), request.uri()),
+            request.uri().path(),
+        ) {
+            return false;
+        }
+
+        if let Some((name, value)) = &self.header
+            && request.headers().get(name) == Some(value)
+        {
+            return true;
+        }
+
+        if let Some((name, value)) = &self.cookie
+            && has_cookie(request, name, value)
+        {
+            return true;
+        }
+
+        self.percentage > 0 && rand::random_range(0..100) < self.percentage
+    }
+}
+
+fn has_cookie(request: &Request, name: &str, value: &str) -> bool {
+    request
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .any(|(cookie_name, cookie_value)| {
+            cookie_name == name && cookie_value.trim_matches('"') == value
+        })
+}
+
+impl<S> tower::Layer<S> for CanaryMiddleware {
+    type Service = CanaryService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CanaryService {
+            inner,
+            config: Arc::clone(&self.config),
+        }
+    }
+}
+
+/// Service that routes some of the requests to an alternate set of routes.
+///
+/// Used by [`CanaryMiddleware`].
+#[derive(Debug, Clone)]
+pub struct CanaryService<S> {
+    inner: S,
+    config: Arc<CanaryConfig>,
+}
+
+impl<S> Service<Request> for CanaryService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if self.config.should_route(&req) {
+            let config = Arc::clone(&self.config);
+            Box::pin(async move { config.router.handle(req).await })
+        } else {
+            Box::pin(self.inner.call(req))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::html::Html;
+    use crate::router::Route;
+    use crate::test::TestRequestBuilder;
+
+    async fn canary_handler() -> Html {
+        Html::new("canary")
+    }
+
+    fn canary_router() -> Router {
+        Router::with_urls([Route::with_handler("/checkout/", canary_handler)])
+    }
+
+    fn request_with_header<N: TryInto<HeaderName>>(name: N, value: &'static str) -> Request
+    where
+        N::Error: std::fmt::Debug,
+    {
+        let mut request = TestRequestBuilder::get("/checkout/").build();
+        request
+            .headers_mut()
+            .insert(name.try_into().unwrap(), HeaderValue::from_static(value));
+        request
+    }
+
+    async fn call(middleware: &CanaryMiddleware, request: Request) -> String {
+        let service = middleware.layer(tower::service_fn(|_request: Request| async {
+            Ok::<_, Error>(Response::new(crate::Body::fixed("main")))
+        }));
+
+        let response = service.oneshot(request).await.unwrap();
+        let body = response.into_body().into_bytes().await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[cot::test]
+    async fn routes_by_percentage() {
+        let always = CanaryMiddleware::new(canary_router()).percentage(100);
+        let never = CanaryMiddleware::new(canary_router()).percentage(0);
+
+        let request = || TestRequestBuilder::get("/checkout/").build();
+        assert_eq!(call(&always, request()).await, "canary");
+        assert_eq!(call(&never, request()).await, "main");
+    }
+
+    #[cot::test]
+    async fn routes_by_header() {
+        let middleware = CanaryMiddleware::new(canary_router()).header("x-canary", "1");
+
+        let request = request_with_header("x-canary", "1");
+        assert_eq!(call(&middleware, request).await, "canary");
+
+        let request = request_with_header("x-canary", "0");
+        assert_eq!(call(&middleware, request).await, "main");
+    }
+
+    #[cot::test]
+    async fn routes_by_cookie() {
+        let middleware = CanaryMiddleware::new(canary_router()).cookie("beta", "yes");
+
+        let request = request_with_header(header::COOKIE, "session=abc; beta=yes");
+        assert_eq!(call(&middleware, request).await, "canary");
+
+        let request = request_with_header(header::COOKIE, "beta=no");
+        assert_eq!(call(&middleware, request).await, "main");
+    }
+
+    #[cot::test]
+    async fn unknown_path_goes_to_main_handler() {
+        let middleware = CanaryMiddleware::new(canary_router()).percentage(100);
+
+        let request = TestRequestBuilder::get("/other/").build();
+        assert_eq!(call(&middleware, request).await, "main");
+    }
+
+    #[test]
+    #[should_panic(expected = "canary percentage must be between 0 and 100")]
+    fn invalid_percentage() {
+        let _ = CanaryMiddleware::new(Router::empty()).percentage(101);
+    }
+}
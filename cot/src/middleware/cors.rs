@@ -0,0 +1,824 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use cot_core::error::impl_into_cot_error;
+use futures_core::future::BoxFuture;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header};
+use thiserror::Error;
+use tower::Service;
+
+use crate::config::{CorsMiddlewareConfig, CorsRouteConfig};
+use crate::project::MiddlewareContext;
+use crate::request::Request;
+use crate::response::Response;
+use crate::{Body, Error};
+
+const ANY: &str = "*";
+
+/// A middleware that implements [Cross-Origin Resource Sharing
+/// (CORS)](https://developer.mozilla.org/en-US/docs/Web/HTTP/Guides/CORS).
+///
+/// By default, browsers don't allow the scripts running on one origin (such
+/// as `https://app.example.com`) to read the responses from a different one
+/// (such as `https://api.example.com`). This middleware adds the
+/// `Access-Control-*` headers that tell the browser which origins, methods,
+/// and headers are allowed, so that a JSON API served by Cot can be used by a
+/// front-end hosted elsewhere.
+///
+/// The preflight requests (`OPTIONS` requests with the
+/// `Access-Control-Request-Method` header) are answered by the middleware
+/// itself with `204 No Content`, without calling the handler. The requests
+/// without the `Origin` header are passed through unchanged. If the origin,
+/// the method, or one of the headers of a request is not allowed, the CORS
+/// headers are simply not added, and it's the browser that rejects the
+/// response.
+///
+/// The policy is read from the [`CorsMiddlewareConfig`]. Different routes
+/// can use different policies; see [`CorsMiddlewareConfig::routes`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::CorsMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+/// use cot::{Project, ProjectContext};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(CorsMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CorsMiddleware {
+    policies: Arc<CorsPolicies>,
+}
+
+impl CorsMiddleware {
+    /// Creates a new [`CorsMiddleware`] using the given configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration contains an invalid HTTP method
+    /// or header name, an origin that is not a valid header value, or if it
+    /// allows any origin (`*`) together with the credentials.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::CorsMiddlewareConfig;
+    /// use cot::middleware::CorsMiddleware;
+    ///
+    /// let middleware = CorsMiddleware::new(
+    ///     &CorsMiddlewareConfig::builder()
+    ///         .allowed_origins(vec!["https://app.example.com".to_owned()])
+    ///         .build(),
+    /// )?;
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn new(config: &CorsMiddlewareConfig) -> crate::Result<Self> {
+        Ok(Self {
+            policies: Arc::new(CorsPolicies::new(config)?),
+        })
+    }
+
+    /// Creates a new [`CorsMiddleware`] based on the
+    /// [`MiddlewareConfig::cors`](crate::config::MiddlewareConfig::cors)
+    /// configuration.
+    ///
+    /// The configuration read from the config files is validated when it's
+    /// loaded (see [`ProjectConfig::from_toml`]), so an invalid CORS policy
+    /// is reported as a config error when the project starts.
+    ///
+    /// [`ProjectConfig::from_toml`]: crate::config::ProjectConfig::from_toml
+    ///
+    /// # Panics
+    ///
+    /// Panics if the configuration was created manually (rather than loaded
+    /// from a config file) and is invalid; see [`CorsMiddleware::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CorsMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+    /// use cot::{Project, ProjectContext};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> RootHandler {
+    ///         handler
+    ///             .middleware(CorsMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// This will allow cross-origin requests from `https://app.example.com`,
+    /// and from any origin for the routes under `/api/public/`, if the config
+    /// file contains the following:
+    ///
+    /// ```toml
+    /// [middlewares.cors]
+    /// allowed_origins = ["https://app.example.com"]
+    /// allowed_methods = ["GET", "POST", "PUT", "DELETE"]
+    /// allowed_headers = ["content-type"]
+    /// allow_credentials = true
+    /// max_age = "1h"
+    ///
+    /// [[middlewares.cors.routes]]
+    /// path = "/api/public/"
+    /// allowed_origins = ["*"]
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::new(&context.config().middlewares.cors).expect("invalid CORS configuration")
+    }
+}
+
+impl<S> tower::Layer<S> for CorsMiddleware {
+    type Service = CorsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CorsService {
+            inner,
+            policies: Arc::clone(&self.policies),
+        }
+    }
+}
+
+/// Service that adds the CORS headers to the responses and answers the
+/// preflight requests.
+///
+/// Used by [`CorsMiddleware`].
+#[derive(Debug, Clone)]
+pub struct CorsService<S> {
+    inner: S,
+    policies: Arc<CorsPolicies>,
+}
+
+impl<S> Service<Request> for CorsService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let Some(origin) = req.headers().get(header::ORIGIN).cloned() else {
+            return Box::pin(self.inner.call(req));
+        };
+        let policy = self.policies.for_path(req.uri().path());
+
+        if is_preflight(&req) {
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::NO_CONTENT;
+            policy.add_preflight_headers(&origin, req.headers(), response.headers_mut());
+            return Box::pin(async move { Ok(response) });
+        }
+
+        let headers = policy.response_headers(&origin);
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let mut response = future.await?;
+            let response_headers = response.headers_mut();
+            for (name, value) in &headers {
+                if name == header::VARY {
+                    response_headers.append(name, value.clone());
+                } else {
+                    response_headers.insert(name, value.clone());
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+fn is_preflight(request: &Request) -> bool {
+    request.method() == Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+#[derive(Debug)]
+struct CorsPolicies {
+    default: CorsPolicy,
+    /// Sorted from the longest path prefix to the shortest one.
+    routes: Vec<(String, CorsPolicy)>,
+}
+
+impl CorsPolicies {
+    fn new(config: &CorsMiddlewareConfig) -> Result<Self, CorsConfigError> {
+        let default = CorsPolicy::new(
+            &config.allowed_origins,
+            &config.allowed_methods,
+            &config.allowed_headers,
+            &config.exposed_headers,
+            config.allow_credentials,
+            config.max_age,
+        )?;
+        let mut routes = config
+            .routes
+            .iter()
+            .map(|route| Ok((route.path.clone(), CorsPolicy::from_route(route)?)))
+            .collect::<Result<Vec<_>, CorsConfigError>>()?;
+        // the longest matching prefix wins
+        routes.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+
+        Ok(Self { default, routes })
+    }
+
+    fn for_path(&self, path: &str) -> &CorsPolicy {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| is_path_prefix(prefix, path))
+            .map_or(&self.default, |(_, policy)| policy)
+    }
+}
+
+/// Returns whether `prefix` matches `path` at a segment boundary, so that
+/// `/api` matches `/api` and `/api/items`, but not `/apiary`.
+fn is_path_prefix(prefix: &str, path: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'))
+}
+
+/// Validates the CORS configuration; used when the config is loaded.
+pub(crate) fn validate_config(config: &CorsMiddlewareConfig) -> Result<(), CorsConfigError> {
+    CorsPolicies::new(config).map(|_| ())
+}
+
+/// A CORS policy, with the header values precomputed.
+#[derive(Debug)]
+struct CorsPolicy {
+    any_origin: bool,
+    allowed_origins: Vec<HeaderValue>,
+    allowed_methods: Vec<Method>,
+    allowed_methods_value: HeaderValue,
+    any_header: bool,
+    allowed_headers: Vec<HeaderName>,
+    exposed_headers: Option<HeaderValue>,
+    allow_credentials: bool,
+    max_age: Option<HeaderValue>,
+}
+
+impl CorsPolicy {
+    fn new(
+        origins: &[String],
+        methods: &[String],
+        headers: &[String],
+        exposed_headers: &[String],
+        allow_credentials: bool,
+        max_age: Option<Duration>,
+    ) -> Result<Self, CorsConfigError> {
+        let any_origin = origins.iter().any(|origin| origin == ANY);
+        if any_origin && allow_credentials {
+            // reflecting any origin with the credentials would let every
+            // website make authenticated requests on the user's behalf
+            return Err(CorsConfigError::AnyOriginWithCredentials);
+        }
+
+        let allowed_methods: Vec<Method> = if methods.is_empty() {
+            vec![Method::GET, Method::HEAD, Method::POST]
+        } else {
+            methods
+                .iter()
+                .map(|method| {
+                    Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                        .map_err(|_| CorsConfigError::InvalidMethod(method.clone()))
+                })
+                .collect::<Result<_, _>>()?
+        };
+        let allowed_methods_value = join_header_value(allowed_methods.iter().map(Method::as_str))
+            .expect("HTTP methods are always valid header values");
+
+        let exposed_headers = exposed_headers
+            .iter()
+            .map(|name| parse_header_name(name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            any_origin,
+            allowed_origins: origins
+                .iter()
+                .filter(|origin| *origin != ANY)
+                .map(|origin| {
+                    HeaderValue::try_from(origin.trim_end_matches('/'))
+                        .map_err(|_| CorsConfigError::InvalidOrigin(origin.clone()))
+                })
+                .collect::<Result<_, _>>()?,
+            allowed_methods,
+            allowed_methods_value,
+            any_header: headers.iter().any(|name| name == ANY),
+            allowed_headers: headers
+                .iter()
+                .filter(|name| *name != ANY)
+                .map(|name| parse_header_name(name))
+                .collect::<Result<_, _>>()?,
+            exposed_headers: join_header_value(exposed_headers.iter().map(HeaderName::as_str)),
+            allow_credentials,
+            max_age: max_age.map(|max_age| HeaderValue::from(max_age.as_secs())),
+        })
+    }
+
+    fn from_route(route: &CorsRouteConfig) -> Result<Self, CorsConfigError> {
+        Self::new(
+            &route.allowed_origins,
+            &route.allowed_methods,
+            &route.allowed_headers,
+            &route.exposed_headers,
+            route.allow_credentials,
+            route.max_age,
+        )
+    }
+
+    fn is_origin_allowed(&self, origin: &HeaderValue) -> bool {
+        self.any_origin
+            || self
+                .allowed_origins
+                .iter()
+                .any(|allowed| allowed.as_bytes().eq_ignore_ascii_case(origin.as_bytes()))
+    }
+
+    /// Returns the value of the `Access-Control-Allow-Origin` header for the
+    /// given request origin, or `None` if the origin is not allowed.
+    fn allow_origin(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        if !self.is_origin_allowed(origin) {
+            None
+        } else if self.any_origin && !self.allow_credentials {
+            Some(HeaderValue::from_static(ANY))
+        } else {
+            Some(origin.clone())
+        }
+    }
+
+    /// Returns whether the response depends on the `Origin` header, which
+    /// is the case unless any origin is allowed without the credentials.
+    fn varies_by_origin(&self) -> bool {
+        !self.any_origin || self.allow_credentials
+    }
+
+    /// Returns the headers added to the responses to the non-preflight
+    /// requests.
+    fn response_headers(&self, origin: &HeaderValue) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if self.varies_by_origin() {
+            headers.append(header::VARY, HeaderValue::from_static("origin"));
+        }
+        let Some(allow_origin) = self.allow_origin(origin) else {
+            return headers;
+        };
+
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(exposed_headers) = &self.exposed_headers {
+            headers.insert(
+                header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                exposed_headers.clone(),
+            );
+        }
+        headers
+    }
+
+    fn add_preflight_headers(
+        &self,
+        origin: &HeaderValue,
+        request_headers: &HeaderMap,
+        headers: &mut HeaderMap,
+    ) {
+        headers.insert(
+            header::VARY,
+            HeaderValue::from_static(
+                "origin, access-control-request-method, access-control-request-headers",
+            ),
+        );
+
+        let Some(allow_origin) = self.allow_origin(origin) else {
+            return;
+        };
+        let method_allowed = request_headers
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|method| Method::from_bytes(method.as_bytes()).ok())
+            .is_some_and(|method| self.allowed_methods.contains(&method));
+        let requested_headers = request_headers
+            .get_all(header::ACCESS_CONTROL_REQUEST_HEADERS)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|name| !name.is_empty());
+        let mut allowed_headers = Vec::new();
+        for name in requested_headers {
+            let Ok(name) = HeaderName::try_from(name) else {
+                return;
+            };
+            if !self.any_header && !self.allowed_headers.contains(&name) {
+                return;
+            }
+            allowed_headers.push(name);
+        }
+        if !method_allowed {
+            return;
+        }
+
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            self.allowed_methods_value.clone(),
+        );
+        if let Some(allowed_headers) =
+            join_header_value(allowed_headers.iter().map(HeaderName::as_str))
+        {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        }
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(max_age) = &self.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, max_age.clone());
+        }
+    }
+}
+
+fn parse_header_name(name: &str) -> Result<HeaderName, CorsConfigError> {
+    HeaderName::try_from(name).map_err(|_| CorsConfigError::InvalidHeaderName(name.to_owned()))
+}
+
+/// Joins the values into a comma-separated header value, or returns `None` if
+/// there are no values.
+fn join_header_value<'a>(values: impl Iterator<Item = &'a str>) -> Option<HeaderValue> {
+    let joined = values.collect::<Vec<_>>().join(", ");
+    if joined.is_empty() {
+        None
+    } else {
+        Some(HeaderValue::try_from(joined).expect("joined header names should be valid"))
+    }
+}
+
+/// An error returned when the CORS configuration is invalid.
+#[derive(Debug, Error)]
+pub(crate) enum CorsConfigError {
+    /// An HTTP method is invalid.
+    #[error("invalid HTTP method in the CORS configuration: {0}")]
+    InvalidMethod(String),
+    /// An origin is not a valid header value.
+    #[error("invalid origin in the CORS configuration: {0}")]
+    InvalidOrigin(String),
+    /// A header name is invalid.
+    #[error("invalid header name in the CORS configuration: {0}")]
+    InvalidHeaderName(String),
+    /// Any origin is allowed together with the credentials.
+    #[error(
+        "the CORS configuration cannot allow any origin (`*`) together with the credentials; \
+        list the allowed origins explicitly instead"
+    )]
+    AnyOriginWithCredentials,
+}
+impl_into_cot_error!(CorsConfigError);
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    const APP_ORIGIN: &str = "https://app.example.com";
+
+    fn app_config() -> CorsMiddlewareConfig {
+        CorsMiddlewareConfig::builder()
+            .allowed_origins(vec![APP_ORIGIN.to_owned()])
+            .allowed_methods(vec!["get".to_owned(), "PUT".to_owned()])
+            .allowed_headers(vec!["content-type".to_owned()])
+            .exposed_headers(vec!["etag".to_owned()])
+            .allow_credentials(true)
+            .max_age(Duration::from_secs(600))
+            .build()
+    }
+
+    async fn call(
+        middleware: &CorsMiddleware,
+        method: Method,
+        url: &str,
+        headers: &[(HeaderName, &'static str)],
+    ) -> Response {
+        let service = middleware.layer(tower::service_fn(|_request: Request| async {
+            Ok::<_, Error>(Response::new(Body::fixed("handler")))
+        }));
+
+        let mut request = TestRequestBuilder::get(url).build();
+        *request.method_mut() = method;
+        for (name, value) in headers {
+            request
+                .headers_mut()
+                .insert(name, HeaderValue::from_static(value));
+        }
+
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[cot::test]
+    async fn no_origin_passes_through() {
+        let response = call(
+            &CorsMiddleware::new(&app_config()).unwrap(),
+            Method::GET,
+            "/",
+            &[],
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            !response
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        );
+        assert!(!response.headers().contains_key(header::VARY));
+    }
+
+    #[cot::test]
+    async fn allowed_origin() {
+        let response = call(
+            &CorsMiddleware::new(&app_config()).unwrap(),
+            Method::GET,
+            "/",
+            &[(header::ORIGIN, APP_ORIGIN)],
+        )
+        .await;
+
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], APP_ORIGIN);
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_EXPOSE_HEADERS], "etag");
+        assert_eq!(headers[header::VARY], "origin");
+    }
+
+    #[cot::test]
+    async fn disallowed_origin() {
+        let response = call(
+            &CorsMiddleware::new(&app_config()).unwrap(),
+            Method::GET,
+            "/",
+            &[(header::ORIGIN, "https://evil.example.com")],
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            !response
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        );
+        assert_eq!(response.headers()[header::VARY], "origin");
+    }
+
+    #[cot::test]
+    async fn any_origin() {
+        let config = CorsMiddlewareConfig::builder()
+            .allowed_origins(vec![ANY.to_owned()])
+            .build();
+
+        let response = call(
+            &CorsMiddleware::new(&config).unwrap(),
+            Method::GET,
+            "/",
+            &[(header::ORIGIN, "https://anything.example.org")],
+        )
+        .await;
+
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(!response.headers().contains_key(header::VARY));
+    }
+
+    #[cot::test]
+    async fn preflight() {
+        let response = call(
+            &CorsMiddleware::new(&app_config()).unwrap(),
+            Method::OPTIONS,
+            "/api/items/1",
+            &[
+                (header::ORIGIN, APP_ORIGIN),
+                (header::ACCESS_CONTROL_REQUEST_METHOD, "PUT"),
+                (header::ACCESS_CONTROL_REQUEST_HEADERS, "Content-Type"),
+            ],
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], APP_ORIGIN);
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET, PUT");
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "content-type"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+    }
+
+    #[cot::test]
+    async fn preflight_disallowed() {
+        let middleware = CorsMiddleware::new(&app_config()).unwrap();
+
+        for request_headers in [
+            [
+                (header::ORIGIN, APP_ORIGIN),
+                (header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE"),
+                (header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type"),
+            ],
+            [
+                (header::ORIGIN, APP_ORIGIN),
+                (header::ACCESS_CONTROL_REQUEST_METHOD, "PUT"),
+                (header::ACCESS_CONTROL_REQUEST_HEADERS, "x-custom"),
+            ],
+            [
+                (header::ORIGIN, "https://evil.example.com"),
+                (header::ACCESS_CONTROL_REQUEST_METHOD, "GET"),
+                (header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type"),
+            ],
+        ] {
+            let response = call(&middleware, Method::OPTIONS, "/", &request_headers).await;
+
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+            assert!(
+                !response
+                    .headers()
+                    .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            );
+        }
+    }
+
+    #[cot::test]
+    async fn options_without_request_method_is_not_preflight() {
+        let response = call(
+            &CorsMiddleware::new(&app_config()).unwrap(),
+            Method::OPTIONS,
+            "/",
+            &[(header::ORIGIN, APP_ORIGIN)],
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn route_policy() {
+        let config = CorsMiddlewareConfig {
+            routes: vec![
+                CorsRouteConfig::builder()
+                    .path("/api")
+                    .allowed_origins(vec!["https://other.example.com".to_owned()])
+                    .build(),
+                CorsRouteConfig::builder()
+                    .path("/api/public/")
+                    .allowed_origins(vec![ANY.to_owned()])
+                    .build(),
+            ],
+            ..app_config()
+        };
+        let middleware = CorsMiddleware::new(&config).unwrap();
+        let origin_header = |response: &Response| {
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .cloned()
+        };
+
+        let response = call(
+            &middleware,
+            Method::GET,
+            "/api/public/items",
+            &[(header::ORIGIN, "https://anything.example.org")],
+        )
+        .await;
+        assert_eq!(origin_header(&response).unwrap(), "*");
+
+        let response = call(
+            &middleware,
+            Method::GET,
+            "/api/items",
+            &[(header::ORIGIN, APP_ORIGIN)],
+        )
+        .await;
+        assert_eq!(origin_header(&response), None);
+
+        let response = call(
+            &middleware,
+            Method::GET,
+            "/items",
+            &[(header::ORIGIN, APP_ORIGIN)],
+        )
+        .await;
+        assert_eq!(origin_header(&response).unwrap(), APP_ORIGIN);
+
+        let response = call(
+            &middleware,
+            Method::GET,
+            "/apiary",
+            &[(header::ORIGIN, APP_ORIGIN)],
+        )
+        .await;
+        assert_eq!(origin_header(&response).unwrap(), APP_ORIGIN);
+    }
+
+    #[test]
+    fn path_prefix_matches_segments() {
+        assert!(is_path_prefix("/api", "/api"));
+        assert!(is_path_prefix("/api", "/api/items"));
+        assert!(!is_path_prefix("/api", "/apiary"));
+        assert!(is_path_prefix("/api/", "/api/items"));
+        assert!(!is_path_prefix("/api/", "/api"));
+        assert!(is_path_prefix("/", "/items"));
+    }
+
+    #[test]
+    fn invalid_config() {
+        for (config, expected) in [
+            (
+                CorsMiddlewareConfig::builder()
+                    .allowed_headers(vec!["not a header".to_owned()])
+                    .build(),
+                "invalid header name in the CORS configuration: not a header",
+            ),
+            (
+                CorsMiddlewareConfig::builder()
+                    .allowed_methods(vec!["GET POST".to_owned()])
+                    .build(),
+                "invalid HTTP method in the CORS configuration: GET POST",
+            ),
+            (
+                CorsMiddlewareConfig::builder()
+                    .allowed_origins(vec!["https://app.example.com\n".to_owned()])
+                    .build(),
+                "invalid origin in the CORS configuration",
+            ),
+            (
+                CorsMiddlewareConfig::builder()
+                    .allowed_origins(vec![ANY.to_owned()])
+                    .allow_credentials(true)
+                    .build(),
+                "cannot allow any origin",
+            ),
+            (
+                CorsMiddlewareConfig::builder()
+                    .routes(vec![
+                        CorsRouteConfig::builder()
+                            .path("/api/")
+                            .allowed_origins(vec![ANY.to_owned()])
+                            .allow_credentials(true)
+                            .build(),
+                    ])
+                    .build(),
+                "cannot allow any origin",
+            ),
+        ] {
+            let error = CorsMiddleware::new(&config).unwrap_err();
+
+            assert!(error.to_string().contains(expected), "{error}");
+        }
+    }
+
+    #[test]
+    fn invalid_config_from_toml() {
+        let error = crate::config::ProjectConfig::from_toml(
+            r#"
+[middlewares.cors]
+allowed_origins = ["*"]
+allow_credentials = true
+"#,
+        )
+        .unwrap_err();
+
+        assert!(
+            error.to_string().contains("cannot allow any origin"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::{HeaderMap, HeaderValue, Method, StatusCode, header};
+use http_body::Body as _;
+use tower::Service;
+
+use crate::request::Request;
+use crate::response::{ETag, Response, is_not_modified};
+use crate::utils::http_date::parse_http_date;
+use crate::{Body, Error};
+
+/// The headers describing the body, which are removed from the
+/// `304 Not Modified` responses.
+const CONTENT_HEADERS: [header::HeaderName; 4] = [
+    header::CONTENT_TYPE,
+    header::CONTENT_LENGTH,
+    header::CONTENT_ENCODING,
+    header::CONTENT_LANGUAGE,
+];
+
+/// A middleware that adds the `ETag` header to the responses and handles the
+/// conditional `GET` and `HEAD` requests.
+///
+/// For the successful (`200 OK`) responses to `GET` and `HEAD` requests that
+/// don't have the `ETag` header set by the handler, the entity tag is computed
+/// from the hash of the response body. The responses with a streaming body
+/// (see [`Body::streaming`]) are left as they are, since computing the hash
+/// would require buffering the whole body in memory.
+///
+/// If the request contains the `If-None-Match` header matching the entity tag
+/// of the response, or the `If-Modified-Since` header and the response has
+/// the `Last-Modified` header with a date that is not later than it, the body
+/// is dropped and `304 Not Modified` is returned instead, saving bandwidth.
+/// Note that the handler is still called, so this doesn't reduce the work
+/// done by the server; for that, check the preconditions in the handler
+/// itself.
+///
+/// The middleware can be added to the whole project, as well as to individual
+/// routes (using [`Route::with_middleware`](crate::router::Route::with_middleware))
+/// or routers (using [`Router::with_middleware`](crate::router::Router::with_middleware)),
+/// with different settings for each of them.
+///
+/// # Examples
+///
+/// ```
+/// use cot::html::Html;
+/// use cot::middleware::ETagMiddleware;
+/// use cot::router::{Route, Router};
+///
+/// async fn report() -> Html {
+///     Html::new("a report that rarely changes")
+/// }
+///
+/// let router = Router::with_urls([
+///     Route::with_handler("/report/", report).with_middleware(ETagMiddleware::new().weak(true))
+/// ]);
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ETagMiddleware {
+    weak: bool,
+}
+
+impl ETagMiddleware {
+    /// Creates a new [`ETagMiddleware`] that generates strong entity tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::ETagMiddleware;
+    ///
+    /// let middleware = ETagMiddleware::new();
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { weak: false }
+    }
+
+    /// Sets whether the generated entity tags should be weak.
+    ///
+    /// A strong entity tag promises that the body is byte-for-byte identical,
+    /// so it should be weak if the body can be modified later (e.g. by a
+    /// compression middleware or a reverse proxy), or if it contains parts
+    /// that change without changing the meaning of the page, such as CSRF
+    /// tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::ETagMiddleware;
+    ///
+    /// let middleware = ETagMiddleware::new().weak(true);
+    /// ```
+    #[must_use]
+    pub const fn weak(mut self, weak: bool) -> Self {
+        self.weak = weak;
+        self
+    }
+}
+
+impl<S> tower::Layer<S> for ETagMiddleware {
+    type Service = ETagService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ETagService {
+            inner,
+            weak: self.weak,
+        }
+    }
+}
+
+/// Service that adds the `ETag` header to the responses and handles the
+/// conditional requests.
+///
+/// Used by [`ETagMiddleware`].
+#[derive(Debug, Clone)]
+pub struct ETagService<S> {
+    inner: S,
+    weak: bool,
+}
+
+impl<S> Service<Request> for ETagService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if !matches!(*req.method(), Method::GET | Method::HEAD) {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let weak = self.weak;
+        let request_headers = req.headers().clone();
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let response = future.await?;
+            if response.status() != StatusCode::OK {
+                return Ok(response);
+            }
+
+            let response = add_etag(response, weak).await?;
+            Ok(not_modified(response, &request_headers))
+        })
+    }
+}
+
+/// Adds the `ETag` header computed from the response body, unless the
+/// response already has one or its body is streaming.
+async fn add_etag(response: Response, weak: bool) -> crate::Result<Response> {
+    if response.headers().contains_key(header::ETAG)
+        || response.body().size_hint().exact().is_none()
+    {
+        return Ok(response);
+    }
+
+    let (mut head, body) = response.into_parts();
+    let content = body.into_bytes().await?;
+    let etag = ETag::from_content(&content);
+    let etag = if weak { ETag::weak(etag.tag()) } else { etag };
+    head.headers.insert(header::ETAG, HeaderValue::from(&etag));
+
+    Ok(Response::from_parts(head, Body::fixed(content)))
+}
+
+/// Replaces the response with `304 Not Modified` if the client already has
+/// the current version of it.
+fn not_modified(response: Response, request_headers: &HeaderMap) -> Response {
+    let headers = response.headers();
+    let etag = headers
+        .get(header::ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .and_then(|etag| etag.parse::<ETag>().ok());
+    let last_modified = headers
+        .get(header::LAST_MODIFIED)
+        .and_then(|last_modified| last_modified.to_str().ok())
+        .and_then(parse_http_date);
+
+    if !is_not_modified(request_headers, etag.as_ref(), last_modified) {
+        return response;
+    }
+
+    let (mut head, _body) = response.into_parts();
+    head.status = StatusCode::NOT_MODIFIED;
+    for name in CONTENT_HEADERS {
+        head.headers.remove(name);
+    }
+    Response::from_parts(head, Body::empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::response::ResponseExt;
+    use crate::test::TestRequestBuilder;
+
+    async fn call(
+        middleware: ETagMiddleware,
+        request: Request,
+        response: fn() -> Response,
+    ) -> Response {
+        let service = middleware.layer(tower::service_fn(move |_request: Request| async move {
+            Ok::<_, Error>(response())
+        }));
+
+        service.oneshot(request).await.unwrap()
+    }
+
+    fn get(headers: &[(header::HeaderName, &'static str)]) -> Request {
+        let mut request = TestRequestBuilder::get("/").build();
+        for (name, value) in headers {
+            request
+                .headers_mut()
+                .insert(name, HeaderValue::from_static(value));
+        }
+        request
+    }
+
+    fn hello() -> Response {
+        Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(Body::fixed("Hello, world!"))
+            .unwrap()
+    }
+
+    #[cot::test]
+    async fn adds_etag() {
+        let response = call(ETagMiddleware::new(), get(&[]), hello).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::ETAG],
+            HeaderValue::from(&ETag::from_content(b"Hello, world!"))
+        );
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "Hello, world!"
+        );
+    }
+
+    #[cot::test]
+    async fn adds_weak_etag() {
+        let response = call(ETagMiddleware::new().weak(true), get(&[]), hello).await;
+
+        let etag: ETag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(etag.is_weak());
+    }
+
+    #[cot::test]
+    async fn if_none_match() {
+        let etag = ETag::from_content(b"Hello, world!").to_string();
+        let request = get(&[(header::IF_NONE_MATCH, etag.leak())]);
+
+        let response = call(ETagMiddleware::new(), request, hello).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(response.headers().contains_key(header::ETAG));
+        assert!(!response.headers().contains_key(header::CONTENT_TYPE));
+        assert!(response.into_body().into_bytes().await.unwrap().is_empty());
+
+        let request = get(&[(header::IF_NONE_MATCH, r#""other""#)]);
+        let response = call(ETagMiddleware::new(), request, hello).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn if_modified_since() {
+        fn last_modified() -> Response {
+            Response::builder()
+                .header(header::LAST_MODIFIED, "Sun, 06 Nov 1994 08:49:37 GMT")
+                .body(Body::streaming(stream::once(async {
+                    Ok("Hello, world!".into())
+                })))
+                .unwrap()
+        }
+
+        let request = get(&[(header::IF_MODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:37 GMT")]);
+        let response = call(ETagMiddleware::new(), request, last_modified).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        // streaming bodies don't get an ETag
+        assert!(!response.headers().contains_key(header::ETAG));
+
+        let request = get(&[(header::IF_MODIFIED_SINCE, "Sat, 05 Nov 1994 08:49:37 GMT")]);
+        let response = call(ETagMiddleware::new(), request, last_modified).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn keeps_existing_etag() {
+        fn with_etag() -> Response {
+            Response::builder()
+                .header(header::ETAG, r#"W/"v1""#)
+                .body(Body::fixed("Hello, world!"))
+                .unwrap()
+        }
+
+        let request = get(&[(header::IF_NONE_MATCH, r#""v1""#)]);
+        let response = call(ETagMiddleware::new(), request, with_etag).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], r#"W/"v1""#);
+    }
+
+    #[cot::test]
+    async fn ignores_other_methods_and_statuses() {
+        fn not_found() -> Response {
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::fixed("Not found"))
+                .unwrap()
+        }
+
+        let request = TestRequestBuilder::post("/").build();
+        let response = call(ETagMiddleware::new(), request, hello).await;
+        assert!(!response.headers().contains_key(header::ETAG));
+
+        let response = call(ETagMiddleware::new(), get(&[]), not_found).await;
+        assert!(!response.headers().contains_key(header::ETAG));
+    }
+}
@@ -0,0 +1,372 @@
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::{HeaderValue, Method, StatusCode, header};
+use tower::Service;
+
+use crate::config::TrailingSlash;
+use crate::project::MiddlewareContext;
+use crate::request::{Request, RequestExt};
+use crate::response::{Response, ResponseExt};
+use crate::router::request_host;
+use crate::{Body, Error};
+
+/// A middleware that redirects the requests to the canonical version of the
+/// path with regard to the trailing slash.
+///
+/// Depending on the [`TrailingSlash`] policy, a request for `/todos/add` is
+/// redirected to `/todos/add/` ([`TrailingSlash::Always`]), or the other way
+/// round ([`TrailingSlash::Never`]). Similarly to Django's `APPEND_SLASH`
+/// setting, the request is only redirected if the requested
+/// path does not match any route, and the normalized one does. This means
+/// that the routes that are defined both with and without the trailing slash
+/// keep working as usual, and that the requests for non-existent pages still
+/// end with a `404 Not Found` instead of a redirect.
+///
+/// The query string is preserved. `GET` and `HEAD` requests are redirected
+/// with `301 Moved Permanently`, while the other methods are redirected with
+/// `308 Permanent Redirect`, so that the browsers don't change the method
+/// and drop the request body.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::NormalizePathMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+/// use cot::{Project, ProjectContext};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(NormalizePathMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct NormalizePathMiddleware {
+    trailing_slash: TrailingSlash,
+}
+
+impl NormalizePathMiddleware {
+    /// Creates a new [`NormalizePathMiddleware`] that appends the trailing
+    /// slash to the paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::NormalizePathMiddleware;
+    ///
+    /// let middleware = NormalizePathMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            trailing_slash: TrailingSlash::Always,
+        }
+    }
+
+    /// Creates a new [`NormalizePathMiddleware`] based on the
+    /// [`MiddlewareConfig::normalize_path`](crate::config::MiddlewareConfig::normalize_path)
+    /// configuration.
+    ///
+    /// If the trailing slash policy is not set in the config, the middleware
+    /// does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::NormalizePathMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+    /// use cot::{Project, ProjectContext};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> RootHandler {
+    ///         handler
+    ///             .middleware(NormalizePathMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// This will strip the trailing slashes if the config file contains the
+    /// following:
+    ///
+    /// ```toml
+    /// [middlewares.normalize_path]
+    /// trailing_slash = "never"
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self {
+            trailing_slash: context.config().middlewares.normalize_path.trailing_slash,
+        }
+    }
+
+    /// Sets the trailing slash policy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::TrailingSlash;
+    /// use cot::middleware::NormalizePathMiddleware;
+    ///
+    /// let middleware = NormalizePathMiddleware::new().trailing_slash(TrailingSlash::Never);
+    /// ```
+    #[must_use]
+    pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> Self {
+        self.trailing_slash = trailing_slash;
+        self
+    }
+}
+
+impl Default for NormalizePathMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for NormalizePathMiddleware {
+    type Service = NormalizePathService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        NormalizePathService {
+            inner,
+            trailing_slash: self.trailing_slash,
+        }
+    }
+}
+
+/// Service that redirects the requests to the canonical version of the path
+/// with regard to the trailing slash.
+///
+/// Used by [`NormalizePathMiddleware`].
+#[derive(Debug, Clone)]
+pub struct NormalizePathService<S> {
+    inner: S,
+    trailing_slash: TrailingSlash,
+}
+
+impl<S> Service<Request> for NormalizePathService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if let Some(response) = normalize_redirect(&req, self.trailing_slash) {
+            return Box::pin(async move { Ok(response) });
+        }
+
+        Box::pin(self.inner.call(req))
+    }
+}
+
+/// Returns the redirect response if the request path should be normalized.
+fn normalize_redirect(request: &Request, trailing_slash: TrailingSlash) -> Option<Response> {
+    let path = request.uri().path();
+    let normalized = normalized_path(path, trailing_slash)?;
+
+    let router = request.router();
+    let host = request_host(request.headers(), request.uri());
+    if router.has_handler(host, path) || !router.has_handler(host, &normalized) {
+        return None;
+    }
+
+    let location = match request.uri().query() {
+        Some(query) => format!("{normalized}?{query}"),
+        None => normalized,
+    };
+    let location = HeaderValue::try_from(location).ok()?;
+    let status = if matches!(*request.method(), Method::GET | Method::HEAD) {
+        StatusCode::MOVED_PERMANENTLY
+    } else {
+        StatusCode::PERMANENT_REDIRECT
+    };
+
+    Some(
+        Response::builder()
+            .status(status)
+            .header(header::LOCATION, location)
+            .body(Body::empty())
+            .expect("redirect response should be valid"),
+    )
+}
+
+fn normalized_path(path: &str, trailing_slash: TrailingSlash) -> Option<String> {
+    match trailing_slash {
+        TrailingSlash::Always if !path.ends_with('/') => Some(format!("{path}/")),
+        TrailingSlash::Never if path.len() > 1 && path.ends_with('/') => {
+            Some(path.trim_end_matches('/').to_owned()).filter(|path| !path.is_empty())
+        }
+        TrailingSlash::Preserve | TrailingSlash::Always | TrailingSlash::Never => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::config::{MiddlewareConfig, NormalizePathMiddlewareConfig, ProjectConfig};
+    use crate::html::Html;
+    use crate::router::{Route, Router};
+    use crate::test::TestRequestBuilder;
+    use crate::{Bootstrapper, Project};
+
+    async fn index() -> Html {
+        Html::new("index")
+    }
+
+    fn router() -> Router {
+        Router::with_urls([
+            Route::with_handler("/todos/add/", index),
+            Route::with_handler("/todos/list", index),
+            Route::with_handler("/both", index),
+            Route::with_handler("/both/", index),
+        ])
+    }
+
+    async fn call(middleware: NormalizePathMiddleware, request: Request) -> Response {
+        let service = middleware.layer(tower::service_fn(|_request: Request| async {
+            Ok::<_, Error>(Response::new(Body::empty()))
+        }));
+
+        service.oneshot(request).await.unwrap()
+    }
+
+    async fn get(middleware: NormalizePathMiddleware, url: &str) -> Response {
+        call(
+            middleware,
+            TestRequestBuilder::get(url).router(router()).build(),
+        )
+        .await
+    }
+
+    #[cot::test]
+    async fn append_slash() {
+        let middleware = NormalizePathMiddleware::new();
+
+        let response = get(middleware, "/todos/add?page=2").await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "/todos/add/?page=2"
+        );
+
+        // no route for the normalized path
+        let response = get(middleware, "/todos/list").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = get(middleware, "/missing").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        // the path has a route already
+        let response = get(middleware, "/both").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn strip_slash() {
+        let middleware = NormalizePathMiddleware::new().trailing_slash(TrailingSlash::Never);
+
+        let response = get(middleware, "/todos/list/").await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "/todos/list"
+        );
+
+        let response = get(middleware, "/todos/add/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = get(middleware, "/both/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = get(middleware, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn no_normalization() {
+        let middleware = NormalizePathMiddleware::new().trailing_slash(TrailingSlash::Preserve);
+
+        let response = get(middleware, "/todos/add").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn permanent_redirect_for_post() {
+        let request = TestRequestBuilder::post("/todos/add")
+            .router(router())
+            .build();
+
+        let response = call(NormalizePathMiddleware::new(), request).await;
+
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "/todos/add/"
+        );
+    }
+
+    #[test]
+    fn normalized_paths() {
+        assert_eq!(
+            normalized_path("/a", TrailingSlash::Always),
+            Some("/a/".to_owned())
+        );
+        assert_eq!(normalized_path("/a/", TrailingSlash::Always), None);
+        assert_eq!(
+            normalized_path("/a//", TrailingSlash::Never),
+            Some("/a".to_owned())
+        );
+        assert_eq!(normalized_path("/", TrailingSlash::Never), None);
+        assert_eq!(normalized_path("//", TrailingSlash::Never), None);
+        assert_eq!(normalized_path("/a", TrailingSlash::Preserve), None);
+    }
+
+    #[cot::test]
+    async fn from_context() {
+        struct TestProject;
+        impl Project for TestProject {}
+
+        let config = ProjectConfig::builder()
+            .middlewares(
+                MiddlewareConfig::builder()
+                    .normalize_path(
+                        NormalizePathMiddlewareConfig::builder()
+                            .trailing_slash(TrailingSlash::Never)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(config)
+            .with_apps()
+            .with_database()
+            .await
+            .unwrap()
+            .with_cache()
+            .await
+            .unwrap();
+
+        let middleware = NormalizePathMiddleware::from_context(bootstrapper.context());
+
+        assert_eq!(middleware.trailing_slash, TrailingSlash::Never);
+    }
+}
@@ -0,0 +1,324 @@
+//! Detection of known-bad middleware orders.
+//!
+//! Middlewares declare their requirements by implementing
+//! [`MiddlewareMetadata`]. [`RootHandlerBuilder`](crate::project::RootHandlerBuilder)
+//! records the metadata of the middlewares as they are added, and the order is
+//! validated when the project is booted. The metadata of the middlewares
+//! provided by Cot is recorded automatically; other middlewares need to be
+//! added with
+//! [`RootHandlerBuilder::middleware_with_metadata`](crate::project::RootHandlerBuilder::middleware_with_metadata)
+//! or one of its variants.
+//!
+//! Note that middlewares added later wrap the ones added earlier, so they are
+//! the first ones to process the request.
+
+use std::any::Any;
+
+use cot_core::error::impl_into_cot_error;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::middleware::{AuthMiddleware, SecureRedirectMiddleware, SessionMiddleware};
+use crate::static_files::StaticFilesMiddleware;
+
+/// A requirement a middleware has regarding other middlewares.
+///
+/// The middlewares are referenced by their [`MiddlewareMetadata::NAME`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MiddlewareRequirement {
+    /// The middleware with the given name must be added after this one, so
+    /// that it processes the request first. Violating this is an error, as
+    /// the middleware won't work without it.
+    Requires(&'static str),
+    /// If the middleware with the given name is used, it should be added
+    /// after this one. Violating this only results in a warning.
+    Before(&'static str),
+}
+
+/// Metadata of a middleware used to validate the middleware order.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::{MiddlewareMetadata, MiddlewareRequirement, SessionMiddleware};
+///
+/// struct CartMiddleware;
+///
+/// impl MiddlewareMetadata for CartMiddleware {
+///     const NAME: &'static str = "CartMiddleware";
+///     // the cart is stored in the session
+///     const REQUIREMENTS: &'static [MiddlewareRequirement] =
+///         &[MiddlewareRequirement::Requires(SessionMiddleware::NAME)];
+/// }
+/// ```
+pub trait MiddlewareMetadata {
+    /// The name of the middleware, as referenced by other middlewares'
+    /// requirements.
+    const NAME: &'static str;
+    /// The requirements the middleware has regarding other middlewares.
+    const REQUIREMENTS: &'static [MiddlewareRequirement] = &[];
+}
+
+impl MiddlewareMetadata for AuthMiddleware {
+    const NAME: &'static str = "AuthMiddleware";
+    const REQUIREMENTS: &'static [MiddlewareRequirement] =
+        &[MiddlewareRequirement::Requires(SessionMiddleware::NAME)];
+}
+
+impl MiddlewareMetadata for SessionMiddleware {
+    const NAME: &'static str = "SessionMiddleware";
+}
+
+impl MiddlewareMetadata for StaticFilesMiddleware {
+    const NAME: &'static str = "StaticFilesMiddleware";
+    // otherwise the static files are served without being redirected to HTTPS
+    #[cfg(not(feature = "live-reload"))]
+    const REQUIREMENTS: &'static [MiddlewareRequirement] = &[MiddlewareRequirement::Before(
+        SecureRedirectMiddleware::NAME,
+    )];
+    // additionally, the static HTML files would be served without passing
+    // through the live reload middleware, so they wouldn't get the reload
+    // script injected
+    #[cfg(feature = "live-reload")]
+    const REQUIREMENTS: &'static [MiddlewareRequirement] = &[
+        MiddlewareRequirement::Before(SecureRedirectMiddleware::NAME),
+        MiddlewareRequirement::Before(crate::middleware::LiveReloadMiddleware::NAME),
+    ];
+}
+
+impl MiddlewareMetadata for SecureRedirectMiddleware {
+    const NAME: &'static str = "SecureRedirectMiddleware";
+}
+
+#[cfg(feature = "db")]
+impl MiddlewareMetadata for crate::error::log::ErrorLogMiddleware {
+    const NAME: &'static str = "ErrorLogMiddleware";
+    // the user that made the request is only known if the auth middleware
+    // processes the request first
+    const REQUIREMENTS: &'static [MiddlewareRequirement] =
+        &[MiddlewareRequirement::Before(AuthMiddleware::NAME)];
+}
+
+#[cfg(feature = "live-reload")]
+impl MiddlewareMetadata for crate::middleware::LiveReloadMiddleware {
+    const NAME: &'static str = "LiveReloadMiddleware";
+}
+
+/// The metadata of a single middleware added to a handler.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct MiddlewareInfo {
+    name: &'static str,
+    requirements: &'static [MiddlewareRequirement],
+}
+
+impl MiddlewareInfo {
+    pub(crate) fn of<T: MiddlewareMetadata>() -> Self {
+        Self {
+            name: T::NAME,
+            requirements: T::REQUIREMENTS,
+        }
+    }
+
+    fn of_value<T: MiddlewareMetadata + 'static>(middleware: &dyn Any) -> Option<Self> {
+        middleware.is::<T>().then(Self::of::<T>)
+    }
+
+    /// Returns the metadata of the given middleware, if it's one of the
+    /// middlewares provided by Cot that declare it.
+    pub(crate) fn lookup(middleware: &dyn Any) -> Option<Self> {
+        let info = Self::of_value::<AuthMiddleware>(middleware)
+            .or_else(|| Self::of_value::<SessionMiddleware>(middleware))
+            .or_else(|| Self::of_value::<StaticFilesMiddleware>(middleware))
+            .or_else(|| Self::of_value::<SecureRedirectMiddleware>(middleware));
+        #[cfg(feature = "db")]
+        let info =
+            info.or_else(|| Self::of_value::<crate::error::log::ErrorLogMiddleware>(middleware));
+        #[cfg(feature = "live-reload")]
+        let info =
+            info.or_else(|| Self::of_value::<crate::middleware::LiveReloadMiddleware>(middleware));
+
+        info
+    }
+}
+
+/// The middlewares added to the main request handler and the error handler,
+/// in the order they were added.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct MiddlewareStack {
+    handler: Vec<MiddlewareInfo>,
+    error_handler: Vec<MiddlewareInfo>,
+}
+
+impl MiddlewareStack {
+    pub(crate) fn push_handler(&mut self, info: Option<MiddlewareInfo>) {
+        self.handler.extend(info);
+    }
+
+    pub(crate) fn push_error_handler(&mut self, info: Option<MiddlewareInfo>) {
+        self.error_handler.extend(info);
+    }
+
+    /// Checks the middleware order, logging a warning for each suboptimal
+    /// order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a middleware is missing another middleware it
+    /// requires.
+    pub(crate) fn validate(&self) -> Result<(), InvalidMiddlewareOrder> {
+        validate_handler(&self.handler, "the request handler")?;
+        validate_handler(&self.error_handler, "the error handler")
+    }
+}
+
+fn validate_handler(
+    middlewares: &[MiddlewareInfo],
+    handler: &'static str,
+) -> Result<(), InvalidMiddlewareOrder> {
+    for (index, middleware) in middlewares.iter().enumerate() {
+        let position = |name| middlewares.iter().position(|other| other.name == name);
+
+        for requirement in middleware.requirements {
+            match *requirement {
+                MiddlewareRequirement::Requires(required) => {
+                    if position(required).is_none_or(|position| position < index) {
+                        return Err(InvalidMiddlewareOrder {
+                            middleware: middleware.name,
+                            required,
+                            handler,
+                        });
+                    }
+                }
+                MiddlewareRequirement::Before(other) => {
+                    if position(other).is_some_and(|position| position < index) {
+                        warn!(
+                            "{} was added after {other} to {handler}; it should be added \
+                            before it instead",
+                            middleware.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// An error returned when a middleware is used without another middleware it
+/// requires.
+#[derive(Debug, Error)]
+#[error(
+    "{middleware} requires {required} to be added after it to {handler}, so that \
+    {required} processes the requests first"
+)]
+pub(crate) struct InvalidMiddlewareOrder {
+    middleware: &'static str,
+    required: &'static str,
+    handler: &'static str,
+}
+impl_into_cot_error!(InvalidMiddlewareOrder);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(middlewares: &[MiddlewareInfo]) -> MiddlewareStack {
+        MiddlewareStack {
+            handler: middlewares.to_vec(),
+            error_handler: middlewares.to_vec(),
+        }
+    }
+
+    #[test]
+    fn lookup_unknown_middleware() {
+        assert_eq!(
+            MiddlewareInfo::lookup(&tower::layer::util::Identity::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn lookup_cot_middleware() {
+        assert_eq!(
+            MiddlewareInfo::lookup(&SessionMiddleware::default()),
+            Some(MiddlewareInfo::of::<SessionMiddleware>())
+        );
+    }
+
+    #[test]
+    fn third_party_middleware_metadata() {
+        struct CartMiddleware;
+        impl MiddlewareMetadata for CartMiddleware {
+            const NAME: &'static str = "CartMiddleware";
+            const REQUIREMENTS: &'static [MiddlewareRequirement] =
+                &[MiddlewareRequirement::Requires(SessionMiddleware::NAME)];
+        }
+
+        let mut stack = MiddlewareStack::default();
+        stack.push_handler(Some(MiddlewareInfo::of::<CartMiddleware>()));
+        let error = stack.validate().unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("CartMiddleware requires SessionMiddleware")
+        );
+
+        stack.push_handler(Some(MiddlewareInfo::of::<SessionMiddleware>()));
+        assert!(stack.validate().is_ok());
+    }
+
+    #[test]
+    fn auth_before_session_is_valid() {
+        let stack = stack(&[
+            MiddlewareInfo::of::<AuthMiddleware>(),
+            MiddlewareInfo::of::<SessionMiddleware>(),
+        ]);
+
+        assert!(stack.validate().is_ok());
+    }
+
+    #[test]
+    fn auth_without_session_is_invalid() {
+        let stack = stack(&[MiddlewareInfo::of::<AuthMiddleware>()]);
+
+        let error = stack.validate().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "AuthMiddleware requires SessionMiddleware to be added after it to the request \
+            handler, so that SessionMiddleware processes the requests first"
+        );
+    }
+
+    #[test]
+    fn auth_after_session_is_invalid() {
+        let stack = stack(&[
+            MiddlewareInfo::of::<SessionMiddleware>(),
+            MiddlewareInfo::of::<AuthMiddleware>(),
+        ]);
+
+        assert!(stack.validate().is_err());
+    }
+
+    #[test]
+    fn auth_in_error_handler_only_is_invalid() {
+        let mut stack = MiddlewareStack::default();
+        stack.push_handler(MiddlewareInfo::lookup(&SessionMiddleware::default()));
+        stack.push_error_handler(MiddlewareInfo::lookup(&AuthMiddleware::new()));
+
+        let error = stack.validate().unwrap_err();
+        assert!(error.to_string().contains("the error handler"));
+    }
+
+    #[cfg(feature = "live-reload")]
+    #[test]
+    fn static_files_after_live_reload_is_only_a_warning() {
+        let stack = stack(&[
+            MiddlewareInfo::of::<crate::middleware::LiveReloadMiddleware>(),
+            MiddlewareInfo::of::<StaticFilesMiddleware>(),
+        ]);
+
+        assert!(stack.validate().is_ok());
+    }
+}
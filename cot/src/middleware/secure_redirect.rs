@@ -0,0 +1,629 @@
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use cot_core::error::impl_into_cot_error;
+use futures_core::future::BoxFuture;
+use http::{HeaderName, HeaderValue, StatusCode, header};
+use thiserror::Error;
+use tower::Service;
+
+use crate::project::MiddlewareContext;
+use crate::request::{ConnectionScheme, Request};
+use crate::response::{Response, ResponseExt};
+use crate::{Body, Error};
+
+const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+
+/// A middleware that redirects plain HTTP requests to HTTPS and adds the
+/// `Strict-Transport-Security` (HSTS) header to the responses.
+///
+/// The requests received over a TLS connection accepted by Cot itself (see
+/// [`ConnectionScheme`]) are always considered secure. Otherwise, the scheme
+/// of the request URI is used.
+///
+/// When Cot runs behind a reverse proxy terminating TLS, the proxy headers can
+/// be trusted instead by enabling
+/// [`trust_forwarded_proto`](Self::trust_forwarded_proto). The middleware
+/// then determines the scheme of the original request using (in this order):
+/// * the last value of the `X-Forwarded-Proto` header,
+/// * the `proto` parameter of the last element of the `Forwarded` header,
+/// * the scheme of the request URI.
+///
+/// The last values are used, as these are the ones appended by the proxy
+/// closest to Cot; the earlier ones can be set by the client. If none of these
+/// is present, the request is assumed to be made over plain HTTP. Make sure
+/// your reverse proxy sets (or appends to) one of these headers, as otherwise
+/// all the requests will be redirected, resulting in a redirect loop.
+///
+/// The proxy headers are not trusted by default, as without a proxy in front
+/// of Cot they come straight from the client, which could then skip the
+/// redirect by sending `X-Forwarded-Proto: https`.
+///
+/// The HTTP requests are redirected with `301 Moved Permanently` to the same
+/// host and path using the `https` scheme. The HSTS header, if configured, is
+/// only added to the responses served over HTTPS, as the browsers ignore it
+/// otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use cot::middleware::SecureRedirectMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+/// use cot::{Project, ProjectContext};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(SecureRedirectMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "the flags are independent configuration options"
+)]
+#[derive(Debug, Copy, Clone)]
+pub struct SecureRedirectMiddleware {
+    redirect: bool,
+    trust_forwarded_proto: bool,
+    hsts_max_age: Option<Duration>,
+    hsts_include_subdomains: bool,
+    hsts_preload: bool,
+}
+
+impl SecureRedirectMiddleware {
+    /// Creates a new [`SecureRedirectMiddleware`] that redirects HTTP requests
+    /// to HTTPS and doesn't send the HSTS header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::SecureRedirectMiddleware;
+    ///
+    /// let middleware =
+    ///     SecureRedirectMiddleware::new().hsts_max_age(Duration::from_secs(31_536_000));
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            redirect: true,
+            trust_forwarded_proto: false,
+            hsts_max_age: None,
+            hsts_include_subdomains: false,
+            hsts_preload: false,
+        }
+    }
+
+    /// Creates a new [`SecureRedirectMiddleware`] based on the
+    /// [`MiddlewareConfig::secure_redirect`](crate::config::MiddlewareConfig::secure_redirect)
+    /// configuration.
+    ///
+    /// If the redirect is not enabled in the config and the HSTS max age is
+    /// not set, the middleware does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecureRedirectMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+    /// use cot::{Project, ProjectContext};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> RootHandler {
+    ///         handler
+    ///             .middleware(SecureRedirectMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// This will redirect the requests and send the HSTS header if the config
+    /// file contains the following:
+    ///
+    /// ```toml
+    /// [middlewares.secure_redirect]
+    /// enabled = true
+    /// trust_forwarded_proto = true
+    /// hsts_max_age = "1year"
+    /// hsts_include_subdomains = true
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        let config = &context.config().middlewares.secure_redirect;
+        Self {
+            redirect: config.enabled,
+            trust_forwarded_proto: config.trust_forwarded_proto,
+            hsts_max_age: config.hsts_max_age,
+            hsts_include_subdomains: config.hsts_include_subdomains,
+            hsts_preload: config.hsts_preload,
+        }
+    }
+
+    /// Sets whether the `X-Forwarded-Proto` and `Forwarded` headers are
+    /// trusted to determine the scheme of the original request.
+    ///
+    /// Only enable this when Cot runs behind a reverse proxy that sets these
+    /// headers; otherwise, they can be set by the client. Defaults to `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::SecureRedirectMiddleware;
+    ///
+    /// let middleware = SecureRedirectMiddleware::new().trust_forwarded_proto(true);
+    /// ```
+    #[must_use]
+    pub fn trust_forwarded_proto(self, trust_forwarded_proto: bool) -> Self {
+        Self {
+            trust_forwarded_proto,
+            ..self
+        }
+    }
+
+    /// Sets the `max-age` of the `Strict-Transport-Security` header. The
+    /// header is sent only if this is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::SecureRedirectMiddleware;
+    ///
+    /// let middleware = SecureRedirectMiddleware::new().hsts_max_age(Duration::from_secs(3600));
+    /// ```
+    #[must_use]
+    pub fn hsts_max_age(self, max_age: Duration) -> Self {
+        Self {
+            hsts_max_age: Some(max_age),
+            ..self
+        }
+    }
+
+    /// Sets whether the `includeSubDomains` directive is added to the
+    /// `Strict-Transport-Security` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::SecureRedirectMiddleware;
+    ///
+    /// let middleware = SecureRedirectMiddleware::new()
+    ///     .hsts_max_age(Duration::from_secs(3600))
+    ///     .hsts_include_subdomains(true);
+    /// ```
+    #[must_use]
+    pub fn hsts_include_subdomains(self, include_subdomains: bool) -> Self {
+        Self {
+            hsts_include_subdomains: include_subdomains,
+            ..self
+        }
+    }
+
+    /// Sets whether the `preload` directive is added to the
+    /// `Strict-Transport-Security` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::SecureRedirectMiddleware;
+    ///
+    /// let middleware = SecureRedirectMiddleware::new()
+    ///     .hsts_max_age(Duration::from_secs(63_072_000))
+    ///     .hsts_include_subdomains(true)
+    ///     .hsts_preload(true);
+    /// ```
+    #[must_use]
+    pub fn hsts_preload(self, preload: bool) -> Self {
+        Self {
+            hsts_preload: preload,
+            ..self
+        }
+    }
+
+    fn hsts_header(&self) -> Option<HeaderValue> {
+        let max_age = self.hsts_max_age?;
+
+        let mut value = format!("max-age={}", max_age.as_secs());
+        if self.hsts_include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if self.hsts_preload {
+            value.push_str("; preload");
+        }
+
+        Some(HeaderValue::try_from(value).expect("HSTS header should always be valid"))
+    }
+}
+
+impl Default for SecureRedirectMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for SecureRedirectMiddleware {
+    type Service = SecureRedirectService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SecureRedirectService {
+            inner,
+            redirect: self.redirect,
+            trust_forwarded_proto: self.trust_forwarded_proto,
+            hsts: self.hsts_header(),
+        }
+    }
+}
+
+/// Service that redirects plain HTTP requests to HTTPS and adds the
+/// `Strict-Transport-Security` header to the responses.
+///
+/// Used by [`SecureRedirectMiddleware`].
+#[derive(Debug, Clone)]
+pub struct SecureRedirectService<S> {
+    inner: S,
+    redirect: bool,
+    trust_forwarded_proto: bool,
+    hsts: Option<HeaderValue>,
+}
+
+impl<S> Service<Request> for SecureRedirectService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let secure = is_secure(&req, self.trust_forwarded_proto);
+
+        if !secure && self.redirect {
+            let response = https_redirect(&req);
+            return Box::pin(async move { response });
+        }
+
+        let hsts = if secure { self.hsts.clone() } else { None };
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let mut response = future.await?;
+            if let Some(hsts) = hsts {
+                response
+                    .headers_mut()
+                    .insert(header::STRICT_TRANSPORT_SECURITY, hsts);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Returns whether the request was originally made over HTTPS.
+fn is_secure(request: &Request, trust_forwarded_proto: bool) -> bool {
+    request_scheme(request, trust_forwarded_proto)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https"))
+}
+
+fn request_scheme(request: &Request, trust_forwarded_proto: bool) -> Option<&str> {
+    if let Some(scheme) = request.extensions().get::<ConnectionScheme>()
+        && *scheme == ConnectionScheme::Https
+    {
+        return Some(scheme.as_str());
+    }
+
+    if !trust_forwarded_proto {
+        return request.uri().scheme_str();
+    }
+
+    let headers = request.headers();
+
+    if let Some(proto) = headers.get(X_FORWARDED_PROTO) {
+        // a comma-separated list if there are multiple proxies; the last one
+        // is appended by the proxy Cot is connected to, while the first one
+        // might have been sent by the client
+        let proto = proto.to_str().ok()?;
+        return proto.rsplit(',').next().map(str::trim);
+    }
+
+    if let Some(forwarded) = headers.get(header::FORWARDED) {
+        let forwarded = forwarded.to_str().ok()?;
+        let last = forwarded.rsplit(',').next()?;
+        return last.split(';').find_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            name.eq_ignore_ascii_case("proto")
+                .then(|| value.trim_matches('"'))
+        });
+    }
+
+    request.uri().scheme_str()
+}
+
+fn https_redirect(request: &Request) -> crate::Result<Response> {
+    let host = match request.headers().get(header::HOST) {
+        Some(host) => host.to_str().ok(),
+        None => request.uri().authority().map(http::uri::Authority::as_str),
+    }
+    .ok_or(MissingHost)?;
+    // the default HTTP port doesn't make sense for HTTPS
+    let host = host.strip_suffix(":80").unwrap_or(host);
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map_or("/", http::uri::PathAndQuery::as_str);
+
+    let location = HeaderValue::try_from(format!("https://{host}{path_and_query}"))
+        .map_err(|_| MissingHost)?;
+
+    Ok(Response::builder()
+        .status(StatusCode::MOVED_PERMANENTLY)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .expect("redirect response should be valid"))
+}
+
+#[derive(Debug, Error)]
+#[error("cannot redirect to HTTPS: the request does not contain a valid host")]
+struct MissingHost;
+impl_into_cot_error!(MissingHost, BAD_REQUEST);
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::config::{MiddlewareConfig, ProjectConfig, SecureRedirectMiddlewareConfig};
+    use crate::test::TestRequestBuilder;
+    use crate::{Bootstrapper, Project};
+
+    async fn call(
+        middleware: &SecureRedirectMiddleware,
+        url: &str,
+        headers: &[(HeaderName, &'static str)],
+    ) -> Response {
+        let service = middleware.layer(tower::service_fn(|_request: Request| async {
+            Ok::<_, Error>(Response::new(Body::empty()))
+        }));
+
+        let mut request = TestRequestBuilder::get(url).build();
+        for (name, value) in headers {
+            request
+                .headers_mut()
+                .insert(name, HeaderValue::from_static(value));
+        }
+
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[cot::test]
+    async fn redirects_http() {
+        let middleware = SecureRedirectMiddleware::new();
+
+        let response = call(
+            &middleware,
+            "/path/?page=2",
+            &[(header::HOST, "example.com")],
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "https://example.com/path/?page=2"
+        );
+    }
+
+    #[cot::test]
+    async fn forwarded_proto() {
+        let middleware = SecureRedirectMiddleware::new().trust_forwarded_proto(true);
+
+        let response = call(
+            &middleware,
+            "/",
+            &[
+                (header::HOST, "example.com"),
+                (X_FORWARDED_PROTO, "http, https"),
+            ],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // the first value can be set by the client, so it's not trusted
+        let response = call(
+            &middleware,
+            "/",
+            &[
+                (header::HOST, "example.com"),
+                (X_FORWARDED_PROTO, "https, http"),
+            ],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+
+        let response = call(
+            &middleware,
+            "/",
+            &[
+                (header::HOST, "example.com"),
+                (
+                    header::FORWARDED,
+                    "for=192.0.2.60;proto=https;by=203.0.113.43",
+                ),
+            ],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = call(
+            &middleware,
+            "/",
+            &[
+                (header::HOST, "example.com"),
+                (header::FORWARDED, "proto=https, for=192.0.2.60;proto=http"),
+            ],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+
+        let response = call(
+            &middleware,
+            "/",
+            &[(header::HOST, "example.com"), (X_FORWARDED_PROTO, "http")],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+    }
+
+    #[cot::test]
+    async fn forwarded_proto_untrusted() {
+        let middleware = SecureRedirectMiddleware::new().hsts_max_age(Duration::from_secs(3600));
+
+        for forwarded in [
+            (X_FORWARDED_PROTO, "https"),
+            (header::FORWARDED, "for=192.0.2.60;proto=https"),
+        ] {
+            let response = call(
+                &middleware,
+                "/",
+                &[(header::HOST, "example.com"), forwarded],
+            )
+            .await;
+
+            assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+            assert!(
+                response
+                    .headers()
+                    .get(header::STRICT_TRANSPORT_SECURITY)
+                    .is_none()
+            );
+        }
+    }
+
+    #[cot::test]
+    async fn tls_connection() {
+        let middleware = SecureRedirectMiddleware::new().hsts_max_age(Duration::from_secs(3600));
+        let service = middleware.layer(tower::service_fn(|_request: Request| async {
+            Ok::<_, Error>(Response::new(Body::empty()))
+        }));
+
+        let mut request = TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert(header::HOST, HeaderValue::from_static("example.com"));
+        // the connection is encrypted, regardless of what the headers say
+        request
+            .headers_mut()
+            .insert(X_FORWARDED_PROTO, HeaderValue::from_static("http"));
+        request.extensions_mut().insert(ConnectionScheme::Https);
+        let response = service.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .unwrap(),
+            "max-age=3600"
+        );
+    }
+
+    #[cot::test]
+    async fn hsts_header() {
+        let middleware = SecureRedirectMiddleware::new()
+            .hsts_max_age(Duration::from_secs(31_536_000))
+            .hsts_include_subdomains(true)
+            .hsts_preload(true)
+            .trust_forwarded_proto(true);
+
+        let response = call(&middleware, "/", &[(X_FORWARDED_PROTO, "https")]).await;
+
+        assert_eq!(
+            response
+                .headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .unwrap(),
+            "max-age=31536000; includeSubDomains; preload"
+        );
+    }
+
+    #[cot::test]
+    async fn no_hsts_over_http() {
+        let mut middleware =
+            SecureRedirectMiddleware::new().hsts_max_age(Duration::from_secs(3600));
+        middleware.redirect = false;
+
+        let response = call(&middleware, "/", &[(header::HOST, "example.com")]).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response
+                .headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .is_none()
+        );
+    }
+
+    #[cot::test]
+    async fn from_context() {
+        struct TestProject;
+        impl Project for TestProject {}
+
+        let config = ProjectConfig::builder()
+            .middlewares(
+                MiddlewareConfig::builder()
+                    .secure_redirect(
+                        SecureRedirectMiddlewareConfig::builder()
+                            .enabled(true)
+                            .trust_forwarded_proto(true)
+                            .hsts_max_age(Duration::from_secs(3600))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(config)
+            .with_apps()
+            .with_database()
+            .await
+            .unwrap()
+            .with_cache()
+            .await
+            .unwrap();
+
+        let middleware = SecureRedirectMiddleware::from_context(bootstrapper.context());
+
+        assert!(middleware.redirect);
+        assert!(middleware.trust_forwarded_proto);
+        assert_eq!(middleware.hsts_header().unwrap(), "max-age=3600");
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let config = ProjectConfig::default().middlewares.secure_redirect;
+
+        assert!(!config.enabled);
+        assert!(!config.trust_forwarded_proto);
+        assert_eq!(config.hsts_max_age, None);
+    }
+}
@@ -0,0 +1,153 @@
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use tower::Service;
+
+use crate::Error;
+use crate::deadline::Deadline;
+use crate::request::Request;
+use crate::response::Response;
+
+/// A middleware that limits how long the processing of a request can take.
+///
+/// The middleware sets a [`Deadline`] for each request. The deadline is
+/// available to the handlers through the [`Deadline`] extractor and
+/// [`Deadline::current`], and is respected by the database queries, so that
+/// a single slow downstream service can't hold a request for minutes. If the
+/// handler doesn't finish before the deadline, the request fails with
+/// `504 Gateway Timeout`.
+///
+/// Note that the deadline only applies to producing the response; the
+/// streaming response bodies can still be sent after it expires.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::middleware::TimeoutMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+/// use cot::{Project, ProjectContext};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(TimeoutMiddleware::new(Duration::from_secs(30)))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct TimeoutMiddleware {
+    timeout: Duration,
+}
+
+impl TimeoutMiddleware {
+    /// Creates a new [`TimeoutMiddleware`] with the given timeout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::middleware::TimeoutMiddleware;
+    ///
+    /// let middleware = TimeoutMiddleware::new(Duration::from_secs(30));
+    /// ```
+    #[must_use]
+    pub const fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<S> tower::Layer<S> for TimeoutMiddleware {
+    type Service = TimeoutService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeoutService {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Service that limits how long the processing of a request can take.
+///
+/// Used by [`TimeoutMiddleware`].
+#[derive(Debug, Clone)]
+pub struct TimeoutService<S> {
+    inner: S,
+    timeout: Duration,
+}
+
+impl<S> Service<Request> for TimeoutService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let deadline = Deadline::after(self.timeout).min(Deadline::current());
+        req.extensions_mut().insert(deadline);
+
+        let response = self.inner.call(req);
+        Box::pin(deadline.scope(async move { deadline.run(response).await? }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::request::RequestExt;
+    use crate::test::TestRequestBuilder;
+
+    async fn call<F>(timeout: Duration, handler: fn(Request) -> F) -> crate::Result<Response>
+    where
+        F: Future<Output = crate::Result<Response>> + Send + 'static,
+    {
+        let service = TimeoutMiddleware::new(timeout).layer(tower::service_fn(handler));
+
+        service.oneshot(TestRequestBuilder::get("/").build()).await
+    }
+
+    #[cot::test]
+    async fn sets_deadline() {
+        let response = call(Duration::from_secs(60), |mut request| async move {
+            let deadline: Deadline = request.extract_from_head().await?;
+            assert_eq!(deadline, Deadline::current());
+            assert!(deadline.remaining().unwrap() <= Duration::from_secs(60));
+            Ok(Response::new(crate::Body::empty()))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn times_out() {
+        let error = call(Duration::from_millis(1), |_request| async {
+            std::future::pending().await
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::GATEWAY_TIMEOUT);
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use futures_util::StreamExt;
+use http_body_util::BodyExt;
+use serde::Serialize;
+use tokio::sync::watch;
+use tower::Service;
+
+use crate::request::extractors::FromRequestHead;
+use crate::request::{Request, RequestHead};
+use crate::response::Response;
+use crate::{Body, Error};
+
+/// The name of the query parameter or header containing the ID of an upload.
+///
+/// Plain HTML forms can't set custom headers, so the ID is typically added to
+/// the query string of the form's `action` URL, for instance
+/// `/upload/?X-Progress-ID=abc123`.
+pub const UPLOAD_ID_PARAM: &str = "X-Progress-ID";
+
+/// The progress of a single upload.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct UploadProgress {
+    received: u64,
+    total: Option<u64>,
+}
+
+impl UploadProgress {
+    /// Returns the number of bytes of the request body received so far.
+    #[must_use]
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// Returns the total size of the request body in bytes, if the request
+    /// has a `Content-Length` header.
+    #[must_use]
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+}
+
+/// A registry of the uploads currently in progress.
+///
+/// The tracker is shared by all the requests handled by an
+/// [`UploadProgressMiddleware`], and is available to the request handlers as
+/// an extractor. This allows to create an endpoint that reports the progress
+/// of an upload identified by the ID passed in the [`UPLOAD_ID_PARAM`] query
+/// parameter or header of the upload request.
+///
+/// The progress of an upload is available from the moment its request is
+/// received until its body is dropped, which typically happens when the
+/// response is sent.
+///
+/// # Examples
+///
+/// ```
+/// use cot::error::NotFound;
+/// use cot::json::Json;
+/// use cot::middleware::{UploadProgress, UploadProgressTracker};
+/// use cot::request::extractors::Path;
+///
+/// async fn upload_progress(
+///     tracker: UploadProgressTracker,
+///     Path(upload_id): Path<String>,
+/// ) -> cot::Result<Json<UploadProgress>> {
+///     let progress = tracker
+///         .get(&upload_id)
+///         .ok_or_else(|| NotFound::with_message("upload not found"))?;
+///     Ok(Json(progress))
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UploadProgressTracker {
+    uploads: Arc<Mutex<HashMap<String, watch::Sender<UploadProgress>>>>,
+}
+
+impl UploadProgressTracker {
+    /// Creates a new, empty [`UploadProgressTracker`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::UploadProgressTracker;
+    ///
+    /// let tracker = UploadProgressTracker::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current progress of the upload with the given ID, or `None`
+    /// if there is no such upload in progress.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal mutex is poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::UploadProgressTracker;
+    ///
+    /// let tracker = UploadProgressTracker::new();
+    /// assert!(tracker.get("abc123").is_none());
+    /// ```
+    #[must_use]
+    pub fn get(&self, upload_id: &str) -> Option<UploadProgress> {
+        self.lock().get(upload_id).map(|sender| *sender.borrow())
+    }
+
+    /// Returns a channel receiving the progress updates of the upload with
+    /// the given ID, or `None` if there is no such upload in progress.
+    ///
+    /// The channel is closed when the upload body is dropped. This is useful
+    /// for pushing the progress to the clients, for instance using
+    /// server-sent events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal mutex is poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::UploadProgressTracker;
+    ///
+    /// let tracker = UploadProgressTracker::new();
+    /// assert!(tracker.subscribe("abc123").is_none());
+    /// ```
+    #[must_use]
+    pub fn subscribe(&self, upload_id: &str) -> Option<watch::Receiver<UploadProgress>> {
+        self.lock().get(upload_id).map(watch::Sender::subscribe)
+    }
+
+    fn start(&self, upload_id: String, total: Option<u64>) -> UploadGuard {
+        let (sender, _) = watch::channel(UploadProgress { received: 0, total });
+        self.lock().insert(upload_id.clone(), sender.clone());
+
+        UploadGuard {
+            tracker: self.clone(),
+            upload_id,
+            sender,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, watch::Sender<UploadProgress>>> {
+        self.uploads
+            .lock()
+            .expect("upload progress mutex should not be poisoned")
+    }
+}
+
+impl FromRequestHead for UploadProgressTracker {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        let tracker = head
+            .extensions
+            .get::<UploadProgressTracker>()
+            .expect("UploadProgressMiddleware not enabled for the route/project")
+            .clone();
+
+        Ok(tracker)
+    }
+}
+
+/// Updates the progress of an upload and removes it from the tracker when
+/// dropped.
+#[derive(Debug)]
+struct UploadGuard {
+    tracker: UploadProgressTracker,
+    upload_id: String,
+    sender: watch::Sender<UploadProgress>,
+}
+
+impl UploadGuard {
+    fn add_received(&self, bytes: usize) {
+        self.sender.send_modify(|progress| {
+            progress.received += bytes as u64;
+        });
+    }
+}
+
+impl Drop for UploadGuard {
+    fn drop(&mut self) {
+        let mut uploads = self.tracker.lock();
+        // another upload with the same ID might have been started in the
+        // meantime; don't remove it
+        if uploads
+            .get(&self.upload_id)
+            .is_some_and(|sender| sender.same_channel(&self.sender))
+        {
+            uploads.remove(&self.upload_id);
+        }
+    }
+}
+
+/// A middleware that tracks the progress of request body uploads.
+///
+/// The progress is tracked for the requests that have an upload ID in the
+/// [`UPLOAD_ID_PARAM`] query parameter or header, by counting the bytes of
+/// the request body as it's being read (for instance, while a multipart form
+/// is being parsed). The progress can then be read using the
+/// [`UploadProgressTracker`] extractor.
+///
+/// # Examples
+///
+/// ```
+/// use cot::Project;
+/// use cot::middleware::UploadProgressMiddleware;
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler.middleware(UploadProgressMiddleware::new()).build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UploadProgressMiddleware {
+    tracker: UploadProgressTracker,
+}
+
+impl UploadProgressMiddleware {
+    /// Creates a new [`UploadProgressMiddleware`] with an empty tracker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::UploadProgressMiddleware;
+    ///
+    /// let middleware = UploadProgressMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> tower::Layer<S> for UploadProgressMiddleware {
+    type Service = UploadProgressService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        UploadProgressService {
+            inner,
+            tracker: self.tracker.clone(),
+        }
+    }
+}
+
+/// Service that tracks the progress of request body uploads.
+///
+/// Used by [`UploadProgressMiddleware`].
+#[derive(Debug, Clone)]
+pub struct UploadProgressService<S> {
+    inner: S,
+    tracker: UploadProgressTracker,
+}
+
+impl<S> Service<Request> for UploadProgressService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        req.extensions_mut().insert(self.tracker.clone());
+
+        if let Some(upload_id) = upload_id(&req) {
+            let total = req
+                .headers()
+                .get(http::header::CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok()?.parse().ok());
+            let guard = self.tracker.start(upload_id, total);
+
+            let body = std::mem::take(req.body_mut());
+            let stream = body.into_data_stream().inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    guard.add_received(chunk.len());
+                }
+            });
+            *req.body_mut() = Body::streaming(stream);
+        }
+
+        Box::pin(self.inner.call(req))
+    }
+}
+
+fn upload_id(request: &Request) -> Option<String> {
+    let from_query = request.uri().query().and_then(|query| {
+        form_urlencoded::parse(query.as_bytes())
+            .find(|(name, _)| name == UPLOAD_ID_PARAM)
+            .map(|(_, value)| value.into_owned())
+    });
+
+    from_query
+        .or_else(|| {
+            request
+                .headers()
+                .get(UPLOAD_ID_PARAM)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned)
+        })
+        .filter(|upload_id| !upload_id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::request::RequestExt;
+    use crate::test::TestRequestBuilder;
+
+    #[cot::test]
+    async fn tracks_progress() {
+        let middleware = UploadProgressMiddleware::new();
+        let tracker = middleware.tracker.clone();
+        let service = middleware.layer(tower::service_fn(|mut request: Request| async move {
+            let tracker: UploadProgressTracker = request.extract_from_head().await?;
+            assert_eq!(
+                tracker.get("abc"),
+                Some(UploadProgress {
+                    received: 0,
+                    total: Some(11),
+                })
+            );
+
+            // keep the body alive, as the upload is removed once it's dropped
+            let mut body = std::mem::take(request.body_mut()).into_data_stream();
+            let chunk = body.next().await.unwrap()?;
+            assert_eq!(chunk, "Hello world");
+            assert_eq!(tracker.get("abc").unwrap().received(), 11);
+
+            Ok(Response::new(Body::empty()))
+        }));
+
+        let mut request = TestRequestBuilder::post("/upload/?X-Progress-ID=abc").build();
+        *request.body_mut() = Body::fixed("Hello world");
+        request.headers_mut().insert(
+            http::header::CONTENT_LENGTH,
+            http::HeaderValue::from_static("11"),
+        );
+
+        service.oneshot(request).await.unwrap();
+        assert_eq!(tracker.get("abc"), None);
+    }
+
+    #[cot::test]
+    async fn no_upload_id() {
+        let middleware = UploadProgressMiddleware::new();
+        let tracker = middleware.tracker.clone();
+        let service = middleware.layer(tower::service_fn(|mut request: Request| async move {
+            let tracker: UploadProgressTracker = request.extract_from_head().await?;
+            assert!(tracker.lock().is_empty());
+
+            Ok(Response::new(Body::empty()))
+        }));
+
+        service
+            .oneshot(TestRequestBuilder::post("/upload/").build())
+            .await
+            .unwrap();
+        assert!(tracker.lock().is_empty());
+    }
+
+    #[cot::test]
+    async fn removes_finished_uploads() {
+        let tracker = UploadProgressTracker::new();
+        let guard = tracker.start("abc".to_owned(), None);
+        let mut receiver = tracker.subscribe("abc").unwrap();
+
+        guard.add_received(5);
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(receiver.borrow_and_update().received(), 5);
+
+        drop(guard);
+        assert_eq!(tracker.get("abc"), None);
+        assert!(receiver.changed().await.is_err());
+    }
+
+    #[cot::test]
+    async fn restarted_upload_is_kept() {
+        let tracker = UploadProgressTracker::new();
+        let first = tracker.start("abc".to_owned(), None);
+        let _second = tracker.start("abc".to_owned(), Some(10));
+
+        drop(first);
+        assert_eq!(tracker.get("abc").unwrap().total(), Some(10));
+    }
+
+    #[test]
+    fn upload_id_from_header() {
+        let mut request = TestRequestBuilder::post("/upload/").build();
+        request
+            .headers_mut()
+            .insert(UPLOAD_ID_PARAM, http::HeaderValue::from_static("abc"));
+
+        assert_eq!(upload_id(&request), Some("abc".to_owned()));
+        assert_eq!(
+            upload_id(&TestRequestBuilder::post("/upload/?X-Progress-ID=").build()),
+            None
+        );
+    }
+}
@@ -0,0 +1,456 @@
+//! Navigation menus.
+//!
+//! Apps declare the items they want to show in the navigation menu of the
+//! project with [`App::nav_items`]. The [`Nav`] extractor collects the items
+//! of all the registered apps, resolves their URLs, and hides the ones the
+//! current user doesn't have the permission for, so that the templates don't
+//! need to hard-code the links to the other apps.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::html::Html;
+//! use cot::nav::{Nav, NavItem};
+//! use cot::router::{Route, Router};
+//! use cot::{App, Template};
+//!
+//! struct BlogApp;
+//! impl App for BlogApp {
+//!     fn name(&self) -> &str {
+//!         "blog"
+//!     }
+//!
+//!     fn router(&self) -> Router {
+//!         Router::with_urls([
+//!             Route::with_handler_and_name("/", index, "index"),
+//!             Route::with_handler_and_name("/new", index, "new_post"),
+//!         ])
+//!     }
+//!
+//!     fn nav_items(&self) -> Vec<NavItem> {
+//!         vec![
+//!             NavItem::new("Blog", "index"),
+//!             NavItem::new("New post", "new_post")
+//!                 .with_permission("blog.add_post")
+//!                 .with_icon("pencil"),
+//!         ]
+//!     }
+//! }
+//!
+//! #[derive(Template)]
+//! #[template(
+//!     source = r#"<nav>{% for item in nav.items() %}<a href="{{ item.url() }}">{{ item.label() }}</a>{% endfor %}</nav>"#,
+//!     ext = "html"
+//! )]
+//! struct IndexTemplate {
+//!     nav: Nav,
+//! }
+//!
+//! async fn index(nav: Nav) -> cot::Result<Html> {
+//!     Ok(Html::new(IndexTemplate { nav }.render()?))
+//! }
+//! ```
+
+use std::borrow::Cow;
+
+use crate::App;
+use crate::auth::{Auth, User};
+use crate::request::extractors::FromRequestHead;
+use crate::request::{RequestExt, RequestHead};
+use crate::router::Router;
+use crate::router::path::ReverseParamMap;
+
+/// An item of the navigation menu, declared by an app.
+///
+/// The item points to a named route of the app that declares it. It is
+/// returned by [`App::nav_items`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::nav::NavItem;
+///
+/// let item = NavItem::new("Reports", "report_list")
+///     .with_permission("reports.view")
+///     .with_icon("chart");
+/// assert_eq!(item.label(), "Reports");
+/// assert_eq!(item.route_name(), "report_list");
+/// assert_eq!(item.permission(), Some("reports.view"));
+/// assert_eq!(item.icon(), Some("chart"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavItem {
+    label: Cow<'static, str>,
+    route_name: Cow<'static, str>,
+    permission: Option<Cow<'static, str>>,
+    icon: Option<Cow<'static, str>>,
+}
+
+impl NavItem {
+    /// Creates a new navigation item with the given label, pointing to the
+    /// route with the given name.
+    ///
+    /// The route is looked up in the router of the app that declares the
+    /// item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::nav::NavItem;
+    ///
+    /// let item = NavItem::new("Reports", "report_list");
+    /// ```
+    #[must_use]
+    pub fn new(
+        label: impl Into<Cow<'static, str>>,
+        route_name: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            route_name: route_name.into(),
+            permission: None,
+            icon: None,
+        }
+    }
+
+    /// Sets the permission required to see the item.
+    ///
+    /// The item is only shown to the users for whom
+    /// [`User::has_permission`] returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::nav::NavItem;
+    ///
+    /// let item = NavItem::new("Reports", "report_list").with_permission("reports.view");
+    /// ```
+    #[must_use]
+    pub fn with_permission(mut self, permission: impl Into<Cow<'static, str>>) -> Self {
+        self.permission = Some(permission.into());
+        self
+    }
+
+    /// Sets the icon of the item.
+    ///
+    /// The meaning of the icon is up to the template that renders the
+    /// navigation; it is typically the name of an icon or a CSS class.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::nav::NavItem;
+    ///
+    /// let item = NavItem::new("Reports", "report_list").with_icon("chart");
+    /// ```
+    #[must_use]
+    pub fn with_icon(mut self, icon: impl Into<Cow<'static, str>>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Returns the label of the item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::nav::NavItem;
+    ///
+    /// let item = NavItem::new("Reports", "report_list");
+    /// assert_eq!(item.label(), "Reports");
+    /// ```
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the name of the route the item points to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::nav::NavItem;
+    ///
+    /// let item = NavItem::new("Reports", "report_list");
+    /// assert_eq!(item.route_name(), "report_list");
+    /// ```
+    #[must_use]
+    pub fn route_name(&self) -> &str {
+        &self.route_name
+    }
+
+    /// Returns the permission required to see the item, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::nav::NavItem;
+    ///
+    /// let item = NavItem::new("Reports", "report_list");
+    /// assert_eq!(item.permission(), None);
+    /// ```
+    #[must_use]
+    pub fn permission(&self) -> Option<&str> {
+        self.permission.as_deref()
+    }
+
+    /// Returns the icon of the item, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::nav::NavItem;
+    ///
+    /// let item = NavItem::new("Reports", "report_list");
+    /// assert_eq!(item.icon(), None);
+    /// ```
+    #[must_use]
+    pub fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+
+    fn is_visible_to(&self, user: Option<&dyn User>) -> bool {
+        match &self.permission {
+            None => true,
+            Some(permission) => user.is_some_and(|user| user.has_permission(permission)),
+        }
+    }
+}
+
+/// A navigation link, resolved from a [`NavItem`] for the current request.
+///
+/// # Examples
+///
+/// ```
+/// use cot::nav::Nav;
+///
+/// async fn handler(nav: Nav) {
+///     for link in nav.items() {
+///         println!("{}: {}", link.label(), link.url());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavLink {
+    app_name: String,
+    label: Cow<'static, str>,
+    url: String,
+    icon: Option<Cow<'static, str>>,
+    active: bool,
+}
+
+impl NavLink {
+    /// Returns the name of the app that declared the item.
+    #[must_use]
+    pub fn app_name(&self) -> &str {
+        &self.app_name
+    }
+
+    /// Returns the label of the link.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the URL the link points to.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the icon of the link, if any.
+    #[must_use]
+    pub fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+
+    /// Returns `true` if the link points to the current page.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+/// The navigation menu of the project, filtered for the current user.
+///
+/// This contains the [`NavItem`]s declared by all the registered apps, in the
+/// order the apps were registered, except for the ones that require a
+/// permission that the current user doesn't have. If the
+/// [`AuthMiddleware`](crate::middleware::AuthMiddleware) is not enabled, the
+/// items that require a permission are never shown.
+///
+/// # Examples
+///
+/// ```
+/// use cot::nav::Nav;
+/// use cot::test::TestRequestBuilder;
+/// use cot::request::extractors::FromRequestHead;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let request = TestRequestBuilder::get("/").build();
+/// let (head, _) = request.into_parts();
+///
+/// let nav = Nav::from_request_head(&head).await?;
+/// assert!(nav.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Nav {
+    items: Vec<NavLink>,
+}
+
+impl Nav {
+    /// Returns the links of the navigation menu.
+    #[must_use]
+    pub fn items(&self) -> &[NavLink] {
+        &self.items
+    }
+
+    /// Returns `true` if there are no links in the navigation menu.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Builds the navigation menu from the items declared by the given apps.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the route of any of the visible items doesn't
+    /// exist in the router.
+    fn build(
+        apps: &[Box<dyn App>],
+        router: &Router,
+        user: Option<&dyn User>,
+        current_path: &str,
+    ) -> crate::Result<Self> {
+        let mut items = Vec::new();
+        for app in apps {
+            for item in app.nav_items() {
+                if !item.is_visible_to(user) {
+                    continue;
+                }
+
+                let url =
+                    router.reverse(Some(app.name()), item.route_name(), &ReverseParamMap::new())?;
+                items.push(NavLink {
+                    app_name: app.name().to_owned(),
+                    active: url == current_path,
+                    label: item.label,
+                    url,
+                    icon: item.icon,
+                });
+            }
+        }
+
+        Ok(Self { items })
+    }
+}
+
+impl FromRequestHead for Nav {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        let auth = head.extensions.get::<Auth>();
+        let user = auth.map(Auth::user);
+
+        Self::build(
+            head.context().apps(),
+            head.router(),
+            user.as_deref().map(|user| -> &dyn User { user }),
+            head.uri.path(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cot_core::request::AppName;
+
+    use super::*;
+    use crate::auth::MockUser;
+    use crate::html::Html;
+    use crate::router::Route;
+
+    async fn handler() -> Html {
+        Html::new("")
+    }
+
+    struct TestApp;
+
+    impl App for TestApp {
+        fn name(&self) -> &'static str {
+            "test_app"
+        }
+
+        fn router(&self) -> Router {
+            Router::with_urls([
+                Route::with_handler_and_name("/", handler, "index"),
+                Route::with_handler_and_name("/secret/", handler, "secret"),
+            ])
+        }
+
+        fn nav_items(&self) -> Vec<NavItem> {
+            vec![
+                NavItem::new("Index", "index").with_icon("home"),
+                NavItem::new("Secret", "secret").with_permission("test.secret"),
+            ]
+        }
+    }
+
+    fn test_router() -> Router {
+        let mut app_router = TestApp.router();
+        app_router.set_app_name(AppName(TestApp.name().to_owned()));
+
+        Router::with_urls([Route::with_router("/app", app_router)])
+    }
+
+    #[test]
+    fn nav_without_user() {
+        let apps: Vec<Box<dyn App>> = vec![Box::new(TestApp)];
+
+        let nav = Nav::build(&apps, &test_router(), None, "/app/").unwrap();
+
+        assert_eq!(nav.items().len(), 1);
+        let link = &nav.items()[0];
+        assert_eq!(link.app_name(), "test_app");
+        assert_eq!(link.label(), "Index");
+        assert_eq!(link.url(), "/app/");
+        assert_eq!(link.icon(), Some("home"));
+        assert!(link.is_active());
+    }
+
+    #[test]
+    fn nav_filters_by_permission() {
+        let apps: Vec<Box<dyn App>> = vec![Box::new(TestApp)];
+        let mut user = MockUser::new();
+        user.expect_has_permission()
+            .returning(|permission| permission == "test.secret");
+
+        let nav = Nav::build(&apps, &test_router(), Some(&user), "/").unwrap();
+
+        let labels: Vec<_> = nav.items().iter().map(NavLink::label).collect();
+        assert_eq!(labels, vec!["Index", "Secret"]);
+        assert_eq!(nav.items()[1].url(), "/app/secret/");
+        assert!(!nav.items()[1].is_active());
+    }
+
+    #[test]
+    fn nav_unknown_route() {
+        struct BrokenApp;
+
+        impl App for BrokenApp {
+            fn name(&self) -> &'static str {
+                "broken_app"
+            }
+
+            fn nav_items(&self) -> Vec<NavItem> {
+                vec![NavItem::new("Missing", "missing")]
+            }
+        }
+
+        let apps: Vec<Box<dyn App>> = vec![Box::new(BrokenApp)];
+
+        assert!(Nav::build(&apps, &test_router(), None, "/").is_err());
+    }
+}
@@ -0,0 +1,523 @@
+//! In-process notifications and long polling.
+//!
+//! This module provides simple primitives for building real-time features,
+//! such as live notifications or chat, without using web sockets:
+//! * [`Notifier`] is a registry of [`Topic`]s, one for each message type. It
+//!   is shared by the whole project and available as
+//!   [`ProjectContext::notifier`](crate::ProjectContext::notifier) and as an
+//!   extractor.
+//! * [`Topic`] broadcasts the published messages to all its subscribers.
+//! * [`Subscription`] receives the messages published to a topic. It can be
+//!   used as an extractor, in which case it subscribes to the topic before the
+//!   request handler is called.
+//! * [`LongPoll`] waits for the next message of a subscription for a limited
+//!   time and turns it into a response.
+//!
+//! Note that the messages are only delivered within a single process; they
+//! are not shared between multiple instances of the server.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use cot::json::Json;
+//! use cot::notify::{LongPoll, Notifier, Subscription};
+//! use cot::response::Response;
+//! use serde::Serialize;
+//!
+//! #[derive(Debug, Clone, Serialize)]
+//! struct ChatMessage {
+//!     text: String,
+//! }
+//!
+//! async fn send(notifier: Notifier) -> cot::Result<Response> {
+//!     notifier.publish(ChatMessage {
+//!         text: "Hello!".to_owned(),
+//!     });
+//!     # unimplemented!()
+//! }
+//!
+//! async fn poll(subscription: Subscription<ChatMessage>) -> cot::Result<Response> {
+//!     LongPoll::new(subscription)
+//!         .timeout(Duration::from_secs(30))
+//!         .respond(Json)
+//!         .await
+//! }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use crate::Body;
+use crate::deadline::Deadline;
+use crate::request::extractors::FromRequestHead;
+use crate::request::{RequestExt, RequestHead};
+use crate::response::{IntoResponse, Response};
+
+/// The default number of messages a topic buffers for the subscribers that
+/// haven't received them yet.
+pub const DEFAULT_TOPIC_CAPACITY: usize = 64;
+
+/// The default time [`LongPoll`] waits for a message.
+pub const DEFAULT_LONG_POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A registry of [`Topic`]s, one for each message type.
+///
+/// Cloning the notifier is cheap and all the clones share the same topics.
+///
+/// # Examples
+///
+/// ```
+/// use cot::notify::Notifier;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct OrderShipped(u32);
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let notifier = Notifier::new();
+/// let mut subscription = notifier.subscribe::<OrderShipped>();
+///
+/// notifier.publish(OrderShipped(42));
+///
+/// assert_eq!(subscription.recv().await, Some(OrderShipped(42)));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    capacity: usize,
+    topics: Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>>,
+}
+
+impl Notifier {
+    /// Creates a new notifier whose topics buffer
+    /// [`DEFAULT_TOPIC_CAPACITY`] messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::notify::Notifier;
+    ///
+    /// let notifier = Notifier::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_TOPIC_CAPACITY)
+    }
+
+    /// Creates a new notifier whose topics buffer the given number of
+    /// messages.
+    ///
+    /// The subscribers that fall behind by more than `capacity` messages skip
+    /// the oldest ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::notify::Notifier;
+    ///
+    /// let notifier = Notifier::with_capacity(1024);
+    /// ```
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "topic capacity must be greater than zero");
+
+        Self {
+            capacity,
+            topics: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the topic for the messages of type `T`, creating it if it
+    /// doesn't exist yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock guarding the topics is poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::notify::Notifier;
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct OrderShipped(u32);
+    ///
+    /// let notifier = Notifier::new();
+    /// let topic = notifier.topic::<OrderShipped>();
+    /// assert_eq!(topic.subscriber_count(), 0);
+    /// ```
+    #[must_use]
+    pub fn topic<T: Clone + Send + Sync + 'static>(&self) -> Topic<T> {
+        let mut topics = self.topics.lock().expect("notifier lock poisoned");
+        let sender = topics
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(broadcast::Sender::<T>::new(self.capacity)))
+            .downcast_ref::<broadcast::Sender<T>>()
+            .expect("topic should have the sender of its message type");
+
+        Topic {
+            sender: sender.clone(),
+        }
+    }
+
+    /// Publishes a message to the topic for the messages of type `T`.
+    ///
+    /// This is a shorthand for `notifier.topic::<T>().publish(message)`.
+    /// Returns the number of subscribers the message was sent to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock guarding the topics is poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::notify::Notifier;
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct OrderShipped(u32);
+    ///
+    /// let notifier = Notifier::new();
+    /// assert_eq!(notifier.publish(OrderShipped(42)), 0);
+    /// ```
+    pub fn publish<T: Clone + Send + Sync + 'static>(&self, message: T) -> usize {
+        self.topic::<T>().publish(message)
+    }
+
+    /// Subscribes to the topic for the messages of type `T`.
+    ///
+    /// This is a shorthand for `notifier.topic::<T>().subscribe()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock guarding the topics is poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::notify::Notifier;
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct OrderShipped(u32);
+    ///
+    /// let notifier = Notifier::new();
+    /// let subscription = notifier.subscribe::<OrderShipped>();
+    /// ```
+    #[must_use]
+    pub fn subscribe<T: Clone + Send + Sync + 'static>(&self) -> Subscription<T> {
+        self.topic::<T>().subscribe()
+    }
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromRequestHead for Notifier {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        Ok(head.context().notifier().clone())
+    }
+}
+
+/// A topic that broadcasts messages of type `T` to all its subscribers.
+///
+/// A topic is obtained with [`Notifier::topic`].
+#[derive(Debug, Clone)]
+pub struct Topic<T> {
+    sender: broadcast::Sender<T>,
+}
+
+impl<T: Clone + Send + Sync + 'static> Topic<T> {
+    /// Publishes a message to all the current subscribers.
+    ///
+    /// Returns the number of subscribers the message was sent to. If there
+    /// are no subscribers, the message is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::notify::Notifier;
+    ///
+    /// let topic = Notifier::new().topic::<String>();
+    /// let _subscription = topic.subscribe();
+    ///
+    /// assert_eq!(topic.publish("hello".to_owned()), 1);
+    /// ```
+    pub fn publish(&self, message: T) -> usize {
+        self.sender.send(message).unwrap_or(0)
+    }
+
+    /// Creates a new subscription that receives the messages published after
+    /// this call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::notify::Notifier;
+    ///
+    /// let topic = Notifier::new().topic::<String>();
+    /// let subscription = topic.subscribe();
+    /// ```
+    #[must_use]
+    pub fn subscribe(&self) -> Subscription<T> {
+        Subscription {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// Returns the number of the current subscribers.
+    #[must_use]
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+/// A subscription to a [`Topic`].
+///
+/// When used as an extractor, the subscription is created before the request
+/// handler is called, so no message published while the handler runs is
+/// missed.
+///
+/// # Examples
+///
+/// ```
+/// use cot::notify::Subscription;
+/// use cot::response::Response;
+///
+/// #[derive(Debug, Clone)]
+/// struct OrderShipped(u32);
+///
+/// async fn wait_for_order(mut subscription: Subscription<OrderShipped>) -> cot::Result<Response> {
+///     while let Some(OrderShipped(id)) = subscription.recv().await {
+///         // ...
+///     }
+///     # unimplemented!()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Subscription<T> {
+    receiver: broadcast::Receiver<T>,
+}
+
+impl<T: Clone + Send + Sync + 'static> Subscription<T> {
+    /// Waits for the next message.
+    ///
+    /// If the subscription fell behind by more than the capacity of the
+    /// topic, the missed messages are skipped. Returns `None` if the topic
+    /// has been dropped.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(message) => return Some(message),
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> FromRequestHead for Subscription<T> {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        Ok(head.context().notifier().subscribe())
+    }
+}
+
+/// Waits for the next message of a [`Subscription`] for a limited time.
+///
+/// This can be used to implement long polling: the client sends a request
+/// that is held until there is a new message or the timeout passes, and then
+/// immediately sends another one. If the request has a
+/// [`Deadline`] that expires sooner than the timeout, the deadline is used
+/// instead.
+///
+/// # Examples
+///
+/// ```
+/// use cot::json::Json;
+/// use cot::notify::{LongPoll, Subscription};
+/// use cot::response::Response;
+///
+/// async fn poll(subscription: Subscription<String>) -> cot::Result<Response> {
+///     LongPoll::new(subscription).respond(Json).await
+/// }
+/// ```
+#[derive(Debug)]
+#[must_use = "long poll does nothing until awaited"]
+pub struct LongPoll<T> {
+    subscription: Subscription<T>,
+    timeout: Duration,
+}
+
+impl<T: Clone + Send + Sync + 'static> LongPoll<T> {
+    /// Creates a new long poll that waits for the next message of the given
+    /// subscription for [`DEFAULT_LONG_POLL_TIMEOUT`].
+    pub fn new(subscription: Subscription<T>) -> Self {
+        Self {
+            subscription,
+            timeout: DEFAULT_LONG_POLL_TIMEOUT,
+        }
+    }
+
+    /// Sets the maximum time to wait for a message.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Waits for the next message.
+    ///
+    /// Returns `None` if no message has been published before the timeout
+    /// passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::notify::{LongPoll, Notifier};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let notifier = Notifier::new();
+    /// let long_poll = LongPoll::new(notifier.subscribe::<String>()).timeout(Duration::from_millis(10));
+    ///
+    /// assert_eq!(long_poll.wait().await, None);
+    /// # }
+    /// ```
+    pub async fn wait(mut self) -> Option<T> {
+        Deadline::after(self.timeout)
+            .min(Deadline::current())
+            .run(self.subscription.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Waits for the next message and converts it to a response with the
+    /// given function.
+    ///
+    /// If no message has been published before the timeout passed, an empty
+    /// `204 No Content` response is returned, so that the client can simply
+    /// send the next request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response could not be created.
+    pub async fn respond<F, R>(self, f: F) -> crate::Result<Response>
+    where
+        F: FnOnce(T) -> R,
+        R: IntoResponse,
+    {
+        if let Some(message) = self.wait().await {
+            f(message).into_response()
+        } else {
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = http::StatusCode::NO_CONTENT;
+            Ok(response)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::request::RequestExt;
+    use crate::test::TestRequestBuilder;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Message(u32);
+
+    #[cot::test]
+    async fn publish_subscribe() {
+        let notifier = Notifier::new();
+        let mut first = notifier.subscribe::<Message>();
+        let mut second = notifier.subscribe::<Message>();
+
+        assert_eq!(notifier.publish(Message(1)), 2);
+
+        assert_eq!(first.recv().await, Some(Message(1)));
+        assert_eq!(second.recv().await, Some(Message(1)));
+    }
+
+    #[cot::test]
+    async fn topics_are_separate_per_type() {
+        let notifier = Notifier::new();
+        let _subscription = notifier.subscribe::<Message>();
+
+        assert_eq!(notifier.publish(String::from("unrelated")), 0);
+        assert_eq!(notifier.topic::<Message>().subscriber_count(), 1);
+    }
+
+    #[cot::test]
+    async fn lagged_subscription_skips_messages() {
+        let notifier = Notifier::with_capacity(2);
+        let mut subscription = notifier.subscribe::<Message>();
+
+        for i in 0..5 {
+            notifier.publish(Message(i));
+        }
+
+        assert_eq!(subscription.recv().await, Some(Message(3)));
+        assert_eq!(subscription.recv().await, Some(Message(4)));
+    }
+
+    #[cot::test]
+    async fn long_poll_receives_message() {
+        let notifier = Notifier::new();
+        let long_poll = LongPoll::new(notifier.subscribe::<Message>());
+
+        let publisher = notifier.clone();
+        tokio::spawn(async move { publisher.publish(Message(7)) });
+
+        assert_eq!(long_poll.wait().await, Some(Message(7)));
+    }
+
+    #[cot::test]
+    async fn long_poll_times_out() {
+        let notifier = Notifier::new();
+
+        let response = LongPoll::new(notifier.subscribe::<Message>())
+            .timeout(Duration::from_millis(1))
+            .respond(|Message(id)| id.to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[cot::test]
+    async fn long_poll_respects_deadline() {
+        let notifier = Notifier::new();
+        let long_poll = LongPoll::new(notifier.subscribe::<Message>());
+
+        let message = Deadline::after(Duration::from_millis(1))
+            .scope(long_poll.wait())
+            .await;
+
+        assert_eq!(message, None);
+    }
+
+    #[cot::test]
+    async fn subscription_from_request() {
+        let mut request = TestRequestBuilder::get("/").build();
+        let mut subscription: Subscription<Message> = request.extract_from_head().await.unwrap();
+
+        request.context().notifier().publish(Message(3));
+
+        assert_eq!(subscription.recv().await, Some(Message(3)));
+    }
+}
@@ -209,8 +209,10 @@ use serde_json::Value;
 use crate::auth::Auth;
 use crate::form::Form;
 use crate::json::Json;
-use crate::request::extractors::{FromRequest, FromRequestHead, Path, RequestForm, UrlQuery};
-use crate::request::{Request, RequestHead};
+use crate::request::extractors::{
+    FromRequest, FromRequestHead, Path, RequestForm, UrlQuery, ValidatedJson,
+};
+use crate::request::{ExpectedPathParams, Request, RequestHead};
 use crate::response::{Response, WithExtension};
 use crate::router::Urls;
 use crate::session::Session;
@@ -561,6 +563,10 @@ where
     fn handle(&self, request: Request) -> impl Future<Output = cot::Result<Response>> + Send {
         self.0.handle(request)
     }
+
+    fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+        self.0.expected_path_params()
+    }
 }
 
 impl<T: FromRequest> FromRequest for NoApi<T> {
@@ -573,6 +579,10 @@ impl<T: FromRequestHead> FromRequestHead for NoApi<T> {
     async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
         T::from_request_head(head).await.map(Self)
     }
+
+    fn expected_path_params() -> Option<ExpectedPathParams> {
+        T::expected_path_params()
+    }
 }
 
 impl<T> ApiOperationPart for NoApi<T> {}
@@ -841,6 +851,8 @@ impl ApiOperationPart for Urls {}
 impl ApiOperationPart for Method {}
 impl ApiOperationPart for Session {}
 impl ApiOperationPart for Auth {}
+impl ApiOperationPart for crate::auth::BearerToken {}
+impl ApiOperationPart for crate::auth::BearerAuth {}
 #[cfg(feature = "db")]
 impl ApiOperationPart for crate::db::Database {}
 
@@ -868,6 +880,16 @@ impl<D: JsonSchema> ApiOperationPart for Json<D> {
     }
 }
 
+impl<D: JsonSchema> ApiOperationPart for ValidatedJson<D> {
+    fn modify_api_operation(
+        operation: &mut Operation,
+        route_context: &RouteContext<'_>,
+        schema_generator: &mut SchemaGenerator,
+    ) {
+        Json::<D>::modify_api_operation(operation, route_context, schema_generator);
+    }
+}
+
 impl<D: JsonSchema> ApiOperationPart for Path<D> {
     #[track_caller]
     fn modify_api_operation(
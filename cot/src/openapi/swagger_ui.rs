@@ -1,6 +1,11 @@
 //! The OpenAPI (Swagger) UI router.
 //!
 //! This module provides a [`cot::App`] which serves the OpenAPI (Swagger) UI.
+//!
+//! By default, the Swagger UI assets are bundled with Cot and served through
+//! the static files pipeline, so the UI works without the internet access. See
+//! [`SwaggerUiConfig`](crate::config::SwaggerUiConfig) for how to load them
+//! from a CDN instead.
 
 use std::borrow::Cow;
 use std::sync::{Arc, OnceLock};
@@ -9,10 +14,11 @@ use bytes::Bytes;
 use swagger_ui_redist::SwaggerUiStaticFile;
 
 use crate::App;
+use crate::config::{SwaggerUiAssets, SwaggerUiConfig};
 use crate::html::Html;
 use crate::json::Json;
-use crate::request::extractors::StaticFiles;
-use crate::request::{Request, RequestExt};
+use crate::request::extractors::{FromRequestHead, StaticFiles};
+use crate::request::{Request, RequestExt, RequestHead};
 use crate::router::{Route, Router};
 use crate::static_files::StaticFile;
 
@@ -75,13 +81,29 @@ impl SwaggerUi {
 
     fn build_swagger_ui(
         openapi_path: Cow<'static, str>,
+        config: &SwaggerUiConfig,
         static_files: &StaticFiles,
     ) -> crate::Result<swagger_ui_redist::SwaggerUi> {
         let mut swagger_ui = swagger_ui_redist::SwaggerUi::new();
         swagger_ui.config().urls([openapi_path]);
-        for static_file in SwaggerUiStaticFile::all() {
-            let file_path = static_files.url_for(&Self::static_file_path(*static_file))?;
-            swagger_ui.override_file_path(*static_file, file_path.to_owned());
+        match config.assets {
+            SwaggerUiAssets::Bundled => {
+                for static_file in SwaggerUiStaticFile::all() {
+                    let file_path = static_files.url_for(&Self::static_file_path(*static_file))?;
+                    swagger_ui.override_file_path(*static_file, file_path.to_owned());
+                }
+                // the default validator is an online service
+                swagger_ui.config().validator_url("none");
+            }
+            SwaggerUiAssets::Cdn => {
+                let cdn_url = config.cdn_url.trim_end_matches('/');
+                for static_file in SwaggerUiStaticFile::all() {
+                    swagger_ui.override_file_path(
+                        *static_file,
+                        format!("{cdn_url}/{}", static_file.file_name()),
+                    );
+                }
+            }
         }
 
         Ok(swagger_ui)
@@ -101,12 +123,17 @@ impl App for SwaggerUi {
         let swagger_ui = Arc::clone(&self.inner);
         let openapi_path = Arc::clone(&self.openapi_path);
 
-        let swagger_handler = async move |static_files: StaticFiles| {
+        let swagger_handler = async move |head: RequestHead| {
+            let static_files = StaticFiles::from_request_head(&head).await?;
             let swagger_ui = swagger_ui.get_or_init(move || {
                 // TODO return an error when feature(once_cell_get_mut) is stable:
                 // https://github.com/rust-lang/rust/issues/121641
-                Self::build_swagger_ui((*openapi_path).clone(), &static_files)
-                    .expect("could not build swagger UI")
+                Self::build_swagger_ui(
+                    (*openapi_path).clone(),
+                    &head.context().config().swagger_ui,
+                    &static_files,
+                )
+                .expect("could not build swagger UI")
             });
             let swagger = swagger_ui.serve().map_err(cot::Error::internal)?;
             Ok::<_, crate::Error>(Html::new(swagger))
@@ -130,3 +157,49 @@ impl App for SwaggerUi {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DEFAULT_SWAGGER_UI_CDN_URL, ProjectConfig};
+    use crate::test::TestRequestBuilder;
+
+    async fn swagger_html(swagger_ui_config: SwaggerUiConfig) -> String {
+        let swagger_ui = SwaggerUi::new();
+        let mut builder = TestRequestBuilder::get("/");
+        builder.config(
+            ProjectConfig::builder()
+                .swagger_ui(swagger_ui_config)
+                .build(),
+        );
+        for static_file in SwaggerUiStaticFile::all() {
+            builder.static_file(SwaggerUi::static_file_path(*static_file), "");
+        }
+
+        let response = swagger_ui.router().handle(builder.build()).await.unwrap();
+        let body = response.into_body().into_bytes().await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[cot::test]
+    async fn bundled_assets() {
+        let html = swagger_html(SwaggerUiConfig::default()).await;
+
+        assert!(html.contains("/static/swagger/swagger-ui-bundle.js"));
+        assert!(html.contains(r#""validatorUrl": "none""#));
+        assert!(!html.contains(DEFAULT_SWAGGER_UI_CDN_URL));
+    }
+
+    #[cot::test]
+    async fn cdn_assets() {
+        let config = SwaggerUiConfig::builder()
+            .assets(SwaggerUiAssets::Cdn)
+            .cdn_url("https://cdn.example.com/swagger-ui/")
+            .build();
+
+        let html = swagger_html(config).await;
+
+        assert!(html.contains("https://cdn.example.com/swagger-ui/swagger-ui-bundle.js"));
+        assert!(!html.contains("/static/swagger/"));
+    }
+}
@@ -0,0 +1,611 @@
+//! Pagination of database query results.
+//!
+//! This module provides the [`Paginator`] type, which splits the results of a
+//! [`Query`] into pages, and the [`PageParams`] extractor, which reads the
+//! requested page from the URL query parameters.
+//!
+//! Two kinds of pagination are supported:
+//!
+//! * page-based pagination ([`Paginator::page`]), which allows jumping to any
+//!   page and knows the total number of pages, but needs to count all the
+//!   results and can skip or repeat objects when the data changes between
+//!   requests,
+//! * cursor-based pagination ([`Paginator::cursor_page`]), which only allows
+//!   moving to the next page, but stays efficient for large tables and is not
+//!   affected by the objects being added or removed.
+//!
+//! In both cases, the objects are sorted by their primary key.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::db::{Auto, Database, Model, model};
+//! use cot::html::Html;
+//! use cot::pagination::{PageParams, Paginator};
+//!
+//! #[model]
+//! struct Post {
+//!     #[model(primary_key)]
+//!     id: Auto<i32>,
+//!     title: String,
+//! }
+//!
+//! async fn list_posts(db: Database, params: PageParams) -> cot::Result<Html> {
+//!     let page = Paginator::new(Post::objects())
+//!         .with_per_page(params.per_page().unwrap_or(10))
+//!         .page(&db, params.page())
+//!         .await?;
+//!
+//!     let titles: Vec<_> = page.items().iter().map(|post| post.title.as_str()).collect();
+//!     Ok(Html::new(format!(
+//!         "{} (page {} of {})",
+//!         titles.join(", "),
+//!         page.number(),
+//!         page.page_count()
+//!     )))
+//! }
+//! ```
+
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use cot_core::error::impl_into_cot_error;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::db::query::{Expr, Query};
+use crate::db::{Database, Model};
+use crate::request::RequestHead;
+use crate::request::extractors::{FromRequestHead, UrlQuery};
+
+/// The number of objects on a page if not specified otherwise.
+pub const DEFAULT_PER_PAGE: u64 = 20;
+
+/// The maximum number of objects on a page that can be requested with the
+/// `per_page` URL query parameter.
+pub const MAX_PER_PAGE: u64 = 100;
+
+/// The requested page, read from the `page`, `per_page`, and `cursor` URL
+/// query parameters.
+///
+/// All the parameters are optional. The page number defaults to 1, and the
+/// number of objects per page is limited to [`MAX_PER_PAGE`].
+///
+/// # Errors
+///
+/// Extracting this type results in a 400 Bad Request error if the query
+/// parameters can't be parsed, e.g. when the page is not a number.
+///
+/// # Examples
+///
+/// ```
+/// use cot::pagination::PageParams;
+/// use cot::request::extractors::FromRequestHead;
+/// use cot::test::TestRequestBuilder;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let request = TestRequestBuilder::get("/posts/?page=3&per_page=10").build();
+/// let (head, _body) = request.into_parts();
+///
+/// let params = PageParams::from_request_head(&head).await?;
+/// assert_eq!(params.page(), 3);
+/// assert_eq!(params.per_page(), Some(10));
+/// assert_eq!(params.cursor(), None);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PageParams {
+    page: Option<u64>,
+    per_page: Option<u64>,
+    cursor: Option<String>,
+}
+
+impl PageParams {
+    /// Returns the requested page number, starting from 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::pagination::PageParams;
+    ///
+    /// assert_eq!(PageParams::default().page(), 1);
+    /// ```
+    #[must_use]
+    pub fn page(&self) -> u64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// Returns the requested number of objects per page, if any.
+    ///
+    /// The value is clamped between 1 and [`MAX_PER_PAGE`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::pagination::PageParams;
+    ///
+    /// assert_eq!(PageParams::default().per_page(), None);
+    /// ```
+    #[must_use]
+    pub fn per_page(&self) -> Option<u64> {
+        self.per_page
+            .map(|per_page| per_page.clamp(1, MAX_PER_PAGE))
+    }
+
+    /// Returns the requested cursor, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::pagination::PageParams;
+    ///
+    /// assert_eq!(PageParams::default().cursor(), None);
+    /// ```
+    #[must_use]
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+}
+
+impl FromRequestHead for PageParams {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        let UrlQuery(params) = UrlQuery::<Self>::from_request_head(head).await?;
+        Ok(params)
+    }
+}
+
+/// Splits the results of a database query into pages.
+///
+/// See the [module documentation](self) for more details.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, Database, Model, model};
+/// use cot::pagination::Paginator;
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// # async fn example(db: Database) -> cot::Result<()> {
+/// let paginator = Paginator::new(Post::objects()).with_per_page(10);
+///
+/// let page = paginator.page(&db, 1).await?;
+/// let next_page = page.next_page_number();
+///
+/// let page = paginator.cursor_page(&db, None).await?;
+/// let next_cursor = page.next_cursor();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Paginator<T> {
+    query: Query<T>,
+    per_page: u64,
+}
+
+impl<T: Model> Paginator<T> {
+    /// Creates a paginator for the given query, with [`DEFAULT_PER_PAGE`]
+    /// objects per page.
+    ///
+    /// The limit and offset set on the query are overridden by the paginator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, Model, model};
+    /// use cot::pagination::{DEFAULT_PER_PAGE, Paginator};
+    ///
+    /// #[model]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    /// }
+    ///
+    /// let paginator = Paginator::new(Post::objects());
+    /// assert_eq!(paginator.per_page(), DEFAULT_PER_PAGE);
+    /// ```
+    #[must_use]
+    pub fn new(query: Query<T>) -> Self {
+        Self {
+            query,
+            per_page: DEFAULT_PER_PAGE,
+        }
+    }
+
+    /// Sets the number of objects per page.
+    ///
+    /// Values lower than 1 are treated as 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, Model, model};
+    /// use cot::pagination::Paginator;
+    ///
+    /// #[model]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    /// }
+    ///
+    /// let paginator = Paginator::new(Post::objects()).with_per_page(50);
+    /// assert_eq!(paginator.per_page(), 50);
+    /// ```
+    #[must_use]
+    pub fn with_per_page(mut self, per_page: u64) -> Self {
+        self.per_page = per_page.max(1);
+        self
+    }
+
+    /// Returns the number of objects per page.
+    #[must_use]
+    pub fn per_page(&self) -> u64 {
+        self.per_page
+    }
+
+    /// Returns the page with the given number, starting from 1.
+    ///
+    /// The first page is always returned, even if there are no objects.
+    ///
+    /// # Errors
+    ///
+    /// Returns a 404 Not Found error if the page number is out of range.
+    ///
+    /// Returns an error if the database query fails.
+    pub async fn page(&self, db: &Database, number: u64) -> crate::Result<Page<T>> {
+        let total_count = self.query.count(db).await?;
+        let page_count = page_count(total_count, self.per_page);
+        if number == 0 || number > page_count {
+            return Err(InvalidPage(number).into());
+        }
+
+        let items = self
+            .query
+            .clone()
+            .order_by_primary_key()
+            .limit(self.per_page)
+            .offset((number - 1) * self.per_page)
+            .all(db)
+            .await?;
+
+        Ok(Page {
+            items,
+            number,
+            limit: self.per_page,
+            total_count,
+        })
+    }
+
+    /// Returns the page starting after the object with the given cursor, or the
+    /// first page if the cursor is `None`.
+    ///
+    /// The cursor is the primary key of the last object on the previous page,
+    /// as returned by [`CursorPage::next_cursor`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a 400 Bad Request error if the cursor is not a valid primary
+    /// key.
+    ///
+    /// Returns an error if the database query fails.
+    pub async fn cursor_page(
+        &self,
+        db: &Database,
+        cursor: Option<&str>,
+    ) -> crate::Result<CursorPage<T>>
+    where
+        T::PrimaryKey: FromStr + Display,
+    {
+        let mut query = self.query.clone();
+        if let Some(cursor) = cursor {
+            let primary_key = cursor
+                .parse::<T::PrimaryKey>()
+                .map_err(|_| InvalidCursor(cursor.to_owned()))?;
+            query.and_filter(Expr::gt(
+                Expr::field(T::PRIMARY_KEY_NAME),
+                Expr::value(primary_key),
+            ));
+        }
+
+        let mut items = query
+            .order_by_primary_key()
+            .limit(self.per_page + 1)
+            .all(db)
+            .await?;
+        let has_next = items.len() as u64 > self.per_page;
+        items.truncate(usize::try_from(self.per_page).unwrap_or(usize::MAX));
+        let next_cursor = if has_next {
+            items.last().map(|item| item.primary_key().to_string())
+        } else {
+            None
+        };
+
+        Ok(CursorPage {
+            items,
+            per_page: self.per_page,
+            next_cursor,
+        })
+    }
+}
+
+fn page_count(total_count: u64, per_page: u64) -> u64 {
+    total_count.div_ceil(per_page).max(1)
+}
+
+/// A single page of objects returned by [`Paginator::page`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::Template;
+/// use cot::pagination::Page;
+///
+/// #[derive(Template)]
+/// #[template(
+///     source = r#"
+/// {%- for post in page.items() %}<p>{{ post }}</p>{% endfor -%}
+/// {%- if let Some(previous) = page.previous_page_number() %}<a href="?page={{ previous }}">Previous</a>{% endif -%}
+/// Page {{ page.number() }} of {{ page.page_count() }}
+/// {%- if let Some(next) = page.next_page_number() %}<a href="?page={{ next }}">Next</a>{% endif -%}
+/// "#,
+///     ext = "html"
+/// )]
+/// struct PostsTemplate {
+///     page: Page<String>,
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    items: Vec<T>,
+    number: u64,
+    limit: u64,
+    total_count: u64,
+}
+
+impl<T> Page<T> {
+    /// Returns the objects on this page.
+    #[must_use]
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns the objects on this page, consuming the page.
+    #[must_use]
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    /// Returns the number of this page, starting from 1.
+    #[must_use]
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the maximum number of objects on a page.
+    #[must_use]
+    pub fn per_page(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the total number of objects on all pages.
+    #[must_use]
+    pub fn total_count(&self) -> u64 {
+        self.total_count
+    }
+
+    /// Returns the total number of pages.
+    ///
+    /// This is always at least 1, even if there are no objects.
+    #[must_use]
+    pub fn page_count(&self) -> u64 {
+        page_count(self.total_count, self.limit)
+    }
+
+    /// Returns the numbers of all pages, which is useful for displaying
+    /// links to each of them.
+    #[must_use]
+    pub fn page_range(&self) -> RangeInclusive<u64> {
+        1..=self.page_count()
+    }
+
+    /// Returns whether there is a page after this one.
+    #[must_use]
+    pub fn has_next(&self) -> bool {
+        self.number < self.page_count()
+    }
+
+    /// Returns whether there is a page before this one.
+    #[must_use]
+    pub fn has_previous(&self) -> bool {
+        self.number > 1
+    }
+
+    /// Returns the number of the next page, if any.
+    #[must_use]
+    pub fn next_page_number(&self) -> Option<u64> {
+        self.has_next().then(|| self.number + 1)
+    }
+
+    /// Returns the number of the previous page, if any.
+    #[must_use]
+    pub fn previous_page_number(&self) -> Option<u64> {
+        self.has_previous().then(|| self.number - 1)
+    }
+
+    /// Returns the 1-based index of the first object on this page among all
+    /// the objects, or 0 if there are no objects.
+    #[must_use]
+    pub fn start_index(&self) -> u64 {
+        if self.items.is_empty() {
+            0
+        } else {
+            (self.number - 1) * self.limit + 1
+        }
+    }
+
+    /// Returns the 1-based index of the last object on this page among all
+    /// the objects, or 0 if there are no objects.
+    #[must_use]
+    pub fn end_index(&self) -> u64 {
+        (self.number - 1) * self.limit + self.items.len() as u64
+    }
+}
+
+/// A single page of objects returned by [`Paginator::cursor_page`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorPage<T> {
+    items: Vec<T>,
+    per_page: u64,
+    next_cursor: Option<String>,
+}
+
+impl<T> CursorPage<T> {
+    /// Returns the objects on this page.
+    #[must_use]
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns the objects on this page, consuming the page.
+    #[must_use]
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    /// Returns the maximum number of objects on a page.
+    #[must_use]
+    pub fn per_page(&self) -> u64 {
+        self.per_page
+    }
+
+    /// Returns whether there is a page after this one.
+    #[must_use]
+    pub fn has_next(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+
+    /// Returns the cursor that should be passed to
+    /// [`Paginator::cursor_page`] to get the next page, if there is one.
+    #[must_use]
+    pub fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("page {0} not found")]
+struct InvalidPage(u64);
+impl_into_cot_error!(InvalidPage, NOT_FOUND);
+
+#[derive(Debug, Error)]
+#[error("invalid pagination cursor: `{0}`")]
+struct InvalidCursor(String);
+impl_into_cot_error!(InvalidCursor, BAD_REQUEST);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    fn make_page(items: Vec<u32>, number: u64, total_count: u64) -> Page<u32> {
+        Page {
+            items,
+            number,
+            limit: 2,
+            total_count,
+        }
+    }
+
+    #[test]
+    fn page_first() {
+        let page = make_page(vec![1, 2], 1, 5);
+
+        assert_eq!(page.page_count(), 3);
+        assert_eq!(page.page_range(), 1..=3);
+        assert!(page.has_next());
+        assert!(!page.has_previous());
+        assert_eq!(page.next_page_number(), Some(2));
+        assert_eq!(page.previous_page_number(), None);
+        assert_eq!(page.start_index(), 1);
+        assert_eq!(page.end_index(), 2);
+    }
+
+    #[test]
+    fn page_last() {
+        let page = make_page(vec![5], 3, 5);
+
+        assert!(!page.has_next());
+        assert!(page.has_previous());
+        assert_eq!(page.next_page_number(), None);
+        assert_eq!(page.previous_page_number(), Some(2));
+        assert_eq!(page.start_index(), 5);
+        assert_eq!(page.end_index(), 5);
+        assert_eq!(page.into_items(), vec![5]);
+    }
+
+    #[test]
+    fn page_empty() {
+        let page = make_page(vec![], 1, 0);
+
+        assert_eq!(page.page_count(), 1);
+        assert!(!page.has_next());
+        assert!(!page.has_previous());
+        assert_eq!(page.start_index(), 0);
+        assert_eq!(page.end_index(), 0);
+    }
+
+    #[test]
+    fn cursor_page() {
+        let page = CursorPage {
+            items: vec![1, 2],
+            per_page: 2,
+            next_cursor: Some("2".to_owned()),
+        };
+
+        assert!(page.has_next());
+        assert_eq!(page.next_cursor(), Some("2"));
+        assert_eq!(page.items(), [1, 2]);
+    }
+
+    #[cot::test]
+    async fn page_params() {
+        let request = TestRequestBuilder::get("/?page=0&per_page=1000&cursor=abc").build();
+        let (head, _body) = request.into_parts();
+
+        let params = PageParams::from_request_head(&head).await.unwrap();
+
+        assert_eq!(params.page(), 1);
+        assert_eq!(params.per_page(), Some(MAX_PER_PAGE));
+        assert_eq!(params.cursor(), Some("abc"));
+    }
+
+    #[cot::test]
+    async fn page_params_invalid() {
+        let request = TestRequestBuilder::get("/?page=abc").build();
+        let (head, _body) = request.into_parts();
+
+        let error = PageParams::from_request_head(&head).await.unwrap_err();
+
+        assert_eq!(error.status_code(), crate::StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            crate::Error::from(InvalidPage(3)).status_code(),
+            crate::StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            crate::Error::from(InvalidCursor("x".to_owned())).status_code(),
+            crate::StatusCode::BAD_REQUEST
+        );
+    }
+}
@@ -11,6 +11,9 @@ pub use aide::openapi::{Operation, RequestBody, Response as OpenApiResponse, Sta
 pub use async_trait::async_trait;
 pub use bytes::Bytes;
 pub use cot_macros::ModelHelper;
+#[cfg(feature = "fake")]
+pub use fake;
+pub use serde;
 pub use tokio;
 
 pub mod askama {
@@ -22,6 +25,25 @@ pub mod askama {
 #[cfg(feature = "db")]
 pub use crate::utils::graph::apply_permutation;
 
+/// Returns the HTML ID of a form field in a form context with the given prefix.
+#[must_use]
+pub fn prefixed_form_field_id(prefix: Option<&str>, field_id: &str) -> String {
+    match prefix {
+        Some(prefix) => format!("{prefix}-{field_id}"),
+        None => field_id.to_owned(),
+    }
+}
+
+/// Removes the prefix of a form context from the HTML ID of a form field, if
+/// it's there.
+#[must_use]
+pub fn strip_form_prefix<'a>(prefix: Option<&str>, field_id: &'a str) -> &'a str {
+    prefix
+        .and_then(|prefix| field_id.strip_prefix(prefix))
+        .and_then(|field_id| field_id.strip_prefix('-'))
+        .unwrap_or(field_id)
+}
+
 /// The version of the crate.
 ///
 /// This is used in the CLI to specify the version of the crate to use in the
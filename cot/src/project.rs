@@ -36,11 +36,13 @@ use futures_util::FutureExt;
 use thiserror::Error;
 use tower::util::BoxCloneSyncService;
 use tower::{Layer, Service};
-use tracing::{error, info, trace};
+use tracing::{Instrument, debug, error, field, info, info_span, trace};
 
 use crate::admin::AdminModelManager;
 #[cfg(feature = "db")]
 use crate::auth::db::DatabaseUserBackend;
+#[cfg(feature = "db")]
+use crate::auth::db::token::ApiTokenBackend;
 use crate::auth::{AuthBackend, NoAuthBackend};
 #[cfg(feature = "cache")]
 use crate::cache::Cache;
@@ -53,21 +55,41 @@ use crate::config::{AuthBackendConfig, ProjectConfig};
 #[cfg(feature = "db")]
 use crate::db::Database;
 #[cfg(feature = "db")]
-use crate::db::migrations::{MigrationEngine, SyncDynMigration};
+use crate::db::migrations::{
+    MigrationEngine, MigrationProgress, MigrationStatus, ModelSchema, SyncDynMigration,
+};
 #[cfg(feature = "email")]
 use crate::email::Email;
 use crate::error::UncaughtPanic;
 use crate::error::handler::{DynErrorPageHandler, RequestOuterError};
+use crate::error::stream::{StreamErrorGuard, StreamErrorHook};
 use crate::error_page::Diagnostics;
+#[cfg(feature = "gdpr")]
+use crate::gdpr::PersonalDataModel;
 use crate::html::Html;
-use crate::middleware::{IntoCotError, IntoCotErrorLayer, IntoCotResponse, IntoCotResponseLayer};
-use crate::request::{Request, RequestExt, RequestHead};
+#[cfg(feature = "http-client")]
+use crate::http_client::HttpClient;
+use crate::middleware::ordering::{MiddlewareInfo, MiddlewareStack};
+use crate::middleware::{
+    IntoCotError, IntoCotErrorLayer, IntoCotResponse, IntoCotResponseLayer, MiddlewareMetadata,
+};
+use crate::nav::NavItem;
+use crate::notify::Notifier;
+use crate::request::{ConnectionScheme, Request, RequestExt, RequestHead};
 use crate::response::{IntoResponse, Response};
-use crate::router::{Route, Router, RouterService};
+use crate::router::{Route, Router, RouterService, Urls};
+use crate::shutdown::ShutdownCoordinator;
 use crate::static_files::StaticFile;
+#[cfg(feature = "storage")]
+use crate::storage::FileStorage;
+#[cfg(feature = "tasks")]
+use crate::tasks::{TaskRegistry, TaskWorker, Tasks};
 use crate::utils::accept_header_parser::AcceptHeaderParser;
 use crate::{Body, Error, cli, error_page};
 
+#[cfg(feature = "tls")]
+mod tls;
+
 /// A building block for a Cot project.
 ///
 /// A Cot app is a part (ideally, reusable) of a Cot project that is
@@ -151,6 +173,44 @@ pub trait App: Send + Sync {
         vec![]
     }
 
+    /// Returns the schemas of the models defined by the app. By default, it
+    /// returns an empty list.
+    ///
+    /// At startup, the models are compared with the database schema created
+    /// by the migrations, and a warning is logged for each difference (which
+    /// usually means that new migrations need to be generated).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::App;
+    /// use cot::db::migrations::ModelSchema;
+    /// use cot::db::{Auto, model};
+    ///
+    /// #[model]
+    /// struct Todo {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     title: String,
+    /// }
+    ///
+    /// struct TodoApp;
+    ///
+    /// impl App for TodoApp {
+    ///     fn name(&self) -> &str {
+    ///         "todo"
+    ///     }
+    ///
+    ///     fn models(&self) -> Vec<ModelSchema> {
+    ///         vec![ModelSchema::of::<Todo>()]
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "db")]
+    fn models(&self) -> Vec<ModelSchema> {
+        vec![]
+    }
+
     /// Returns the admin model managers for the app. By default, it returns an
     /// empty list.
     fn admin_model_managers(&self) -> Vec<Box<dyn AdminModelManager>> {
@@ -162,6 +222,43 @@ pub trait App: Send + Sync {
     fn static_files(&self) -> Vec<StaticFile> {
         vec![]
     }
+
+    /// Returns the items the app adds to the navigation menu of the project.
+    /// By default, it returns an empty list.
+    ///
+    /// The items point to the named routes of this app. See the
+    /// [`nav`](crate::nav) module for how the menu is rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::App;
+    /// use cot::nav::NavItem;
+    ///
+    /// struct MyApp;
+    /// impl App for MyApp {
+    ///     fn name(&self) -> &str {
+    ///         "my_app"
+    ///     }
+    ///
+    ///     fn nav_items(&self) -> Vec<NavItem> {
+    ///         vec![NavItem::new("Reports", "report_list").with_permission("reports.view")]
+    ///     }
+    /// }
+    /// ```
+    fn nav_items(&self) -> Vec<NavItem> {
+        vec![]
+    }
+
+    /// Returns the models of the app that store the personal data of the
+    /// users. By default, it returns an empty list.
+    ///
+    /// These are used to export and anonymize the data of a user; see the
+    /// [`gdpr`](crate::gdpr) module for more details.
+    #[cfg(feature = "gdpr")]
+    fn personal_data_models(&self) -> Vec<PersonalDataModel> {
+        vec![]
+    }
 }
 
 /// The main trait for a Cot project.
@@ -223,7 +320,10 @@ pub trait Project {
     /// directory in the current working directory (for instance, if
     /// `config_name` is `test`, then `config/test.toml` in the current working
     /// directory is read). If the file does not exist, it tries to read the
-    /// file directly at `config_name` path.
+    /// file directly at `config_name` path. The environment variables
+    /// referenced in the file are interpolated and the `COT__*` environment
+    /// variables override the config keys, as described in
+    /// [`ProjectConfig::from_toml_with_env`].
     ///
     /// You might want to override this method if you want to read the
     /// configuration from a different source, or if you want to hardcode
@@ -261,7 +361,7 @@ pub trait Project {
     /// ```
     /// use async_trait::async_trait;
     /// use clap::{ArgMatches, Command};
-    /// use cot::cli::{Cli, CliTask};
+    /// use cot::cli::{Cli, CliTask, TaskOutcome};
     /// use cot::project::WithConfig;
     /// use cot::{Bootstrapper, Project};
     ///
@@ -277,10 +377,8 @@ pub trait Project {
     ///         &mut self,
     ///         _matches: &ArgMatches,
     ///         _bootstrapper: Bootstrapper<WithConfig>,
-    ///     ) -> cot::Result<()> {
-    ///         println!("Frobnicating...");
-    ///
-    ///         Ok(())
+    ///     ) -> cot::Result<TaskOutcome> {
+    ///         Ok(TaskOutcome::new().with_message("Frobnicated"))
     ///     }
     /// }
     ///
@@ -294,6 +392,43 @@ pub trait Project {
     #[expect(unused_variables)]
     fn register_tasks(&self, cli: &mut Cli) {}
 
+    /// Registers the background tasks that can be run by the project.
+    ///
+    /// The tasks are run by a [`TaskWorker`](crate::tasks::TaskWorker) that
+    /// is started along with the server. See the [`tasks`](crate::tasks)
+    /// module for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::{Task, TaskRegistry};
+    /// use cot::{Project, ProjectContext};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize)]
+    /// struct GenerateReport {
+    ///     report_id: i64,
+    /// }
+    ///
+    /// impl Task for GenerateReport {
+    ///     const NAME: &'static str = "generate_report";
+    ///
+    ///     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn register_background_tasks(&self, tasks: &mut TaskRegistry) {
+    ///         tasks.register::<GenerateReport>();
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "tasks")]
+    #[expect(unused_variables)]
+    fn register_background_tasks(&self, tasks: &mut TaskRegistry) {}
+
     /// Registers the apps for the project.
     ///
     /// # Examples
@@ -346,16 +481,33 @@ pub trait Project {
         match &context.config().auth_backend {
             AuthBackendConfig::None => Arc::new(NoAuthBackend) as Arc<dyn AuthBackend>,
             #[cfg(feature = "db")]
-            AuthBackendConfig::Database => Arc::new(DatabaseUserBackend::new(
-                context
-                    .try_database()
-                    .expect(
-                        "Database missing when constructing database auth backend. \
-                        Make sure the database config is set up correctly or disable \
-                        authentication in the config.",
-                    )
-                    .clone(),
-            )) as Arc<dyn AuthBackend>,
+            AuthBackendConfig::Database => Arc::new(
+                DatabaseUserBackend::new(
+                    context
+                        .try_database()
+                        .expect(
+                            "Database missing when constructing database auth backend. \
+                            Make sure the database config is set up correctly or disable \
+                            authentication in the config.",
+                        )
+                        .clone(),
+                )
+                .password_hasher(context.config().password_hasher),
+            ) as Arc<dyn AuthBackend>,
+            #[cfg(feature = "db")]
+            AuthBackendConfig::ApiToken => Arc::new(
+                ApiTokenBackend::new(
+                    context
+                        .try_database()
+                        .expect(
+                            "Database missing when constructing API token auth backend. \
+                            Make sure the database config is set up correctly or disable \
+                            authentication in the config.",
+                        )
+                        .clone(),
+                )
+                .password_hasher(context.config().password_hasher),
+            ) as Arc<dyn AuthBackend>,
         }
     }
 
@@ -432,6 +584,35 @@ pub trait Project {
     fn error_handler(&self) -> DynErrorPageHandler {
         DynErrorPageHandler::new(default_error_handler)
     }
+
+    /// Returns the hook that is called when an error occurs while streaming
+    /// a response body.
+    ///
+    /// Such errors are always logged; the hook can be used to additionally
+    /// report them, for instance to an error tracking service. See the
+    /// [`stream`](crate::error::stream) module for more details. By default,
+    /// no hook is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::Project;
+    /// use cot::error::stream::{StreamError, StreamErrorHook};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn stream_error_hook(&self) -> Option<Arc<dyn StreamErrorHook>> {
+    ///         Some(Arc::new(|error: &StreamError| {
+    ///             eprintln!("Streaming {} failed: {}", error.uri(), error.error());
+    ///         }))
+    ///     }
+    /// }
+    /// ```
+    fn stream_error_hook(&self) -> Option<Arc<dyn StreamErrorHook>> {
+        None
+    }
 }
 
 /// An alias for `ProjectContext` in appropriate phase for use with the
@@ -475,6 +656,7 @@ pub type MiddlewareContext = ProjectContext<WithCache>;
 pub struct RootHandlerBuilder<S = RouterService, SE = DynErrorPageHandler> {
     handler: S,
     error_handler: SE,
+    middlewares: MiddlewareStack,
 }
 
 impl<RootService, ErrorHandlerService> RootHandlerBuilder<RootService, ErrorHandlerService>
@@ -489,7 +671,19 @@ where
     /// Adds middleware to the project.
     ///
     /// This method is used to add middleware to the project. The middleware
-    /// will be applied to all routes in the project.
+    /// will be applied to all routes in the project. To apply middleware only
+    /// to some of the routes, use [`Route::with_middleware`](crate::router::Route::with_middleware)
+    /// or [`Router::with_middleware`](crate::router::Router::with_middleware)
+    /// instead.
+    ///
+    /// Middlewares added later wrap the ones added earlier, so they process
+    /// the requests first. Some of the middlewares provided by Cot depend on
+    /// others; for instance, [`AuthMiddleware`](crate::middleware::AuthMiddleware)
+    /// must be added before [`SessionMiddleware`](crate::middleware::SessionMiddleware).
+    /// The order is validated when the project is booted. Other middlewares
+    /// can declare their requirements by implementing
+    /// [`MiddlewareMetadata`] and being added with
+    /// [`Self::middleware_with_metadata`].
     ///
     /// # Examples
     ///
@@ -519,9 +713,86 @@ where
         WrappedMiddleware<M, RootService>,
         WrappedMiddleware<M, ErrorHandlerService>,
     >
+    where
+        M: Layer<RootService> + Layer<ErrorHandlerService> + 'static,
+    {
+        let info = MiddlewareInfo::lookup(&middleware);
+        self.add_middleware(middleware, info)
+    }
+
+    /// Adds middleware to both the main request handler and the error
+    /// handler, validating its order according to its [`MiddlewareMetadata`].
+    ///
+    /// This works like [`Self::middleware`], but makes it possible for
+    /// middlewares not provided by Cot to declare their requirements regarding
+    /// other middlewares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Project;
+    /// use cot::middleware::{MiddlewareMetadata, MiddlewareRequirement, SessionMiddleware};
+    /// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+    ///
+    /// #[derive(Clone)]
+    /// struct VisitCounterMiddleware;
+    ///
+    /// impl<S> tower::Layer<S> for VisitCounterMiddleware {
+    ///     type Service = S;
+    ///
+    ///     fn layer(&self, inner: S) -> S {
+    ///         inner
+    ///     }
+    /// }
+    ///
+    /// impl MiddlewareMetadata for VisitCounterMiddleware {
+    ///     const NAME: &'static str = "VisitCounterMiddleware";
+    ///     const REQUIREMENTS: &'static [MiddlewareRequirement] =
+    ///         &[MiddlewareRequirement::Requires(SessionMiddleware::NAME)];
+    /// }
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn middlewares(
+    ///         &self,
+    ///         handler: RootHandlerBuilder,
+    ///         context: &MiddlewareContext,
+    ///     ) -> RootHandler {
+    ///         handler
+    ///             .middleware_with_metadata(VisitCounterMiddleware)
+    ///             .middleware(SessionMiddleware::from_context(context))
+    ///             .build()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn middleware_with_metadata<M>(
+        self,
+        middleware: M,
+    ) -> RootHandlerBuilder<
+        WrappedMiddleware<M, RootService>,
+        WrappedMiddleware<M, ErrorHandlerService>,
+    >
+    where
+        M: Layer<RootService> + Layer<ErrorHandlerService> + MiddlewareMetadata + 'static,
+    {
+        self.add_middleware(middleware, Some(MiddlewareInfo::of::<M>()))
+    }
+
+    fn add_middleware<M>(
+        self,
+        middleware: M,
+        info: Option<MiddlewareInfo>,
+    ) -> RootHandlerBuilder<
+        WrappedMiddleware<M, RootService>,
+        WrappedMiddleware<M, ErrorHandlerService>,
+    >
     where
         M: Layer<RootService> + Layer<ErrorHandlerService>,
     {
+        let mut middlewares = self.middlewares;
+        middlewares.push_handler(info);
+        middlewares.push_error_handler(info);
         let layer = (
             IntoCotErrorLayer::new(),
             IntoCotResponseLayer::new(),
@@ -531,6 +802,7 @@ where
         RootHandlerBuilder {
             handler: layer.layer(self.handler),
             error_handler: layer.layer(self.error_handler),
+            middlewares,
         }
     }
 
@@ -566,9 +838,39 @@ where
         self,
         middleware: M,
     ) -> RootHandlerBuilder<WrappedMiddleware<M, RootService>, ErrorHandlerService>
+    where
+        M: Layer<RootService> + 'static,
+    {
+        let info = MiddlewareInfo::lookup(&middleware);
+        self.add_main_handler_middleware(middleware, info)
+    }
+
+    /// Adds middleware only to the main request handler, validating its order
+    /// according to its [`MiddlewareMetadata`].
+    ///
+    /// See [`Self::middleware_with_metadata`] for an example of declaring the
+    /// metadata.
+    #[must_use]
+    pub fn main_handler_middleware_with_metadata<M>(
+        self,
+        middleware: M,
+    ) -> RootHandlerBuilder<WrappedMiddleware<M, RootService>, ErrorHandlerService>
+    where
+        M: Layer<RootService> + MiddlewareMetadata + 'static,
+    {
+        self.add_main_handler_middleware(middleware, Some(MiddlewareInfo::of::<M>()))
+    }
+
+    fn add_main_handler_middleware<M>(
+        self,
+        middleware: M,
+        info: Option<MiddlewareInfo>,
+    ) -> RootHandlerBuilder<WrappedMiddleware<M, RootService>, ErrorHandlerService>
     where
         M: Layer<RootService>,
     {
+        let mut middlewares = self.middlewares;
+        middlewares.push_handler(info);
         let layer = (
             IntoCotErrorLayer::new(),
             IntoCotResponseLayer::new(),
@@ -578,6 +880,7 @@ where
         RootHandlerBuilder {
             handler: layer.layer(self.handler),
             error_handler: self.error_handler,
+            middlewares,
         }
     }
 
@@ -613,9 +916,39 @@ where
         self,
         middleware: M,
     ) -> RootHandlerBuilder<RootService, WrappedMiddleware<M, ErrorHandlerService>>
+    where
+        M: Layer<ErrorHandlerService> + 'static,
+    {
+        let info = MiddlewareInfo::lookup(&middleware);
+        self.add_error_handler_middleware(middleware, info)
+    }
+
+    /// Adds middleware only to the error handler, validating its order
+    /// according to its [`MiddlewareMetadata`].
+    ///
+    /// See [`Self::middleware_with_metadata`] for an example of declaring the
+    /// metadata.
+    #[must_use]
+    pub fn error_handler_middleware_with_metadata<M>(
+        self,
+        middleware: M,
+    ) -> RootHandlerBuilder<RootService, WrappedMiddleware<M, ErrorHandlerService>>
+    where
+        M: Layer<ErrorHandlerService> + MiddlewareMetadata + 'static,
+    {
+        self.add_error_handler_middleware(middleware, Some(MiddlewareInfo::of::<M>()))
+    }
+
+    fn add_error_handler_middleware<M>(
+        self,
+        middleware: M,
+        info: Option<MiddlewareInfo>,
+    ) -> RootHandlerBuilder<RootService, WrappedMiddleware<M, ErrorHandlerService>>
     where
         M: Layer<ErrorHandlerService>,
     {
+        let mut middlewares = self.middlewares;
+        middlewares.push_error_handler(info);
         let layer = (
             IntoCotErrorLayer::new(),
             IntoCotResponseLayer::new(),
@@ -625,6 +958,7 @@ where
         RootHandlerBuilder {
             handler: self.handler,
             error_handler: layer.layer(self.error_handler),
+            middlewares,
         }
     }
 
@@ -654,6 +988,7 @@ where
         RootHandler {
             handler: BoxedHandler::new(self.handler),
             error_handler: BoxedHandler::new(self.error_handler),
+            middlewares: self.middlewares,
         }
     }
 }
@@ -696,6 +1031,8 @@ pub struct RootHandler {
     /// The error handler that processes errors that occur during request
     /// handling.
     pub(crate) error_handler: BoxedHandler,
+    /// The middlewares applied to the handlers, used to validate their order.
+    pub(crate) middlewares: MiddlewareStack,
 }
 
 /// A helper struct to build the apps for the project.
@@ -798,6 +1135,132 @@ impl AppBuilder {
         self.urls.push(Route::with_router(url_prefix, router));
         self.register(app);
     }
+
+    /// Registers the apps only if the given condition is met.
+    ///
+    /// This is typically used to register apps depending on the project
+    /// configuration, such as the debug mode or the [feature
+    /// flags](crate::config::ProjectConfig::features), so that tools that
+    /// shouldn't be exposed in production are not registered there by
+    /// accident.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::{AppBuilder, RegisterAppsContext};
+    /// use cot::{App, Project};
+    ///
+    /// struct HelloApp;
+    ///
+    /// impl App for HelloApp {
+    ///     fn name(&self) -> &'static str {
+    ///         env!("CARGO_PKG_NAME")
+    ///     }
+    /// }
+    ///
+    /// struct ApiDocsApp;
+    ///
+    /// impl App for ApiDocsApp {
+    ///     fn name(&self) -> &'static str {
+    ///         "api_docs"
+    ///     }
+    /// }
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn register_apps(&self, apps: &mut AppBuilder, context: &RegisterAppsContext) {
+    ///         apps.register_with_views(HelloApp, "");
+    ///         apps.when(context.config().debug || context.is_feature_enabled("api_docs"))
+    ///             .register_with_views(ApiDocsApp, "/docs");
+    ///     }
+    /// }
+    /// ```
+    pub fn when(&mut self, condition: bool) -> ConditionalAppBuilder<'_> {
+        ConditionalAppBuilder {
+            builder: self,
+            condition,
+        }
+    }
+}
+
+/// A helper struct to register apps only if a condition is met.
+///
+/// This is returned by [`AppBuilder::when`].
+#[derive(Debug)]
+pub struct ConditionalAppBuilder<'a> {
+    builder: &'a mut AppBuilder,
+    condition: bool,
+}
+
+impl ConditionalAppBuilder<'_> {
+    /// Registers an app if the condition is met.
+    ///
+    /// See [`AppBuilder::register`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::{AppBuilder, RegisterAppsContext};
+    /// use cot::{App, Project};
+    ///
+    /// struct DebugApp;
+    ///
+    /// impl App for DebugApp {
+    ///     fn name(&self) -> &'static str {
+    ///         "debug"
+    ///     }
+    /// }
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn register_apps(&self, apps: &mut AppBuilder, context: &RegisterAppsContext) {
+    ///         apps.when(context.config().debug).register(DebugApp);
+    ///     }
+    /// }
+    /// ```
+    pub fn register<T: App + 'static>(&mut self, app: T) -> &mut Self {
+        if self.condition {
+            self.builder.register(app);
+        } else {
+            debug!(app = app.name(), "Skipping the app registration");
+        }
+        self
+    }
+
+    /// Registers an app with views if the condition is met.
+    ///
+    /// See [`AppBuilder::register_with_views`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::{AppBuilder, RegisterAppsContext};
+    /// use cot::{App, Project};
+    ///
+    /// struct DebugApp;
+    ///
+    /// impl App for DebugApp {
+    ///     fn name(&self) -> &'static str {
+    ///         "debug"
+    ///     }
+    /// }
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn register_apps(&self, apps: &mut AppBuilder, context: &RegisterAppsContext) {
+    ///         apps.when(context.is_feature_enabled("debug_tools"))
+    ///             .register_with_views(DebugApp, "/debug");
+    ///     }
+    /// }
+    /// ```
+    pub fn register_with_views<T: App + 'static>(&mut self, app: T, url_prefix: &str) -> &mut Self {
+        if self.condition {
+            self.builder.register_with_views(app, url_prefix);
+        } else {
+            debug!(app = app.name(), "Skipping the app registration");
+        }
+        self
+    }
 }
 
 async fn default_error_handler(error: RequestOuterError) -> crate::Result<impl IntoResponse> {
@@ -1039,7 +1502,7 @@ fn read_config(config: &str) -> cot::Result<ProjectConfig> {
         source: err,
     })?;
 
-    ProjectConfig::from_toml(&config_content)
+    ProjectConfig::from_toml_with_env(&config_content)
 }
 
 #[derive(Debug, Error)]
@@ -1221,14 +1684,30 @@ impl Bootstrapper<WithApps> {
         })
     }
 
+    /// Moves forward to the with-database phase, using an already connected
+    /// database instead of connecting to the one from the configuration.
+    ///
+    /// This is used by the test server to share a test database with the
+    /// test code.
+    #[cfg(all(feature = "db", feature = "test"))]
+    pub(crate) fn with_existing_database(self, database: Database) -> Bootstrapper<WithDatabase> {
+        let context = self.context.with_database(Some(database));
+
+        Bootstrapper {
+            project: self.project,
+            context,
+            handler: self.handler,
+            error_handler: self.error_handler,
+        }
+    }
+
     #[cfg(feature = "db")]
     async fn init_database(config: &DatabaseConfig) -> cot::Result<Option<Database>> {
-        match &config.url {
-            Some(url) => {
-                let database = Database::new(url.as_str()).await?;
-                Ok(Some(database))
-            }
-            None => Ok(None),
+        if config.url.is_some() {
+            let database = Database::with_config(config).await?;
+            Ok(Some(database))
+        } else {
+            Ok(None)
         }
     }
 }
@@ -1349,7 +1828,15 @@ impl Bootstrapper<WithCache> {
     /// # Errors
     ///
     /// This method may return an error if it cannot initialize any of the
-    /// project's components, such as the database.
+    /// project's components, such as the database, if the middlewares are
+    /// added in an invalid order (e.g. [`AuthMiddleware`] is used without
+    /// [`SessionMiddleware`] added after it), or if any route doesn't have the
+    /// path parameters its handler's [`Path`] extractor expects (see
+    /// [`Router`'s documentation](Router#path-parameters)).
+    ///
+    /// [`AuthMiddleware`]: crate::middleware::AuthMiddleware
+    /// [`SessionMiddleware`]: crate::middleware::SessionMiddleware
+    /// [`Path`]: crate::request::extractors::Path
     ///
     /// # Examples
     ///
@@ -1379,11 +1866,24 @@ impl Bootstrapper<WithCache> {
         let handler_builder = RootHandlerBuilder {
             handler: router_service,
             error_handler: self.project.error_handler(),
+            middlewares: MiddlewareStack::default(),
         };
         let handler = self.project.middlewares(handler_builder, &self.context);
+        handler.middlewares.validate()?;
+        self.context.router.verify_path_params()?;
 
         let auth_backend = self.project.auth_backend(&self.context);
-        let context = self.context.with_auth(auth_backend);
+        #[cfg(feature = "tasks")]
+        let tasks = Tasks::from_config(
+            &self.context.config.tasks,
+            #[cfg(feature = "db")]
+            self.context.database.clone(),
+        )?;
+        let context = self.context.with_auth(
+            auth_backend,
+            #[cfg(feature = "tasks")]
+            tasks,
+        );
 
         Ok(Bootstrapper {
             project: self.project,
@@ -1418,7 +1918,19 @@ impl Bootstrapper<Initialized> {
     /// ```
     #[must_use]
     pub fn finish(self) -> BootstrappedProject {
+        #[cfg(feature = "tasks")]
+        let task_registry = {
+            let mut task_registry = TaskRegistry::new();
+            #[cfg(feature = "email")]
+            task_registry.register::<crate::email::queue::SendEmail>();
+            self.project.register_background_tasks(&mut task_registry);
+            task_registry
+        };
+
         BootstrappedProject {
+            stream_error_hook: self.project.stream_error_hook(),
+            #[cfg(feature = "tasks")]
+            task_registry,
             context: self.context,
             handler: self.handler,
             error_handler: self.error_handler,
@@ -1465,6 +1977,14 @@ pub struct BootstrappedProject {
     /// The error handler that processes errors that occur during request
     /// handling.
     pub error_handler: BoxedHandler,
+    /// The hook that is called when an error occurs while streaming a
+    /// response body.
+    #[debug("..")]
+    pub stream_error_hook: Option<Arc<dyn StreamErrorHook>>,
+    /// The background tasks that can be run by the project's
+    /// [`TaskWorker`](crate::tasks::TaskWorker).
+    #[cfg(feature = "tasks")]
+    pub task_registry: TaskRegistry,
 }
 
 mod sealed {
@@ -1527,6 +2047,12 @@ pub trait BootstrapPhase: sealed::Sealed {
     /// The type of the email service.
     #[cfg(feature = "email")]
     type Email: Debug;
+    /// The type of the file storage.
+    #[cfg(feature = "storage")]
+    type Storage: Debug;
+    /// The type of the HTTP client.
+    #[cfg(feature = "http-client")]
+    type HttpClient: Debug;
 
     /// The type of the apps.
     type Apps;
@@ -1540,6 +2066,9 @@ pub trait BootstrapPhase: sealed::Sealed {
     /// The type of the cache.
     #[cfg(feature = "cache")]
     type Cache: Debug;
+    /// The type of the background tasks handle.
+    #[cfg(feature = "tasks")]
+    type Tasks: Debug;
 }
 
 /// First phase of bootstrapping a Cot project, the uninitialized phase.
@@ -1558,6 +2087,10 @@ impl BootstrapPhase for Uninitialized {
     type Config = ();
     #[cfg(feature = "email")]
     type Email = ();
+    #[cfg(feature = "storage")]
+    type Storage = ();
+    #[cfg(feature = "http-client")]
+    type HttpClient = ();
     type Apps = ();
     type Router = ();
     #[cfg(feature = "db")]
@@ -1565,6 +2098,8 @@ impl BootstrapPhase for Uninitialized {
     type AuthBackend = ();
     #[cfg(feature = "cache")]
     type Cache = ();
+    #[cfg(feature = "tasks")]
+    type Tasks = ();
 }
 
 /// Second phase of bootstrapping a Cot project, the with-config phase.
@@ -1583,6 +2118,10 @@ impl BootstrapPhase for WithConfig {
     type Config = Arc<ProjectConfig>;
     #[cfg(feature = "email")]
     type Email = Email;
+    #[cfg(feature = "storage")]
+    type Storage = FileStorage;
+    #[cfg(feature = "http-client")]
+    type HttpClient = HttpClient;
     type Apps = ();
     type Router = ();
     #[cfg(feature = "db")]
@@ -1590,6 +2129,8 @@ impl BootstrapPhase for WithConfig {
     type AuthBackend = ();
     #[cfg(feature = "cache")]
     type Cache = ();
+    #[cfg(feature = "tasks")]
+    type Tasks = ();
 }
 
 /// Third phase of bootstrapping a Cot project, the with-apps phase.
@@ -1608,6 +2149,10 @@ impl BootstrapPhase for WithApps {
     type Config = <WithConfig as BootstrapPhase>::Config;
     #[cfg(feature = "email")]
     type Email = <WithConfig as BootstrapPhase>::Email;
+    #[cfg(feature = "storage")]
+    type Storage = <WithConfig as BootstrapPhase>::Storage;
+    #[cfg(feature = "http-client")]
+    type HttpClient = <WithConfig as BootstrapPhase>::HttpClient;
     type Apps = Vec<Box<dyn App>>;
     type Router = Arc<Router>;
     #[cfg(feature = "db")]
@@ -1615,6 +2160,8 @@ impl BootstrapPhase for WithApps {
     type AuthBackend = ();
     #[cfg(feature = "cache")]
     type Cache = ();
+    #[cfg(feature = "tasks")]
+    type Tasks = ();
 }
 
 /// Fourth phase of bootstrapping a Cot project, the with-database phase.
@@ -1633,6 +2180,10 @@ impl BootstrapPhase for WithDatabase {
     type Config = <WithApps as BootstrapPhase>::Config;
     #[cfg(feature = "email")]
     type Email = <WithApps as BootstrapPhase>::Email;
+    #[cfg(feature = "storage")]
+    type Storage = <WithApps as BootstrapPhase>::Storage;
+    #[cfg(feature = "http-client")]
+    type HttpClient = <WithApps as BootstrapPhase>::HttpClient;
     type Apps = <WithApps as BootstrapPhase>::Apps;
     type Router = <WithApps as BootstrapPhase>::Router;
     #[cfg(feature = "db")]
@@ -1640,6 +2191,8 @@ impl BootstrapPhase for WithDatabase {
     type AuthBackend = <WithApps as BootstrapPhase>::AuthBackend;
     #[cfg(feature = "cache")]
     type Cache = ();
+    #[cfg(feature = "tasks")]
+    type Tasks = ();
 }
 
 /// Fifth phase of bootstrapping a Cot project, the with-cache phase.
@@ -1658,6 +2211,10 @@ impl BootstrapPhase for WithCache {
     type Config = <WithApps as BootstrapPhase>::Config;
     #[cfg(feature = "email")]
     type Email = <WithApps as BootstrapPhase>::Email;
+    #[cfg(feature = "storage")]
+    type Storage = <WithApps as BootstrapPhase>::Storage;
+    #[cfg(feature = "http-client")]
+    type HttpClient = <WithApps as BootstrapPhase>::HttpClient;
     type Apps = <WithApps as BootstrapPhase>::Apps;
     type Router = <WithApps as BootstrapPhase>::Router;
     #[cfg(feature = "db")]
@@ -1665,6 +2222,8 @@ impl BootstrapPhase for WithCache {
     type AuthBackend = <WithApps as BootstrapPhase>::AuthBackend;
     #[cfg(feature = "cache")]
     type Cache = Cache;
+    #[cfg(feature = "tasks")]
+    type Tasks = ();
 }
 
 /// The final phase of bootstrapping a Cot project, the initialized phase.
@@ -1683,6 +2242,10 @@ impl BootstrapPhase for Initialized {
     type Config = <WithDatabase as BootstrapPhase>::Config;
     #[cfg(feature = "email")]
     type Email = <WithDatabase as BootstrapPhase>::Email;
+    #[cfg(feature = "storage")]
+    type Storage = <WithDatabase as BootstrapPhase>::Storage;
+    #[cfg(feature = "http-client")]
+    type HttpClient = <WithDatabase as BootstrapPhase>::HttpClient;
     type Apps = <WithDatabase as BootstrapPhase>::Apps;
     type Router = <WithDatabase as BootstrapPhase>::Router;
     #[cfg(feature = "db")]
@@ -1690,6 +2253,8 @@ impl BootstrapPhase for Initialized {
     type AuthBackend = Arc<dyn AuthBackend>;
     #[cfg(feature = "cache")]
     type Cache = <WithCache as BootstrapPhase>::Cache;
+    #[cfg(feature = "tasks")]
+    type Tasks = Tasks;
 }
 
 /// Shared context and configs for all apps. Used in conjunction with the
@@ -1708,11 +2273,18 @@ pub struct ProjectContext<S: BootstrapPhase = Initialized> {
     cache: S::Cache,
     #[cfg(feature = "email")]
     email: S::Email,
+    #[cfg(feature = "storage")]
+    storage: S::Storage,
+    #[cfg(feature = "http-client")]
+    http_client: S::HttpClient,
+    notifier: Notifier,
+    #[cfg(feature = "tasks")]
+    tasks: S::Tasks,
 }
 
 impl ProjectContext<Uninitialized> {
     #[must_use]
-    pub(crate) const fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             config: (),
             apps: (),
@@ -1724,6 +2296,13 @@ impl ProjectContext<Uninitialized> {
             cache: (),
             #[cfg(feature = "email")]
             email: (),
+            #[cfg(feature = "storage")]
+            storage: (),
+            #[cfg(feature = "http-client")]
+            http_client: (),
+            notifier: Notifier::new(),
+            #[cfg(feature = "tasks")]
+            tasks: (),
         }
     }
 
@@ -1734,6 +2313,18 @@ impl ProjectContext<Uninitialized> {
                 panic!("failed to initialize email service: {err:?}");
             })
         };
+        #[cfg(feature = "storage")]
+        let storage = {
+            FileStorage::from_config(&config.storage).unwrap_or_else(|err| {
+                panic!("failed to initialize file storage: {err:?}");
+            })
+        };
+        #[cfg(feature = "http-client")]
+        let http_client = {
+            HttpClient::from_config(&config.http_client).unwrap_or_else(|err| {
+                panic!("failed to initialize HTTP client: {err:?}");
+            })
+        };
 
         ProjectContext {
             config: Arc::new(config),
@@ -1746,6 +2337,13 @@ impl ProjectContext<Uninitialized> {
             cache: self.cache,
             #[cfg(feature = "email")]
             email,
+            #[cfg(feature = "storage")]
+            storage,
+            #[cfg(feature = "http-client")]
+            http_client,
+            notifier: self.notifier,
+            #[cfg(feature = "tasks")]
+            tasks: self.tasks,
         }
     }
 }
@@ -1774,6 +2372,41 @@ impl<S: BootstrapPhase<Config = Arc<ProjectConfig>>> ProjectContext<S> {
     pub fn config(&self) -> &ProjectConfig {
         &self.config
     }
+
+    /// Returns whether the feature flag with the given name is enabled in the
+    /// project configuration.
+    ///
+    /// This is a shorthand for
+    /// [`FeaturesConfig::is_enabled`](crate::config::FeaturesConfig::is_enabled)
+    /// called on the [`features`](ProjectConfig::features) of the project
+    /// config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::project::{AppBuilder, RegisterAppsContext};
+    /// use cot::{App, Project};
+    ///
+    /// struct AdminApp;
+    ///
+    /// impl App for AdminApp {
+    ///     fn name(&self) -> &'static str {
+    ///         "admin"
+    ///     }
+    /// }
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn register_apps(&self, apps: &mut AppBuilder, context: &RegisterAppsContext) {
+    ///         apps.when(context.is_feature_enabled("admin"))
+    ///             .register_with_views(AdminApp, "/admin");
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn is_feature_enabled(&self, name: &str) -> bool {
+        self.config.features.is_enabled(name)
+    }
 }
 
 impl ProjectContext<WithConfig> {
@@ -1790,6 +2423,13 @@ impl ProjectContext<WithConfig> {
             cache: self.cache,
             #[cfg(feature = "email")]
             email: self.email,
+            #[cfg(feature = "storage")]
+            storage: self.storage,
+            #[cfg(feature = "http-client")]
+            http_client: self.http_client,
+            notifier: self.notifier,
+            #[cfg(feature = "tasks")]
+            tasks: self.tasks,
         }
     }
 }
@@ -1822,6 +2462,10 @@ impl ProjectContext<WithApps> {
         self,
         #[cfg(feature = "db")] database: Option<Database>,
     ) -> ProjectContext<WithDatabase> {
+        #[cfg(feature = "db")]
+        let database =
+            database.map(|database| database.with_change_notifier(self.notifier.clone()));
+
         ProjectContext {
             config: self.config,
             apps: self.apps,
@@ -1833,6 +2477,13 @@ impl ProjectContext<WithApps> {
             cache: self.cache,
             #[cfg(feature = "email")]
             email: self.email,
+            #[cfg(feature = "storage")]
+            storage: self.storage,
+            #[cfg(feature = "http-client")]
+            http_client: self.http_client,
+            notifier: self.notifier,
+            #[cfg(feature = "tasks")]
+            tasks: self.tasks,
         }
     }
 }
@@ -1840,24 +2491,51 @@ impl ProjectContext<WithApps> {
 impl ProjectContext<WithDatabase> {
     #[must_use]
     fn with_cache(self, #[cfg(feature = "cache")] cache: Cache) -> ProjectContext<WithCache> {
+        #[cfg(all(feature = "db", feature = "cache"))]
+        let database = self
+            .database
+            .map(|database| database.with_query_cache(cache.clone()));
+        #[cfg(all(feature = "db", not(feature = "cache")))]
+        let database = self.database;
+
         ProjectContext {
             config: self.config,
             apps: self.apps,
             router: self.router,
             auth_backend: self.auth_backend,
             #[cfg(feature = "db")]
-            database: self.database,
+            database,
             #[cfg(feature = "cache")]
             cache,
             #[cfg(feature = "email")]
             email: self.email,
+            #[cfg(feature = "storage")]
+            storage: self.storage,
+            #[cfg(feature = "http-client")]
+            http_client: self.http_client,
+            notifier: self.notifier,
+            #[cfg(feature = "tasks")]
+            tasks: self.tasks,
         }
     }
 }
 
 impl ProjectContext<WithCache> {
     #[must_use]
-    fn with_auth(self, auth_backend: Arc<dyn AuthBackend>) -> ProjectContext<Initialized> {
+    fn with_auth(
+        self,
+        auth_backend: Arc<dyn AuthBackend>,
+        #[cfg(feature = "tasks")] tasks: Tasks,
+    ) -> ProjectContext<Initialized> {
+        #[cfg(all(feature = "email", feature = "tasks"))]
+        let email = if self.config.email.queue.enabled {
+            self.email.with_queue(tasks.clone())
+        } else {
+            self.email
+        };
+        #[cfg(all(feature = "email", not(feature = "tasks")))]
+        let email = self.email;
+
         ProjectContext {
             config: self.config,
             apps: self.apps,
@@ -1868,12 +2546,24 @@ impl ProjectContext<WithCache> {
             #[cfg(feature = "cache")]
             cache: self.cache,
             #[cfg(feature = "email")]
-            email: self.email,
+            email,
+            #[cfg(feature = "storage")]
+            storage: self.storage,
+            #[cfg(feature = "http-client")]
+            http_client: self.http_client,
+            notifier: self.notifier,
+            #[cfg(feature = "tasks")]
+            tasks,
         }
     }
 }
 impl ProjectContext<Initialized> {
     #[cfg(feature = "test")]
+    #[allow(
+        clippy::allow_attributes,
+        clippy::too_many_arguments,
+        reason = "the number of arguments depends on the enabled features"
+    )]
     pub(crate) fn initialized(
         config: <Initialized as BootstrapPhase>::Config,
         apps: <Initialized as BootstrapPhase>::Apps,
@@ -1882,6 +2572,10 @@ impl ProjectContext<Initialized> {
         #[cfg(feature = "db")] database: <Initialized as BootstrapPhase>::Database,
         #[cfg(feature = "cache")] cache: <Initialized as BootstrapPhase>::Cache,
         #[cfg(feature = "email")] email: <Initialized as BootstrapPhase>::Email,
+        #[cfg(feature = "storage")] storage: <Initialized as BootstrapPhase>::Storage,
+        #[cfg(feature = "http-client")] http_client: <Initialized as BootstrapPhase>::HttpClient,
+        notifier: Notifier,
+        #[cfg(feature = "tasks")] tasks: <Initialized as BootstrapPhase>::Tasks,
     ) -> Self {
         Self {
             config,
@@ -1894,10 +2588,38 @@ impl ProjectContext<Initialized> {
             cache,
             #[cfg(feature = "email")]
             email,
+            #[cfg(feature = "storage")]
+            storage,
+            #[cfg(feature = "http-client")]
+            http_client,
+            notifier,
+            #[cfg(feature = "tasks")]
+            tasks,
         }
     }
 }
 
+impl<S: BootstrapPhase> ProjectContext<S> {
+    /// Returns the notifier for the project.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     let notifier = request.context().notifier();
+    ///     // ...
+    /// #    unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+}
+
 impl<S: BootstrapPhase<Router = Arc<Router>>> ProjectContext<S> {
     /// Returns the router for the project.
     ///
@@ -1923,6 +2645,63 @@ impl<S: BootstrapPhase<Router = Arc<Router>>> ProjectContext<S> {
         &self.router
     }
 }
+
+impl<S: BootstrapPhase<Config = Arc<ProjectConfig>, Router = Arc<Router>>> ProjectContext<S> {
+    /// Returns a [`Urls`] object that can be used to reverse the named routes
+    /// of the project outside of a request, such as in background tasks,
+    /// emails, or CLI commands.
+    ///
+    /// Since there is no current route, the returned object does not have an
+    /// app name, so routes that belong to an app have to be referenced with
+    /// the app name included (e.g. `"my_app:index"`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::ProjectConfig;
+    /// use cot::project::{App, AppBuilder, RegisterAppsContext};
+    /// use cot::router::{Route, Router};
+    /// use cot::{Bootstrapper, Project, reverse};
+    ///
+    /// async fn index() -> &'static str {
+    ///     "Hello world!"
+    /// }
+    ///
+    /// struct MyApp;
+    /// impl App for MyApp {
+    ///     fn name(&self) -> &'static str {
+    ///         "my_app"
+    ///     }
+    ///
+    ///     fn router(&self) -> Router {
+    ///         Router::with_urls([Route::with_handler_and_name("/hello", index, "index")])
+    ///     }
+    /// }
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {
+    ///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+    ///         apps.register_with_views(MyApp, "/app");
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let bootstrapper = Bootstrapper::new(MyProject)
+    ///     .with_config(ProjectConfig::default())
+    ///     .boot()
+    ///     .await?;
+    ///
+    /// let urls = bootstrapper.context().urls();
+    /// assert_eq!(reverse!(urls, "my_app:index")?, "/app/hello");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn urls(&self) -> Urls {
+        Urls::new(Arc::clone(&self.router), self.config.canonical_url.clone())
+    }
+}
 impl<S: BootstrapPhase<AuthBackend = Arc<dyn AuthBackend>>> ProjectContext<S> {
     /// Returns the authentication backend for the project.
     ///
@@ -1966,6 +2745,58 @@ impl<S: BootstrapPhase<Email = Email>> ProjectContext<S> {
     }
 }
 
+#[cfg(feature = "storage")]
+impl<S: BootstrapPhase<Storage = FileStorage>> ProjectContext<S> {
+    /// Returns the file storage for the project.
+    ///
+    /// The storage is configured with the
+    /// [`StorageConfig`](crate::config::StorageConfig).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     let storage = request.context().storage();
+    ///     // ...
+    /// #    unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn storage(&self) -> &FileStorage {
+        &self.storage
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl<S: BootstrapPhase<HttpClient = HttpClient>> ProjectContext<S> {
+    /// Returns the HTTP client for the project.
+    ///
+    /// The client is configured with the
+    /// [`HttpClientConfig`](crate::config::HttpClientConfig). To propagate the
+    /// tracing headers of the request being handled, use the
+    /// [`HttpClient`] extractor instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     let http_client = request.context().http_client();
+    ///     // ...
+    /// #    unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn http_client(&self) -> &HttpClient {
+        &self.http_client
+    }
+}
+
 #[cfg(feature = "cache")]
 impl<S: BootstrapPhase<Cache = Cache>> ProjectContext<S> {
     /// Returns the cache for the project.
@@ -1989,6 +2820,30 @@ impl<S: BootstrapPhase<Cache = Cache>> ProjectContext<S> {
     }
 }
 
+#[cfg(feature = "tasks")]
+impl<S: BootstrapPhase<Tasks = Tasks>> ProjectContext<S> {
+    /// Returns the handle used to enqueue background tasks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn index(request: Request) -> cot::Result<Response> {
+    ///     let tasks = request.context().tasks();
+    ///     // can also be accessed via:
+    ///     let tasks = request.tasks();
+    ///     // ...
+    /// #    unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn tasks(&self) -> &Tasks {
+        &self.tasks
+    }
+}
+
 #[cfg(feature = "db")]
 impl<S: BootstrapPhase<Database = Option<Database>>> ProjectContext<S> {
     /// Returns the database for the project, if it is enabled.
@@ -2048,15 +2903,48 @@ impl<S: BootstrapPhase<Database = Option<Database>>> ProjectContext<S> {
 /// This function takes a Cot project and an address string and runs the
 /// project on the given address.
 ///
+/// On Unix systems, the address can also be a path to a Unix domain socket
+/// prefixed with `unix:` (e.g. `unix:/run/myproject.sock`), which is useful
+/// when running the project behind a reverse proxy on the same machine. If a
+/// socket file already exists at the path, it is replaced.
+///
+/// If TLS is configured in [`ProjectConfig::tls`], the server accepts TLS
+/// connections on TCP addresses (see [`run_at_tls`]).
+///
 /// # Errors
 ///
-/// This function returns an error if the server fails to start.
+/// This function returns an error if the server fails to start, or if the TLS
+/// certificate chain or private key can't be loaded.
 pub async fn run(bootstrapper: Bootstrapper<Initialized>, address_str: &str) -> cot::Result<()> {
+    #[cfg(unix)]
+    if let Some(path) = address_str.strip_prefix("unix:") {
+        let listener = bind_unix(std::path::Path::new(path)).map_err(StartServerError)?;
+        return Box::pin(run_at_unix(bootstrapper, listener)).await;
+    }
+
     let listener = tokio::net::TcpListener::bind(address_str)
         .await
         .map_err(StartServerError)?;
 
-    run_at(bootstrapper, listener).await
+    #[cfg(feature = "tls")]
+    if tls::is_enabled(&bootstrapper.context().config().tls) {
+        let tls_config = tls::server_config(&bootstrapper.context().config().tls)?;
+        return Box::pin(run_at_tls(bootstrapper, listener, tls_config)).await;
+    }
+
+    Box::pin(run_at(bootstrapper, listener)).await
+}
+
+#[cfg(unix)]
+fn bind_unix(path: &std::path::Path) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    // remove the socket left over by the previous run of the server
+    if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+
+    tokio::net::UnixListener::bind(path)
 }
 
 /// Runs the Cot project on the given listener.
@@ -2097,93 +2985,225 @@ pub async fn run_at_with_shutdown(
     listener: tokio::net::TcpListener,
     shutdown_signal: impl Future<Output = ()> + Send + 'static,
 ) -> cot::Result<()> {
+    let address = format!(
+        "http://{}",
+        listener.local_addr().map_err(StartServerError)?
+    );
+
+    serve(
+        bootstrapper,
+        listener,
+        ConnectionScheme::Http,
+        &address,
+        shutdown_signal,
+    )
+    .await
+}
+
+/// Runs the Cot project on the given Unix domain socket listener.
+///
+/// This is useful when running the project behind a reverse proxy (such as
+/// nginx) on the same machine. The [`run`] function can also bind the socket
+/// for you if the address is prefixed with `unix:`.
+///
+/// # Errors
+///
+/// This function returns an error if the server fails to start.
+#[cfg(unix)]
+pub async fn run_at_unix(
+    bootstrapper: Bootstrapper<Initialized>,
+    listener: tokio::net::UnixListener,
+) -> cot::Result<()> {
+    run_at_unix_with_shutdown(bootstrapper, listener, shutdown_signal()).await
+}
+
+/// Runs the Cot project on the given Unix domain socket listener, shutting
+/// the server down gracefully when the given future completes.
+///
+/// See [`run_at_unix`] and [`run_at_with_shutdown`] for more details.
+///
+/// # Errors
+///
+/// This function returns an error if the server fails to start.
+#[cfg(unix)]
+pub async fn run_at_unix_with_shutdown(
+    bootstrapper: Bootstrapper<Initialized>,
+    listener: tokio::net::UnixListener,
+    shutdown_signal: impl Future<Output = ()> + Send + 'static,
+) -> cot::Result<()> {
+    let local_addr = listener.local_addr().map_err(StartServerError)?;
+    let address = match local_addr.as_pathname() {
+        Some(path) => format!("unix:{}", path.display()),
+        None => "an unnamed Unix socket".to_owned(),
+    };
+
+    serve(
+        bootstrapper,
+        listener,
+        ConnectionScheme::Http,
+        &address,
+        shutdown_signal,
+    )
+    .await
+}
+
+/// Runs the Cot project on the given listener, accepting TLS (HTTPS)
+/// connections.
+///
+/// The TLS handshakes are performed concurrently with a timeout, so slow
+/// clients don't prevent other clients from connecting. To serve HTTP/2 over
+/// TLS, enable the `http2` feature and include `h2` in the
+/// [`alpn_protocols`](tokio_rustls::rustls::ServerConfig::alpn_protocols) of the TLS
+/// configuration.
+///
+/// Typically, it's more convenient to set the certificate chain and the
+/// private key paths in [`ProjectConfig::tls`] and use the [`run`] function.
+///
+/// # Errors
+///
+/// This function returns an error if the server fails to start.
+#[cfg(feature = "tls")]
+pub async fn run_at_tls(
+    bootstrapper: Bootstrapper<Initialized>,
+    listener: tokio::net::TcpListener,
+    tls_config: Arc<tokio_rustls::rustls::ServerConfig>,
+) -> cot::Result<()> {
+    run_at_tls_with_shutdown(bootstrapper, listener, tls_config, shutdown_signal()).await
+}
+
+/// Runs the Cot project on the given listener, accepting TLS (HTTPS)
+/// connections and shutting the server down gracefully when the given future
+/// completes.
+///
+/// See [`run_at_tls`] and [`run_at_with_shutdown`] for more details.
+///
+/// # Errors
+///
+/// This function returns an error if the server fails to start.
+#[cfg(feature = "tls")]
+pub async fn run_at_tls_with_shutdown(
+    bootstrapper: Bootstrapper<Initialized>,
+    listener: tokio::net::TcpListener,
+    tls_config: Arc<tokio_rustls::rustls::ServerConfig>,
+    shutdown_signal: impl Future<Output = ()> + Send + 'static,
+) -> cot::Result<()> {
+    let address = format!(
+        "https://{}",
+        listener.local_addr().map_err(StartServerError)?
+    );
+    let listener = tls::TlsListener::new(listener, tls_config);
+
+    serve(
+        bootstrapper,
+        listener,
+        ConnectionScheme::Https,
+        &address,
+        shutdown_signal,
+    )
+    .await
+}
+
+async fn serve<L>(
+    bootstrapper: Bootstrapper<Initialized>,
+    listener: L,
+    scheme: ConnectionScheme,
+    address: &str,
+    shutdown_signal: impl Future<Output = ()> + Send + 'static,
+) -> cot::Result<()>
+where
+    L: axum::serve::Listener,
+    L::Addr: Debug,
+{
     let BootstrappedProject {
         mut context,
         mut handler,
         mut error_handler,
+        stream_error_hook,
+        #[cfg(feature = "tasks")]
+        task_registry,
     } = bootstrapper.finish();
 
     #[cfg(feature = "db")]
     if let Some(database) = &context.database {
-        let mut migrations: Vec<Box<SyncDynMigration>> = Vec::new();
-        for app in &context.apps {
-            migrations.extend(app.migrations());
-        }
-        let migration_engine = MigrationEngine::new(migrations)?;
-        migration_engine.run(database).await?;
+        run_migrations(&context.apps, database).await?;
     }
 
-    let mut apps = std::mem::take(&mut context.apps);
-    for app in &mut apps {
-        info!("Initializing app: {}", app.name());
-
-        app.init(&mut context).await?;
-    }
-    context.apps = apps;
+    init_apps(&mut context).await?;
+    #[cfg(feature = "opentelemetry")]
+    let tracer_guard = crate::telemetry::init(&context.config().opentelemetry)?;
 
     let context = Arc::new(context);
     let is_debug = context.config().debug;
     let register_panic_hook = context.config().register_panic_hook;
+    let grace_period = context.config().shutdown.grace_period;
+    let pre_stop_delay = context.config().shutdown.pre_stop_delay;
+    let shutdown_coordinator = ShutdownCoordinator::new();
+    let request_shutdown_coordinator = shutdown_coordinator.clone();
+    let stream_error_guard =
+        StreamErrorGuard::new(context.config().stream_error_policy, stream_error_hook);
     #[cfg(feature = "db")]
     let context_cleanup = context.clone();
+    #[cfg(feature = "tasks")]
+    let task_worker = tokio::spawn(
+        TaskWorker::new(Arc::clone(&context), task_registry).run(shutdown_coordinator.signal()),
+    );
 
-    let handler = move |axum_request: axum::extract::Request| async move {
-        // todo root tracing span
-        // todo per-router error handlers
-        let request = request_axum_to_cot(axum_request, Arc::clone(&context));
-        let (head, request) = request.into_parts();
-        let head_for_error_handler = head.clone();
-        let request = Request::from_parts(head, request);
-
-        let (request_head, request) = request_parts_for_diagnostics(request);
-
-        let catch_unwind_response = AssertUnwindSafe(pass_to_axum(request, &mut handler))
-            .catch_unwind()
-            .await;
+    let handler = move |axum_request: axum::extract::Request| {
+        let span = request_span(&axum_request);
+        async move {
+            // todo per-router error handlers
+            let method = axum_request.method().clone();
+            let uri = axum_request.uri().clone();
+            let mut request = request_axum_to_cot(axum_request, Arc::clone(&context), scheme);
+            request_shutdown_coordinator.insert_extensions(request.extensions_mut());
+            let (head, request) = request.into_parts();
+            let head_for_error_handler = head.clone();
+            let request = Request::from_parts(head, request);
+
+            let (request_head, request) = request_parts_for_diagnostics(request);
+
+            let response = match pass_to_axum_catch_unwind(request, &mut handler).await {
+                Ok(response) => response,
+                Err(error_response) => {
+                    if is_debug && accepts_html(request_head.as_ref()) {
+                        let diagnostics = Diagnostics::new(
+                            context.config().clone(),
+                            Arc::clone(&context.router),
+                            request_head,
+                        );
+
+                        build_cot_error_page(error_response, &diagnostics)
+                    } else {
+                        build_custom_error_page(
+                            &mut error_handler,
+                            error_response,
+                            head_for_error_handler,
+                        )
+                        .await
+                    }
+                }
+            };
 
-        let response: Result<axum::response::Response, ErrorResponse> = match catch_unwind_response
-        {
-            Ok(Ok(response)) => Ok(response),
-            Ok(Err(error)) => Err(ErrorResponse::ErrorReturned(error)),
-            Err(error) => Err(ErrorResponse::Panic(error)),
-        };
+            let span = tracing::Span::current();
+            span.record("status", response.status().as_u16());
+            #[cfg(feature = "opentelemetry")]
+            crate::telemetry::finish_request_span(&span, response.status());
 
-        match response {
-            Ok(response) => response,
-            Err(error_response) => {
-                if is_debug && accepts_html(request_head.as_ref()) {
-                    let diagnostics = Diagnostics::new(
-                        context.config().clone(),
-                        Arc::clone(&context.router),
-                        request_head,
-                    );
-
-                    build_cot_error_page(error_response, &diagnostics)
-                } else {
-                    build_custom_error_page(
-                        &mut error_handler,
-                        error_response,
-                        head_for_error_handler,
-                    )
-                    .await
-                }
-            }
+            response.map(|body| {
+                let body = stream_error_guard.wrap(method, uri, body);
+                axum::body::Body::new(request_shutdown_coordinator.track(body))
+            })
         }
+        .instrument(span)
     };
 
-    eprintln!(
-        "Starting the server at http://{}",
-        listener.local_addr().map_err(StartServerError)?
-    );
+    eprintln!("Starting the server at {address}");
 
     if register_panic_hook {
-        let current_hook = std::panic::take_hook();
-        let new_hook = move |hook_info: &std::panic::PanicHookInfo<'_>| {
-            current_hook(hook_info);
-            error_page::error_page_panic_hook(hook_info);
-        };
-        std::panic::set_hook(Box::new(new_hook));
+        set_error_page_panic_hook();
     }
+    let shutdown_signal =
+        shutdown_coordinator.shut_down_after(shutdown_signal, pre_stop_delay, grace_period);
     axum::serve(listener, handler.into_make_service())
         .with_graceful_shutdown(shutdown_signal)
         .await
@@ -2191,10 +3211,87 @@ pub async fn run_at_with_shutdown(
     if register_panic_hook {
         let _ = std::panic::take_hook();
     }
+    // let the task that is currently running finish before closing the database
+    #[cfg(feature = "tasks")]
+    if let Err(error) = task_worker.await {
+        error!("Background task worker failed: {error}");
+    }
     #[cfg(feature = "db")]
     if let Some(database) = &context_cleanup.database {
         database.close().await?;
     }
+    #[cfg(feature = "opentelemetry")]
+    if let Some(tracer_guard) = tracer_guard {
+        tracer_guard.shutdown().await;
+    }
+
+    Ok(())
+}
+
+/// Runs the background task worker of the Cot project without starting the
+/// HTTP server.
+///
+/// The project is bootstrapped the same way as for [`run`], so the worker
+/// shares the configuration, the database and the apps with the server. This
+/// makes it possible to deploy the web server and the task worker as separate
+/// processes running the same binary. The worker runs until a Ctrl+C or a
+/// termination signal is received.
+///
+/// # Errors
+///
+/// This function returns an error if the migrations fail to run or any of the
+/// apps fail to initialize.
+#[cfg(feature = "tasks")]
+pub async fn run_worker(bootstrapper: Bootstrapper<Initialized>) -> cot::Result<()> {
+    run_worker_with_shutdown(bootstrapper, shutdown_signal()).await
+}
+
+/// Runs the background task worker of the Cot project without starting the
+/// HTTP server.
+///
+/// This is similar to [`run_worker`], but it takes a shutdown signal that can
+/// be used to stop the worker in a response to a signal or other event. The
+/// task that is currently running is allowed to finish before the function
+/// returns.
+///
+/// # Errors
+///
+/// This function returns an error if the migrations fail to run or any of the
+/// apps fail to initialize.
+#[cfg(feature = "tasks")]
+pub async fn run_worker_with_shutdown(
+    bootstrapper: Bootstrapper<Initialized>,
+    shutdown_signal: impl Future<Output = ()> + Send + 'static,
+) -> cot::Result<()> {
+    let BootstrappedProject {
+        mut context,
+        task_registry,
+        ..
+    } = bootstrapper.finish();
+
+    #[cfg(feature = "db")]
+    if let Some(database) = &context.database {
+        run_migrations(&context.apps, database).await?;
+    }
+
+    init_apps(&mut context).await?;
+
+    let context = Arc::new(context);
+    let shutdown_coordinator = ShutdownCoordinator::new();
+    let worker =
+        TaskWorker::new(Arc::clone(&context), task_registry).run(shutdown_coordinator.signal());
+    let shutdown_signal = async move {
+        shutdown_signal.await;
+        shutdown_coordinator.start_shutdown(None);
+    };
+
+    eprintln!("Starting the background task worker");
+    tokio::join!(worker, shutdown_signal);
+
+    #[cfg(feature = "db")]
+    if let Some(database) = &context.database {
+        database.close().await?;
+    }
 
     Ok(())
 }
@@ -2209,6 +3306,133 @@ impl From<StartServerError> for Error {
     }
 }
 
+async fn init_apps(context: &mut ProjectContext) -> cot::Result<()> {
+    let mut apps = std::mem::take(&mut context.apps);
+    for app in &mut apps {
+        info!("Initializing app: {}", app.name());
+
+        app.init(context).await?;
+    }
+    context.apps = apps;
+
+    Ok(())
+}
+
+/// Applies the migrations of all the apps, warns about the models that don't
+/// match them, and creates the default permissions of the admin models.
+///
+/// Destructive migrations are only applied to a database that doesn't have
+/// any migrations applied yet, with a warning. Otherwise, they have to be
+/// applied explicitly with the `migration apply --allow-destructive` command.
+#[cfg(feature = "db")]
+pub(crate) async fn run_migrations(apps: &[Box<dyn App>], database: &Database) -> cot::Result<()> {
+    let migration_engine = migration_engine(apps)?.allow_destructive_on_empty_database(true);
+    migration_engine.run(database).await?;
+    finish_migrations(apps, database, &migration_engine).await
+}
+
+/// Applies the migrations of all the apps like [`run_migrations`], reporting
+/// the progress to the given function.
+///
+/// Destructive migrations are only applied if `allow_destructive` is `true`.
+#[cfg(feature = "db")]
+pub(crate) async fn run_migrations_with_progress<F>(
+    apps: &[Box<dyn App>],
+    database: &Database,
+    allow_destructive: bool,
+    progress: F,
+) -> cot::Result<()>
+where
+    F: FnMut(MigrationProgress<'_>) + Send,
+{
+    let migration_engine = migration_engine(apps)?.allow_destructive(allow_destructive);
+    migration_engine
+        .run_with_progress(database, progress)
+        .await?;
+    finish_migrations(apps, database, &migration_engine).await
+}
+
+/// Warns about the models that don't match the applied migrations, and creates
+/// the default permissions of the admin models.
+#[cfg(feature = "db")]
+async fn finish_migrations(
+    apps: &[Box<dyn App>],
+    database: &Database,
+    migration_engine: &MigrationEngine,
+) -> cot::Result<()> {
+    let models: Vec<_> = apps.iter().flat_map(|app| app.models()).collect();
+    for drift in migration_engine.check_models(&models) {
+        tracing::warn!(
+            "The models don't match the migrations: {drift}; you may need to generate new migrations"
+        );
+    }
+
+    crate::auth::db::create_default_permissions(apps, database).await?;
+
+    Ok(())
+}
+
+/// Reverts the migrations of all the apps, either the most recently applied
+/// one or all the ones applied after the given migration.
+#[cfg(feature = "db")]
+pub(crate) async fn revert_migrations(
+    apps: &[Box<dyn App>],
+    database: &Database,
+    to: Option<&str>,
+) -> cot::Result<()> {
+    migration_engine(apps)?.revert(database, to).await?;
+
+    Ok(())
+}
+
+/// Returns whether each of the migrations of all the apps has been applied.
+#[cfg(feature = "db")]
+pub(crate) async fn migration_status(
+    apps: &[Box<dyn App>],
+    database: &Database,
+) -> cot::Result<Vec<MigrationStatus>> {
+    Ok(migration_engine(apps)?.status(database).await?)
+}
+
+#[cfg(feature = "db")]
+fn migration_engine(apps: &[Box<dyn App>]) -> cot::Result<MigrationEngine> {
+    let mut migrations: Vec<Box<SyncDynMigration>> = Vec::new();
+    for app in apps {
+        migrations.extend(app.migrations());
+    }
+    Ok(MigrationEngine::new(migrations)?)
+}
+
+fn set_error_page_panic_hook() {
+    let current_hook = std::panic::take_hook();
+    let new_hook = move |hook_info: &std::panic::PanicHookInfo<'_>| {
+        current_hook(hook_info);
+        error_page::error_page_panic_hook(hook_info);
+    };
+    std::panic::set_hook(Box::new(new_hook));
+}
+
+/// Creates the span of the request.
+///
+/// The spans created while handling the request, such as the database
+/// queries, are nested under it. The route name, the response status, and the
+/// ID of the authenticated user are recorded once they are known.
+fn request_span(request: &axum::extract::Request) -> tracing::Span {
+    let span = info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        route = field::Empty,
+        status = field::Empty,
+        user_id = field::Empty,
+        otel.kind = field::Empty,
+        otel.status_code = field::Empty,
+    );
+    #[cfg(feature = "opentelemetry")]
+    crate::telemetry::start_request_span(&span, request.headers());
+    span
+}
+
 fn accepts_html(head: Option<&RequestHead>) -> bool {
     head.and_then(|p| p.headers.get(http::header::ACCEPT))
         .is_some_and(|accept| {
@@ -2326,9 +3550,11 @@ fn request_parts_for_diagnostics(request: Request) -> (Option<RequestHead>, Requ
 fn request_axum_to_cot(
     axum_request: axum::extract::Request,
     context: Arc<ProjectContext>,
+    scheme: ConnectionScheme,
 ) -> Request {
     let mut request = axum_request.map(Body::axum);
     prepare_request(&mut request, context);
+    request.extensions_mut().insert(scheme);
     request
 }
 
@@ -2336,6 +3562,22 @@ pub(crate) fn prepare_request(request: &mut Request, context: Arc<ProjectContext
     request.extensions_mut().insert(context);
 }
 
+/// Passes the request to the handler, catching the panics so that they can be
+/// turned into an error page.
+async fn pass_to_axum_catch_unwind(
+    request: Request,
+    handler: &mut BoxedHandler,
+) -> Result<axum::response::Response, ErrorResponse> {
+    match AssertUnwindSafe(pass_to_axum(request, handler))
+        .catch_unwind()
+        .await
+    {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(error)) => Err(ErrorResponse::ErrorReturned(error)),
+        Err(error) => Err(ErrorResponse::Panic(error)),
+    }
+}
+
 async fn pass_to_axum(
     request: Request,
     handler: &mut BoxedHandler,
@@ -2385,7 +3627,7 @@ mod tests {
     use super::*;
     use crate::StatusCode;
     use crate::auth::UserId;
-    use crate::config::{SecretKey, Timeout};
+    use crate::config::{FeaturesConfig, SecretKey, Timeout};
     use crate::error::handler::{RequestError, RequestOuterError};
     use crate::html::Html;
     use crate::request::extractors::FromRequestHead;
@@ -2431,6 +3673,7 @@ mod tests {
             error_handler: service_fn(|_: Request| async move {
                 Err::<Response, _>(Error::internal("error"))
             }),
+            middlewares: MiddlewareStack::default(),
         };
 
         let root_handler = root_handler_builder
@@ -2450,6 +3693,7 @@ mod tests {
             error_handler: service_fn(|_: Request| async move {
                 Err::<Response, _>(Error::internal("error"))
             }),
+            middlewares: MiddlewareStack::default(),
         };
 
         let root_handler = root_handler_builder
@@ -2469,6 +3713,7 @@ mod tests {
             error_handler: service_fn(|_: Request| async move {
                 Err::<Response, _>(Error::internal("error"))
             }),
+            middlewares: MiddlewareStack::default(),
         };
 
         let root_handler = root_handler_builder
@@ -2529,6 +3774,121 @@ mod tests {
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
+    #[cot::test]
+    async fn boot_invalid_middleware_order() {
+        struct TestProject;
+        impl Project for TestProject {
+            fn middlewares(
+                &self,
+                handler: RootHandlerBuilder,
+                context: &MiddlewareContext,
+            ) -> RootHandler {
+                handler
+                    .middleware(crate::middleware::SessionMiddleware::from_context(context))
+                    .middleware(crate::middleware::AuthMiddleware::new())
+                    .build()
+            }
+        }
+
+        let result = Bootstrapper::new(TestProject)
+            .with_config(ProjectConfig::default())
+            .boot()
+            .await;
+
+        let error = result.err().unwrap();
+        assert!(
+            error
+                .to_string()
+                .starts_with("AuthMiddleware requires SessionMiddleware"),
+            "{error}"
+        );
+    }
+
+    #[cot::test]
+    async fn boot_invalid_third_party_middleware_order() {
+        #[derive(Clone)]
+        struct CartMiddleware;
+        impl<S> Layer<S> for CartMiddleware {
+            type Service = S;
+
+            fn layer(&self, inner: S) -> S {
+                inner
+            }
+        }
+        impl MiddlewareMetadata for CartMiddleware {
+            const NAME: &'static str = "CartMiddleware";
+            const REQUIREMENTS: &'static [crate::middleware::MiddlewareRequirement] =
+                &[crate::middleware::MiddlewareRequirement::Requires(
+                    crate::middleware::SessionMiddleware::NAME,
+                )];
+        }
+
+        struct TestProject;
+        impl Project for TestProject {
+            fn middlewares(
+                &self,
+                handler: RootHandlerBuilder,
+                _context: &MiddlewareContext,
+            ) -> RootHandler {
+                handler.middleware_with_metadata(CartMiddleware).build()
+            }
+        }
+
+        let result = Bootstrapper::new(TestProject)
+            .with_config(ProjectConfig::default())
+            .boot()
+            .await;
+
+        let error = result.err().unwrap();
+        assert!(
+            error
+                .to_string()
+                .starts_with("CartMiddleware requires SessionMiddleware"),
+            "{error}"
+        );
+    }
+
+    #[cot::test]
+    async fn boot_path_param_mismatch() {
+        async fn todo(_todo_id: crate::request::extractors::Path<i32>) -> Html {
+            unimplemented!()
+        }
+
+        struct PathApp;
+        impl App for PathApp {
+            fn name(&self) -> &'static str {
+                "todos"
+            }
+
+            fn router(&self) -> Router {
+                Router::with_urls([
+                    Route::with_handler("/{todo_id}", todo),
+                    Route::with_handler("/{list_id}/{todo_id}", todo),
+                ])
+            }
+        }
+
+        struct TestProject;
+        impl Project for TestProject {
+            fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+                apps.register_with_views(PathApp, "/todos");
+            }
+        }
+
+        let result = Bootstrapper::new(TestProject)
+            .with_config(ProjectConfig::default())
+            .boot()
+            .await;
+
+        let error = result.err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "route path parameters don't match the `Path` extractors of their handlers:\n\
+            route `/todos/{list_id}/{todo_id}` has 2 parameter(s) (list_id, todo_id), but its \
+            handler expects 1 parameter(s)"
+        );
+    }
+
     #[test]
     fn project_default_config() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -2563,6 +3923,51 @@ mod tests {
         assert!(apps.apps.is_empty());
     }
 
+    #[test]
+    fn app_builder_when() {
+        struct TestApp(&'static str);
+        impl App for TestApp {
+            fn name(&self) -> &str {
+                self.0
+            }
+        }
+
+        let config = ProjectConfig::builder()
+            .debug(false)
+            .features(FeaturesConfig::from_iter([("api_docs", true)]))
+            .build();
+        let context = ProjectContext::new().with_config(config);
+        let mut apps = AppBuilder::new();
+
+        apps.when(context.config().debug)
+            .register(TestApp("debug"))
+            .register_with_views(TestApp("debug_views"), "/debug");
+        apps.when(context.is_feature_enabled("api_docs"))
+            .register_with_views(TestApp("api_docs"), "/docs");
+        apps.when(context.is_feature_enabled("unknown"))
+            .register(TestApp("unknown"));
+
+        let names: Vec<_> = apps.apps.iter().map(|app| app.name()).collect();
+        assert_eq!(names, ["api_docs"]);
+        assert_eq!(apps.urls.len(), 1);
+    }
+
+    #[test]
+    fn project_context_urls() {
+        async fn index() -> &'static str {
+            "Hello world!"
+        }
+
+        let router = Router::with_urls([Route::with_handler_and_name("/about", index, "about")]);
+        let context = ProjectContext::new()
+            .with_config(ProjectConfig::default())
+            .with_apps(vec![], Arc::new(router));
+
+        let urls = context.urls();
+        assert_eq!(urls.app_name(), None);
+        assert_eq!(crate::reverse!(urls, "about").unwrap(), "/about");
+    }
+
     #[cot::test]
     async fn default_auth_backend() {
         let cache_memory = Cache::new(
@@ -2614,6 +4019,27 @@ mod tests {
         assert_eq!(bootstrapper.context().router.routes().len(), 1);
     }
 
+    #[cfg(feature = "tasks")]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+    )]
+    async fn run_worker_stops_on_shutdown() {
+        struct TestProject;
+        impl Project for TestProject {}
+
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(ProjectConfig::default())
+            .boot()
+            .await
+            .unwrap();
+
+        run_worker_with_shutdown(bootstrapper, std::future::ready(()))
+            .await
+            .unwrap();
+    }
+
     #[cot::test]
     async fn build_custom_error_page_poll_ready_failure() {
         #[derive(Clone)]
@@ -2739,4 +4165,154 @@ mod tests {
         let request_error = head.extensions.get::<RequestOuterError>().unwrap();
         assert_eq!(request_error.to_string(), "test error");
     }
+
+    #[cfg(unix)]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+    )]
+    async fn run_at_unix_socket() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let socket_path = temp_dir.path().join("cot.sock");
+        drop(bind_unix(&socket_path).unwrap());
+        // the socket left over by the previous listener is replaced
+        let listener = bind_unix(&socket_path).unwrap();
+
+        let bootstrapper = Bootstrapper::new(TestProject)
+            .with_config(ProjectConfig::default())
+            .boot()
+            .await
+            .unwrap();
+        let (send, recv) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(run_at_unix_with_shutdown(
+            bootstrapper,
+            listener,
+            async move {
+                recv.await.unwrap();
+            },
+        ));
+
+        let mut stream = tokio::net::UnixStream::connect(&socket_path).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+
+        send.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    mod destructive_migrations {
+        use super::*;
+        use crate::db::migrations::{Field, Migration, MigrationDependency, Operation};
+        use crate::db::{DatabaseField, Identifier};
+
+        struct CreateModelMigration;
+
+        impl Migration for CreateModelMigration {
+            const APP_NAME: &'static str = "migrations";
+            const MIGRATION_NAME: &'static str = "m_0001_initial";
+            const DEPENDENCIES: &'static [MigrationDependency] = &[];
+            const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+                .table_name(Identifier::new("migrations__todo"))
+                .fields(&[
+                    Field::new(Identifier::new("id"), <i32 as DatabaseField>::TYPE)
+                        .primary_key()
+                        .auto(),
+                    Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+                ])
+                .build()];
+        }
+
+        struct RemoveFieldMigration;
+
+        impl Migration for RemoveFieldMigration {
+            const APP_NAME: &'static str = "migrations";
+            const MIGRATION_NAME: &'static str = "m_0002_remove_title";
+            const DEPENDENCIES: &'static [MigrationDependency] = &[MigrationDependency::migration(
+                "migrations",
+                "m_0001_initial",
+            )];
+            const OPERATIONS: &'static [Operation] = &[Operation::remove_field()
+                .table_name(Identifier::new("migrations__todo"))
+                .field(Field::new(
+                    Identifier::new("title"),
+                    <String as DatabaseField>::TYPE,
+                ))
+                .build()];
+        }
+
+        struct MigrationsApp {
+            remove_field: bool,
+        }
+
+        impl App for MigrationsApp {
+            fn name(&self) -> &'static str {
+                "migrations"
+            }
+
+            fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
+                let mut migrations =
+                    crate::db::migrations::wrap_migrations(&[&CreateModelMigration]);
+                if self.remove_field {
+                    migrations.extend(crate::db::migrations::wrap_migrations(&[
+                        &RemoveFieldMigration,
+                    ]));
+                }
+                migrations
+            }
+        }
+
+        fn apps(remove_field: bool) -> Vec<Box<dyn App>> {
+            vec![Box::new(MigrationsApp { remove_field })]
+        }
+
+        #[cot::test]
+        #[cfg_attr(
+            miri,
+            ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+        )]
+        async fn run_migrations_destructive_on_empty_database() {
+            let database = Database::new("sqlite::memory:").await.unwrap();
+
+            run_migrations(&apps(true), &database).await.unwrap();
+        }
+
+        #[cot::test]
+        #[cfg_attr(
+            miri,
+            ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+        )]
+        async fn run_migrations_destructive_on_existing_database() {
+            let database = Database::new("sqlite::memory:").await.unwrap();
+            run_migrations(&apps(false), &database).await.unwrap();
+
+            let error = run_migrations(&apps(true), &database).await.unwrap_err();
+
+            assert!(error.to_string().contains("m_0002_remove_title"), "{error}");
+            run_migrations_with_progress(&apps(true), &database, true, |_| {})
+                .await
+                .unwrap();
+        }
+
+        #[cot::test]
+        #[cfg_attr(
+            miri,
+            ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+        )]
+        async fn run_migrations_with_progress_destructive_on_empty_database() {
+            let database = Database::new("sqlite::memory:").await.unwrap();
+
+            let result = run_migrations_with_progress(&apps(true), &database, false, |_| {}).await;
+
+            assert!(result.is_err());
+        }
+    }
 }
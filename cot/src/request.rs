@@ -17,7 +17,9 @@ use std::sync::Arc;
 
 use cot_core::request::{AppName, InvalidContentType, RouteName};
 #[doc(inline)]
-pub use cot_core::request::{PathParams, PathParamsDeserializerError, Request, RequestHead};
+pub use cot_core::request::{
+    ExpectedPathParams, PathParams, PathParamsDeserializerError, Request, RequestHead,
+};
 use http::Extensions;
 
 use crate::Result;
@@ -90,6 +92,107 @@ pub trait RequestExt: private::Sealed {
     #[must_use]
     fn project_config(&self) -> &crate::config::ProjectConfig;
 
+    /// Get the canonical URL configuration of the project.
+    ///
+    /// This is a shorthand for `request.project_config().canonical_url`, and
+    /// is mainly useful for the [`reverse_canonical!`](crate::reverse_canonical)
+    /// macro.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(mut request: Request) -> cot::Result<Response> {
+    ///     let home_url = request.canonical_url_config().url_for("/");
+    ///     // ... do something with the URL
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    fn canonical_url_config(&self) -> &crate::config::CanonicalUrlConfig {
+        &self.project_config().canonical_url
+    }
+
+    /// Get the handle used to enqueue background tasks.
+    ///
+    /// This is a shorthand for `request.context().tasks()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(mut request: Request) -> cot::Result<Response> {
+    ///     let tasks = request.tasks();
+    ///     // ... enqueue some tasks
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[cfg(feature = "tasks")]
+    #[must_use]
+    fn tasks(&self) -> &crate::tasks::Tasks {
+        self.context().tasks()
+    }
+
+    /// Get the email sender of the project.
+    ///
+    /// This is a shorthand for `request.context().email()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::common_types::Email;
+    /// use cot::email::EmailMessage;
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(mut request: Request) -> cot::Result<Response> {
+    ///     let message = EmailMessage::builder()
+    ///         .from(Email::try_from("no-reply@example.com").unwrap())
+    ///         .to(vec![Email::try_from("user@example.com").unwrap()])
+    ///         .subject("Greetings")
+    ///         .body("Hello from cot!")
+    ///         .build()?;
+    ///     request.email().send(message).await?;
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[cfg(feature = "email")]
+    #[must_use]
+    fn email(&self) -> &crate::email::Email {
+        self.context().email()
+    }
+
+    /// Get the handle used to add [flash messages](crate::messages) that will
+    /// be displayed to the user on the next rendered page.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`SessionMiddleware`](crate::middleware::SessionMiddleware)
+    /// is not enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    ///
+    /// async fn my_handler(mut request: Request) -> cot::Result<Response> {
+    ///     request.messages().success("Saved!").await?;
+    ///     // ... redirect to another page
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    fn messages(&self) -> crate::messages::Messages {
+        crate::messages::Messages::new(
+            crate::session::Session::from_extensions(self.extensions()).clone(),
+        )
+    }
+
     /// Get the router.
     ///
     /// # Examples
@@ -357,6 +460,178 @@ impl RequestExt for RequestHead {
     }
 }
 
+/// Parameters captured from the host the request was sent to.
+///
+/// The parameters are only available in the handlers of the routers
+/// constrained to a host pattern using [`Router::with_host`], such as
+/// `{tenant}.example.com`. This is useful for multi-tenant apps, where the
+/// subdomain identifies the tenant. In the other handlers, the parameters are
+/// empty.
+///
+/// # Examples
+///
+/// ```
+/// use cot::html::Html;
+/// use cot::request::HostParams;
+/// use cot::router::{Route, Router};
+/// use cot::test::TestRequestBuilder;
+///
+/// async fn dashboard(host_params: HostParams) -> cot::Result<Html> {
+///     let tenant: String = host_params.parse()?;
+///     Ok(Html::new(format!("Dashboard of {tenant}")))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let router = Router::with_host(
+///     "{tenant}.example.com",
+///     Router::with_urls([Route::with_handler("/", dashboard)]),
+/// );
+///
+/// let request = TestRequestBuilder::get("https://acme.example.com/").build();
+/// let response = router.handle(request).await?;
+/// assert_eq!(response.into_body().into_bytes().await?, "Dashboard of acme");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HostParams {
+    params: PathParams,
+}
+
+impl HostParams {
+    pub(crate) fn new(params: PathParams) -> Self {
+        Self { params }
+    }
+
+    /// Returns the value of a host parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::Html;
+    /// use cot::request::HostParams;
+    ///
+    /// async fn dashboard(host_params: HostParams) -> Html {
+    ///     let tenant = host_params.get("tenant").unwrap_or("default");
+    ///     Html::new(format!("Dashboard of {tenant}"))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.params.get(name)
+    }
+
+    /// Iterates over the host parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::HostParams;
+    ///
+    /// let host_params = HostParams::default();
+    /// for (name, value) in host_params.iter() {
+    ///     println!("{}: {}", name, value);
+    /// }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter()
+    }
+
+    /// Returns `true` if there are no host parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::HostParams;
+    ///
+    /// let host_params = HostParams::default();
+    /// assert!(host_params.is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Deserializes the host parameters into a type `T` implementing
+    /// `serde::Deserialize`, the same way as [`PathParams::parse`].
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the host parameters could not be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::Html;
+    /// use cot::request::HostParams;
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Tenant {
+    ///     tenant: String,
+    ///     region: String,
+    /// }
+    ///
+    /// async fn dashboard(host_params: HostParams) -> cot::Result<Html> {
+    ///     let Tenant { tenant, region } = host_params.parse()?;
+    ///     Ok(Html::new(format!("Dashboard of {tenant} ({region})")))
+    /// }
+    /// ```
+    pub fn parse<'de, T: serde::Deserialize<'de>>(
+        &'de self,
+    ) -> std::result::Result<T, PathParamsDeserializerError> {
+        self.params.parse()
+    }
+}
+
+/// The scheme of the connection a request was received on.
+///
+/// Cot inserts this into the extensions of every request it serves:
+/// [`ConnectionScheme::Https`] if the connection was accepted by
+/// [`run_at_tls`](crate::project::run_at_tls) (or [`run`](crate::run) with
+/// TLS enabled in the config), and [`ConnectionScheme::Http`] otherwise. Note
+/// that this describes the connection to Cot itself; when running behind a
+/// reverse proxy terminating TLS, the scheme used by the client is usually
+/// passed in the `X-Forwarded-Proto` or `Forwarded` header instead.
+///
+/// # Examples
+///
+/// ```
+/// use cot::request::{ConnectionScheme, Request};
+///
+/// fn is_encrypted(request: &Request) -> bool {
+///     request.extensions().get::<ConnectionScheme>() == Some(&ConnectionScheme::Https)
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConnectionScheme {
+    /// A plain HTTP connection.
+    Http,
+    /// A connection encrypted with TLS.
+    Https,
+}
+
+impl ConnectionScheme {
+    /// Returns the scheme as it appears in the URLs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::ConnectionScheme;
+    ///
+    /// assert_eq!(ConnectionScheme::Http.as_str(), "http");
+    /// assert_eq!(ConnectionScheme::Https.as_str(), "https");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
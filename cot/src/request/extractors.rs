@@ -69,14 +69,20 @@ pub use cot_core::request::extractors::FromRequest;
 /// See [`crate::request::extractors`] documentation for more information about
 pub use cot_core::request::extractors::FromRequestHead;
 #[doc(inline)]
-pub use cot_core::request::extractors::{Path, UrlQuery};
+pub use cot_core::request::extractors::{Extension, Path, UrlQuery};
 
 use crate::Body;
 use crate::auth::Auth;
+#[cfg(feature = "json")]
+use crate::form::FormFieldValidationError;
 use crate::form::{Form, FormResult};
-use crate::request::{Request, RequestExt, RequestHead};
+use crate::request::{HostParams, Request, RequestExt, RequestHead};
+use crate::response::ETag;
 use crate::router::Urls;
 use crate::session::Session;
+use crate::utils::accept_header_parser::AcceptHeaderParser;
+#[cfg(feature = "json")]
+use crate::validation::{Validate, ValidationErrors};
 
 impl FromRequestHead for Urls {
     async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
@@ -84,6 +90,148 @@ impl FromRequestHead for Urls {
     }
 }
 
+impl FromRequestHead for HostParams {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        Ok(head.extensions.get::<Self>().cloned().unwrap_or_default())
+    }
+}
+
+/// An extractor that gets the `Accept` header of the request, which lists the
+/// media types the client is willing to receive.
+///
+/// This is mainly used to build a [`Negotiate`](crate::response::Negotiate)
+/// response, which renders a value in the format preferred by the client. If
+/// the request doesn't contain the header, any media type is acceptable.
+///
+/// # Examples
+///
+/// ```
+/// use cot::html::Html;
+/// use cot::request::extractors::Accept;
+///
+/// async fn my_handler(accept: Accept) -> Html {
+///     if accept.accepts("text/html") {
+///         Html::new("<p>Hello!</p>")
+///     } else {
+///         Html::new("Hello!")
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Accept(AcceptHeaderParser);
+
+impl Accept {
+    /// Returns `true` if the client accepts the given media type, such as
+    /// `application/json`. Wildcards in the header, like `text/*` or `*/*`,
+    /// are taken into account.
+    ///
+    /// Returns `false` if the media type is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::json::Json;
+    /// use cot::request::extractors::Accept;
+    /// use cot::response::{IntoResponse, Response};
+    ///
+    /// async fn my_handler(accept: Accept) -> cot::Result<Response> {
+    ///     if accept.accepts("application/json") {
+    ///         Json(vec![1, 2, 3]).into_response()
+    ///     } else {
+    ///         "1, 2, 3".into_response()
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn accepts(&self, media_type: &str) -> bool {
+        media_type
+            .parse()
+            .is_ok_and(|media_type| self.quality(&media_type) > 0.0)
+    }
+
+    pub(crate) fn quality(&self, media_type: &mime::Mime) -> f32 {
+        self.0.quality(media_type)
+    }
+}
+
+impl FromRequestHead for Accept {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let accept = head
+            .headers
+            .get(http::header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .unwrap_or_default();
+        Ok(Self(AcceptHeaderParser::parse(accept)))
+    }
+}
+
+/// An extractor that gets the canonical URL of the current page.
+///
+/// The URL is generated from the path of the request according to the
+/// [`CanonicalUrlConfig`](crate::config::CanonicalUrlConfig) of the project;
+/// the query string is dropped. It is mainly useful for emitting the
+/// `<link rel="canonical">` tag in templates, so that search engines don't
+/// index the same page under multiple URLs.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::{CanonicalUrlConfig, ProjectConfig};
+/// use cot::html::Html;
+/// use cot::request::extractors::CanonicalUrl;
+/// use cot::test::TestRequestBuilder;
+///
+/// async fn my_handler(canonical_url: CanonicalUrl) -> Html {
+///     Html::new(format!(
+///         "<html><head><link rel=\"canonical\" href=\"{canonical_url}\"></head></html>"
+///     ))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// # use cot::RequestHandler;
+/// let config = ProjectConfig::builder()
+///     .canonical_url(CanonicalUrlConfig::builder().host("example.com").build())
+///     .build();
+/// let request = TestRequestBuilder::get("/blog/?page=2")
+///     .config(config)
+///     .build();
+///
+/// assert_eq!(
+///     my_handler.handle(request).await?.into_body().into_bytes().await?,
+///     "<html><head><link rel=\"canonical\" href=\"https://example.com/blog/\"></head></html>"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, derive_more::Display)]
+pub struct CanonicalUrl(String);
+
+impl CanonicalUrl {
+    /// Returns the canonical URL as a string slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::Html;
+    /// use cot::request::extractors::CanonicalUrl;
+    ///
+    /// async fn my_handler(canonical_url: CanonicalUrl) -> Html {
+    ///     Html::new(canonical_url.as_str())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromRequestHead for CanonicalUrl {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        Ok(Self(head.canonical_url_config().url_for(head.uri.path())))
+    }
+}
+
 /// An extractor that gets the request body as form data and deserializes it
 /// into a type `F` implementing [`Form`].
 ///
@@ -139,6 +287,93 @@ impl<F: Form> FromRequest for RequestForm<F> {
     }
 }
 
+/// An extractor that gets the request body as JSON, deserializes it into a
+/// type `D`, and validates it with the [`Validate`] trait.
+///
+/// Unlike [`Json`](crate::json::Json), the deserialization and validation
+/// errors are not returned as errors of the extractor. Instead, they are
+/// returned as [`ValidationErrors`] in the extracted value, keyed by the path
+/// of the invalid field, so that the handler can return them as a `422
+/// Unprocessable Entity` JSON response.
+///
+/// # Errors
+///
+/// Throws an error if the content type is not `application/json`. Throws an
+/// error if the request body could not be read or is not valid JSON.
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::FormFieldValidationError;
+/// use cot::json::Json;
+/// use cot::request::extractors::ValidatedJson;
+/// use cot::response::{IntoResponse, Response};
+/// use cot::test::TestRequestBuilder;
+/// use cot::validation::{Validate, ValidationErrors};
+/// use cot::{RequestHandler, StatusCode};
+///
+/// #[derive(serde::Deserialize)]
+/// struct NewUser {
+///     username: String,
+/// }
+///
+/// impl Validate for NewUser {
+///     fn validate(&self) -> Result<(), ValidationErrors> {
+///         let mut errors = ValidationErrors::new();
+///         if self.username.is_empty() {
+///             errors.add_field_error("username", FormFieldValidationError::Required);
+///         }
+///         errors.into_result()
+///     }
+/// }
+///
+/// async fn create_user(ValidatedJson(user): ValidatedJson<NewUser>) -> cot::Result<Response> {
+///     match user {
+///         Ok(user) => Json(format!("Hello {}!", user.username)).into_response(),
+///         Err(errors) => errors.into_response(),
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let request = TestRequestBuilder::post("/")
+///     .json(&serde_json::json!({"username": ""}))
+///     .build();
+///
+/// let response = create_user.handle(request).await?;
+/// assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub struct ValidatedJson<D>(pub Result<D, ValidationErrors>);
+
+#[cfg(feature = "json")]
+impl<D: serde::de::DeserializeOwned + Validate> FromRequest for ValidatedJson<D> {
+    async fn from_request(head: &RequestHead, body: Body) -> cot::Result<Self> {
+        let crate::json::Json(value) =
+            crate::json::Json::<serde_json::Value>::from_request(head, body).await?;
+
+        let data: D = match serde_path_to_error::deserialize(value) {
+            Ok(data) => data,
+            Err(error) => {
+                let message =
+                    FormFieldValidationError::with_code("invalid_json", error.inner().to_string());
+                let mut errors = ValidationErrors::new();
+                if error.path().iter().next().is_some() {
+                    errors.add_field_error(error.path().to_string(), message);
+                } else {
+                    errors.add_non_field_error(message);
+                }
+                return Ok(Self(Err(errors)));
+            }
+        };
+
+        Ok(Self(data.validate().map(|()| data)))
+    }
+}
+
 #[cfg(feature = "db")]
 impl FromRequestHead for crate::db::Database {
     async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
@@ -146,6 +381,207 @@ impl FromRequestHead for crate::db::Database {
     }
 }
 
+/// An extractor that loads a model instance from the database, using the
+/// primary key from the request path.
+///
+/// The primary key is read from the path parameter called `id`. If there is no
+/// such parameter, but the route has exactly one path parameter, that one is
+/// used instead.
+///
+/// This removes the need to load the object and check whether it exists in
+/// every detail, update or delete handler.
+///
+/// # Errors
+///
+/// Throws a [`NotFound`](crate::error::NotFound) error (which results in a 404
+/// Not Found response) if the primary key in the path is invalid or if there
+/// is no object with the given primary key in the database.
+///
+/// Throws an error if the route has no suitable path parameter or if the
+/// database query fails.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, model};
+/// use cot::html::Html;
+/// use cot::request::extractors::ExistingModel;
+/// use cot::router::{Route, Router};
+///
+/// #[model]
+/// struct TodoItem {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// async fn todo_detail(ExistingModel(todo): ExistingModel<TodoItem>) -> Html {
+///     Html::new(format!("Todo: {}", todo.title))
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler_and_name(
+///     "/todos/{id}/",
+///     todo_detail,
+///     "todo_detail",
+/// )]);
+/// ```
+#[cfg(feature = "db")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistingModel<M>(pub M);
+
+#[cfg(feature = "db")]
+impl<M> FromRequestHead for ExistingModel<M>
+where
+    M: crate::db::Model,
+    M::PrimaryKey: std::str::FromStr + Send,
+{
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let params = head
+            .extensions
+            .get::<crate::request::PathParams>()
+            .expect("PathParams extension missing");
+        let id = match params.get("id") {
+            Some(id) => id,
+            None if params.len() == 1 => params.get_index(0).expect("one param is present"),
+            None => return Err(MissingPrimaryKeyParam.into()),
+        };
+
+        let pk = id.parse::<M::PrimaryKey>().map_err(|_| {
+            crate::error::NotFound::with_message(format!("invalid object ID: `{id}`"))
+        })?;
+        let database = head.context().database();
+        let object = M::get_by_primary_key(database, pk).await?.ok_or_else(|| {
+            crate::error::NotFound::with_message(format!("object with ID `{id}` not found"))
+        })?;
+
+        Ok(Self(object))
+    }
+}
+
+#[cfg(feature = "db")]
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "could not find the primary key in the path; the route must have a parameter \
+    called `id` or exactly one parameter"
+)]
+struct MissingPrimaryKeyParam;
+#[cfg(feature = "db")]
+impl_into_cot_error!(MissingPrimaryKeyParam);
+
+/// An extractor that gets the `If-Match` header of the request, used for
+/// optimistic concurrency control.
+///
+/// Use this extractor in handlers that modify a resource (e.g. `PUT`, `PATCH`
+/// or `DELETE` API routes) and call [`IfMatch::check`] with the current
+/// [`ETag`] of the resource before modifying it. This prevents clients from
+/// overwriting changes made by someone else since they fetched the resource.
+///
+/// # Errors
+///
+/// Throws an error resulting in a 428 Precondition Required response if the
+/// request doesn't have an `If-Match` header.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, Database, Model, model};
+/// use cot::html::Html;
+/// use cot::request::extractors::{ExistingModel, IfMatch};
+/// use cot::response::ETag;
+///
+/// #[model]
+/// struct TodoItem {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+///     version: i64,
+/// }
+///
+/// async fn todo_update(
+///     db: Database,
+///     if_match: IfMatch,
+///     ExistingModel(mut todo): ExistingModel<TodoItem>,
+/// ) -> cot::Result<Html> {
+///     if_match.check(&ETag::from_version(todo.version))?;
+///
+///     todo.version += 1;
+///     todo.save(&db).await?;
+///     Ok(Html::new("Updated"))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IfMatch(http::HeaderValue);
+
+impl IfMatch {
+    /// Returns `true` if the `If-Match` header matches the given entity tag.
+    ///
+    /// The header matches if it's `*` or if it contains an entity tag equal
+    /// to the given one, using the strong comparison function. This means a
+    /// weak entity tag never matches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::RequestExt;
+    /// use cot::request::extractors::IfMatch;
+    /// use cot::response::ETag;
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let mut request = TestRequestBuilder::get("/").build();
+    /// request
+    ///     .headers_mut()
+    ///     .insert(cot::http::header::IF_MATCH, "\"v1\"".parse().unwrap());
+    ///
+    /// let if_match: IfMatch = request.extract_from_head().await?;
+    /// assert!(if_match.matches(&ETag::strong("v1")));
+    /// assert!(!if_match.matches(&ETag::strong("v2")));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn matches(&self, etag: &ETag) -> bool {
+        etag.matches_header(&self.0, false)
+    }
+
+    /// Checks that the `If-Match` header matches the given entity tag.
+    ///
+    /// See [`IfMatch::matches`] for details on how the header is matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error resulting in a 412 Precondition Failed response if
+    /// the header doesn't match the entity tag.
+    pub fn check(&self, etag: &ETag) -> cot::Result<()> {
+        if self.matches(etag) {
+            Ok(())
+        } else {
+            Err(PreconditionFailed.into())
+        }
+    }
+}
+
+impl FromRequestHead for IfMatch {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let value = head
+            .headers
+            .get(http::header::IF_MATCH)
+            .ok_or(PreconditionRequired)?;
+        Ok(Self(value.clone()))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("the `If-Match` header is required for this request")]
+struct PreconditionRequired;
+impl_into_cot_error!(PreconditionRequired, PRECONDITION_REQUIRED);
+
+#[derive(Debug, thiserror::Error)]
+#[error("the `If-Match` header doesn't match the current version of the resource")]
+struct PreconditionFailed;
+impl_into_cot_error!(PreconditionFailed, PRECONDITION_FAILED);
+
 #[cfg(feature = "cache")]
 impl FromRequestHead for crate::cache::Cache {
     async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
@@ -160,6 +596,20 @@ impl FromRequestHead for crate::email::Email {
     }
 }
 
+#[cfg(feature = "storage")]
+impl FromRequestHead for crate::storage::FileStorage {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        Ok(head.context().storage().clone())
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl FromRequestHead for crate::http_client::HttpClient {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        Ok(head.context().http_client().with_headers_from(head))
+    }
+}
+
 /// An extractor that allows you to access static files metadata (e.g., their
 /// URLs).
 ///
@@ -290,8 +740,8 @@ impl FromRequestHead for Auth {
 
 #[cfg(test)]
 mod tests {
-    use cot_core::Method;
     use cot_core::html::Html;
+    use cot_core::{Method, StatusCode};
 
     use super::*;
     use crate::request::extractors::FromRequest;
@@ -318,6 +768,25 @@ mod tests {
         assert!(reverse!(urls, "test_route").is_ok());
     }
 
+    #[cot::test]
+    async fn canonical_url_extraction() {
+        let config = crate::config::ProjectConfig::builder()
+            .canonical_url(
+                crate::config::CanonicalUrlConfig::builder()
+                    .host("example.com")
+                    .trailing_slash(crate::config::TrailingSlash::Never)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::get("/blog/?page=2")
+            .config(config)
+            .build();
+
+        let canonical_url: CanonicalUrl = request.extract_from_head().await.unwrap();
+
+        assert_eq!(canonical_url.as_str(), "https://example.com/blog");
+    }
+
     #[cot::test]
     async fn method_extraction() {
         let mut request = TestRequestBuilder::get("/test/").build();
@@ -351,6 +820,92 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn validated_json() {
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        struct MyData {
+            hello: String,
+        }
+
+        impl Validate for MyData {
+            fn validate(&self) -> Result<(), ValidationErrors> {
+                let mut errors = ValidationErrors::new();
+                if self.hello != "world" {
+                    errors.add_field_error(
+                        "hello",
+                        FormFieldValidationError::invalid_value(self.hello.clone()),
+                    );
+                }
+                errors.into_result()
+            }
+        }
+
+        async fn extract(data: serde_json::Value) -> Result<MyData, ValidationErrors> {
+            let request = TestRequestBuilder::post("/").json(&data).build();
+            let (head, body) = request.into_parts();
+            let ValidatedJson(result) = ValidatedJson::from_request(&head, body).await.unwrap();
+            result
+        }
+
+        assert_eq!(
+            extract(serde_json::json!({"hello": "world"}))
+                .await
+                .unwrap(),
+            MyData {
+                hello: "world".to_string(),
+            }
+        );
+
+        let errors = extract(serde_json::json!({"hello": "there"}))
+            .await
+            .unwrap_err();
+        assert_eq!(errors.field_errors("hello")[0].code(), "invalid");
+
+        let errors = extract(serde_json::json!({"hello": 1})).await.unwrap_err();
+        assert_eq!(errors.field_errors("hello")[0].code(), "invalid_json");
+
+        let errors = extract(serde_json::json!({})).await.unwrap_err();
+        assert_eq!(errors.non_field_errors()[0].code(), "invalid_json");
+    }
+
+    #[cot::test]
+    async fn if_match_extraction() {
+        let mut request = TestRequestBuilder::get("/").build();
+        request.headers_mut().insert(
+            http::header::IF_MATCH,
+            http::HeaderValue::from_static(r#""v1", "v2""#),
+        );
+
+        let if_match: IfMatch = request.extract_from_head().await.unwrap();
+
+        assert!(if_match.check(&ETag::strong("v2")).is_ok());
+        let error = if_match.check(&ETag::strong("v3")).unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::PRECONDITION_FAILED);
+        assert!(!if_match.matches(&ETag::weak("v1")));
+    }
+
+    #[cot::test]
+    async fn if_match_any() {
+        let mut request = TestRequestBuilder::get("/").build();
+        request
+            .headers_mut()
+            .insert(http::header::IF_MATCH, http::HeaderValue::from_static("*"));
+
+        let if_match: IfMatch = request.extract_from_head().await.unwrap();
+
+        assert!(if_match.matches(&ETag::from_version(1)));
+    }
+
+    #[cot::test]
+    async fn if_match_missing() {
+        let mut request = TestRequestBuilder::get("/").build();
+
+        let error = request.extract_from_head::<IfMatch>().await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::PRECONDITION_REQUIRED);
+    }
+
     #[cfg(feature = "db")]
     #[cot::test]
     #[cfg_attr(
@@ -386,4 +941,15 @@ mod tests {
         let email_service = request.extract_from_head::<crate::email::Email>().await;
         assert!(email_service.is_ok());
     }
+
+    #[cfg(feature = "storage")]
+    #[cot::test]
+    async fn request_storage() {
+        let mut request = TestRequestBuilder::get("/").build();
+
+        let storage = request
+            .extract_from_head::<crate::storage::FileStorage>()
+            .await;
+        assert!(storage.is_ok());
+    }
 }
@@ -0,0 +1,34 @@
+//! HTTP response type and helper methods.
+//!
+//! Cot uses the [`Response`](http::Response) type from the [`http`] crate to
+//! represent outgoing HTTP responses. Most of the time you don't need to build
+//! responses by hand, as anything implementing [`IntoResponse`] can be
+//! returned from a handler.
+//!
+//! On top of the core response types, this module provides the [`ETag`] type
+//! for working with entity tags, the `JsonStream` type for streaming large
+//! JSON arrays, the `Negotiate` type for choosing the response format based on
+//! the `Accept` header, the [`download`] submodule containing helpers for file
+//! downloads and data exports, and the [`multipart`] submodule for streaming
+//! multipart responses.
+
+#[doc(inline)]
+pub use cot_core::response::{
+    IntoResponse, Redirect, Response, ResponseExt, ResponseHead, WithBody, WithContentType,
+    WithExtension, WithHeader, WithStatus,
+};
+
+pub mod download;
+mod etag;
+#[cfg(feature = "json")]
+mod json_stream;
+pub mod multipart;
+#[cfg(feature = "json")]
+mod negotiate;
+
+pub(crate) use etag::is_not_modified;
+pub use etag::{ETag, ETagParseError};
+#[cfg(feature = "json")]
+pub use json_stream::JsonStream;
+#[cfg(feature = "json")]
+pub use negotiate::{Negotiate, Render};
@@ -0,0 +1,1737 @@
+//! Helpers for file download and data export responses.
+//!
+//! This module contains response types that are useful for endpoints that
+//! let users download data, such as files or CSV exports. The responses are
+//! streamed to the client, so the whole file doesn't need to be loaded in
+//! memory before sending it.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::response::download::CsvResponse;
+//!
+//! async fn export_users() -> CsvResponse {
+//!     let users = vec![("1", "Alice"), ("2", "Bob")];
+//!
+//!     CsvResponse::builder()
+//!         .header(["id", "name"])
+//!         .filename("users.csv")
+//!         .rows(users.into_iter().map(|(id, name)| [id, name]))
+//! }
+//! ```
+
+use std::fmt::{Display, Formatter, Write};
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::SystemTime;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use cot_core::error::impl_into_cot_error;
+use derive_more::with_trait::Debug;
+use futures_core::Stream;
+use futures_util::{StreamExt, stream};
+use http::{HeaderValue, header};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt};
+
+use crate::Body;
+use crate::error::NotFound;
+use crate::response::{ETag, IntoResponse, Response, ResponseExt, is_not_modified};
+use crate::utils::http_date::{format_http_date, parse_http_date, truncate_to_seconds};
+
+const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+#[cfg(feature = "xlsx")]
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// The disposition type of a [`ContentDisposition`] header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DispositionType {
+    /// The content should be displayed inside the browser, if possible.
+    Inline,
+    /// The content should be downloaded and saved locally.
+    Attachment,
+}
+
+impl DispositionType {
+    #[must_use]
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Attachment => "attachment",
+        }
+    }
+}
+
+/// A value of the `Content-Disposition` header.
+///
+/// This type takes care of encoding the file name correctly, as described in
+/// [RFC 6266](https://datatracker.ietf.org/doc/html/rfc6266). For file names
+/// that contain non-ASCII characters, an ASCII-only fallback is emitted in the
+/// `filename` parameter and the full name is emitted in the `filename*`
+/// parameter using the
+/// [RFC 5987](https://datatracker.ietf.org/doc/html/rfc5987) encoding.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::download::ContentDisposition;
+///
+/// let disposition = ContentDisposition::attachment("report.csv");
+/// assert_eq!(disposition.to_string(), r#"attachment; filename="report.csv""#);
+///
+/// let disposition = ContentDisposition::attachment("résumé.pdf");
+/// assert_eq!(
+///     disposition.to_string(),
+///     r#"attachment; filename="r_sum_.pdf"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContentDisposition {
+    disposition_type: DispositionType,
+    filename: Option<String>,
+}
+
+impl ContentDisposition {
+    /// Creates a new `Content-Disposition` value telling the browser to
+    /// download the content and save it under the given file name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::ContentDisposition;
+    ///
+    /// let disposition = ContentDisposition::attachment("report.csv");
+    /// ```
+    #[must_use]
+    pub fn attachment<T: Into<String>>(filename: T) -> Self {
+        Self {
+            disposition_type: DispositionType::Attachment,
+            filename: Some(filename.into()),
+        }
+    }
+
+    /// Creates a new `Content-Disposition` value telling the browser to
+    /// display the content inline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::ContentDisposition;
+    ///
+    /// let disposition = ContentDisposition::inline();
+    /// assert_eq!(disposition.to_string(), "inline");
+    /// ```
+    #[must_use]
+    pub fn inline() -> Self {
+        Self {
+            disposition_type: DispositionType::Inline,
+            filename: None,
+        }
+    }
+
+    /// Sets the file name suggested to the browser.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::ContentDisposition;
+    ///
+    /// let disposition = ContentDisposition::inline().with_filename("image.png");
+    /// assert_eq!(disposition.to_string(), r#"inline; filename="image.png""#);
+    /// ```
+    #[must_use]
+    pub fn with_filename<T: Into<String>>(mut self, filename: T) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Returns the disposition type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::{ContentDisposition, DispositionType};
+    ///
+    /// let disposition = ContentDisposition::attachment("report.csv");
+    /// assert_eq!(disposition.disposition_type(), DispositionType::Attachment);
+    /// ```
+    #[must_use]
+    pub fn disposition_type(&self) -> DispositionType {
+        self.disposition_type
+    }
+
+    /// Returns the file name, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::ContentDisposition;
+    ///
+    /// let disposition = ContentDisposition::attachment("report.csv");
+    /// assert_eq!(disposition.filename(), Some("report.csv"));
+    /// ```
+    #[must_use]
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+}
+
+impl From<&ContentDisposition> for HeaderValue {
+    /// Converts the `Content-Disposition` value into a [`HeaderValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::ContentDisposition;
+    /// use http::HeaderValue;
+    ///
+    /// let value = HeaderValue::from(&ContentDisposition::attachment("report.csv"));
+    /// assert_eq!(value, r#"attachment; filename="report.csv""#);
+    /// ```
+    fn from(disposition: &ContentDisposition) -> Self {
+        HeaderValue::try_from(disposition.to_string())
+            .expect("Content-Disposition value should always be a valid header value")
+    }
+}
+
+impl Display for ContentDisposition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.disposition_type.as_str())?;
+
+        if let Some(filename) = &self.filename {
+            let fallback = ascii_filename_fallback(filename);
+            write!(f, "; filename=\"{fallback}\"")?;
+            if fallback != *filename {
+                write!(f, "; filename*=UTF-8''{}", rfc5987_encode(filename))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns a version of the file name that is safe to put inside a quoted
+/// string in an HTTP header.
+fn ascii_filename_fallback(filename: &str) -> String {
+    filename
+        .chars()
+        .map(|ch| match ch {
+            '"' | '\\' => '_',
+            ' '..='~' => ch,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Percent-encodes a value as an RFC 5987 `value-chars` production.
+fn rfc5987_encode(value: &str) -> String {
+    const fn is_attr_char(byte: u8) -> bool {
+        byte.is_ascii_alphanumeric()
+            || matches!(
+                byte,
+                b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+            )
+    }
+
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if is_attr_char(byte) {
+            encoded.push(char::from(byte));
+        } else {
+            write!(encoded, "%{byte:02X}").expect("writing to a String should never fail");
+        }
+    }
+    encoded
+}
+
+/// A streaming CSV response.
+///
+/// The rows are encoded lazily as the response body is being sent, so it's
+/// possible to export large datasets (e.g. coming from a database query)
+/// without buffering the entire file in memory. Fields are quoted as described
+/// in [RFC 4180](https://datatracker.ietf.org/doc/html/rfc4180) when needed.
+///
+/// Use [`CsvResponse::builder`] to customize the header row, the delimiter, or
+/// the file name the browser should use when saving the file.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::download::CsvResponse;
+///
+/// async fn export() -> CsvResponse {
+///     CsvResponse::from_rows([["1", "Alice"], ["2", "Bob"]])
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CsvResponse {
+    body: Body,
+    disposition: Option<ContentDisposition>,
+}
+
+impl CsvResponse {
+    /// Returns a builder that can be used to customize the CSV output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::CsvResponse;
+    ///
+    /// let response = CsvResponse::builder()
+    ///     .header(["id", "name"])
+    ///     .delimiter(b';')
+    ///     .filename("users.csv")
+    ///     .rows([["1", "Alice"]]);
+    /// ```
+    #[must_use]
+    pub fn builder() -> CsvResponseBuilder {
+        CsvResponseBuilder::default()
+    }
+
+    /// Creates a CSV response from an iterator of rows, using the default
+    /// options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::CsvResponse;
+    ///
+    /// let response = CsvResponse::from_rows([["1", "Alice"], ["2", "Bob"]]);
+    /// ```
+    #[must_use]
+    pub fn from_rows<I, R, T>(rows: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        I::IntoIter: Send + 'static,
+        R: IntoIterator<Item = T> + 'static,
+        T: AsRef<str> + 'static,
+    {
+        Self::builder().rows(rows)
+    }
+
+    /// Creates a CSV response from a stream of rows, using the default
+    /// options.
+    ///
+    /// If the stream yields an error, the response body is terminated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::CsvResponse;
+    ///
+    /// let rows = futures_util::stream::iter([
+    ///     Ok::<_, cot::Error>(["1", "Alice"]),
+    ///     Ok(["2", "Bob"]),
+    /// ]);
+    /// let response = CsvResponse::from_stream(rows);
+    /// ```
+    #[must_use]
+    pub fn from_stream<S, R, T>(rows: S) -> Self
+    where
+        S: Stream<Item = crate::Result<R>> + Send + 'static,
+        R: IntoIterator<Item = T> + 'static,
+        T: AsRef<str> + 'static,
+    {
+        Self::builder().stream(rows)
+    }
+}
+
+impl IntoResponse for CsvResponse {
+    fn into_response(self) -> crate::Result<Response> {
+        let mut builder = Response::builder().header(header::CONTENT_TYPE, CSV_CONTENT_TYPE);
+        if let Some(disposition) = &self.disposition {
+            builder = builder.header(header::CONTENT_DISPOSITION, HeaderValue::from(disposition));
+        }
+
+        Ok(builder
+            .body(self.body)
+            .expect("CSV response should always be valid"))
+    }
+}
+
+/// A builder for [`CsvResponse`].
+///
+/// This is returned by [`CsvResponse::builder`].
+#[derive(Debug, Clone)]
+pub struct CsvResponseBuilder {
+    header: Option<Vec<String>>,
+    delimiter: u8,
+    disposition: Option<ContentDisposition>,
+}
+
+impl Default for CsvResponseBuilder {
+    fn default() -> Self {
+        Self {
+            header: None,
+            delimiter: b',',
+            disposition: None,
+        }
+    }
+}
+
+impl CsvResponseBuilder {
+    /// Sets the header row that is emitted before any of the data rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::CsvResponse;
+    ///
+    /// let response = CsvResponse::builder()
+    ///     .header(["id", "name"])
+    ///     .rows([["1", "Alice"]]);
+    /// ```
+    pub fn header<I, T>(&mut self, header: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.header = Some(header.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the field delimiter. The default is a comma (`,`).
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the delimiter is a double quote, a carriage
+    /// return, or a line feed, since these characters have a special meaning
+    /// in CSV.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::CsvResponse;
+    ///
+    /// let response = CsvResponse::builder().delimiter(b';').rows([["1", "Alice"]]);
+    /// ```
+    pub fn delimiter(&mut self, delimiter: u8) -> &mut Self {
+        assert!(
+            !matches!(delimiter, b'"' | b'\r' | b'\n'),
+            "invalid CSV delimiter: {:?}",
+            char::from(delimiter)
+        );
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Makes the browser download the response as an attachment with the
+    /// given file name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::CsvResponse;
+    ///
+    /// let response = CsvResponse::builder()
+    ///     .filename("users.csv")
+    ///     .rows([["1", "Alice"]]);
+    /// ```
+    pub fn filename<T: Into<String>>(&mut self, filename: T) -> &mut Self {
+        self.disposition = Some(ContentDisposition::attachment(filename));
+        self
+    }
+
+    /// Sets the `Content-Disposition` header of the response.
+    ///
+    /// This is useful if you want to display the CSV inline instead of
+    /// downloading it. If you only want to set the file name, see
+    /// [`Self::filename`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::{ContentDisposition, CsvResponse};
+    ///
+    /// let response = CsvResponse::builder()
+    ///     .content_disposition(ContentDisposition::inline())
+    ///     .rows([["1", "Alice"]]);
+    /// ```
+    pub fn content_disposition(&mut self, disposition: ContentDisposition) -> &mut Self {
+        self.disposition = Some(disposition);
+        self
+    }
+
+    /// Builds a [`CsvResponse`] from an iterator of rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::CsvResponse;
+    ///
+    /// let response = CsvResponse::builder().rows([["1", "Alice"], ["2", "Bob"]]);
+    /// ```
+    pub fn rows<I, R, T>(&mut self, rows: I) -> CsvResponse
+    where
+        I: IntoIterator<Item = R>,
+        I::IntoIter: Send + 'static,
+        R: IntoIterator<Item = T> + 'static,
+        T: AsRef<str> + 'static,
+    {
+        self.stream(stream::iter(rows.into_iter().map(Ok)))
+    }
+
+    /// Builds a [`CsvResponse`] from a stream of rows.
+    ///
+    /// If the stream yields an error, the response body is terminated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::CsvResponse;
+    ///
+    /// let rows = futures_util::stream::iter([
+    ///     Ok::<_, cot::Error>(["1", "Alice"]),
+    ///     Ok(["2", "Bob"]),
+    /// ]);
+    /// let response = CsvResponse::builder().header(["id", "name"]).stream(rows);
+    /// ```
+    pub fn stream<S, R, T>(&mut self, rows: S) -> CsvResponse
+    where
+        S: Stream<Item = crate::Result<R>> + Send + 'static,
+        R: IntoIterator<Item = T> + 'static,
+        T: AsRef<str> + 'static,
+    {
+        let delimiter = self.delimiter;
+        let header = self
+            .header
+            .as_ref()
+            .map(|header| encode_csv_record(header, delimiter));
+
+        let rows = rows.map(move |row| row.map(|row| encode_csv_record(row, delimiter)));
+        let body = stream::iter(header.map(Ok)).chain(rows);
+
+        CsvResponse {
+            body: Body::streaming(body),
+            disposition: self.disposition.clone(),
+        }
+    }
+}
+
+/// Encodes a single CSV record, including the trailing line break.
+fn encode_csv_record<R, T>(record: R, delimiter: u8) -> Bytes
+where
+    R: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    let mut buf = BytesMut::new();
+
+    for (index, field) in record.into_iter().enumerate() {
+        if index > 0 {
+            buf.put_u8(delimiter);
+        }
+
+        let field = field.as_ref();
+        let needs_quotes = field
+            .bytes()
+            .any(|byte| matches!(byte, b'"' | b'\r' | b'\n') || byte == delimiter);
+        if needs_quotes {
+            buf.put_u8(b'"');
+            for part in field.split_inclusive('"') {
+                buf.put_slice(part.as_bytes());
+                if part.ends_with('"') {
+                    buf.put_u8(b'"');
+                }
+            }
+            buf.put_u8(b'"');
+        } else {
+            buf.put_slice(field.as_bytes());
+        }
+    }
+
+    buf.put_slice(b"\r\n");
+    buf.freeze()
+}
+
+/// The size of the chunks the files are read in when streaming them.
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A response that streams a file, or any other [`AsyncRead`] source, to the
+/// client.
+///
+/// The response sets the `Content-Type` header based on the file extension,
+/// and can optionally set the `Content-Disposition` header to make the browser
+/// download the file. When the request headers are passed to the builder, it
+/// also handles conditional requests (`If-None-Match`, `If-Modified-Since`)
+/// and range requests (`Range`, `If-Range`), so downloads can be resumed and
+/// media files can be seeked.
+///
+/// Use [`FileResponse::builder`] to create an instance of this type.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cot::request::Request;
+/// use cot::response::download::FileResponse;
+///
+/// async fn download(request: Request) -> cot::Result<FileResponse> {
+///     FileResponse::builder()
+///         .path("media/report.pdf")
+///         .attachment("Quarterly report.pdf")
+///         .request_headers(request.headers())
+///         .build()
+///         .await
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FileResponse {
+    status: http::StatusCode,
+    headers: http::HeaderMap,
+    body: Body,
+}
+
+impl FileResponse {
+    /// Returns a builder for a new file response.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::response::download::FileResponse;
+    ///
+    /// # async fn test() -> cot::Result<()> {
+    /// let response = FileResponse::builder()
+    ///     .path("static/image.png")
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn builder() -> FileResponseBuilder {
+        FileResponseBuilder::default()
+    }
+
+    /// Returns the status code of the response.
+    ///
+    /// This is `200 OK` for regular responses, `206 Partial Content` for range
+    /// requests, `304 Not Modified` for conditional requests where the client
+    /// already has the current version of the file, and `416 Range Not
+    /// Satisfiable` for invalid ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::response::download::FileResponse;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let response = FileResponse::builder()
+    ///     .reader(&b"Hello, world!"[..])
+    ///     .build()
+    ///     .await?;
+    /// assert_eq!(response.status(), StatusCode::OK);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn status(&self) -> http::StatusCode {
+        self.status
+    }
+}
+
+impl IntoResponse for FileResponse {
+    fn into_response(self) -> crate::Result<Response> {
+        let mut response = Response::new(self.body);
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        Ok(response)
+    }
+}
+
+#[derive(Debug)]
+enum FileSource {
+    Path(PathBuf),
+    Reader(#[debug("..")] Pin<Box<dyn AsyncRead + Send>>),
+}
+
+enum OpenedFileSource {
+    File(tokio::fs::File),
+    Reader(Pin<Box<dyn AsyncRead + Send>>),
+}
+
+/// A builder for [`FileResponse`].
+///
+/// This is returned by [`FileResponse::builder`].
+#[derive(Debug, Default)]
+pub struct FileResponseBuilder {
+    source: Option<FileSource>,
+    content_length: Option<u64>,
+    content_type: Option<mime::Mime>,
+    disposition: Option<ContentDisposition>,
+    etag: Option<ETag>,
+    last_modified: Option<SystemTime>,
+    request_headers: http::HeaderMap,
+}
+
+impl FileResponseBuilder {
+    /// Serves the file at the given path.
+    ///
+    /// The content type, length, last modification time, and entity tag are
+    /// determined automatically based on the file, unless set explicitly.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::response::download::FileResponse;
+    ///
+    /// # async fn test() -> cot::Result<()> {
+    /// let response = FileResponse::builder()
+    ///     .path("media/video.mp4")
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.source = Some(FileSource::Path(path.into()));
+        self
+    }
+
+    /// Serves the data read from the given reader.
+    ///
+    /// Since the length of the data can't be determined automatically, it's
+    /// recommended to also call [`Self::content_length`]; otherwise, range
+    /// requests are not supported and the response is sent without the
+    /// `Content-Length` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::FileResponse;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let data = b"Hello, world!";
+    /// let response = FileResponse::builder()
+    ///     .reader(&data[..])
+    ///     .content_length(data.len() as u64)
+    ///     .attachment("hello.txt")
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn reader<R: AsyncRead + Send + 'static>(mut self, reader: R) -> Self {
+        self.source = Some(FileSource::Reader(Box::pin(reader)));
+        self
+    }
+
+    /// Sets the length of the content in bytes.
+    ///
+    /// This only needs to be set when serving data from a
+    /// [reader](Self::reader), as the length of files is determined
+    /// automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::FileResponse;
+    ///
+    /// let builder = FileResponse::builder()
+    ///     .reader(&b"data"[..])
+    ///     .content_length(4);
+    /// ```
+    #[must_use]
+    pub fn content_length(mut self, length: u64) -> Self {
+        self.content_length = Some(length);
+        self
+    }
+
+    /// Sets the content type of the response.
+    ///
+    /// If not set, the content type is guessed based on the file extension of
+    /// the path or the file name passed to [`Self::attachment`], falling back
+    /// to `application/octet-stream`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::FileResponse;
+    ///
+    /// let builder = FileResponse::builder()
+    ///     .reader(&b"{}"[..])
+    ///     .content_type(mime::APPLICATION_JSON);
+    /// ```
+    #[must_use]
+    pub fn content_type(mut self, content_type: mime::Mime) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Makes the browser download the file and save it under the given name.
+    ///
+    /// Non-ASCII file names are fully supported; see [`ContentDisposition`]
+    /// for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::FileResponse;
+    ///
+    /// let builder = FileResponse::builder()
+    ///     .path("media/report-2024.pdf")
+    ///     .attachment("Raport roczny.pdf");
+    /// ```
+    #[must_use]
+    pub fn attachment<T: Into<String>>(mut self, filename: T) -> Self {
+        self.disposition = Some(ContentDisposition::attachment(filename));
+        self
+    }
+
+    /// Makes the browser display the file inline, if possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::FileResponse;
+    ///
+    /// let builder = FileResponse::builder().path("media/image.png").inline();
+    /// ```
+    #[must_use]
+    pub fn inline(mut self) -> Self {
+        self.disposition = Some(ContentDisposition::inline());
+        self
+    }
+
+    /// Sets the `Content-Disposition` header of the response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::{ContentDisposition, FileResponse};
+    ///
+    /// let builder = FileResponse::builder()
+    ///     .path("media/image.png")
+    ///     .content_disposition(ContentDisposition::inline().with_filename("image.png"));
+    /// ```
+    #[must_use]
+    pub fn content_disposition(mut self, disposition: ContentDisposition) -> Self {
+        self.disposition = Some(disposition);
+        self
+    }
+
+    /// Sets the entity tag of the file.
+    ///
+    /// When serving a file from a path, a strong entity tag is generated
+    /// automatically based on the file size and modification time, so that
+    /// it can be used to resume interrupted downloads with `If-Range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    /// use cot::response::download::FileResponse;
+    ///
+    /// let builder = FileResponse::builder()
+    ///     .reader(&b"data"[..])
+    ///     .etag(ETag::strong("v1"));
+    /// ```
+    #[must_use]
+    pub fn etag(mut self, etag: ETag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Sets the last modification time of the file.
+    ///
+    /// When serving a file from a path, this is determined automatically
+    /// based on the file metadata.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::SystemTime;
+    ///
+    /// use cot::response::download::FileResponse;
+    ///
+    /// let builder = FileResponse::builder()
+    ///     .reader(&b"data"[..])
+    ///     .last_modified(SystemTime::now());
+    /// ```
+    #[must_use]
+    pub fn last_modified(mut self, last_modified: SystemTime) -> Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    /// Passes the headers of the request that is being responded to.
+    ///
+    /// This enables handling of conditional requests (`If-None-Match`,
+    /// `If-Modified-Since`) and range requests (`Range`, `If-Range`).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::request::Request;
+    /// use cot::response::download::FileResponse;
+    ///
+    /// async fn download(request: Request) -> cot::Result<FileResponse> {
+    ///     FileResponse::builder()
+    ///         .path("media/video.mp4")
+    ///         .request_headers(request.headers())
+    ///         .build()
+    ///         .await
+    /// }
+    /// ```
+    #[must_use]
+    pub fn request_headers(mut self, headers: &http::HeaderMap) -> Self {
+        self.request_headers.clear();
+        for name in [
+            header::RANGE,
+            header::IF_RANGE,
+            header::IF_NONE_MATCH,
+            header::IF_MODIFIED_SINCE,
+        ] {
+            if let Some(value) = headers.get(&name) {
+                self.request_headers.insert(name, value.clone());
+            }
+        }
+        self
+    }
+
+    /// Builds the response.
+    ///
+    /// When serving a file from a path, this opens the file and reads its
+    /// metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NotFound`] error if the file doesn't exist, and an
+    /// internal error if it can't be opened for other reasons.
+    ///
+    /// # Panics
+    ///
+    /// Panics if neither [`Self::path`] nor [`Self::reader`] was called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::FileResponse;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let response = FileResponse::builder()
+    ///     .reader(&b"Hello, world!"[..])
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn build(self) -> crate::Result<FileResponse> {
+        let source = self
+            .source
+            .expect("either `path` or `reader` must be set on the FileResponseBuilder");
+
+        let mut content_length = self.content_length;
+        let mut etag = self.etag;
+        let mut last_modified = self.last_modified;
+        let mut content_type = self.content_type;
+
+        let source = match source {
+            FileSource::Path(path) => {
+                let file = tokio::fs::File::open(&path)
+                    .await
+                    .map_err(|error| open_file_error(&path, error))?;
+                let metadata = file
+                    .metadata()
+                    .await
+                    .map_err(|error| open_file_error(&path, error))?;
+                if !metadata.is_file() {
+                    return Err(NotFound::with_message(format!(
+                        "`{}` is not a file",
+                        path.display()
+                    ))
+                    .into());
+                }
+
+                content_length.get_or_insert(metadata.len());
+                if let Ok(modified) = metadata.modified() {
+                    last_modified.get_or_insert(modified);
+                    etag.get_or_insert_with(|| file_etag(metadata.len(), modified));
+                }
+                content_type
+                    .get_or_insert_with(|| mime_guess::from_path(&path).first_or_octet_stream());
+
+                OpenedFileSource::File(file)
+            }
+            FileSource::Reader(reader) => OpenedFileSource::Reader(reader),
+        };
+
+        let content_type = content_type.unwrap_or_else(|| {
+            self.disposition
+                .as_ref()
+                .and_then(ContentDisposition::filename)
+                .map_or(mime::APPLICATION_OCTET_STREAM, |filename| {
+                    mime_guess::from_path(filename).first_or_octet_stream()
+                })
+        });
+        let last_modified = last_modified.map(truncate_to_seconds);
+
+        let mut headers = http::HeaderMap::new();
+        if let Some(etag) = &etag {
+            headers.insert(header::ETAG, HeaderValue::from(etag));
+        }
+        if let Some(last_modified) = last_modified {
+            headers.insert(
+                header::LAST_MODIFIED,
+                HeaderValue::try_from(format_http_date(last_modified))
+                    .expect("HTTP date should always be a valid header value"),
+            );
+        }
+
+        if is_not_modified(&self.request_headers, etag.as_ref(), last_modified) {
+            return Ok(FileResponse {
+                status: http::StatusCode::NOT_MODIFIED,
+                headers,
+                body: Body::empty(),
+            });
+        }
+
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::try_from(content_type.to_string())
+                .expect("MIME type should always be a valid header value"),
+        );
+        if let Some(disposition) = &self.disposition {
+            headers.insert(header::CONTENT_DISPOSITION, HeaderValue::from(disposition));
+        }
+
+        let Some(content_length) = content_length else {
+            return Ok(FileResponse {
+                status: http::StatusCode::OK,
+                headers,
+                body: Body::streaming(file_stream(source, 0, None)),
+            });
+        };
+
+        headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        let range = if is_range_applicable(&self.request_headers, etag.as_ref(), last_modified) {
+            self.request_headers
+                .get(header::RANGE)
+                .and_then(|range| range.to_str().ok())
+                .and_then(|range| parse_range(range, content_length))
+        } else {
+            None
+        };
+
+        Ok(ranged_response(source, headers, content_length, range))
+    }
+}
+
+fn ranged_response(
+    source: OpenedFileSource,
+    mut headers: http::HeaderMap,
+    content_length: u64,
+    range: Option<ByteRange>,
+) -> FileResponse {
+    match range {
+        None => {
+            headers.insert(header::CONTENT_LENGTH, HeaderValue::from(content_length));
+            FileResponse {
+                status: http::StatusCode::OK,
+                headers,
+                body: Body::streaming(file_stream(source, 0, Some(content_length))),
+            }
+        }
+        Some(ByteRange::Satisfiable { start, end }) => {
+            let length = end - start + 1;
+            headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+            headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::try_from(format!("bytes {start}-{end}/{content_length}"))
+                    .expect("Content-Range should always be a valid header value"),
+            );
+            FileResponse {
+                status: http::StatusCode::PARTIAL_CONTENT,
+                headers,
+                body: Body::streaming(file_stream(source, start, Some(length))),
+            }
+        }
+        Some(ByteRange::Unsatisfiable) => {
+            headers.remove(header::CONTENT_TYPE);
+            headers.remove(header::CONTENT_DISPOSITION);
+            headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::try_from(format!("bytes */{content_length}"))
+                    .expect("Content-Range should always be a valid header value"),
+            );
+            FileResponse {
+                status: http::StatusCode::RANGE_NOT_SATISFIABLE,
+                headers,
+                body: Body::empty(),
+            }
+        }
+    }
+}
+
+fn open_file_error(path: &Path, error: std::io::Error) -> crate::Error {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        NotFound::with_message(format!("file `{}` not found", path.display())).into()
+    } else {
+        OpenFileError(error).into()
+    }
+}
+
+/// Returns the entity tag of a file with the given size and modification time.
+///
+/// The tag is strong, as `If-Range` only accepts strong validators. Like in
+/// most web servers, it's assumed that a file with the same size and (high
+/// resolution) modification time has the same content.
+fn file_etag(length: u64, modified: SystemTime) -> ETag {
+    let modified = modified
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    ETag::strong(format!("{length:x}-{modified:x}"))
+}
+
+/// Checks the `If-Range` precondition.
+///
+/// Returns `true` if the `Range` header should be honored.
+pub(crate) fn is_range_applicable(
+    request_headers: &http::HeaderMap,
+    etag: Option<&ETag>,
+    last_modified: Option<SystemTime>,
+) -> bool {
+    let Some(if_range) = request_headers.get(header::IF_RANGE) else {
+        return true;
+    };
+    let Ok(if_range) = if_range.to_str() else {
+        return false;
+    };
+
+    if let Ok(if_range_etag) = if_range.parse::<ETag>() {
+        etag.is_some_and(|etag| etag.strong_eq(&if_range_etag))
+    } else if let Some(if_range_date) = parse_http_date(if_range) {
+        last_modified == Some(if_range_date)
+    } else {
+        false
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ByteRange {
+    /// A satisfiable range; both ends are inclusive.
+    Satisfiable {
+        start: u64,
+        end: u64,
+    },
+    Unsatisfiable,
+}
+
+/// Parses the value of a `Range` header.
+///
+/// Only a single byte range is supported; for headers containing multiple
+/// ranges, or ones that are invalid, `None` is returned, meaning that the
+/// header should be ignored and the full content should be served.
+pub(crate) fn parse_range(range: &str, length: u64) -> Option<ByteRange> {
+    let range = range.trim().strip_prefix("bytes=")?.trim();
+    if range.contains(',') {
+        return None;
+    }
+
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let (start, end) = if start.is_empty() {
+        // suffix range, e.g. `bytes=-500`
+        let suffix_length: u64 = end.parse().ok()?;
+        if suffix_length == 0 || length == 0 {
+            return Some(ByteRange::Unsatisfiable);
+        }
+        (length.saturating_sub(suffix_length), length - 1)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() {
+            u64::MAX
+        } else {
+            end.parse().ok()?
+        };
+        if end < start {
+            return None;
+        }
+        if start >= length {
+            return Some(ByteRange::Unsatisfiable);
+        }
+        (start, end.min(length - 1))
+    };
+
+    Some(ByteRange::Satisfiable { start, end })
+}
+
+/// Creates a stream reading `limit` bytes (or everything, if `limit` is
+/// `None`) from the source, starting at the given `offset`.
+fn file_stream(
+    source: OpenedFileSource,
+    offset: u64,
+    limit: Option<u64>,
+) -> impl Stream<Item = crate::Result<Bytes>> + Send {
+    enum State {
+        Initial(OpenedFileSource),
+        Reading(Pin<Box<dyn AsyncRead + Send>>),
+    }
+
+    stream::try_unfold(
+        (State::Initial(source), limit),
+        move |(state, remaining)| async move {
+            let mut reader: Pin<Box<dyn AsyncRead + Send>> = match state {
+                State::Initial(OpenedFileSource::File(mut file)) => {
+                    if offset > 0 {
+                        file.seek(SeekFrom::Start(offset))
+                            .await
+                            .map_err(ReadFileError)?;
+                    }
+                    Box::pin(file)
+                }
+                State::Initial(OpenedFileSource::Reader(mut reader)) => {
+                    if offset > 0 {
+                        tokio::io::copy(&mut (&mut reader).take(offset), &mut tokio::io::sink())
+                            .await
+                            .map_err(ReadFileError)?;
+                    }
+                    reader
+                }
+                State::Reading(reader) => reader,
+            };
+
+            let chunk_size = remaining.map_or(FILE_CHUNK_SIZE, |remaining| {
+                usize::try_from(remaining).map_or(FILE_CHUNK_SIZE, |r| r.min(FILE_CHUNK_SIZE))
+            });
+            if chunk_size == 0 {
+                return Ok(None);
+            }
+
+            let mut buf = BytesMut::with_capacity(chunk_size);
+            let read = (&mut reader)
+                .take(chunk_size as u64)
+                .read_buf(&mut buf)
+                .await
+                .map_err(ReadFileError)?;
+            if read == 0 {
+                return Ok(None);
+            }
+
+            let remaining = remaining.map(|remaining| remaining - read as u64);
+            Ok(Some((buf.freeze(), (State::Reading(reader), remaining))))
+        },
+    )
+}
+
+#[derive(Debug, Error)]
+#[error("could not open file: {0}")]
+struct OpenFileError(#[source] std::io::Error);
+impl_into_cot_error!(OpenFileError);
+
+#[derive(Debug, Error)]
+#[error("could not read file: {0}")]
+struct ReadFileError(#[source] std::io::Error);
+impl_into_cot_error!(ReadFileError);
+
+/// An XLSX (Microsoft Excel) spreadsheet response.
+///
+/// Unlike [`CsvResponse`], the spreadsheet is built in memory before sending
+/// it, as the XLSX format doesn't allow streaming the contents.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::download::XlsxResponse;
+///
+/// async fn export() -> cot::Result<XlsxResponse> {
+///     let response = XlsxResponse::from_rows([["id", "name"], ["1", "Alice"]])?;
+///     Ok(response.with_filename("users.xlsx"))
+/// }
+/// ```
+#[cfg(feature = "xlsx")]
+#[derive(Debug)]
+pub struct XlsxResponse {
+    #[debug("..")]
+    workbook: rust_xlsxwriter::Workbook,
+    disposition: Option<ContentDisposition>,
+}
+
+#[cfg(feature = "xlsx")]
+impl XlsxResponse {
+    /// Creates a new XLSX response from a workbook.
+    ///
+    /// This allows you to use the full API of [`rust_xlsxwriter`] to create
+    /// the spreadsheet, including multiple worksheets and formatting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::XlsxResponse;
+    /// use cot::rust_xlsxwriter::Workbook;
+    ///
+    /// let mut workbook = Workbook::new();
+    /// workbook.add_worksheet().write_string(0, 0, "Hello")?;
+    /// let response = XlsxResponse::new(workbook);
+    /// # Ok::<(), cot::rust_xlsxwriter::XlsxError>(())
+    /// ```
+    #[must_use]
+    pub fn new(workbook: rust_xlsxwriter::Workbook) -> Self {
+        Self {
+            workbook,
+            disposition: None,
+        }
+    }
+
+    /// Creates a new XLSX response containing a single worksheet with the
+    /// given rows.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the data cannot be written to the
+    /// worksheet, e.g. because the number of rows or columns exceeds the
+    /// limits of the XLSX format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::XlsxResponse;
+    ///
+    /// let response = XlsxResponse::from_rows([["id", "name"], ["1", "Alice"]])?;
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn from_rows<I, R, T>(rows: I) -> crate::Result<Self>
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = T>,
+        T: rust_xlsxwriter::IntoExcelData,
+    {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let worksheet = workbook.add_worksheet();
+
+        for (row_index, row) in rows.into_iter().enumerate() {
+            let row_index = u32::try_from(row_index)
+                .map_err(|_| XlsxError(rust_xlsxwriter::XlsxError::RowColumnLimitError))?;
+            worksheet.write_row(row_index, 0, row).map_err(XlsxError)?;
+        }
+
+        Ok(Self::new(workbook))
+    }
+
+    /// Makes the browser download the response as an attachment with the
+    /// given file name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::download::XlsxResponse;
+    ///
+    /// let response = XlsxResponse::from_rows([["1", "Alice"]])?.with_filename("users.xlsx");
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_filename<T: Into<String>>(mut self, filename: T) -> Self {
+        self.disposition = Some(ContentDisposition::attachment(filename));
+        self
+    }
+}
+
+#[cfg(feature = "xlsx")]
+impl IntoResponse for XlsxResponse {
+    fn into_response(mut self) -> crate::Result<Response> {
+        let data = self.workbook.save_to_buffer().map_err(XlsxError)?;
+
+        let mut builder = Response::builder().header(header::CONTENT_TYPE, XLSX_CONTENT_TYPE);
+        if let Some(disposition) = &self.disposition {
+            builder = builder.header(header::CONTENT_DISPOSITION, HeaderValue::from(disposition));
+        }
+
+        Ok(builder
+            .body(Body::fixed(data))
+            .expect("XLSX response should always be valid"))
+    }
+}
+
+#[cfg(feature = "xlsx")]
+#[derive(Debug, Error)]
+#[error("could not create XLSX file: {0}")]
+struct XlsxError(#[source] rust_xlsxwriter::XlsxError);
+#[cfg(feature = "xlsx")]
+impl_into_cot_error!(XlsxError);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_string(response: Response) -> String {
+        let bytes = response.into_body().into_bytes().await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn content_disposition_ascii() {
+        let disposition = ContentDisposition::attachment("report.csv");
+
+        assert_eq!(
+            disposition.to_string(),
+            r#"attachment; filename="report.csv""#
+        );
+    }
+
+    #[test]
+    fn content_disposition_non_ascii() {
+        let disposition = ContentDisposition::attachment("zażółć gęślą.txt");
+
+        assert_eq!(
+            disposition.to_string(),
+            "attachment; filename=\"za____ g__l_.txt\"; \
+             filename*=UTF-8''za%C5%BC%C3%B3%C5%82%C4%87%20g%C4%99%C5%9Bl%C4%85.txt"
+        );
+    }
+
+    #[test]
+    fn content_disposition_escapes_quotes() {
+        let disposition = ContentDisposition::attachment("my \"file\".txt");
+
+        assert_eq!(
+            disposition.to_string(),
+            "attachment; filename=\"my _file_.txt\"; filename*=UTF-8''my%20%22file%22.txt"
+        );
+    }
+
+    #[test]
+    fn content_disposition_inline() {
+        assert_eq!(ContentDisposition::inline().to_string(), "inline");
+        assert_eq!(
+            HeaderValue::from(&ContentDisposition::inline().with_filename("a.png")),
+            r#"inline; filename="a.png""#
+        );
+    }
+
+    #[test]
+    fn encode_csv_record_quoting() {
+        let record = encode_csv_record(["plain", "with,comma", "with \"quote\"", "a\nb"], b',');
+
+        assert_eq!(
+            record,
+            "plain,\"with,comma\",\"with \"\"quote\"\"\",\"a\nb\"\r\n"
+        );
+    }
+
+    #[test]
+    fn encode_csv_record_custom_delimiter() {
+        let record = encode_csv_record(["a,b", "c;d"], b';');
+
+        assert_eq!(record, "a,b;\"c;d\"\r\n");
+    }
+
+    #[cot::test]
+    async fn csv_response_from_rows() {
+        let response = CsvResponse::builder()
+            .header(["id", "name"])
+            .filename("users.csv")
+            .rows([["1", "Alice"], ["2", "Bob, Jr."]])
+            .into_response()
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            CSV_CONTENT_TYPE
+        );
+        assert_eq!(
+            response.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            r#"attachment; filename="users.csv""#
+        );
+        assert_eq!(
+            body_string(response).await,
+            "id,name\r\n1,Alice\r\n2,\"Bob, Jr.\"\r\n"
+        );
+    }
+
+    #[cot::test]
+    async fn csv_response_from_stream() {
+        let rows = stream::iter([Ok(vec!["1".to_string()]), Ok(vec!["2".to_string()])]);
+        let response = CsvResponse::from_stream(rows).into_response().unwrap();
+
+        assert!(
+            response
+                .headers()
+                .get(header::CONTENT_DISPOSITION)
+                .is_none()
+        );
+        assert_eq!(body_string(response).await, "1\r\n2\r\n");
+    }
+
+    #[cot::test]
+    async fn csv_response_stream_error() {
+        let rows = stream::iter([
+            Ok(["1"]),
+            Err(crate::Error::internal("database error")),
+            Ok(["2"]),
+        ]);
+        let response = CsvResponse::from_stream(rows).into_response().unwrap();
+
+        assert!(response.into_body().into_bytes().await.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid CSV delimiter")]
+    fn csv_response_invalid_delimiter() {
+        CsvResponse::builder().delimiter(b'"');
+    }
+
+    #[test]
+    fn parse_range_valid() {
+        assert_eq!(
+            parse_range("bytes=0-4", 10),
+            Some(ByteRange::Satisfiable { start: 0, end: 4 })
+        );
+        assert_eq!(
+            parse_range("bytes=5-", 10),
+            Some(ByteRange::Satisfiable { start: 5, end: 9 })
+        );
+        assert_eq!(
+            parse_range("bytes=-3", 10),
+            Some(ByteRange::Satisfiable { start: 7, end: 9 })
+        );
+        assert_eq!(
+            parse_range("bytes=-30", 10),
+            Some(ByteRange::Satisfiable { start: 0, end: 9 })
+        );
+        assert_eq!(
+            parse_range("bytes=8-100", 10),
+            Some(ByteRange::Satisfiable { start: 8, end: 9 })
+        );
+    }
+
+    #[test]
+    fn parse_range_unsatisfiable() {
+        assert_eq!(parse_range("bytes=10-", 10), Some(ByteRange::Unsatisfiable));
+        assert_eq!(parse_range("bytes=-0", 10), Some(ByteRange::Unsatisfiable));
+    }
+
+    #[test]
+    fn parse_range_ignored() {
+        assert_eq!(parse_range("bytes=0-1,3-4", 10), None);
+        assert_eq!(parse_range("bytes=5-2", 10), None);
+        assert_eq!(parse_range("items=0-1", 10), None);
+        assert_eq!(parse_range("bytes=a-b", 10), None);
+    }
+
+    async fn file_response(
+        builder: FileResponseBuilder,
+        request_headers: &[(header::HeaderName, &str)],
+    ) -> Response {
+        let mut headers = http::HeaderMap::new();
+        for (name, value) in request_headers {
+            headers.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+        }
+
+        builder
+            .request_headers(&headers)
+            .build()
+            .await
+            .unwrap()
+            .into_response()
+            .unwrap()
+    }
+
+    fn temp_file(content: &[u8]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(".txt").tempfile().unwrap();
+        std::io::Write::write_all(&mut file, content).unwrap();
+        file
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn file_response_path() {
+        let file = temp_file(b"Hello, world!");
+
+        let response = file_response(FileResponse::builder().path(file.path()), &[]).await;
+
+        assert_eq!(response.status(), http::StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers.get(header::CONTENT_TYPE).unwrap(), "text/plain");
+        assert_eq!(headers.get(header::CONTENT_LENGTH).unwrap(), "13");
+        assert_eq!(headers.get(header::ACCEPT_RANGES).unwrap(), "bytes");
+        assert!(headers.contains_key(header::ETAG));
+        assert!(headers.contains_key(header::LAST_MODIFIED));
+        assert_eq!(body_string(response).await, "Hello, world!");
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn file_response_not_found() {
+        let error = FileResponse::builder()
+            .path("/nonexistent/file.txt")
+            .build()
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status_code(), http::StatusCode::NOT_FOUND);
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn file_response_range() {
+        let file = temp_file(b"Hello, world!");
+
+        let response = file_response(
+            FileResponse::builder().path(file.path()),
+            &[(header::RANGE, "bytes=7-11")],
+        )
+        .await;
+
+        assert_eq!(response.status(), http::StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 7-11/13"
+        );
+        assert_eq!(response.headers().get(header::CONTENT_LENGTH).unwrap(), "5");
+        assert_eq!(body_string(response).await, "world");
+    }
+
+    #[cot::test]
+    async fn file_response_reader_range() {
+        let response = file_response(
+            FileResponse::builder()
+                .reader(&b"Hello, world!"[..])
+                .content_length(13),
+            &[(header::RANGE, "bytes=-6")],
+        )
+        .await;
+
+        assert_eq!(response.status(), http::StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 7-12/13"
+        );
+        assert_eq!(body_string(response).await, "world!");
+    }
+
+    #[cot::test]
+    async fn file_response_range_not_satisfiable() {
+        let response = file_response(
+            FileResponse::builder()
+                .reader(&b"Hello, world!"[..])
+                .content_length(13),
+            &[(header::RANGE, "bytes=20-")],
+        )
+        .await;
+
+        assert_eq!(response.status(), http::StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */13"
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn file_response_if_range_etag() {
+        let file = temp_file(b"Hello, world!");
+        let response = file_response(FileResponse::builder().path(file.path()), &[]).await;
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(!etag.starts_with("W/"), "{etag}");
+
+        let response = file_response(
+            FileResponse::builder().path(file.path()),
+            &[(header::RANGE, "bytes=7-11"), (header::IF_RANGE, etag)],
+        )
+        .await;
+
+        assert_eq!(response.status(), http::StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body_string(response).await, "world");
+    }
+
+    #[cot::test]
+    async fn file_response_if_range_mismatch() {
+        let response = file_response(
+            FileResponse::builder()
+                .reader(&b"Hello, world!"[..])
+                .content_length(13)
+                .etag(ETag::strong("v2")),
+            &[(header::RANGE, "bytes=0-4"), (header::IF_RANGE, r#""v1""#)],
+        )
+        .await;
+
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(body_string(response).await, "Hello, world!");
+    }
+
+    #[cot::test]
+    async fn file_response_if_none_match() {
+        let response = file_response(
+            FileResponse::builder()
+                .reader(&b"Hello, world!"[..])
+                .etag(ETag::strong("v1")),
+            &[(header::IF_NONE_MATCH, r#"W/"v1""#)],
+        )
+        .await;
+
+        assert_eq!(response.status(), http::StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(header::ETAG).unwrap(), r#""v1""#);
+        assert_eq!(body_string(response).await, "");
+    }
+
+    #[cot::test]
+    async fn file_response_if_modified_since() {
+        let last_modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(784_111_777);
+
+        let response = file_response(
+            FileResponse::builder()
+                .reader(&b"Hello, world!"[..])
+                .last_modified(last_modified),
+            &[(header::IF_MODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:37 GMT")],
+        )
+        .await;
+
+        assert_eq!(response.status(), http::StatusCode::NOT_MODIFIED);
+    }
+
+    #[cot::test]
+    async fn file_response_attachment() {
+        let response = file_response(
+            FileResponse::builder()
+                .reader(&b"%PDF"[..])
+                .attachment("zażółć.pdf"),
+            &[],
+        )
+        .await;
+
+        let headers = response.headers();
+        assert_eq!(
+            headers.get(header::CONTENT_TYPE).unwrap(),
+            "application/pdf"
+        );
+        assert_eq!(
+            headers.get(header::CONTENT_DISPOSITION).unwrap(),
+            r#"attachment; filename="za____.pdf"; filename*=UTF-8''za%C5%BC%C3%B3%C5%82%C4%87.pdf"#
+        );
+        assert!(!headers.contains_key(header::CONTENT_LENGTH));
+        assert!(!headers.contains_key(header::ACCEPT_RANGES));
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[cot::test]
+    async fn xlsx_response_from_rows() {
+        let response = XlsxResponse::from_rows([["id", "name"], ["1", "Alice"]])
+            .unwrap()
+            .with_filename("users.xlsx")
+            .into_response()
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            XLSX_CONTENT_TYPE
+        );
+        assert_eq!(
+            response.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            r#"attachment; filename="users.xlsx""#
+        );
+        let body = response.into_body().into_bytes().await.unwrap();
+        // XLSX files are ZIP archives
+        assert!(body.starts_with(b"PK"));
+    }
+}
@@ -0,0 +1,361 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use digest::Digest;
+use http::{HeaderValue, header};
+use thiserror::Error;
+
+use crate::utils::http_date::parse_http_date;
+
+/// An entity tag, as used in the `ETag`, `If-Match`, and `If-None-Match`
+/// headers.
+///
+/// Entity tags identify a specific version of a resource. A strong entity tag
+/// changes whenever the representation changes in any way, while a weak one
+/// (prefixed with `W/` in headers) only needs to change when the semantics of
+/// the resource change.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::ETag;
+///
+/// let etag = ETag::strong("abc123");
+/// assert_eq!(etag.to_string(), r#""abc123""#);
+///
+/// let etag: ETag = r#"W/"abc123""#.parse().unwrap();
+/// assert!(etag.is_weak());
+/// assert_eq!(etag.tag(), "abc123");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ETag {
+    weak: bool,
+    tag: String,
+}
+
+impl ETag {
+    /// Creates a new strong entity tag.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the tag contains a double quote or characters
+    /// that are not allowed in an HTTP header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// let etag = ETag::strong("v1");
+    /// assert!(!etag.is_weak());
+    /// ```
+    #[must_use]
+    pub fn strong<T: Into<String>>(tag: T) -> Self {
+        Self::new(false, tag.into())
+    }
+
+    /// Creates a new weak entity tag.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the tag contains a double quote or characters
+    /// that are not allowed in an HTTP header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// let etag = ETag::weak("v1");
+    /// assert_eq!(etag.to_string(), r#"W/"v1""#);
+    /// ```
+    #[must_use]
+    pub fn weak<T: Into<String>>(tag: T) -> Self {
+        Self::new(true, tag.into())
+    }
+
+    /// Creates a strong entity tag by hashing the given content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// let etag = ETag::from_content(b"Hello, world!");
+    /// assert_eq!(etag, ETag::from_content(b"Hello, world!"));
+    /// assert_ne!(etag, ETag::from_content(b"Goodbye, world!"));
+    /// ```
+    #[must_use]
+    pub fn from_content(content: &[u8]) -> Self {
+        Self::strong(hex::encode(
+            &sha2::Sha256::digest(content).as_slice()[0..16],
+        ))
+    }
+
+    /// Creates a strong entity tag from the version of a resource.
+    ///
+    /// This is typically used to create entity tags for model instances from
+    /// their version or last modification time column, so that clients can
+    /// use them in `If-Match` headers for optimistic concurrency control (see
+    /// [`IfMatch`](crate::request::extractors::IfMatch)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{DateTime, FixedOffset};
+    /// use cot::response::ETag;
+    ///
+    /// let updated_at = DateTime::<FixedOffset>::parse_from_rfc3339("2025-01-01T12:00:00Z").unwrap();
+    ///
+    /// let etag = ETag::from_version(updated_at);
+    /// assert_eq!(etag, ETag::from_version(updated_at));
+    /// assert_ne!(etag, ETag::from_version(42));
+    /// ```
+    #[must_use]
+    pub fn from_version<V: Display>(version: V) -> Self {
+        Self::from_content(version.to_string().as_bytes())
+    }
+
+    fn new(weak: bool, tag: String) -> Self {
+        assert!(
+            is_valid_tag(&tag),
+            "invalid entity tag: {tag:?}; entity tags cannot contain double quotes, \
+             whitespace, or control characters"
+        );
+        Self { weak, tag }
+    }
+
+    /// Returns `true` if this is a weak entity tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// assert!(ETag::weak("v1").is_weak());
+    /// assert!(!ETag::strong("v1").is_weak());
+    /// ```
+    #[must_use]
+    pub fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// Returns the opaque tag value, without the quotes and the weakness
+    /// indicator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// assert_eq!(ETag::weak("v1").tag(), "v1");
+    /// ```
+    #[must_use]
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Compares two entity tags using the strong comparison function: both
+    /// tags have to be strong and have the same value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// assert!(ETag::strong("v1").strong_eq(&ETag::strong("v1")));
+    /// assert!(!ETag::weak("v1").strong_eq(&ETag::strong("v1")));
+    /// ```
+    #[must_use]
+    pub fn strong_eq(&self, other: &Self) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Compares two entity tags using the weak comparison function: the tag
+    /// values have to be the same, regardless of the weakness.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::ETag;
+    ///
+    /// assert!(ETag::weak("v1").weak_eq(&ETag::strong("v1")));
+    /// assert!(!ETag::weak("v1").weak_eq(&ETag::weak("v2")));
+    /// ```
+    #[must_use]
+    pub fn weak_eq(&self, other: &Self) -> bool {
+        self.tag == other.tag
+    }
+
+    /// Checks whether this entity tag matches the value of an `If-None-Match`
+    /// header (using the weak comparison) or an `If-Match` header (using the
+    /// strong comparison), depending on the `weak` parameter.
+    ///
+    /// The header value can be either `*` or a comma-separated list of
+    /// entity tags. Invalid entries are ignored.
+    pub(crate) fn matches_header(&self, header: &HeaderValue, weak: bool) -> bool {
+        let Ok(header) = header.to_str() else {
+            return false;
+        };
+
+        if header.trim() == "*" {
+            return true;
+        }
+
+        split_etag_list(header)
+            .filter_map(|etag| etag.parse::<ETag>().ok())
+            .any(|etag| {
+                if weak {
+                    self.weak_eq(&etag)
+                } else {
+                    self.strong_eq(&etag)
+                }
+            })
+    }
+}
+
+/// Checks the `If-None-Match` and `If-Modified-Since` preconditions.
+///
+/// Returns `true` if the client already has the current version of the
+/// resource.
+pub(crate) fn is_not_modified(
+    request_headers: &http::HeaderMap,
+    etag: Option<&ETag>,
+    last_modified: Option<SystemTime>,
+) -> bool {
+    if let Some(if_none_match) = request_headers.get(header::IF_NONE_MATCH) {
+        // If-Modified-Since must be ignored when If-None-Match is present
+        return etag.is_some_and(|etag| etag.matches_header(if_none_match, true));
+    }
+
+    let if_modified_since = request_headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_http_date);
+    match (if_modified_since, last_modified) {
+        (Some(if_modified_since), Some(last_modified)) => last_modified <= if_modified_since,
+        _ => false,
+    }
+}
+
+/// Splits a comma-separated list of entity tags. Commas are allowed inside
+/// the quoted tags, so a simple `split(',')` is not enough.
+fn split_etag_list(header: &str) -> impl Iterator<Item = &str> {
+    let mut rest = header;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start_matches([' ', '\t', ',']);
+        if rest.is_empty() {
+            return None;
+        }
+
+        let quote_start = rest.find('"')?;
+        let quote_end = rest[quote_start + 1..].find('"')? + quote_start + 1;
+        let (etag, remaining) = rest.split_at(quote_end + 1);
+        rest = remaining;
+        Some(etag)
+    })
+}
+
+fn is_valid_tag(tag: &str) -> bool {
+    tag.bytes()
+        .all(|byte| byte == 0x21 || (0x23..=0x7e).contains(&byte) || byte >= 0x80)
+}
+
+impl Display for ETag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.weak {
+            f.write_str("W/")?;
+        }
+        write!(f, "\"{}\"", self.tag)
+    }
+}
+
+impl FromStr for ETag {
+    type Err = ETagParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (weak, quoted) = match value.strip_prefix("W/") {
+            Some(quoted) => (true, quoted),
+            None => (false, value),
+        };
+
+        let tag = quoted
+            .strip_prefix('"')
+            .and_then(|quoted| quoted.strip_suffix('"'))
+            .ok_or(ETagParseError)?;
+        if !is_valid_tag(tag) {
+            return Err(ETagParseError);
+        }
+
+        Ok(Self {
+            weak,
+            tag: tag.to_owned(),
+        })
+    }
+}
+
+impl From<&ETag> for HeaderValue {
+    fn from(etag: &ETag) -> Self {
+        HeaderValue::try_from(etag.to_string())
+            .expect("entity tag should always be a valid header value")
+    }
+}
+
+/// An error returned when parsing an invalid entity tag.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[error("invalid entity tag")]
+pub struct ETagParseError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_strong() {
+        let etag: ETag = r#""abc""#.parse().unwrap();
+
+        assert_eq!(etag, ETag::strong("abc"));
+    }
+
+    #[test]
+    fn parse_weak() {
+        let etag: ETag = r#"W/"abc""#.parse().unwrap();
+
+        assert_eq!(etag, ETag::weak("abc"));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!("abc".parse::<ETag>(), Err(ETagParseError));
+        assert_eq!(r#""a"b""#.parse::<ETag>(), Err(ETagParseError));
+        assert_eq!(r#"w/"abc""#.parse::<ETag>(), Err(ETagParseError));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid entity tag")]
+    fn new_invalid() {
+        let _ = ETag::strong("a\"b");
+    }
+
+    #[test]
+    fn from_version() {
+        let etag = ETag::from_version(1);
+
+        assert!(!etag.is_weak());
+        assert_eq!(etag, ETag::from_version("1"));
+        assert_ne!(etag, ETag::from_version(2));
+    }
+
+    #[test]
+    fn matches_header() {
+        let etag = ETag::strong("abc");
+
+        assert!(etag.matches_header(&HeaderValue::from_static("*"), true));
+        assert!(etag.matches_header(&HeaderValue::from_static(r#""abc""#), false));
+        assert!(etag.matches_header(&HeaderValue::from_static(r#""x", W/"abc""#), true));
+        assert!(!etag.matches_header(&HeaderValue::from_static(r#""x", W/"abc""#), false));
+        assert!(!etag.matches_header(&HeaderValue::from_static(r#""x,y""#), true));
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt::{Debug, Formatter};
+use std::pin::Pin;
+
+use bytes::Bytes;
+use cot_core::error::impl_into_cot_error;
+use cot_core::headers::JSON_CONTENT_TYPE;
+use futures_core::Stream;
+use futures_util::{StreamExt, stream};
+use http::header;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::Body;
+use crate::response::{IntoResponse, Response, ResponseExt};
+
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+/// A "reasonable default" for the serialized size of a single item.
+const DEFAULT_ITEM_SIZE: usize = 128;
+
+type ItemStream<T> = Pin<Box<dyn Stream<Item = crate::Result<T>> + Send>>;
+
+#[derive(Debug, Error)]
+#[error("JSON serialization error: {0}")]
+struct JsonStreamSerializeError(serde_path_to_error::Error<serde_json::Error>);
+impl_into_cot_error!(JsonStreamSerializeError, INTERNAL_SERVER_ERROR);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum JsonStreamFormat {
+    Array,
+    Lines,
+}
+
+/// A response that serializes a stream of items as JSON, one item at a time.
+///
+/// Unlike [`Json`](crate::json::Json), which serializes the whole value into
+/// memory before sending it, this only serializes an item when the client is
+/// ready to receive more data. This makes it suitable for endpoints returning
+/// large datasets, such as tens of thousands of database rows, as the memory
+/// usage doesn't depend on the number of items, and the client starts
+/// receiving the data right away.
+///
+/// The items are sent either as a single JSON array (with the
+/// `application/json` content type), or as [newline-delimited
+/// JSON](https://github.com/ndjson/ndjson-spec), with one item per line (with
+/// the `application/x-ndjson` content type). If the stream yields an error,
+/// or an item fails to serialize, the response body is terminated, so the
+/// client receives an incomplete document.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::JsonStream;
+/// use futures_util::{StreamExt, stream};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Reading {
+///     sensor: u32,
+///     value: f64,
+/// }
+///
+/// async fn readings() -> JsonStream<Reading> {
+///     let readings = stream::iter(0..10_000).map(|sensor| {
+///         Ok(Reading {
+///             sensor,
+///             value: 21.5,
+///         })
+///     });
+///
+///     JsonStream::new(readings)
+/// }
+/// ```
+pub struct JsonStream<T> {
+    items: ItemStream<T>,
+    format: JsonStreamFormat,
+}
+
+impl<T: Serialize + 'static> JsonStream<T> {
+    /// Creates a response that sends the items as a JSON array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::JsonStream;
+    ///
+    /// let items = futures_util::stream::iter([Ok::<_, cot::Error>(1), Ok(2), Ok(3)]);
+    /// let response = JsonStream::new(items);
+    /// ```
+    #[must_use]
+    pub fn new<S>(items: S) -> Self
+    where
+        S: Stream<Item = crate::Result<T>> + Send + 'static,
+    {
+        Self::with_format(items, JsonStreamFormat::Array)
+    }
+
+    /// Creates a response that sends the items as newline-delimited JSON,
+    /// with one item per line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::JsonStream;
+    ///
+    /// let items = futures_util::stream::iter([Ok::<_, cot::Error>("first"), Ok("second")]);
+    /// let response = JsonStream::ndjson(items);
+    /// ```
+    #[must_use]
+    pub fn ndjson<S>(items: S) -> Self
+    where
+        S: Stream<Item = crate::Result<T>> + Send + 'static,
+    {
+        Self::with_format(items, JsonStreamFormat::Lines)
+    }
+
+    fn with_format<S>(items: S, format: JsonStreamFormat) -> Self
+    where
+        S: Stream<Item = crate::Result<T>> + Send + 'static,
+    {
+        Self {
+            items: Box::pin(items),
+            format,
+        }
+    }
+
+    fn into_body(self) -> Body {
+        let format = self.format;
+        let items = self.items.enumerate().map(move |(index, item)| {
+            let mut buf = Vec::with_capacity(DEFAULT_ITEM_SIZE);
+            if format == JsonStreamFormat::Array && index > 0 {
+                buf.push(b',');
+            }
+            let mut serializer = serde_json::Serializer::new(&mut buf);
+            serde_path_to_error::serialize(&item?, &mut serializer)
+                .map_err(JsonStreamSerializeError)?;
+            if format == JsonStreamFormat::Lines {
+                buf.push(b'\n');
+            }
+            Ok(Bytes::from(buf))
+        });
+
+        match format {
+            JsonStreamFormat::Array => Body::streaming(
+                stream::once(async { Ok(Bytes::from_static(b"[")) })
+                    .chain(items)
+                    .chain(stream::once(async { Ok(Bytes::from_static(b"]")) })),
+            ),
+            JsonStreamFormat::Lines => Body::streaming(items),
+        }
+    }
+}
+
+impl<T> Debug for JsonStream<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonStream")
+            .field("items", &"...")
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+impl<T: Serialize + 'static> IntoResponse for JsonStream<T> {
+    fn into_response(self) -> crate::Result<Response> {
+        let content_type = match self.format {
+            JsonStreamFormat::Array => JSON_CONTENT_TYPE,
+            JsonStreamFormat::Lines => NDJSON_CONTENT_TYPE,
+        };
+
+        Ok(Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .body(self.into_body())
+            .expect("JSON stream response should always be valid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    async fn body_of<T: Serialize + 'static>(response: JsonStream<T>) -> crate::Result<Bytes> {
+        response.into_response()?.into_body().into_bytes().await
+    }
+
+    #[cot::test]
+    async fn array() {
+        let items = stream::iter([
+            Ok(HashMap::from([("id", 1)])),
+            Ok(HashMap::from([("id", 2)])),
+        ]);
+
+        let response = JsonStream::new(items).into_response().unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], JSON_CONTENT_TYPE);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            r#"[{"id":1},{"id":2}]"#
+        );
+    }
+
+    #[cot::test]
+    async fn array_empty() {
+        let items = stream::iter(Vec::<crate::Result<i32>>::new());
+
+        assert_eq!(body_of(JsonStream::new(items)).await.unwrap(), "[]");
+    }
+
+    #[cot::test]
+    async fn ndjson() {
+        let items = stream::iter([Ok("first"), Ok("second")]);
+
+        let response = JsonStream::ndjson(items).into_response().unwrap();
+
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            NDJSON_CONTENT_TYPE
+        );
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            "\"first\"\n\"second\"\n"
+        );
+    }
+
+    #[cot::test]
+    async fn stream_error_terminates_body() {
+        let items = stream::iter([Ok(1), Err(crate::Error::internal("connection lost"))]);
+
+        let error = body_of(JsonStream::new(items)).await.unwrap_err();
+
+        assert!(error.to_string().contains("connection lost"));
+    }
+
+    #[cot::test]
+    async fn serialization_error_terminates_body() {
+        let items = stream::iter([Ok(HashMap::from([((1, 2), "tuple keys are invalid")]))]);
+
+        let error = body_of(JsonStream::ndjson(items)).await.unwrap_err();
+
+        assert!(error.to_string().contains("JSON serialization error"));
+    }
+}
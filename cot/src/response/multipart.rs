@@ -0,0 +1,476 @@
+//! Streaming multipart responses.
+//!
+//! This module contains [`MultipartResponse`], which sends a stream of
+//! [`Part`]s, each with its own headers, as a single `multipart/*` response.
+//! The most common use case is `multipart/x-mixed-replace`, where each part
+//! replaces the previous one in the browser, which can be used for things like
+//! MJPEG camera streams or progressively refined results.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use cot::response::multipart::{MultipartResponse, Part};
+//! use futures_util::{StreamExt, stream};
+//!
+//! async fn progress() -> MultipartResponse {
+//!     let parts = stream::iter((0..=100).step_by(10)).then(|percent| async move {
+//!         tokio::time::sleep(Duration::from_millis(100)).await;
+//!         Ok(Part::new(format!("{percent}% done")).content_type("text/plain"))
+//!     });
+//!
+//!     MultipartResponse::mixed_replace(parts)
+//! }
+//! ```
+
+use bytes::{BufMut, Bytes, BytesMut};
+use cot_core::error::impl_into_cot_error;
+use futures_core::Stream;
+use futures_util::{StreamExt, stream};
+use http::{HeaderMap, HeaderName, HeaderValue, header};
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use thiserror::Error;
+
+use crate::Body;
+use crate::response::{IntoResponse, Response, ResponseExt};
+
+const BOUNDARY_LENGTH: usize = 32;
+const MAX_BOUNDARY_LENGTH: usize = 70;
+
+/// An error that can occur while producing a multipart response body.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum MultipartError {
+    /// The body of a part contains the boundary delimiter, so it would be
+    /// interpreted as the end of the part.
+    #[error("the body of a multipart part contains the boundary `{0}`")]
+    BoundaryInBody(String),
+}
+impl_into_cot_error!(MultipartError);
+
+/// A single part of a [`MultipartResponse`].
+///
+/// The `Content-Length` header of the part is set automatically.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::multipart::Part;
+///
+/// let part = Part::new(vec![0xFF, 0xD8, 0xFF])
+///     .content_type("image/jpeg")
+///     .header("x-frame-number", "1");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Part {
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl Part {
+    /// Creates a new part with the given body and no headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::Part;
+    ///
+    /// let part = Part::new("Hello, world!");
+    /// assert_eq!(part.body(), "Hello, world!");
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<Bytes>>(body: T) -> Self {
+        Self {
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Sets the `Content-Type` header of the part.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the content type is not a valid header value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::Part;
+    ///
+    /// let part = Part::new("{}").content_type("application/json");
+    /// assert_eq!(part.headers()["content-type"], "application/json");
+    /// ```
+    #[must_use]
+    pub fn content_type(self, content_type: &str) -> Self {
+        self.header(header::CONTENT_TYPE, content_type)
+    }
+
+    /// Adds a header to the part.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name or the value is not a valid header name or value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::Part;
+    ///
+    /// let part = Part::new("...").header("x-frame-number", "1");
+    /// assert_eq!(part.headers()["x-frame-number"], "1");
+    /// ```
+    #[must_use]
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: std::fmt::Debug,
+    {
+        self.headers.append(
+            name.try_into().expect("invalid part header name"),
+            value.try_into().expect("invalid part header value"),
+        );
+        self
+    }
+
+    /// Returns the headers of the part.
+    #[must_use]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the body of the part.
+    #[must_use]
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    fn encode(&self, boundary: &str) -> Result<Bytes, MultipartError> {
+        let delimiter = format!("--{boundary}");
+        if contains(&self.body, delimiter.as_bytes()) {
+            return Err(MultipartError::BoundaryInBody(boundary.to_owned()));
+        }
+
+        let mut buf = BytesMut::with_capacity(self.body.len() + 128);
+        buf.put_slice(delimiter.as_bytes());
+        buf.put_slice(b"\r\n");
+        for (name, value) in &self.headers {
+            if name == header::CONTENT_LENGTH {
+                continue;
+            }
+            buf.put_slice(name.as_str().as_bytes());
+            buf.put_slice(b": ");
+            buf.put_slice(value.as_bytes());
+            buf.put_slice(b"\r\n");
+        }
+        buf.put_slice(format!("content-length: {}\r\n\r\n", self.body.len()).as_bytes());
+        buf.put_slice(&self.body);
+        buf.put_slice(b"\r\n");
+        Ok(buf.freeze())
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+/// A streaming `multipart/*` response.
+///
+/// The parts are encoded lazily as the response body is being sent, with the
+/// boundary and the headers of each part added automatically. If the stream
+/// of parts yields an error, or a part contains the boundary, the response
+/// body is terminated.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::multipart::{MultipartResponse, Part};
+///
+/// async fn report() -> MultipartResponse {
+///     MultipartResponse::builder().subtype("mixed").parts([
+///         Part::new("Summary").content_type("text/plain"),
+///         Part::new("<p>Details</p>").content_type("text/html"),
+///     ])
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MultipartResponse {
+    content_type: HeaderValue,
+    body: Body,
+}
+
+impl MultipartResponse {
+    /// Returns a builder that can be used to customize the multipart
+    /// subtype and boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::{MultipartResponse, Part};
+    ///
+    /// let response = MultipartResponse::builder()
+    ///     .boundary("frame")
+    ///     .parts([Part::new("Hello")]);
+    /// ```
+    #[must_use]
+    pub fn builder() -> MultipartResponseBuilder {
+        MultipartResponseBuilder::default()
+    }
+
+    /// Creates a `multipart/x-mixed-replace` response from a stream of parts,
+    /// where each part replaces the previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::{MultipartResponse, Part};
+    ///
+    /// let frames = futures_util::stream::iter([
+    ///     Ok::<_, cot::Error>(Part::new(vec![0xFF, 0xD8]).content_type("image/jpeg")),
+    ///     Ok(Part::new(vec![0xFF, 0xD8]).content_type("image/jpeg")),
+    /// ]);
+    /// let response = MultipartResponse::mixed_replace(frames);
+    /// ```
+    #[must_use]
+    pub fn mixed_replace<S>(parts: S) -> Self
+    where
+        S: Stream<Item = crate::Result<Part>> + Send + 'static,
+    {
+        Self::builder().subtype("x-mixed-replace").stream(parts)
+    }
+}
+
+impl IntoResponse for MultipartResponse {
+    fn into_response(self) -> crate::Result<Response> {
+        Ok(Response::builder()
+            .header(header::CONTENT_TYPE, self.content_type)
+            .body(self.body)
+            .expect("multipart response should always be valid"))
+    }
+}
+
+/// A builder for [`MultipartResponse`].
+///
+/// This is returned by [`MultipartResponse::builder`].
+#[derive(Debug, Clone)]
+pub struct MultipartResponseBuilder {
+    subtype: String,
+    boundary: Option<String>,
+}
+
+impl Default for MultipartResponseBuilder {
+    fn default() -> Self {
+        Self {
+            subtype: "mixed".to_owned(),
+            boundary: None,
+        }
+    }
+}
+
+impl MultipartResponseBuilder {
+    /// Sets the multipart subtype, such as `mixed` (the default),
+    /// `x-mixed-replace`, or `related`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::{MultipartResponse, Part};
+    ///
+    /// let response = MultipartResponse::builder()
+    ///     .subtype("related")
+    ///     .parts([Part::new("Hello")]);
+    /// ```
+    pub fn subtype<T: Into<String>>(&mut self, subtype: T) -> &mut Self {
+        self.subtype = subtype.into();
+        self
+    }
+
+    /// Sets the boundary that separates the parts.
+    ///
+    /// By default, a random boundary is generated for each response, which
+    /// is recommended unless the client expects a specific boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the boundary is empty, longer than 70 characters, or
+    /// contains characters that are not allowed by
+    /// [RFC 2046](https://datatracker.ietf.org/doc/html/rfc2046#section-5.1.1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::{MultipartResponse, Part};
+    ///
+    /// let response = MultipartResponse::builder()
+    ///     .boundary("frame")
+    ///     .parts([Part::new("Hello")]);
+    /// ```
+    pub fn boundary<T: Into<String>>(&mut self, boundary: T) -> &mut Self {
+        let boundary = boundary.into();
+        assert!(
+            is_valid_boundary(&boundary),
+            "invalid multipart boundary: {boundary:?}"
+        );
+        self.boundary = Some(boundary);
+        self
+    }
+
+    /// Builds a [`MultipartResponse`] from an iterator of parts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::{MultipartResponse, Part};
+    ///
+    /// let response = MultipartResponse::builder().parts([Part::new("first"), Part::new("second")]);
+    /// ```
+    pub fn parts<I>(&mut self, parts: I) -> MultipartResponse
+    where
+        I: IntoIterator<Item = Part>,
+        I::IntoIter: Send + 'static,
+    {
+        self.stream(stream::iter(parts.into_iter().map(Ok)))
+    }
+
+    /// Builds a [`MultipartResponse`] from a stream of parts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the subtype contains characters that are not allowed in a
+    /// header value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::multipart::{MultipartResponse, Part};
+    ///
+    /// let parts = futures_util::stream::iter([Ok::<_, cot::Error>(Part::new("Hello"))]);
+    /// let response = MultipartResponse::builder().stream(parts);
+    /// ```
+    pub fn stream<S>(&mut self, parts: S) -> MultipartResponse
+    where
+        S: Stream<Item = crate::Result<Part>> + Send + 'static,
+    {
+        let boundary = self.boundary.clone().unwrap_or_else(random_boundary);
+        let content_type =
+            HeaderValue::try_from(format!("multipart/{}; boundary={boundary}", self.subtype))
+                .expect("multipart content type should be a valid header value");
+
+        let closing = Bytes::from(format!("--{boundary}--\r\n"));
+        let parts = parts.map(move |part| Ok(part?.encode(&boundary)?));
+        let body = parts.chain(stream::once(async move { Ok(closing) }));
+
+        MultipartResponse {
+            content_type,
+            body: Body::streaming(body),
+        }
+    }
+}
+
+fn random_boundary() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(BOUNDARY_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+fn is_valid_boundary(boundary: &str) -> bool {
+    (1..=MAX_BOUNDARY_LENGTH).contains(&boundary.len())
+        && !boundary.ends_with(' ')
+        && boundary
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+
+    async fn body_of(response: MultipartResponse) -> crate::Result<Bytes> {
+        response.into_response()?.into_body().into_bytes().await
+    }
+
+    #[cot::test]
+    async fn encodes_parts() {
+        let response = MultipartResponse::builder().boundary("frame").parts([
+            Part::new("first").content_type("text/plain"),
+            Part::new("second").header("x-index", "2"),
+        ]);
+
+        assert_eq!(response.content_type, "multipart/mixed; boundary=frame");
+        assert_eq!(
+            body_of(response).await.unwrap(),
+            "--frame\r\ncontent-type: text/plain\r\ncontent-length: 5\r\n\r\nfirst\r\n\
+             --frame\r\nx-index: 2\r\ncontent-length: 6\r\n\r\nsecond\r\n\
+             --frame--\r\n"
+        );
+    }
+
+    #[cot::test]
+    async fn mixed_replace() {
+        let parts = stream::iter([Ok(Part::new("frame"))]);
+
+        let response = MultipartResponse::mixed_replace(parts)
+            .into_response()
+            .unwrap();
+
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        assert!(content_type.starts_with("multipart/x-mixed-replace; boundary="));
+    }
+
+    #[test]
+    fn random_boundary_is_valid() {
+        let boundary = random_boundary();
+
+        assert_eq!(boundary.len(), BOUNDARY_LENGTH);
+        assert!(is_valid_boundary(&boundary));
+    }
+
+    #[test]
+    fn boundary_validation() {
+        assert!(is_valid_boundary("simple-boundary_1"));
+        assert!(!is_valid_boundary(""));
+        assert!(!is_valid_boundary("trailing "));
+        assert!(!is_valid_boundary("quote\""));
+        assert!(!is_valid_boundary(&"a".repeat(71)));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid multipart boundary")]
+    fn invalid_boundary_panics() {
+        let _ = MultipartResponse::builder().boundary("bad\r\nboundary");
+    }
+
+    #[cot::test]
+    async fn boundary_in_body_fails() {
+        let response = MultipartResponse::builder()
+            .boundary("frame")
+            .parts([Part::new("data\r\n--frame\r\nmore")]);
+
+        let error = body_of(response).await.unwrap_err();
+
+        assert!(error.to_string().contains("contains the boundary `frame`"));
+    }
+
+    #[cot::test]
+    async fn stream_error_terminates_body() {
+        let parts = stream::iter([
+            Ok(Part::new("first")),
+            Err(crate::Error::internal("camera disconnected")),
+        ]);
+
+        let error = body_of(MultipartResponse::builder().stream(parts))
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("camera disconnected"));
+    }
+}
@@ -0,0 +1,314 @@
+use cot_core::error::impl_into_cot_error;
+use http::{HeaderValue, header};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::html::Html;
+use crate::json::Json;
+use crate::request::extractors::Accept;
+use crate::response::{IntoResponse, Response};
+
+/// A type that can be rendered as an HTML page and, optionally, as plain text.
+///
+/// This is used by [`Negotiate`] to render the value when the client prefers
+/// HTML or plain text over JSON.
+///
+/// # Examples
+///
+/// ```
+/// use cot::Template;
+/// use cot::html::Html;
+/// use cot::response::Render;
+///
+/// #[derive(serde::Serialize, Template)]
+/// #[template(source = "<h1>{{ title }}</h1>", ext = "html")]
+/// struct Todo {
+///     title: String,
+/// }
+///
+/// impl Render for Todo {
+///     fn render_html(&self) -> cot::Result<Html> {
+///         Ok(Html::new(self.render()?))
+///     }
+///
+///     fn render_text(&self) -> Option<cot::Result<String>> {
+///         Some(Ok(self.title.clone()))
+///     }
+/// }
+/// ```
+pub trait Render {
+    /// Renders the value as an HTML page.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value could not be rendered, e.g. because of
+    /// a template error.
+    fn render_html(&self) -> crate::Result<Html>;
+
+    /// Renders the value as plain text, or returns [`None`] if the value
+    /// can't be rendered as plain text, which is the default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value could not be rendered.
+    fn render_text(&self) -> Option<crate::Result<String>> {
+        None
+    }
+}
+
+/// A response that renders a value as JSON, HTML, or plain text, depending on
+/// the `Accept` header of the request.
+///
+/// This is useful for the endpoints that are shared by an API and HTML views.
+/// The value is rendered:
+/// * as JSON (with the `application/json` content type) using its
+///   [`Serialize`] implementation,
+/// * as HTML (with the `text/html` content type) using
+///   [`Render::render_html`],
+/// * as plain text (with the `text/plain` content type) using
+///   [`Render::render_text`], if it's supported.
+///
+/// The format with the highest quality in the `Accept` header is chosen; if
+/// multiple formats have the same quality (e.g. when the header is `*/*` or
+/// is missing), the order above is used. If the client doesn't accept any of
+/// these formats, a `406 Not Acceptable` error is returned. The response
+/// contains the `Vary: Accept` header, so that the caches store the
+/// responses for different `Accept` headers separately.
+///
+/// # Examples
+///
+/// ```
+/// use cot::html::Html;
+/// use cot::request::extractors::Accept;
+/// use cot::response::{Negotiate, Render};
+///
+/// #[derive(serde::Serialize)]
+/// struct Todo {
+///     title: String,
+/// }
+///
+/// impl Render for Todo {
+///     fn render_html(&self) -> cot::Result<Html> {
+///         Ok(Html::new(format!("<h1>{}</h1>", self.title)))
+///     }
+/// }
+///
+/// async fn todo(accept: Accept) -> Negotiate<Todo> {
+///     let todo = Todo {
+///         title: "Buy milk".to_owned(),
+///     };
+///
+///     Negotiate::new(accept, todo)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Negotiate<T> {
+    accept: Accept,
+    value: T,
+}
+
+impl<T> Negotiate<T> {
+    /// Creates a new [`Negotiate`] response rendering the value in the format
+    /// preferred by the client.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::Html;
+    /// use cot::request::extractors::Accept;
+    /// use cot::response::{Negotiate, Render};
+    ///
+    /// #[derive(serde::Serialize)]
+    /// struct Status {
+    ///     ok: bool,
+    /// }
+    ///
+    /// impl Render for Status {
+    ///     fn render_html(&self) -> cot::Result<Html> {
+    ///         Ok(Html::new(format!("<p>ok: {}</p>", self.ok)))
+    ///     }
+    /// }
+    ///
+    /// async fn status(accept: Accept) -> Negotiate<Status> {
+    ///     Negotiate::new(accept, Status { ok: true })
+    /// }
+    /// ```
+    #[must_use]
+    pub fn new(accept: Accept, value: T) -> Self {
+        Self { accept, value }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Json,
+    Html,
+    Text,
+}
+
+impl<T: Serialize + Render> IntoResponse for Negotiate<T> {
+    fn into_response(self) -> crate::Result<Response> {
+        let mut formats: Vec<_> = [
+            (Format::Json, mime::APPLICATION_JSON),
+            (Format::Html, mime::TEXT_HTML),
+            (Format::Text, mime::TEXT_PLAIN),
+        ]
+        .into_iter()
+        .map(|(format, media_type)| (format, self.accept.quality(&media_type)))
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect();
+        // stable sort, so the formats with the same quality keep their order
+        formats.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        for (format, _) in formats {
+            let response = match format {
+                Format::Json => Json(self.value).into_response(),
+                Format::Html => self.value.render_html()?.into_response(),
+                Format::Text => match self.value.render_text() {
+                    Some(text) => text?.into_response(),
+                    None => continue,
+                },
+            };
+
+            return response.map(|mut response| {
+                response
+                    .headers_mut()
+                    .append(header::VARY, HeaderValue::from_static("accept"));
+                response
+            });
+        }
+
+        Err(NotAcceptable.into())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("the response cannot be rendered in any of the formats accepted by the client")]
+struct NotAcceptable;
+impl_into_cot_error!(NotAcceptable, NOT_ACCEPTABLE);
+
+#[cfg(test)]
+mod tests {
+    use cot_core::headers::{HTML_CONTENT_TYPE, JSON_CONTENT_TYPE, PLAIN_TEXT_CONTENT_TYPE};
+
+    use super::*;
+    use crate::StatusCode;
+    use crate::request::extractors::FromRequestHead;
+    use crate::test::TestRequestBuilder;
+
+    #[derive(Serialize)]
+    struct Todo {
+        title: &'static str,
+    }
+
+    impl Render for Todo {
+        fn render_html(&self) -> crate::Result<Html> {
+            Ok(Html::new(format!("<h1>{}</h1>", self.title)))
+        }
+    }
+
+    #[derive(Serialize)]
+    struct TextTodo(Todo);
+
+    impl Render for TextTodo {
+        fn render_html(&self) -> crate::Result<Html> {
+            self.0.render_html()
+        }
+
+        fn render_text(&self) -> Option<crate::Result<String>> {
+            Some(Ok(self.0.title.to_owned()))
+        }
+    }
+
+    async fn accept(header: Option<&'static str>) -> Accept {
+        let mut request = TestRequestBuilder::get("/").build();
+        if let Some(header) = header {
+            request
+                .headers_mut()
+                .insert(header::ACCEPT, HeaderValue::from_static(header));
+        }
+        let (head, _body) = request.into_parts();
+
+        Accept::from_request_head(&head).await.unwrap()
+    }
+
+    async fn negotiate<T: Serialize + Render>(
+        header: Option<&'static str>,
+        value: T,
+    ) -> crate::Result<(HeaderValue, String)> {
+        let response = Negotiate::new(accept(header).await, value).into_response()?;
+        assert_eq!(response.headers()[header::VARY], "accept");
+
+        let content_type = response.headers()[header::CONTENT_TYPE].clone();
+        let body = response.into_body().into_bytes().await?;
+        Ok((content_type, String::from_utf8(body.to_vec()).unwrap()))
+    }
+
+    #[cot::test]
+    async fn json() {
+        let todo = Todo { title: "Buy milk" };
+
+        let (content_type, body) = negotiate(Some("application/json"), todo).await.unwrap();
+
+        assert_eq!(content_type, JSON_CONTENT_TYPE);
+        assert_eq!(body, r#"{"title":"Buy milk"}"#);
+    }
+
+    #[cot::test]
+    async fn html() {
+        let todo = Todo { title: "Buy milk" };
+
+        let (content_type, body) = negotiate(
+            Some("text/html, application/xhtml+xml, application/xml;q=0.9, */*;q=0.8"),
+            todo,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(content_type, HTML_CONTENT_TYPE);
+        assert_eq!(body, "<h1>Buy milk</h1>");
+    }
+
+    #[cot::test]
+    async fn text() {
+        let todo = TextTodo(Todo { title: "Buy milk" });
+
+        let (content_type, body) = negotiate(Some("text/plain"), todo).await.unwrap();
+
+        assert_eq!(content_type, PLAIN_TEXT_CONTENT_TYPE);
+        assert_eq!(body, "Buy milk");
+    }
+
+    #[cot::test]
+    async fn text_unsupported() {
+        let todo = Todo { title: "Buy milk" };
+
+        let (content_type, _body) = negotiate(Some("text/plain, text/html;q=0.5"), todo)
+            .await
+            .unwrap();
+
+        assert_eq!(content_type, HTML_CONTENT_TYPE);
+    }
+
+    #[cot::test]
+    async fn any() {
+        let (content_type, _body) = negotiate(None, Todo { title: "Buy milk" }).await.unwrap();
+        assert_eq!(content_type, JSON_CONTENT_TYPE);
+
+        let (content_type, _body) = negotiate(Some("*/*"), Todo { title: "Buy milk" })
+            .await
+            .unwrap();
+        assert_eq!(content_type, JSON_CONTENT_TYPE);
+    }
+
+    #[cot::test]
+    async fn not_acceptable() {
+        let todo = Todo { title: "Buy milk" };
+
+        let error = negotiate(Some("image/png, application/json;q=0"), todo)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::NOT_ACCEPTABLE);
+    }
+}
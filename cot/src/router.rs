@@ -31,23 +31,55 @@ use cot_core::error::impl_into_cot_error;
 use cot_core::handler::{BoxRequestHandler, RequestHandler, into_box_request_handler};
 use cot_core::request::{AppName, RouteName};
 use derive_more::with_trait::Debug;
+use tower::{Layer, Service, ServiceExt};
 use tracing::debug;
 
+use crate::config::CanonicalUrlConfig;
 use crate::error::NotFound;
-use crate::request::{PathParams, Request, RequestExt, RequestHead};
+use crate::middleware::{IntoCotErrorLayer, IntoCotResponseLayer};
+use crate::project::WrappedMiddleware;
+use crate::request::{
+    ExpectedPathParams, HostParams, PathParams, Request, RequestExt, RequestHead,
+};
 use crate::response::Response;
+use crate::router::host::HostMatcher;
 use crate::router::path::{CaptureResult, PathMatcher, ReverseParamMap};
 use crate::{Error, Result};
 
+pub mod filters;
+mod host;
 pub mod method;
 pub mod path;
 
+pub(crate) use host::request_host;
+
 /// A router that can be used to route requests to their respective views.
 ///
 /// This struct is responsible for routing requests to their respective views.
 /// It can be created directly by calling the [`Router::with_urls`] method, and
 /// that's what is typically done in [`cot::App::router`] implementations.
 ///
+/// # Path parameters
+///
+/// When the project is booted, the path parameters of each route (including
+/// the parameters of the routes it is nested in) are checked against the
+/// [`Path`](crate::request::extractors::Path) extractors of its handler. If a
+/// route is missing a parameter the handler expects, or has a different number
+/// of parameters, the project fails to start with an error listing every such
+/// route.
+///
+/// Only the names and the number of the parameters are checked. The following
+/// is still only detected when a request is handled:
+///
+/// * values that can't be parsed into the parameter's type, e.g. `{todo_id}`
+///   with a non-numeric value extracted as `Path<i32>`,
+/// * parameters read by extractors that don't implement
+///   [`FromRequestHead::expected_path_params`], such as custom extractors or
+///   handlers reading [`PathParams`] directly,
+/// * parameters extracted into maps, which accept any parameters.
+///
+/// [`FromRequestHead::expected_path_params`]: crate::request::extractors::FromRequestHead::expected_path_params
+///
 /// # Examples
 ///
 /// ```
@@ -66,6 +98,9 @@ pub struct Router {
     app_name: Option<AppName>,
     urls: Vec<Route>,
     names: HashMap<RouteName, Arc<PathMatcher>>,
+    host: Option<Arc<HostMatcher>>,
+    #[debug("{:?}", fallback.as_ref().map(|_| "handler(...)"))]
+    fallback: Option<Arc<dyn BoxRequestHandler + Send + Sync>>,
 }
 
 impl Router {
@@ -115,26 +150,240 @@ impl Router {
             app_name: None,
             urls,
             names,
+            host: None,
+            fallback: None,
         }
     }
 
+    /// Constrain the given router to the requests sent to a host matching
+    /// the given pattern.
+    ///
+    /// The pattern consists of dot-separated labels, such as
+    /// `admin.example.com`. A label can be a parameter in curly braces, such
+    /// as `{tenant}.example.com`, which matches any single label (e.g. a
+    /// subdomain); the matched values are available to the handlers through
+    /// the [`HostParams`] extractor. The hosts are compared
+    /// case-insensitively, and the port is ignored.
+    ///
+    /// The routes of the returned router (including the fallback handler)
+    /// only match the requests whose `Host` header matches the pattern, so
+    /// that the other routers can handle the same paths for the other hosts.
+    /// The router can be nested in other routers, or used as the router of an
+    /// app, so that the app is only available under the given host.
+    ///
+    /// Note that the URLs of the routes in the returned router are still
+    /// reversed as paths, without the host.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the host pattern is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::Html;
+    /// use cot::request::HostParams;
+    /// use cot::router::{Route, Router};
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// async fn admin() -> Html {
+    ///     Html::new("admin")
+    /// }
+    ///
+    /// async fn tenant_home(host_params: HostParams) -> Html {
+    ///     Html::new(format!("home of {}", host_params.get("tenant").unwrap()))
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let router = Router::with_urls([
+    ///     Route::with_router(
+    ///         "",
+    ///         Router::with_host(
+    ///             "admin.example.com",
+    ///             Router::with_urls([Route::with_handler("/", admin)]),
+    ///         ),
+    ///     ),
+    ///     Route::with_router(
+    ///         "",
+    ///         Router::with_host(
+    ///             "{tenant}.example.com",
+    ///             Router::with_urls([Route::with_handler("/", tenant_home)]),
+    ///         ),
+    ///     ),
+    /// ]);
+    ///
+    /// let request = TestRequestBuilder::get("https://admin.example.com/").build();
+    /// let response = router.handle(request).await?;
+    /// assert_eq!(response.into_body().into_bytes().await?, "admin");
+    ///
+    /// let request = TestRequestBuilder::get("https://acme.example.com/").build();
+    /// let response = router.handle(request).await?;
+    /// assert_eq!(response.into_body().into_bytes().await?, "home of acme");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_host(host: &str, mut router: Router) -> Self {
+        router.host = Some(Arc::new(HostMatcher::new(host)));
+        router
+    }
+
+    /// Set a fallback handler that gets called when no route matches the
+    /// request path.
+    ///
+    /// When the router is nested in another one (for instance, when it's the
+    /// router of an app registered with a URL prefix), the fallback handler is
+    /// only called for the request paths starting with that prefix. If the
+    /// prefixes of multiple routers with a fallback handler match the request
+    /// path, the handler of the most deeply nested router is called. If none
+    /// of them does, a [`NotFound`] error is returned, resulting in the
+    /// project's error page.
+    ///
+    /// The fallback handler is only called when there is no route for the
+    /// request path in any of the routers, regardless of the order in which
+    /// the routers are registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::StatusCode;
+    /// use cot::html::Html;
+    /// use cot::json::Json;
+    /// use cot::response::IntoResponse;
+    /// use cot::router::{Route, Router};
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// async fn index() -> Html {
+    ///     Html::new("index")
+    /// }
+    ///
+    /// async fn api_not_found() -> impl IntoResponse {
+    ///     Json(serde_json::json!({"error": "not found"})).with_status(StatusCode::NOT_FOUND)
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let api_router = Router::with_urls([]).fallback(api_not_found);
+    /// let router = Router::with_urls([
+    ///     Route::with_handler("/", index),
+    ///     Route::with_router("/api", api_router),
+    /// ]);
+    ///
+    /// let request = TestRequestBuilder::get("/api/missing")
+    ///     .router(router.clone())
+    ///     .build();
+    /// let response = router.handle(request).await?;
+    /// assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    /// assert_eq!(
+    ///     response.into_body().into_bytes().await?,
+    ///     r#"{"error":"not found"}"#
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn fallback<HandlerParams, H>(mut self, handler: H) -> Self
+    where
+        HandlerParams: 'static,
+        H: RequestHandler<HandlerParams> + Send + Sync + 'static,
+    {
+        self.fallback = Some(Arc::new(into_box_request_handler(handler)));
+        self
+    }
+
+    /// Adds middleware to all the routes of this router, including the ones
+    /// in nested routers and the fallback handler.
+    ///
+    /// This is useful for applying middleware, such as authentication or rate
+    /// limiting, only to a part of the project (for instance, to the router
+    /// of a single app), instead of to all the routes with
+    /// [`RootHandlerBuilder::middleware`](crate::project::RootHandlerBuilder::middleware).
+    ///
+    /// This is equivalent to calling [`Route::with_middleware`] on every route
+    /// of this router, so the middleware only runs for the requests that match
+    /// one of the routes (or the fallback handler set before calling this
+    /// method). It runs after the middlewares added to the project, so it can
+    /// use e.g. the session or the authenticated user set by them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::html::Html;
+    /// use cot::middleware::AuthMiddleware;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn dashboard() -> Html {
+    ///     Html::new("dashboard")
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler("/dashboard/", dashboard)])
+    ///     .with_middleware(AuthMiddleware::new());
+    /// ```
+    #[must_use]
+    pub fn with_middleware<M>(self, middleware: M) -> Self
+    where
+        M: Layer<RouteHandlerService>,
+        WrappedMiddleware<M, RouteHandlerService>:
+            Service<Request, Response = Response, Error = Error> + Send + Sync + Clone + 'static,
+        <WrappedMiddleware<M, RouteHandlerService> as Service<Request>>::Future: Send,
+    {
+        self.apply_middleware(&middleware)
+    }
+
+    fn apply_middleware<M>(mut self, middleware: &M) -> Self
+    where
+        M: Layer<RouteHandlerService>,
+        WrappedMiddleware<M, RouteHandlerService>:
+            Service<Request, Response = Response, Error = Error> + Send + Sync + Clone + 'static,
+        <WrappedMiddleware<M, RouteHandlerService> as Service<Request>>::Future: Send,
+    {
+        self.urls = self
+            .urls
+            .into_iter()
+            .map(|route| route.apply_middleware(middleware))
+            .collect();
+        self.fallback = self
+            .fallback
+            .map(|handler| wrap_handler_with_middleware(handler, middleware));
+        self
+    }
+
     pub(crate) fn set_app_name(&mut self, app_name: AppName) {
         self.app_name = Some(app_name);
     }
 
-    async fn route(&self, mut request: Request, request_path: &str) -> Result<Response> {
+    async fn route(
+        &self,
+        mut request: Request,
+        host: Option<&str>,
+        request_path: &str,
+    ) -> Result<Response> {
         debug!("Routing request to {}", request_path);
 
-        if let Some(result) = self.get_handler(request_path) {
+        if let Some(result) = self
+            .get_handler(host, request_path)
+            .or_else(|| self.get_fallback(host, request_path))
+        {
             let mut path_params = PathParams::new();
             for (key, value) in result.params.iter().rev() {
                 path_params.insert(key.clone(), value.clone());
             }
             request.extensions_mut().insert(path_params);
+            if !result.host_params.is_empty() {
+                let mut host_params = PathParams::new();
+                for (key, value) in result.host_params {
+                    host_params.insert(key, value);
+                }
+                request
+                    .extensions_mut()
+                    .insert(HostParams::new(host_params));
+            }
             if let Some(app_name) = result.app_name {
                 request.extensions_mut().insert(app_name);
             }
             if let Some(name) = result.name {
+                tracing::Span::current().record("route", name.0.as_str());
                 request.extensions_mut().insert(name);
             }
             result.handler.handle(request).await
@@ -144,7 +393,9 @@ impl Router {
         }
     }
 
-    fn get_handler(&self, request_path: &str) -> Option<HandlerFound<'_>> {
+    fn get_handler(&self, host: Option<&str>, request_path: &str) -> Option<HandlerFound<'_>> {
+        let host_params = self.match_host(host)?;
+
         for route in &self.urls {
             if let Some(matches) = route.url.capture(request_path) {
                 let matches_fully = matches.matches_fully();
@@ -157,16 +408,18 @@ impl Router {
                                 app_name: self.app_name.clone(),
                                 name: route.name.clone(),
                                 params: Self::matches_to_path_params(&matches, Vec::new()),
+                                host_params,
                             });
                         }
                     }
                     RouteInner::Router(router) => {
-                        if let Some(result) = router.get_handler(matches.remaining_path) {
+                        if let Some(result) = router.get_handler(host, matches.remaining_path) {
                             return Some(HandlerFound {
                                 handler: result.handler,
                                 app_name: result.app_name.or_else(|| self.app_name.clone()),
                                 name: result.name,
                                 params: Self::matches_to_path_params(&matches, result.params),
+                                host_params: [host_params, result.host_params].concat(),
                             });
                         }
                     }
@@ -179,6 +432,7 @@ impl Router {
                                 app_name: self.app_name.clone(),
                                 name: route.name.clone(),
                                 params: Self::matches_to_path_params(&matches, Vec::new()),
+                                host_params,
                             });
                         }
                     }
@@ -189,6 +443,50 @@ impl Router {
         None
     }
 
+    /// Returns the fallback handler of the most deeply nested router whose
+    /// prefix matches the given path.
+    fn get_fallback(&self, host: Option<&str>, request_path: &str) -> Option<HandlerFound<'_>> {
+        let host_params = self.match_host(host)?;
+
+        for route in &self.urls {
+            if let RouteInner::Router(router) = &route.view
+                && let Some(matches) = route.url.capture(request_path)
+                && let Some(result) = router.get_fallback(host, matches.remaining_path)
+            {
+                return Some(HandlerFound {
+                    handler: result.handler,
+                    app_name: result.app_name.or_else(|| self.app_name.clone()),
+                    name: None,
+                    params: Self::matches_to_path_params(&matches, result.params),
+                    host_params: [host_params, result.host_params].concat(),
+                });
+            }
+        }
+
+        self.fallback.as_deref().map(|handler| HandlerFound {
+            handler,
+            app_name: self.app_name.clone(),
+            name: None,
+            params: Vec::new(),
+            host_params,
+        })
+    }
+
+    /// Returns the parameters captured from the host if the router is not
+    /// constrained to a host, or the host matches its pattern.
+    fn match_host(&self, host: Option<&str>) -> Option<Vec<(String, String)>> {
+        match &self.host {
+            Some(matcher) => matcher.capture(host?),
+            None => Some(Vec::new()),
+        }
+    }
+
+    /// Returns `true` if there is a handler for the given host and path in
+    /// this router, not counting the fallback handlers.
+    pub(crate) fn has_handler(&self, host: Option<&str>, request_path: &str) -> bool {
+        self.get_handler(host, request_path).is_some()
+    }
+
     fn matches_to_path_params(
         matches: &CaptureResult<'_, '_>,
         mut path_params: Vec<(String, String)>,
@@ -210,8 +508,9 @@ impl Router {
     ///
     /// This method re-throws any errors that occur in the request handler.
     pub async fn handle(&self, request: Request) -> Result<Response> {
+        let host = request_host(request.headers(), request.uri()).map(str::to_owned);
         let path = request.uri().path().to_owned();
-        self.route(request, &path).await
+        self.route(request, host.as_deref(), &path).await
     }
 
     /// Generates a URL for a view using its name.
@@ -296,6 +595,91 @@ impl Router {
         Ok(None)
     }
 
+    /// Returns the names of the parameters of the route with the given name,
+    /// including the parameters of the routes it is nested in, or [`None`] if
+    /// the route doesn't exist.
+    ///
+    /// The route is looked up the same way as in [`Self::reverse_option`].
+    pub(crate) fn route_param_names(
+        &self,
+        app_name: Option<&str>,
+        name: &str,
+    ) -> Option<Vec<&str>> {
+        if app_name.is_some()
+            && self.app_name.is_some()
+            && app_name != self.app_name.as_ref().map(|name| name.0.as_str())
+        {
+            return None;
+        }
+
+        if let Some(matcher) = self.names.get(&RouteName(String::from(name))) {
+            return Some(matcher.param_names().collect());
+        }
+
+        for route in &self.urls {
+            if let RouteInner::Router(router) = &route.view
+                && let Some(param_names) = router.route_param_names(app_name, name)
+            {
+                return Some(route.url.param_names().chain(param_names).collect());
+            }
+        }
+        None
+    }
+
+    /// Returns the descriptions of the routes whose handlers expect different
+    /// path parameters than the routes have, including the parameters of the
+    /// routes they are nested in.
+    pub(crate) fn path_param_mismatches(&self) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        self.collect_path_param_mismatches("", &[], &mut mismatches);
+        mismatches
+    }
+
+    /// Checks that the routes have the path parameters their handlers expect,
+    /// returning an error that lists every mismatched route.
+    ///
+    /// This is run when the project is booted, so that a route whose `Path`
+    /// extractor can't succeed makes the project fail to start.
+    pub(crate) fn verify_path_params(&self) -> Result<()> {
+        let mismatches = self.path_param_mismatches();
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(PathParamMismatches(mismatches).into())
+        }
+    }
+
+    fn collect_path_param_mismatches<'a>(
+        &'a self,
+        url_prefix: &str,
+        prefix_param_names: &[&'a str],
+        mismatches: &mut Vec<String>,
+    ) {
+        for route in &self.urls {
+            let url = format!("{url_prefix}{}", route.url);
+            let param_names: Vec<_> = prefix_param_names
+                .iter()
+                .copied()
+                .chain(route.url.param_names())
+                .collect();
+
+            if let RouteInner::Router(router) = &route.view {
+                router.collect_path_param_mismatches(&url, &param_names, mismatches);
+                continue;
+            }
+
+            for expected in &route.expected_path_params {
+                if !expected.matches(&param_names) {
+                    mismatches.push(format!(
+                        "route `{url}` has {} parameter(s) ({}), but its handler expects {expected}",
+                        param_names.len(),
+                        param_names.join(", "),
+                    ));
+                }
+            }
+        }
+    }
+
     /// Get the routes in this router.
     ///
     /// # Examples
@@ -444,6 +828,14 @@ impl Default for Router {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "route path parameters don't match the `Path` extractors of their handlers:\n{}",
+    .0.join("\n")
+)]
+struct PathParamMismatches(Vec<String>);
+impl_into_cot_error!(PathParamMismatches);
+
 #[derive(Debug, thiserror::Error)]
 #[error("failed to reverse route `{view_name}` due to view not existing")]
 struct NoViewToReverse {
@@ -459,6 +851,7 @@ struct HandlerFound<'a> {
     app_name: Option<AppName>,
     name: Option<RouteName>,
     params: Vec<(String, String)>,
+    host_params: Vec<(String, String)>,
 }
 
 /// A service that routes requests to their respective views.
@@ -496,7 +889,7 @@ impl RouterService {
     }
 }
 
-impl tower::Service<Request> for RouterService {
+impl Service<Request> for RouterService {
     type Error = Error;
     type Future = Pin<Box<dyn Future<Output = Result<Self::Response>> + Send>>;
     type Response = Response;
@@ -511,6 +904,116 @@ impl tower::Service<Request> for RouterService {
     }
 }
 
+/// A [`tower::Service`] calling the handler of a route.
+///
+/// This is the service wrapped by the middlewares added with
+/// [`Route::with_middleware`] and [`Router::with_middleware`].
+#[derive(Debug, Clone)]
+pub struct RouteHandlerService {
+    #[debug("handler(...)")]
+    handler: Arc<dyn BoxRequestHandler + Send + Sync>,
+}
+
+impl Service<Request> for RouteHandlerService {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response>> + Send>>;
+    type Response = Response;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let handler = self.handler.clone();
+        Box::pin(async move { handler.handle(req).await })
+    }
+}
+
+fn wrap_with_middleware<M>(
+    service: RouteHandlerService,
+    middleware: &M,
+) -> WrappedMiddleware<M, RouteHandlerService>
+where
+    M: Layer<RouteHandlerService>,
+{
+    (
+        IntoCotErrorLayer::new(),
+        IntoCotResponseLayer::new(),
+        middleware,
+    )
+        .layer(service)
+}
+
+fn wrap_handler_with_middleware<M>(
+    handler: Arc<dyn BoxRequestHandler + Send + Sync>,
+    middleware: &M,
+) -> Arc<dyn BoxRequestHandler + Send + Sync>
+where
+    M: Layer<RouteHandlerService>,
+    WrappedMiddleware<M, RouteHandlerService>:
+        Service<Request, Response = Response, Error = Error> + Send + Sync + Clone + 'static,
+    <WrappedMiddleware<M, RouteHandlerService> as Service<Request>>::Future: Send,
+{
+    let service = wrap_with_middleware(RouteHandlerService { handler }, middleware);
+    Arc::new(MiddlewareHandler(service))
+}
+
+/// A request handler calling a route handler wrapped by middlewares.
+struct MiddlewareHandler<S>(S);
+
+impl<S> BoxRequestHandler for MiddlewareHandler<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Send + Sync + Clone,
+    S::Future: Send,
+{
+    fn handle(
+        &self,
+        request: Request,
+    ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send + '_>> {
+        Box::pin(self.0.clone().oneshot(request))
+    }
+}
+
+/// An OpenAPI route handler wrapped by middlewares.
+#[cfg(feature = "openapi")]
+struct MiddlewareApiHandler<S> {
+    service: S,
+    handler: Arc<dyn crate::openapi::BoxApiEndpointRequestHandler + Send + Sync>,
+}
+
+#[cfg(feature = "openapi")]
+impl<S> BoxRequestHandler for MiddlewareApiHandler<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Send + Sync + Clone,
+    S::Future: Send,
+{
+    fn handle(
+        &self,
+        request: Request,
+    ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send + '_>> {
+        Box::pin(self.service.clone().oneshot(request))
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl<S> crate::openapi::AsApiRoute for MiddlewareApiHandler<S> {
+    fn as_api_route(
+        &self,
+        route_context: &crate::openapi::RouteContext<'_>,
+        schema_generator: &mut schemars::SchemaGenerator,
+    ) -> aide::openapi::PathItem {
+        self.handler.as_api_route(route_context, schema_generator)
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl<S> crate::openapi::BoxApiEndpointRequestHandler for MiddlewareApiHandler<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Send + Sync + Clone,
+    S::Future: Send,
+{
+}
+
 // used in the reverse! macro; not part of public API
 #[doc(hidden)]
 #[must_use]
@@ -545,6 +1048,8 @@ pub struct Route {
     url: Arc<PathMatcher>,
     view: RouteInner,
     name: Option<RouteName>,
+    /// The path parameters expected by the extractors of the handler.
+    expected_path_params: Vec<ExpectedPathParams>,
 }
 
 impl Route {
@@ -570,10 +1075,12 @@ impl Route {
         HandlerParams: 'static,
         H: RequestHandler<HandlerParams> + Send + Sync + 'static,
     {
+        let expected_path_params = handler.expected_path_params();
         Self {
             url: Arc::new(PathMatcher::new(url)),
             view: RouteInner::Handler(Arc::new(into_box_request_handler(handler))),
             name: None,
+            expected_path_params,
         }
     }
 
@@ -605,12 +1112,14 @@ impl Route {
         HandlerParams: 'static,
         H: RequestHandler<HandlerParams> + crate::openapi::AsApiRoute + Send + Sync + 'static,
     {
+        let expected_path_params = handler.expected_path_params();
         Self {
             url: Arc::new(PathMatcher::new(url)),
             view: RouteInner::ApiHandler(Arc::new(
                 crate::openapi::into_box_api_endpoint_request_handler(handler),
             )),
             name: None,
+            expected_path_params,
         }
     }
 
@@ -638,10 +1147,12 @@ impl Route {
         HandlerParams: 'static,
         H: RequestHandler<HandlerParams> + Send + Sync + 'static,
     {
+        let expected_path_params = handler.expected_path_params();
         Self {
             url: Arc::new(PathMatcher::new(url)),
             view: RouteInner::Handler(Arc::new(into_box_request_handler(handler))),
             name: Some(RouteName(name.into())),
+            expected_path_params,
         }
     }
 
@@ -674,15 +1185,77 @@ impl Route {
         HandlerParams: 'static,
         H: RequestHandler<HandlerParams> + crate::openapi::AsApiRoute + Send + Sync + 'static,
     {
+        let expected_path_params = handler.expected_path_params();
         Self {
             url: Arc::new(PathMatcher::new(url)),
             view: RouteInner::ApiHandler(Arc::new(
                 crate::openapi::into_box_api_endpoint_request_handler(handler),
             )),
             name: Some(RouteName(name.into())),
+            expected_path_params,
         }
     }
 
+    /// Create a new route upgrading the requests to WebSocket connections and
+    /// passing them to the given handler.
+    ///
+    /// If the handler needs other data from the request, use the
+    /// [`WebSocketUpgrade`](crate::websocket::WebSocketUpgrade) extractor in a
+    /// regular request handler instead. See the [`crate::websocket`] module
+    /// documentation for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::{Route, Router};
+    /// use cot::websocket::{Message, WebSocket};
+    ///
+    /// async fn chat(mut socket: WebSocket) -> cot::Result<()> {
+    ///     socket.send(Message::Text("Welcome!".to_owned())).await
+    /// }
+    ///
+    /// let route = Route::with_websocket_handler("/chat/", chat);
+    /// ```
+    #[cfg(feature = "websocket")]
+    pub fn with_websocket_handler<H, Fut>(url: &str, handler: H) -> Self
+    where
+        H: FnOnce(crate::websocket::WebSocket) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self::with_handler(url, crate::websocket::WebSocketRequestHandler(handler))
+    }
+
+    /// Create a new route upgrading the requests to WebSocket connections and
+    /// passing them to the given handler, with the given name.
+    ///
+    /// See [`Self::with_websocket_handler`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::router::{Route, Router};
+    /// use cot::websocket::{Message, WebSocket};
+    ///
+    /// async fn chat(mut socket: WebSocket) -> cot::Result<()> {
+    ///     socket.send(Message::Text("Welcome!".to_owned())).await
+    /// }
+    ///
+    /// let route = Route::with_websocket_handler_and_name("/chat/", chat, "chat");
+    /// ```
+    #[cfg(feature = "websocket")]
+    pub fn with_websocket_handler_and_name<N, H, Fut>(url: &str, handler: H, name: N) -> Self
+    where
+        N: Into<String>,
+        H: FnOnce(crate::websocket::WebSocket) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self::with_handler_and_name(
+            url,
+            crate::websocket::WebSocketRequestHandler(handler),
+            name,
+        )
+    }
+
     /// Create a new route with the given router.
     ///
     /// # Examples
@@ -705,9 +1278,72 @@ impl Route {
             url: Arc::new(PathMatcher::new(url)),
             view: RouteInner::Router(router),
             name: None,
+            expected_path_params: Vec::new(),
         }
     }
 
+    /// Adds middleware to this route.
+    ///
+    /// The middleware only runs for the requests handled by this route, after
+    /// the middlewares added to the project with
+    /// [`RootHandlerBuilder::middleware`](crate::project::RootHandlerBuilder::middleware).
+    /// Middlewares added later wrap the ones added earlier, so they process
+    /// the requests first. If the route contains a router, the middleware is
+    /// added to all of its routes, as with [`Router::with_middleware`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::html::Html;
+    /// use cot::middleware::TimeoutMiddleware;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn report() -> Html {
+    ///     Html::new("report")
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler("/report/", report)
+    ///     .with_middleware(TimeoutMiddleware::new(Duration::from_secs(60)))]);
+    /// ```
+    #[must_use]
+    pub fn with_middleware<M>(self, middleware: M) -> Self
+    where
+        M: Layer<RouteHandlerService>,
+        WrappedMiddleware<M, RouteHandlerService>:
+            Service<Request, Response = Response, Error = Error> + Send + Sync + Clone + 'static,
+        <WrappedMiddleware<M, RouteHandlerService> as Service<Request>>::Future: Send,
+    {
+        self.apply_middleware(&middleware)
+    }
+
+    fn apply_middleware<M>(mut self, middleware: &M) -> Self
+    where
+        M: Layer<RouteHandlerService>,
+        WrappedMiddleware<M, RouteHandlerService>:
+            Service<Request, Response = Response, Error = Error> + Send + Sync + Clone + 'static,
+        <WrappedMiddleware<M, RouteHandlerService> as Service<Request>>::Future: Send,
+    {
+        self.view = match self.view {
+            RouteInner::Handler(handler) => {
+                RouteInner::Handler(wrap_handler_with_middleware(handler, middleware))
+            }
+            RouteInner::Router(router) => RouteInner::Router(router.apply_middleware(middleware)),
+            #[cfg(feature = "openapi")]
+            RouteInner::ApiHandler(handler) => {
+                let service = wrap_with_middleware(
+                    RouteHandlerService {
+                        handler: handler.clone(),
+                    },
+                    middleware,
+                );
+                RouteInner::ApiHandler(Arc::new(MiddlewareApiHandler { service, handler }))
+            }
+        };
+        self
+    }
+
     /// Get the URL for this route.
     ///
     /// # Examples
@@ -858,6 +1494,74 @@ macro_rules! reverse {
     }};
 }
 
+/// Get the canonical URL for a view by its registered name and given params.
+///
+/// This works like [`reverse!`], but the resulting URL is normalized according
+/// to the [`CanonicalUrlConfig`] of the project: its trailing slash is
+/// adjusted to the [`CanonicalUrlConfig::trailing_slash`] setting, and if
+/// [`CanonicalUrlConfig::host`] is set, the URL is absolute. This is useful
+/// for generating links that leave the website, such as the ones in emails,
+/// feeds or sitemaps.
+///
+/// # Return value
+///
+/// Returns a [`cot::Result<String>`] that contains the URL for the view. You
+/// will typically want to append `?` to the macro call to get the URL.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::{CanonicalUrlConfig, ProjectConfig};
+/// use cot::html::Html;
+/// use cot::router::{Route, Router, Urls};
+/// use cot::test::TestRequestBuilder;
+/// use cot::{RequestHandler, reverse_canonical};
+///
+/// async fn my_handler(urls: Urls) -> cot::Result<Html> {
+///     let url = reverse_canonical!(urls, "home")?;
+///     Ok(Html::new(url))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let router = Router::with_urls([Route::with_handler_and_name("/", my_handler, "home")]);
+/// let config = ProjectConfig::builder()
+///     .canonical_url(CanonicalUrlConfig::builder().host("example.com").build())
+///     .build();
+/// let request = TestRequestBuilder::get("/")
+///     .router(router)
+///     .config(config)
+///     .build();
+///
+/// assert_eq!(
+///     my_handler
+///         .handle(request)
+///         .await?
+///         .into_body()
+///         .into_bytes()
+///         .await?,
+///     "https://example.com/"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! reverse_canonical {
+    ($request:expr, $view_name:literal $(, $($key:ident = $value:expr),*)?) => {{
+        #[allow(
+            clippy::allow_attributes,
+            unused_imports,
+            reason = "allow using either `Request` or `Urls` objects"
+        )]
+        use $crate::request::RequestExt;
+        $crate::reverse!(
+            $request,
+            $view_name,
+            $( $($key = $value),* )?
+        ).map(|url| $request.canonical_url_config().url_for(&url))
+    }};
+}
+
 /// A helper structure to allow reversing URLs from a request handler.
 ///
 /// This is mainly useful as an extractor to allow reversing URLs without
@@ -897,6 +1601,7 @@ macro_rules! reverse {
 pub struct Urls {
     app_name: Option<String>,
     router: Arc<Router>,
+    canonical_url: CanonicalUrlConfig,
 }
 
 impl Urls {
@@ -924,6 +1629,15 @@ impl Urls {
         Self {
             app_name: request.app_name().map(ToOwned::to_owned),
             router: Arc::clone(request.router()),
+            canonical_url: request.canonical_url_config().clone(),
+        }
+    }
+
+    pub(crate) fn new(router: Arc<Router>, canonical_url: CanonicalUrlConfig) -> Self {
+        Self {
+            app_name: None,
+            router,
+            canonical_url,
         }
     }
 
@@ -931,6 +1645,7 @@ impl Urls {
         Self {
             app_name: request_head.app_name().map(ToOwned::to_owned),
             router: Arc::clone(request_head.router()),
+            canonical_url: request_head.canonical_url_config().clone(),
         }
     }
 
@@ -977,6 +1692,27 @@ impl Urls {
     pub fn router(&self) -> &Router {
         &self.router
     }
+
+    /// Get the canonical URL configuration of the project.
+    ///
+    /// This is mainly useful for the [`reverse_canonical!`] macro.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::router::Urls;
+    ///
+    /// async fn my_handler(urls: Urls) -> cot::Result<Response> {
+    ///     let home_url = urls.canonical_url_config().url_for("/");
+    ///     // ... do something with the URL
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn canonical_url_config(&self) -> &CanonicalUrlConfig {
+        &self.canonical_url
+    }
 }
 
 impl Debug for RouteInner {
@@ -1038,6 +1774,7 @@ mod tests {
     use crate::StatusCode;
     use crate::html::Html;
     use crate::request::Request;
+    use crate::request::extractors::Path;
     use crate::response::{IntoResponse, Response};
     use crate::test::TestRequestBuilder;
 
@@ -1111,7 +1848,7 @@ mod tests {
     async fn router_route() {
         let route = Route::with_handler("/test", MockHandler);
         let router = Router::with_urls(vec![route.clone()]);
-        let response = router.route(test_request(), "/test").await.unwrap();
+        let response = router.route(test_request(), None, "/test").await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
 
@@ -1141,6 +1878,156 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[cot::test]
+    async fn router_fallback() {
+        async fn site_not_found() -> Html {
+            Html::new("site")
+        }
+        async fn api_not_found(request: Request) -> Html {
+            let version: String = request.path_params().parse().unwrap();
+            Html::new(format!("api {version}"))
+        }
+
+        async fn body(router: &Router, path: &str) -> String {
+            let response = router
+                .handle(TestRequestBuilder::get(path).build())
+                .await
+                .unwrap();
+            String::from_utf8(response.into_body().into_bytes().await.unwrap().to_vec()).unwrap()
+        }
+
+        let api_router = Router::with_urls(vec![Route::with_handler("/test", MockHandler)])
+            .fallback(api_not_found);
+        let router = Router::with_urls(vec![
+            Route::with_router("/api/{version}", api_router),
+            Route::with_handler("/test", MockHandler),
+        ])
+        .fallback(site_not_found);
+
+        assert_eq!(body(&router, "/api/v1/test").await, "OK");
+        assert_eq!(body(&router, "/api/v1/missing").await, "api v1");
+        assert_eq!(body(&router, "/missing").await, "site");
+        assert!(!router.has_handler(None, "/api/v1/missing"));
+    }
+
+    #[cot::test]
+    async fn router_with_host() {
+        async fn tenant(host_params: HostParams, Path(region): Path<String>) -> Html {
+            Html::new(format!(
+                "tenant {} {region}",
+                host_params.get("tenant").unwrap()
+            ))
+        }
+        async fn site_not_found() -> Html {
+            Html::new("site")
+        }
+
+        async fn handle(router: &Router, host: &str, path: &str) -> Result<String> {
+            let mut request = TestRequestBuilder::get(path).build();
+            request
+                .headers_mut()
+                .insert(http::header::HOST, host.parse().unwrap());
+            let response = router.handle(request).await?;
+            Ok(String::from_utf8(response.into_body().into_bytes().await?.to_vec()).unwrap())
+        }
+
+        let router = Router::with_urls(vec![
+            Route::with_router(
+                "",
+                Router::with_host(
+                    "admin.example.com",
+                    Router::with_urls(vec![Route::with_handler("/", MockHandler)]),
+                ),
+            ),
+            Route::with_router(
+                "/{region}",
+                Router::with_host(
+                    "{tenant}.example.com",
+                    Router::with_urls(vec![Route::with_handler("/", tenant)]),
+                ),
+            ),
+        ])
+        .fallback(site_not_found);
+
+        assert_eq!(
+            handle(&router, "admin.example.com:8000", "/")
+                .await
+                .unwrap(),
+            "OK"
+        );
+        assert_eq!(
+            handle(&router, "acme.example.com", "/eu/").await.unwrap(),
+            "tenant acme eu"
+        );
+        assert_eq!(
+            handle(&router, "example.com", "/eu/").await.unwrap(),
+            "site"
+        );
+        assert!(!router.has_handler(Some("admin.example.org"), "/"));
+        assert!(!router.has_handler(None, "/"));
+    }
+
+    #[cot::test]
+    async fn route_with_middleware() {
+        let router = Router::with_urls(vec![
+            Route::with_handler("/with", MockHandler).with_middleware(test_middleware("route")),
+            Route::with_handler("/without", MockHandler),
+        ]);
+
+        let response = router
+            .handle(TestRequestBuilder::get("/with").build())
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-middleware"], "route");
+        let response = router
+            .handle(TestRequestBuilder::get("/without").build())
+            .await
+            .unwrap();
+        assert!(!response.headers().contains_key("x-middleware"));
+    }
+
+    #[cot::test]
+    async fn router_with_middleware() {
+        let sub_router = Router::with_urls(vec![Route::with_handler("/test", MockHandler)]);
+        let router = Router::with_urls(vec![
+            Route::with_handler("/test", MockHandler),
+            Route::with_router("/sub", sub_router),
+        ])
+        .fallback(MockHandler)
+        .with_middleware(test_middleware("router"));
+
+        for path in ["/test", "/sub/test", "/missing"] {
+            let response = router
+                .handle(TestRequestBuilder::get(path).build())
+                .await
+                .unwrap();
+            assert_eq!(response.headers()["x-middleware"], "router");
+        }
+    }
+
+    fn test_middleware(
+        value: &'static str,
+    ) -> tower::util::MapResponseLayer<impl Fn(Response) -> Response + Clone> {
+        tower::util::MapResponseLayer::new(move |mut response: Response| {
+            response
+                .headers_mut()
+                .insert("x-middleware", http::HeaderValue::from_static(value));
+            response
+        })
+    }
+
+    #[cot::test]
+    async fn router_no_fallback() {
+        let sub_router = Router::with_urls(vec![Route::with_handler("/test", MockHandler)]);
+        let router = Router::with_urls(vec![Route::with_router("/sub", sub_router)]);
+
+        let result = router
+            .handle(TestRequestBuilder::get("/sub/missing").build())
+            .await;
+
+        assert_eq!(result.unwrap_err().status_code(), StatusCode::NOT_FOUND);
+    }
+
     #[test]
     fn router_reverse() {
         let route = Route::with_handler_and_name("/test", MockHandler, "test");
@@ -1206,6 +2093,65 @@ mod tests {
         assert_eq!(url, "/test");
     }
 
+    #[test]
+    fn router_route_param_names() {
+        let route = Route::with_handler_and_name("/posts/{post_id}", MockHandler, "post");
+        let mut app_router = Router::with_urls(vec![route]);
+        app_router.set_app_name(AppName("blog".to_string()));
+        let root_router = Router::with_urls(vec![Route::with_router("/{lang}", app_router)]);
+
+        assert_eq!(
+            root_router.route_param_names(Some("blog"), "post"),
+            Some(vec!["lang", "post_id"])
+        );
+        assert_eq!(
+            root_router.route_param_names(None, "post"),
+            Some(vec!["lang", "post_id"])
+        );
+        assert_eq!(root_router.route_param_names(Some("shop"), "post"), None);
+        assert_eq!(root_router.route_param_names(None, "missing"), None);
+    }
+
+    #[test]
+    fn router_path_param_mismatches() {
+        #[derive(serde::Deserialize)]
+        struct PostParams {
+            #[expect(dead_code)]
+            lang: String,
+            #[expect(dead_code)]
+            post_id: i32,
+        }
+
+        async fn post(_params: Path<PostParams>) -> Html {
+            unimplemented!()
+        }
+        async fn post_by_number(_params: Path<i32>) -> Html {
+            unimplemented!()
+        }
+        async fn post_comment(_params: Path<(String, i32, i32)>) -> Html {
+            unimplemented!()
+        }
+
+        let app_router = Router::with_urls(vec![
+            Route::with_handler("/posts/{post_id}", post),
+            Route::with_handler("/posts/{id}", post_by_number),
+            Route::with_handler(
+                "/posts/{post_id}/comments/{comment_id}",
+                method::get(post_comment),
+            ),
+            Route::with_handler("/posts/{post_id}/comments/", MockHandler),
+        ]);
+        let root_router = Router::with_urls(vec![Route::with_router("/{lang}", app_router)]);
+
+        assert_eq!(
+            root_router.path_param_mismatches(),
+            vec![
+                "route `/{lang}/posts/{id}` has 2 parameter(s) (lang, id), but its handler \
+                expects 1 parameter(s)"
+            ]
+        );
+    }
+
     #[test]
     fn router_routes() {
         let route = Route::with_handler("/test", MockHandler);
@@ -1269,6 +2215,31 @@ mod tests {
         assert_eq!(response.headers().get("location").unwrap(), "/test/123");
     }
 
+    #[test]
+    fn test_reverse_canonical_macro() {
+        let route = Route::with_handler_and_name("/test/{id}", MockHandler, "test");
+        let router = Router::with_urls(vec![route]);
+        let config = crate::config::ProjectConfig::builder()
+            .canonical_url(
+                CanonicalUrlConfig::builder()
+                    .host("example.com")
+                    .trailing_slash(crate::config::TrailingSlash::Always)
+                    .build(),
+            )
+            .build();
+
+        let request = TestRequestBuilder::get("/")
+            .router(router)
+            .config(config)
+            .build();
+        let url = cot::reverse_canonical!(request, "test", id = 123).unwrap();
+        assert_eq!(url, "https://example.com/test/123/");
+
+        let urls = Urls::from_request(&request);
+        let url = cot::reverse_canonical!(urls, "test", id = 123).unwrap();
+        assert_eq!(url, "https://example.com/test/123/");
+    }
+
     fn test_request() -> Request {
         TestRequestBuilder::get("/test").build()
     }
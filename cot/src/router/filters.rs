@@ -0,0 +1,351 @@
+//! Template filters for generating URLs.
+//!
+//! The [`url`] filter reverses a named route in an askama template, just like
+//! the [`reverse!`](crate::reverse) macro does, but takes the route parameters
+//! positionally, in the order they appear in the route's path:
+//!
+//! ```
+//! use cot::Template;
+//! use cot::router::{Route, Router, Urls};
+//! use cot::test::TestRequestBuilder;
+//!
+//! mod filters {
+//!     pub(crate) use cot::router::filters::url;
+//! }
+//!
+//! #[derive(Template)]
+//! #[template(
+//!     source = r#"<a href="{{ urls|url("remove-todo", todo_id) }}">Remove</a>"#,
+//!     ext = "html"
+//! )]
+//! struct TodoTemplate {
+//!     urls: Urls,
+//!     todo_id: i32,
+//! }
+//!
+//! # async fn remove_todo() {}
+//! # fn main() -> cot::Result<()> {
+//! let router = Router::with_urls([Route::with_handler_and_name(
+//!     "/todos/{todo_id}/remove",
+//!     remove_todo,
+//!     "remove-todo",
+//! )]);
+//! let request = TestRequestBuilder::get("/").router(router).build();
+//! let template = TodoTemplate {
+//!     urls: Urls::from_request(&request),
+//!     todo_id: 123,
+//! };
+//!
+//! assert_eq!(
+//!     template.render()?,
+//!     r#"<a href="/todos/123/remove">Remove</a>"#
+//! );
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Askama filters take exactly one argument per parameter declared in the
+//! filter signature, so the route parameters are passed as a single value:
+//! either the value itself for routes with one parameter, a tuple for routes
+//! with more than one parameter, or `()` for routes without parameters:
+//!
+//! ```html
+//! <a href="{{ urls|url("index", ()) }}">Home</a>
+//! <a href="{{ urls|url("remove-todo", todo.id) }}">Remove</a>
+//! <a href="{{ urls|url("post", (post.year, &post.slug)) }}">Read more</a>
+//! ```
+//!
+//! Note that `{{ urls|url("post", post.year, post.slug) }}` doesn't compile.
+//!
+//! # Validation
+//!
+//! The view names and the parameter counts are only checked when the template
+//! is rendered, not when it is compiled: routes are registered at runtime, so
+//! there is no route table to validate the template against at compile time.
+//! Rendering a template that refers to a view that doesn't exist, or passes
+//! the wrong number of parameters, returns an error instead.
+//!
+//! To catch these mistakes before the project is deployed, run the `verify`
+//! command, which checks the `url` filters in the template files against the
+//! project router.
+
+use std::fmt::Display;
+
+use cot_core::error::impl_into_cot_error;
+
+use crate::__private::askama;
+use crate::router::path::ReverseParamMap;
+use crate::router::{Urls, split_view_name};
+
+/// Get a URL for a view by its registered name and given positional params.
+///
+/// The view name can be prefixed with the app name (`"app:view"`). Otherwise,
+/// the view is looked up in the app of the current request first. `params` is
+/// either a single value, a tuple of values, or `()` for routes without any
+/// parameters; see [`UrlParams`].
+///
+/// # Errors
+///
+/// Returns an error if the view doesn't exist, or if the number of parameters
+/// doesn't match the number of parameters of the route.
+///
+/// # Examples
+///
+/// ```html
+/// <a href="{{ urls|url("index", ()) }}">Home</a>
+/// <a href="{{ urls|url("todos:remove-todo", todo.id) }}">Remove</a>
+/// <a href="{{ urls|url("post", (post.year, &post.slug)) }}">Read more</a>
+/// ```
+pub use self::url_filter::url;
+
+mod url_filter {
+    #![expect(
+        missing_docs,
+        clippy::missing_errors_doc,
+        clippy::inline_always,
+        reason = "the filter is documented at its re-export; the items are generated by askama"
+    )]
+
+    use super::{UrlParams, askama, reverse_positional};
+    use crate::router::Urls;
+
+    #[crate::filter_fn]
+    pub fn url<P: UrlParams>(
+        urls: &Urls,
+        _env: &dyn askama::Values,
+        view_name: &str,
+        params: P,
+    ) -> askama::Result<String> {
+        reverse_positional(urls, view_name, &params).map_err(askama::Error::custom)
+    }
+}
+
+fn reverse_positional<P: UrlParams + ?Sized>(
+    urls: &Urls,
+    view_name: &str,
+    params: &P,
+) -> crate::Result<String> {
+    let (app_name, view_name) = split_view_name(view_name);
+    let app_name = app_name.or_else(|| urls.app_name());
+    let router = urls.router();
+
+    let values = params.url_params();
+    let mut param_map = ReverseParamMap::new();
+    if let Some(param_names) = router.route_param_names(app_name, view_name) {
+        if param_names.len() != values.len() {
+            return Err(UrlParamCountMismatch {
+                view_name: view_name.to_owned(),
+                expected: param_names.len(),
+                actual: values.len(),
+            }
+            .into());
+        }
+        for (name, value) in param_names.into_iter().zip(values) {
+            param_map.insert(name, value);
+        }
+    }
+
+    router.reverse(app_name, view_name, &param_map)
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("failed to reverse route `{view_name}`: expected {expected} parameters, got {actual}")]
+struct UrlParamCountMismatch {
+    view_name: String,
+    expected: usize,
+    actual: usize,
+}
+impl_into_cot_error!(UrlParamCountMismatch);
+
+/// Positional route parameters passed to the [`url`] filter.
+///
+/// This is implemented for `()` (no parameters), for tuples of up to 6
+/// [`Display`] values, and for the primitive types and strings, so that a
+/// single parameter doesn't have to be wrapped in a tuple. Values of other
+/// types can be passed as a single-element tuple, e.g. `(value,)`. Values that
+/// aren't [`Copy`] have to be borrowed inside tuples, e.g. `(year, &slug)`.
+pub trait UrlParams {
+    /// Returns the parameter values, in the order of the route's parameters.
+    fn url_params(&self) -> Vec<String>;
+}
+
+impl<T: UrlParams + ?Sized> UrlParams for &T {
+    fn url_params(&self) -> Vec<String> {
+        (**self).url_params()
+    }
+}
+
+impl UrlParams for () {
+    fn url_params(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+macro_rules! impl_url_params_for_value {
+    ($($ty:ty),*) => {
+        $(
+            impl UrlParams for $ty {
+                fn url_params(&self) -> Vec<String> {
+                    vec![self.to_string()]
+                }
+            }
+        )*
+    };
+}
+
+impl_url_params_for_value!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, bool, char, str, String
+);
+
+macro_rules! impl_url_params_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Display),+> UrlParams for ($($name,)+) {
+            fn url_params(&self) -> Vec<String> {
+                #[expect(non_snake_case, reason = "the bindings reuse the type parameter names")]
+                let ($($name,)+) = self;
+                vec![$($name.to_string()),+]
+            }
+        }
+    };
+}
+
+impl_url_params_for_tuple!(A);
+impl_url_params_for_tuple!(A, B);
+impl_url_params_for_tuple!(A, B, C);
+impl_url_params_for_tuple!(A, B, C, D);
+impl_url_params_for_tuple!(A, B, C, D, E);
+impl_url_params_for_tuple!(A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Template;
+    use crate::router::{Route, Router};
+    use crate::test::TestRequestBuilder;
+
+    mod filters {
+        pub(crate) use crate::router::filters::url;
+    }
+
+    async fn test_handler() {}
+
+    fn test_urls() -> Urls {
+        let router = Router::with_urls([
+            Route::with_handler_and_name("/", test_handler, "index"),
+            Route::with_handler_and_name("/todos/{todo_id}/remove", test_handler, "remove-todo"),
+            Route::with_handler_and_name("/posts/{year}/{slug}", test_handler, "post"),
+        ]);
+        let request = TestRequestBuilder::get("/").router(router).build();
+        Urls::from_request(&request)
+    }
+
+    #[derive(Template)]
+    #[template(
+        source = r#"{{ urls|url("index", ()) }} {{ urls|url("remove-todo", todo_id) }} {{ urls|url("post", (year, &slug)) }}"#,
+        ext = "txt"
+    )]
+    struct UrlsTemplate {
+        urls: Urls,
+        todo_id: i32,
+        year: u32,
+        slug: String,
+    }
+
+    #[derive(Template)]
+    #[template(source = r#"{{ urls|url("missing", ()) }}"#, ext = "txt")]
+    struct MissingViewTemplate {
+        urls: Urls,
+    }
+
+    #[derive(Template)]
+    #[template(source = r#"{{ urls|url("post", slug) }}"#, ext = "txt")]
+    struct WrongParamCountTemplate {
+        urls: Urls,
+        slug: String,
+    }
+
+    #[derive(Template)]
+    #[template(source = r#"{{ urls|url("remove-todo", (1, 2)) }}"#, ext = "txt")]
+    struct TooManyParamsTemplate {
+        urls: Urls,
+    }
+
+    #[derive(Template)]
+    #[template(source = r#"{{ urls|url("remove-todo", ()) }}"#, ext = "txt")]
+    struct MissingParamsTemplate {
+        urls: Urls,
+    }
+
+    #[test]
+    fn url_filter() {
+        let template = UrlsTemplate {
+            urls: test_urls(),
+            todo_id: 123,
+            year: 2024,
+            slug: "hello-world".to_owned(),
+        };
+
+        assert_eq!(
+            template.render().unwrap(),
+            "/ /todos/123/remove /posts/2024/hello-world"
+        );
+    }
+
+    #[test]
+    fn url_filter_missing_view() {
+        let template = MissingViewTemplate { urls: test_urls() };
+
+        let error = template.render().unwrap_err();
+
+        assert!(error.to_string().contains("view not existing"), "{error}");
+    }
+
+    #[test]
+    fn url_filter_wrong_param_count() {
+        let template = WrongParamCountTemplate {
+            urls: test_urls(),
+            slug: "hello-world".to_owned(),
+        };
+
+        let error = template.render().unwrap_err();
+
+        assert!(
+            error.to_string().contains("expected 2 parameters, got 1"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn url_filter_too_many_params() {
+        let template = TooManyParamsTemplate { urls: test_urls() };
+
+        let error = template.render().unwrap_err();
+
+        assert!(
+            error.to_string().contains("expected 1 parameters, got 2"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn url_filter_missing_params() {
+        let template = MissingParamsTemplate { urls: test_urls() };
+
+        let error = template.render().unwrap_err();
+
+        assert!(
+            error.to_string().contains("expected 1 parameters, got 0"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn reverse_positional_tuple() {
+        let urls = test_urls();
+
+        assert_eq!(
+            reverse_positional(&urls, "remove-todo", &(5,)).unwrap(),
+            "/todos/5/remove"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Host matching for host-based routing.
+
+use std::fmt::Display;
+
+use http::{HeaderMap, Uri, header};
+use tracing::debug;
+
+use crate::router::path::PathMatcher;
+
+/// A matcher for host patterns, such as `admin.example.com` or
+/// `{tenant}.example.com`.
+///
+/// The pattern consists of dot-separated labels; each label is either a
+/// literal that is compared case-insensitively, or a parameter in curly
+/// braces that matches exactly one label of the host.
+#[derive(Debug, Clone)]
+pub(super) struct HostMatcher {
+    labels: Vec<HostLabel>,
+}
+
+impl HostMatcher {
+    #[must_use]
+    pub(super) fn new(host_pattern: &str) -> Self {
+        let host_pattern = host_pattern.strip_suffix('.').unwrap_or(host_pattern);
+        assert!(!host_pattern.is_empty(), "Host pattern cannot be empty");
+
+        let labels = host_pattern
+            .split('.')
+            .map(|label| {
+                assert!(
+                    !label.is_empty(),
+                    "Empty label in host pattern: `{host_pattern}`"
+                );
+
+                if let Some(param) = label.strip_prefix('{') {
+                    let param_name = param
+                        .strip_suffix('}')
+                        .unwrap_or_else(|| panic!("Unclosed parameter: `{param}`"))
+                        .trim();
+                    assert!(
+                        PathMatcher::is_param_name_valid(param_name),
+                        "Invalid parameter name: `{param_name}`"
+                    );
+                    HostLabel::Param {
+                        name: param_name.to_owned(),
+                    }
+                } else {
+                    assert!(
+                        !label.contains(['{', '}']),
+                        "Parameters must span a whole label: `{label}`"
+                    );
+                    HostLabel::Literal(label.to_ascii_lowercase())
+                }
+            })
+            .collect();
+
+        Self { labels }
+    }
+
+    /// Matches the host (without the port) against the pattern, returning the
+    /// captured parameters if it matches.
+    #[must_use]
+    pub(super) fn capture(&self, host: &str) -> Option<Vec<(String, String)>> {
+        debug!("Matching host `{}` against pattern `{}`", host, self);
+
+        let host = host.strip_suffix('.').unwrap_or(host);
+        let host_labels: Vec<_> = host.split('.').collect();
+        if host_labels.len() != self.labels.len() {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        for (label, host_label) in self.labels.iter().zip(host_labels) {
+            match label {
+                HostLabel::Literal(literal) => {
+                    if !literal.eq_ignore_ascii_case(host_label) {
+                        return None;
+                    }
+                }
+                HostLabel::Param { name } => {
+                    if host_label.is_empty() {
+                        return None;
+                    }
+                    params.push((name.clone(), host_label.to_ascii_lowercase()));
+                }
+            }
+        }
+
+        Some(params)
+    }
+}
+
+impl Display for HostMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, label) in self.labels.iter().enumerate() {
+            if index > 0 {
+                write!(f, ".")?;
+            }
+            match label {
+                HostLabel::Literal(literal) => write!(f, "{literal}")?,
+                HostLabel::Param { name } => write!(f, "{{{name}}}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum HostLabel {
+    Literal(String),
+    Param { name: String },
+}
+
+/// Returns the host the request was sent to, without the port.
+///
+/// The `Host` header is used if it's present; otherwise, the authority from
+/// the request URI (which is how HTTP/2 requests specify the host) is used.
+pub(crate) fn request_host<'a>(headers: &'a HeaderMap, uri: &'a Uri) -> Option<&'a str> {
+    let host = match headers.get(header::HOST) {
+        Some(host) => host.to_str().ok()?,
+        None => uri.authority()?.as_str(),
+    };
+
+    if host.starts_with('[') {
+        // IPv6 address, e.g. `[::1]:8000`
+        let end = host.find(']')?;
+        return Some(&host[..=end]);
+    }
+
+    Some(host.split_once(':').map_or(host, |(host, _port)| host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn host_matcher_literal() {
+        let matcher = HostMatcher::new("admin.example.com");
+
+        assert_eq!(matcher.capture("admin.example.com"), Some(params(&[])));
+        assert_eq!(matcher.capture("Admin.Example.com."), Some(params(&[])));
+        assert_eq!(matcher.capture("example.com"), None);
+        assert_eq!(matcher.capture("www.admin.example.com"), None);
+    }
+
+    #[test]
+    fn host_matcher_params() {
+        let matcher = HostMatcher::new("{tenant}.{ region }.example.com");
+
+        assert_eq!(
+            matcher.capture("Acme.eu.example.com"),
+            Some(params(&[("tenant", "acme"), ("region", "eu")]))
+        );
+        assert_eq!(matcher.capture("acme.example.com"), None);
+        assert_eq!(matcher.capture(".eu.example.com"), None);
+    }
+
+    #[test]
+    fn host_matcher_display() {
+        let matcher = HostMatcher::new("{tenant}.Example.com");
+
+        assert_eq!(matcher.to_string(), "{tenant}.example.com");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid parameter name: `123`")]
+    fn host_matcher_invalid_param_name() {
+        let _ = HostMatcher::new("{123}.example.com");
+    }
+
+    #[test]
+    #[should_panic(expected = "Parameters must span a whole label: `app-{tenant}`")]
+    fn host_matcher_partial_label_param() {
+        let _ = HostMatcher::new("app-{tenant}.example.com");
+    }
+
+    #[test]
+    fn request_host_port() {
+        let mut headers = HeaderMap::new();
+        let uri = Uri::from_static("https://example.org:8443/path");
+        assert_eq!(request_host(&headers, &uri), Some("example.org"));
+
+        headers.insert(header::HOST, "example.com:8000".parse().unwrap());
+        assert_eq!(request_host(&headers, &uri), Some("example.com"));
+
+        headers.insert(header::HOST, "[::1]:8000".parse().unwrap());
+        assert_eq!(request_host(&headers, &uri), Some("[::1]"));
+    }
+}
@@ -8,7 +8,7 @@ use std::fmt::{Debug, Formatter};
 use cot_core::handler::{BoxRequestHandler, into_box_request_handler};
 
 use crate::error::MethodNotAllowed;
-use crate::request::Request;
+use crate::request::{ExpectedPathParams, Request};
 use crate::response::Response;
 use crate::{Method, RequestHandler};
 
@@ -248,6 +248,10 @@ impl RequestHandler for MethodRouter {
     fn handle(&self, request: Request) -> impl Future<Output = cot::Result<Response>> + Send {
         self.inner.handle(request)
     }
+
+    fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+        self.inner.expected_path_params()
+    }
 }
 
 #[derive(Debug)]
@@ -316,9 +320,35 @@ impl<T: RequestHandler + Send + Sync> RequestHandler for InnerMethodRouter<T> {
 
         self.fallback.handle(request).await
     }
+
+    fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+        [
+            &self.get,
+            &self.head,
+            &self.delete,
+            &self.options,
+            &self.patch,
+            &self.post,
+            &self.put,
+            &self.trace,
+        ]
+        .into_iter()
+        .flatten()
+        .flat_map(RequestHandler::expected_path_params)
+        .chain(
+            self.connect
+                .iter()
+                .flat_map(RequestHandler::expected_path_params),
+        )
+        .chain(self.fallback.expected_path_params())
+        .collect()
+    }
 }
 
-struct InnerHandler(Box<dyn BoxRequestHandler + Send + Sync>);
+struct InnerHandler {
+    handler: Box<dyn BoxRequestHandler + Send + Sync>,
+    expected_path_params: Vec<ExpectedPathParams>,
+}
 
 impl InnerHandler {
     fn new<HandlerParams, H>(handler: H) -> Self
@@ -326,7 +356,10 @@ impl InnerHandler {
         HandlerParams: 'static,
         H: RequestHandler<HandlerParams> + Send + Sync + 'static,
     {
-        Self(Box::new(into_box_request_handler(handler)))
+        Self {
+            expected_path_params: handler.expected_path_params(),
+            handler: Box::new(into_box_request_handler(handler)),
+        }
     }
 }
 
@@ -338,7 +371,11 @@ impl Debug for InnerHandler {
 
 impl RequestHandler for InnerHandler {
     fn handle(&self, request: Request) -> impl Future<Output = cot::Result<Response>> + Send {
-        self.0.handle(request)
+        self.handler.handle(request)
+    }
+
+    fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+        self.expected_path_params.clone()
     }
 }
 
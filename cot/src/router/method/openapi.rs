@@ -8,7 +8,7 @@ use std::fmt::{Debug, Formatter};
 
 use aide::openapi::Operation;
 use cot::openapi::RouteContext;
-use cot::request::Request;
+use cot::request::{ExpectedPathParams, Request};
 use cot::response::Response;
 use cot::router::method::InnerHandler;
 use schemars::SchemaGenerator;
@@ -318,6 +318,10 @@ impl RequestHandler for ApiMethodRouter {
     fn handle(&self, request: Request) -> impl Future<Output = cot::Result<Response>> + Send {
         self.inner.handle(request)
     }
+
+    fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+        self.inner.expected_path_params()
+    }
 }
 
 impl AsApiRoute for ApiMethodRouter {
@@ -352,7 +356,10 @@ impl AsApiRoute for ApiMethodRouter {
     }
 }
 
-struct InnerApiHandler(Box<dyn BoxApiRequestHandler + Send + Sync>);
+struct InnerApiHandler {
+    handler: Box<dyn BoxApiRequestHandler + Send + Sync>,
+    expected_path_params: Vec<ExpectedPathParams>,
+}
 
 impl InnerApiHandler {
     fn new<HandlerParams, ApiParams, H>(handler: H) -> Self
@@ -361,7 +368,10 @@ impl InnerApiHandler {
         ApiParams: 'static,
         H: RequestHandler<HandlerParams> + AsApiOperation<ApiParams> + Send + Sync + 'static,
     {
-        Self(Box::new(into_box_api_request_handler(handler)))
+        Self {
+            expected_path_params: handler.expected_path_params(),
+            handler: Box::new(into_box_api_request_handler(handler)),
+        }
     }
 }
 
@@ -373,7 +383,11 @@ impl Debug for InnerApiHandler {
 
 impl RequestHandler for InnerApiHandler {
     fn handle(&self, request: Request) -> impl Future<Output = cot::Result<Response>> + Send {
-        self.0.handle(request)
+        self.handler.handle(request)
+    }
+
+    fn expected_path_params(&self) -> Vec<ExpectedPathParams> {
+        self.expected_path_params.clone()
     }
 }
 
@@ -383,7 +397,8 @@ impl AsApiOperation for InnerApiHandler {
         route_context: &RouteContext<'_>,
         schema_generator: &mut SchemaGenerator,
     ) -> Option<Operation> {
-        self.0.as_api_operation(route_context, schema_generator)
+        self.handler
+            .as_api_operation(route_context, schema_generator)
     }
 }
 
@@ -36,11 +36,7 @@ impl PathMatcher {
             .chain([None])
             .enumerate()
             .peekable();
-        loop {
-            let Some((index, ch)) = char_iter.next() else {
-                break;
-            };
-
+        while let Some((index, ch)) = char_iter.next() {
             match (ch, state) {
                 (Some('{') | None, State::Literal { start }) => {
                     let literal = &path_pattern[start..index];
@@ -98,7 +94,7 @@ impl PathMatcher {
         Self { parts }
     }
 
-    fn is_param_name_valid(name: &str) -> bool {
+    pub(super) fn is_param_name_valid(name: &str) -> bool {
         if name.is_empty() {
             return false;
         }
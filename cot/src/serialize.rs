@@ -0,0 +1,354 @@
+//! Serialization profiles for API responses.
+//!
+//! The same model is often returned by several API endpoints, each of them
+//! exposing a different set of its fields: a list endpoint might only return
+//! the ID and the title of each post, while the detail endpoint returns the
+//! whole post. Instead of writing a separate struct for each of these
+//! representations, you can derive [`ApiSerialize`] and mark the fields that
+//! should only be included in some of the *profiles*.
+//!
+//! The `#[api(...)]` field attribute accepts the following options:
+//!
+//! * `exclude` – never serialize the field, for instance because it contains
+//!   a password hash or other internal data,
+//! * `rename = "name"` – serialize the field under a different name,
+//! * `profile = "name"` – only serialize the field in the given profile; can
+//!   be given multiple times. Fields without any profiles are serialized in
+//!   all of them,
+//! * `nested` – serialize the field value using its own [`ApiSerialize`]
+//!   implementation with the same profile, instead of its [`Serialize`]
+//!   implementation.
+//!
+//! A value is serialized using a profile by wrapping it in [`Profiled`], which
+//! implements [`Serialize`], so it can be passed to
+//! [`Json`](crate::json::Json) or any other serde-based response.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::serialize::ApiSerialize;
+//!
+//! #[derive(ApiSerialize)]
+//! struct Post {
+//!     id: i32,
+//!     title: String,
+//!     #[api(profile = "detail")]
+//!     content: String,
+//!     #[api(rename = "author")]
+//!     author_name: String,
+//!     #[api(exclude)]
+//!     moderation_notes: String,
+//! }
+//!
+//! let post = Post {
+//!     id: 1,
+//!     title: "Hello".to_owned(),
+//!     content: "Hello, world!".to_owned(),
+//!     author_name: "Alice".to_owned(),
+//!     moderation_notes: "ok".to_owned(),
+//! };
+//!
+//! assert_eq!(
+//!     serde_json::to_string(&post.with_profile("list")).unwrap(),
+//!     r#"{"id":1,"title":"Hello","author":"Alice"}"#
+//! );
+//! ```
+
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
+
+/// Derive macro for the [`ApiSerialize`] trait.
+///
+/// See the [module-level documentation](self) for the supported attributes.
+pub use cot_macros::ApiSerialize;
+
+/// The name of the profile used when none is specified explicitly.
+///
+/// Only the fields that are not restricted to any profiles are serialized in
+/// it.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A type that can be serialized differently depending on the profile.
+///
+/// Typically, this is implemented using the [`ApiSerialize`] derive macro.
+/// See the [module-level documentation](self) for more information.
+pub trait ApiSerialize {
+    /// Serializes the value using the given profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails.
+    fn serialize_profile<S: Serializer>(
+        &self,
+        profile: &str,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+
+    /// Wraps the value in [`Profiled`], so that it's serialized using the
+    /// given profile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::json::Json;
+    /// use cot::serialize::ApiSerialize;
+    ///
+    /// #[derive(ApiSerialize)]
+    /// struct Post {
+    ///     id: i32,
+    ///     #[api(profile = "detail")]
+    ///     content: String,
+    /// }
+    ///
+    /// async fn list_posts() -> Json<impl serde::Serialize> {
+    ///     let posts = vec![Post {
+    ///         id: 1,
+    ///         content: "Hello, world!".to_owned(),
+    ///     }];
+    ///     Json(posts.with_profile("list"))
+    /// }
+    /// ```
+    fn with_profile(self, profile: &'static str) -> Profiled<Self>
+    where
+        Self: Sized,
+    {
+        Profiled::new(self, profile)
+    }
+}
+
+impl<T: ApiSerialize + ?Sized> ApiSerialize for &T {
+    fn serialize_profile<S: Serializer>(
+        &self,
+        profile: &str,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        (**self).serialize_profile(profile, serializer)
+    }
+}
+
+impl<T: ApiSerialize> ApiSerialize for Option<T> {
+    fn serialize_profile<S: Serializer>(
+        &self,
+        profile: &str,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(value) => value.serialize_profile(profile, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<T: ApiSerialize> ApiSerialize for [T] {
+    fn serialize_profile<S: Serializer>(
+        &self,
+        profile: &str,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for value in self {
+            seq.serialize_element(&ProfiledRef::new(value, profile))?;
+        }
+        seq.end()
+    }
+}
+
+impl<T: ApiSerialize> ApiSerialize for Vec<T> {
+    fn serialize_profile<S: Serializer>(
+        &self,
+        profile: &str,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize_profile(profile, serializer)
+    }
+}
+
+/// A value that is serialized using the given profile.
+///
+/// This is typically created using [`ApiSerialize::with_profile`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::serialize::{ApiSerialize, Profiled};
+///
+/// #[derive(ApiSerialize)]
+/// struct User {
+///     id: i32,
+///     #[api(profile = "detail")]
+///     email: String,
+/// }
+///
+/// let user = User {
+///     id: 1,
+///     email: "alice@example.com".to_owned(),
+/// };
+/// let profiled = Profiled::new(user, "detail");
+///
+/// assert_eq!(
+///     serde_json::to_string(&profiled).unwrap(),
+///     r#"{"id":1,"email":"alice@example.com"}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profiled<T> {
+    value: T,
+    profile: &'static str,
+}
+
+impl<T: ApiSerialize> Profiled<T> {
+    /// Wraps the value, so that it's serialized using the given profile.
+    #[must_use]
+    pub const fn new(value: T, profile: &'static str) -> Self {
+        Self { value, profile }
+    }
+
+    /// Returns the name of the profile.
+    #[must_use]
+    pub const fn profile(&self) -> &'static str {
+        self.profile
+    }
+
+    /// Returns the wrapped value.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: ApiSerialize> Serialize for Profiled<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize_profile(self.profile, serializer)
+    }
+}
+
+/// A reference to a value that is serialized using the given profile.
+///
+/// This is used by the [`ApiSerialize`] derive macro for the `nested` fields.
+#[doc(hidden)] // not part of the public API; used in the derive macro
+#[derive(Debug)]
+pub struct ProfiledRef<'a, T: ?Sized> {
+    value: &'a T,
+    profile: &'a str,
+}
+
+impl<'a, T: ApiSerialize + ?Sized> ProfiledRef<'a, T> {
+    #[must_use]
+    pub const fn new(value: &'a T, profile: &'a str) -> Self {
+        Self { value, profile }
+    }
+}
+
+impl<T: ApiSerialize + ?Sized> Serialize for ProfiledRef<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize_profile(self.profile, serializer)
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[derive(ApiSerialize)]
+    struct Comment {
+        id: i32,
+        #[api(profile = "detail")]
+        text: String,
+    }
+
+    fn comment(id: i32) -> Comment {
+        Comment {
+            id,
+            text: format!("comment {id}"),
+        }
+    }
+
+    #[derive(ApiSerialize)]
+    struct Post {
+        id: i32,
+        #[api(rename = "headline")]
+        title: String,
+        #[api(profile = "detail", profile = "admin")]
+        content: String,
+        #[api(profile = "admin")]
+        status: &'static str,
+        #[api(exclude)]
+        #[expect(dead_code)]
+        secret: String,
+        #[api(nested, profile = "detail")]
+        comments: Vec<Comment>,
+    }
+
+    fn post() -> Post {
+        Post {
+            id: 1,
+            title: "Hello".to_owned(),
+            content: "Hello, world!".to_owned(),
+            status: "published",
+            secret: "hidden".to_owned(),
+            comments: vec![comment(1), comment(2)],
+        }
+    }
+
+    fn to_json<T: ApiSerialize>(value: T, profile: &'static str) -> serde_json::Value {
+        serde_json::to_value(value.with_profile(profile)).unwrap()
+    }
+
+    #[test]
+    fn default_profile() {
+        assert_eq!(
+            to_json(post(), DEFAULT_PROFILE),
+            serde_json::json!({"id": 1, "headline": "Hello"})
+        );
+    }
+
+    #[test]
+    fn detail_profile() {
+        assert_eq!(
+            to_json(post(), "detail"),
+            serde_json::json!({
+                "id": 1,
+                "headline": "Hello",
+                "content": "Hello, world!",
+                "comments": [
+                    {"id": 1, "text": "comment 1"},
+                    {"id": 2, "text": "comment 2"},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn multiple_profiles() {
+        assert_eq!(
+            to_json(post(), "admin"),
+            serde_json::json!({
+                "id": 1,
+                "headline": "Hello",
+                "content": "Hello, world!",
+                "status": "published",
+            })
+        );
+    }
+
+    #[test]
+    fn collections() {
+        assert_eq!(
+            to_json(vec![comment(1), comment(2)], "list"),
+            serde_json::json!([{"id": 1}, {"id": 2}])
+        );
+        assert_eq!(
+            to_json(Some(comment(1)), "detail"),
+            serde_json::json!({"id": 1, "text": "comment 1"})
+        );
+        assert_eq!(to_json(None::<Comment>, "detail"), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn profiled() {
+        let profiled = Profiled::new(comment(1), "detail");
+
+        assert_eq!(profiled.profile(), "detail");
+        assert_eq!(profiled.into_inner().id, 1);
+    }
+}
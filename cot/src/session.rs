@@ -42,6 +42,50 @@ pub mod db;
 pub mod store;
 
 use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+/// The expiry of a single session, set with [`Session::set_expiry`].
+///
+/// This overrides the expiry configured in
+/// [`SessionMiddlewareConfig::expiry`](crate::config::SessionMiddlewareConfig::expiry)
+/// for one session only, for instance to implement a "remember me" checkbox
+/// on a login form.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::session::SessionExpiry;
+///
+/// let expiry = SessionExpiry::from(Duration::from_secs(3600));
+/// assert_eq!(expiry, SessionExpiry::Duration(Duration::from_secs(3600)));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SessionExpiry {
+    /// The session expires after the given duration of inactivity.
+    Duration(Duration),
+    /// The session expires when the user closes the browser.
+    AtBrowserClose,
+}
+
+impl From<Duration> for SessionExpiry {
+    fn from(duration: Duration) -> Self {
+        Self::Duration(duration)
+    }
+}
+
+impl From<SessionExpiry> for tower_sessions::Expiry {
+    fn from(expiry: SessionExpiry) -> Self {
+        match expiry {
+            SessionExpiry::Duration(duration) => Self::OnInactivity(
+                time::Duration::try_from(duration).unwrap_or(time::Duration::MAX),
+            ),
+            SessionExpiry::AtBrowserClose => Self::OnSessionEnd,
+        }
+    }
+}
 
 /// A session object.
 ///
@@ -138,6 +182,83 @@ impl Session {
         Self::from_extensions(request.extensions())
     }
 
+    /// Sets the expiry of this session, overriding the one set in the
+    /// project config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::session::{Session, SessionExpiry};
+    ///
+    /// async fn remember_me(session: Session) -> cot::Result<()> {
+    ///     session.set_expiry(Duration::from_secs(14 * 24 * 60 * 60));
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn forget_me(session: Session) -> cot::Result<()> {
+    ///     session.set_expiry(SessionExpiry::AtBrowserClose);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_expiry<E: Into<SessionExpiry>>(&self, expiry: E) {
+        self.inner.set_expiry(Some(expiry.into().into()));
+    }
+
+    /// Generates a new session ID while keeping the session data.
+    ///
+    /// This should be called whenever the privilege level of the user changes
+    /// (such as after logging in) to prevent
+    /// [session fixation](https://owasp.org/www-community/attacks/Session_fixation)
+    /// attacks. [`Auth::login`](crate::auth::Auth::login) does this
+    /// automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session could not be loaded from or removed
+    /// from the session store.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::Session;
+    ///
+    /// async fn elevate(session: Session) -> cot::Result<()> {
+    ///     session.cycle_key().await?;
+    ///     session.insert("sudo", true).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn cycle_key(&self) -> Result<(), tower_sessions::session::Error> {
+        self.inner.cycle_id().await
+    }
+
+    /// Removes all the data from the session and deletes it from the session
+    /// store.
+    ///
+    /// The session cookie is removed from the client as well.
+    /// [`Auth::logout`](crate::auth::Auth::logout) does this automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session could not be deleted from the session
+    /// store.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::session::Session;
+    ///
+    /// async fn reset(session: Session) -> cot::Result<()> {
+    ///     session.flush().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn flush(&self) -> Result<(), tower_sessions::session::Error> {
+        self.inner.flush().await
+    }
+
     #[track_caller]
     #[must_use]
     pub(crate) fn from_extensions(extensions: &http::Extensions) -> &Self {
@@ -160,3 +281,78 @@ impl DerefMut for Session {
         &mut self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::session::store::memory::MemoryStore;
+
+    fn make_session() -> Session {
+        let inner = tower_sessions::Session::new(None, Arc::new(MemoryStore::new()), None);
+        Session::new(inner)
+    }
+
+    #[test]
+    fn session_expiry_into_tower_expiry() {
+        assert_eq!(
+            tower_sessions::Expiry::from(SessionExpiry::Duration(Duration::from_secs(60))),
+            tower_sessions::Expiry::OnInactivity(time::Duration::seconds(60))
+        );
+        assert_eq!(
+            tower_sessions::Expiry::from(SessionExpiry::AtBrowserClose),
+            tower_sessions::Expiry::OnSessionEnd
+        );
+        assert_eq!(
+            tower_sessions::Expiry::from(SessionExpiry::Duration(Duration::MAX)),
+            tower_sessions::Expiry::OnInactivity(time::Duration::MAX)
+        );
+    }
+
+    #[cot::test]
+    async fn set_expiry() {
+        let session = make_session();
+
+        session.set_expiry(Duration::from_secs(60));
+        assert_eq!(
+            session.expiry(),
+            Some(tower_sessions::Expiry::OnInactivity(
+                time::Duration::seconds(60)
+            ))
+        );
+        assert!(session.is_modified());
+
+        session.set_expiry(SessionExpiry::AtBrowserClose);
+        assert_eq!(session.expiry(), Some(tower_sessions::Expiry::OnSessionEnd));
+    }
+
+    #[cot::test]
+    async fn cycle_key() {
+        let session = make_session();
+        session.insert("key", "value").await.unwrap();
+        session.save().await.unwrap();
+        let old_id = session.id().unwrap();
+
+        session.cycle_key().await.unwrap();
+        session.save().await.unwrap();
+
+        assert_ne!(session.id(), Some(old_id));
+        assert_eq!(
+            session.get::<String>("key").await.unwrap(),
+            Some("value".to_owned())
+        );
+    }
+
+    #[cot::test]
+    async fn flush() {
+        let session = make_session();
+        session.insert("key", "value").await.unwrap();
+        session.save().await.unwrap();
+
+        session.flush().await.unwrap();
+
+        assert!(session.id().is_none());
+        assert!(session.is_empty().await);
+    }
+}
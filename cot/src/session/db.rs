@@ -4,7 +4,7 @@
 //! in a database using the Cot ORM.
 pub mod migrations;
 
-use cot::db::migrations::SyncDynMigration;
+use cot::db::migrations::{ModelSchema, SyncDynMigration};
 
 use crate::App;
 use crate::db::{Auto, model};
@@ -87,6 +87,10 @@ impl App for SessionApp {
     fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
         cot::db::migrations::wrap_migrations(migrations::MIGRATIONS)
     }
+
+    fn models(&self) -> Vec<ModelSchema> {
+        vec![ModelSchema::of::<Session>()]
+    }
 }
 
 #[cfg(test)]
@@ -106,4 +110,13 @@ mod tests {
         let migrations = app1.migrations();
         assert!(!migrations.is_empty());
     }
+
+    #[test]
+    fn test_session_app_models_match_migrations() {
+        let app = SessionApp::new();
+
+        let engine = crate::db::migrations::MigrationEngine::new(app.migrations()).unwrap();
+
+        assert_eq!(engine.check_models(&app.models()), vec![]);
+    }
 }
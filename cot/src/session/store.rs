@@ -7,7 +7,14 @@
 //! Session stores are responsible for persisting session data between requests.
 //! Different implementations store data in different places, such as memory,
 //! files, databases, or external caching services like Redis.
+//!
+//! All the stores provided by Cot also implement [`ManagedSessionStore`],
+//! which allows listing and cleaning up the stored sessions. This is exposed to
+//! the request handlers by the [`SessionStoreManager`] type, and is used by the
+//! `sessions` CLI command and the session management page in the admin panel.
 
+#[cfg(feature = "json")]
+pub mod cookie;
 #[cfg(all(feature = "db", feature = "json"))]
 pub mod db;
 #[cfg(feature = "json")]
@@ -19,9 +26,31 @@ pub mod redis;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use cot_core::error::impl_into_cot_error;
+use derive_more::with_trait::Debug;
+use serde::Deserialize;
+use thiserror::Error;
+use time::OffsetDateTime;
 use tower_sessions::session::{Id, Record};
 use tower_sessions::{SessionStore, session_store};
 
+use crate::auth::{USER_ID_SESSION_KEY, UserId};
+#[cfg(feature = "cache")]
+use crate::config::CacheType;
+use crate::config::SessionStoreTypeConfig;
+use crate::project::MiddlewareContext;
+use crate::request::RequestHead;
+use crate::request::extractors::FromRequestHead;
+#[cfg(feature = "json")]
+use crate::session::store::cookie::CookieStore;
+#[cfg(all(feature = "db", feature = "json"))]
+use crate::session::store::db::DbStore;
+#[cfg(feature = "json")]
+use crate::session::store::file::FileStore;
+use crate::session::store::memory::MemoryStore;
+#[cfg(feature = "redis")]
+use crate::session::store::redis::RedisStore;
+
 pub(crate) const MAX_COLLISION_RETRIES: u32 = 32;
 pub(crate) const ERROR_PREFIX: &str = "session store:";
 
@@ -83,3 +112,432 @@ impl SessionStore for SessionStoreWrapper {
         self.0.delete(session_id).await
     }
 }
+
+/// A session store that allows listing and cleaning up the stored sessions.
+///
+/// This is implemented by all the session stores provided by Cot and is used
+/// by the [`SessionStoreManager`].
+#[async_trait]
+pub trait ManagedSessionStore: SessionStore {
+    /// Returns all the session records in the store, including the expired
+    /// ones that haven't been removed yet.
+    async fn list(&self) -> session_store::Result<Vec<Record>>;
+
+    /// Removes all the expired sessions from the store.
+    ///
+    /// Returns the number of removed sessions. Stores that remove expired
+    /// sessions automatically (such as Redis) always return 0.
+    async fn delete_expired(&self) -> session_store::Result<u64>;
+}
+
+/// Creates a session store based on the configuration.
+///
+/// # Panics
+///
+/// Panics if the session store could not be created, for example, because the
+/// Redis server is not reachable.
+#[cfg_attr(
+    not(feature = "json"),
+    expect(
+        unused_variables,
+        reason = "context is only used by the cookie and database stores"
+    )
+)]
+pub(crate) fn session_store_from_config(
+    config: &SessionStoreTypeConfig,
+    context: &MiddlewareContext,
+) -> Arc<dyn ManagedSessionStore + Send + Sync> {
+    match config {
+        SessionStoreTypeConfig::Memory => Arc::new(MemoryStore::new()),
+        #[cfg(feature = "json")]
+        SessionStoreTypeConfig::File { path } => Arc::new(
+            FileStore::new(path.clone())
+                .unwrap_or_else(|err| panic!("could not create File store: {err}")),
+        ),
+        #[cfg(feature = "cache")]
+        SessionStoreTypeConfig::Cache { uri } => {
+            let cache_type = CacheType::try_from(uri.clone())
+                .unwrap_or_else(|e| panic!("could not convert cache URI `{uri}`: {e}"));
+            match cache_type {
+                #[cfg(feature = "redis")]
+                CacheType::Redis => Arc::new(
+                    RedisStore::new(uri)
+                        .unwrap_or_else(|e| panic!("could not connect to Redis at `{uri}`: {e}")),
+                ),
+            }
+        }
+        #[cfg(feature = "redis")]
+        SessionStoreTypeConfig::Redis { url, key_prefix } => Arc::new(
+            RedisStore::new(url)
+                .unwrap_or_else(|e| panic!("could not connect to Redis at `{url}`: {e}"))
+                .with_key_prefix(key_prefix.clone()),
+        ),
+        #[cfg(all(feature = "db", feature = "json"))]
+        SessionStoreTypeConfig::Database => Arc::new(DbStore::new(context.database().clone())),
+        #[cfg(feature = "json")]
+        SessionStoreTypeConfig::Cookie { max_size } => Arc::new(CookieStore::from_project_config(
+            context.config(),
+            *max_size,
+        )),
+    }
+}
+
+/// Information about a single session stored in a session store.
+///
+/// This is returned by the [`SessionStoreManager`] methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    id: Id,
+    expiry_date: OffsetDateTime,
+    user_id: Option<UserId>,
+}
+
+impl SessionInfo {
+    fn from_record(record: &Record) -> Self {
+        let user_id = record
+            .data
+            .get(USER_ID_SESSION_KEY)
+            .and_then(|user_id| UserId::deserialize(user_id).ok());
+
+        Self {
+            id: record.id,
+            expiry_date: record.expiry_date,
+            user_id,
+        }
+    }
+
+    /// Returns the ID of the session, which is the value stored in the
+    /// session cookie.
+    #[must_use]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns the date when the session expires.
+    #[must_use]
+    pub fn expiry_date(&self) -> OffsetDateTime {
+        self.expiry_date
+    }
+
+    /// Returns `true` if the session has already expired.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.expiry_date <= OffsetDateTime::now_utc()
+    }
+
+    /// Returns the ID of the user logged in with this session, if any.
+    #[must_use]
+    pub fn user_id(&self) -> Option<&UserId> {
+        self.user_id.as_ref()
+    }
+}
+
+/// A handle for managing the sessions stored in the project's session store.
+///
+/// This can be extracted in a request handler when the session middleware was
+/// created with
+/// [`SessionMiddleware::from_context`](crate::middleware::SessionMiddleware::from_context).
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::UserId;
+/// use cot::html::Html;
+/// use cot::session::store::SessionStoreManager;
+///
+/// async fn log_out_everywhere(sessions: SessionStoreManager) -> cot::Result<Html> {
+///     let removed = sessions.delete_for_user(&UserId::Int(1)).await?;
+///
+///     Ok(Html::new(format!("Removed {removed} sessions")))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionStoreManager(#[debug("..")] Arc<dyn ManagedSessionStore + Send + Sync>);
+
+impl SessionStoreManager {
+    /// Creates a new [`SessionStoreManager`] for the given store.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::session::store::SessionStoreManager;
+    /// use cot::session::store::memory::MemoryStore;
+    ///
+    /// let manager = SessionStoreManager::new(Arc::new(MemoryStore::new()));
+    /// ```
+    #[must_use]
+    pub fn new(store: Arc<dyn ManagedSessionStore + Send + Sync>) -> Self {
+        Self(store)
+    }
+
+    /// Returns all the sessions in the store, ordered by their expiry date.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session store could not be accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::session::store::SessionStoreManager;
+    /// use cot::session::store::memory::MemoryStore;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let manager = SessionStoreManager::new(Arc::new(MemoryStore::new()));
+    /// assert!(manager.list().await?.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list(&self) -> crate::Result<Vec<SessionInfo>> {
+        let mut sessions: Vec<_> = self
+            .0
+            .list()
+            .await
+            .map_err(SessionStoreError)?
+            .iter()
+            .map(SessionInfo::from_record)
+            .collect();
+        sessions.sort_by_key(SessionInfo::expiry_date);
+
+        Ok(sessions)
+    }
+
+    /// Returns the active sessions of the given user, ordered by their expiry
+    /// date.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session store could not be accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::auth::UserId;
+    /// use cot::session::store::SessionStoreManager;
+    /// use cot::session::store::memory::MemoryStore;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let manager = SessionStoreManager::new(Arc::new(MemoryStore::new()));
+    /// assert!(manager.list_for_user(&UserId::Int(1)).await?.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_for_user(&self, user_id: &UserId) -> crate::Result<Vec<SessionInfo>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|session| !session.is_expired() && session.user_id() == Some(user_id))
+            .collect())
+    }
+
+    /// Removes the session with the given ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session store could not be accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::session::store::SessionStoreManager;
+    /// use cot::session::store::memory::MemoryStore;
+    /// use tower_sessions::session::Id;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let manager = SessionStoreManager::new(Arc::new(MemoryStore::new()));
+    /// manager.delete(&Id::default()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete(&self, id: &Id) -> crate::Result<()> {
+        self.0.delete(id).await.map_err(SessionStoreError)?;
+        Ok(())
+    }
+
+    /// Removes all the sessions of the given user, logging them out on all
+    /// devices.
+    ///
+    /// Returns the number of removed sessions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session store could not be accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::auth::UserId;
+    /// use cot::session::store::SessionStoreManager;
+    /// use cot::session::store::memory::MemoryStore;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let manager = SessionStoreManager::new(Arc::new(MemoryStore::new()));
+    /// assert_eq!(manager.delete_for_user(&UserId::Int(1)).await?, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_for_user(&self, user_id: &UserId) -> crate::Result<u64> {
+        let sessions = self.list_for_user(user_id).await?;
+        for session in &sessions {
+            self.delete(&session.id).await?;
+        }
+
+        Ok(sessions.len() as u64)
+    }
+
+    /// Removes all the expired sessions from the store.
+    ///
+    /// Returns the number of removed sessions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session store could not be accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::session::store::SessionStoreManager;
+    /// use cot::session::store::memory::MemoryStore;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let manager = SessionStoreManager::new(Arc::new(MemoryStore::new()));
+    /// assert_eq!(manager.purge_expired().await?, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn purge_expired(&self) -> crate::Result<u64> {
+        Ok(self.0.delete_expired().await.map_err(SessionStoreError)?)
+    }
+}
+
+impl FromRequestHead for SessionStoreManager {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        head.extensions
+            .get::<Self>()
+            .cloned()
+            .ok_or_else(|| SessionStoreManagerMissing.into())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{ERROR_PREFIX} {0}")]
+struct SessionStoreError(#[source] session_store::Error);
+impl_into_cot_error!(SessionStoreError);
+
+#[derive(Debug, Error)]
+#[error(
+    "session store manager is not available; make sure the session middleware is created \
+     with `SessionMiddleware::from_context`"
+)]
+struct SessionStoreManagerMissing;
+impl_into_cot_error!(SessionStoreManagerMissing);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use time::Duration;
+
+    use super::*;
+    use crate::session::store::memory::MemoryStore;
+
+    fn make_record(user_id: Option<UserId>, expiry: Duration) -> Record {
+        let mut data = HashMap::new();
+        if let Some(user_id) = user_id {
+            data.insert(
+                USER_ID_SESSION_KEY.to_owned(),
+                serde_json::to_value(user_id).unwrap(),
+            );
+        }
+
+        Record {
+            id: Id::default(),
+            data,
+            expiry_date: OffsetDateTime::now_utc() + expiry,
+        }
+    }
+
+    async fn make_manager(records: &mut [Record]) -> SessionStoreManager {
+        let store = MemoryStore::new();
+        for record in records {
+            store.create(record).await.unwrap();
+        }
+
+        SessionStoreManager::new(Arc::new(store))
+    }
+
+    #[cot::test]
+    async fn manager_list() {
+        let mut records = [
+            make_record(Some(UserId::Int(1)), Duration::hours(2)),
+            make_record(None, Duration::hours(1)),
+        ];
+        let manager = make_manager(&mut records).await;
+
+        let sessions = manager.list().await.unwrap();
+
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id(), records[1].id);
+        assert_eq!(sessions[0].user_id(), None);
+        assert_eq!(sessions[1].id(), records[0].id);
+        assert_eq!(sessions[1].user_id(), Some(&UserId::Int(1)));
+    }
+
+    #[cot::test]
+    async fn manager_delete_for_user() {
+        let mut records = [
+            make_record(Some(UserId::Int(1)), Duration::hours(1)),
+            make_record(Some(UserId::Int(1)), Duration::hours(1)),
+            make_record(Some(UserId::String("1".to_owned())), Duration::hours(1)),
+            make_record(None, Duration::hours(1)),
+        ];
+        let manager = make_manager(&mut records).await;
+
+        let removed = manager.delete_for_user(&UserId::Int(1)).await.unwrap();
+
+        assert_eq!(removed, 2);
+        let sessions = manager.list().await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert!(
+            manager
+                .list_for_user(&UserId::Int(1))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[cot::test]
+    async fn manager_purge_expired() {
+        let mut records = [
+            make_record(None, Duration::hours(1)),
+            make_record(None, Duration::hours(-1)),
+        ];
+        let manager = make_manager(&mut records).await;
+
+        let removed = manager.purge_expired().await.unwrap();
+
+        assert_eq!(removed, 1);
+        let sessions = manager.list().await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id(), records[0].id);
+    }
+}
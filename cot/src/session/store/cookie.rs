@@ -0,0 +1,701 @@
+//! Cookie session store
+//!
+//! This module provides a session store that keeps the whole session in the
+//! session cookie instead of storing it on the server side.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::config::SecretKey;
+//! use cot::middleware::SessionMiddleware;
+//! use cot::session::store::cookie::CookieStore;
+//!
+//! let store = CookieStore::new(SecretKey::new(b"secret"))
+//!     .with_fallback_keys([SecretKey::new(b"old secret")])
+//!     .with_max_size(2048);
+//! let middleware = SessionMiddleware::new(store);
+//! ```
+//!
+//! The session record is serialized as JSON and signed with the project's
+//! [`SecretKey`] using HMAC-SHA256, so the clients can't tamper with it.
+//! However, the data is **not** encrypted, so it can be read by anyone who has
+//! access to the cookie. Don't store any secrets in the session when using
+//! this store.
+//!
+//! Cookies signed with one of the
+//! [fallback keys](crate::config::ProjectConfig::fallback_secret_keys) are
+//! accepted as well, and they are re-signed with the current secret key the
+//! next time the session is saved.
+//!
+//! Since the sessions only exist in the clients' cookies, the session cannot be
+//! invalidated on the server side (other than by rotating the secret key), and
+//! listing the sessions always returns an empty list.
+
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use futures_core::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use http::HeaderValue;
+use http::header::{COOKIE, SET_COOKIE};
+use sha2::Sha256;
+use thiserror::Error;
+use time::OffsetDateTime;
+use tower::Service;
+use tower_sessions::cookie::Cookie;
+use tower_sessions::session::{Id, Record};
+use tower_sessions::{SessionStore, session_store};
+
+use crate::config::{ProjectConfig, SecretKey};
+use crate::session::store::{ERROR_PREFIX, ManagedSessionStore};
+
+/// The default maximum size of the session cookie value, in bytes.
+///
+/// Most browsers reject cookies larger than 4096 bytes (including the cookie
+/// name and attributes), so this leaves some room for the rest of the cookie.
+pub const DEFAULT_MAX_SIZE: usize = 4000;
+
+type CookieHmac = Hmac<Sha256>;
+
+tokio::task_local! {
+    static CURRENT_SESSION: Arc<Mutex<CookieSessionState>>;
+}
+
+/// Errors that can occur when using the cookie session store.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CookieStoreError {
+    /// The encoded session is larger than the maximum allowed cookie size.
+    #[error(
+        "{ERROR_PREFIX} the session cookie is too large ({size} bytes, the maximum is {max_size} \
+         bytes)"
+    )]
+    TooLarge {
+        /// The size of the encoded session cookie value.
+        size: usize,
+        /// The maximum allowed size of the session cookie value.
+        max_size: usize,
+    },
+
+    /// An error occurred during JSON serialization.
+    #[error("{ERROR_PREFIX} serialization error: {0}")]
+    Serialize(serde_json::Error),
+
+    /// The store was used outside of the
+    /// [`SessionMiddleware`](crate::middleware::SessionMiddleware).
+    #[error("{ERROR_PREFIX} the cookie store can only be used inside the session middleware")]
+    OutsideMiddleware,
+}
+
+impl From<CookieStoreError> for session_store::Error {
+    fn from(err: CookieStoreError) -> session_store::Error {
+        match err {
+            CookieStoreError::TooLarge { .. } | CookieStoreError::Serialize(_) => {
+                session_store::Error::Encode(err.to_string())
+            }
+            CookieStoreError::OutsideMiddleware => session_store::Error::Backend(err.to_string()),
+        }
+    }
+}
+
+/// A session store that keeps the session data in a signed cookie.
+///
+/// See the [module documentation](self) for more details.
+///
+/// This store only works together with the
+/// [`SessionMiddleware`](crate::middleware::SessionMiddleware), which reads
+/// and writes the session cookie. It can be selected in the configuration with
+/// [`SessionStoreTypeConfig::Cookie`](crate::config::SessionStoreTypeConfig::Cookie).
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::SecretKey;
+/// use cot::session::store::cookie::CookieStore;
+///
+/// let store = CookieStore::new(SecretKey::new(b"secret"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CookieStore {
+    /// The secret key used for signing the cookies.
+    secret_key: SecretKey,
+    /// The keys that are accepted when verifying the cookies.
+    fallback_keys: Arc<[SecretKey]>,
+    /// The maximum size of the session cookie value, in bytes.
+    max_size: usize,
+}
+
+impl CookieStore {
+    /// Creates a new cookie session store that signs the cookies with the
+    /// given secret key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::SecretKey;
+    /// use cot::session::store::cookie::CookieStore;
+    ///
+    /// let store = CookieStore::new(SecretKey::new(b"secret"));
+    /// ```
+    #[must_use]
+    pub fn new(secret_key: SecretKey) -> Self {
+        Self {
+            secret_key,
+            fallback_keys: Arc::new([]),
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    pub(crate) fn from_project_config(config: &ProjectConfig, max_size: usize) -> Self {
+        Self::new(config.secret_key.clone())
+            .with_fallback_keys(config.fallback_secret_keys.iter().cloned())
+            .with_max_size(max_size)
+    }
+
+    /// Sets the keys that are accepted when verifying the session cookies, in
+    /// addition to the main secret key.
+    ///
+    /// This allows rotating the secret key without logging out all the users.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::SecretKey;
+    /// use cot::session::store::cookie::CookieStore;
+    ///
+    /// let store = CookieStore::new(SecretKey::new(b"new secret"))
+    ///     .with_fallback_keys([SecretKey::new(b"old secret")]);
+    /// ```
+    #[must_use]
+    pub fn with_fallback_keys<I: IntoIterator<Item = SecretKey>>(mut self, keys: I) -> Self {
+        self.fallback_keys = keys.into_iter().collect();
+        self
+    }
+
+    /// Sets the maximum size of the session cookie value, in bytes.
+    ///
+    /// Saving a session that doesn't fit in the cookie results in an error.
+    /// The default is [`DEFAULT_MAX_SIZE`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::SecretKey;
+    /// use cot::session::store::cookie::CookieStore;
+    ///
+    /// let store = CookieStore::new(SecretKey::new(b"secret")).with_max_size(2048);
+    /// assert_eq!(store.max_size(), 2048);
+    /// ```
+    #[must_use]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Returns the maximum size of the session cookie value, in bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::SecretKey;
+    /// use cot::session::store::cookie::{CookieStore, DEFAULT_MAX_SIZE};
+    ///
+    /// let store = CookieStore::new(SecretKey::new(b"secret"));
+    /// assert_eq!(store.max_size(), DEFAULT_MAX_SIZE);
+    /// ```
+    #[must_use]
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    fn encode(&self, record: &Record) -> Result<String, CookieStoreError> {
+        let json = serde_json::to_vec(record).map_err(CookieStoreError::Serialize)?;
+        let payload = URL_SAFE_NO_PAD.encode(json);
+        let signature = cookie_hmac(&self.secret_key, &payload)
+            .finalize()
+            .into_bytes();
+        let value = format!("{payload}.{}", URL_SAFE_NO_PAD.encode(signature));
+
+        if value.len() > self.max_size {
+            return Err(CookieStoreError::TooLarge {
+                size: value.len(),
+                max_size: self.max_size,
+            });
+        }
+        Ok(value)
+    }
+
+    fn decode(&self, value: &str) -> Option<Record> {
+        let (payload, signature) = value.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+
+        let keys = std::iter::once(&self.secret_key).chain(self.fallback_keys.iter());
+        let mut verified = false;
+        for key in keys {
+            if cookie_hmac(key, payload).verify_slice(&signature).is_ok() {
+                verified = true;
+                break;
+            }
+        }
+        if !verified {
+            tracing::warn!("possibly suspicious activity: invalid session cookie signature");
+            return None;
+        }
+
+        let json = URL_SAFE_NO_PAD.decode(payload).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+
+    fn with_state<T>(
+        f: impl FnOnce(&mut CookieSessionState) -> Result<T, CookieStoreError>,
+    ) -> session_store::Result<T> {
+        CURRENT_SESSION
+            .try_with(|state| {
+                let mut state = state.lock().expect("session cookie state lock poisoned");
+                f(&mut state)
+            })
+            .map_err(|_| CookieStoreError::OutsideMiddleware)?
+            .map_err(Into::into)
+    }
+}
+
+fn cookie_hmac(secret_key: &SecretKey, payload: &str) -> CookieHmac {
+    let mut mac =
+        CookieHmac::new_from_slice(secret_key.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+#[async_trait]
+impl SessionStore for CookieStore {
+    async fn create(&self, session_record: &mut Record) -> session_store::Result<()> {
+        self.save(session_record).await
+    }
+
+    async fn save(&self, session_record: &Record) -> session_store::Result<()> {
+        let value = self.encode(session_record)?;
+        Self::with_state(|state| {
+            state.record = Some(session_record.clone());
+            state.encoded = Some(value);
+            Ok(())
+        })
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        Self::with_state(|state| {
+            Ok(state
+                .record
+                .as_ref()
+                .filter(|record| record.id == *session_id)
+                .filter(|record| record.expiry_date > OffsetDateTime::now_utc())
+                .cloned())
+        })
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        Self::with_state(|state| {
+            if state
+                .record
+                .as_ref()
+                .is_some_and(|record| record.id == *session_id)
+            {
+                *state = CookieSessionState::default();
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl ManagedSessionStore for CookieStore {
+    async fn list(&self) -> session_store::Result<Vec<Record>> {
+        Ok(Vec::new())
+    }
+
+    async fn delete_expired(&self) -> session_store::Result<u64> {
+        Ok(0)
+    }
+}
+
+/// The session stored in the cookie of the request currently being handled.
+#[derive(Debug, Default)]
+struct CookieSessionState {
+    record: Option<Record>,
+    /// The signed cookie value, set when the session has been saved.
+    encoded: Option<String>,
+}
+
+/// A service that moves the session between the session cookie and the
+/// [`CookieStore`].
+///
+/// On the way in, the signed session cookie is verified and replaced with the
+/// session ID, so that the inner session layer can load the session from the
+/// store. On the way out, the session ID in the `Set-Cookie` header is replaced
+/// with the signed session. If no cookie store is used, the requests are
+/// passed through unchanged.
+///
+/// This is used internally by
+/// [`SessionMiddleware`](crate::middleware::SessionMiddleware) and shouldn't be
+/// useful on its own.
+#[derive(Debug, Clone)]
+pub struct CookieSessionService<S> {
+    inner: S,
+    store: Option<CookieStore>,
+    name: Cow<'static, str>,
+}
+
+impl<S> CookieSessionService<S> {
+    pub(crate) fn new(inner: S, store: Option<CookieStore>, name: Cow<'static, str>) -> Self {
+        Self { inner, store, name }
+    }
+}
+
+impl<ReqBody, ResBody, S> Service<http::Request<ReqBody>> for CookieSessionService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send,
+    ReqBody: Send + 'static,
+    ResBody: Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        // Only the service that has been polled ready can be called; see
+        // https://docs.rs/tower/latest/tower/trait.Service.html#be-careful-when-cloning-inner-services
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let Some(store) = self.store.clone() else {
+            return Box::pin(inner.call(req));
+        };
+        let name = self.name.clone();
+
+        let record = take_request_cookie(req.headers_mut(), &name, &store);
+        let state = Arc::new(Mutex::new(CookieSessionState {
+            record,
+            encoded: None,
+        }));
+
+        Box::pin(async move {
+            let mut response = CURRENT_SESSION
+                .scope(Arc::clone(&state), inner.call(req))
+                .await?;
+
+            let state =
+                std::mem::take(&mut *state.lock().expect("session cookie state lock poisoned"));
+            if let (Some(record), Some(encoded)) = (state.record, state.encoded) {
+                set_response_cookie(response.headers_mut(), &name, record.id, &encoded);
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+/// Replaces the signed session cookie in the request with the session ID and
+/// returns the session record stored in it.
+///
+/// Cookies that fail the verification are removed from the request.
+fn take_request_cookie(
+    headers: &mut http::HeaderMap,
+    name: &str,
+    store: &CookieStore,
+) -> Option<Record> {
+    let mut record = None;
+    let mut found = false;
+    let mut cookie_headers = Vec::new();
+
+    for header in headers.get_all(COOKIE) {
+        let Ok(header) = header.to_str() else {
+            cookie_headers.push(header.clone());
+            continue;
+        };
+
+        let mut cookies = Vec::new();
+        for cookie in header.split(';').map(str::trim) {
+            match cookie.split_once('=') {
+                Some((cookie_name, value)) if cookie_name == name => {
+                    found = true;
+                    if let Some(decoded) = store.decode(value) {
+                        cookies.push(format!("{name}={}", decoded.id));
+                        record = Some(decoded);
+                    }
+                }
+                _ => cookies.push(cookie.to_owned()),
+            }
+        }
+
+        if !cookies.is_empty() {
+            cookie_headers.push(
+                HeaderValue::from_str(&cookies.join("; "))
+                    .expect("cookie header built from a valid header must be valid"),
+            );
+        }
+    }
+
+    if found {
+        headers.remove(COOKIE);
+        for header in cookie_headers {
+            headers.append(COOKIE, header);
+        }
+    }
+
+    record
+}
+
+/// Replaces the session ID in the response's session cookie with the signed
+/// session.
+fn set_response_cookie(headers: &mut http::HeaderMap, name: &str, id: Id, encoded: &str) {
+    let id = id.to_string();
+
+    let set_cookie_headers: Vec<_> = headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .map(|header| {
+            let cookie = header
+                .to_str()
+                .ok()
+                .and_then(|value| Cookie::parse(value).ok())
+                .filter(|cookie| cookie.name() == name && cookie.value() == id);
+            match cookie {
+                Some(mut cookie) => {
+                    cookie.set_value(encoded);
+                    HeaderValue::from_str(&cookie.to_string())
+                        .expect("session cookie must be a valid header value")
+                }
+                None => header.clone(),
+            }
+        })
+        .collect();
+
+    headers.remove(SET_COOKIE);
+    for header in set_cookie_headers {
+        headers.append(SET_COOKIE, header);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use time::Duration;
+
+    use super::*;
+
+    fn make_store() -> CookieStore {
+        CookieStore::new(SecretKey::new(b"secret"))
+    }
+
+    fn make_record() -> Record {
+        Record {
+            id: Id::default(),
+            data: HashMap::from([("key".to_owned(), serde_json::json!("value"))]),
+            expiry_date: OffsetDateTime::now_utc() + Duration::minutes(30),
+        }
+    }
+
+    fn cookie_header(headers: &http::HeaderMap) -> &str {
+        headers.get(COOKIE).unwrap().to_str().unwrap()
+    }
+
+    #[test]
+    fn encode_decode() {
+        let store = make_store();
+        let record = make_record();
+
+        let encoded = store.encode(&record).unwrap();
+
+        assert_eq!(store.decode(&encoded), Some(record));
+    }
+
+    #[test]
+    fn decode_tampered() {
+        let store = make_store();
+        let encoded = store.encode(&make_record()).unwrap();
+        let (payload, signature) = encoded.split_once('.').unwrap();
+        let other_payload = URL_SAFE_NO_PAD.encode(
+            serde_json::to_vec(&Record {
+                data: HashMap::new(),
+                ..make_record()
+            })
+            .unwrap(),
+        );
+
+        assert!(
+            store
+                .decode(&format!("{other_payload}.{signature}"))
+                .is_none()
+        );
+        assert!(store.decode(payload).is_none());
+        assert!(store.decode("invalid").is_none());
+    }
+
+    #[test]
+    fn decode_wrong_key() {
+        let encoded = make_store().encode(&make_record()).unwrap();
+        let store = CookieStore::new(SecretKey::new(b"other"));
+
+        assert!(store.decode(&encoded).is_none());
+    }
+
+    #[test]
+    fn decode_fallback_key() {
+        let record = make_record();
+        let encoded = CookieStore::new(SecretKey::new(b"old"))
+            .encode(&record)
+            .unwrap();
+        let store = make_store().with_fallback_keys([SecretKey::new(b"old")]);
+
+        assert_eq!(store.decode(&encoded), Some(record));
+    }
+
+    #[test]
+    fn encode_too_large() {
+        let store = make_store().with_max_size(64);
+
+        let error = store.encode(&make_record()).unwrap_err();
+
+        assert!(matches!(
+            error,
+            CookieStoreError::TooLarge { max_size: 64, .. }
+        ));
+        assert!(matches!(
+            session_store::Error::from(error),
+            session_store::Error::Encode(_)
+        ));
+    }
+
+    #[cot::test]
+    async fn store_outside_middleware() {
+        let store = make_store();
+        let mut record = make_record();
+
+        let error = store.create(&mut record).await.unwrap_err();
+
+        assert!(matches!(error, session_store::Error::Backend(_)));
+        assert!(store.list().await.unwrap().is_empty());
+        assert_eq!(store.delete_expired().await.unwrap(), 0);
+    }
+
+    #[cot::test]
+    async fn store_inside_middleware() {
+        let store = make_store();
+        let mut record = make_record();
+        let state = Arc::new(Mutex::new(CookieSessionState::default()));
+
+        CURRENT_SESSION
+            .scope(Arc::clone(&state), async {
+                store.create(&mut record).await.unwrap();
+                assert_eq!(store.load(&record.id).await.unwrap(), Some(record.clone()));
+                assert_eq!(store.load(&Id::default()).await.unwrap(), None);
+
+                store.delete(&record.id).await.unwrap();
+                assert_eq!(store.load(&record.id).await.unwrap(), None);
+
+                store.save(&record).await.unwrap();
+            })
+            .await;
+
+        let state = state.lock().unwrap();
+        assert_eq!(state.record, Some(record));
+        assert!(state.encoded.is_some());
+    }
+
+    #[cot::test]
+    async fn load_expired() {
+        let store = make_store();
+        let record = Record {
+            expiry_date: OffsetDateTime::now_utc() - Duration::minutes(1),
+            ..make_record()
+        };
+        let state = Arc::new(Mutex::new(CookieSessionState {
+            record: Some(record.clone()),
+            encoded: None,
+        }));
+
+        let loaded = CURRENT_SESSION
+            .scope(state, store.load(&record.id))
+            .await
+            .unwrap();
+
+        assert_eq!(loaded, None);
+    }
+
+    #[test]
+    fn take_request_cookie_valid() {
+        let store = make_store();
+        let record = make_record();
+        let encoded = store.encode(&record).unwrap();
+        let mut headers = http::HeaderMap::new();
+        headers.insert(
+            COOKIE,
+            HeaderValue::from_str(&format!("a=b; id={encoded}; c=d")).unwrap(),
+        );
+
+        let loaded = take_request_cookie(&mut headers, "id", &store);
+
+        assert_eq!(loaded, Some(record.clone()));
+        assert_eq!(
+            cookie_header(&headers),
+            format!("a=b; id={}; c=d", record.id)
+        );
+    }
+
+    #[test]
+    fn take_request_cookie_invalid() {
+        let store = make_store();
+        let mut headers = http::HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("a=b; id=invalid"));
+
+        let loaded = take_request_cookie(&mut headers, "id", &store);
+
+        assert_eq!(loaded, None);
+        assert_eq!(cookie_header(&headers), "a=b");
+    }
+
+    #[test]
+    fn take_request_cookie_missing() {
+        let store = make_store();
+        let mut headers = http::HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("a=b"));
+
+        let loaded = take_request_cookie(&mut headers, "id", &store);
+
+        assert_eq!(loaded, None);
+        assert_eq!(cookie_header(&headers), "a=b");
+    }
+
+    #[test]
+    fn set_response_cookie_replaces_id() {
+        let id = Id::default();
+        let mut headers = http::HeaderMap::new();
+        headers.append(SET_COOKIE, HeaderValue::from_static("a=b; Path=/"));
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_str(&format!("id={id}; HttpOnly; Path=/")).unwrap(),
+        );
+
+        set_response_cookie(&mut headers, "id", id, "signed.value");
+
+        let cookies: Vec<_> = headers
+            .get_all(SET_COOKIE)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect();
+        assert_eq!(
+            cookies,
+            ["a=b; Path=/", "id=signed.value; HttpOnly; Path=/"]
+        );
+    }
+}
@@ -31,7 +31,7 @@ use tower_sessions::{SessionStore, session_store};
 
 use crate::db::{Auto, Database, DatabaseError, Model, query};
 use crate::session::db::Session;
-use crate::session::store::{ERROR_PREFIX, MAX_COLLISION_RETRIES};
+use crate::session::store::{ERROR_PREFIX, MAX_COLLISION_RETRIES, ManagedSessionStore};
 use crate::utils::chrono::DateTimeWithOffsetAdapter;
 
 /// Errors that can occur while interacting with the database session store.
@@ -137,7 +137,7 @@ impl SessionStore for DbStore {
                 Ok(()) => {
                     return Ok(());
                 }
-                Err(DatabaseError::UniqueViolation) => {
+                Err(DatabaseError::UniqueViolation { .. }) => {
                     // If a unique constraint violation occurs, we need to generate a new ID
                     record.id = Id::default();
                 }
@@ -177,23 +177,7 @@ impl SessionStore for DbStore {
             .await
             .map_err(DbStoreError::DatabaseError)?;
         if let Some(session) = query {
-            let data = serde_json::from_str::<HashMap<String, serde_json::Value>>(&session.data)
-                .map_err(|err| DbStoreError::Serialize(Box::new(err)))?;
-
-            let id = session
-                .key
-                .parse::<Id>()
-                .map_err(|err| DbStoreError::Deserialize(Box::new(err)))?;
-
-            let expiry_date = DateTimeWithOffsetAdapter::new(session.expiry).into_offsetdatetime();
-
-            let rec = Record {
-                id,
-                data,
-                expiry_date,
-            };
-
-            Ok(Some(rec))
+            Ok(Some(session_to_record(&session)?))
         } else {
             Ok(None)
         }
@@ -209,6 +193,55 @@ impl SessionStore for DbStore {
     }
 }
 
+#[async_trait]
+impl ManagedSessionStore for DbStore {
+    async fn list(&self) -> session_store::Result<Vec<Record>> {
+        let sessions = Session::objects()
+            .all(&self.connection)
+            .await
+            .map_err(DbStoreError::DatabaseError)?;
+
+        Ok(sessions
+            .iter()
+            .map(session_to_record)
+            .collect::<Result<_, _>>()?)
+    }
+
+    async fn delete_expired(&self) -> session_store::Result<u64> {
+        // the expiry dates are filtered here rather than in the query, as the
+        // stored dates can have different offsets, which makes comparing them
+        // in the database unreliable
+        let now = time::OffsetDateTime::now_utc();
+        let mut deleted = 0;
+        for record in self.list().await? {
+            if record.expiry_date <= now {
+                self.delete(&record.id).await?;
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    }
+}
+
+fn session_to_record(session: &Session) -> Result<Record, DbStoreError> {
+    let data = serde_json::from_str::<HashMap<String, serde_json::Value>>(&session.data)
+        .map_err(|err| DbStoreError::Serialize(Box::new(err)))?;
+
+    let id = session
+        .key
+        .parse::<Id>()
+        .map_err(|err| DbStoreError::Deserialize(Box::new(err)))?;
+
+    let expiry_date = DateTimeWithOffsetAdapter::new(session.expiry).into_offsetdatetime();
+
+    Ok(Record {
+        id,
+        data,
+        expiry_date,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::io;
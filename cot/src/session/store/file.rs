@@ -24,7 +24,7 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tower_sessions::session::{Id, Record};
 use tower_sessions::{SessionStore, session_store};
 
-use crate::session::store::{ERROR_PREFIX, MAX_COLLISION_RETRIES};
+use crate::session::store::{ERROR_PREFIX, MAX_COLLISION_RETRIES, ManagedSessionStore};
 
 /// Errors that can occur when using the File session store.
 #[derive(Debug, Error)]
@@ -101,6 +101,21 @@ impl FileStore {
         Ok(file_store)
     }
 
+    /// Reads the session record from the given file.
+    ///
+    /// Returns `None` if the file doesn't exist.
+    async fn read_record(path: &Path) -> Result<Option<Record>, FileStoreError> {
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(FileStoreError::Io(Box::new(err))),
+        };
+
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|err| FileStoreError::Deserialize(Box::new(err)))
+    }
+
     async fn create_dir_if_not_exists(&self) -> Result<(), FileStoreError> {
         tokio::fs::create_dir_all(&self.dir_path)
             .await
@@ -200,6 +215,52 @@ impl SessionStore for FileStore {
     }
 }
 
+#[async_trait]
+impl ManagedSessionStore for FileStore {
+    async fn list(&self) -> session_store::Result<Vec<Record>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir_path).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(FileStoreError::Io(Box::new(err)))?,
+        };
+
+        let mut records = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|err| FileStoreError::Io(Box::new(err)))?
+        {
+            // skip any files that are not session records
+            let is_session_file = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.parse::<Id>().is_ok());
+            if !is_session_file {
+                continue;
+            }
+
+            if let Some(record) = Self::read_record(&entry.path()).await? {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    async fn delete_expired(&self) -> session_store::Result<u64> {
+        let now = time::OffsetDateTime::now_utc();
+        let mut deleted = 0;
+        for record in self.list().await? {
+            if record.expiry_date <= now {
+                self.delete(&record.id).await?;
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -379,6 +440,23 @@ mod tests {
         assert!(p1.is_file() && p2.is_file());
     }
 
+    #[cot::test]
+    async fn test_list_and_delete_expired() {
+        let store = make_store();
+        let mut active = make_record();
+        let mut expired = make_record();
+        expired.expiry_date = OffsetDateTime::now_utc() - Duration::minutes(30);
+        store.create(&mut active).await.unwrap();
+        store.create(&mut expired).await.unwrap();
+        fs::write(store.dir_path.join("not-a-session"), "")
+            .await
+            .unwrap();
+        assert_eq!(store.list().await.unwrap().len(), 2);
+
+        assert_eq!(store.delete_expired().await.unwrap(), 1);
+        assert_eq!(store.list().await.unwrap(), vec![active]);
+    }
+
     #[cot::test]
     async fn test_from_file_store_error_to_session_store_error() {
         let io_err = io::Error::other("io problem");
@@ -18,6 +18,8 @@ use tokio::sync::Mutex;
 use tower_sessions::session::{Id, Record};
 use tower_sessions::{SessionStore, session_store};
 
+use crate::session::store::ManagedSessionStore;
+
 /// An in-memory session store implementation.
 ///
 /// This store keeps all sessions in memory using a thread-safe hashmap.
@@ -80,6 +82,21 @@ impl SessionStore for MemoryStore {
     }
 }
 
+#[async_trait]
+impl ManagedSessionStore for MemoryStore {
+    async fn list(&self) -> session_store::Result<Vec<Record>> {
+        Ok(self.0.lock().await.values().cloned().collect())
+    }
+
+    async fn delete_expired(&self) -> session_store::Result<u64> {
+        let mut store_guard = self.0.lock().await;
+        let len_before = store_guard.len();
+        store_guard.retain(|_, Record { expiry_date, .. }| is_active(*expiry_date));
+
+        Ok((len_before - store_guard.len()) as u64)
+    }
+}
+
 fn is_active(expiry_date: OffsetDateTime) -> bool {
     expiry_date > OffsetDateTime::now_utc()
 }
@@ -157,4 +174,25 @@ mod tests {
         store.create(&mut record2).await.unwrap();
         assert_ne!(record1.id, record2.id); // IDs should be different
     }
+
+    #[cot::test]
+    async fn test_list_and_delete_expired() {
+        let store = MemoryStore::default();
+        let mut active = Record {
+            id: Id::default(),
+            data: HashMap::default(),
+            expiry_date: OffsetDateTime::now_utc() + Duration::minutes(30),
+        };
+        let mut expired = Record {
+            id: Id::default(),
+            data: HashMap::default(),
+            expiry_date: OffsetDateTime::now_utc() - Duration::minutes(30),
+        };
+        store.create(&mut active).await.unwrap();
+        store.create(&mut expired).await.unwrap();
+        assert_eq!(store.list().await.unwrap().len(), 2);
+
+        assert_eq!(store.delete_expired().await.unwrap(), 1);
+        assert_eq!(store.list().await.unwrap(), vec![active]);
+    }
 }
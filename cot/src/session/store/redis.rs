@@ -8,8 +8,15 @@
 //! use cot::config::CacheUrl;
 //! use cot::session::store::redis::RedisStore;
 //!
-//! let store = RedisStore::new(&CacheUrl::from("redis://127.0.0.1/")).unwrap();
+//! let store = RedisStore::new(&CacheUrl::from("redis://127.0.0.1/"))
+//!     .unwrap()
+//!     .with_key_prefix("myapp:session:");
 //! ```
+//!
+//! The sessions are stored with a Redis expiration time matching the expiry
+//! date of the session, which is determined by
+//! [`SessionMiddlewareConfig::expiry`](crate::config::SessionMiddlewareConfig::expiry),
+//! so Redis removes the expired sessions by itself.
 
 use std::error::Error;
 
@@ -22,7 +29,7 @@ use tower_sessions::session::{Id, Record};
 use tower_sessions::{SessionStore, session_store};
 
 use crate::config::CacheUrl;
-use crate::session::store::{ERROR_PREFIX, MAX_COLLISION_RETRIES};
+use crate::session::store::{ERROR_PREFIX, MAX_COLLISION_RETRIES, ManagedSessionStore};
 
 #[derive(Debug, Error)]
 /// Errors that can occur when using the Redis session store.
@@ -84,6 +91,8 @@ impl From<RedisStoreError> for session_store::Error {
 pub struct RedisStore {
     /// The Redis connection pool.
     pool: RedisPool,
+    /// The prefix prepended to the session IDs to form the Redis keys.
+    key_prefix: String,
 }
 
 impl RedisStore {
@@ -116,7 +125,58 @@ impl RedisStore {
             .create_pool(Some(Runtime::Tokio1))
             .map_err(|err| RedisStoreError::PoolCreation(Box::new(err)))?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            key_prefix: String::new(),
+        })
+    }
+
+    /// Sets the prefix prepended to the session IDs to form the Redis keys.
+    ///
+    /// This is useful when the Redis database is shared with other
+    /// applications or with the cache. By default, no prefix is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::CacheUrl;
+    /// use cot::session::store::redis::RedisStore;
+    ///
+    /// let store = RedisStore::new(&CacheUrl::from("redis://127.0.0.1/"))
+    ///     .unwrap()
+    ///     .with_key_prefix("myapp:session:");
+    /// assert_eq!(store.key_prefix(), "myapp:session:");
+    /// ```
+    #[must_use]
+    pub fn with_key_prefix<T: Into<String>>(mut self, key_prefix: T) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Returns the prefix prepended to the session IDs to form the Redis
+    /// keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::CacheUrl;
+    /// use cot::session::store::redis::RedisStore;
+    ///
+    /// let store = RedisStore::new(&CacheUrl::from("redis://127.0.0.1/")).unwrap();
+    /// assert_eq!(store.key_prefix(), "");
+    /// ```
+    #[must_use]
+    pub fn key_prefix(&self) -> &str {
+        &self.key_prefix
+    }
+
+    fn key(&self, session_id: &Id) -> String {
+        format!("{}{session_id}", self.key_prefix)
+    }
+
+    /// Returns the `SCAN` pattern matching all the keys with the key prefix.
+    fn key_pattern(&self) -> String {
+        format!("{}*", escape_glob(&self.key_prefix))
     }
 
     /// Asynchronously checks out a Redis connection from the internal pool.
@@ -154,10 +214,12 @@ impl RedisStore {
 
 fn get_expiry_as_u64(expiry: OffsetDateTime) -> u64 {
     let now = OffsetDateTime::now_utc();
+    // Redis rejects a zero expiration time, so an already expired session is
+    // kept for one more second instead
     expiry
         .unix_timestamp()
         .saturating_sub(now.unix_timestamp())
-        .max(0)
+        .max(1)
         .unsigned_abs()
 }
 
@@ -172,7 +234,7 @@ impl SessionStore for RedisStore {
             .with_expiration(SetExpiry::EX(get_expiry_as_u64(session_record.expiry_date)));
 
         for _ in 0..=MAX_COLLISION_RETRIES {
-            let key = session_record.id.to_string();
+            let key = self.key(&session_record.id);
             let set_ok: bool = conn
                 .set_options(key, &data, options)
                 .await
@@ -187,7 +249,7 @@ impl SessionStore for RedisStore {
     }
     async fn save(&self, session_record: &Record) -> session_store::Result<()> {
         let mut conn = self.get_connection().await?;
-        let key: String = session_record.id.to_string();
+        let key = self.key(&session_record.id);
         let data: String = serde_json::to_string(&session_record)
             .map_err(|err| RedisStoreError::Serialize(Box::new(err)))?;
 
@@ -208,7 +270,7 @@ impl SessionStore for RedisStore {
 
     async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
         let mut conn = self.get_connection().await?;
-        let key = session_id.to_string();
+        let key = self.key(session_id);
         let data: Option<String> = conn
             .get(key)
             .await
@@ -223,7 +285,7 @@ impl SessionStore for RedisStore {
 
     async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
         let mut conn = self.get_connection().await?;
-        let key = session_id.to_string();
+        let key = self.key(session_id);
         conn.del::<_, ()>(key)
             .await
             .map_err(|err| RedisStoreError::Command(Box::new(err)))?;
@@ -231,6 +293,61 @@ impl SessionStore for RedisStore {
     }
 }
 
+/// Escapes the special characters of the Redis glob-style patterns.
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[async_trait]
+impl ManagedSessionStore for RedisStore {
+    async fn list(&self) -> session_store::Result<Vec<Record>> {
+        let mut conn = self.get_connection().await?;
+
+        let mut keys = Vec::new();
+        {
+            let mut iter = conn
+                .scan_match::<_, String>(self.key_pattern())
+                .await
+                .map_err(|err| RedisStoreError::Command(Box::new(err)))?;
+            while let Some(key) = iter.next_item().await {
+                // the database can be shared with the cache, so skip any keys
+                // that are not session IDs
+                let is_session_key = key
+                    .strip_prefix(self.key_prefix.as_str())
+                    .is_some_and(|id| id.parse::<Id>().is_ok());
+                if is_session_key {
+                    keys.push(key);
+                }
+            }
+        }
+
+        let mut records = Vec::with_capacity(keys.len());
+        for key in keys {
+            let data: Option<String> = conn
+                .get(key)
+                .await
+                .map_err(|err| RedisStoreError::Command(Box::new(err)))?;
+            if let Some(record) = data.and_then(|data| serde_json::from_str::<Record>(&data).ok()) {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    async fn delete_expired(&self) -> session_store::Result<u64> {
+        // Redis removes the expired keys automatically
+        Ok(0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -310,6 +427,19 @@ mod tests {
         store.delete(&rec.id).await.expect("second delete");
     }
 
+    #[cot::test]
+    #[ignore = "requires external Redis service"]
+    async fn test_list() {
+        let store = make_store().await;
+        let mut rec = make_record();
+        store.create(&mut rec).await.unwrap();
+
+        let records = store.list().await.expect("list failed");
+        assert!(records.contains(&rec));
+
+        store.delete(&rec.id).await.unwrap();
+    }
+
     #[cot::test]
     #[ignore = "requires external Redis service"]
     async fn test_create_id_collision() {
@@ -337,6 +467,77 @@ mod tests {
         assert!(loaded1.is_some() && loaded2.is_some());
     }
 
+    #[cot::test]
+    #[ignore = "requires external Redis service"]
+    async fn test_key_prefix() {
+        let store = make_store().await.with_key_prefix("cot-test:session:");
+        let mut rec = make_record();
+        store.create(&mut rec).await.unwrap();
+
+        let mut conn = store.get_connection().await.unwrap();
+        let exists: bool = conn
+            .exists(format!("cot-test:session:{}", rec.id))
+            .await
+            .unwrap();
+        assert!(exists);
+        assert!(store.list().await.unwrap().contains(&rec));
+
+        let unprefixed_store = make_store().await;
+        assert!(unprefixed_store.load(&rec.id).await.unwrap().is_none());
+        assert!(!unprefixed_store.list().await.unwrap().contains(&rec));
+
+        store.delete(&rec.id).await.unwrap();
+    }
+
+    #[test]
+    fn test_key() {
+        let store = RedisStore::new(&CacheUrl::from("redis://127.0.0.1/"))
+            .unwrap()
+            .with_key_prefix("session:");
+        let id = Id::default();
+
+        assert_eq!(store.key(&id), format!("session:{id}"));
+    }
+
+    #[test]
+    fn test_key_without_prefix() {
+        let store = RedisStore::new(&CacheUrl::from("redis://127.0.0.1/")).unwrap();
+        let id = Id::default();
+
+        assert_eq!(store.key(&id), id.to_string());
+        assert_eq!(store.key_pattern(), "*");
+    }
+
+    #[test]
+    fn test_key_special_characters() {
+        let store = RedisStore::new(&CacheUrl::from("redis://127.0.0.1/"))
+            .unwrap()
+            .with_key_prefix("app*[1]\\:");
+        let id = Id::default();
+
+        // the keys themselves are not patterns, so they are not escaped
+        assert_eq!(store.key(&id), format!("app*[1]\\:{id}"));
+        assert_eq!(store.key_pattern(), "app\\*\\[1\\]\\\\:*");
+    }
+
+    #[test]
+    fn test_escape_glob() {
+        assert_eq!(escape_glob(""), "");
+        assert_eq!(escape_glob("cot:session:"), "cot:session:");
+        assert_eq!(escape_glob("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\");
+        assert_eq!(escape_glob("\\*"), "\\\\\\*");
+        assert_eq!(escape_glob("[^a-z]"), "\\[^a-z\\]");
+    }
+
+    #[test]
+    fn test_expiry_is_positive() {
+        let past = OffsetDateTime::now_utc() - Duration::minutes(1);
+        assert_eq!(get_expiry_as_u64(past), 1);
+
+        let future = OffsetDateTime::now_utc() + Duration::minutes(30);
+        assert!(get_expiry_as_u64(future) > 1);
+    }
+
     #[cot::test]
     async fn test_from_redis_store_error_to_session_store_error() {
         let pool_err = io::Error::other("pool conn failure");
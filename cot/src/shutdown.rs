@@ -0,0 +1,629 @@
+//! Graceful shutdown support for long-running responses.
+//!
+//! When the server receives a shutdown signal, it stops accepting new
+//! connections and waits for the in-flight requests to complete. This is not a
+//! problem for regular responses, but streaming responses, such as server-sent
+//! events or large file downloads, can run for a very long time.
+//!
+//! Cot keeps track of the in-flight streaming responses and handles them
+//! according to the [`ShutdownConfig`](crate::config::ShutdownConfig):
+//! * by default, the server waits until all the responses finish,
+//! * if the [`grace_period`](crate::config::ShutdownConfig::grace_period) is
+//!   set, the responses that are still running after it expires are
+//!   terminated.
+//!
+//! In addition to that, the streaming responses can observe the shutdown using
+//! the [`ShutdownSignal`] extractor and finish on their own, for instance
+//! after sending a final event to the client.
+//!
+//! To avoid racing with the load balancer health checks, the server reports
+//! itself as not ready (see [`Readiness`] and [`readiness_check`]) as soon as
+//! the shutdown signal is received. If the
+//! [`pre_stop_delay`](crate::config::ShutdownConfig::pre_stop_delay) is set, it
+//! then keeps serving the requests for the given time before it stops
+//! accepting new connections, giving the load balancers time to notice.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use derive_more::with_trait::Debug;
+use futures_core::Stream;
+use futures_util::{FutureExt, StreamExt, stream};
+use http::StatusCode;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::Body;
+use crate::request::RequestHead;
+use crate::request::extractors::FromRequestHead;
+use crate::response::Response;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ShutdownState {
+    Running,
+    ShuttingDown,
+    Terminating,
+}
+
+/// A signal that is triggered when the server starts shutting down.
+///
+/// This can be used by long-running (typically streaming) responses to finish
+/// gracefully when the server is shutting down, instead of being terminated
+/// abruptly. It can be extracted in request handlers; when the request is not
+/// handled by a running server (e.g. in tests using
+/// [`TestRequestBuilder`](crate::test::TestRequestBuilder)), the signal is
+/// never triggered.
+///
+/// # Examples
+///
+/// ```
+/// use cot::Body;
+/// use cot::response::{Response, ResponseExt};
+/// use cot::shutdown::ShutdownSignal;
+/// use futures_util::stream;
+///
+/// async fn events(shutdown: ShutdownSignal) -> Response {
+///     let events = stream::repeat_with(|| Ok("data: tick\n\n".into()));
+///     let events =
+///         shutdown.stream_until_shutdown(events, Some(Ok("event: shutdown\ndata: bye\n\n".into())));
+///
+///     Response::builder()
+///         .header("content-type", "text/event-stream")
+///         .body(Body::streaming(events))
+///         .unwrap()
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal {
+    receiver: Option<watch::Receiver<ShutdownState>>,
+}
+
+impl ShutdownSignal {
+    /// Returns a signal that is never triggered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::shutdown::ShutdownSignal;
+    ///
+    /// let signal = ShutdownSignal::never();
+    /// assert!(!signal.is_shutting_down());
+    /// ```
+    #[must_use]
+    pub fn never() -> Self {
+        Self { receiver: None }
+    }
+
+    /// Returns `true` if the server has started shutting down.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::shutdown::ShutdownSignal;
+    ///
+    /// async fn handler(shutdown: ShutdownSignal) {
+    ///     if shutdown.is_shutting_down() {
+    ///         // don't start any new long-running work
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn is_shutting_down(&self) -> bool {
+        self.receiver
+            .as_ref()
+            .is_some_and(|receiver| *receiver.borrow() != ShutdownState::Running)
+    }
+
+    /// Waits until the server starts shutting down.
+    ///
+    /// If the signal is never triggered, the returned future never completes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::shutdown::ShutdownSignal;
+    ///
+    /// async fn handler(shutdown: ShutdownSignal) {
+    ///     tokio::select! {
+    ///         () = shutdown.wait() => {
+    ///             // clean up
+    ///         }
+    ///         () = tokio::time::sleep(std::time::Duration::from_secs(60)) => {
+    ///             // do some long-running work
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn wait(&self) {
+        let Some(receiver) = &self.receiver else {
+            return std::future::pending().await;
+        };
+
+        let mut receiver = receiver.clone();
+        // an error means the server is gone, so there's nothing to wait for
+        let _ = receiver
+            .wait_for(|state| *state != ShutdownState::Running)
+            .await;
+    }
+
+    /// Wraps a stream so that it ends when the server starts shutting down.
+    ///
+    /// If `final_item` is provided, it is yielded as the last item of the
+    /// stream, both when the stream ends because of the shutdown and when it
+    /// ends on its own. This is useful for sending a final event to the
+    /// clients, for instance to tell them to reconnect later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::Body;
+    /// use cot::shutdown::ShutdownSignal;
+    /// use futures_util::stream;
+    ///
+    /// async fn handler(shutdown: ShutdownSignal) -> Body {
+    ///     let events = stream::repeat_with(|| Ok("data: tick\n\n".into()));
+    ///     Body::streaming(
+    ///         shutdown.stream_until_shutdown(events, Some(Ok("event: shutdown\n\n".into()))),
+    ///     )
+    /// }
+    /// ```
+    pub fn stream_until_shutdown<S>(
+        &self,
+        stream: S,
+        final_item: Option<S::Item>,
+    ) -> impl Stream<Item = S::Item> + Send + 'static
+    where
+        S: Stream + Send + 'static,
+        S::Item: Send,
+    {
+        let signal = self.clone();
+        stream
+            .take_until(async move { signal.wait().await })
+            .chain(stream::iter(final_item))
+    }
+}
+
+impl FromRequestHead for ShutdownSignal {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        Ok(head
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .unwrap_or_else(Self::never))
+    }
+}
+
+/// The readiness of the server to receive traffic.
+///
+/// The server is ready until it receives the shutdown signal, or until it's
+/// drained manually using [`Readiness::drain`]. This allows load balancers
+/// (e.g. in blue/green deployments) to stop sending traffic to the server
+/// before it stops accepting new connections. The readiness is typically
+/// exposed to the load balancer using the [`readiness_check`] handler.
+///
+/// It can be extracted in request handlers; when the request is not handled by
+/// a running server (e.g. in tests using
+/// [`TestRequestBuilder`](crate::test::TestRequestBuilder)), an independent,
+/// ready instance is returned.
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::Response;
+/// use cot::shutdown::Readiness;
+///
+/// // this should be protected, e.g. by being only reachable from the internal network
+/// async fn drain(readiness: Readiness) -> cot::Result<Response> {
+///     readiness.drain();
+///     // ...
+/// #    unimplemented!()
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Readiness {
+    draining: Arc<AtomicBool>,
+}
+
+impl Readiness {
+    /// Returns `true` if the server is ready to receive traffic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::shutdown::Readiness;
+    ///
+    /// let readiness = Readiness::default();
+    /// assert!(readiness.is_ready());
+    /// ```
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        !self.draining.load(Ordering::Acquire)
+    }
+
+    /// Marks the server as not ready to receive traffic.
+    ///
+    /// The server still handles all the incoming requests; only the readiness
+    /// reported by [`readiness_check`] changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::shutdown::Readiness;
+    ///
+    /// let readiness = Readiness::default();
+    /// readiness.drain();
+    /// assert!(!readiness.is_ready());
+    /// ```
+    pub fn drain(&self) {
+        self.draining.store(true, Ordering::Release);
+    }
+
+    /// Marks the server as ready to receive traffic again after it was
+    /// drained using [`Readiness::drain`].
+    ///
+    /// Note that this has no effect on the server that is already shutting
+    /// down, other than changing the reported readiness.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::shutdown::Readiness;
+    ///
+    /// let readiness = Readiness::default();
+    /// readiness.drain();
+    /// readiness.resume();
+    /// assert!(readiness.is_ready());
+    /// ```
+    pub fn resume(&self) {
+        self.draining.store(false, Ordering::Release);
+    }
+}
+
+impl FromRequestHead for Readiness {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        Ok(head.extensions.get::<Self>().cloned().unwrap_or_default())
+    }
+}
+
+/// A request handler reporting the readiness of the server.
+///
+/// It returns `200 OK` when the server is ready to receive traffic and
+/// `503 Service Unavailable` when it's shutting down or was drained using
+/// [`Readiness::drain`]. It is meant to be used as the health check endpoint
+/// of a load balancer.
+///
+/// # Examples
+///
+/// ```
+/// use cot::router::{Route, Router};
+/// use cot::shutdown::readiness_check;
+///
+/// let router = Router::with_urls([Route::with_handler("/ready", readiness_check)]);
+/// ```
+#[expect(clippy::unused_async, reason = "request handlers have to be async")]
+pub async fn readiness_check(readiness: Readiness) -> Response {
+    let (status, body) = if readiness.is_ready() {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not ready")
+    };
+
+    let mut response = Response::new(Body::fixed(body));
+    *response.status_mut() = status;
+    response
+}
+
+/// Coordinates the shutdown of the server with the in-flight streaming
+/// responses.
+#[derive(Debug, Clone)]
+pub(crate) struct ShutdownCoordinator {
+    sender: Arc<watch::Sender<ShutdownState>>,
+    in_flight: Arc<AtomicUsize>,
+    readiness: Readiness,
+}
+
+impl ShutdownCoordinator {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = watch::channel(ShutdownState::Running);
+        Self {
+            sender: Arc::new(sender),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            readiness: Readiness::default(),
+        }
+    }
+
+    pub(crate) fn readiness(&self) -> Readiness {
+        self.readiness.clone()
+    }
+
+    pub(crate) fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal {
+            receiver: Some(self.sender.subscribe()),
+        }
+    }
+
+    /// Makes the shutdown signal and the readiness state available to the
+    /// request handlers.
+    pub(crate) fn insert_extensions(&self, extensions: &mut http::Extensions) {
+        extensions.insert(self.signal());
+        extensions.insert(self.readiness());
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Waits for the `shutdown_signal`, marks the server as not ready, waits
+    /// for `pre_stop_delay` (if set) and then starts the shutdown.
+    ///
+    /// The delay lets the load balancers notice that the server is going away
+    /// before it stops accepting new connections.
+    pub(crate) async fn shut_down_after(
+        self,
+        shutdown_signal: impl Future<Output = ()>,
+        pre_stop_delay: Option<Duration>,
+        grace_period: Option<Duration>,
+    ) {
+        shutdown_signal.await;
+        self.readiness.drain();
+        if let Some(pre_stop_delay) = pre_stop_delay {
+            info!(?pre_stop_delay, "Draining the server before shutting down");
+            tokio::time::sleep(pre_stop_delay).await;
+        }
+        self.start_shutdown(grace_period);
+    }
+
+    /// Notifies the in-flight responses that the server is shutting down.
+    ///
+    /// If `grace_period` is set, the streaming responses that are still
+    /// running after it expires are terminated.
+    pub(crate) fn start_shutdown(&self, grace_period: Option<Duration>) {
+        self.readiness.drain();
+        self.sender.send_replace(ShutdownState::ShuttingDown);
+
+        let in_flight = self.in_flight();
+        if in_flight > 0 {
+            info!(
+                in_flight,
+                ?grace_period,
+                "Waiting for in-flight streaming responses"
+            );
+        }
+
+        if let Some(grace_period) = grace_period {
+            let sender = Arc::clone(&self.sender);
+            tokio::spawn(async move {
+                tokio::time::sleep(grace_period).await;
+                sender.send_replace(ShutdownState::Terminating);
+            });
+        }
+    }
+
+    /// Wraps the response body so that it's tracked as in-flight and can be
+    /// terminated after the grace period.
+    ///
+    /// Bodies with a known size are returned as-is, as they are not expected
+    /// to be long-running.
+    pub(crate) fn track<B>(&self, body: B) -> TrackedBody<B>
+    where
+        B: HttpBody,
+    {
+        if body.size_hint().exact().is_some() {
+            return TrackedBody {
+                inner: body,
+                tracking: None,
+            };
+        }
+
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let mut receiver = self.sender.subscribe();
+        let terminated = async move {
+            let _ = receiver
+                .wait_for(|state| *state == ShutdownState::Terminating)
+                .await;
+        }
+        .boxed();
+
+        TrackedBody {
+            inner: body,
+            tracking: Some(Tracking {
+                terminated,
+                in_flight: Arc::clone(&self.in_flight),
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Tracking {
+    #[debug("..")]
+    terminated: Pin<Box<dyn Future<Output = ()> + Send>>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for Tracking {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A response body that is tracked by the [`ShutdownCoordinator`].
+    #[derive(Debug)]
+    pub(crate) struct TrackedBody<B> {
+        #[pin]
+        inner: B,
+        tracking: Option<Tracking>,
+    }
+}
+
+impl<B> HttpBody for TrackedBody<B>
+where
+    B: HttpBody<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+
+        if let Some(tracking) = this.tracking
+            && tracking.terminated.as_mut().poll(cx).is_ready()
+        {
+            *this.tracking = None;
+            return Poll::Ready(None);
+        }
+
+        let result = this.inner.poll_frame(cx);
+        if matches!(result, Poll::Ready(None)) {
+            *this.tracking = None;
+        }
+        result
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http_body_util::BodyExt;
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    fn pending_body() -> Body {
+        Body::streaming(stream::pending())
+    }
+
+    #[cot::test]
+    async fn signal_never() {
+        let signal = ShutdownSignal::never();
+
+        assert!(!signal.is_shutting_down());
+        assert!(signal.wait().now_or_never().is_none());
+    }
+
+    #[cot::test]
+    async fn signal_triggered() {
+        let coordinator = ShutdownCoordinator::new();
+        let signal = coordinator.signal();
+        assert!(!signal.is_shutting_down());
+
+        coordinator.start_shutdown(None);
+
+        assert!(signal.is_shutting_down());
+        signal.wait().await;
+    }
+
+    #[cot::test]
+    async fn signal_extractor_without_server() {
+        let request = TestRequestBuilder::get("/").build();
+        let (head, _) = request.into_parts();
+
+        let signal = ShutdownSignal::from_request_head(&head).await.unwrap();
+
+        assert!(!signal.is_shutting_down());
+    }
+
+    #[cot::test]
+    async fn readiness_drained_on_shutdown() {
+        let coordinator = ShutdownCoordinator::new();
+        let readiness = coordinator.readiness();
+        assert!(readiness.is_ready());
+
+        coordinator.start_shutdown(None);
+
+        assert!(!readiness.is_ready());
+    }
+
+    #[cot::test]
+    async fn readiness_check_status() {
+        let readiness = Readiness::default();
+        let response = readiness_check(readiness.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        readiness.drain();
+        let response = readiness_check(readiness.clone()).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        readiness.resume();
+        let response = readiness_check(readiness).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn stream_until_shutdown_final_item() {
+        let coordinator = ShutdownCoordinator::new();
+        let signal = coordinator.signal();
+        let stream = signal.stream_until_shutdown(stream::repeat(1), Some(2));
+
+        coordinator.start_shutdown(None);
+        let items: Vec<_> = stream.collect().await;
+
+        assert_eq!(items.last(), Some(&2));
+    }
+
+    #[cot::test]
+    async fn tracks_streaming_bodies_only() {
+        let coordinator = ShutdownCoordinator::new();
+
+        let fixed = coordinator.track(Body::fixed("hello"));
+        assert_eq!(coordinator.in_flight(), 0);
+
+        let streaming = coordinator.track(pending_body());
+        assert_eq!(coordinator.in_flight(), 1);
+
+        drop(streaming);
+        drop(fixed);
+        assert_eq!(coordinator.in_flight(), 0);
+    }
+
+    #[cot::test]
+    async fn finished_body_is_not_in_flight() {
+        let coordinator = ShutdownCoordinator::new();
+
+        let body = coordinator.track(Body::streaming(stream::iter([Ok(Bytes::from("a"))])));
+        assert_eq!(coordinator.in_flight(), 1);
+
+        let mut body = std::pin::pin!(body);
+        while body.frame().await.is_some() {}
+        assert_eq!(coordinator.in_flight(), 0);
+    }
+
+    #[cot::test]
+    async fn terminates_after_grace_period() {
+        let coordinator = ShutdownCoordinator::new();
+        let body = coordinator.track(pending_body());
+
+        coordinator.start_shutdown(Some(Duration::from_millis(10)));
+        let collected = tokio::time::timeout(Duration::from_secs(5), body.collect()).await;
+
+        assert!(collected.is_ok());
+        assert_eq!(coordinator.in_flight(), 0);
+    }
+
+    #[cot::test]
+    async fn waits_without_grace_period() {
+        let coordinator = ShutdownCoordinator::new();
+        let body = coordinator.track(pending_body());
+
+        coordinator.start_shutdown(None);
+        let collected = tokio::time::timeout(Duration::from_millis(50), body.collect()).await;
+
+        assert!(collected.is_err());
+    }
+}
@@ -0,0 +1,463 @@
+//! Runtime-tunable site settings stored in the database.
+//!
+//! This module provides an app storing key-value settings in the database, so
+//! that they can be changed (for instance, in the admin panel) without
+//! redeploying the project. The values are stored as strings, and are
+//! converted to and from the requested types using the [`FromStr`] and
+//! [`Display`] traits.
+//!
+//! When the `cache` feature is enabled and the project has a cache
+//! configured, the settings are cached, and the cached values are invalidated
+//! whenever a setting is changed.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::html::Html;
+//! use cot::site_settings::SiteSettings;
+//!
+//! async fn index(settings: SiteSettings) -> cot::Result<Html> {
+//!     let signups_enabled = settings.get_or("signups_enabled", false).await?;
+//!
+//!     if signups_enabled {
+//!         Ok(Html::new("Sign up now!"))
+//!     } else {
+//!         Ok(Html::new("Signups are closed."))
+//!     }
+//! }
+//! ```
+
+pub mod migrations;
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// Importing `Auto` from `cot` instead of `crate` so that the migration generator
+// can figure out it's an autogenerated field
+use cot::db::Auto;
+use cot_core::error::impl_into_cot_error;
+use cot_macros::AdminModel;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::App;
+use crate::admin::{AdminModelManager, DefaultAdminModelManager};
+use crate::db::migrations::{ModelSchema, SyncDynMigration};
+use crate::db::{Database, LimitedString, Model, model, query};
+use crate::form::Form;
+use crate::request::extractors::FromRequestHead;
+use crate::request::{RequestExt, RequestHead};
+
+pub(crate) const MAX_KEY_LENGTH: u32 = 255;
+
+/// How long the settings are cached for. The cached values are invalidated
+/// when a setting is changed, so this only matters for changes made outside
+/// of Cot (e.g. with raw SQL queries).
+#[cfg(feature = "cache")]
+const CACHE_EXPIRY: crate::config::Timeout =
+    crate::config::Timeout::After(std::time::Duration::from_secs(300));
+
+/// A single site setting stored in the database.
+#[derive(Debug, Clone, Form, AdminModel, Serialize, Deserialize)]
+#[model]
+pub struct SiteSetting {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    #[model(unique)]
+    key: LimitedString<MAX_KEY_LENGTH>,
+    value: String,
+}
+
+impl SiteSetting {
+    /// Returns the key of the setting.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the raw value of the setting.
+    #[must_use]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl Display for SiteSetting {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.key)
+    }
+}
+
+/// An error that occurs when accessing the site settings.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum SiteSettingsError {
+    /// The key is too long.
+    #[error("setting key is too long (max {MAX_KEY_LENGTH} characters, got {0})")]
+    KeyTooLong(usize),
+    /// The stored value couldn't be converted to the requested type.
+    #[error("invalid value of the `{key}` setting: {message}")]
+    InvalidValue {
+        /// The key of the setting.
+        key: String,
+        /// The error message returned when parsing the value.
+        message: String,
+    },
+}
+impl_into_cot_error!(SiteSettingsError);
+
+/// Typed access to the site settings stored in the database.
+///
+/// This can be extracted from requests in the request handlers. Requires the
+/// [`SiteSettingsApp`] to be registered, so that its migrations are applied.
+///
+/// # Examples
+///
+/// ```
+/// use cot::html::Html;
+/// use cot::site_settings::SiteSettings;
+///
+/// async fn close_signups(settings: SiteSettings) -> cot::Result<Html> {
+///     settings.set("signups_enabled", false).await?;
+///
+///     Ok(Html::new("Signups closed."))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SiteSettings {
+    database: Database,
+}
+
+impl SiteSettings {
+    /// Creates a new instance accessing the settings stored in the given
+    /// database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::site_settings::SiteSettings;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// let settings = SiteSettings::new(db);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// Returns the value of the setting with the given key, or `None` if the
+    /// setting doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value couldn't be converted to the requested
+    /// type, or if there was a problem accessing the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::site_settings::SiteSettings;
+    ///
+    /// async fn max_upload_size(settings: &SiteSettings) -> cot::Result<u64> {
+    ///     let size = settings.get::<u64>("max_upload_size").await?;
+    ///     Ok(size.unwrap_or(10 * 1024 * 1024))
+    /// }
+    /// ```
+    pub async fn get<T>(&self, key: &str) -> cot::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let Some(setting) = self.get_setting(key).await? else {
+            return Ok(None);
+        };
+
+        match setting.value.parse() {
+            Ok(value) => Ok(Some(value)),
+            Err(error) => Err(SiteSettingsError::InvalidValue {
+                key: key.to_owned(),
+                message: error.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Returns the value of the setting with the given key, or `default` if
+    /// the setting doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value couldn't be converted to the requested
+    /// type, or if there was a problem accessing the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::site_settings::SiteSettings;
+    ///
+    /// async fn signups_enabled(settings: &SiteSettings) -> cot::Result<bool> {
+    ///     settings.get_or("signups_enabled", true).await
+    /// }
+    /// ```
+    pub async fn get_or<T>(&self, key: &str, default: T) -> cot::Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Ok(self.get(key).await?.unwrap_or(default))
+    }
+
+    /// Sets the value of the setting with the given key, creating the setting
+    /// if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is longer than 255 characters, or if there
+    /// was a problem accessing the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::site_settings::SiteSettings;
+    ///
+    /// async fn enable_signups(settings: &SiteSettings) -> cot::Result<()> {
+    ///     settings.set("signups_enabled", true).await
+    /// }
+    /// ```
+    pub async fn set<T: Display>(&self, key: &str, value: T) -> cot::Result<()> {
+        let key = limited_key(key)?;
+        let setting = query!(SiteSetting, $key == key.clone())
+            .get(&self.database)
+            .await?;
+
+        let mut setting = match setting {
+            Some(mut setting) => {
+                setting.value = value.to_string();
+                setting
+            }
+            None => SiteSetting {
+                id: Auto::auto(),
+                key,
+                value: value.to_string(),
+            },
+        };
+        setting.save(&self.database).await?;
+        Ok(())
+    }
+
+    /// Removes the setting with the given key. Does nothing if the setting
+    /// doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is longer than 255 characters, or if there
+    /// was a problem accessing the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::site_settings::SiteSettings;
+    ///
+    /// async fn reset_signups(settings: &SiteSettings) -> cot::Result<()> {
+    ///     settings.remove("signups_enabled").await
+    /// }
+    /// ```
+    pub async fn remove(&self, key: &str) -> cot::Result<()> {
+        let key = limited_key(key)?;
+        query!(SiteSetting, $key == key)
+            .delete(&self.database)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_setting(&self, key: &str) -> cot::Result<Option<SiteSetting>> {
+        let key = limited_key(key)?;
+
+        #[cfg(feature = "cache")]
+        let setting = query!(SiteSetting, $key == key)
+            .cached(CACHE_EXPIRY)
+            .get(&self.database)
+            .await?;
+        #[cfg(not(feature = "cache"))]
+        let setting = query!(SiteSetting, $key == key).get(&self.database).await?;
+
+        Ok(setting)
+    }
+}
+
+fn limited_key(key: &str) -> Result<LimitedString<MAX_KEY_LENGTH>, SiteSettingsError> {
+    LimitedString::new(key).map_err(|_| SiteSettingsError::KeyTooLong(key.len()))
+}
+
+impl FromRequestHead for SiteSettings {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        Ok(Self::new(head.context().database().clone()))
+    }
+}
+
+/// An app that provides the site settings model, its migrations, and its
+/// admin panel integration.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cot::config::{DatabaseConfig, ProjectConfig};
+/// use cot::project::RegisterAppsContext;
+/// use cot::site_settings::SiteSettingsApp;
+/// use cot::{App, AppBuilder, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn config(&self, config_name: &str) -> cot::Result<ProjectConfig> {
+///         Ok(ProjectConfig::builder()
+///             .database(DatabaseConfig::builder().url("sqlite::memory:").build())
+///             .build())
+///     }
+///
+///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+///         apps.register(SiteSettingsApp::new());
+///     }
+/// }
+///
+/// #[cot::main]
+/// fn main() -> impl Project {
+///     MyProject
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
+pub struct SiteSettingsApp;
+
+impl SiteSettingsApp {
+    /// Create a new instance of the site settings app.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::site_settings::SiteSettingsApp;
+    /// let app = SiteSettingsApp::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for SiteSettingsApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl App for SiteSettingsApp {
+    fn name(&self) -> &'static str {
+        "cot_site_settings"
+    }
+
+    fn admin_model_managers(&self) -> Vec<Box<dyn AdminModelManager>> {
+        vec![Box::new(DefaultAdminModelManager::<SiteSetting>::new())]
+    }
+
+    fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
+        cot::db::migrations::wrap_migrations(migrations::MIGRATIONS)
+    }
+
+    fn models(&self) -> Vec<ModelSchema> {
+        vec![ModelSchema::of::<SiteSetting>()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestDatabase;
+
+    async fn test_settings() -> (TestDatabase, SiteSettings) {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db
+            .add_migrations(migrations::MIGRATIONS.to_vec())
+            .run_migrations()
+            .await;
+        let settings = SiteSettings::new(test_db.database());
+
+        (test_db, settings)
+    }
+
+    #[test]
+    fn site_settings_app_models_match_migrations() {
+        let app = SiteSettingsApp::new();
+
+        let engine = crate::db::migrations::MigrationEngine::new(app.migrations()).unwrap();
+
+        assert_eq!(engine.check_models(&app.models()), vec![]);
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn get_set_remove() {
+        let (_test_db, settings) = test_settings().await;
+
+        assert_eq!(settings.get::<bool>("signups_enabled").await.unwrap(), None);
+        assert!(settings.get_or("signups_enabled", true).await.unwrap());
+
+        settings.set("signups_enabled", false).await.unwrap();
+        assert_eq!(
+            settings.get::<bool>("signups_enabled").await.unwrap(),
+            Some(false)
+        );
+
+        settings.set("signups_enabled", true).await.unwrap();
+        assert_eq!(
+            settings.get::<bool>("signups_enabled").await.unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            SiteSetting::objects()
+                .count(&settings.database)
+                .await
+                .unwrap(),
+            1
+        );
+
+        settings.remove("signups_enabled").await.unwrap();
+        assert_eq!(settings.get::<bool>("signups_enabled").await.unwrap(), None);
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn get_invalid_value() {
+        let (_test_db, settings) = test_settings().await;
+
+        settings.set("max_upload_size", "large").await.unwrap();
+
+        let error = settings.get::<u64>("max_upload_size").await.unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("invalid value of the `max_upload_size` setting")
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn key_too_long() {
+        let (_test_db, settings) = test_settings().await;
+
+        let error = settings.set(&"a".repeat(256), 1).await.unwrap_err();
+        assert!(error.to_string().contains("setting key is too long"));
+    }
+}
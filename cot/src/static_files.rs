@@ -3,9 +3,9 @@
 //! This module provides middleware for serving static files from the `static`
 //! directory of the project.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::future::Future;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -15,15 +15,18 @@ use bytes::Bytes;
 use cot_core::error::impl_into_cot_error;
 use digest::Digest;
 use futures_core::ready;
-use http::{Request, header};
+use http::{HeaderMap, HeaderValue, Method, Request, StatusCode, header};
 use pin_project_lite::pin_project;
+use serde::Serialize;
 use thiserror::Error;
 use tower::Service;
+use tracing::warn;
 
 use crate::Body;
 use crate::config::{StaticFilesConfig, StaticFilesPathRewriteMode};
 use crate::project::MiddlewareContext;
-use crate::response::{Response, ResponseExt};
+use crate::response::download::{ByteRange, is_range_applicable, parse_range};
+use crate::response::{ETag, Response, ResponseExt};
 
 /// Macro to define static files by specifying paths.
 ///
@@ -74,11 +77,23 @@ macro_rules! static_files {
     };
 }
 
+/// The name of the manifest file written by the `collect-static` command in
+/// the [`StaticFilesPathRewriteMode::FilenameHash`] mode.
+const MANIFEST_FILE_NAME: &str = "staticfiles.toml";
+
+/// The `Cache-Control` header value of the files with the hash in their names,
+/// which never change.
+#[cfg(feature = "storage")]
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
 /// Struct representing a collection of static files.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct StaticFiles {
     url_prefix: String,
     files: HashMap<String, StaticFileWithMeta>,
+    /// Maps the hashed paths to the original ones; only populated in the
+    /// [`StaticFilesPathRewriteMode::FilenameHash`] mode.
+    hashed_paths: HashMap<String, String>,
     rewrite_mode: StaticFilesPathRewriteMode,
     cache_timeout: Option<Duration>,
 }
@@ -86,9 +101,16 @@ pub(crate) struct StaticFiles {
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct StaticFileWithMeta {
     url: String,
+    hashed_path: String,
+    etag: ETag,
     file: StaticFile,
 }
 
+#[derive(Debug, Serialize)]
+struct StaticFilesManifest<'a> {
+    files: BTreeMap<&'a str, &'a str>,
+}
+
 impl StaticFiles {
     /// Creates a new `StaticFiles` instance.
     #[must_use]
@@ -96,6 +118,7 @@ impl StaticFiles {
         Self {
             url_prefix: config.url.clone(),
             files: HashMap::new(),
+            hashed_paths: HashMap::new(),
             rewrite_mode: config.rewrite.clone(),
             cache_timeout: config.cache_timeout,
         }
@@ -103,25 +126,71 @@ impl StaticFiles {
 
     pub(crate) fn add_file(&mut self, file: StaticFile) {
         let path = file.path.clone();
+        let hash = Self::file_hash(&file);
+        let hashed_path = hashed_file_path(&path, &hash);
         let file = StaticFileWithMeta {
-            url: self.file_url(&file),
+            url: self.file_url(&path, &hash, &hashed_path),
+            hashed_path: hashed_path.clone(),
+            etag: ETag::from_content(&file.content),
             file,
         };
-        self.files.insert(path, file);
+
+        if let Some(previous) = self.files.insert(path.clone(), file) {
+            self.hashed_paths.remove(&previous.hashed_path);
+        }
+        if self.rewrite_mode == StaticFilesPathRewriteMode::FilenameHash {
+            self.hashed_paths.insert(hashed_path, path);
+        }
     }
 
-    fn file_url(&self, file: &StaticFile) -> String {
+    /// Adds all the files in the directory (recursively), with the paths
+    /// relative to the directory.
+    pub(crate) fn add_dir(&mut self, dir: &Path) -> Result<(), LoadStaticDirError> {
+        self.add_dir_entries(dir, "")
+            .map_err(|source| LoadStaticDirError {
+                dir: dir.to_owned(),
+                source,
+            })
+    }
+
+    fn add_dir_entries(&mut self, dir: &Path, path_prefix: &str) -> std::io::Result<()> {
+        let mut entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+        // make the order (and hence the precedence of duplicate paths) deterministic
+        entries.sort_by_key(std::fs::DirEntry::file_name);
+
+        for entry in entries {
+            let entry_path = entry.path();
+            let Some(name) = entry.file_name().to_str().map(ToOwned::to_owned) else {
+                warn!(
+                    "Skipping static file with a non-UTF-8 name: `{}`",
+                    entry_path.display()
+                );
+                continue;
+            };
+            let path = format!("{path_prefix}{name}");
+
+            // `metadata` follows the symlinks, unlike `DirEntry::file_type`
+            if std::fs::metadata(&entry_path)?.is_dir() {
+                self.add_dir_entries(&entry_path, &format!("{path}/"))?;
+            } else {
+                let content = std::fs::read(&entry_path)?;
+                self.add_file(StaticFile::new(path, content));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn file_url(&self, path: &str, hash: &str, hashed_path: &str) -> String {
         match self.rewrite_mode {
             StaticFilesPathRewriteMode::None => {
-                format!("{}{}", self.url_prefix, file.path.clone())
+                format!("{}{}", self.url_prefix, path)
             }
             StaticFilesPathRewriteMode::QueryParam => {
-                format!(
-                    "{}{}?v={}",
-                    self.url_prefix,
-                    file.path.clone(),
-                    Self::file_hash(file)
-                )
+                format!("{}{}?v={}", self.url_prefix, path, hash)
+            }
+            StaticFilesPathRewriteMode::FilenameHash => {
+                format!("{}{}", self.url_prefix, hashed_path)
             }
         }
     }
@@ -132,10 +201,12 @@ impl StaticFiles {
     }
 
     #[must_use]
-    fn get_file(&self, path: &str) -> Option<&StaticFile> {
-        self.files
-            .get(path)
-            .map(|file_with_meta| &file_with_meta.file)
+    fn get_file(&self, path: &str) -> Option<&StaticFileWithMeta> {
+        self.files.get(path).or_else(|| {
+            self.hashed_paths
+                .get(path)
+                .and_then(|path| self.files.get(path))
+        })
     }
 
     #[must_use]
@@ -146,17 +217,152 @@ impl StaticFiles {
     }
 
     pub(crate) fn collect_into(&self, path: &Path) -> Result<(), CollectStaticError> {
+        let write_hashed = self.rewrite_mode == StaticFilesPathRewriteMode::FilenameHash;
+
         for (file_path, file_with_meta) in &self.files {
-            let file_path = path.join(file_path);
-            std::fs::create_dir_all(
-                file_path
-                    .parent()
-                    .expect("a joined file path should always have a parent"),
-            )?;
-            std::fs::write(file_path, &file_with_meta.file.content)?;
+            write_file(&path.join(file_path), &file_with_meta.file.content)?;
+            if write_hashed {
+                write_file(
+                    &path.join(&file_with_meta.hashed_path),
+                    &file_with_meta.file.content,
+                )?;
+            }
+        }
+
+        if write_hashed {
+            write_file(&path.join(MANIFEST_FILE_NAME), self.manifest().as_bytes())?;
         }
+
         Ok(())
     }
+
+    /// Uploads the static files to the storage, overwriting the files that
+    /// are already there, and optionally deletes all the other files from the
+    /// storage.
+    ///
+    /// The files are uploaded along with their content types and the
+    /// `Cache-Control` values they would be served with by the middleware,
+    /// except for the copies with the hash in their names, which never change
+    /// and hence are cached for a year.
+    #[cfg(feature = "storage")]
+    pub(crate) async fn collect_into_storage(
+        &self,
+        storage: &crate::storage::FileStorage,
+        delete_orphans: bool,
+    ) -> crate::storage::StorageResult<CollectedStaticFiles> {
+        use std::collections::HashSet;
+
+        use crate::storage::SaveOptions;
+
+        let write_hashed = self.rewrite_mode == StaticFilesPathRewriteMode::FilenameHash;
+        let mut uploads = Vec::new();
+        for (file_path, file_with_meta) in &self.files {
+            let content = &file_with_meta.file.content;
+            let mut options =
+                SaveOptions::new().content_type(file_with_meta.file.mime_type.to_string());
+            if let Some(cache_control) = self.cache_control() {
+                options = options.cache_control(cache_control);
+            }
+            uploads.push((file_path.clone(), content.clone(), options.clone()));
+            if write_hashed {
+                uploads.push((
+                    file_with_meta.hashed_path.clone(),
+                    content.clone(),
+                    options.cache_control(IMMUTABLE_CACHE_CONTROL),
+                ));
+            }
+        }
+        if write_hashed {
+            uploads.push((
+                MANIFEST_FILE_NAME.to_owned(),
+                Bytes::from(self.manifest()),
+                SaveOptions::new()
+                    .content_type("application/toml")
+                    .cache_control("no-cache"),
+            ));
+        }
+        uploads.sort_by(|(name, ..), (other, ..)| name.cmp(other));
+
+        let mut uploaded = HashSet::new();
+        for (name, content, options) in uploads {
+            storage
+                .save_overwriting(
+                    &name,
+                    Box::pin(futures_util::stream::once(async move { Ok(content) })),
+                    &options,
+                )
+                .await?;
+            uploaded.insert(name);
+        }
+
+        let mut deleted = 0;
+        if delete_orphans {
+            for name in storage.list().await? {
+                if !uploaded.contains(&name) {
+                    storage.delete(&name).await?;
+                    deleted += 1;
+                }
+            }
+        }
+
+        Ok(CollectedStaticFiles {
+            uploaded: uploaded.len(),
+            deleted,
+        })
+    }
+
+    /// Returns the `Cache-Control` header value the files are served with.
+    fn cache_control(&self) -> Option<String> {
+        self.cache_timeout
+            .map(|timeout| format!("max-age={}", timeout.as_secs()))
+    }
+
+    /// Returns the manifest mapping the original paths of the files to the
+    /// hashed ones.
+    fn manifest(&self) -> String {
+        let manifest = StaticFilesManifest {
+            files: self
+                .files
+                .iter()
+                .map(|(file_path, file_with_meta)| {
+                    (file_path.as_str(), file_with_meta.hashed_path.as_str())
+                })
+                .collect(),
+        };
+        toml::to_string(&manifest).expect("the manifest should always be serializable")
+    }
+}
+
+/// The result of [`StaticFiles::collect_into_storage`].
+#[cfg(feature = "storage")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct CollectedStaticFiles {
+    /// The number of files uploaded to the storage.
+    pub(crate) uploaded: usize,
+    /// The number of orphaned files deleted from the storage.
+    pub(crate) deleted: usize,
+}
+
+fn write_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    std::fs::create_dir_all(
+        path.parent()
+            .expect("a joined file path should always have a parent"),
+    )?;
+    std::fs::write(path, content)
+}
+
+/// Inserts the hash into the file name of the path, before the extension, e.g.
+/// `css/style.css` becomes `css/style.<hash>.css`.
+fn hashed_file_path(path: &str, hash: &str) -> String {
+    let file_name_start = path.rfind('/').map_or(0, |index| index + 1);
+    match path[file_name_start..].rfind('.') {
+        // a leading dot (as in `.htaccess`) doesn't start the extension
+        Some(dot) if dot > 0 => {
+            let (stem, extension) = path.split_at(file_name_start + dot);
+            format!("{stem}.{hash}{extension}")
+        }
+        _ => format!("{path}.{hash}"),
+    }
 }
 
 #[derive(Debug, Error)]
@@ -164,6 +370,71 @@ impl StaticFiles {
 pub(crate) struct CollectStaticError(#[from] std::io::Error);
 impl_into_cot_error!(CollectStaticError);
 
+#[cfg(feature = "storage")]
+#[derive(Debug, Error)]
+#[error("`static_files.storage` must be configured to upload the static files to a storage")]
+pub(crate) struct StaticFilesStorageNotConfigured;
+#[cfg(feature = "storage")]
+impl_into_cot_error!(StaticFilesStorageNotConfigured);
+
+#[derive(Debug, Error)]
+#[error("could not read the static files directory `{}`: {source}", dir.display())]
+pub(crate) struct LoadStaticDirError {
+    dir: PathBuf,
+    #[source]
+    source: std::io::Error,
+}
+
+impl StaticFileWithMeta {
+    /// Returns the response for the file, honoring the `Range` header of
+    /// `GET` requests.
+    #[must_use]
+    fn as_response(&self, method: &Method, request_headers: &HeaderMap) -> Response {
+        let mut response = self.file.as_response();
+        let headers = response.headers_mut();
+        headers.insert(header::ETAG, HeaderValue::from(&self.etag));
+        headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+        if method != Method::GET || !is_range_applicable(request_headers, Some(&self.etag), None) {
+            return response;
+        }
+        let content_length = self.file.content.len() as u64;
+        let Some(range) = request_headers
+            .get(header::RANGE)
+            .and_then(|range| range.to_str().ok())
+            .and_then(|range| parse_range(range, content_length))
+        else {
+            return response;
+        };
+
+        let (mut head, _body) = response.into_parts();
+        let body = match range {
+            ByteRange::Satisfiable { start, end } => {
+                let start = usize::try_from(start).expect("range start is within the content");
+                let end = usize::try_from(end).expect("range end is within the content");
+                head.status = StatusCode::PARTIAL_CONTENT;
+                head.headers.insert(
+                    header::CONTENT_RANGE,
+                    HeaderValue::try_from(format!("bytes {start}-{end}/{content_length}"))
+                        .expect("Content-Range should always be a valid header value"),
+                );
+                Body::fixed(self.file.content.slice(start..=end))
+            }
+            ByteRange::Unsatisfiable => {
+                head.status = StatusCode::RANGE_NOT_SATISFIABLE;
+                head.headers.remove(header::CONTENT_TYPE);
+                head.headers.insert(
+                    header::CONTENT_RANGE,
+                    HeaderValue::try_from(format!("bytes */{content_length}"))
+                        .expect("Content-Range should always be a valid header value"),
+                );
+                Body::empty()
+            }
+        };
+        Response::from_parts(head, body)
+    }
+}
+
 impl From<&MiddlewareContext> for StaticFiles {
     fn from(context: &MiddlewareContext) -> Self {
         let mut static_files = StaticFiles::new(&context.config().static_files);
@@ -173,6 +444,11 @@ impl From<&MiddlewareContext> for StaticFiles {
                 static_files.add_file(file);
             }
         }
+        for dir in &context.config().static_files.dirs {
+            static_files
+                .add_dir(dir)
+                .unwrap_or_else(|error| panic!("{error}"));
+        }
 
         static_files
     }
@@ -266,11 +542,18 @@ impl StaticFile {
 ///
 /// This middleware serves static files defined by the applications by using
 /// the [`CotApp::static_files`](crate::App::static_files) trait
-/// method. The middleware serves files from the `/static/` path.
+/// method, as well as the files from the directories configured in
+/// [`StaticFilesConfig::dirs`]. The middleware serves files from the
+/// `/static/` path.
 ///
 /// When a request is made to a path starting with `/static/`, the middleware
 /// checks if the file exists in the static files collection. If it does, the
 /// file is served. Otherwise, the request is passed to the inner service.
+///
+/// The responses contain the `ETag` header, and single byte ranges requested
+/// with the `Range` header (optionally guarded by `If-Range`) are served with
+/// `206 Partial Content`, so media files can be seeked and downloads can be
+/// resumed by the browsers.
 #[derive(Debug, Clone)]
 pub struct StaticFilesMiddleware {
     static_files: Arc<StaticFiles>,
@@ -279,6 +562,11 @@ pub struct StaticFilesMiddleware {
 impl StaticFilesMiddleware {
     /// Creates a new `StaticFilesMiddleware` instance from the project
     /// context.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the directories configured in
+    /// [`StaticFilesConfig::dirs`] cannot be read.
     #[must_use]
     pub fn from_context(context: &MiddlewareContext) -> Self {
         Self {
@@ -332,16 +620,16 @@ where
             if let Some(stripped_path) = path.strip_prefix(&self.static_files.url_prefix) {
                 self.static_files
                     .get_file(stripped_path)
-                    .map(StaticFile::as_response)
+                    .map(|file| file.as_response(req.method(), req.headers()))
             } else {
                 None
             };
 
         if let Some(mut response) = file_contents {
-            if let Some(timeout) = self.static_files.cache_timeout {
+            if let Some(cache_control) = self.static_files.cache_control() {
                 response.headers_mut().insert(
                     header::CACHE_CONTROL,
-                    header::HeaderValue::from_str(&format!("max-age={}", timeout.as_secs()))
+                    HeaderValue::from_str(&cache_control)
                         .expect("failed to create cache control header"),
                 );
             }
@@ -419,7 +707,10 @@ mod tests {
         let file = static_files.get_file("test.txt");
 
         assert!(file.is_some());
-        assert_eq!(file.unwrap().content, Bytes::from("This is a test file"));
+        assert_eq!(
+            file.unwrap().file.content,
+            Bytes::from("This is a test file")
+        );
     }
 
     #[cot::test]
@@ -472,6 +763,75 @@ mod tests {
         );
     }
 
+    async fn static_file_request(headers: &[(header::HeaderName, &str)]) -> Response {
+        let middleware = StaticFilesMiddleware {
+            static_files: Arc::new(create_static_files()),
+        };
+        let service = middleware.layer(tower::service_fn(|_req| async {
+            Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+        }));
+
+        let mut request = Request::builder().uri("/static/test.txt");
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        let request = request.body(Body::empty()).unwrap();
+
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_range() {
+        let response = static_file_request(&[(header::RANGE, "bytes=5-8")]).await;
+
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 5-8/19");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/plain");
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            Bytes::from("is a")
+        );
+
+        let response = static_file_request(&[(header::RANGE, "bytes=-4")]).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            Bytes::from("file")
+        );
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_range_not_satisfiable() {
+        let response = static_file_request(&[(header::RANGE, "bytes=100-")]).await;
+
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */19");
+        assert!(!response.headers().contains_key(header::CONTENT_TYPE));
+        assert!(response.into_body().into_bytes().await.unwrap().is_empty());
+    }
+
+    #[cot::test]
+    async fn static_files_middleware_if_range() {
+        let etag = ETag::from_content(b"This is a test file").to_string();
+
+        let response =
+            static_file_request(&[(header::RANGE, "bytes=0-3"), (header::IF_RANGE, &etag)]).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+
+        let response = static_file_request(&[
+            (header::RANGE, "bytes=0-3"),
+            (header::IF_RANGE, r#""outdated""#),
+        ])
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.into_body().into_bytes().await.unwrap(),
+            Bytes::from("This is a test file")
+        );
+    }
+
     #[cot::test]
     async fn static_files_middleware_with_config() {
         let mut static_files = StaticFiles::new(
@@ -578,14 +938,14 @@ mod tests {
         let middleware = StaticFilesMiddleware::from_context(bootstrapper.context());
         let static_files = middleware.static_files;
 
-        let file = static_files.get_file("test/test.txt").unwrap();
+        let file = &static_files.get_file("test/test.txt").unwrap().file;
         assert_eq!(file.mime_type, mime::TEXT_PLAIN);
         assert_eq!(
             file.content,
             Bytes::from_static(include_bytes!("../static/test/test.txt"))
         );
 
-        let file = static_files.get_file("app2/test.js").unwrap();
+        let file = &static_files.get_file("app2/test.js").unwrap().file;
         assert_eq!(file.content, Bytes::from("test"));
     }
 
@@ -619,6 +979,210 @@ mod tests {
         );
     }
 
+    #[test]
+    fn collect_into_filename_hash() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp_path = temp_dir.path().to_path_buf();
+
+        let mut static_files = StaticFiles::new(
+            &StaticFilesConfig::builder()
+                .rewrite(StaticFilesPathRewriteMode::FilenameHash)
+                .build(),
+        );
+        static_files.add_file(StaticFile::new("css/style.css", "body {}"));
+        static_files.collect_into(&temp_path).unwrap();
+
+        let hashed_path = static_files
+            .path_for("css/style.css")
+            .unwrap()
+            .strip_prefix("/static/")
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(temp_path.join("css/style.css")).unwrap(),
+            "body {}"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_path.join(hashed_path)).unwrap(),
+            "body {}"
+        );
+
+        let manifest: toml::Table =
+            toml::from_str(&fs::read_to_string(temp_path.join(MANIFEST_FILE_NAME)).unwrap())
+                .unwrap();
+        assert_eq!(
+            manifest["files"]["css/style.css"].as_str(),
+            Some(hashed_path)
+        );
+    }
+
+    /// A storage recording the options the files were saved with.
+    #[cfg(feature = "storage")]
+    #[derive(Debug, Clone, Default)]
+    struct RecordingStorage {
+        files: crate::storage::memory::Memory,
+        options: Arc<std::sync::Mutex<HashMap<String, crate::storage::SaveOptions>>>,
+    }
+
+    #[cfg(feature = "storage")]
+    impl crate::storage::Storage for RecordingStorage {
+        async fn save(
+            &self,
+            name: &str,
+            content: crate::storage::ByteStream<'_>,
+        ) -> crate::storage::StorageResult<()> {
+            self.files.save(name, content).await
+        }
+
+        async fn save_with_options(
+            &self,
+            name: &str,
+            content: crate::storage::ByteStream<'_>,
+            options: &crate::storage::SaveOptions,
+        ) -> crate::storage::StorageResult<()> {
+            self.options
+                .lock()
+                .unwrap()
+                .insert(name.to_owned(), options.clone());
+            self.files.save(name, content).await
+        }
+
+        async fn open(&self, name: &str) -> crate::storage::StorageResult<Bytes> {
+            self.files.open(name).await
+        }
+
+        async fn delete(&self, name: &str) -> crate::storage::StorageResult<()> {
+            self.files.delete(name).await
+        }
+
+        async fn exists(&self, name: &str) -> crate::storage::StorageResult<bool> {
+            self.files.exists(name).await
+        }
+
+        fn url(&self, name: &str) -> String {
+            self.files.url(name)
+        }
+
+        async fn list(&self) -> crate::storage::StorageResult<Vec<String>> {
+            self.files.list().await
+        }
+    }
+
+    #[cfg(feature = "storage")]
+    #[cot::test]
+    async fn collect_into_storage() {
+        use crate::storage::{FileStorage, Storage};
+
+        let backend = RecordingStorage::default();
+        let storage = FileStorage::new(backend.clone());
+        storage.save_bytes("old.css", "body {}").await.unwrap();
+
+        let mut static_files = StaticFiles::new(
+            &StaticFilesConfig::builder()
+                .rewrite(StaticFilesPathRewriteMode::FilenameHash)
+                .cache_timeout(Duration::from_secs(3600))
+                .build(),
+        );
+        static_files.add_file(StaticFile::new("css/style.css", "p {}"));
+        let collected = static_files
+            .collect_into_storage(&storage, false)
+            .await
+            .unwrap();
+
+        let hashed_path = static_files
+            .path_for("css/style.css")
+            .unwrap()
+            .strip_prefix("/static/")
+            .unwrap();
+        assert_eq!(
+            collected,
+            CollectedStaticFiles {
+                uploaded: 3,
+                deleted: 0
+            }
+        );
+        let mut expected = vec!["css/style.css", hashed_path, "old.css", MANIFEST_FILE_NAME];
+        expected.sort_unstable();
+        assert_eq!(storage.list().await.unwrap(), expected);
+        assert_eq!(storage.open(hashed_path).await.unwrap(), "p {}");
+
+        let options = backend.options.lock().unwrap().clone();
+        let original = &options["css/style.css"];
+        assert_eq!(original.cache_control.as_deref(), Some("max-age=3600"));
+        assert_eq!(original.content_type.as_deref(), Some("text/css"));
+        let hashed = &options[hashed_path];
+        assert_eq!(
+            hashed.cache_control.as_deref(),
+            Some(IMMUTABLE_CACHE_CONTROL)
+        );
+        assert_eq!(hashed.content_type.as_deref(), Some("text/css"));
+        assert_eq!(
+            options[MANIFEST_FILE_NAME].cache_control.as_deref(),
+            Some("no-cache")
+        );
+        assert!(backend.exists("old.css").await.unwrap());
+    }
+
+    #[cfg(feature = "storage")]
+    #[cot::test]
+    async fn collect_into_storage_delete_orphans() {
+        use crate::storage::FileStorage;
+        use crate::storage::memory::Memory;
+
+        let storage = FileStorage::new(Memory::new());
+        storage.save_bytes("old.css", "body {}").await.unwrap();
+        storage.save_bytes("test.txt", "old").await.unwrap();
+
+        let mut static_files = StaticFiles::new(&StaticFilesConfig::default());
+        static_files.add_file(StaticFile::new("test.txt", "new"));
+        let collected = static_files
+            .collect_into_storage(&storage, true)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            collected,
+            CollectedStaticFiles {
+                uploaded: 1,
+                deleted: 1
+            }
+        );
+        assert_eq!(storage.list().await.unwrap(), vec!["test.txt"]);
+        assert_eq!(storage.open("test.txt").await.unwrap(), "new");
+    }
+
+    #[test]
+    fn add_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("css/vendor")).unwrap();
+        fs::write(temp_dir.path().join("test.txt"), "overridden").unwrap();
+        fs::write(temp_dir.path().join("css/vendor/lib.css"), "body {}").unwrap();
+
+        let mut static_files = create_static_files();
+        static_files.add_dir(temp_dir.path()).unwrap();
+
+        let file = &static_files.get_file("css/vendor/lib.css").unwrap().file;
+        assert_eq!(file.content, Bytes::from("body {}"));
+        assert_eq!(file.mime_type, mime::TEXT_CSS);
+        let file = &static_files.get_file("test.txt").unwrap().file;
+        assert_eq!(file.content, Bytes::from("overridden"));
+    }
+
+    #[test]
+    fn add_dir_missing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut static_files = create_static_files();
+
+        let error = static_files
+            .add_dir(&temp_dir.path().join("missing"))
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("could not read the static files directory")
+        );
+    }
+
     #[test]
     fn collect_into_empty() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -670,6 +1234,9 @@ mod tests {
             url: "/static/".to_string(),
             rewrite: StaticFilesPathRewriteMode::None,
             cache_timeout: None,
+            dirs: Vec::new(),
+            #[cfg(feature = "storage")]
+            storage: None,
         });
 
         let file = StaticFile::new("test.txt", "test content");
@@ -684,6 +1251,9 @@ mod tests {
             url: "/static/".to_string(),
             rewrite: StaticFilesPathRewriteMode::QueryParam,
             cache_timeout: None,
+            dirs: Vec::new(),
+            #[cfg(feature = "storage")]
+            storage: None,
         });
 
         let file = StaticFile::new("test.txt", "test content");
@@ -694,12 +1264,49 @@ mod tests {
         assert_eq!(url.len(), "/static/test.txt?v=".len() + 12); // 6 bytes of hash in hex = 12 chars
     }
 
+    #[test]
+    fn static_files_filename_hash() {
+        let mut static_files = StaticFiles::new(
+            &StaticFilesConfig::builder()
+                .rewrite(StaticFilesPathRewriteMode::FilenameHash)
+                .build(),
+        );
+        static_files.add_file(StaticFile::new("css/style.css", "content 1"));
+
+        let url = static_files.path_for("css/style.css").unwrap().to_owned();
+        let hash = StaticFiles::file_hash(&StaticFile::new("css/style.css", "content 1"));
+        assert_eq!(url, format!("/static/css/style.{hash}.css"));
+
+        let hashed_path = url.strip_prefix("/static/").unwrap();
+        assert!(static_files.get_file(hashed_path).is_some());
+        assert!(static_files.get_file("css/style.css").is_some());
+
+        // the old hashed path is no longer served after the file is replaced
+        static_files.add_file(StaticFile::new("css/style.css", "content 2"));
+        assert!(static_files.get_file(hashed_path).is_none());
+    }
+
+    #[test]
+    fn hashed_file_paths() {
+        assert_eq!(hashed_file_path("style.css", "abc"), "style.abc.css");
+        assert_eq!(
+            hashed_file_path("js/app.min.js", "abc"),
+            "js/app.min.abc.js"
+        );
+        assert_eq!(hashed_file_path("LICENSE", "abc"), "LICENSE.abc");
+        assert_eq!(hashed_file_path(".htaccess", "abc"), ".htaccess.abc");
+        assert_eq!(hashed_file_path("v1.2/README", "abc"), "v1.2/README.abc");
+    }
+
     #[test]
     fn static_files_url_rewriting_with_different_prefix() {
         let mut static_files = StaticFiles::new(&StaticFilesConfig {
             url: "/assets/".to_string(),
             rewrite: StaticFilesPathRewriteMode::QueryParam,
             cache_timeout: None,
+            dirs: Vec::new(),
+            #[cfg(feature = "storage")]
+            storage: None,
         });
 
         let file = StaticFile::new("images/logo.png", "fake image data");
@@ -715,6 +1322,9 @@ mod tests {
             url: "/static/".to_string(),
             rewrite: StaticFilesPathRewriteMode::QueryParam,
             cache_timeout: None,
+            dirs: Vec::new(),
+            #[cfg(feature = "storage")]
+            storage: None,
         });
 
         let file = StaticFile::new("test.txt", "test content");
@@ -738,6 +1348,9 @@ mod tests {
             url: "/static/".to_string(),
             rewrite: StaticFilesPathRewriteMode::QueryParam,
             cache_timeout: None,
+            dirs: Vec::new(),
+            #[cfg(feature = "storage")]
+            storage: None,
         });
 
         let file1 = StaticFile::new("test.txt", "content 1");
@@ -0,0 +1,1116 @@
+//! File storage for Cot.
+//!
+//! This module provides a [`Storage`] trait abstracting over the places the
+//! files (typically the ones uploaded by the users) can be stored in, along
+//! with the [`FileStorage`] handle used by the project to access the storage
+//! configured in [`StorageConfig`]. The available backends are in the
+//! [`filesystem`], [`memory`], and [`s3`] submodules.
+//!
+//! The files are identified by their names, which are relative paths using
+//! `/` as the separator, such as `avatars/alice.png`. The names can't be
+//! absolute, and can't contain `.` or `..` segments.
+//!
+//! Uploaded files can be saved to the storage directly by using the
+//! [`StoredFile`] type in a form, which streams the file contents to the
+//! storage instead of keeping them in memory. [`StoredFile`] can also be used
+//! as a model field, which stores a reference to the file in the database.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::request::{Request, RequestExt};
+//! use cot::response::Response;
+//!
+//! async fn upload_report(request: Request) -> cot::Result<Response> {
+//!     let storage = request.context().storage();
+//!     let name = storage
+//!         .save_bytes("reports/monthly.csv", "month,total\n2025-01,42\n")
+//!         .await?;
+//!     println!("The report is available at {}", storage.url(&name));
+//!     # unimplemented!()
+//! }
+//! ```
+
+pub mod filesystem;
+pub mod memory;
+#[cfg(feature = "s3")]
+pub mod s3;
+
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use cot_core::error::impl_into_cot_error;
+use derive_more::with_trait::Debug;
+use futures_core::Stream;
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::{StorageBackendTypeConfig, StorageConfig};
+#[cfg(feature = "mysql")]
+use crate::db::impl_mysql::MySqlValueRef;
+#[cfg(feature = "postgres")]
+use crate::db::impl_postgres::PostgresValueRef;
+#[cfg(feature = "sqlite")]
+use crate::db::impl_sqlite::SqliteValueRef;
+#[cfg(feature = "db")]
+use crate::db::{
+    ColumnType, DatabaseError, DatabaseField, DbValue, FromDbValue, SqlxValueRef, ToDbValue,
+};
+use crate::storage::filesystem::Filesystem;
+#[cfg(feature = "s3")]
+use crate::storage::s3::S3;
+
+const ERROR_PREFIX: &str = "storage error:";
+/// The URL prefix of the stored files, used by the filesystem and in-memory
+/// backends if not configured otherwise.
+pub const DEFAULT_BASE_URL: &str = "/media/";
+const RANDOM_SUFFIX_LENGTH: usize = 7;
+const MAX_NAME_ATTEMPTS: usize = 100;
+
+/// Errors that can occur while accessing a file storage.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum StorageError {
+    /// The file with the given name does not exist.
+    #[error("{ERROR_PREFIX} file not found: `{0}`")]
+    NotFound(String),
+    /// The given file name is not valid.
+    #[error("{ERROR_PREFIX} invalid file name: `{0}`")]
+    InvalidName(String),
+    /// Could not find a name that is not already taken by another file.
+    #[error("{ERROR_PREFIX} could not find an available name for `{0}`")]
+    NameUnavailable(String),
+    /// Failed to read the contents of the file being saved.
+    #[error("{ERROR_PREFIX} failed to read the file contents: {0}")]
+    Read(std::io::Error),
+    /// The underlying storage backend returned an error.
+    #[error("{ERROR_PREFIX} backend error: {0}")]
+    Backend(Box<dyn StdError + Send + Sync + 'static>),
+}
+
+impl_into_cot_error!(StorageError);
+
+/// A convenience alias for results returned by storage operations.
+pub type StorageResult<T> = Result<T, StorageError>;
+
+/// A stream of chunks of a file's contents, used to save files to a storage
+/// without keeping them in memory.
+pub type ByteStream<'a> = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + 'a>>;
+
+/// A generic asynchronous file storage interface.
+///
+/// The [`Storage`] trait abstracts over different file storage backends. The
+/// names passed to its methods are validated by [`FileStorage`] before the
+/// backend is called.
+pub trait Storage: Send + Sync + 'static {
+    /// Saves a file with the given name, overwriting it if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the contents could not be read or
+    /// the file could not be written.
+    fn save(
+        &self,
+        name: &str,
+        content: ByteStream<'_>,
+    ) -> impl Future<Output = StorageResult<()>> + Send;
+
+    /// Returns the contents of the file with the given name.
+    ///
+    /// # Errors
+    ///
+    /// This method should return [`StorageError::NotFound`] if the file does
+    /// not exist, or another error if it could not be read.
+    fn open(&self, name: &str) -> impl Future<Output = StorageResult<Bytes>> + Send;
+
+    /// Removes the file with the given name. Succeeds even if the file does
+    /// not exist.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the file could not be removed.
+    fn delete(&self, name: &str) -> impl Future<Output = StorageResult<()>> + Send;
+
+    /// Returns whether a file with the given name exists.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the storage could not be accessed.
+    fn exists(&self, name: &str) -> impl Future<Output = StorageResult<bool>> + Send;
+
+    /// Returns the URL the file with the given name is available at.
+    fn url(&self, name: &str) -> String;
+
+    /// Saves a file like [`Self::save`], storing the given metadata along
+    /// with it, so that it's used when the storage serves the file (e.g. from
+    /// an S3 bucket behind a CDN).
+    ///
+    /// The default implementation ignores the options, which is appropriate
+    /// for the backends that don't serve the files themselves, such as the
+    /// filesystem one.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the contents could not be read or
+    /// the file could not be written.
+    fn save_with_options(
+        &self,
+        name: &str,
+        content: ByteStream<'_>,
+        _options: &SaveOptions,
+    ) -> impl Future<Output = StorageResult<()>> + Send {
+        self.save(name, content)
+    }
+
+    /// Returns the names of all the files in the storage, in no particular
+    /// order.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the storage could not be accessed.
+    fn list(&self) -> impl Future<Output = StorageResult<Vec<String>>> + Send;
+}
+
+/// The metadata stored along with a file by [`Storage::save_with_options`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::storage::SaveOptions;
+///
+/// let options = SaveOptions::new()
+///     .cache_control("public, max-age=31536000, immutable")
+///     .content_type("text/css");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SaveOptions {
+    /// The value of the `Cache-Control` header the file is served with.
+    pub cache_control: Option<String>,
+    /// The value of the `Content-Type` header the file is served with.
+    pub content_type: Option<String>,
+}
+
+impl SaveOptions {
+    /// Creates new, empty save options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::SaveOptions;
+    ///
+    /// let options = SaveOptions::new();
+    /// assert_eq!(options.cache_control, None);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of the `Cache-Control` header the file is served with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::SaveOptions;
+    ///
+    /// let options = SaveOptions::new().cache_control("no-cache");
+    /// assert_eq!(options.cache_control.as_deref(), Some("no-cache"));
+    /// ```
+    #[must_use]
+    pub fn cache_control<T: Into<String>>(mut self, cache_control: T) -> Self {
+        self.cache_control = Some(cache_control.into());
+        self
+    }
+
+    /// Sets the value of the `Content-Type` header the file is served with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::SaveOptions;
+    ///
+    /// let options = SaveOptions::new().content_type("text/css");
+    /// assert_eq!(options.content_type.as_deref(), Some("text/css"));
+    /// ```
+    #[must_use]
+    pub fn content_type<T: Into<String>>(mut self, content_type: T) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+}
+
+pub(crate) trait BoxedStorage: Send + Sync + 'static {
+    fn save<'a>(
+        &'a self,
+        name: &'a str,
+        content: ByteStream<'a>,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<()>> + Send + 'a>>;
+
+    fn open<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<Bytes>> + Send + 'a>>;
+
+    fn delete<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<()>> + Send + 'a>>;
+
+    fn exists<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<bool>> + Send + 'a>>;
+
+    fn url(&self, name: &str) -> String;
+
+    fn save_with_options<'a>(
+        &'a self,
+        name: &'a str,
+        content: ByteStream<'a>,
+        options: &'a SaveOptions,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<()>> + Send + 'a>>;
+
+    fn list(&self) -> Pin<Box<dyn Future<Output = StorageResult<Vec<String>>> + Send + '_>>;
+}
+
+impl<T: Storage> BoxedStorage for T {
+    fn save<'a>(
+        &'a self,
+        name: &'a str,
+        content: ByteStream<'a>,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<()>> + Send + 'a>> {
+        Box::pin(async move { T::save(self, name, content).await })
+    }
+
+    fn open<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<Bytes>> + Send + 'a>> {
+        Box::pin(async move { T::open(self, name).await })
+    }
+
+    fn delete<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<()>> + Send + 'a>> {
+        Box::pin(async move { T::delete(self, name).await })
+    }
+
+    fn exists<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<bool>> + Send + 'a>> {
+        Box::pin(async move { T::exists(self, name).await })
+    }
+
+    fn url(&self, name: &str) -> String {
+        T::url(self, name)
+    }
+
+    fn save_with_options<'a>(
+        &'a self,
+        name: &'a str,
+        content: ByteStream<'a>,
+        options: &'a SaveOptions,
+    ) -> Pin<Box<dyn Future<Output = StorageResult<()>> + Send + 'a>> {
+        Box::pin(async move { T::save_with_options(self, name, content, options).await })
+    }
+
+    fn list(&self) -> Pin<Box<dyn Future<Output = StorageResult<Vec<String>>> + Send + '_>> {
+        Box::pin(async move { T::list(self).await })
+    }
+}
+
+#[derive(Debug)]
+struct FileStorageImpl {
+    #[debug("..")]
+    backend: Box<dyn BoxedStorage>,
+}
+
+/// A high-level interface to a file storage.
+///
+/// This struct wraps a [`Storage`] implementation, validates the file names
+/// passed to it, and makes sure saving a file never overwrites another one.
+/// The storage configured for the project is available through
+/// [`ProjectContext::storage`](crate::project::ProjectContext::storage), or
+/// by using this type as an extractor.
+///
+/// # Examples
+///
+/// ```
+/// use cot::storage::FileStorage;
+/// use cot::storage::filesystem::Filesystem;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// # let dir = tempfile::tempdir().unwrap();
+/// let storage = FileStorage::new(Filesystem::new(dir.path(), "/media/"));
+/// let name = storage.save_bytes("hello.txt", "Hello, world!").await?;
+///
+/// assert_eq!(name, "hello.txt");
+/// assert_eq!(storage.open(&name).await?, "Hello, world!");
+/// assert_eq!(storage.url(&name), "/media/hello.txt");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    inner: Arc<FileStorageImpl>,
+}
+
+impl FileStorage {
+    /// Creates a new file storage using the given backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::FileStorage;
+    /// use cot::storage::filesystem::Filesystem;
+    ///
+    /// let storage = FileStorage::new(Filesystem::new("media", "/media/"));
+    /// ```
+    pub fn new(backend: impl Storage) -> Self {
+        let backend: Box<dyn BoxedStorage> = Box::new(backend);
+        Self {
+            inner: Arc::new(FileStorageImpl { backend }),
+        }
+    }
+
+    /// Constructs a [`FileStorage`] from the provided [`StorageConfig`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage backend could not be created from the
+    /// config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::StorageConfig;
+    /// use cot::storage::FileStorage;
+    ///
+    /// let storage = FileStorage::from_config(&StorageConfig::default())?;
+    /// # Ok::<(), cot::storage::StorageError>(())
+    /// ```
+    pub fn from_config(config: &StorageConfig) -> StorageResult<Self> {
+        let base_url = config.base_url.clone();
+
+        let this = match &config.backend.backend_type {
+            StorageBackendTypeConfig::Filesystem { root } => Self::new(Filesystem::new(
+                root,
+                base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_owned()),
+            )),
+            #[cfg(feature = "s3")]
+            StorageBackendTypeConfig::S3 {
+                bucket,
+                region,
+                endpoint,
+                access_key_id,
+                secret_access_key,
+                path_style,
+            } => {
+                let mut s3 = S3::builder()
+                    .bucket(bucket)
+                    .region(region)
+                    .access_key_id(access_key_id)
+                    .secret_access_key(secret_access_key.clone())
+                    .path_style(*path_style);
+                if let Some(endpoint) = endpoint {
+                    s3 = s3.endpoint(endpoint);
+                }
+                if let Some(base_url) = base_url {
+                    s3 = s3.base_url(base_url);
+                }
+                Self::new(s3.build()?)
+            }
+        };
+        Ok(this)
+    }
+
+    /// Saves a file, streaming its contents to the storage.
+    ///
+    /// If a file with the given name already exists, a random suffix is
+    /// appended to the file stem (e.g. `avatar_x8Gk2Qa.png`), so that the
+    /// existing file is not overwritten. The name the file was saved under is
+    /// returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not valid, or the file could not be
+    /// saved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use cot::storage::FileStorage;
+    /// use cot::storage::filesystem::Filesystem;
+    /// use futures_util::stream;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// # let dir = tempfile::tempdir().unwrap();
+    /// let storage = FileStorage::new(Filesystem::new(dir.path(), "/media/"));
+    /// let chunks = stream::iter([Ok(Bytes::from("Hello, ")), Ok(Bytes::from("world!"))]);
+    /// let name = storage.save("hello.txt", Box::pin(chunks)).await?;
+    ///
+    /// assert_eq!(storage.open(&name).await?, "Hello, world!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn save(&self, name: &str, content: ByteStream<'_>) -> StorageResult<String> {
+        validate_name(name)?;
+        let name = self.available_name(name).await?;
+        self.inner.backend.save(&name, content).await?;
+        Ok(name)
+    }
+
+    /// Saves a file with the given contents.
+    ///
+    /// This is a convenience wrapper around [`Self::save`] for contents that
+    /// are already in memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not valid, or the file could not be
+    /// saved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::FileStorage;
+    /// use cot::storage::filesystem::Filesystem;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// # let dir = tempfile::tempdir().unwrap();
+    /// let storage = FileStorage::new(Filesystem::new(dir.path(), "/media/"));
+    /// let first = storage.save_bytes("notes.txt", "first").await?;
+    /// let second = storage.save_bytes("notes.txt", "second").await?;
+    ///
+    /// assert_ne!(first, second);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn save_bytes<T: Into<Bytes>>(
+        &self,
+        name: &str,
+        content: T,
+    ) -> StorageResult<String> {
+        let content = content.into();
+        self.save(
+            name,
+            Box::pin(futures_util::stream::once(async move { Ok(content) })),
+        )
+        .await
+    }
+
+    /// Returns the contents of the file with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError::NotFound`] if the file does not exist, or
+    /// another error if the name is not valid or the file could not be read.
+    pub async fn open(&self, name: &str) -> StorageResult<Bytes> {
+        validate_name(name)?;
+        self.inner.backend.open(name).await
+    }
+
+    /// Removes the file with the given name. Succeeds even if the file does
+    /// not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not valid or the file could not be
+    /// removed.
+    pub async fn delete(&self, name: &str) -> StorageResult<()> {
+        validate_name(name)?;
+        self.inner.backend.delete(name).await
+    }
+
+    /// Returns whether a file with the given name exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not valid or the storage could not be
+    /// accessed.
+    pub async fn exists(&self, name: &str) -> StorageResult<bool> {
+        validate_name(name)?;
+        self.inner.backend.exists(name).await
+    }
+
+    /// Returns the URL the file with the given name is available at.
+    #[must_use]
+    pub fn url(&self, name: &str) -> String {
+        self.inner.backend.url(name)
+    }
+
+    /// Saves a file under exactly the given name, overwriting the existing
+    /// file, if any, and storing the given metadata along with it.
+    ///
+    /// Unlike [`Self::save`], this never changes the name of the file, which
+    /// makes it suitable for the files whose names are known in advance,
+    /// such as the static files uploaded by the `collect-static` command.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not valid, or the file could not be
+    /// saved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::memory::Memory;
+    /// use cot::storage::{FileStorage, SaveOptions};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let storage = FileStorage::new(Memory::new());
+    /// let options = SaveOptions::new().content_type("text/plain");
+    /// storage
+    ///     .save_overwriting("notes.txt", Box::pin(futures_util::stream::empty()), &options)
+    ///     .await?;
+    /// storage
+    ///     .save_overwriting("notes.txt", Box::pin(futures_util::stream::empty()), &options)
+    ///     .await?;
+    ///
+    /// assert_eq!(storage.list().await?, vec!["notes.txt"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn save_overwriting(
+        &self,
+        name: &str,
+        content: ByteStream<'_>,
+        options: &SaveOptions,
+    ) -> StorageResult<()> {
+        validate_name(name)?;
+        self.inner
+            .backend
+            .save_with_options(name, content, options)
+            .await
+    }
+
+    /// Returns the names of all the files in the storage, sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage could not be accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::FileStorage;
+    /// use cot::storage::memory::Memory;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let storage = FileStorage::new(Memory::new());
+    /// storage.save_bytes("b.txt", "b").await?;
+    /// storage.save_bytes("a/c.txt", "c").await?;
+    ///
+    /// assert_eq!(storage.list().await?, vec!["a/c.txt", "b.txt"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list(&self) -> StorageResult<Vec<String>> {
+        let mut names = self.inner.backend.list().await?;
+        names.sort_unstable();
+        Ok(names)
+    }
+
+    async fn available_name(&self, name: &str) -> StorageResult<String> {
+        if !self.inner.backend.exists(name).await? {
+            return Ok(name.to_owned());
+        }
+
+        let (dir, file_name) = name
+            .rsplit_once('/')
+            .map_or(("", name), |(dir, file_name)| (dir, file_name));
+        let (stem, extension) = match file_name.find('.') {
+            Some(index) if index > 0 => file_name.split_at(index),
+            _ => (file_name, ""),
+        };
+
+        for _ in 0..MAX_NAME_ATTEMPTS {
+            let suffix: String = rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(RANDOM_SUFFIX_LENGTH)
+                .map(char::from)
+                .collect();
+            let candidate_file_name = format!("{stem}_{suffix}{extension}");
+            let candidate = if dir.is_empty() {
+                candidate_file_name
+            } else {
+                format!("{dir}/{candidate_file_name}")
+            };
+
+            if !self.inner.backend.exists(&candidate).await? {
+                return Ok(candidate);
+            }
+        }
+
+        Err(StorageError::NameUnavailable(name.to_owned()))
+    }
+}
+
+/// A reference to a file saved in a [`FileStorage`], along with the metadata
+/// of the uploaded file.
+///
+/// This is the value of the [`StorageFileField`](crate::form::fields::StorageFileField)
+/// form field, and it can be stored in the database as a model field. Only
+/// the reference is stored (as a JSON-encoded text column); the file contents
+/// are kept in the storage, and the URL is generated by the storage when
+/// needed, so changing the storage's base URL doesn't invalidate the stored
+/// references.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, model};
+/// use cot::storage::StoredFile;
+///
+/// #[model]
+/// struct Document {
+///     #[model(primary_key)]
+///     id: Auto<i64>,
+///     title: String,
+///     file: StoredFile,
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StoredFile {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filename: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+}
+
+impl StoredFile {
+    /// Creates a reference to the file saved under the given name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::{FileStorage, StoredFile};
+    /// use cot::storage::memory::Memory;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let storage = FileStorage::new(Memory::new());
+    /// let name = storage.save_bytes("report.csv", "month,total\n").await?;
+    /// let file = StoredFile::new(name);
+    ///
+    /// assert_eq!(file.url(&storage), "/media/report.csv");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        Self {
+            name: name.into(),
+            filename: None,
+            content_type: None,
+            size: None,
+        }
+    }
+
+    pub(crate) fn uploaded(
+        name: String,
+        filename: Option<String>,
+        content_type: Option<String>,
+        size: u64,
+    ) -> Self {
+        Self {
+            name,
+            filename,
+            content_type,
+            size: Some(size),
+        }
+    }
+
+    /// Get the name the file has been saved under in the storage.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the original filename of the uploaded file, as sent by the
+    /// browser.
+    #[must_use]
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Get the content (MIME) type of the uploaded file, as sent by the
+    /// browser.
+    #[must_use]
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Get the size of the file in bytes, if known.
+    #[must_use]
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// Returns the URL the file is available at in the given storage.
+    #[must_use]
+    pub fn url(&self, storage: &FileStorage) -> String {
+        storage.url(&self.name)
+    }
+
+    /// Reads the contents of the file from the given storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file does not exist or could not be read.
+    pub async fn open(&self, storage: &FileStorage) -> StorageResult<Bytes> {
+        storage.open(&self.name).await
+    }
+
+    /// Deletes the file from the given storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be deleted.
+    pub async fn delete(&self, storage: &FileStorage) -> StorageResult<()> {
+        storage.delete(&self.name).await
+    }
+}
+
+impl Display for StoredFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.filename.as_deref().unwrap_or(&self.name))
+    }
+}
+
+/// A reference to an image saved in a [`FileStorage`].
+///
+/// This is the value of the [`ImageField`](crate::form::fields::ImageField)
+/// form field, which checks that the uploaded file is an image. Apart from
+/// that, it behaves the same as [`StoredFile`], and it's stored in the
+/// database the same way.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StoredImage(StoredFile);
+
+impl StoredImage {
+    pub(crate) fn new(file: StoredFile) -> Self {
+        Self(file)
+    }
+
+    /// Get the name the image has been saved under in the storage.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    /// Get the original filename of the uploaded image, as sent by the
+    /// browser.
+    #[must_use]
+    pub fn filename(&self) -> Option<&str> {
+        self.0.filename()
+    }
+
+    /// Get the content (MIME) type of the uploaded image, as sent by the
+    /// browser.
+    #[must_use]
+    pub fn content_type(&self) -> Option<&str> {
+        self.0.content_type()
+    }
+
+    /// Get the size of the image in bytes, if known.
+    #[must_use]
+    pub fn size(&self) -> Option<u64> {
+        self.0.size()
+    }
+
+    /// Returns the URL the image is available at in the given storage.
+    #[must_use]
+    pub fn url(&self, storage: &FileStorage) -> String {
+        self.0.url(storage)
+    }
+
+    /// Returns the reference to the image as a [`StoredFile`].
+    #[must_use]
+    pub fn as_file(&self) -> &StoredFile {
+        &self.0
+    }
+
+    /// Converts the image into a [`StoredFile`].
+    #[must_use]
+    pub fn into_file(self) -> StoredFile {
+        self.0
+    }
+}
+
+impl Display for StoredImage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// Implements the database traits for a type stored as a JSON-encoded text
+/// column, along with its `Option`.
+#[cfg(feature = "db")]
+macro_rules! impl_json_db_field {
+    ($ty:ty) => {
+        impl ToDbValue for $ty {
+            fn to_db_value(&self) -> DbValue {
+                to_json(self).into()
+            }
+        }
+
+        impl ToDbValue for Option<$ty> {
+            fn to_db_value(&self) -> DbValue {
+                self.as_ref().map(to_json).into()
+            }
+        }
+
+        impl FromDbValue for $ty {
+            #[cfg(feature = "sqlite")]
+            fn from_sqlite(value: SqliteValueRef<'_>) -> crate::db::Result<Self> {
+                from_json(&value.get::<String>()?)
+            }
+
+            #[cfg(feature = "postgres")]
+            fn from_postgres(value: PostgresValueRef<'_>) -> crate::db::Result<Self> {
+                from_json(&value.get::<String>()?)
+            }
+
+            #[cfg(feature = "mysql")]
+            fn from_mysql(value: MySqlValueRef<'_>) -> crate::db::Result<Self> {
+                from_json(&value.get::<String>()?)
+            }
+        }
+
+        impl FromDbValue for Option<$ty> {
+            #[cfg(feature = "sqlite")]
+            fn from_sqlite(value: SqliteValueRef<'_>) -> crate::db::Result<Self> {
+                value
+                    .get::<Option<String>>()?
+                    .as_deref()
+                    .map(from_json)
+                    .transpose()
+            }
+
+            #[cfg(feature = "postgres")]
+            fn from_postgres(value: PostgresValueRef<'_>) -> crate::db::Result<Self> {
+                value
+                    .get::<Option<String>>()?
+                    .as_deref()
+                    .map(from_json)
+                    .transpose()
+            }
+
+            #[cfg(feature = "mysql")]
+            fn from_mysql(value: MySqlValueRef<'_>) -> crate::db::Result<Self> {
+                value
+                    .get::<Option<String>>()?
+                    .as_deref()
+                    .map(from_json)
+                    .transpose()
+            }
+        }
+
+        impl DatabaseField for $ty {
+            const TYPE: ColumnType = ColumnType::Text;
+        }
+    };
+}
+
+#[cfg(feature = "db")]
+impl_json_db_field!(StoredFile);
+#[cfg(feature = "db")]
+impl_json_db_field!(StoredImage);
+
+#[cfg(feature = "db")]
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("serializing a stored file reference never fails")
+}
+
+#[cfg(all(
+    feature = "db",
+    any(feature = "sqlite", feature = "postgres", feature = "mysql")
+))]
+fn from_json<T: serde::de::DeserializeOwned>(value: &str) -> crate::db::Result<T> {
+    serde_json::from_str(value).map_err(DatabaseError::value_decode)
+}
+
+/// Checks whether the given string is a valid file name: a non-empty relative
+/// path using `/` as the separator, without empty, `.`, or `..` segments.
+pub(crate) fn validate_name(name: &str) -> StorageResult<()> {
+    let is_valid = !name.is_empty()
+        && !name.contains(['\\', '\0'])
+        && name
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidName(name.to_owned()))
+    }
+}
+
+/// Joins the base URL with the file name, percent-encoding each segment of the
+/// name.
+pub(crate) fn join_url(base_url: &str, name: &str) -> String {
+    let mut url = url::Url::parse("http://localhost/").expect("the URL is valid");
+    url.path_segments_mut()
+        .expect("the URL can be a base")
+        .pop_if_empty()
+        .extend(name.split('/'));
+    let encoded_name = &url.path()[1..];
+
+    if base_url.ends_with('/') {
+        format!("{base_url}{encoded_name}")
+    } else {
+        format!("{base_url}/{encoded_name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage() -> (tempfile::TempDir, FileStorage) {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FileStorage::new(Filesystem::new(dir.path(), "/media/"));
+        (dir, storage)
+    }
+
+    #[test]
+    fn validate_name_valid() {
+        assert!(validate_name("file.txt").is_ok());
+        assert!(validate_name("avatars/2025/alice.png").is_ok());
+        assert!(validate_name(".hidden").is_ok());
+    }
+
+    #[test]
+    fn validate_name_invalid() {
+        for name in [
+            "",
+            "/etc/passwd",
+            "../secret",
+            "a/../../b",
+            "a/./b",
+            "a//b",
+            "dir/",
+            "a\\b",
+            "a\0b",
+        ] {
+            assert!(
+                matches!(validate_name(name), Err(StorageError::InvalidName(_))),
+                "`{name}` should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn join_url_encodes_segments() {
+        assert_eq!(join_url("/media/", "a b/c?.txt"), "/media/a%20b/c%3F.txt");
+        assert_eq!(
+            join_url("https://cdn.example.com/media", "x.png"),
+            "https://cdn.example.com/media/x.png"
+        );
+    }
+
+    #[cot::test]
+    async fn save_open_delete() {
+        let (_dir, storage) = storage();
+
+        let name = storage.save_bytes("docs/a.txt", "content").await.unwrap();
+        assert_eq!(name, "docs/a.txt");
+        assert!(storage.exists(&name).await.unwrap());
+        assert_eq!(storage.open(&name).await.unwrap(), "content");
+
+        storage.delete(&name).await.unwrap();
+        assert!(!storage.exists(&name).await.unwrap());
+        assert!(matches!(
+            storage.open(&name).await,
+            Err(StorageError::NotFound(_))
+        ));
+        storage.delete(&name).await.unwrap();
+    }
+
+    #[cot::test]
+    async fn save_does_not_overwrite() {
+        let (_dir, storage) = storage();
+
+        let first = storage.save_bytes("docs/a.tar.gz", "1").await.unwrap();
+        let second = storage.save_bytes("docs/a.tar.gz", "2").await.unwrap();
+
+        assert_eq!(first, "docs/a.tar.gz");
+        assert_ne!(first, second);
+        assert!(second.starts_with("docs/a_"));
+        assert!(second.ends_with(".tar.gz"));
+        assert_eq!(storage.open(&first).await.unwrap(), "1");
+        assert_eq!(storage.open(&second).await.unwrap(), "2");
+    }
+
+    #[cot::test]
+    async fn invalid_name_rejected() {
+        let (_dir, storage) = storage();
+
+        assert!(matches!(
+            storage.save_bytes("../escape.txt", "x").await,
+            Err(StorageError::InvalidName(_))
+        ));
+        assert!(matches!(
+            storage.open("/etc/passwd").await,
+            Err(StorageError::InvalidName(_))
+        ));
+    }
+
+    #[test]
+    fn from_config_filesystem() {
+        let storage = FileStorage::from_config(&StorageConfig::default()).unwrap();
+
+        assert_eq!(storage.url("a.png"), "/media/a.png");
+    }
+
+    #[test]
+    fn stored_file_serialize() {
+        let file = StoredFile::uploaded(
+            "avatars/me.png".to_owned(),
+            Some("me.png".to_owned()),
+            Some("image/png".to_owned()),
+            1024,
+        );
+
+        let json = serde_json::to_string(&file).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"avatars/me.png","filename":"me.png","content_type":"image/png","size":1024}"#
+        );
+        assert_eq!(serde_json::from_str::<StoredFile>(&json).unwrap(), file);
+        assert_eq!(
+            serde_json::from_str::<StoredFile>(r#"{"name":"report.pdf"}"#).unwrap(),
+            StoredFile::new("report.pdf")
+        );
+    }
+
+    #[cot::test]
+    async fn stored_file_storage_access() {
+        let (_dir, storage) = storage();
+        let file = StoredFile::new(storage.save_bytes("a b.txt", "abc").await.unwrap());
+
+        assert_eq!(file.url(&storage), "/media/a%20b.txt");
+        assert_eq!(file.open(&storage).await.unwrap(), "abc");
+        file.delete(&storage).await.unwrap();
+        assert!(!storage.exists(file.name()).await.unwrap());
+    }
+}
@@ -0,0 +1,260 @@
+//! Filesystem storage backend.
+//!
+//! This backend stores the files in a directory on the local filesystem. It's
+//! the default backend, suitable for development and for single-server
+//! deployments.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::storage::FileStorage;
+//! use cot::storage::filesystem::Filesystem;
+//!
+//! let storage = FileStorage::new(Filesystem::new("media", "/media/"));
+//! ```
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use futures_util::StreamExt;
+use tokio::io::AsyncWriteExt;
+
+use crate::storage::{ByteStream, Storage, StorageError, StorageResult, join_url, validate_name};
+
+/// A storage backend keeping the files in a directory on the local
+/// filesystem.
+///
+/// The files are written to a temporary file first and moved to the
+/// destination once all their contents have been received, so a partially
+/// saved file is never visible under its final name.
+///
+/// Note that Cot doesn't serve the stored files; the directory should be
+/// exposed under the base URL by the web server in front of the application.
+#[derive(Debug, Clone)]
+pub struct Filesystem {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl Filesystem {
+    /// Creates a new filesystem backend storing the files in the given
+    /// directory, which are available under the given base URL.
+    ///
+    /// The directory is created when the first file is saved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::filesystem::Filesystem;
+    ///
+    /// let backend = Filesystem::new("/var/lib/myapp/media", "https://media.example.com/");
+    /// ```
+    #[must_use]
+    pub fn new<P: Into<PathBuf>, U: Into<String>>(root: P, base_url: U) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.into(),
+        }
+    }
+
+    /// Returns the directory the files are stored in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use cot::storage::filesystem::Filesystem;
+    ///
+    /// let backend = Filesystem::new("media", "/media/");
+    /// assert_eq!(backend.root(), Path::new("media"));
+    /// ```
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, name: &str) -> StorageResult<PathBuf> {
+        validate_name(name)?;
+        Ok(name
+            .split('/')
+            .fold(self.root.clone(), |path, segment| path.join(segment)))
+    }
+
+    async fn write(path: &Path, mut content: ByteStream<'_>) -> StorageResult<()> {
+        let mut file = tokio::fs::File::create(path).await.map_err(backend_error)?;
+        while let Some(chunk) = content.next().await {
+            let chunk = chunk.map_err(StorageError::Read)?;
+            file.write_all(&chunk).await.map_err(backend_error)?;
+        }
+        file.flush().await.map_err(backend_error)?;
+        file.sync_all().await.map_err(backend_error)?;
+        Ok(())
+    }
+}
+
+impl Storage for Filesystem {
+    async fn save(&self, name: &str, content: ByteStream<'_>) -> StorageResult<()> {
+        let path = self.path(name)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(backend_error)?;
+        }
+
+        let mut temp_file_name = path
+            .file_name()
+            .expect("validated names always have a file name")
+            .to_os_string();
+        temp_file_name.push(".part");
+        let temp_path = path.with_file_name(temp_file_name);
+
+        if let Err(error) = Self::write(&temp_path, content).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(error);
+        }
+        tokio::fs::rename(&temp_path, &path)
+            .await
+            .map_err(backend_error)
+    }
+
+    async fn open(&self, name: &str) -> StorageResult<Bytes> {
+        match tokio::fs::read(self.path(name)?).await {
+            Ok(content) => Ok(Bytes::from(content)),
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                Err(StorageError::NotFound(name.to_owned()))
+            }
+            Err(error) => Err(backend_error(error)),
+        }
+    }
+
+    async fn delete(&self, name: &str) -> StorageResult<()> {
+        match tokio::fs::remove_file(self.path(name)?).await {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(backend_error(error)),
+        }
+    }
+
+    async fn exists(&self, name: &str) -> StorageResult<bool> {
+        tokio::fs::try_exists(self.path(name)?)
+            .await
+            .map_err(backend_error)
+    }
+
+    fn url(&self, name: &str) -> String {
+        join_url(&self.base_url, name)
+    }
+
+    async fn list(&self) -> StorageResult<Vec<String>> {
+        let mut names = Vec::new();
+        let mut dirs = vec![(self.root.clone(), String::new())];
+        while let Some((dir, prefix)) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                // nothing has been saved yet
+                Err(error) if error.kind() == ErrorKind::NotFound && prefix.is_empty() => {
+                    break;
+                }
+                Err(error) => return Err(backend_error(error)),
+            };
+            while let Some(entry) = entries.next_entry().await.map_err(backend_error)? {
+                let Some(file_name) = entry.file_name().to_str().map(ToOwned::to_owned) else {
+                    continue;
+                };
+                let name = format!("{prefix}{file_name}");
+                if entry.file_type().await.map_err(backend_error)?.is_dir() {
+                    dirs.push((entry.path(), format!("{name}/")));
+                } else {
+                    names.push(name);
+                }
+            }
+        }
+        Ok(names)
+    }
+}
+
+fn backend_error(error: std::io::Error) -> StorageError {
+    StorageError::Backend(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+
+    #[cot::test]
+    async fn save_creates_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Filesystem::new(dir.path(), "/media/");
+
+        backend
+            .save(
+                "a/b/c.txt",
+                Box::pin(stream::iter([Ok(Bytes::from("abc"))])),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read(dir.path().join("a").join("b").join("c.txt")).unwrap(),
+            b"abc"
+        );
+    }
+
+    #[cot::test]
+    async fn save_failed_stream_leaves_no_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Filesystem::new(dir.path(), "/media/");
+
+        let result = backend
+            .save(
+                "broken.txt",
+                Box::pin(stream::iter([
+                    Ok(Bytes::from("partial")),
+                    Err(std::io::Error::other("connection reset")),
+                ])),
+            )
+            .await;
+
+        assert!(matches!(result, Err(StorageError::Read(_))));
+        assert!(!backend.exists("broken.txt").await.unwrap());
+        assert!(!dir.path().join("broken.txt.part").exists());
+    }
+
+    #[cot::test]
+    async fn list_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = Filesystem::new(dir.path(), "/media/");
+        for name in ["a.txt", "b/c.txt", "b/d/e.txt"] {
+            backend
+                .save(name, Box::pin(stream::iter([Ok(Bytes::from("x"))])))
+                .await
+                .unwrap();
+        }
+
+        let mut names = backend.list().await.unwrap();
+        names.sort();
+
+        assert_eq!(names, vec!["a.txt", "b/c.txt", "b/d/e.txt"]);
+        assert!(
+            Filesystem::new(dir.path().join("missing"), "/media/")
+                .list()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn url() {
+        let backend = Filesystem::new("media", "https://media.example.com/files");
+
+        assert_eq!(
+            backend.url("avatars/a b.png"),
+            "https://media.example.com/files/avatars/a%20b.png"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! In-memory storage backend.
+//!
+//! This backend keeps the files in the memory of the server process, so they
+//! are lost when the server is restarted. It is intended primarily for
+//! testing.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::storage::FileStorage;
+//! use cot::storage::memory::Memory;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> cot::Result<()> {
+//! let storage = FileStorage::new(Memory::new());
+//! let name = storage.save_bytes("hello.txt", "Hello, world!").await?;
+//!
+//! assert_eq!(storage.open(&name).await?, "Hello, world!");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use bytes::{Bytes, BytesMut};
+use futures_util::StreamExt;
+
+use crate::storage::{
+    ByteStream, DEFAULT_BASE_URL, Storage, StorageError, StorageResult, join_url,
+};
+
+/// A storage backend keeping the files in memory.
+///
+/// Cloning the backend is cheap and all the clones share the same files.
+///
+/// # Examples
+///
+/// ```
+/// use cot::storage::memory::Memory;
+///
+/// let backend = Memory::new();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memory {
+    files: Arc<Mutex<HashMap<String, Bytes>>>,
+    base_url: String,
+}
+
+impl Memory {
+    /// Creates a new, empty in-memory storage backend, with the files
+    /// available under [`DEFAULT_BASE_URL`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::memory::Memory;
+    ///
+    /// let backend = Memory::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Creates a new, empty in-memory storage backend, with the files
+    /// available under the given base URL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::memory::Memory;
+    ///
+    /// let backend = Memory::with_base_url("https://media.example.com/");
+    /// ```
+    #[must_use]
+    pub fn with_base_url<T: Into<String>>(base_url: T) -> Self {
+        Self {
+            files: Arc::new(Mutex::new(HashMap::new())),
+            base_url: base_url.into(),
+        }
+    }
+
+    fn files(&self) -> std::sync::MutexGuard<'_, HashMap<String, Bytes>> {
+        self.files
+            .lock()
+            .expect("the storage mutex is never poisoned")
+    }
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Storage for Memory {
+    async fn save(&self, name: &str, mut content: ByteStream<'_>) -> StorageResult<()> {
+        let mut buffer = BytesMut::new();
+        while let Some(chunk) = content.next().await {
+            buffer.extend_from_slice(&chunk.map_err(StorageError::Read)?);
+        }
+        self.files().insert(name.to_owned(), buffer.freeze());
+        Ok(())
+    }
+
+    async fn open(&self, name: &str) -> StorageResult<Bytes> {
+        self.files()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| StorageError::NotFound(name.to_owned()))
+    }
+
+    async fn delete(&self, name: &str) -> StorageResult<()> {
+        self.files().remove(name);
+        Ok(())
+    }
+
+    async fn exists(&self, name: &str) -> StorageResult<bool> {
+        Ok(self.files().contains_key(name))
+    }
+
+    fn url(&self, name: &str) -> String {
+        join_url(&self.base_url, name)
+    }
+
+    async fn list(&self) -> StorageResult<Vec<String>> {
+        Ok(self.files().keys().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+
+    #[cot::test]
+    async fn clones_share_files() {
+        let backend = Memory::new();
+        let clone = backend.clone();
+
+        backend
+            .save(
+                "a.txt",
+                Box::pin(stream::iter([Ok(Bytes::from("a")), Ok(Bytes::from("b"))])),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(clone.open("a.txt").await.unwrap(), "ab");
+        clone.delete("a.txt").await.unwrap();
+        assert!(!backend.exists("a.txt").await.unwrap());
+    }
+}
@@ -0,0 +1,980 @@
+//! Background tasks.
+//!
+//! This module provides a way to run work outside the request-response cycle,
+//! such as sending emails or generating reports:
+//! * [`Task`] is implemented by the types describing a unit of work. The
+//!   tasks are serialized to JSON when they are enqueued, so that they can be
+//!   stored in a queue.
+//! * [`Tasks`] enqueues the tasks. It is shared by the whole project and
+//!   available as [`ProjectContext::tasks`], as
+//!   [`RequestExt::tasks`](crate::request::RequestExt::tasks), and as an
+//!   extractor.
+//! * [`TaskQueue`](queue::TaskQueue) stores the enqueued tasks. The
+//!   [`queue`] module contains an in-memory and a database-backed queue; the
+//!   one to use is chosen with [`TasksConfig`].
+//! * [`TaskWorker`] takes the tasks from the queue and runs them. The tasks it
+//!   knows about are registered in [`Project::register_background_tasks`],
+//!   and it is started automatically when the server is started.
+//! * [`schedule`] allows running the tasks periodically, at a fixed interval
+//!   or according to a cron expression.
+//!
+//! A task that fails or panics is logged and isn't retried automatically; the
+//! tasks can be delayed with [`Tasks::enqueue_in`], which can be used to
+//! retry them with a backoff.
+//!
+//! [`Project::register_background_tasks`]: crate::Project::register_background_tasks
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::request::{Request, RequestExt};
+//! use cot::response::Response;
+//! use cot::tasks::{Task, TaskRegistry};
+//! use cot::{Project, ProjectContext};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Serialize, Deserialize)]
+//! struct SendWelcomeEmail {
+//!     user_id: i64,
+//! }
+//!
+//! impl Task for SendWelcomeEmail {
+//!     const NAME: &'static str = "send_welcome_email";
+//!
+//!     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+//!         // send the email
+//!         Ok(())
+//!     }
+//! }
+//!
+//! async fn sign_up(request: Request) -> cot::Result<Response> {
+//!     // create the user
+//!     request
+//!         .tasks()
+//!         .enqueue(SendWelcomeEmail { user_id: 1 })
+//!         .await?;
+//!     # unimplemented!()
+//! }
+//!
+//! struct MyProject;
+//! impl Project for MyProject {
+//!     fn register_background_tasks(&self, tasks: &mut TaskRegistry) {
+//!         tasks.register::<SendWelcomeEmail>();
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "db")]
+pub mod db;
+pub mod queue;
+pub mod schedule;
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use cot_core::error::impl_into_cot_error;
+use derive_more::with_trait::Debug;
+use futures_util::FutureExt;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use tokio::sync::Notify;
+use tracing::{Instrument, debug_span, error};
+
+use crate::ProjectContext;
+use crate::config::{TaskQueueTypeConfig, TasksConfig};
+#[cfg(feature = "db")]
+use crate::db::Database;
+use crate::request::extractors::FromRequestHead;
+use crate::request::{RequestExt, RequestHead};
+use crate::shutdown::ShutdownSignal;
+#[cfg(feature = "db")]
+use crate::tasks::queue::db::DbQueue;
+use crate::tasks::queue::memory::Memory;
+use crate::tasks::queue::{BoxedTaskQueue, QueuedTask, TaskQueue, TaskQueueError};
+use crate::tasks::schedule::{Schedule, ScheduledJob};
+
+/// The default time the [`TaskWorker`] waits before checking the queue for
+/// new tasks again.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const ERROR_PREFIX: &str = "background task error:";
+
+/// Errors that can occur while enqueuing or running background tasks.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TasksError {
+    /// An error occurred in the task queue.
+    #[error("{ERROR_PREFIX} {0}")]
+    Queue(#[from] TaskQueueError),
+    /// The task could not be serialized.
+    #[error("{ERROR_PREFIX} failed to serialize the task `{name}`: {source}")]
+    Serialize {
+        /// The name of the task.
+        name: &'static str,
+        /// The serialization error.
+        source: serde_json::Error,
+    },
+    /// The task could not be deserialized.
+    #[error("{ERROR_PREFIX} failed to deserialize the task `{name}`: {source}")]
+    Deserialize {
+        /// The name of the task.
+        name: String,
+        /// The deserialization error.
+        source: serde_json::Error,
+    },
+    /// No task with the given name has been registered.
+    #[error("{ERROR_PREFIX} no task is registered with the name `{0}`")]
+    UnknownTask(String),
+    /// The database task queue is configured, but the project has no
+    /// database.
+    #[cfg(feature = "db")]
+    #[error("{ERROR_PREFIX} the database task queue requires a database to be configured")]
+    DatabaseNotConfigured,
+}
+
+impl_into_cot_error!(TasksError);
+
+/// A convenience alias for results returned by the background task
+/// operations.
+pub type TasksResult<T> = Result<T, TasksError>;
+
+/// A unit of work that can be run in the background.
+///
+/// The task is serialized to JSON when it's enqueued with [`Tasks::enqueue`],
+/// and deserialized right before it's run by the [`TaskWorker`]. To be run,
+/// the task needs to be registered in
+/// [`Project::register_background_tasks`](crate::Project::register_background_tasks).
+///
+/// # Examples
+///
+/// ```
+/// use cot::ProjectContext;
+/// use cot::tasks::Task;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// struct GenerateReport {
+///     report_id: i64,
+/// }
+///
+/// impl Task for GenerateReport {
+///     const NAME: &'static str = "generate_report";
+///
+///     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+///         // generate the report
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Task: Serialize + DeserializeOwned + Send + 'static {
+    /// The name of the task.
+    ///
+    /// The name is stored in the queue along with the task and is used to
+    /// find the code to run it, so it must be unique within the project and
+    /// it shouldn't change while there are tasks of this type in the queue.
+    const NAME: &'static str;
+
+    /// Runs the task.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the task fails. The error is
+    /// logged by the [`TaskWorker`], and the task is not retried. A task that
+    /// should be retried can enqueue itself again with
+    /// [`Tasks::enqueue_in`].
+    fn run(self, context: &ProjectContext) -> impl Future<Output = crate::Result<()>> + Send;
+}
+
+#[derive(Debug)]
+struct TasksImpl {
+    #[debug("..")]
+    queue: Box<dyn BoxedTaskQueue>,
+    notify: Notify,
+    scheduled_jobs: Mutex<Vec<ScheduledJob>>,
+}
+
+/// A handle used to enqueue background tasks.
+///
+/// Cloning the handle is cheap and all the clones share the same queue.
+///
+/// # Examples
+///
+/// ```
+/// use cot::ProjectContext;
+/// use cot::tasks::Tasks;
+/// use cot::tasks::queue::memory::Memory;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// struct GenerateReport {
+///     report_id: i64,
+/// }
+///
+/// impl cot::tasks::Task for GenerateReport {
+///     const NAME: &'static str = "generate_report";
+///
+///     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let tasks = Tasks::new(Memory::new());
+/// tasks.enqueue(GenerateReport { report_id: 1 }).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tasks {
+    inner: Arc<TasksImpl>,
+}
+
+impl Tasks {
+    /// Creates a new handle that stores the tasks in the given queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::Tasks;
+    /// use cot::tasks::queue::memory::Memory;
+    ///
+    /// let tasks = Tasks::new(Memory::new());
+    /// ```
+    #[must_use]
+    pub fn new(queue: impl TaskQueue) -> Self {
+        let queue: Box<dyn BoxedTaskQueue> = Box::new(queue);
+        Self {
+            inner: Arc::new(TasksImpl {
+                queue,
+                notify: Notify::new(),
+                scheduled_jobs: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Creates a new handle from the given configuration.
+    ///
+    /// The database is only used if the configuration specifies the database
+    /// queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TasksError::DatabaseNotConfigured`] if the database queue is
+    /// configured, but no database is given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::TasksConfig;
+    /// use cot::tasks::Tasks;
+    ///
+    /// let tasks = Tasks::from_config(&TasksConfig::default(), None)?;
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn from_config(
+        config: &TasksConfig,
+        #[cfg(feature = "db")] database: Option<Database>,
+    ) -> TasksResult<Self> {
+        let tasks = match &config.queue.queue_type {
+            TaskQueueTypeConfig::Memory => Self::new(Memory::new()),
+            #[cfg(feature = "db")]
+            TaskQueueTypeConfig::Database => {
+                let database = database.ok_or(TasksError::DatabaseNotConfigured)?;
+                Self::new(DbQueue::new(database))
+            }
+        };
+
+        Ok(tasks)
+    }
+
+    /// Adds a task to the queue, to be run in the background.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TasksError::Serialize`] if the task could not be serialized,
+    /// or [`TasksError::Queue`] if the task could not be stored in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::request::{Request, RequestExt};
+    /// use cot::response::Response;
+    /// use cot::tasks::Task;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize)]
+    /// struct GenerateReport {
+    ///     report_id: i64,
+    /// }
+    ///
+    /// impl Task for GenerateReport {
+    ///     const NAME: &'static str = "generate_report";
+    ///
+    ///     async fn run(self, context: &cot::ProjectContext) -> cot::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// async fn generate(request: Request) -> cot::Result<Response> {
+    ///     request
+    ///         .tasks()
+    ///         .enqueue(GenerateReport { report_id: 1 })
+    ///         .await?;
+    ///     # unimplemented!()
+    /// }
+    /// ```
+    pub async fn enqueue<T: Task>(&self, task: T) -> TasksResult<()> {
+        self.inner.queue.push(Self::serialize(&task)?).await?;
+        self.inner.notify.notify_one();
+
+        Ok(())
+    }
+
+    /// Adds a task to the queue, to be run in the background after the given
+    /// delay.
+    ///
+    /// The task is run by the first [`TaskWorker`] that checks the queue once
+    /// the delay has passed, so it can be run up to the worker's poll interval
+    /// later than requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TasksError::Serialize`] if the task could not be serialized,
+    /// or [`TasksError::Queue`] if the task could not be stored in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::ProjectContext;
+    /// use cot::tasks::queue::memory::Memory;
+    /// use cot::tasks::{Task, Tasks};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize)]
+    /// struct SendReminder {
+    ///     user_id: i64,
+    /// }
+    ///
+    /// impl Task for SendReminder {
+    ///     const NAME: &'static str = "send_reminder";
+    ///
+    ///     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let tasks = Tasks::new(Memory::new());
+    /// tasks
+    ///     .enqueue_in(SendReminder { user_id: 1 }, Duration::from_secs(60 * 60))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn enqueue_in<T: Task>(&self, task: T, delay: Duration) -> TasksResult<()> {
+        let run_at = TimeDelta::from_std(delay)
+            .ok()
+            .and_then(|delay| Utc::now().checked_add_signed(delay))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        self.inner
+            .queue
+            .push(Self::serialize(&task)?.with_run_at(run_at))
+            .await?;
+
+        Ok(())
+    }
+
+    fn serialize<T: Task>(task: &T) -> TasksResult<QueuedTask> {
+        let payload = serde_json::to_string(task).map_err(|source| TasksError::Serialize {
+            name: T::NAME,
+            source,
+        })?;
+
+        Ok(QueuedTask::new(T::NAME, payload))
+    }
+
+    /// Registers a task to be run periodically by the [`TaskWorker`].
+    ///
+    /// The task is cloned for each run and run directly by the worker,
+    /// without going through the queue. This is typically called in
+    /// [`App::init`](crate::App::init); the jobs scheduled after the worker
+    /// has been started are not run. See the [`schedule`] module for more
+    /// details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::ProjectContext;
+    /// use cot::tasks::schedule::Schedule;
+    /// use cot::tasks::{Task, Tasks};
+    /// use cot::tasks::queue::memory::Memory;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Clone, Serialize, Deserialize)]
+    /// struct RefreshExchangeRates;
+    ///
+    /// impl Task for RefreshExchangeRates {
+    ///     const NAME: &'static str = "refresh_exchange_rates";
+    ///
+    ///     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let tasks = Tasks::new(Memory::new());
+    /// tasks.schedule(
+    ///     Schedule::every(Duration::from_secs(10 * 60)),
+    ///     RefreshExchangeRates,
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock guarding the scheduled jobs is poisoned.
+    pub fn schedule<T: Task + Clone + Sync>(&self, schedule: Schedule, task: T) {
+        self.inner
+            .scheduled_jobs
+            .lock()
+            .expect("scheduled jobs lock poisoned")
+            .push(ScheduledJob::new(schedule, task));
+    }
+
+    fn scheduled_jobs(&self) -> Vec<ScheduledJob> {
+        self.inner
+            .scheduled_jobs
+            .lock()
+            .expect("scheduled jobs lock poisoned")
+            .clone()
+    }
+}
+
+impl FromRequestHead for Tasks {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        Ok(head.context().tasks().clone())
+    }
+}
+
+type TaskRunner =
+    fn(Arc<ProjectContext>, String) -> Pin<Box<dyn Future<Output = crate::Result<()>> + Send>>;
+
+fn run_serialized_task<T: Task>(
+    context: Arc<ProjectContext>,
+    payload: String,
+) -> Pin<Box<dyn Future<Output = crate::Result<()>> + Send>> {
+    Box::pin(async move {
+        let task: T = serde_json::from_str(&payload).map_err(|source| TasksError::Deserialize {
+            name: T::NAME.to_owned(),
+            source,
+        })?;
+        task.run(&context).await
+    })
+}
+
+/// The registry of the tasks the [`TaskWorker`] can run.
+///
+/// The tasks are registered in
+/// [`Project::register_background_tasks`](crate::Project::register_background_tasks).
+///
+/// # Examples
+///
+/// ```
+/// use cot::ProjectContext;
+/// use cot::tasks::{Task, TaskRegistry};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// struct GenerateReport {
+///     report_id: i64,
+/// }
+///
+/// impl Task for GenerateReport {
+///     const NAME: &'static str = "generate_report";
+///
+///     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// let mut registry = TaskRegistry::new();
+/// registry.register::<GenerateReport>();
+/// assert!(registry.contains("generate_report"));
+/// ```
+#[derive(Debug, Default)]
+pub struct TaskRegistry {
+    #[debug("..")]
+    runners: HashMap<&'static str, (TypeId, TaskRunner)>,
+}
+
+impl TaskRegistry {
+    /// Creates a new, empty task registry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::TaskRegistry;
+    ///
+    /// let registry = TaskRegistry::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the task of type `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a different task with the same [`Task::NAME`] has already
+    /// been registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::ProjectContext;
+    /// use cot::tasks::{Task, TaskRegistry};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize)]
+    /// struct GenerateReport {
+    ///     report_id: i64,
+    /// }
+    ///
+    /// impl Task for GenerateReport {
+    ///     const NAME: &'static str = "generate_report";
+    ///
+    ///     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut registry = TaskRegistry::new();
+    /// registry.register::<GenerateReport>();
+    /// ```
+    pub fn register<T: Task>(&mut self) -> &mut Self {
+        let previous = self
+            .runners
+            .insert(T::NAME, (TypeId::of::<T>(), run_serialized_task::<T>));
+        if let Some((type_id, _)) = previous {
+            assert_eq!(
+                type_id,
+                TypeId::of::<T>(),
+                "a different task is already registered with the name `{}`",
+                T::NAME
+            );
+        }
+        self
+    }
+
+    /// Returns `true` if a task with the given name has been registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::TaskRegistry;
+    ///
+    /// let registry = TaskRegistry::new();
+    /// assert!(!registry.contains("generate_report"));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.runners.contains_key(name)
+    }
+}
+
+/// Runs the background tasks stored in the queue.
+///
+/// The worker is started automatically by [`run`](crate::project::run) and
+/// the related functions, and is stopped when the server shuts down. It can
+/// also be used directly, for example to run the tasks in a separate process
+/// from a custom [`CliTask`](crate::cli::CliTask), or to run the enqueued
+/// tasks in tests.
+///
+/// The tasks from the queue are run one at a time. A task that fails or
+/// panics is logged and isn't retried. The periodic jobs registered with
+/// [`Tasks::schedule`] are run alongside them.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use cot::config::ProjectConfig;
+/// use cot::tasks::{TaskRegistry, TaskWorker};
+/// use cot::{Bootstrapper, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {}
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let bootstrapper = Bootstrapper::new(MyProject)
+///     .with_config(ProjectConfig::default())
+///     .boot()
+///     .await?;
+/// let project = bootstrapper.finish();
+///
+/// let worker = TaskWorker::new(Arc::new(project.context), project.task_registry);
+/// assert_eq!(worker.run_pending().await?, 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TaskWorker {
+    context: Arc<ProjectContext>,
+    registry: TaskRegistry,
+    poll_interval: Duration,
+}
+
+impl TaskWorker {
+    /// Creates a new worker that runs the tasks from the queue of the given
+    /// project context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::config::ProjectConfig;
+    /// use cot::tasks::{TaskRegistry, TaskWorker};
+    /// use cot::{Bootstrapper, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {}
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let bootstrapper = Bootstrapper::new(MyProject)
+    ///     .with_config(ProjectConfig::default())
+    ///     .boot()
+    ///     .await?;
+    /// let project = bootstrapper.finish();
+    ///
+    /// let worker = TaskWorker::new(Arc::new(project.context), TaskRegistry::new());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn new(context: Arc<ProjectContext>, registry: TaskRegistry) -> Self {
+        let poll_interval = context
+            .config()
+            .tasks
+            .poll_interval
+            .unwrap_or(DEFAULT_POLL_INTERVAL);
+
+        Self {
+            context,
+            registry,
+            poll_interval,
+        }
+    }
+
+    /// Runs the tasks and the periodic jobs until the given shutdown signal
+    /// is triggered.
+    ///
+    /// The tasks and jobs that are running when the signal is triggered are
+    /// allowed to finish. The errors returned by the queue are logged, and
+    /// the worker tries again after the poll interval.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use cot::config::ProjectConfig;
+    /// use cot::shutdown::ShutdownSignal;
+    /// use cot::tasks::TaskWorker;
+    /// use cot::{Bootstrapper, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {}
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let bootstrapper = Bootstrapper::new(MyProject)
+    ///     .with_config(ProjectConfig::default())
+    ///     .boot()
+    ///     .await?;
+    /// let project = bootstrapper.finish();
+    ///
+    /// TaskWorker::new(Arc::new(project.context), project.task_registry)
+    ///     .run(ShutdownSignal::never())
+    ///     .await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run(self, shutdown: ShutdownSignal) {
+        let scheduler = schedule::run_scheduler(
+            Arc::clone(&self.context),
+            self.context.tasks().scheduled_jobs(),
+            shutdown.clone(),
+        );
+        let queue = async {
+            let notify = &self.context.tasks().inner.notify;
+
+            while !shutdown.is_shutting_down() {
+                if let Err(error) = self.run_pending().await {
+                    error!("Failed to get a background task from the queue: {error}");
+                }
+
+                tokio::select! {
+                    () = shutdown.wait() => break,
+                    () = notify.notified() => {}
+                    () = tokio::time::sleep(self.poll_interval) => {}
+                }
+            }
+        };
+
+        tokio::join!(scheduler, queue);
+    }
+
+    /// Runs all the tasks that are currently in the queue, and returns the
+    /// number of tasks that have been run.
+    ///
+    /// The tasks that fail are logged and counted as run.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TasksError::Queue`] if a task could not be retrieved from
+    /// the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::config::ProjectConfig;
+    /// use cot::tasks::{TaskRegistry, TaskWorker};
+    /// use cot::{Bootstrapper, Project};
+    ///
+    /// struct MyProject;
+    /// impl Project for MyProject {}
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let bootstrapper = Bootstrapper::new(MyProject)
+    ///     .with_config(ProjectConfig::default())
+    ///     .boot()
+    ///     .await?;
+    /// let project = bootstrapper.finish();
+    ///
+    /// let worker = TaskWorker::new(Arc::new(project.context), project.task_registry);
+    /// assert_eq!(worker.run_pending().await?, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_pending(&self) -> TasksResult<usize> {
+        let mut count = 0;
+        while let Some(task) = self.context.tasks().inner.queue.pop().await? {
+            self.run_task(task).await;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    async fn run_task(&self, task: QueuedTask) {
+        let span = debug_span!("background_task", name = task.name());
+        async {
+            let Some((_, runner)) = self.registry.runners.get(task.name()) else {
+                let error = TasksError::UnknownTask(task.name().to_owned());
+                error!("Background task failed: {error}");
+                return;
+            };
+
+            let future = runner(Arc::clone(&self.context), task.payload().to_owned());
+            match AssertUnwindSafe(future).catch_unwind().await {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => error!("Background task failed: {error}"),
+                Err(_) => error!("Background task panicked"),
+            }
+        }
+        .instrument(span)
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use derive_more::with_trait::Debug;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::config::ProjectConfig;
+    use crate::{Bootstrapper, Project};
+
+    static RUN_TASKS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct RecordTask {
+        value: u32,
+    }
+
+    impl Task for RecordTask {
+        const NAME: &'static str = "record";
+
+        async fn run(self, _context: &ProjectContext) -> crate::Result<()> {
+            RUN_TASKS.lock().unwrap().push(self.value);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct FailingTask;
+
+    impl Task for FailingTask {
+        const NAME: &'static str = "failing";
+
+        async fn run(self, _context: &ProjectContext) -> crate::Result<()> {
+            Err(crate::Error::internal("task failed"))
+        }
+    }
+
+    struct TestProject;
+    impl Project for TestProject {
+        fn register_background_tasks(&self, tasks: &mut TaskRegistry) {
+            tasks.register::<RecordTask>().register::<FailingTask>();
+        }
+    }
+
+    async fn test_worker() -> TaskWorker {
+        let project = Bootstrapper::new(TestProject)
+            .with_config(ProjectConfig::default())
+            .boot()
+            .await
+            .unwrap()
+            .finish();
+
+        TaskWorker::new(Arc::new(project.context), project.task_registry)
+    }
+
+    #[test]
+    fn registry_register_same_task_twice() {
+        let mut registry = TaskRegistry::new();
+        registry.register::<RecordTask>().register::<RecordTask>();
+
+        assert!(registry.contains("record"));
+    }
+
+    #[test]
+    #[should_panic(expected = "a different task is already registered with the name `record`")]
+    fn registry_register_duplicate_name() {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct OtherRecordTask;
+
+        impl Task for OtherRecordTask {
+            const NAME: &'static str = "record";
+
+            async fn run(self, _context: &ProjectContext) -> crate::Result<()> {
+                Ok(())
+            }
+        }
+
+        TaskRegistry::new()
+            .register::<RecordTask>()
+            .register::<OtherRecordTask>();
+    }
+
+    #[cot::test]
+    async fn worker_runs_enqueued_tasks() {
+        let worker = test_worker().await;
+        let tasks = worker.context.tasks();
+
+        tasks.enqueue(RecordTask { value: 1 }).await.unwrap();
+        tasks.enqueue(FailingTask).await.unwrap();
+        tasks.enqueue(RecordTask { value: 2 }).await.unwrap();
+        tasks
+            .inner
+            .queue
+            .push(QueuedTask::new("unknown", "{}"))
+            .await
+            .unwrap();
+
+        assert_eq!(worker.run_pending().await.unwrap(), 4);
+        assert_eq!(worker.run_pending().await.unwrap(), 0);
+        assert_eq!(*RUN_TASKS.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[cot::test]
+    async fn worker_skips_delayed_tasks() {
+        let worker = test_worker().await;
+        let tasks = worker.context.tasks();
+
+        tasks
+            .enqueue_in(FailingTask, Duration::from_secs(60 * 60))
+            .await
+            .unwrap();
+
+        assert_eq!(worker.run_pending().await.unwrap(), 0);
+    }
+
+    #[cot::test]
+    async fn worker_runs_scheduled_jobs_without_overlap() {
+        static RUNNING: AtomicUsize = AtomicUsize::new(0);
+        static MAX_RUNNING: AtomicUsize = AtomicUsize::new(0);
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        struct SlowTask;
+
+        impl Task for SlowTask {
+            const NAME: &'static str = "slow";
+
+            async fn run(self, _context: &ProjectContext) -> crate::Result<()> {
+                let running = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+                MAX_RUNNING.fetch_max(running, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                RUNNING.fetch_sub(1, Ordering::SeqCst);
+                RUNS.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let worker = test_worker().await;
+        worker
+            .context
+            .tasks()
+            .schedule(Schedule::every(Duration::from_millis(10)), SlowTask);
+
+        let shutdown = crate::shutdown::ShutdownCoordinator::new();
+        let handle = tokio::spawn(worker.run(shutdown.signal()));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        shutdown.start_shutdown(None);
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("worker should stop after the shutdown has started")
+            .unwrap();
+
+        assert!(RUNS.load(Ordering::SeqCst) > 0);
+        assert_eq!(MAX_RUNNING.load(Ordering::SeqCst), 1);
+        assert_eq!(RUNNING.load(Ordering::SeqCst), 0);
+    }
+
+    #[cot::test]
+    async fn worker_stops_on_shutdown() {
+        let worker = test_worker().await;
+
+        let shutdown = crate::shutdown::ShutdownCoordinator::new();
+        let handle = tokio::spawn(worker.run(shutdown.signal()));
+        shutdown.start_shutdown(None);
+
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("worker should stop after the shutdown has started")
+            .unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+//! Database-backed task storage.
+//!
+//! This module provides a model and an app for storing the background tasks
+//! in a database using the Cot ORM. It is used by the
+//! [`DbQueue`](crate::tasks::queue::db::DbQueue).
+pub mod migrations;
+
+use cot::db::migrations::{ModelSchema, SyncDynMigration};
+
+use crate::App;
+use crate::db::{Auto, model};
+
+/// A background task stored in the database that hasn't been run yet.
+#[derive(Debug, Clone)]
+#[model]
+pub struct PendingTask {
+    #[model(primary_key)]
+    pub(crate) id: Auto<i64>,
+    pub(crate) name: String,
+    pub(crate) payload: String,
+    pub(crate) run_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+/// An app that provides the storage of background tasks in the database.
+///
+/// This app registers the pending task model and its migrations. It needs to
+/// be registered when the
+/// [`TaskQueueTypeConfig::Database`](crate::config::TaskQueueTypeConfig::Database)
+/// queue is used.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cot::config::{
+///     DatabaseConfig, ProjectConfig, TaskQueueConfig, TaskQueueTypeConfig, TasksConfig,
+/// };
+/// use cot::project::RegisterAppsContext;
+/// use cot::tasks::db::TasksApp;
+/// use cot::{AppBuilder, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn config(&self, config_name: &str) -> cot::Result<ProjectConfig> {
+///         Ok(ProjectConfig::builder()
+///             .database(DatabaseConfig::builder().url("sqlite::memory:").build())
+///             .tasks(
+///                 TasksConfig::builder()
+///                     .queue(
+///                         TaskQueueConfig::builder()
+///                             .queue_type(TaskQueueTypeConfig::Database)
+///                             .build(),
+///                     )
+///                     .build(),
+///             )
+///             .build())
+///     }
+///
+///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+///         apps.register(TasksApp::new());
+///     }
+/// }
+///
+/// #[cot::main]
+/// fn main() -> impl Project {
+///     MyProject
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
+pub struct TasksApp;
+
+impl TasksApp {
+    /// Create a new instance of the background tasks app.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::db::TasksApp;
+    /// let app = TasksApp::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for TasksApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl App for TasksApp {
+    fn name(&self) -> &'static str {
+        "cot_tasks"
+    }
+
+    fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
+        cot::db::migrations::wrap_migrations(migrations::MIGRATIONS)
+    }
+
+    fn models(&self) -> Vec<ModelSchema> {
+        vec![ModelSchema::of::<PendingTask>()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tasks_app_models_match_migrations() {
+        let app = TasksApp::new();
+
+        let engine = crate::db::migrations::MigrationEngine::new(app.migrations()).unwrap();
+
+        assert_eq!(engine.check_models(&app.models()), vec![]);
+    }
+}
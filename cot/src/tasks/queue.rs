@@ -0,0 +1,206 @@
+//! This module defines the queues that store the background tasks in Cot.
+//!
+//! It provides a [`TaskQueue`] trait that can be implemented by different
+//! storage backends (e.g., in-memory, database). The module also defines error
+//! handling for queue operations.
+use std::error::Error as StdError;
+use std::future::Future;
+use std::pin::Pin;
+
+use chrono::{DateTime, Utc};
+use cot_core::error::impl_into_cot_error;
+use thiserror::Error;
+
+#[cfg(feature = "db")]
+pub mod db;
+pub mod memory;
+
+const ERROR_PREFIX: &str = "task queue error:";
+
+/// Errors that can occur while storing or retrieving tasks using a queue
+/// backend.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TaskQueueError {
+    /// The underlying queue backend returned an error.
+    #[error("{ERROR_PREFIX} backend error: {0}")]
+    Backend(Box<dyn StdError + Send + Sync + 'static>),
+}
+
+impl_into_cot_error!(TaskQueueError);
+
+/// A convenience alias for results returned by task queue operations.
+pub type TaskQueueResult<T> = Result<T, TaskQueueError>;
+
+/// A task that is waiting in a queue to be run.
+///
+/// The task is stored as its name (see [`Task::NAME`](super::Task::NAME)) and
+/// its JSON-serialized payload. A task can optionally be delayed until a given
+/// time with [`QueuedTask::with_run_at`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::tasks::queue::QueuedTask;
+///
+/// let task = QueuedTask::new("send_welcome_email", r#"{"user_id":1}"#);
+/// assert_eq!(task.name(), "send_welcome_email");
+/// assert_eq!(task.payload(), r#"{"user_id":1}"#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedTask {
+    name: String,
+    payload: String,
+    run_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedTask {
+    /// Creates a new queued task with the given name and JSON payload.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::queue::QueuedTask;
+    ///
+    /// let task = QueuedTask::new("send_welcome_email", r#"{"user_id":1}"#);
+    /// ```
+    #[must_use]
+    pub fn new(name: impl Into<String>, payload: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            payload: payload.into(),
+            run_at: None,
+        }
+    }
+
+    /// Delays the task, so that it isn't returned by [`TaskQueue::pop`]
+    /// before the given time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeDelta, Utc};
+    /// use cot::tasks::queue::QueuedTask;
+    ///
+    /// let run_at = Utc::now() + TimeDelta::minutes(5);
+    /// let task = QueuedTask::new("send_welcome_email", "{}").with_run_at(run_at);
+    /// assert_eq!(task.run_at(), Some(run_at));
+    /// ```
+    #[must_use]
+    pub fn with_run_at(mut self, run_at: DateTime<Utc>) -> Self {
+        self.run_at = Some(run_at);
+        self
+    }
+
+    /// Returns the name of the task.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::queue::QueuedTask;
+    ///
+    /// let task = QueuedTask::new("send_welcome_email", "{}");
+    /// assert_eq!(task.name(), "send_welcome_email");
+    /// ```
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the JSON payload of the task.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::queue::QueuedTask;
+    ///
+    /// let task = QueuedTask::new("send_welcome_email", "{}");
+    /// assert_eq!(task.payload(), "{}");
+    /// ```
+    #[must_use]
+    pub fn payload(&self) -> &str {
+        &self.payload
+    }
+
+    /// Returns the time before which the task shouldn't be run, or `None` if
+    /// the task can be run right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::queue::QueuedTask;
+    ///
+    /// let task = QueuedTask::new("send_welcome_email", "{}");
+    /// assert_eq!(task.run_at(), None);
+    /// ```
+    #[must_use]
+    pub fn run_at(&self) -> Option<DateTime<Utc>> {
+        self.run_at
+    }
+
+    /// Returns whether the task can be run at the given time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeDelta, Utc};
+    /// use cot::tasks::queue::QueuedTask;
+    ///
+    /// let now = Utc::now();
+    /// let task = QueuedTask::new("send_welcome_email", "{}").with_run_at(now);
+    /// assert!(task.is_due(now));
+    /// assert!(!task.is_due(now - TimeDelta::seconds(1)));
+    /// ```
+    #[must_use]
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.run_at.is_none_or(|run_at| run_at <= now)
+    }
+}
+
+/// A generic asynchronous task queue interface.
+///
+/// The [`TaskQueue`] trait abstracts over different task storage backends.
+/// Each task pushed to the queue should be returned by [`TaskQueue::pop`]
+/// exactly once, even if the queue is shared by multiple workers.
+pub trait TaskQueue: Send + Sync + 'static {
+    /// Adds a task to the queue.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if there is an issue storing the task.
+    fn push(&self, task: QueuedTask) -> impl Future<Output = TaskQueueResult<()>> + Send;
+
+    /// Removes a task that is due to run (see [`QueuedTask::is_due`]) from
+    /// the queue and returns it, or returns `None` if there is no such task.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if there is an issue retrieving the
+    /// task.
+    fn pop(&self) -> impl Future<Output = TaskQueueResult<Option<QueuedTask>>> + Send;
+}
+
+pub(crate) trait BoxedTaskQueue: Send + Sync + 'static {
+    fn push(
+        &self,
+        task: QueuedTask,
+    ) -> Pin<Box<dyn Future<Output = TaskQueueResult<()>> + Send + '_>>;
+
+    fn pop(&self)
+    -> Pin<Box<dyn Future<Output = TaskQueueResult<Option<QueuedTask>>> + Send + '_>>;
+}
+
+impl<T: TaskQueue> BoxedTaskQueue for T {
+    fn push(
+        &self,
+        task: QueuedTask,
+    ) -> Pin<Box<dyn Future<Output = TaskQueueResult<()>> + Send + '_>> {
+        Box::pin(async move { T::push(self, task).await })
+    }
+
+    fn pop(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = TaskQueueResult<Option<QueuedTask>>> + Send + '_>> {
+        Box::pin(async move { T::pop(self).await })
+    }
+}
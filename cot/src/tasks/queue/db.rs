@@ -0,0 +1,203 @@
+//! Database-backed task queue.
+//!
+//! This module provides a task queue implementation that stores the tasks in
+//! a database using the Cot ORM. The tasks survive server restarts and can be
+//! shared between multiple server instances using the same database. The
+//! [`TasksApp`](crate::tasks::db::TasksApp) needs to be registered in the
+//! project for the table storing the tasks to be created.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::db::Database;
+//! use cot::tasks::queue::db::DbQueue;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> cot::Result<()> {
+//! let db = Database::new("sqlite::memory:").await?;
+//! let queue = DbQueue::new(db);
+//! # Ok(())
+//! # }
+//! ```
+use chrono::Utc;
+use thiserror::Error;
+
+use crate::db::{Auto, Database, DatabaseError, query};
+use crate::tasks::db::PendingTask;
+use crate::tasks::queue::{ERROR_PREFIX, QueuedTask, TaskQueue, TaskQueueError, TaskQueueResult};
+
+/// Errors that can occur while using the database task queue.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DbQueueError {
+    /// An error occurred while interacting with the database.
+    #[error("{ERROR_PREFIX} {0}")]
+    DatabaseError(#[from] DatabaseError),
+}
+
+impl From<DbQueueError> for TaskQueueError {
+    fn from(err: DbQueueError) -> Self {
+        TaskQueueError::Backend(Box::new(err))
+    }
+}
+
+/// A task queue that stores the tasks in the database.
+///
+/// The tasks that are due to run are generally returned in the order they
+/// were added, but this isn't guaranteed, as it depends on the database
+/// engine. The returned tasks don't have the [`QueuedTask::run_at`] time set,
+/// as they can be run right away.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::Database;
+/// use cot::tasks::queue::db::DbQueue;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let db = Database::new("sqlite::memory:").await?;
+/// let queue = DbQueue::new(db);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DbQueue {
+    connection: Database,
+}
+
+impl DbQueue {
+    /// Creates a new `DbQueue` instance with the provided database connection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::Database;
+    /// use cot::tasks::queue::db::DbQueue;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let db = Database::new("sqlite::memory:").await?;
+    /// let queue = DbQueue::new(db);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn new(connection: Database) -> DbQueue {
+        DbQueue { connection }
+    }
+}
+
+impl TaskQueue for DbQueue {
+    async fn push(&self, task: QueuedTask) -> TaskQueueResult<()> {
+        let mut model = PendingTask {
+            id: Auto::auto(),
+            run_at: task.run_at.unwrap_or_else(Utc::now).fixed_offset(),
+            name: task.name,
+            payload: task.payload,
+        };
+        self.connection
+            .insert(&mut model)
+            .await
+            .map_err(DbQueueError::from)?;
+
+        Ok(())
+    }
+
+    async fn pop(&self) -> TaskQueueResult<Option<QueuedTask>> {
+        let now = Utc::now().fixed_offset();
+        loop {
+            let Some(model) = query!(PendingTask, $run_at <= now)
+                .get(&self.connection)
+                .await
+                .map_err(DbQueueError::from)?
+            else {
+                return Ok(None);
+            };
+
+            // another worker might have claimed the task in the meantime, in
+            // which case nothing is deleted and we try the next one
+            let id = model.id;
+            let result = query!(PendingTask, $id == id)
+                .delete(&self.connection)
+                .await
+                .map_err(DbQueueError::from)?;
+            if result.rows_affected().0 > 0 {
+                return Ok(Some(QueuedTask::new(model.name, model.payload)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+    use crate::tasks::db::migrations;
+    use crate::test::TestDatabase;
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn push_pop() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db
+            .add_migrations(migrations::MIGRATIONS.to_vec())
+            .run_migrations()
+            .await;
+        let queue = DbQueue::new(test_db.database());
+
+        queue.push(QueuedTask::new("first", "1")).await.unwrap();
+        queue.push(QueuedTask::new("second", "2")).await.unwrap();
+
+        let mut tasks = vec![
+            queue.pop().await.unwrap().unwrap(),
+            queue.pop().await.unwrap().unwrap(),
+        ];
+        tasks.sort_by(|a, b| a.name().cmp(b.name()));
+        assert_eq!(
+            tasks,
+            vec![
+                QueuedTask::new("first", "1"),
+                QueuedTask::new("second", "2")
+            ]
+        );
+        assert_eq!(queue.pop().await.unwrap(), None);
+
+        test_db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn pop_skips_delayed_tasks() {
+        let mut test_db = TestDatabase::new_sqlite().await.unwrap();
+        test_db
+            .add_migrations(migrations::MIGRATIONS.to_vec())
+            .run_migrations()
+            .await;
+        let queue = DbQueue::new(test_db.database());
+
+        queue
+            .push(QueuedTask::new("later", "1").with_run_at(Utc::now() + TimeDelta::hours(1)))
+            .await
+            .unwrap();
+        queue
+            .push(QueuedTask::new("past", "2").with_run_at(Utc::now() - TimeDelta::hours(1)))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            queue.pop().await.unwrap(),
+            Some(QueuedTask::new("past", "2"))
+        );
+        assert_eq!(queue.pop().await.unwrap(), None);
+
+        test_db.cleanup().await.unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+//! In-memory task queue.
+//!
+//! This backend stores the tasks in the memory of the server process. The
+//! tasks that haven't been run yet are lost when the server is restarted, so
+//! it is intended primarily for development and testing environments.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::tasks::queue::memory::Memory;
+//! use cot::tasks::queue::{QueuedTask, TaskQueue};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> cot::Result<()> {
+//! let queue = Memory::new();
+//! queue.push(QueuedTask::new("send_welcome_email", "{}")).await?;
+//!
+//! let task = queue.pop().await?;
+//! assert_eq!(task, Some(QueuedTask::new("send_welcome_email", "{}")));
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+
+use crate::tasks::queue::{QueuedTask, TaskQueue, TaskQueueResult};
+
+/// A task queue that stores the tasks in memory.
+///
+/// Cloning the queue is cheap and all the clones share the same tasks. The
+/// tasks that are due to run are returned in the order they were added.
+///
+/// # Examples
+///
+/// ```
+/// use cot::tasks::queue::memory::Memory;
+///
+/// let queue = Memory::new();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+    tasks: Arc<Mutex<VecDeque<QueuedTask>>>,
+}
+
+impl Memory {
+    /// Creates a new, empty in-memory task queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::queue::memory::Memory;
+    ///
+    /// let queue = Memory::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TaskQueue for Memory {
+    async fn push(&self, task: QueuedTask) -> TaskQueueResult<()> {
+        self.tasks
+            .lock()
+            .expect("task queue lock poisoned")
+            .push_back(task);
+        Ok(())
+    }
+
+    async fn pop(&self) -> TaskQueueResult<Option<QueuedTask>> {
+        let now = Utc::now();
+        let mut tasks = self.tasks.lock().expect("task queue lock poisoned");
+        let index = tasks.iter().position(|task| task.is_due(now));
+
+        Ok(index.and_then(|index| tasks.remove(index)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    #[cot::test]
+    async fn pop_returns_tasks_in_order() {
+        let queue = Memory::new();
+        queue.push(QueuedTask::new("first", "1")).await.unwrap();
+        queue.push(QueuedTask::new("second", "2")).await.unwrap();
+
+        assert_eq!(
+            queue.pop().await.unwrap(),
+            Some(QueuedTask::new("first", "1"))
+        );
+        assert_eq!(
+            queue.pop().await.unwrap(),
+            Some(QueuedTask::new("second", "2"))
+        );
+        assert_eq!(queue.pop().await.unwrap(), None);
+    }
+
+    #[cot::test]
+    async fn pop_skips_delayed_tasks() {
+        let queue = Memory::new();
+        let later = QueuedTask::new("later", "1").with_run_at(Utc::now() + TimeDelta::hours(1));
+        queue.push(later.clone()).await.unwrap();
+        queue.push(QueuedTask::new("now", "2")).await.unwrap();
+
+        assert_eq!(
+            queue.pop().await.unwrap(),
+            Some(QueuedTask::new("now", "2"))
+        );
+        assert_eq!(queue.pop().await.unwrap(), None);
+        assert_eq!(queue.tasks.lock().unwrap().front(), Some(&later));
+    }
+
+    #[cot::test]
+    async fn clones_share_tasks() {
+        let queue = Memory::new();
+        let clone = queue.clone();
+        queue.push(QueuedTask::new("task", "{}")).await.unwrap();
+
+        assert_eq!(
+            clone.pop().await.unwrap(),
+            Some(QueuedTask::new("task", "{}"))
+        );
+        assert_eq!(queue.pop().await.unwrap(), None);
+    }
+}
@@ -0,0 +1,555 @@
+//! Periodic jobs.
+//!
+//! This module allows running the [`Task`]s periodically, either at a fixed
+//! interval, or according to a cron expression. The jobs are typically
+//! registered in [`App::init`](crate::App::init) using
+//! [`Tasks::schedule`](crate::tasks::Tasks::schedule) and
+//! are run by the [`TaskWorker`](crate::tasks::TaskWorker), along with the
+//! tasks from the queue.
+//!
+//! A job is not started again while its previous run is still in progress;
+//! such a run is skipped and a warning is logged. Note that each worker
+//! process runs its own copy of the schedule, so the jobs are run once per
+//! worker process.
+//!
+//! # Examples
+//!
+//! ```
+//! use async_trait::async_trait;
+//! use cot::tasks::Task;
+//! use cot::tasks::schedule::Schedule;
+//! use cot::{App, ProjectContext};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Clone, Serialize, Deserialize)]
+//! struct PurgeExpiredSessions;
+//!
+//! impl Task for PurgeExpiredSessions {
+//!     const NAME: &'static str = "purge_expired_sessions";
+//!
+//!     async fn run(self, context: &ProjectContext) -> cot::Result<()> {
+//!         // purge the sessions
+//!         Ok(())
+//!     }
+//! }
+//!
+//! struct MyApp;
+//! #[async_trait]
+//! impl App for MyApp {
+//!     fn name(&self) -> &str {
+//!         "my_app"
+//!     }
+//!
+//!     async fn init(&self, context: &mut ProjectContext) -> cot::Result<()> {
+//!         context
+//!             .tasks()
+//!             .schedule(Schedule::cron("0 3 * * *")?, PurgeExpiredSessions);
+//!         Ok(())
+//!     }
+//! }
+//! ```
+
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Timelike, Utc};
+use cot_core::error::impl_into_cot_error;
+use derive_more::with_trait::Debug;
+use futures_util::FutureExt;
+use thiserror::Error;
+use tokio::task::JoinSet;
+use tracing::{Instrument, debug_span, error, warn};
+
+use crate::ProjectContext;
+use crate::shutdown::ShutdownSignal;
+use crate::tasks::Task;
+
+const ERROR_PREFIX: &str = "schedule error:";
+
+/// Errors that can occur while creating a [`Schedule`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ScheduleError {
+    /// The cron expression could not be parsed.
+    #[error("{ERROR_PREFIX} invalid cron expression `{expression}`: {reason}")]
+    InvalidCronExpression {
+        /// The expression that could not be parsed.
+        expression: String,
+        /// The reason the expression is invalid.
+        reason: String,
+    },
+}
+
+impl_into_cot_error!(ScheduleError);
+
+/// A convenience alias for results returned by the schedule operations.
+pub type ScheduleResult<T> = Result<T, ScheduleError>;
+
+/// Describes when a periodic job is run.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::tasks::schedule::Schedule;
+///
+/// let every_minute = Schedule::every(Duration::from_secs(60));
+/// let every_night = Schedule::cron("30 2 * * *")?;
+/// # Ok::<(), cot::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    kind: ScheduleKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ScheduleKind {
+    Interval(Duration),
+    Cron(CronExpression),
+}
+
+impl Schedule {
+    /// Creates a schedule that runs the job at a fixed interval.
+    ///
+    /// The first run happens one interval after the worker is started.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interval is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::tasks::schedule::Schedule;
+    ///
+    /// let schedule = Schedule::every(Duration::from_secs(15 * 60));
+    /// ```
+    #[must_use]
+    pub fn every(interval: Duration) -> Self {
+        assert!(
+            !interval.is_zero(),
+            "the schedule interval must not be zero"
+        );
+
+        Self {
+            kind: ScheduleKind::Interval(interval),
+        }
+    }
+
+    /// Creates a schedule from a cron expression.
+    ///
+    /// The expression consists of five fields separated by whitespace:
+    /// minute (0-59), hour (0-23), day of month (1-31), month (1-12), and day
+    /// of week (0-7, where both 0 and 7 mean Sunday). Each field can be `*`,
+    /// a number, a range (`1-5`), a step (`*/15`, `0-30/10`), or a
+    /// comma-separated list of these. The `@yearly`, `@monthly`, `@weekly`,
+    /// `@daily` and `@hourly` shortcuts are supported as well.
+    ///
+    /// If both the day of month and the day of week are restricted, the job
+    /// is run when either of them matches. The times are in UTC.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScheduleError::InvalidCronExpression`] if the expression
+    /// could not be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::schedule::Schedule;
+    ///
+    /// // every 15 minutes during working hours on weekdays
+    /// let schedule = Schedule::cron("*/15 9-17 * * 1-5")?;
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub fn cron(expression: &str) -> ScheduleResult<Self> {
+        let cron = CronExpression::parse(expression).map_err(|reason| {
+            ScheduleError::InvalidCronExpression {
+                expression: expression.to_owned(),
+                reason,
+            }
+        })?;
+
+        Ok(Self {
+            kind: ScheduleKind::Cron(cron),
+        })
+    }
+
+    /// Returns the first time after the given one at which the job should be
+    /// run, or `None` if the job should never be run again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cot::tasks::schedule::Schedule;
+    ///
+    /// let schedule = Schedule::cron("30 2 * * *")?;
+    /// let time = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    ///
+    /// assert_eq!(
+    ///     schedule.next_after(time),
+    ///     Some(Utc.with_ymd_and_hms(2025, 1, 2, 2, 30, 0).unwrap())
+    /// );
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[must_use]
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match &self.kind {
+            ScheduleKind::Interval(interval) => {
+                time.checked_add_signed(TimeDelta::from_std(*interval).ok()?)
+            }
+            ScheduleKind::Cron(cron) => cron.next_after(time),
+        }
+    }
+}
+
+/// A parsed cron expression, with each field stored as a bitmask of the
+/// allowed values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CronExpression {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronExpression {
+    /// Expressions that never match, such as `0 0 30 2 *`, are given up on
+    /// after this many days.
+    const MAX_SEARCH_DAYS: i64 = 5 * 366;
+
+    fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            expression => expression,
+        };
+
+        let fields: Vec<_> = expression.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(format!("expected 5 fields, got {}", fields.len()));
+        };
+
+        let mut days_of_week_mask = Self::parse_field(days_of_week, 0, 7)?;
+        // 7 is an alias for Sunday
+        if days_of_week_mask & (1 << 7) != 0 {
+            days_of_week_mask = (days_of_week_mask & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: Self::parse_field(minutes, 0, 59)?,
+            hours: Self::parse_field(hours, 0, 23)?,
+            days_of_month: Self::parse_field(days_of_month, 1, 31)?,
+            months: Self::parse_field(months, 1, 12)?,
+            days_of_week: days_of_week_mask,
+            any_day_of_month: days_of_month.starts_with('*'),
+            any_day_of_week: days_of_week.starts_with('*'),
+        })
+    }
+
+    fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+        let parse_value = |value: &str| {
+            value
+                .parse::<u32>()
+                .map_err(|_| format!("invalid value `{value}`"))
+        };
+
+        let mut mask = 0;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step = parse_value(step)?;
+                    if step == 0 {
+                        return Err(format!("invalid step in `{part}`"));
+                    }
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                (parse_value(start)?, parse_value(end)?)
+            } else {
+                let value = parse_value(range)?;
+                // `5/15` means "every 15, starting at 5"
+                (value, if step > 1 { max } else { value })
+            };
+
+            if start < min || end > max || start > end {
+                return Err(format!("`{part}` is out of the range {min}-{max}"));
+            }
+
+            for value in (start..=end).step_by(step as usize) {
+                mask |= 1 << value;
+            }
+        }
+
+        Ok(mask)
+    }
+
+    fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let limit = time + TimeDelta::days(Self::MAX_SEARCH_DAYS);
+        let mut time = time.with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+
+        while time <= limit {
+            if !Self::contains(self.months, time.month()) {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !self.matches_day(time) {
+                time = (time.date_naive() + TimeDelta::days(1))
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !Self::contains(self.hours, time.hour()) {
+                time = time.with_minute(0)? + TimeDelta::hours(1);
+            } else if !Self::contains(self.minutes, time.minute()) {
+                time += TimeDelta::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+
+        None
+    }
+
+    fn matches_day(&self, time: DateTime<Utc>) -> bool {
+        let day_of_month = Self::contains(self.days_of_month, time.day());
+        let day_of_week = Self::contains(self.days_of_week, time.weekday().num_days_from_sunday());
+
+        if self.any_day_of_month || self.any_day_of_week {
+            day_of_month && day_of_week
+        } else {
+            day_of_month || day_of_week
+        }
+    }
+
+    fn contains(mask: u64, value: u32) -> bool {
+        mask & (1 << value) != 0
+    }
+}
+
+type JobRunner = Arc<
+    dyn Fn(Arc<ProjectContext>) -> Pin<Box<dyn Future<Output = crate::Result<()>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// A task registered to be run periodically.
+#[derive(Debug, Clone)]
+pub(crate) struct ScheduledJob {
+    name: &'static str,
+    schedule: Schedule,
+    #[debug("..")]
+    runner: JobRunner,
+}
+
+impl ScheduledJob {
+    pub(crate) fn new<T: Task + Clone + Sync>(schedule: Schedule, task: T) -> Self {
+        let runner: JobRunner = Arc::new(move |context| {
+            let task = task.clone();
+            Box::pin(async move { task.run(&context).await })
+        });
+
+        Self {
+            name: T::NAME,
+            schedule,
+            runner,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct JobState {
+    job: ScheduledJob,
+    next_run: Option<DateTime<Utc>>,
+    running: Arc<AtomicBool>,
+}
+
+/// Runs the given jobs according to their schedules until the shutdown
+/// signal is triggered.
+///
+/// The jobs that are running when the signal is triggered are allowed to
+/// finish.
+pub(crate) async fn run_scheduler(
+    context: Arc<ProjectContext>,
+    jobs: Vec<ScheduledJob>,
+    shutdown: ShutdownSignal,
+) {
+    let now = Utc::now();
+    let mut states: Vec<_> = jobs
+        .into_iter()
+        .map(|job| JobState {
+            next_run: job.schedule.next_after(now),
+            job,
+            running: Arc::new(AtomicBool::new(false)),
+        })
+        .collect();
+    let mut running_jobs = JoinSet::new();
+
+    loop {
+        while running_jobs.try_join_next().is_some() {}
+
+        let Some(next_run) = states.iter().filter_map(|state| state.next_run).min() else {
+            break;
+        };
+        let wait = (next_run - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            () = shutdown.wait() => break,
+            () = tokio::time::sleep(wait) => {}
+        }
+
+        let now = Utc::now();
+        for state in &mut states {
+            if state.next_run.is_none_or(|next_run| next_run > now) {
+                continue;
+            }
+            state.next_run = state.job.schedule.next_after(now);
+
+            let name = state.job.name;
+            if state.running.swap(true, Ordering::AcqRel) {
+                warn!(
+                    name,
+                    "Skipping the scheduled job, as its previous run hasn't finished yet"
+                );
+                continue;
+            }
+
+            let running = Arc::clone(&state.running);
+            let future = (state.job.runner)(Arc::clone(&context));
+            let span = debug_span!("scheduled_job", name);
+            running_jobs.spawn(
+                async move {
+                    match AssertUnwindSafe(future).catch_unwind().await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(error)) => error!("Scheduled job failed: {error}"),
+                        Err(_) => error!("Scheduled job panicked"),
+                    }
+                    running.store(false, Ordering::Release);
+                }
+                .instrument(span),
+            );
+        }
+    }
+
+    while running_jobs.join_next().await.is_some() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn time(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn every_next_after() {
+        let schedule = Schedule::every(Duration::from_secs(90));
+
+        assert_eq!(
+            schedule.next_after(time(2025, 1, 1, 0, 0)),
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 1, 30).unwrap())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the schedule interval must not be zero")]
+    fn every_zero() {
+        let _ = Schedule::every(Duration::ZERO);
+    }
+
+    #[test]
+    fn cron_next_after() {
+        let cases = [
+            ("* * * * *", time(2025, 1, 1, 0, 0), time(2025, 1, 1, 0, 1)),
+            (
+                "*/15 * * * *",
+                time(2025, 1, 1, 0, 50),
+                time(2025, 1, 1, 1, 0),
+            ),
+            (
+                "30 2 * * *",
+                time(2025, 1, 1, 2, 30),
+                time(2025, 1, 2, 2, 30),
+            ),
+            (
+                "0 9-17 * * 1-5",
+                time(2025, 1, 3, 18, 0),
+                time(2025, 1, 6, 9, 0),
+            ),
+            (
+                "0 0 1 */3 *",
+                time(2025, 2, 10, 0, 0),
+                time(2025, 4, 1, 0, 0),
+            ),
+            (
+                "0 0 29 2 *",
+                time(2025, 1, 1, 0, 0),
+                time(2028, 2, 29, 0, 0),
+            ),
+            ("0 0 * * 7", time(2025, 1, 1, 0, 0), time(2025, 1, 5, 0, 0)),
+            // either the day of month or the day of week has to match
+            ("0 0 13 * 5", time(2025, 1, 1, 0, 0), time(2025, 1, 3, 0, 0)),
+            ("@monthly", time(2025, 12, 15, 0, 0), time(2026, 1, 1, 0, 0)),
+        ];
+
+        for (expression, after, expected) in cases {
+            let schedule = Schedule::cron(expression).unwrap();
+            assert_eq!(
+                schedule.next_after(after),
+                Some(expected),
+                "expression: {expression}"
+            );
+        }
+    }
+
+    #[test]
+    fn cron_never_matches() {
+        let schedule = Schedule::cron("0 0 30 2 *").unwrap();
+
+        assert_eq!(schedule.next_after(time(2025, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn cron_invalid() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "a * * * *",
+        ] {
+            assert!(
+                matches!(
+                    Schedule::cron(expression),
+                    Err(ScheduleError::InvalidCronExpression { .. })
+                ),
+                "expression: {expression}"
+            );
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! OpenTelemetry integration.
+//!
+//! Cot creates a [`tracing`] span for each request handled by the server,
+//! containing the HTTP method, the URI, the name of the matched route, the
+//! response status, and the ID of the authenticated user. The database queries
+//! executed while handling the request are recorded as `db_query` spans (at
+//! the `DEBUG` level) nested under it.
+//!
+//! To send these spans to an OpenTelemetry collector, add the [`layer`]
+//! returned by this module to your tracing subscriber and configure the
+//! exporter in [`ProjectConfig::opentelemetry`]. The exporter is started along
+//! with the server and the remaining spans are flushed when it shuts down.
+//!
+//! If the request contains the [W3C Trace Context] `traceparent` header, the
+//! request span is made a child of the remote span it identifies, so that the
+//! traces started by other services continue in the project. The context of
+//! the current span is, in turn, sent with the outbound requests made with
+//! `HttpClient::with_headers_from`.
+//!
+//! [`ProjectConfig::opentelemetry`]: crate::config::ProjectConfig::opentelemetry
+//! [W3C Trace Context]: https://www.w3.org/TR/trace-context/
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::layer::SubscriberExt;
+//! use tracing_subscriber::util::SubscriberInitExt;
+//!
+//! tracing_subscriber::registry()
+//!     .with(tracing_subscriber::fmt::layer())
+//!     .with(cot::telemetry::layer())
+//!     .init();
+//! ```
+
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+use cot_core::error::impl_into_cot_error;
+use http::{HeaderMap, StatusCode};
+use opentelemetry::Context;
+use opentelemetry::global::{BoxedSpan, BoxedTracer};
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::noop::NoopTracer;
+use opentelemetry::trace::{SpanBuilder, Tracer, TracerProvider as _};
+use opentelemetry_otlp::{ExporterBuildError, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use thiserror::Error;
+use tracing::{Span, Subscriber, warn};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::{OpenTelemetryConfig, OpenTelemetryExporterConfig};
+
+/// The tracer of the running server; the spans are dropped if it's not set.
+static TRACER: RwLock<Option<Arc<BoxedTracer>>> = RwLock::new(None);
+static NOOP_TRACER: LazyLock<BoxedTracer> =
+    LazyLock::new(|| BoxedTracer::new(Box::new(NoopTracer::new())));
+
+/// Returns a [`tracing_subscriber::Layer`] that sends the spans to the
+/// OpenTelemetry exporter configured in
+/// [`ProjectConfig::opentelemetry`](crate::config::ProjectConfig::opentelemetry).
+///
+/// The layer is meant to be added to the tracing subscriber before the
+/// project is started. Until the server is started (or if no exporter is
+/// configured), the spans are only used to propagate the trace context.
+///
+/// # Examples
+///
+/// ```
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let subscriber = tracing_subscriber::registry().with(cot::telemetry::layer());
+/// ```
+#[must_use]
+pub fn layer<S>() -> impl tracing_subscriber::Layer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(ProjectTracer)
+}
+
+/// A tracer delegating to the tracer of the running server, so that the layer
+/// can be created before the project config is loaded.
+#[derive(Debug, Copy, Clone)]
+struct ProjectTracer;
+
+impl Tracer for ProjectTracer {
+    type Span = BoxedSpan;
+
+    fn build_with_context(&self, builder: SpanBuilder, parent_cx: &Context) -> Self::Span {
+        let tracer = TRACER
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        match tracer {
+            Some(tracer) => tracer.build_with_context(builder, parent_cx),
+            None => NOOP_TRACER.build_with_context(builder, parent_cx),
+        }
+    }
+}
+
+/// Starts exporting the spans as specified in the config.
+///
+/// Returns [`None`] if no exporter is configured.
+pub(crate) fn init(
+    config: &OpenTelemetryConfig,
+) -> Result<Option<TracerGuard>, OpenTelemetryError> {
+    let exporter = match &config.exporter {
+        OpenTelemetryExporterConfig::None => return Ok(None),
+        OpenTelemetryExporterConfig::Otlp { endpoint } => {
+            let mut builder = SpanExporter::builder().with_http();
+            if let Some(endpoint) = endpoint {
+                builder = builder.with_endpoint(endpoint);
+            }
+            builder.build()?
+        }
+    };
+
+    let mut resource = Resource::builder();
+    if let Some(service_name) = &config.service_name {
+        resource = resource.with_service_name(service_name.clone());
+    }
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource.build())
+        .build();
+
+    let tracer = BoxedTracer::new(Box::new(provider.tracer("cot")));
+    *TRACER.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(tracer));
+
+    Ok(Some(TracerGuard { provider }))
+}
+
+/// Exports the remaining spans and stops the exporter when shut down.
+#[derive(Debug)]
+pub(crate) struct TracerGuard {
+    provider: SdkTracerProvider,
+}
+
+impl TracerGuard {
+    pub(crate) async fn shutdown(self) {
+        *TRACER.write().unwrap_or_else(PoisonError::into_inner) = None;
+
+        // flushing the spans blocks the thread until they are exported
+        let result = tokio::task::spawn_blocking(move || self.provider.shutdown()).await;
+        if let Ok(Err(error)) = result {
+            warn!("Failed to export the remaining OpenTelemetry spans: {error}");
+        }
+    }
+}
+
+/// Marks the span as the server span of a request, continuing the trace
+/// specified in the `traceparent` header, if any.
+pub(crate) fn start_request_span(span: &Span, headers: &HeaderMap) {
+    span.record("otel.kind", "server");
+
+    let parent = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+    // this only fails if the OpenTelemetry layer is not installed
+    let _ = span.set_parent(parent);
+}
+
+/// Marks the request span as failed if the response is a server error.
+pub(crate) fn finish_request_span(span: &Span, status: StatusCode) {
+    if status.is_server_error() {
+        span.record("otel.status_code", "error");
+    }
+}
+
+/// Writes the context of the current span to the `traceparent` and
+/// `tracestate` headers, so that the outbound request continues the trace.
+///
+/// Returns `false` without changing the headers if the current span is not
+/// recorded by OpenTelemetry.
+#[cfg(feature = "http-client")]
+pub(crate) fn inject_current_context(headers: &mut HeaderMap) -> bool {
+    use opentelemetry::trace::TraceContextExt;
+
+    let context = Span::current().context();
+    if !context.span().span_context().is_valid() {
+        return false;
+    }
+
+    TraceContextPropagator::new().inject_context(&context, &mut HeaderInjector(headers));
+    true
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(http::HeaderName::as_str).collect()
+    }
+}
+
+#[cfg(feature = "http-client")]
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+#[cfg(feature = "http-client")]
+impl opentelemetry::propagation::Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            http::HeaderName::from_bytes(key.as_bytes()),
+            http::HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// An error that can occur when starting the OpenTelemetry exporter.
+#[derive(Debug, Error)]
+#[error("could not create the OpenTelemetry exporter: {0}")]
+pub(crate) struct OpenTelemetryError(#[from] ExporterBuildError);
+impl_into_cot_error!(OpenTelemetryError);
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{TraceContextExt, TraceId};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn header_extractor() {
+        let mut headers = HeaderMap::new();
+        headers.insert("traceparent", "00-abc-def-01".parse().unwrap());
+
+        let extractor = HeaderExtractor(&headers);
+
+        assert_eq!(extractor.get("traceparent"), Some("00-abc-def-01"));
+        assert_eq!(extractor.get("tracestate"), None);
+        assert_eq!(extractor.keys(), vec!["traceparent"]);
+    }
+
+    #[test]
+    fn request_span_remote_parent() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+                .parse()
+                .unwrap(),
+        );
+        let subscriber = tracing_subscriber::registry().with(layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", otel.kind = tracing::field::Empty);
+            start_request_span(&span, &headers);
+
+            let trace_id = span.context().span().span_context().trace_id();
+            assert_eq!(
+                trace_id,
+                TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap()
+            );
+        });
+    }
+
+    #[cfg(feature = "http-client")]
+    #[test]
+    fn inject_current_context_continues_trace() {
+        let mut incoming = HeaderMap::new();
+        incoming.insert(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+                .parse()
+                .unwrap(),
+        );
+        // the project tracer doesn't create new spans until the server is started
+        let tracer = SdkTracerProvider::builder().build().tracer("test");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", otel.kind = tracing::field::Empty);
+            start_request_span(&span, &incoming);
+            let _entered = span.enter();
+
+            let mut outgoing = HeaderMap::new();
+            assert!(inject_current_context(&mut outgoing));
+
+            let traceparent = outgoing["traceparent"].to_str().unwrap();
+            assert!(traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+            assert_ne!(traceparent, incoming["traceparent"]);
+        });
+    }
+
+    #[cfg(feature = "http-client")]
+    #[test]
+    fn inject_current_context_no_span() {
+        let mut headers = HeaderMap::new();
+
+        assert!(!inject_current_context(&mut headers));
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn init_no_exporter() {
+        let guard = init(&OpenTelemetryConfig::default()).unwrap();
+
+        assert!(guard.is_none());
+    }
+
+    #[cot::test]
+    async fn init_otlp() {
+        let config = OpenTelemetryConfig::builder()
+            .service_name("test")
+            .exporter(OpenTelemetryExporterConfig::Otlp {
+                endpoint: Some("http://localhost:4318/v1/traces".to_owned()),
+            })
+            .build();
+
+        let guard = init(&config).unwrap().unwrap();
+
+        assert!(TRACER.read().unwrap().is_some());
+        guard.shutdown().await;
+        assert!(TRACER.read().unwrap().is_none());
+    }
+}
@@ -1,5 +1,8 @@
 //! Test utilities for Cot projects.
 
+#[cfg(all(feature = "db", feature = "fake"))]
+pub mod factory;
+
 use std::any::Any;
 use std::future::poll_fn;
 use std::marker::PhantomData;
@@ -43,12 +46,19 @@ use crate::db::migrations::{
 use crate::email::Email;
 #[cfg(feature = "email")]
 use crate::email::transport::console::Console;
+#[cfg(feature = "http-client")]
+use crate::http_client::HttpClient;
+use crate::notify::Notifier;
 use crate::project::{prepare_request, prepare_request_for_error_handler, run_at_with_shutdown};
 use crate::request::Request;
 use crate::response::Response;
 use crate::router::Router;
 use crate::session::Session;
 use crate::static_files::{StaticFile, StaticFiles};
+#[cfg(feature = "storage")]
+use crate::storage::FileStorage;
+#[cfg(feature = "tasks")]
+use crate::tasks::Tasks;
 use crate::{Body, Bootstrapper, Project, ProjectContext, Result};
 
 /// A test client for making requests to a Cot project.
@@ -238,6 +248,13 @@ pub struct TestRequestBuilder {
     cache: Option<Cache>,
     #[cfg(feature = "email")]
     email: Option<Email>,
+    #[cfg(feature = "storage")]
+    storage: Option<FileStorage>,
+    #[cfg(feature = "http-client")]
+    http_client: Option<HttpClient>,
+    notifier: Option<Notifier>,
+    #[cfg(feature = "tasks")]
+    tasks: Option<Tasks>,
 }
 
 /// A wrapper over an auth backend that is cloneable.
@@ -295,6 +312,13 @@ impl Default for TestRequestBuilder {
             cache: None,
             #[cfg(feature = "email")]
             email: None,
+            #[cfg(feature = "storage")]
+            storage: None,
+            #[cfg(feature = "http-client")]
+            http_client: None,
+            notifier: None,
+            #[cfg(feature = "tasks")]
+            tasks: None,
         }
     }
 }
@@ -601,6 +625,117 @@ impl TestRequestBuilder {
         self
     }
 
+    /// Add an HTTP client to the request builder.
+    ///
+    /// This is typically used with a [`MockHttpClient`] to avoid calling the
+    /// real services in tests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::RequestHandler;
+    /// use cot::html::Html;
+    /// use cot::http_client::HttpClient;
+    /// use cot::test::{MockHttpClient, TestRequestBuilder};
+    ///
+    /// async fn index(http_client: HttpClient) -> cot::Result<Html> {
+    ///     let response = http_client.get("http://example.com/").send().await?;
+    ///     let text = response.text().await.map_err(cot::Error::internal)?;
+    ///
+    ///     Ok(Html::new(text))
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let mock = MockHttpClient::new(|_request| http::Response::new("Hello world!".into()));
+    /// let request = TestRequestBuilder::get("/")
+    ///     .http_client(mock.client())
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     index
+    ///         .handle(request)
+    ///         .await?
+    ///         .into_body()
+    ///         .into_bytes()
+    ///         .await?,
+    ///     "Hello world!"
+    /// );
+    /// assert_eq!(mock.requests().len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "http-client")]
+    pub fn http_client(&mut self, http_client: HttpClient) -> &mut Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Add a notifier to the request builder.
+    ///
+    /// This allows the test to publish messages to, or subscribe to the
+    /// messages published by, the handler under test.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::notify::Notifier;
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// let notifier = Notifier::new();
+    /// let request = TestRequestBuilder::get("/")
+    ///     .notifier(notifier.clone())
+    ///     .build();
+    /// ```
+    pub fn notifier(&mut self, notifier: Notifier) -> &mut Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Add a background tasks handle to the request builder.
+    ///
+    /// This allows the test to inspect the tasks enqueued by the handler
+    /// under test. If not set, the tasks are stored in a new in-memory queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::tasks::Tasks;
+    /// use cot::tasks::queue::memory::Memory;
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// let queue = Memory::new();
+    /// let request = TestRequestBuilder::get("/")
+    ///     .tasks(Tasks::new(queue.clone()))
+    ///     .build();
+    /// ```
+    #[cfg(feature = "tasks")]
+    pub fn tasks(&mut self, tasks: Tasks) -> &mut Self {
+        self.tasks = Some(tasks);
+        self
+    }
+
+    /// Set the file storage for this request.
+    ///
+    /// If not set, a new storage keeping the files in memory is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::storage::FileStorage;
+    /// use cot::storage::memory::Memory;
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// let request = TestRequestBuilder::get("/")
+    ///     .storage(FileStorage::new(Memory::new()))
+    ///     .build();
+    /// ```
+    #[cfg(feature = "storage")]
+    pub fn storage(&mut self, storage: FileStorage) -> &mut Self {
+        self.storage = Some(storage);
+        self
+    }
+
     /// Use database authentication in the test request.
     ///
     /// Note that this calls [`Self::auth_backend`], [`Self::with_session`],
@@ -785,6 +920,17 @@ impl TestRequestBuilder {
             self.email
                 .clone()
                 .unwrap_or_else(|| Email::new(Console::new())),
+            #[cfg(feature = "storage")]
+            self.storage
+                .clone()
+                .unwrap_or_else(|| FileStorage::new(crate::storage::memory::Memory::new())),
+            #[cfg(feature = "http-client")]
+            self.http_client.clone().unwrap_or_default(),
+            self.notifier.clone().unwrap_or_default(),
+            #[cfg(feature = "tasks")]
+            self.tasks
+                .clone()
+                .unwrap_or_else(|| Tasks::new(crate::tasks::queue::memory::Memory::new())),
         );
         prepare_request(&mut request, Arc::new(context));
 
@@ -835,6 +981,123 @@ impl TestRequestBuilder {
     }
 }
 
+#[cfg(feature = "http-client")]
+type MockHttpHandler =
+    dyn Fn(&http::Request<bytes::Bytes>) -> http::Response<bytes::Bytes> + Send + Sync;
+
+/// A mocked HTTP client that records the requests instead of sending them.
+///
+/// The responses are produced by the handler passed to
+/// [`MockHttpClient::new`]. Use [`MockHttpClient::client`] to get an
+/// [`HttpClient`] that can be passed to
+/// [`TestRequestBuilder::http_client`] or to the code under test.
+///
+/// # Examples
+///
+/// ```
+/// use cot::test::MockHttpClient;
+/// use http::StatusCode;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let mock = MockHttpClient::new(|request| {
+///     if request.uri().path() == "/items/" {
+///         http::Response::new("[]".into())
+///     } else {
+///         let mut response = http::Response::new("not found".into());
+///         *response.status_mut() = StatusCode::NOT_FOUND;
+///         response
+///     }
+/// });
+///
+/// let response = mock.client().get("http://example.com/items/").send().await?;
+/// assert_eq!(response.status(), StatusCode::OK);
+/// assert_eq!(mock.requests()[0].uri().path(), "/items/");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "http-client")]
+#[derive(derive_more::Debug, Clone)]
+pub struct MockHttpClient {
+    #[debug("..")]
+    handler: Arc<MockHttpHandler>,
+    requests: Arc<std::sync::Mutex<Vec<http::Request<bytes::Bytes>>>>,
+}
+
+#[cfg(feature = "http-client")]
+impl MockHttpClient {
+    /// Creates a new mocked HTTP client that responds to the requests with
+    /// the given handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::MockHttpClient;
+    ///
+    /// let mock = MockHttpClient::new(|_request| http::Response::new("ok".into()));
+    /// ```
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(&http::Request<bytes::Bytes>) -> http::Response<bytes::Bytes> + Send + Sync + 'static,
+    {
+        Self {
+            handler: Arc::new(handler),
+            requests: Arc::new(std::sync::Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns an [`HttpClient`] that uses this mock instead of sending the
+    /// requests.
+    ///
+    /// # Panics
+    ///
+    /// The returned client panics when sending a request if the handler
+    /// panicked previously.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::MockHttpClient;
+    ///
+    /// let mock = MockHttpClient::new(|_request| http::Response::new("ok".into()));
+    /// let http_client = mock.client();
+    /// ```
+    #[must_use]
+    pub fn client(&self) -> HttpClient {
+        let mock = self.clone();
+        HttpClient::new().with_mock(Arc::new(move |request| {
+            let response = (mock.handler)(&request);
+            mock.requests
+                .lock()
+                .expect("mock HTTP client lock poisoned")
+                .push(request);
+            response
+        }))
+    }
+
+    /// Returns the requests received by the mock so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a handler panicked while the requests were being recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::MockHttpClient;
+    ///
+    /// let mock = MockHttpClient::new(|_request| http::Response::new("ok".into()));
+    /// assert!(mock.requests().is_empty());
+    /// ```
+    #[must_use]
+    pub fn requests(&self) -> Vec<http::Request<bytes::Bytes>> {
+        self.requests
+            .lock()
+            .expect("mock HTTP client lock poisoned")
+            .clone()
+    }
+}
+
 /// A test database.
 ///
 /// This is used to create a separate database for testing and run migrations on
@@ -1104,6 +1367,8 @@ impl TestDatabase {
 
     /// Run the migrations on the test database.
     ///
+    /// Unlike when the server starts, destructive migrations are applied, too.
+    ///
     /// # Panics
     ///
     /// Panics if the migration engine could not be initialized or if the
@@ -1131,7 +1396,8 @@ impl TestDatabase {
     pub async fn run_migrations(&mut self) -> &mut Self {
         if !self.migrations.is_empty() {
             let engine = MigrationEngine::new(std::mem::take(&mut self.migrations))
-                .expect("Failed to initialize the migration engine");
+                .expect("Failed to initialize the migration engine")
+                .allow_destructive(true);
             engine
                 .run(&self.database())
                 .await
@@ -1326,6 +1592,12 @@ impl DynMigration for TestMigration {
 /// [`macro@cot::test`]. Remember to call [`TestServer::close`] when
 /// you're done with the tests, as the server will not be stopped automatically.
 ///
+/// By default, the server listens on a free port chosen by the operating
+/// system and uses the project's `test` configuration, so multiple servers can
+/// run in parallel. Note, however, that all of them connect to the database
+/// from the configuration; use [`Self::database`] to give each test its own
+/// database, for instance one created with [`TestDatabase`].
+///
 /// # Examples
 ///
 /// ```
@@ -1345,9 +1617,13 @@ impl DynMigration for TestMigration {
 ///     Ok(())
 /// }
 /// ```
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone)]
 pub struct TestServerBuilder<T> {
     project: T,
+    port: u16,
+    config: Option<ProjectConfig>,
+    #[cfg(feature = "db")]
+    database: Option<Database>,
 }
 
 impl<T: Project + Send + 'static> TestServerBuilder<T> {
@@ -1374,7 +1650,111 @@ impl<T: Project + Send + 'static> TestServerBuilder<T> {
     /// ```
     #[must_use]
     pub fn new(project: T) -> Self {
-        Self { project }
+        Self {
+            project,
+            port: 0,
+            config: None,
+            #[cfg(feature = "db")]
+            database: None,
+        }
+    }
+
+    /// Sets the port the server listens on.
+    ///
+    /// By default, a free port is chosen by the operating system, which is
+    /// what you want in most cases, as it allows the tests to run in
+    /// parallel. Setting a fixed port is only useful if something outside
+    /// the test needs to know it in advance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::TestServerBuilder;
+    ///
+    /// struct TestProject;
+    /// impl cot::Project for TestProject {}
+    ///
+    /// #[cot::e2e_test] // note this uses "e2e_test"!
+    /// async fn test_server() -> cot::Result<()> {
+    ///     let server = TestServerBuilder::new(TestProject).port(8123).start().await;
+    ///     assert_eq!(server.address().port(), 8123);
+    ///
+    ///     server.close().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Sets the configuration of the project.
+    ///
+    /// By default, the `test` configuration of the project is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::ProjectConfig;
+    /// use cot::test::TestServerBuilder;
+    ///
+    /// struct TestProject;
+    /// impl cot::Project for TestProject {}
+    ///
+    /// #[cot::e2e_test] // note this uses "e2e_test"!
+    /// async fn test_server() -> cot::Result<()> {
+    ///     let server = TestServerBuilder::new(TestProject)
+    ///         .config(ProjectConfig::dev_default())
+    ///         .start()
+    ///         .await;
+    ///
+    ///     server.close().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn config(mut self, config: ProjectConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Sets the database used by the server.
+    ///
+    /// By default, the server connects to the database from the project
+    /// configuration, which is shared between all the tests. Passing a
+    /// separate database for each test, such as the one created by
+    /// [`TestDatabase::new_postgres`] with the name of the test, allows the
+    /// tests to run in parallel without interfering with each other. This
+    /// also allows the test to fill the database with the data before sending
+    /// the requests, even if it's an in-memory SQLite database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::{TestDatabase, TestServerBuilder};
+    ///
+    /// struct TestProject;
+    /// impl cot::Project for TestProject {}
+    ///
+    /// #[cot::e2e_test] // note this uses "e2e_test"!
+    /// async fn test_server() -> cot::Result<()> {
+    ///     let test_database = TestDatabase::new_sqlite().await?;
+    ///     let server = TestServerBuilder::new(TestProject)
+    ///         .database(test_database.database())
+    ///         .start()
+    ///         .await;
+    ///
+    ///     server.close().await;
+    ///     test_database.cleanup().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "db")]
+    #[must_use]
+    pub fn database(mut self, database: Database) -> Self {
+        self.database = Some(database);
+        self
     }
 
     /// Start the test server.
@@ -1405,7 +1785,7 @@ impl<T: Project + Send + 'static> TestServerBuilder<T> {
     /// }
     /// ```
     pub async fn start(self) -> TestServer<T> {
-        TestServer::start(self.project).await
+        TestServer::start(self).await
     }
 }
 
@@ -1444,8 +1824,8 @@ pub struct TestServer<T> {
 }
 
 impl<T: Project + Send + 'static> TestServer<T> {
-    async fn start(project: T) -> Self {
-        let tcp_listener = TcpListener::bind("0.0.0.0:0")
+    async fn start(builder: TestServerBuilder<T>) -> Self {
+        let tcp_listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, builder.port))
             .await
             .expect("Failed to bind to a port");
         let mut address = tcp_listener
@@ -1456,9 +1836,28 @@ impl<T: Project + Send + 'static> TestServer<T> {
         let (send, recv) = oneshot::channel::<()>();
 
         let server_handle = tokio::task::spawn_local(async move {
-            let bootstrapper = Bootstrapper::new(project)
-                .with_config_name("test")
-                .expect("Failed to get the \"test\" config")
+            let bootstrapper = Bootstrapper::new(builder.project);
+            let bootstrapper = match builder.config {
+                Some(config) => bootstrapper.with_config(config),
+                None => bootstrapper
+                    .with_config_name("test")
+                    .expect("Failed to get the \"test\" config"),
+            };
+            let bootstrapper = bootstrapper.with_apps();
+            #[cfg(feature = "db")]
+            let bootstrapper = match builder.database {
+                Some(database) => bootstrapper.with_existing_database(database),
+                None => bootstrapper
+                    .with_database()
+                    .await
+                    .expect("Failed to connect to the database"),
+            };
+            #[cfg(not(feature = "db"))]
+            let bootstrapper = bootstrapper
+                .with_database()
+                .await
+                .expect("Failed to connect to the database");
+            let bootstrapper = bootstrapper
                 .boot()
                 .await
                 .expect("Failed to boot the project");
@@ -1544,6 +1943,36 @@ impl<T: Project + Send + 'static> TestServer<T> {
         }
     }
 
+    /// Get the URL of the given path on the server.
+    ///
+    /// This is a shortcut for joining [`Self::url`] with the path, which is
+    /// typically what is passed to an HTTP client or a browser driver, such
+    /// as [`fantoccini`](https://docs.rs/fantoccini/latest/fantoccini/).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::TestServerBuilder;
+    ///
+    /// struct TestProject;
+    /// impl cot::Project for TestProject {}
+    ///
+    /// #[cot::e2e_test] // note this uses "e2e_test"!
+    /// async fn test_server() -> cot::Result<()> {
+    ///     let server = TestServerBuilder::new(TestProject).start().await;
+    ///
+    ///     assert_eq!(server.url_for("/admin/"), format!("{}/admin/", server.url()));
+    ///     assert_eq!(server.url_for("admin/"), format!("{}/admin/", server.url()));
+    ///
+    ///     server.close().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn url_for(&self, path: &str) -> String {
+        format!("{}/{}", self.url(), path.strip_prefix('/').unwrap_or(path))
+    }
+
     /// Stop the server.
     ///
     /// Note that this is not automatically called when the `TestServer` is
@@ -1580,6 +2009,41 @@ impl<T: Project + Send + 'static> TestServer<T> {
     }
 }
 
+/// Returns the URL of the `WebDriver` server used to run browser sessions in
+/// end-to-end tests.
+///
+/// The URL is read from the `COT_WEBDRIVER_URL` environment variable, so that
+/// it can be changed without modifying the tests, for instance to point to a
+/// `WebDriver` running in a Docker container. If the variable is not set, it
+/// defaults to `http://localhost:4444`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cot::test::{TestServerBuilder, webdriver_url};
+/// use fantoccini::ClientBuilder;
+///
+/// struct TestProject;
+/// impl cot::Project for TestProject {}
+///
+/// #[cot::e2e_test] // note this uses "e2e_test"!
+/// async fn test_server() -> Result<(), Box<dyn std::error::Error>> {
+///     let server = TestServerBuilder::new(TestProject).start().await;
+///     let driver = ClientBuilder::native().connect(&webdriver_url()).await?;
+///
+///     driver.goto(&server.url_for("/")).await?;
+///     // ...interact with the page
+///
+///     driver.close().await?;
+///     server.close().await;
+///     Ok(())
+/// }
+/// ```
+#[must_use]
+pub fn webdriver_url() -> String {
+    std::env::var("COT_WEBDRIVER_URL").unwrap_or_else(|_| "http://localhost:4444".to_owned())
+}
+
 /// A guard for running tests serially.
 ///
 /// This is mostly useful for tests that need to modify some global state (e.g.
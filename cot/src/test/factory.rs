@@ -0,0 +1,174 @@
+//! Test factories for models.
+//!
+//! Integration tests often need a few rows in the database before a handler
+//! can be exercised, and constructing every one of them by hand (along with
+//! all the rows they reference) quickly gets tedious. Deriving [`Factory`] for
+//! a model generates a builder that fills all the fields that were not set
+//! explicitly with fake, but realistic values:
+//!
+//! * [`Auto`](crate::db::Auto) fields are left for the database to generate,
+//! * [`ForeignKey`](crate::db::ForeignKey) fields reference a new instance of
+//!   the related model, created using its own factory (so the related model
+//!   has to derive [`Factory`], too),
+//! * [`Option`] fields are set to [`None`],
+//! * [`String`] fields get a value based on the name of the field, such as an
+//!   email address for the `email` field, or a person's name for the `name`
+//!   field,
+//! * all the other fields are generated using their [`fake::Dummy`]
+//!   implementation, which exists for most of the field types, including dates
+//!   and times.
+//!
+//! The generated value can be customized using the `#[factory(...)]` field
+//! attribute:
+//!
+//! * `default = expr` – use the given expression as the value of the field,
+//! * `fake = expr` – generate the value using the given [faker](faker).
+//!
+//! The builder is named after the model, with the `Factory` suffix, and has a
+//! setter for each of the model fields.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::db::{Auto, ForeignKey, model};
+//! use cot::test::factory::{Factory, FactoryBuilder};
+//!
+//! #[model]
+//! #[derive(Factory)]
+//! struct Author {
+//!     #[model(primary_key)]
+//!     id: Auto<i32>,
+//!     name: String,
+//!     email: String,
+//! }
+//!
+//! #[model]
+//! #[derive(Factory)]
+//! struct Post {
+//!     #[model(primary_key)]
+//!     id: Auto<i32>,
+//!     author: ForeignKey<Author>,
+//!     title: String,
+//!     #[factory(default = false)]
+//!     published: bool,
+//!     #[factory(fake = cot::test::factory::faker::lorem::en::Word())]
+//!     slug: String,
+//! }
+//!
+//! # async fn test(db: &cot::db::Database) -> cot::db::Result<()> {
+//! // creates the post along with its author
+//! let post = Post::factory().title("Hello, world!").create(db).await?;
+//!
+//! // creates 10 posts, each with its own author
+//! let posts = Post::create_batch(db, 10).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+/// Derive macro for the [`Factory`] trait.
+///
+/// See the [module-level documentation](self) for the supported attributes.
+pub use cot_macros::Factory;
+pub use fake::faker;
+
+use crate::db::{DatabaseBackend, Model, Result};
+
+/// A model that can be created in tests using a factory.
+///
+/// This is typically implemented using the [`Factory`](macro@Factory) derive
+/// macro. See the [module-level documentation](self) for more information.
+#[async_trait]
+pub trait Factory: Model {
+    /// The builder type used to create the model instances.
+    type Builder: FactoryBuilder<Model = Self>;
+
+    /// Returns a builder that can be used to set some of the fields before
+    /// building or creating the model instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, model};
+    /// use cot::test::factory::{Factory, FactoryBuilder};
+    ///
+    /// #[model]
+    /// #[derive(Factory)]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     username: String,
+    /// }
+    ///
+    /// let user = User::factory().username("alice").build();
+    /// assert_eq!(user.username, "alice");
+    /// ```
+    #[must_use]
+    fn factory() -> Self::Builder {
+        Self::Builder::default()
+    }
+
+    /// Builds a model instance with all the fields generated, without saving
+    /// it to the database.
+    ///
+    /// See [`FactoryBuilder::build`] for more details.
+    #[must_use]
+    fn build() -> Self {
+        Self::factory().build()
+    }
+
+    /// Creates a model instance with all the fields generated and saves it to
+    /// the database, along with the instances it references.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the instances could not be saved to the
+    /// database.
+    async fn create<DB: DatabaseBackend>(db: &DB) -> Result<Self> {
+        Self::factory().create(db).await
+    }
+
+    /// Creates `count` model instances with all the fields generated and saves
+    /// them to the database, along with the instances they reference.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the instances could not be saved to the
+    /// database.
+    async fn create_batch<DB: DatabaseBackend>(db: &DB, count: usize) -> Result<Vec<Self>> {
+        let mut instances = Vec::with_capacity(count);
+        for _ in 0..count {
+            instances.push(Self::create(db).await?);
+        }
+        Ok(instances)
+    }
+}
+
+/// A builder of model instances, generated by the [`Factory`](macro@Factory)
+/// derive macro.
+#[async_trait]
+pub trait FactoryBuilder: Default + Send {
+    /// The model built by this builder.
+    type Model: Factory;
+
+    /// Builds the model instance without saving it to the database.
+    ///
+    /// The instances referenced by the foreign keys that were not set
+    /// explicitly are built, but not saved either, so the returned instance
+    /// can't be saved to the database as is. Use [`Self::create`] if the
+    /// instance needs to be stored in the database.
+    #[must_use]
+    fn build(self) -> Self::Model;
+
+    /// Builds the model instance and saves it to the database.
+    ///
+    /// The instances referenced by the foreign keys that were not set
+    /// explicitly are created using their factories and saved to the
+    /// database first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the instances could not be saved to the
+    /// database.
+    async fn create<DB: DatabaseBackend>(self, db: &DB) -> Result<Self::Model>;
+}
@@ -52,6 +52,40 @@ impl AcceptHeaderParser {
             .iter()
             .any(|ct| ct.media_type == *media_type)
     }
+
+    /// Returns the quality (the `q` parameter) of the given media type, taken
+    /// from the most specific media range matching it, or `0.0` if none
+    /// matches.
+    ///
+    /// An empty header means that any media type is acceptable.
+    pub(crate) fn quality(&self, media_type: &Mime) -> f32 {
+        if self.content_types.is_empty() {
+            return 1.0;
+        }
+
+        self.content_types
+            .iter()
+            .filter_map(|ct| {
+                let range = &ct.media_type;
+                let specificity = if range.type_() == mime::STAR {
+                    0
+                } else if range.type_() != media_type.type_() {
+                    return None;
+                } else if range.subtype() == mime::STAR {
+                    1
+                } else if range.subtype() == media_type.subtype() {
+                    2
+                } else {
+                    return None;
+                };
+                Some((specificity, ct.weight))
+            })
+            // `max_by_key` returns the last maximum; the content types are sorted by
+            // the weight, so use `rev` to get the highest weight for the same range
+            .rev()
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, weight)| weight)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -134,4 +168,20 @@ mod tests {
         assert!(!parser.contains_explicit(&mime::IMAGE_PNG));
         assert!(!parser.contains_explicit(&mime::TEXT_XML));
     }
+
+    #[test]
+    fn quality() {
+        let parser = AcceptHeaderParser::parse("text/html, text/*;q=0.5, */*;q=0.1, image/png;q=0");
+
+        assert!((parser.quality(&mime::TEXT_HTML) - 1.0).abs() < 1e-6);
+        assert!((parser.quality(&mime::TEXT_PLAIN) - 0.5).abs() < 1e-6);
+        assert!((parser.quality(&mime::APPLICATION_JSON) - 0.1).abs() < 1e-6);
+        assert!(parser.quality(&mime::IMAGE_PNG).abs() < 1e-6);
+
+        let parser = AcceptHeaderParser::parse("application/json");
+        assert!(parser.quality(&mime::TEXT_HTML).abs() < 1e-6);
+
+        let parser = AcceptHeaderParser::parse("");
+        assert!((parser.quality(&mime::TEXT_HTML) - 1.0).abs() < 1e-6);
+    }
 }
@@ -0,0 +1,67 @@
+//! Formatting and parsing of HTTP dates, as defined in
+//! [RFC 9110](https://datatracker.ietf.org/doc/html/rfc9110#name-date-time-formats).
+
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Formats a timestamp in the IMF-fixdate format (e.g. `Sun, 06 Nov 1994
+/// 08:49:37 GMT`).
+pub(crate) fn format_http_date(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format(HTTP_DATE_FORMAT)
+        .to_string()
+}
+
+/// Parses an HTTP date in the IMF-fixdate format.
+///
+/// Returns `None` if the date is invalid. The obsolete RFC 850 and asctime
+/// formats are not supported.
+pub(crate) fn parse_http_date(value: &str) -> Option<SystemTime> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| SystemTime::from(date.with_timezone(&Utc)))
+}
+
+/// Truncates a timestamp to the whole seconds, as HTTP dates don't support
+/// sub-second precision.
+pub(crate) fn truncate_to_seconds(time: SystemTime) -> SystemTime {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(duration) => SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(duration.as_secs()),
+        Err(_) => time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn format_and_parse() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777);
+
+        let formatted = format_http_date(time);
+
+        assert_eq!(formatted, "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date(&formatted), Some(time));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+
+    #[test]
+    fn truncate() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_500);
+
+        assert_eq!(
+            truncate_to_seconds(time),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1)
+        );
+    }
+}
@@ -0,0 +1,469 @@
+//! Validation errors shared by forms, JSON APIs, and the ORM.
+//!
+//! [`ValidationErrors`] collects the errors found while validating some data,
+//! keyed by the path of the field they apply to (such as `email` or
+//! `address.city`). Each error is a [`FormFieldValidationError`], which has a
+//! machine-readable [`code`](FormFieldValidationError::code) in addition to
+//! its human-readable message.
+//!
+//! The same structure is produced by:
+//!
+//! * form validation, through [`ValidationErrors::from_form_context`],
+//! * JSON request bodies, through the
+//!   [`ValidatedJson`](crate::request::extractors::ValidatedJson) extractor,
+//! * models, through
+//!   [`Model::validate_and_save`](crate::db::Model::validate_and_save),
+//!
+//! and can be displayed in an HTML form with
+//! [`ValidationErrors::apply_to_form_context`], or returned from an API
+//! handler as a `422 Unprocessable Entity` JSON response.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::form::FormFieldValidationError;
+//! use cot::validation::{Validate, ValidationErrors};
+//!
+//! struct NewUser {
+//!     username: String,
+//!     password: String,
+//!     password_confirmation: String,
+//! }
+//!
+//! impl Validate for NewUser {
+//!     fn validate(&self) -> Result<(), ValidationErrors> {
+//!         let mut errors = ValidationErrors::new();
+//!         if self.username.is_empty() {
+//!             errors.add_field_error("username", FormFieldValidationError::Required);
+//!         }
+//!         if self.password != self.password_confirmation {
+//!             errors.add_non_field_error(FormFieldValidationError::with_code(
+//!                 "passwords_mismatch",
+//!                 "Passwords differ.",
+//!             ));
+//!         }
+//!         errors.into_result()
+//!     }
+//! }
+//!
+//! let user = NewUser {
+//!     username: String::new(),
+//!     password: "hunter2".to_owned(),
+//!     password_confirmation: "hunter3".to_owned(),
+//! };
+//! let errors = user.validate().unwrap_err();
+//! assert_eq!(errors.len(), 2);
+//! assert_eq!(errors.field_errors("username")[0].code(), "required");
+//! assert_eq!(errors.non_field_errors()[0].code(), "passwords_mismatch");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use cot_core::error::impl_into_cot_error;
+use serde::Serialize;
+
+use crate::form::{FormContext, FormErrorTarget, FormFieldValidationError};
+#[cfg(feature = "json")]
+use crate::response::{IntoResponse, Response};
+
+/// A type whose values can be validated.
+///
+/// This is implemented for the types that are deserialized from JSON request
+/// bodies with [`ValidatedJson`](crate::request::extractors::ValidatedJson),
+/// or for the models that are saved with
+/// [`Model::validate_and_save`](crate::db::Model::validate_and_save). See the
+/// [module-level documentation](self) for an example.
+pub trait Validate {
+    /// Validates the value.
+    ///
+    /// # Errors
+    ///
+    /// Returns the validation errors if the value is not valid.
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// The errors found while validating some data.
+///
+/// The errors are either attached to a field, identified by its path (such as
+/// `email`, `address.city`, or `items[0].name`), or to the data as a whole.
+///
+/// When serialized, this is an object with the `fields` map from the field
+/// paths to the lists of errors, and the `non_field_errors` list. This is also
+/// the body of the `422 Unprocessable Entity` response this is converted to
+/// with [`IntoResponse`](crate::response::IntoResponse).
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::FormFieldValidationError;
+/// use cot::validation::ValidationErrors;
+///
+/// let mut errors = ValidationErrors::new();
+/// errors.add_field_error("email", FormFieldValidationError::Required);
+///
+/// assert_eq!(
+///     serde_json::to_value(&errors).unwrap(),
+///     serde_json::json!({
+///         "fields": {
+///             "email": [{"code": "required", "message": "This field is required.", "params": {}}]
+///         },
+///         "non_field_errors": []
+///     })
+/// );
+/// ```
+#[must_use]
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationErrors {
+    #[serde(rename = "fields")]
+    field_errors: BTreeMap<String, Vec<FormFieldValidationError>>,
+    non_field_errors: Vec<FormFieldValidationError>,
+}
+
+impl ValidationErrors {
+    /// Creates a new, empty set of validation errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::validation::ValidationErrors;
+    ///
+    /// let errors = ValidationErrors::new();
+    /// assert!(errors.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            field_errors: BTreeMap::new(),
+            non_field_errors: Vec::new(),
+        }
+    }
+
+    /// Adds an error for the field with the given path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::FormFieldValidationError;
+    /// use cot::validation::ValidationErrors;
+    ///
+    /// let mut errors = ValidationErrors::new();
+    /// errors.add_field_error("name", FormFieldValidationError::Required);
+    ///
+    /// assert_eq!(
+    ///     errors.field_errors("name"),
+    ///     [FormFieldValidationError::Required]
+    /// );
+    /// ```
+    pub fn add_field_error<T: Into<String>>(
+        &mut self,
+        path: T,
+        error: FormFieldValidationError,
+    ) -> &mut Self {
+        self.field_errors
+            .entry(path.into())
+            .or_default()
+            .push(error);
+        self
+    }
+
+    /// Adds an error that applies to the data as a whole rather than to a
+    /// single field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::FormFieldValidationError;
+    /// use cot::validation::ValidationErrors;
+    ///
+    /// let mut errors = ValidationErrors::new();
+    /// errors.add_non_field_error(FormFieldValidationError::from_static("Invalid data."));
+    ///
+    /// assert_eq!(errors.non_field_errors().len(), 1);
+    /// ```
+    pub fn add_non_field_error(&mut self, error: FormFieldValidationError) -> &mut Self {
+        self.non_field_errors.push(error);
+        self
+    }
+
+    /// Adds the errors of a nested value, such as an object or a list item,
+    /// prefixing their field paths with the given path.
+    ///
+    /// The non-field errors of the nested value are added as the errors of the
+    /// field with the given path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::FormFieldValidationError;
+    /// use cot::validation::ValidationErrors;
+    ///
+    /// let mut address_errors = ValidationErrors::new();
+    /// address_errors.add_field_error("city", FormFieldValidationError::Required);
+    ///
+    /// let mut errors = ValidationErrors::new();
+    /// errors.extend_nested("address", address_errors);
+    ///
+    /// assert_eq!(
+    ///     errors.field_errors("address.city"),
+    ///     [FormFieldValidationError::Required]
+    /// );
+    /// ```
+    pub fn extend_nested(&mut self, path: &str, errors: ValidationErrors) -> &mut Self {
+        for (field_path, field_errors) in errors.field_errors {
+            let separator = if field_path.starts_with('[') { "" } else { "." };
+            self.field_errors
+                .entry(format!("{path}{separator}{field_path}"))
+                .or_default()
+                .extend(field_errors);
+        }
+        if !errors.non_field_errors.is_empty() {
+            self.field_errors
+                .entry(path.to_owned())
+                .or_default()
+                .extend(errors.non_field_errors);
+        }
+        self
+    }
+
+    /// Returns the errors for the field with the given path.
+    #[must_use]
+    pub fn field_errors(&self, path: &str) -> &[FormFieldValidationError] {
+        self.field_errors.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the errors that apply to the data as a whole.
+    #[must_use]
+    pub fn non_field_errors(&self) -> &[FormFieldValidationError] {
+        &self.non_field_errors
+    }
+
+    /// Returns an iterator over the field paths and their errors, ordered by
+    /// the field path.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &[FormFieldValidationError])> {
+        self.field_errors
+            .iter()
+            .map(|(path, errors)| (path.as_str(), errors.as_slice()))
+    }
+
+    /// Returns the total number of errors.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.field_errors.values().map(Vec::len).sum::<usize>() + self.non_field_errors.len()
+    }
+
+    /// Returns `true` if there are no errors.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `Ok(())` if there are no errors, or `Err(self)` otherwise.
+    ///
+    /// This is useful as the last expression of a [`Validate::validate`]
+    /// implementation.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` if there are any errors.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+
+    /// Collects the validation errors of a form context.
+    ///
+    /// This is typically used with the context returned in
+    /// [`FormResult::ValidationError`](crate::form::FormResult::ValidationError)
+    /// to report the form errors in the same way as the other validation
+    /// errors, for instance in an API response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormContext, FormErrorTarget, FormFieldValidationError};
+    /// use cot::validation::ValidationErrors;
+    ///
+    /// #[derive(Form)]
+    /// struct ContactForm {
+    ///     email: String,
+    /// }
+    ///
+    /// let mut context = <ContactForm as Form>::Context::new();
+    /// context.add_error(
+    ///     FormErrorTarget::Field("email"),
+    ///     FormFieldValidationError::Required,
+    /// );
+    ///
+    /// let errors = ValidationErrors::from_form_context(context);
+    /// assert_eq!(
+    ///     errors.field_errors("email"),
+    ///     [FormFieldValidationError::Required]
+    /// );
+    /// ```
+    pub fn from_form_context<C: FormContext>(mut context: C) -> Self {
+        let field_ids: Vec<String> = context
+            .fields()
+            .map(|field| field.dyn_id().to_owned())
+            .collect();
+
+        let mut errors = Self::new();
+        for field_id in field_ids {
+            let field_errors =
+                std::mem::take(context.errors_for_mut(FormErrorTarget::Field(&field_id)));
+            if !field_errors.is_empty() {
+                errors.field_errors.insert(field_id, field_errors);
+            }
+        }
+        errors.non_field_errors = std::mem::take(context.errors_for_mut(FormErrorTarget::Form));
+        errors
+    }
+
+    /// Adds the errors to a form context, so that they are displayed along
+    /// with the form.
+    ///
+    /// The errors for the field paths that don't match any field of the form
+    /// are added as the errors of the entire form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormContext, FormErrorTarget, FormFieldValidationError};
+    /// use cot::validation::ValidationErrors;
+    ///
+    /// #[derive(Form)]
+    /// struct ContactForm {
+    ///     email: String,
+    /// }
+    ///
+    /// let mut errors = ValidationErrors::new();
+    /// errors.add_field_error("email", FormFieldValidationError::Required);
+    ///
+    /// let mut context = <ContactForm as Form>::Context::new();
+    /// errors.apply_to_form_context(&mut context);
+    /// assert_eq!(
+    ///     context.errors_for(FormErrorTarget::Field("email")),
+    ///     [FormFieldValidationError::Required]
+    /// );
+    /// ```
+    pub fn apply_to_form_context<C: FormContext + ?Sized>(self, context: &mut C) {
+        for (path, field_errors) in self.field_errors {
+            let is_form_field = context.fields().any(|field| field.dyn_id() == path);
+            for error in field_errors {
+                let target = if is_form_field {
+                    FormErrorTarget::Field(&path)
+                } else {
+                    FormErrorTarget::Form
+                };
+                context.add_error(target, error);
+            }
+        }
+        for error in self.non_field_errors {
+            context.add_error(FormErrorTarget::Form, error);
+        }
+    }
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "validation failed")?;
+        let mut separator = ": ";
+        for (path, field_errors) in &self.field_errors {
+            for error in field_errors {
+                write!(f, "{separator}`{path}`: {error}")?;
+                separator = "; ";
+            }
+        }
+        for error in &self.non_field_errors {
+            write!(f, "{separator}{error}")?;
+            separator = "; ";
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl_into_cot_error!(ValidationErrors, UNPROCESSABLE_ENTITY);
+
+#[cfg(feature = "json")]
+impl IntoResponse for ValidationErrors {
+    /// Creates a `422 Unprocessable Entity` response with the errors
+    /// serialized as JSON.
+    fn into_response(self) -> crate::Result<Response> {
+        crate::json::Json(self)
+            .with_status(crate::StatusCode::UNPROCESSABLE_ENTITY)
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extend_nested() {
+        let mut item_errors = ValidationErrors::new();
+        item_errors.add_field_error("name", FormFieldValidationError::Required);
+        item_errors.add_non_field_error(FormFieldValidationError::from_static("Invalid item."));
+        let mut items_errors = ValidationErrors::new();
+        items_errors.extend_nested("[0]", item_errors);
+
+        let mut errors = ValidationErrors::new();
+        errors.extend_nested("items", items_errors);
+
+        assert_eq!(
+            errors.field_errors("items[0].name"),
+            [FormFieldValidationError::Required]
+        );
+        assert_eq!(
+            errors.field_errors("items[0]"),
+            [FormFieldValidationError::from_static("Invalid item.")]
+        );
+        assert!(errors.non_field_errors().is_empty());
+    }
+
+    #[test]
+    fn into_result() {
+        assert!(ValidationErrors::new().into_result().is_ok());
+
+        let mut errors = ValidationErrors::new();
+        errors.add_non_field_error(FormFieldValidationError::Required);
+        assert_eq!(errors.into_result().unwrap_err().len(), 1);
+    }
+
+    #[test]
+    fn display() {
+        let mut errors = ValidationErrors::new();
+        errors
+            .add_field_error("name", FormFieldValidationError::Required)
+            .add_non_field_error(FormFieldValidationError::from_static("Invalid data."));
+
+        assert_eq!(
+            errors.to_string(),
+            "validation failed: `name`: This field is required.; Invalid data."
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[cot::test]
+    async fn into_response() {
+        let mut errors = ValidationErrors::new();
+        errors.add_field_error("name", FormFieldValidationError::Required);
+
+        let response = errors.into_response().unwrap();
+        assert_eq!(response.status(), crate::StatusCode::UNPROCESSABLE_ENTITY);
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({
+                "fields": {
+                    "name": [{
+                        "code": "required",
+                        "message": "This field is required.",
+                        "params": {}
+                    }]
+                },
+                "non_field_errors": []
+            })
+        );
+    }
+}
@@ -0,0 +1,603 @@
+//! Generic views for common operations on models.
+//!
+//! The views in this module wire a model, a form, and a template together, so
+//! that the typical list, detail, create, update, and delete pages can be
+//! added with a few lines of code:
+//!
+//! * [`ListView`] displays a paginated list of objects,
+//! * [`DetailView`] displays a single object,
+//! * [`CreateView`] displays a form and creates a new object from it,
+//! * [`UpdateView`] displays a form pre-populated with an object and updates
+//!   the object with the submitted data,
+//! * [`DeleteView`] displays a confirmation page and deletes an object.
+//!
+//! The views that operate on a single object load it using the primary key
+//! from the request path, in the same way as the
+//! [`ExistingModel`] extractor.
+//!
+//! The pages are rendered with closures, which typically render a
+//! [`Template`](crate::Template), so the views can be used with any template
+//! engine and any page layout. Each closure gets the [`Urls`] of the request,
+//! so that the templates can link to other views.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::db::{Auto, model};
+//! use cot::form::Form;
+//! use cot::html::Html;
+//! use cot::router::{Route, Router};
+//! use cot::views::{CreateView, DetailView, ListView};
+//!
+//! #[derive(Debug, Clone, Form)]
+//! #[model]
+//! struct Post {
+//!     #[model(primary_key)]
+//!     id: Auto<i32>,
+//!     title: String,
+//! }
+//!
+//! let router = Router::with_urls([
+//!     Route::with_handler_and_name(
+//!         "/",
+//!         ListView::<Post>::new(|_urls, page| {
+//!             Ok(Html::new(format!("{} posts", page.total_count())))
+//!         }),
+//!         "post_list",
+//!     ),
+//!     Route::with_handler_and_name(
+//!         "/new/",
+//!         CreateView::<Post>::new(|_urls, _post, _form| Ok(Html::new("<form>...</form>")), "/"),
+//!         "post_create",
+//!     ),
+//!     Route::with_handler_and_name(
+//!         "/{id}/",
+//!         DetailView::<Post>::new(|_urls, post| Ok(Html::new(post.title.clone()))),
+//!         "post_detail",
+//!     ),
+//! ]);
+//! ```
+
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use derive_more::Debug;
+
+use crate::db::Model;
+use crate::db::query::{Expr, Query};
+use crate::error::MethodNotAllowed;
+use crate::form::{Form, FormContext, FormResult};
+use crate::html::Html;
+use crate::pagination::{DEFAULT_PER_PAGE, Page, PageParams, Paginator};
+use crate::request::extractors::ExistingModel;
+use crate::request::{Request, RequestExt};
+use crate::response::{IntoResponse, Redirect, Response};
+use crate::router::Urls;
+use crate::{Method, RequestHandler};
+
+type TemplateFn<T> = Arc<dyn Fn(&Urls, &T) -> crate::Result<Html> + Send + Sync>;
+
+type FormTemplateFn<M, C> = Arc<dyn Fn(&Urls, Option<&M>, &C) -> crate::Result<Html> + Send + Sync>;
+
+/// A view that displays a paginated list of objects.
+///
+/// The page number and the number of objects per page are read from the URL
+/// query parameters (see [`PageParams`]), and the objects are sorted by their
+/// primary key.
+///
+/// # Errors
+///
+/// The view returns a 404 Not Found error if the requested page doesn't exist,
+/// and a 405 Method Not Allowed error for requests other than `GET` and
+/// `HEAD`.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, model};
+/// use cot::html::Html;
+/// use cot::pagination::Page;
+/// use cot::router::{Route, Urls};
+/// use cot::views::ListView;
+/// use cot::Template;
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// #[derive(Template)]
+/// #[template(
+///     source = "{% for post in page.items() %}<p>{{ post.title }}</p>{% endfor %}",
+///     ext = "html"
+/// )]
+/// struct PostListTemplate<'a> {
+///     page: &'a Page<Post>,
+/// }
+///
+/// let view = ListView::new(|_urls: &Urls, page: &Page<Post>| {
+///     Ok(Html::new(PostListTemplate { page }.render()?))
+/// })
+/// .with_per_page(10);
+/// let route = Route::with_handler_and_name("/posts/", view, "post_list");
+/// ```
+#[derive(Debug)]
+pub struct ListView<M> {
+    query: Query<M>,
+    per_page: u64,
+    #[debug("..")]
+    template: TemplateFn<Page<M>>,
+}
+
+impl<M: Model> ListView<M> {
+    /// Creates a list view that displays all objects of the model, rendering
+    /// each page with the given closure.
+    #[must_use]
+    pub fn new<T>(template: T) -> Self
+    where
+        T: Fn(&Urls, &Page<M>) -> crate::Result<Html> + Send + Sync + 'static,
+    {
+        Self {
+            query: M::objects(),
+            per_page: DEFAULT_PER_PAGE,
+            template: Arc::new(template),
+        }
+    }
+
+    /// Sets the query used to fetch the objects, e.g. to only display some of
+    /// them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::db::{Auto, model, query};
+    /// use cot::html::Html;
+    /// use cot::views::ListView;
+    ///
+    /// #[model]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     published: bool,
+    /// }
+    ///
+    /// let view = ListView::new(|_urls, _page| Ok(Html::new("posts")))
+    ///     .with_query(query!(Post, $published == true).clone());
+    /// ```
+    #[must_use]
+    pub fn with_query(mut self, query: Query<M>) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Sets the default number of objects per page, used when it's not
+    /// specified in the URL query parameters.
+    ///
+    /// Defaults to [`DEFAULT_PER_PAGE`].
+    #[must_use]
+    pub fn with_per_page(mut self, per_page: u64) -> Self {
+        self.per_page = per_page;
+        self
+    }
+}
+
+impl<M: Model> Clone for ListView<M> {
+    fn clone(&self) -> Self {
+        Self {
+            query: self.query.clone(),
+            per_page: self.per_page,
+            template: Arc::clone(&self.template),
+        }
+    }
+}
+
+impl<M: Model + Send + Sync> RequestHandler for ListView<M> {
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        check_method(&request, &[Method::GET, Method::HEAD])?;
+
+        let params: PageParams = request.extract_from_head().await?;
+        let urls: Urls = request.extract_from_head().await?;
+        let page = Paginator::new(self.query.clone())
+            .with_per_page(params.per_page().unwrap_or(self.per_page))
+            .page(request.context().database(), params.page())
+            .await?;
+
+        (self.template)(&urls, &page)?.into_response()
+    }
+}
+
+/// A view that displays a single object.
+///
+/// The object is loaded using the primary key from the request path, in the
+/// same way as the [`ExistingModel`] extractor.
+///
+/// # Errors
+///
+/// The view returns a 404 Not Found error if the object doesn't exist, and a
+/// 405 Method Not Allowed error for requests other than `GET` and `HEAD`.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, model};
+/// use cot::html::Html;
+/// use cot::router::Route;
+/// use cot::views::DetailView;
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// let view = DetailView::new(|_urls, post: &Post| Ok(Html::new(post.title.clone())));
+/// let route = Route::with_handler_and_name("/posts/{id}/", view, "post_detail");
+/// ```
+#[derive(Debug)]
+pub struct DetailView<M> {
+    #[debug("..")]
+    template: TemplateFn<M>,
+}
+
+impl<M: Model> DetailView<M> {
+    /// Creates a detail view that renders the object with the given closure.
+    #[must_use]
+    pub fn new<T>(template: T) -> Self
+    where
+        T: Fn(&Urls, &M) -> crate::Result<Html> + Send + Sync + 'static,
+    {
+        Self {
+            template: Arc::new(template),
+        }
+    }
+}
+
+impl<M> Clone for DetailView<M> {
+    fn clone(&self) -> Self {
+        Self {
+            template: Arc::clone(&self.template),
+        }
+    }
+}
+
+impl<M> RequestHandler for DetailView<M>
+where
+    M: Model + Send + Sync,
+    M::PrimaryKey: FromStr + Send,
+{
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        check_method(&request, &[Method::GET, Method::HEAD])?;
+
+        let ExistingModel(object) = request.extract_from_head::<ExistingModel<M>>().await?;
+        let urls: Urls = request.extract_from_head().await?;
+
+        (self.template)(&urls, &object)?.into_response()
+    }
+}
+
+/// A view that displays a form and creates a new object from the submitted
+/// data.
+///
+/// The object is created from the form using the [`From`] implementation, so a
+/// model that implements [`Form`] itself can be used as the form directly
+/// (which is the default). After the object is saved, the view redirects to
+/// the success URL. If the form is invalid, it is displayed again, along with
+/// the validation errors.
+///
+/// The form template closure gets `None` as the object, as it doesn't exist
+/// yet; this allows using the same closure for [`UpdateView`].
+///
+/// # Errors
+///
+/// The view returns a 405 Method Not Allowed error for requests other than
+/// `GET`, `HEAD`, and `POST`.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, model};
+/// use cot::form::Form;
+/// use cot::html::Html;
+/// use cot::router::Route;
+/// use cot::views::CreateView;
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// #[derive(Form)]
+/// struct PostForm {
+///     title: String,
+/// }
+///
+/// impl From<PostForm> for Post {
+///     fn from(form: PostForm) -> Self {
+///         Self {
+///             id: Auto::auto(),
+///             title: form.title,
+///         }
+///     }
+/// }
+///
+/// let view = CreateView::<Post, PostForm>::new(
+///     |_urls, _post, _form| Ok(Html::new("<form>...</form>")),
+///     "/posts/",
+/// );
+/// let route = Route::with_handler_and_name("/posts/new/", view, "post_create");
+/// ```
+#[derive(Debug)]
+pub struct CreateView<M, F: Form = M> {
+    success_url: String,
+    #[debug("..")]
+    template: FormTemplateFn<M, F::Context>,
+    phantom_data: PhantomData<fn() -> F>,
+}
+
+impl<M: Model, F: Form> CreateView<M, F> {
+    /// Creates a view that renders the form with the given closure and
+    /// redirects to `success_url` after the object is created.
+    #[must_use]
+    pub fn new<T>(template: T, success_url: impl Into<String>) -> Self
+    where
+        T: Fn(&Urls, Option<&M>, &F::Context) -> crate::Result<Html> + Send + Sync + 'static,
+    {
+        Self {
+            success_url: success_url.into(),
+            template: Arc::new(template),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<M, F: Form> Clone for CreateView<M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            success_url: self.success_url.clone(),
+            template: Arc::clone(&self.template),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<M, F> RequestHandler for CreateView<M, F>
+where
+    M: Model + From<F> + Send + Sync,
+    F: Form + Send,
+{
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        let form_context = if request.method() == Method::GET || request.method() == Method::HEAD {
+            F::Context::new()
+        } else if request.method() == Method::POST {
+            match F::from_request(&mut request).await? {
+                FormResult::Ok(form) => {
+                    let mut object = M::from(form);
+                    object.insert(request.context().database()).await?;
+
+                    return Redirect::new(&self.success_url).into_response();
+                }
+                FormResult::ValidationError(context) => context,
+            }
+        } else {
+            return Err(MethodNotAllowed::new(request.method().clone()).into());
+        };
+
+        let urls: Urls = request.extract_from_head().await?;
+        (self.template)(&urls, None, &form_context)?.into_response()
+    }
+}
+
+/// A view that displays a form pre-populated with an existing object and
+/// updates the object with the submitted data.
+///
+/// The object is loaded using the primary key from the request path, in the
+/// same way as the [`ExistingModel`] extractor. The form is pre-populated
+/// using the [`From`] implementation converting the object into the form, and
+/// the object is rebuilt from the submitted form using the [`From`]
+/// implementation in the other direction, keeping its primary key. As with
+/// [`CreateView`], a model that implements [`Form`] itself can be used as the
+/// form directly (which is the default).
+///
+/// After the object is saved, the view redirects to the success URL. If the
+/// form is invalid, it is displayed again, along with the validation errors.
+///
+/// # Errors
+///
+/// The view returns a 404 Not Found error if the object doesn't exist, and a
+/// 405 Method Not Allowed error for requests other than `GET`, `HEAD`, and
+/// `POST`.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, model};
+/// use cot::form::Form;
+/// use cot::html::Html;
+/// use cot::router::Route;
+/// use cot::views::UpdateView;
+///
+/// #[derive(Debug, Clone, Form)]
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// let view = UpdateView::<Post>::new(
+///     |_urls, _post, _form| Ok(Html::new("<form>...</form>")),
+///     "/posts/",
+/// );
+/// let route = Route::with_handler_and_name("/posts/{id}/edit/", view, "post_update");
+/// ```
+#[derive(Debug)]
+pub struct UpdateView<M, F: Form = M> {
+    success_url: String,
+    #[debug("..")]
+    template: FormTemplateFn<M, F::Context>,
+    phantom_data: PhantomData<fn() -> F>,
+}
+
+impl<M: Model, F: Form> UpdateView<M, F> {
+    /// Creates a view that renders the form with the given closure and
+    /// redirects to `success_url` after the object is updated.
+    #[must_use]
+    pub fn new<T>(template: T, success_url: impl Into<String>) -> Self
+    where
+        T: Fn(&Urls, Option<&M>, &F::Context) -> crate::Result<Html> + Send + Sync + 'static,
+    {
+        Self {
+            success_url: success_url.into(),
+            template: Arc::new(template),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<M, F: Form> Clone for UpdateView<M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            success_url: self.success_url.clone(),
+            template: Arc::clone(&self.template),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<M, F> RequestHandler for UpdateView<M, F>
+where
+    M: Model + From<F> + Clone + Send + Sync,
+    M::PrimaryKey: FromStr + Send,
+    F: Form + From<M> + Send,
+{
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        let ExistingModel(object) = request.extract_from_head::<ExistingModel<M>>().await?;
+
+        let form_context = if request.method() == Method::GET || request.method() == Method::HEAD {
+            F::from(object.clone()).to_context().await
+        } else if request.method() == Method::POST {
+            match F::from_request(&mut request).await? {
+                FormResult::Ok(form) => {
+                    let mut updated = M::from(form);
+                    updated.set_primary_key(object.primary_key().clone());
+                    updated.update(request.context().database()).await?;
+
+                    return Redirect::new(&self.success_url).into_response();
+                }
+                FormResult::ValidationError(context) => context,
+            }
+        } else {
+            return Err(MethodNotAllowed::new(request.method().clone()).into());
+        };
+
+        let urls: Urls = request.extract_from_head().await?;
+        (self.template)(&urls, Some(&object), &form_context)?.into_response()
+    }
+}
+
+/// A view that displays a confirmation page and deletes an object.
+///
+/// The object is loaded using the primary key from the request path, in the
+/// same way as the [`ExistingModel`] extractor. A `GET` request renders the
+/// confirmation page, which should contain a form submitting a `POST` request
+/// to the same URL. The `POST` request deletes the object and redirects to the
+/// success URL.
+///
+/// # Errors
+///
+/// The view returns a 404 Not Found error if the object doesn't exist, and a
+/// 405 Method Not Allowed error for requests other than `GET`, `HEAD`, and
+/// `POST`.
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, model};
+/// use cot::html::Html;
+/// use cot::router::Route;
+/// use cot::views::DeleteView;
+///
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     title: String,
+/// }
+///
+/// let view = DeleteView::new(
+///     |_urls, post: &Post| Ok(Html::new(format!("Delete {}?", post.title))),
+///     "/posts/",
+/// );
+/// let route = Route::with_handler_and_name("/posts/{id}/delete/", view, "post_delete");
+/// ```
+#[derive(Debug)]
+pub struct DeleteView<M> {
+    success_url: String,
+    #[debug("..")]
+    template: TemplateFn<M>,
+}
+
+impl<M: Model> DeleteView<M> {
+    /// Creates a view that renders the confirmation page with the given
+    /// closure and redirects to `success_url` after the object is deleted.
+    #[must_use]
+    pub fn new<T>(template: T, success_url: impl Into<String>) -> Self
+    where
+        T: Fn(&Urls, &M) -> crate::Result<Html> + Send + Sync + 'static,
+    {
+        Self {
+            success_url: success_url.into(),
+            template: Arc::new(template),
+        }
+    }
+}
+
+impl<M> Clone for DeleteView<M> {
+    fn clone(&self) -> Self {
+        Self {
+            success_url: self.success_url.clone(),
+            template: Arc::clone(&self.template),
+        }
+    }
+}
+
+impl<M> RequestHandler for DeleteView<M>
+where
+    M: Model + Send + Sync,
+    M::PrimaryKey: FromStr + Send,
+{
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        check_method(&request, &[Method::GET, Method::HEAD, Method::POST])?;
+
+        let ExistingModel(object) = request.extract_from_head::<ExistingModel<M>>().await?;
+
+        if request.method() == Method::POST {
+            M::objects()
+                .filter(Expr::eq(
+                    Expr::field(M::PRIMARY_KEY_NAME),
+                    Expr::value(object.primary_key().clone()),
+                ))
+                .delete(request.context().database())
+                .await?;
+
+            return Redirect::new(&self.success_url).into_response();
+        }
+
+        let urls: Urls = request.extract_from_head().await?;
+        (self.template)(&urls, &object)?.into_response()
+    }
+}
+
+fn check_method(request: &Request, allowed: &[Method]) -> crate::Result<()> {
+    if allowed.contains(request.method()) {
+        Ok(())
+    } else {
+        Err(MethodNotAllowed::new(request.method().clone()).into())
+    }
+}
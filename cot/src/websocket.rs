@@ -0,0 +1,436 @@
+//! WebSocket support.
+//!
+//! This module allows request handlers to upgrade HTTP requests to WebSocket
+//! connections and exchange messages with the clients. The simplest way to
+//! handle WebSocket connections is to use
+//! [`Route::with_websocket_handler`](crate::router::Route::with_websocket_handler),
+//! which upgrades every request to the route and passes the connection to the
+//! handler. If the handler needs other data from the request (such as the
+//! session or the database), the [`WebSocketUpgrade`] extractor can be used in
+//! a regular request handler instead.
+//!
+//! The requests go through the middleware stack just like any other request,
+//! so, for instance, the session and authentication data are available when
+//! the request is upgraded.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::router::{Route, Router};
+//! use cot::websocket::{Message, WebSocket};
+//!
+//! async fn echo(mut socket: WebSocket) -> cot::Result<()> {
+//!     while let Some(message) = socket.recv().await {
+//!         if let Message::Text(text) = message? {
+//!             socket.send(Message::Text(text)).await?;
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//!
+//! let router = Router::with_urls([Route::with_websocket_handler("/ws/", echo)]);
+//! ```
+
+use std::borrow::Cow;
+use std::future::Future;
+
+use axum::extract::FromRequestParts;
+use axum::extract::ws;
+use axum::extract::ws::rejection::WebSocketUpgradeRejection;
+use bytes::Bytes;
+use cot_core::error::impl_into_cot_error;
+use thiserror::Error;
+use tracing::error;
+
+use crate::RequestHandler;
+use crate::request::extractors::FromRequestHead;
+use crate::request::{Request, RequestHead};
+use crate::response::Response;
+use crate::{Body, Error};
+
+/// An extractor upgrading the request to a WebSocket connection.
+///
+/// The connection is established once the response returned by
+/// [`Self::on_upgrade`] is sent to the client. If the request is not a valid
+/// WebSocket upgrade request, the extraction fails with a client error (for
+/// instance, `400 Bad Request` or `405 Method Not Allowed`).
+///
+/// # Examples
+///
+/// ```
+/// use cot::response::Response;
+/// use cot::session::Session;
+/// use cot::websocket::{Message, WebSocket, WebSocketUpgrade};
+///
+/// async fn chat(upgrade: WebSocketUpgrade, session: Session) -> cot::Result<Response> {
+///     let name: String = session.get("name").await?.unwrap_or_default();
+///
+///     Ok(upgrade.on_upgrade(async move |mut socket: WebSocket| {
+///         socket.send(Message::Text(format!("Hello, {name}!"))).await
+///     }))
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WebSocketUpgrade(ws::WebSocketUpgrade);
+
+impl WebSocketUpgrade {
+    /// Sets the maximum size of an incoming message. By default, the limit
+    /// is 64 MiB.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::websocket::{WebSocket, WebSocketUpgrade};
+    ///
+    /// async fn handler(upgrade: WebSocketUpgrade) -> Response {
+    ///     upgrade
+    ///         .max_message_size(1024 * 1024)
+    ///         .on_upgrade(async |socket: WebSocket| Ok(()))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn max_message_size(self, size: usize) -> Self {
+        Self(self.0.max_message_size(size))
+    }
+
+    /// Sets the subprotocols supported by the server. The first one of these
+    /// that is requested by the client is selected and sent back in the
+    /// response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::websocket::{WebSocket, WebSocketUpgrade};
+    ///
+    /// async fn handler(upgrade: WebSocketUpgrade) -> Response {
+    ///     upgrade
+    ///         .protocols(["graphql-ws", "graphql-transport-ws"])
+    ///         .on_upgrade(async |socket: WebSocket| Ok(()))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn protocols<I>(self, protocols: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        Self(self.0.protocols(protocols))
+    }
+
+    /// Finishes upgrading the connection and calls the given callback with
+    /// the WebSocket connection.
+    ///
+    /// The returned response must be sent back to the client for the
+    /// connection to be established. The callback is run in a separate task;
+    /// if it returns an error, the error is logged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::response::Response;
+    /// use cot::websocket::{Message, WebSocket, WebSocketUpgrade};
+    ///
+    /// async fn handler(upgrade: WebSocketUpgrade) -> Response {
+    ///     upgrade.on_upgrade(async |mut socket: WebSocket| {
+    ///         socket.send(Message::Text("Hello!".to_owned())).await
+    ///     })
+    /// }
+    /// ```
+    #[must_use = "the response must be returned for the connection to be established"]
+    pub fn on_upgrade<F, Fut>(self, callback: F) -> Response
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = crate::Result<()>> + Send + 'static,
+    {
+        self.0
+            .on_failed_upgrade(|error| {
+                error!("failed to upgrade to a WebSocket connection: {error}");
+            })
+            .on_upgrade(move |socket| async move {
+                if let Err(error) = callback(WebSocket(socket)).await {
+                    error!("error in the WebSocket handler: {error}");
+                }
+            })
+            .map(Body::axum)
+    }
+}
+
+impl FromRequestHead for WebSocketUpgrade {
+    async fn from_request_head(head: &RequestHead) -> crate::Result<Self> {
+        // the upgrade future is shared between the clones, so the original
+        // request head doesn't need to be modified
+        let mut parts = head.clone();
+        let upgrade = ws::WebSocketUpgrade::from_request_parts(&mut parts, &())
+            .await
+            .map_err(InvalidUpgradeRequest)?;
+
+        Ok(Self(upgrade))
+    }
+}
+
+/// An error returned when a request can't be upgraded to a WebSocket
+/// connection.
+#[derive(Debug, Error)]
+#[error("invalid WebSocket upgrade request: {0}")]
+struct InvalidUpgradeRequest(WebSocketUpgradeRejection);
+
+impl From<InvalidUpgradeRequest> for Error {
+    fn from(error: InvalidUpgradeRequest) -> Self {
+        let status_code = error.0.status();
+        Error::with_status(error, status_code)
+    }
+}
+
+/// A WebSocket connection.
+///
+/// # Examples
+///
+/// ```
+/// use cot::websocket::{Message, WebSocket};
+///
+/// async fn handler(mut socket: WebSocket) -> cot::Result<()> {
+///     while let Some(message) = socket.recv().await {
+///         println!("received: {:?}", message?);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WebSocket(ws::WebSocket);
+
+impl WebSocket {
+    /// Receives the next message. Returns `None` if the connection has been
+    /// closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message couldn't be received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::{Message, WebSocket};
+    ///
+    /// async fn handler(mut socket: WebSocket) -> cot::Result<()> {
+    ///     if let Some(Message::Text(text)) = socket.recv().await.transpose()? {
+    ///         println!("received: {text}");
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn recv(&mut self) -> Option<crate::Result<Message>> {
+        let message = self.0.recv().await?;
+        Some(
+            message
+                .map(Message::from_axum)
+                .map_err(|error| WebSocketError(error).into()),
+        )
+    }
+
+    /// Sends a message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message couldn't be sent, for instance because
+    /// the connection has been closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::{Message, WebSocket};
+    ///
+    /// async fn handler(mut socket: WebSocket) -> cot::Result<()> {
+    ///     socket.send(Message::Text("Hello!".to_owned())).await
+    /// }
+    /// ```
+    pub async fn send(&mut self, message: Message) -> crate::Result<()> {
+        self.0
+            .send(message.into_axum())
+            .await
+            .map_err(WebSocketError)?;
+        Ok(())
+    }
+
+    /// Returns the subprotocol selected for the connection, if any.
+    ///
+    /// See [`WebSocketUpgrade::protocols`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::websocket::WebSocket;
+    ///
+    /// async fn handler(socket: WebSocket) -> cot::Result<()> {
+    ///     if let Some(protocol) = socket.protocol() {
+    ///         println!("using {protocol}");
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn protocol(&self) -> Option<&str> {
+        self.0
+            .protocol()
+            .and_then(|protocol| protocol.to_str().ok())
+    }
+}
+
+/// An error that occurred while sending or receiving a WebSocket message.
+#[derive(Debug, Error)]
+#[error("WebSocket error: {0}")]
+struct WebSocketError(axum::Error);
+impl_into_cot_error!(WebSocketError);
+
+/// A WebSocket message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Message {
+    /// A text message.
+    Text(String),
+    /// A binary message.
+    Binary(Bytes),
+    /// A ping message. The pongs are sent automatically in response to the
+    /// pings, so there's no need to handle these manually.
+    Ping(Bytes),
+    /// A pong message.
+    Pong(Bytes),
+    /// A close message, optionally containing the reason of closing the
+    /// connection.
+    Close(Option<CloseFrame>),
+}
+
+impl Message {
+    fn from_axum(message: ws::Message) -> Self {
+        match message {
+            ws::Message::Text(text) => Self::Text(text.as_str().to_owned()),
+            ws::Message::Binary(data) => Self::Binary(data),
+            ws::Message::Ping(data) => Self::Ping(data),
+            ws::Message::Pong(data) => Self::Pong(data),
+            ws::Message::Close(frame) => Self::Close(frame.map(|frame| CloseFrame {
+                code: frame.code,
+                reason: frame.reason.as_str().to_owned(),
+            })),
+        }
+    }
+
+    fn into_axum(self) -> ws::Message {
+        match self {
+            Self::Text(text) => ws::Message::Text(text.into()),
+            Self::Binary(data) => ws::Message::Binary(data),
+            Self::Ping(data) => ws::Message::Ping(data),
+            Self::Pong(data) => ws::Message::Pong(data),
+            Self::Close(frame) => ws::Message::Close(frame.map(|frame| ws::CloseFrame {
+                code: frame.code,
+                reason: frame.reason.into(),
+            })),
+        }
+    }
+}
+
+/// The reason of closing a WebSocket connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseFrame {
+    /// The close code, as defined in [RFC 6455, section 7.4](https://datatracker.ietf.org/doc/html/rfc6455#section-7.4).
+    pub code: u16,
+    /// The human-readable reason of closing the connection.
+    pub reason: String,
+}
+
+/// A request handler upgrading every request to a WebSocket connection, used
+/// by [`Route::with_websocket_handler`](crate::router::Route::with_websocket_handler).
+pub(crate) struct WebSocketRequestHandler<H>(pub(crate) H);
+
+impl<H, Fut> RequestHandler for WebSocketRequestHandler<H>
+where
+    H: FnOnce(WebSocket) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = crate::Result<()>> + Send + 'static,
+{
+    async fn handle(&self, request: Request) -> crate::Result<Response> {
+        let (head, _body) = request.into_parts();
+        let upgrade = WebSocketUpgrade::from_request_head(&head).await?;
+
+        Ok(upgrade.on_upgrade(self.0.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StatusCode;
+    use crate::test::TestRequestBuilder;
+
+    fn upgrade_request() -> Request {
+        let mut request = TestRequestBuilder::get("/ws/").build();
+        let headers = request.headers_mut();
+        headers.insert(http::header::CONNECTION, "upgrade".parse().unwrap());
+        headers.insert(http::header::UPGRADE, "websocket".parse().unwrap());
+        headers.insert(http::header::SEC_WEBSOCKET_VERSION, "13".parse().unwrap());
+        headers.insert(
+            http::header::SEC_WEBSOCKET_KEY,
+            "dGhlIHNhbXBsZSBub25jZQ==".parse().unwrap(),
+        );
+        request
+    }
+
+    #[cot::test]
+    async fn upgrade_not_websocket_request() {
+        let request = TestRequestBuilder::get("/ws/").build();
+        let (head, _body) = request.into_parts();
+
+        let error = WebSocketUpgrade::from_request_head(&head)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cot::test]
+    async fn upgrade_wrong_method() {
+        let mut request = upgrade_request();
+        *request.method_mut() = http::Method::POST;
+        let (head, _body) = request.into_parts();
+
+        let error = WebSocketUpgrade::from_request_head(&head)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[cot::test]
+    async fn upgrade_connection_not_upgradable() {
+        // requests not coming from a real HTTP connection can't be upgraded
+        let (head, _body) = upgrade_request().into_parts();
+
+        let error = WebSocketUpgrade::from_request_head(&head)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::UPGRADE_REQUIRED);
+    }
+
+    #[test]
+    fn message_axum_roundtrip() {
+        let messages = [
+            Message::Text("hello".to_owned()),
+            Message::Binary(Bytes::from_static(b"\x00\x01")),
+            Message::Ping(Bytes::from_static(b"ping")),
+            Message::Pong(Bytes::from_static(b"pong")),
+            Message::Close(None),
+            Message::Close(Some(CloseFrame {
+                code: 1000,
+                reason: "bye".to_owned(),
+            })),
+        ];
+
+        for message in messages {
+            assert_eq!(Message::from_axum(message.clone().into_axum()), message);
+        }
+    }
+}
@@ -0,0 +1,465 @@
+//! Workflow states for models.
+//!
+//! Many models go through a series of states during their lifetime: a blog
+//! post is written as a draft, sent for a review, and then published; an
+//! order is placed, paid, and shipped. A [`Workflow`] describes these states
+//! and the allowed transitions between them, so that a model can't jump from
+//! a draft straight to being published, and so that only the users with the
+//! right permissions can perform each transition.
+//!
+//! The workflow is declared with the `workflow` option of the
+//! [`#[model]`](crate::db::model) attribute. Each `a -> b` pair is an allowed
+//! transition, and the first state is the initial one. The current state is
+//! stored in the `state` field of the model, which has to be a [`String`]; a
+//! different field can be chosen with the `field = "name"` option.
+//!
+//! Each transition requires the user to have a permission (see
+//! [`User::has_permission`]) named `<app>.<model>.<from>_to_<to>`, such as
+//! `blog.post.review_to_published`. Whenever a transition is performed with
+//! [`Workflow::transition`], a [`WorkflowTransitioned`] message is published
+//! to the project [`Notifier`](crate::notify::Notifier), so that other parts
+//! of the project can react to it. Models that also derive
+//! [`AdminModel`](crate::admin::AdminModel) with the `#[admin(workflow)]`
+//! attribute get a button for each available transition in the admin panel.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::auth::User;
+//! use cot::db::{Auto, model};
+//! use cot::workflow::Workflow;
+//!
+//! #[model(workflow(draft -> review -> published, review -> draft))]
+//! struct Post {
+//!     #[model(primary_key)]
+//!     id: Auto<i32>,
+//!     title: String,
+//!     state: String,
+//! }
+//!
+//! struct Editor;
+//!
+//! impl User for Editor {
+//!     fn is_authenticated(&self) -> bool {
+//!         true
+//!     }
+//!
+//!     fn has_permission(&self, permission: &str) -> bool {
+//!         // editors can do anything but publishing
+//!         !permission.ends_with("_to_published")
+//!     }
+//! }
+//!
+//! let mut post = Post {
+//!     id: Auto::auto(),
+//!     title: "Hello".to_owned(),
+//!     state: Post::INITIAL_STATE.to_owned(),
+//! };
+//! assert_eq!(post.workflow_state(), "draft");
+//!
+//! post.check_transition("review", &Editor).unwrap();
+//! post.set_workflow_state("review");
+//!
+//! let available: Vec<_> = post
+//!     .available_transitions(&Editor)
+//!     .iter()
+//!     .map(|transition| transition.target_state())
+//!     .collect();
+//! assert_eq!(available, ["draft"]);
+//! assert!(post.check_transition("published", &Editor).is_err());
+//! ```
+
+#[cfg(feature = "db")]
+use std::fmt::{Debug, Formatter};
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::auth::User;
+#[cfg(feature = "db")]
+use crate::db::{DatabaseBackend, Model};
+#[cfg(feature = "db")]
+use crate::notify::Notifier;
+use crate::{Error, StatusCode};
+
+/// A transition between two states of a [`Workflow`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::workflow::WorkflowTransition;
+///
+/// let transition = WorkflowTransition::new("draft", "published", "blog.post.draft_to_published");
+/// assert_eq!(transition.source_state(), "draft");
+/// assert_eq!(transition.target_state(), "published");
+/// assert_eq!(transition.permission(), "blog.post.draft_to_published");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct WorkflowTransition {
+    from: &'static str,
+    to: &'static str,
+    permission: &'static str,
+}
+
+impl WorkflowTransition {
+    /// Creates a new transition from one state to another, requiring the
+    /// given permission.
+    #[must_use]
+    pub const fn new(from: &'static str, to: &'static str, permission: &'static str) -> Self {
+        Self {
+            from,
+            to,
+            permission,
+        }
+    }
+
+    /// Returns the state the transition starts from.
+    #[must_use]
+    pub const fn source_state(&self) -> &'static str {
+        self.from
+    }
+
+    /// Returns the state the transition leads to.
+    #[must_use]
+    pub const fn target_state(&self) -> &'static str {
+        self.to
+    }
+
+    /// Returns the permission the user needs to perform the transition.
+    #[must_use]
+    pub const fn permission(&self) -> &'static str {
+        self.permission
+    }
+}
+
+/// An error that occurs when a workflow transition can't be performed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum WorkflowError {
+    /// There is no transition between the given states.
+    #[error("cannot transition from `{from}` to `{to}`")]
+    InvalidTransition {
+        /// The current state.
+        from: String,
+        /// The requested state.
+        to: String,
+    },
+    /// The user doesn't have the permission required by the transition.
+    #[error("the `{permission}` permission is required to transition from `{from}` to `{to}`")]
+    PermissionDenied {
+        /// The current state.
+        from: &'static str,
+        /// The requested state.
+        to: &'static str,
+        /// The missing permission.
+        permission: &'static str,
+    },
+}
+
+impl From<WorkflowError> for Error {
+    fn from(error: WorkflowError) -> Self {
+        let status_code = match &error {
+            WorkflowError::InvalidTransition { .. } => StatusCode::CONFLICT,
+            WorkflowError::PermissionDenied { .. } => StatusCode::FORBIDDEN,
+        };
+        Error::with_status(error, status_code)
+    }
+}
+
+/// A model whose instances go through a series of states.
+///
+/// This is typically implemented using the `workflow` option of the
+/// [`#[model]`](crate::db::model) attribute. See the [module-level
+/// documentation](self) for more information.
+#[async_trait]
+pub trait Workflow {
+    /// All the states of the workflow, in the order of declaration.
+    const STATES: &'static [&'static str];
+
+    /// The state new instances start in.
+    const INITIAL_STATE: &'static str;
+
+    /// The allowed transitions between the states.
+    const TRANSITIONS: &'static [WorkflowTransition];
+
+    /// Returns the current state of the instance.
+    fn workflow_state(&self) -> &str;
+
+    /// Sets the current state of the instance, without checking whether the
+    /// transition is allowed.
+    ///
+    /// This is useful for data migrations and tests; in the application code,
+    /// [`Self::transition`] should be used instead.
+    fn set_workflow_state(&mut self, state: &'static str);
+
+    /// Returns the transitions from the current state that the user is
+    /// allowed to perform.
+    fn available_transitions(&self, user: &dyn User) -> Vec<WorkflowTransition> {
+        let state = self.workflow_state();
+        Self::TRANSITIONS
+            .iter()
+            .filter(|transition| {
+                transition.source_state() == state && user.has_permission(transition.permission())
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Checks whether the user is allowed to transition the instance from its
+    /// current state to the given one, and returns the matching transition.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WorkflowError::InvalidTransition`] if there is no transition
+    /// from the current state to the given one.
+    ///
+    /// Returns [`WorkflowError::PermissionDenied`] if the user doesn't have the
+    /// permission required by the transition.
+    fn check_transition(
+        &self,
+        to: &str,
+        user: &dyn User,
+    ) -> Result<WorkflowTransition, WorkflowError> {
+        let state = self.workflow_state();
+        let transition = Self::TRANSITIONS
+            .iter()
+            .find(|transition| {
+                transition.source_state() == state && transition.target_state() == to
+            })
+            .ok_or_else(|| WorkflowError::InvalidTransition {
+                from: state.to_owned(),
+                to: to.to_owned(),
+            })?;
+
+        if !user.has_permission(transition.permission()) {
+            return Err(WorkflowError::PermissionDenied {
+                from: transition.source_state(),
+                to: transition.target_state(),
+                permission: transition.permission(),
+            });
+        }
+        Ok(*transition)
+    }
+
+    /// Transitions the instance to the given state, saves it to the database,
+    /// and publishes a [`WorkflowTransitioned`] message to the notifier.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transition is not allowed for the user (see
+    /// [`Self::check_transition`]), or if the instance could not be saved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::Auth;
+    /// use cot::db::{Auto, Database, model};
+    /// use cot::notify::Notifier;
+    /// use cot::workflow::Workflow;
+    ///
+    /// #[model(workflow(draft -> published))]
+    /// struct Post {
+    ///     #[model(primary_key)]
+    ///     id: Auto<i32>,
+    ///     state: String,
+    /// }
+    ///
+    /// async fn publish(
+    ///     post: &mut Post,
+    ///     db: &Database,
+    ///     notifier: &Notifier,
+    ///     auth: &Auth,
+    /// ) -> cot::Result<()> {
+    ///     post.transition(db, notifier, &*auth.user(), "published")
+    ///         .await
+    /// }
+    /// ```
+    #[cfg(feature = "db")]
+    async fn transition<DB: DatabaseBackend>(
+        &mut self,
+        db: &DB,
+        notifier: &Notifier,
+        user: &(dyn User + Send + Sync),
+        to: &str,
+    ) -> crate::Result<()>
+    where
+        Self: Model,
+        Self::PrimaryKey: Send + Sync,
+    {
+        let transition = self.check_transition(to, user)?;
+        self.set_workflow_state(transition.target_state());
+        self.save(db).await?;
+
+        notifier.publish(WorkflowTransitioned::<Self> {
+            primary_key: self.primary_key().clone(),
+            transition,
+        });
+        Ok(())
+    }
+}
+
+/// A message published to the [`Notifier`] whenever an instance of the model
+/// `M` is transitioned with [`Workflow::transition`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::db::{Auto, model};
+/// use cot::notify::Subscription;
+/// use cot::workflow::WorkflowTransitioned;
+///
+/// #[model(workflow(draft -> published))]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i32>,
+///     state: String,
+/// }
+///
+/// async fn on_published(mut subscription: Subscription<WorkflowTransitioned<Post>>) {
+///     while let Some(message) = subscription.recv().await {
+///         if message.transition().target_state() == "published" {
+///             println!("post {} was published", message.primary_key());
+///         }
+///     }
+/// }
+/// ```
+#[cfg(feature = "db")]
+pub struct WorkflowTransitioned<M: Model> {
+    primary_key: M::PrimaryKey,
+    transition: WorkflowTransition,
+}
+
+#[cfg(feature = "db")]
+impl<M: Model> WorkflowTransitioned<M> {
+    /// Returns the primary key of the transitioned instance.
+    #[must_use]
+    pub fn primary_key(&self) -> &M::PrimaryKey {
+        &self.primary_key
+    }
+
+    /// Returns the performed transition.
+    #[must_use]
+    pub fn transition(&self) -> WorkflowTransition {
+        self.transition
+    }
+}
+
+#[cfg(feature = "db")]
+impl<M: Model> Clone for WorkflowTransitioned<M> {
+    fn clone(&self) -> Self {
+        Self {
+            primary_key: self.primary_key.clone(),
+            transition: self.transition,
+        }
+    }
+}
+
+#[cfg(feature = "db")]
+impl<M: Model> Debug for WorkflowTransitioned<M>
+where
+    M::PrimaryKey: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkflowTransitioned")
+            .field("primary_key", &self.primary_key)
+            .field("transition", &self.transition)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Post {
+        state: String,
+    }
+
+    impl Workflow for Post {
+        const STATES: &'static [&'static str] = &["draft", "review", "published"];
+        const INITIAL_STATE: &'static str = "draft";
+        const TRANSITIONS: &'static [WorkflowTransition] = &[
+            WorkflowTransition::new("draft", "review", "blog.post.draft_to_review"),
+            WorkflowTransition::new("review", "published", "blog.post.review_to_published"),
+            WorkflowTransition::new("review", "draft", "blog.post.review_to_draft"),
+        ];
+
+        fn workflow_state(&self) -> &str {
+            &self.state
+        }
+
+        fn set_workflow_state(&mut self, state: &'static str) {
+            self.state = state.to_owned();
+        }
+    }
+
+    struct Editor;
+
+    impl User for Editor {
+        fn has_permission(&self, permission: &str) -> bool {
+            permission != "blog.post.review_to_published"
+        }
+    }
+
+    fn post(state: &str) -> Post {
+        Post {
+            state: state.to_owned(),
+        }
+    }
+
+    #[test]
+    fn available_transitions() {
+        assert_eq!(
+            post("review").available_transitions(&Editor),
+            [WorkflowTransition::new(
+                "review",
+                "draft",
+                "blog.post.review_to_draft"
+            )]
+        );
+        assert!(post("published").available_transitions(&Editor).is_empty());
+    }
+
+    #[test]
+    fn check_transition() {
+        assert_eq!(
+            post("draft").check_transition("review", &Editor).unwrap(),
+            Post::TRANSITIONS[0]
+        );
+        assert_eq!(
+            post("draft")
+                .check_transition("published", &Editor)
+                .unwrap_err(),
+            WorkflowError::InvalidTransition {
+                from: "draft".to_owned(),
+                to: "published".to_owned(),
+            }
+        );
+        assert_eq!(
+            post("review")
+                .check_transition("published", &Editor)
+                .unwrap_err(),
+            WorkflowError::PermissionDenied {
+                from: "review",
+                to: "published",
+                permission: "blog.post.review_to_published",
+            }
+        );
+    }
+
+    #[test]
+    fn error_status_code() {
+        let error = Error::from(WorkflowError::InvalidTransition {
+            from: "draft".to_owned(),
+            to: "published".to_owned(),
+        });
+        assert_eq!(error.status_code(), StatusCode::CONFLICT);
+
+        let error = Error::from(
+            post("review")
+                .check_transition("published", &Editor)
+                .unwrap_err(),
+        );
+        assert_eq!(error.status_code(), StatusCode::FORBIDDEN);
+    }
+}
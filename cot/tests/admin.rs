@@ -10,7 +10,7 @@ use cot::config::{
 use cot::middleware::{AuthMiddleware, SessionMiddleware};
 use cot::project::{MiddlewareContext, RegisterAppsContext, RootHandler};
 use cot::static_files::StaticFilesMiddleware;
-use cot::test::{TestServer, TestServerBuilder};
+use cot::test::{TestServer, TestServerBuilder, webdriver_url};
 use cot::{App, AppBuilder, Project, ProjectContext};
 use fantoccini::{Client, ClientBuilder, Locator};
 
@@ -53,7 +53,7 @@ impl Project for AdminProject {
 
     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
         apps.register(DatabaseUserApp::new());
-        apps.register_with_views(AdminApp::new(), "/admin");
+        apps.register_with_views(AdminApp::default(), "/admin");
         apps.register(HelloApp);
     }
 
@@ -146,7 +146,7 @@ async fn login_with(
     username: &str,
     password: &str,
 ) -> Result<(), Box<dyn Error>> {
-    driver.goto(&format!("{}/admin/", server.url())).await?;
+    driver.goto(&server.url_for("/admin/")).await?;
 
     let username_form = driver.find(Locator::Id("username")).await?;
     username_form.send_keys(username).await?;
@@ -159,7 +159,5 @@ async fn login_with(
 }
 
 async fn create_webdriver() -> Result<Client, Box<dyn Error>> {
-    Ok(ClientBuilder::native()
-        .connect("http://localhost:4444")
-        .await?)
+    Ok(ClientBuilder::native().connect(&webdriver_url()).await?)
 }
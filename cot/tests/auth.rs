@@ -1,8 +1,10 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 
 use cot::auth::Auth;
-use cot::auth::db::{DatabaseUser, DatabaseUserCredentials};
-use cot::common_types::Password;
+use cot::auth::db::{DatabaseUser, DatabaseUserCredentials, Group, Permission};
+use cot::common_types::{Email, Password};
+use cot::db::Model;
 use cot::request::RequestExt;
 use cot::test::{TestDatabase, TestRequestBuilder};
 
@@ -66,3 +68,112 @@ async fn database_user(test_db: &mut TestDatabase) {
     auth.logout().await.unwrap();
     assert!(!auth.user().is_authenticated());
 }
+
+#[cot_macros::dbtest]
+async fn database_user_email(test_db: &mut TestDatabase) {
+    test_db.with_auth().run_migrations().await;
+
+    let email = Email::new("testuser@example.com").unwrap();
+    let mut user = DatabaseUser::create_user(
+        &**test_db,
+        "testuser".to_string(),
+        &Password::new("password123"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(user.email(), None);
+    assert!(
+        DatabaseUser::find_by_email(&**test_db, &email)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    user.set_email(Some(email.clone()));
+    user.save(&**test_db).await.unwrap();
+
+    let users = DatabaseUser::find_by_email(&**test_db, &email)
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].email(), Some(&email));
+    assert!(!users[0].is_email_confirmed());
+
+    user.confirm_email();
+    user.save(&**test_db).await.unwrap();
+
+    let user = DatabaseUser::get_by_id(&**test_db, user.id())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(user.is_email_confirmed());
+    assert!(user.email_confirmed_at().is_some());
+}
+
+#[cot_macros::dbtest]
+async fn database_user_permissions(test_db: &mut TestDatabase) {
+    test_db.with_auth().run_migrations().await;
+
+    let user = DatabaseUser::create_user(
+        &**test_db,
+        "testuser".to_string(),
+        &Password::new("password123"),
+    )
+    .await
+    .unwrap();
+    assert!(user.permissions(&**test_db).await.unwrap().is_empty());
+
+    // Permission granted directly
+    let view_post = Permission::get_or_create(&**test_db, "blog.view_post")
+        .await
+        .unwrap();
+    view_post.users().add(&**test_db, &user).await.unwrap();
+    assert!(user.has_perm(&**test_db, "blog.view_post").await.unwrap());
+    assert!(!user.has_perm(&**test_db, "blog.change_post").await.unwrap());
+
+    // Permissions granted through a group
+    let change_post = Permission::get_or_create(&**test_db, "blog.change_post")
+        .await
+        .unwrap();
+    let editors = Group::create(&**test_db, "editors").await.unwrap();
+    editors
+        .permissions()
+        .add(&**test_db, &change_post)
+        .await
+        .unwrap();
+    editors
+        .permissions()
+        .add(&**test_db, &view_post)
+        .await
+        .unwrap();
+    editors.users().add(&**test_db, &user).await.unwrap();
+
+    let groups = user.groups(&**test_db).await.unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].name(), "editors");
+    assert_eq!(
+        user.permissions(&**test_db).await.unwrap(),
+        HashSet::from(["blog.view_post".to_owned(), "blog.change_post".to_owned()])
+    );
+
+    // The users returned by the auth backend have their permissions loaded
+    let mut request_builder = TestRequestBuilder::get("/");
+    request_builder.with_db_auth(test_db.database()).await;
+    let mut request = request_builder.with_session().build();
+    let auth: Auth = request.extract_from_head().await.unwrap();
+    let auth_user = auth
+        .authenticate(&DatabaseUserCredentials::new(
+            "testuser".to_string(),
+            Password::new("password123"),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(auth_user.has_permission("blog.change_post"));
+    assert!(!auth_user.has_permission("blog.delete_post"));
+
+    // Removing the user from the group revokes the group permissions
+    editors.users().clear(&**test_db).await.unwrap();
+    assert!(user.has_perm(&**test_db, "blog.view_post").await.unwrap());
+    assert!(!user.has_perm(&**test_db, "blog.change_post").await.unwrap());
+}
@@ -1,13 +1,27 @@
 #![cfg(feature = "fake")]
 #![cfg_attr(miri, ignore)]
 
+use std::time::Duration;
+
+use cot::StatusCode;
 use cot::db::migrations::{Field, Operation};
 use cot::db::query::ExprEq;
 use cot::db::{
     Auto, Database, DatabaseError, DatabaseField, ForeignKey, ForeignKeyOnDeletePolicy,
-    ForeignKeyOnUpdatePolicy, Identifier, LimitedString, Model, model, query,
+    ForeignKeyOnUpdatePolicy, Identifier, LimitedString, ManyToMany, Model, model, query,
+};
+use cot::deadline::Deadline;
+use cot::form::fields::ModelChoice;
+use cot::form::{
+    Form, FormContext, FormErrorTarget, FormField, FormFieldValidationError, ModelForm,
 };
-use cot::test::TestDatabase;
+use cot::html::Html;
+use cot::pagination::Paginator;
+use cot::request::extractors::ExistingModel;
+use cot::router::{Route, Router};
+use cot::test::{TestDatabase, TestRequestBuilder};
+use cot::validation::{Validate, ValidationErrors};
+use cot::views::{CreateView, DeleteView, DetailView, ListView, UpdateView};
 use fake::rand::SeedableRng;
 use fake::rand::rngs::StdRng;
 use fake::{Dummy, Fake, Faker};
@@ -15,7 +29,7 @@ use fake::{Dummy, Fake, Faker};
 struct WeekdaySetFaker;
 
 impl Dummy<WeekdaySetFaker> for chrono::WeekdaySet {
-    fn dummy_with_rng<R: fake::rand::Rng + ?Sized>(_: &WeekdaySetFaker, rng: &mut R) -> Self {
+    fn dummy_with_rng<R: rand::Rng + ?Sized>(_: &WeekdaySetFaker, rng: &mut R) -> Self {
         use chrono::Weekday;
 
         let mut set = chrono::WeekdaySet::EMPTY;
@@ -39,7 +53,7 @@ impl Dummy<WeekdaySetFaker> for chrono::WeekdaySet {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ModelForm)]
 #[model]
 struct TestModel {
     #[model(primary_key)]
@@ -82,6 +96,22 @@ async fn model_crud(test_db: &mut TestDatabase) {
     assert_eq!(TestModel::objects().all(&**test_db).await.unwrap(), vec![]);
 }
 
+#[cot_macros::dbtest]
+async fn model_query_deadline(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let objects = Deadline::after(Duration::from_secs(60))
+        .scope(TestModel::objects().all(&**test_db))
+        .await
+        .unwrap();
+    assert_eq!(objects, vec![]);
+
+    let result = Deadline::after(Duration::ZERO)
+        .scope(TestModel::objects().all(&**test_db))
+        .await;
+    assert!(matches!(result, Err(DatabaseError::DeadlineExceeded)));
+}
+
 #[cot_macros::dbtest]
 async fn model_insert(test_db: &mut TestDatabase) {
     migrate_test_model(&*test_db).await;
@@ -96,7 +126,7 @@ async fn model_insert(test_db: &mut TestDatabase) {
 
     // Can't insert the same model instance again
     let result = model.insert(&**test_db).await;
-    assert!(result.is_err());
+    assert!(matches!(result, Err(DatabaseError::UniqueViolation { .. })));
 
     // Read the model from the database
     let objects = TestModel::objects().all(&**test_db).await.unwrap();
@@ -135,6 +165,178 @@ async fn model_update(test_db: &mut TestDatabase) {
     assert_eq!(objects[0].name, "test2");
 }
 
+impl Validate for TestModel {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.name.is_empty() {
+            errors.add_field_error("name", FormFieldValidationError::Required);
+        }
+        errors.into_result()
+    }
+}
+
+#[cot_macros::dbtest]
+async fn model_validate_and_save(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let mut model = TestModel {
+        id: Auto::auto(),
+        name: String::new(),
+    };
+    let error = model.validate_and_save(&**test_db).await.unwrap_err();
+    let DatabaseError::Validation(errors) = error else {
+        panic!("expected a validation error, got {error:?}");
+    };
+    assert_eq!(
+        errors.field_errors("name"),
+        [FormFieldValidationError::Required]
+    );
+    assert!(
+        TestModel::objects()
+            .all(&**test_db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    model.name = "test".to_owned();
+    model.validate_and_save(&**test_db).await.unwrap();
+    let objects = TestModel::objects().all(&**test_db).await.unwrap();
+    assert_eq!(objects, [model]);
+}
+
+#[cot_macros::dbtest]
+async fn model_transaction_commit(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let count = test_db
+        .transaction(async |tx| {
+            let mut model = TestModel {
+                id: Auto::auto(),
+                name: "test".to_owned(),
+            };
+            model.save(&tx).await?;
+            tx.raw("UPDATE cot__test_model SET name = 'raw'").await?;
+
+            let objects = query!(TestModel, $name == "raw").all(&tx).await?;
+            Ok::<_, DatabaseError>(objects.len())
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let objects = TestModel::objects().all(&**test_db).await.unwrap();
+    assert_eq!(objects.len(), 1);
+    assert_eq!(objects[0].name, "raw");
+}
+
+#[cot_macros::dbtest]
+async fn model_transaction_rollback(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let result = test_db
+        .transaction(async |tx| {
+            let mut model = TestModel {
+                id: Auto::fixed(1),
+                name: "test".to_owned(),
+            };
+            model.insert(&tx).await?;
+            // fails because of the duplicate primary key
+            model.insert(&tx).await
+        })
+        .await;
+    assert!(matches!(result, Err(DatabaseError::UniqueViolation { .. })));
+
+    assert_eq!(TestModel::objects().all(&**test_db).await.unwrap(), vec![]);
+}
+
+#[cot_macros::dbtest]
+async fn model_transaction_handle_after_finish(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let tx = test_db
+        .transaction(async |tx| Ok::<_, DatabaseError>(tx))
+        .await
+        .unwrap();
+
+    let result = TestModel::objects().all(&tx).await;
+    assert!(matches!(result, Err(DatabaseError::TransactionFinished)));
+}
+
+async fn insert_test_models(db: &Database, ids: impl IntoIterator<Item = i32>) {
+    for id in ids {
+        TestModel {
+            id: Auto::fixed(id),
+            name: format!("test{id}"),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+}
+
+fn test_model_ids(models: &[TestModel]) -> Vec<i32> {
+    models.iter().map(|model| model.id.unwrap()).collect()
+}
+
+#[cot_macros::dbtest]
+async fn paginator_page(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+    insert_test_models(test_db, [5, 3, 1, 4, 2]).await;
+
+    let paginator = Paginator::new(TestModel::objects()).with_per_page(2);
+
+    let page = paginator.page(test_db, 1).await.unwrap();
+    assert_eq!(test_model_ids(page.items()), vec![1, 2]);
+    assert_eq!(page.total_count(), 5);
+    assert_eq!(page.page_count(), 3);
+    assert_eq!(page.next_page_number(), Some(2));
+
+    let page = paginator.page(test_db, 3).await.unwrap();
+    assert_eq!(test_model_ids(page.items()), vec![5]);
+    assert!(!page.has_next());
+
+    let error = paginator.page(test_db, 4).await.unwrap_err();
+    assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[cot_macros::dbtest]
+async fn paginator_page_empty(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let page = Paginator::new(TestModel::objects())
+        .page(test_db, 1)
+        .await
+        .unwrap();
+
+    assert!(page.items().is_empty());
+    assert_eq!(page.page_count(), 1);
+}
+
+#[cot_macros::dbtest]
+async fn paginator_cursor_page(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+    insert_test_models(test_db, [5, 3, 1, 4, 2, 6]).await;
+
+    let mut query = TestModel::objects();
+    query.filter(<TestModel as Model>::Fields::name.ne("test6"));
+    let paginator = Paginator::new(query).with_per_page(2);
+
+    let page = paginator.cursor_page(test_db, None).await.unwrap();
+    assert_eq!(test_model_ids(page.items()), vec![1, 2]);
+    assert_eq!(page.next_cursor(), Some("2"));
+
+    let page = paginator.cursor_page(test_db, Some("4")).await.unwrap();
+    assert_eq!(test_model_ids(page.items()), vec![5]);
+    assert_eq!(page.next_cursor(), None);
+
+    let error = paginator
+        .cursor_page(test_db, Some("invalid"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+}
+
 #[cot_macros::dbtest]
 async fn model_macro_filtering(test_db: &mut TestDatabase) {
     migrate_test_model(&*test_db).await;
@@ -160,6 +362,394 @@ async fn model_macro_filtering(test_db: &mut TestDatabase) {
     assert!(objects.is_empty());
 }
 
+#[cot_macros::dbtest]
+async fn existing_model_extractor(test_db: &mut TestDatabase) {
+    async fn detail(ExistingModel(model): ExistingModel<TestModel>) -> Html {
+        Html::new(model.name)
+    }
+
+    migrate_test_model(&*test_db).await;
+    let mut model = TestModel {
+        id: Auto::fixed(1),
+        name: "test".to_owned(),
+    };
+    model.save(&**test_db).await.unwrap();
+    let router = Router::with_urls([Route::with_handler("/{id}/", detail)]);
+
+    let request = TestRequestBuilder::get("/1/")
+        .router(router.clone())
+        .database(test_db.database())
+        .build();
+    let response = router.handle(request).await.unwrap();
+    assert_eq!(response.into_body().into_bytes().await.unwrap(), "test");
+
+    for url in ["/2/", "/invalid/"] {
+        let request = TestRequestBuilder::get(url)
+            .router(router.clone())
+            .database(test_db.database())
+            .build();
+        let error = router.handle(request).await.unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+}
+
+fn test_model_views_router() -> Router {
+    Router::with_urls([
+        Route::with_handler(
+            "/",
+            ListView::<TestModel>::new(|_urls, page| {
+                let names: Vec<_> = page
+                    .items()
+                    .iter()
+                    .map(|model| model.name.as_str())
+                    .collect();
+                Ok(Html::new(names.join(",")))
+            })
+            .with_per_page(2),
+        ),
+        Route::with_handler(
+            "/new/",
+            CreateView::<TestModel, TestModelForm>::new(
+                |_urls, _model, form| Ok(Html::new(format!("errors: {}", form.has_errors()))),
+                "/",
+            ),
+        ),
+        Route::with_handler(
+            "/{id}/",
+            DetailView::<TestModel>::new(|_urls, model| Ok(Html::new(model.name.clone()))),
+        ),
+        Route::with_handler(
+            "/{id}/edit/",
+            UpdateView::<TestModel, TestModelForm>::new(
+                |_urls, model, form| {
+                    Ok(Html::new(format!(
+                        "{}: {:?}",
+                        model.unwrap().name,
+                        form.name.value()
+                    )))
+                },
+                "/",
+            ),
+        ),
+        Route::with_handler(
+            "/{id}/delete/",
+            DeleteView::<TestModel>::new(
+                |_urls, model| Ok(Html::new(format!("delete {}?", model.name))),
+                "/",
+            ),
+        ),
+    ])
+}
+
+async fn handle_test_model_view(
+    test_db: &TestDatabase,
+    router: &Router,
+    request: &mut TestRequestBuilder,
+) -> cot::Result<String> {
+    let request = request
+        .router(router.clone())
+        .database(test_db.database())
+        .build();
+    let response = router.handle(request).await?;
+    let location = response.headers().get(http::header::LOCATION).cloned();
+    let body = response.into_body().into_bytes().await?;
+
+    Ok(match location {
+        Some(location) => format!("redirect: {}", location.to_str().unwrap()),
+        None => String::from_utf8(body.to_vec()).unwrap(),
+    })
+}
+
+#[cot_macros::dbtest]
+async fn model_form(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[("name", "test")])
+        .build();
+    let form = TestModelForm::from_request(&mut request)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut model = form.save(&**test_db).await.unwrap();
+    assert_eq!(model.name, "test");
+    assert_eq!(
+        TestModel::get_by_primary_key(&**test_db, model.id)
+            .await
+            .unwrap(),
+        Some(model.clone())
+    );
+
+    let form = TestModelForm::from(model.clone());
+    assert_eq!(form.name, "test");
+    let form = TestModelForm {
+        name: "updated".to_owned(),
+    };
+    form.save_to(&mut model, &**test_db).await.unwrap();
+    let objects = TestModel::objects().all(&**test_db).await.unwrap();
+    assert_eq!(objects, vec![model]);
+    assert_eq!(objects[0].name, "updated");
+}
+
+impl std::fmt::Display for TestModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Form)]
+struct TestModelChoiceForm {
+    #[form(opts(query = query!(TestModel, $name != "test2").clone()))]
+    model: ModelChoice<TestModel>,
+    models: Vec<ModelChoice<TestModel>>,
+}
+
+#[cot_macros::dbtest]
+async fn model_choice_fields(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+    insert_test_models(test_db, [1, 2, 3]).await;
+
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[("model", "3"), ("models", "1"), ("models", "2")])
+        .build();
+    let mut context = TestModelChoiceForm::build_context(&mut request)
+        .await
+        .unwrap();
+    context.model.load_choices(&**test_db).await.unwrap();
+    context.models.load_choices(&**test_db).await.unwrap();
+
+    let rendered = context.to_string();
+    assert!(rendered.contains(r#"<option value="1">test1</option>"#));
+    assert!(!rendered.contains(r#"<option value="2">test2</option>"#));
+    assert!(rendered.contains(r#"<option value="3" selected>test3</option>"#));
+    assert!(rendered.contains(r#"<option value="2" selected>test2</option>"#));
+
+    let form = TestModelChoiceForm::from_context(&mut context)
+        .await
+        .unwrap();
+    assert_eq!(form.model.name, "test3");
+    assert_eq!(
+        form.models
+            .into_iter()
+            .map(|model| model.into_inner().name)
+            .collect::<Vec<_>>(),
+        ["test1", "test2"]
+    );
+
+    // the model is not one of the available choices
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[("model", "2"), ("models", "1")])
+        .build();
+    let mut context = TestModelChoiceForm::build_context(&mut request)
+        .await
+        .unwrap();
+    context.model.load_choices(&**test_db).await.unwrap();
+    context.models.load_choices(&**test_db).await.unwrap();
+    assert!(
+        TestModelChoiceForm::from_context(&mut context)
+            .await
+            .is_none()
+    );
+    assert_eq!(
+        context.errors_for(FormErrorTarget::Field("model")),
+        &[FormFieldValidationError::invalid_value("2")]
+    );
+}
+
+#[cot_macros::dbtest]
+async fn generic_views(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+    insert_test_models(test_db, [1, 2, 3]).await;
+    let router = test_model_views_router();
+
+    let body = handle_test_model_view(test_db, &router, &mut TestRequestBuilder::get("/"))
+        .await
+        .unwrap();
+    assert_eq!(body, "test1,test2");
+    let body = handle_test_model_view(test_db, &router, &mut TestRequestBuilder::get("/?page=2"))
+        .await
+        .unwrap();
+    assert_eq!(body, "test3");
+
+    let body = handle_test_model_view(test_db, &router, &mut TestRequestBuilder::get("/2/"))
+        .await
+        .unwrap();
+    assert_eq!(body, "test2");
+
+    let body = handle_test_model_view(test_db, &router, &mut TestRequestBuilder::get("/new/"))
+        .await
+        .unwrap();
+    assert_eq!(body, "errors: false");
+    let body = handle_test_model_view(
+        test_db,
+        &router,
+        TestRequestBuilder::post("/new/").form_data(&[("name", "new")]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, "redirect: /");
+    let body = handle_test_model_view(
+        test_db,
+        &router,
+        TestRequestBuilder::post("/new/").form_data(&[("name", "")]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, "errors: true");
+
+    let body = handle_test_model_view(test_db, &router, &mut TestRequestBuilder::get("/1/edit/"))
+        .await
+        .unwrap();
+    assert_eq!(body, r#"test1: Some("test1")"#);
+    let body = handle_test_model_view(
+        test_db,
+        &router,
+        TestRequestBuilder::post("/1/edit/").form_data(&[("name", "updated")]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, "redirect: /");
+
+    let body = handle_test_model_view(test_db, &router, &mut TestRequestBuilder::get("/3/delete/"))
+        .await
+        .unwrap();
+    assert_eq!(body, "delete test3?");
+    let body = handle_test_model_view(
+        test_db,
+        &router,
+        &mut TestRequestBuilder::post("/3/delete/"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, "redirect: /");
+
+    let names: Vec<_> = TestModel::objects()
+        .all(&**test_db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|model| model.name)
+        .collect();
+    assert_eq!(names.len(), 3);
+    assert!(names.contains(&"updated".to_owned()));
+    assert!(names.contains(&"test2".to_owned()));
+    assert!(names.contains(&"new".to_owned()));
+
+    for url in ["/3/", "/3/edit/", "/?page=5"] {
+        let error = handle_test_model_view(test_db, &router, &mut TestRequestBuilder::get(url))
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+    let error = handle_test_model_view(test_db, &router, &mut TestRequestBuilder::post("/1/"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.status_code(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[cfg(feature = "cache")]
+#[cot_macros::dbtest]
+async fn model_cached_query(test_db: &mut TestDatabase) {
+    use cot::cache::Cache;
+    use cot::cache::store::memory::Memory;
+    use cot::config::Timeout;
+
+    let cache = Cache::new(Memory::new(), None, Timeout::Never);
+    let db = test_db.database().with_query_cache(cache);
+    migrate_test_model(&db).await;
+    let query = TestModel::objects().cached(Timeout::Never);
+
+    let mut model = TestModel {
+        id: Auto::fixed(1),
+        name: "test".to_owned(),
+    };
+    model.save(&db).await.unwrap();
+    assert_eq!(query.all(&db).await.unwrap()[0].name, "test");
+
+    // changes made with raw queries are not detected
+    db.raw("UPDATE cot__test_model SET name = 'raw'")
+        .await
+        .unwrap();
+    assert_eq!(query.all(&db).await.unwrap()[0].name, "test");
+
+    // saving the model invalidates the cache
+    model.name = "test2".to_owned();
+    model.save(&db).await.unwrap();
+    assert_eq!(query.all(&db).await.unwrap()[0].name, "test2");
+    assert_eq!(query.get(&db).await.unwrap().unwrap().name, "test2");
+
+    // deleting the model invalidates the cache
+    TestModel::objects().delete(&db).await.unwrap();
+    assert_eq!(query.all(&db).await.unwrap(), vec![]);
+    assert_eq!(query.get(&db).await.unwrap(), None);
+}
+
+#[cot_macros::dbtest]
+async fn model_change_notifications(test_db: &mut TestDatabase) {
+    use cot::db::changes::{ChangeKind, ModelChanges};
+    use cot::notify::Notifier;
+
+    let notifier = Notifier::new();
+    let db = test_db.database().with_change_notifier(notifier.clone());
+    migrate_test_model(&db).await;
+    let mut changes = ModelChanges::<TestModel>::new(&notifier);
+
+    let mut model = TestModel {
+        id: Auto::auto(),
+        name: "test".to_owned(),
+    };
+    model.save(&db).await.unwrap();
+    let change = changes.recv().await.unwrap();
+    assert_eq!(change.kind(), ChangeKind::Saved);
+    assert!(change.is_for(&model.id));
+
+    // changes made in a transaction that is rolled back are not published
+    let result: Result<(), DatabaseError> = db
+        .transaction(async |tx| {
+            model.name = "test2".to_owned();
+            model.save(&tx).await?;
+            Err(DatabaseError::TransactionFinished)
+        })
+        .await;
+    assert!(result.is_err());
+
+    let mut changes = changes.for_primary_key(&model.id);
+    TestModel::objects().delete(&db).await.unwrap();
+    let change = changes.recv().await.unwrap();
+    assert_eq!(change.kind(), ChangeKind::Deleted);
+    assert!(change.is_for(&model.id));
+}
+
+#[cot_macros::dbtest]
+async fn read_only_mode(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    let mut model = TestModel {
+        id: Auto::auto(),
+        name: "test".to_owned(),
+    };
+    model.save(&**test_db).await.unwrap();
+
+    test_db.set_read_only(true);
+    let error = model.save(&**test_db).await.unwrap_err();
+    assert!(matches!(error, DatabaseError::ReadOnly { ref table } if table == "cot__test_model"));
+    assert_eq!(
+        cot::Error::from(error).status_code(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert!(matches!(
+        TestModel::objects().delete(&**test_db).await,
+        Err(DatabaseError::ReadOnly { .. })
+    ));
+
+    // reads are still allowed
+    let models = TestModel::objects().all(&**test_db).await.unwrap();
+    assert_eq!(models.len(), 1);
+
+    test_db.set_read_only(false);
+    TestModel::objects().delete(&**test_db).await.unwrap();
+}
+
 async fn migrate_test_model(db: &Database) {
     CREATE_TEST_MODEL.forwards(db).await.unwrap();
 }
@@ -386,8 +976,7 @@ async fn foreign_keys(db: &mut TestDatabase) {
         .delete(&**db)
         .await
         .unwrap_err();
-    // expected foreign key violation
-    assert!(matches!(error, DatabaseError::DatabaseEngineError(_)));
+    assert!(matches!(error, DatabaseError::ForeignKeyViolation { .. }));
 
     query!(Track, $artist == &artist)
         .delete(&**db)
@@ -553,6 +1142,110 @@ async fn foreign_keys_cascade(db: &mut TestDatabase) {
     assert!(Child::objects().all(&**db).await.unwrap().is_empty());
 }
 
+#[cot_macros::dbtest]
+async fn many_to_many(db: &mut TestDatabase) {
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Post {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        tags: ManyToMany<Tag>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Tag {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+    }
+
+    const CREATE_POST: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__post"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+        ])
+        .build();
+    const CREATE_TAG: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__tag"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        ])
+        .build();
+    const CREATE_POST_TAGS: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__post_tags"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(
+                Identifier::new("source"),
+                <ForeignKey<Post> as DatabaseField>::TYPE,
+            )
+            .foreign_key(
+                <Post as Model>::TABLE_NAME,
+                <Post as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Restrict,
+                ForeignKeyOnUpdatePolicy::Restrict,
+            ),
+            Field::new(
+                Identifier::new("target"),
+                <ForeignKey<Tag> as DatabaseField>::TYPE,
+            )
+            .foreign_key(
+                <Tag as Model>::TABLE_NAME,
+                <Tag as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Restrict,
+                ForeignKeyOnUpdatePolicy::Restrict,
+            ),
+        ])
+        .build();
+
+    run_migrations!(db, CREATE_POST, CREATE_TAG, CREATE_POST_TAGS);
+
+    let mut post = Post {
+        id: Auto::auto(),
+        tags: ManyToMany::new(),
+    };
+    let error = post.tags().all(&**db).await.unwrap_err();
+    assert!(matches!(error, DatabaseError::UnsavedModel));
+    post.save(&**db).await.unwrap();
+
+    let mut tags = Vec::new();
+    for name in ["rust", "web", "orm"] {
+        let mut tag = Tag {
+            id: Auto::auto(),
+            name: name.to_owned(),
+        };
+        tag.save(&**db).await.unwrap();
+        tags.push(tag);
+    }
+    assert!(post.tags().all(&**db).await.unwrap().is_empty());
+
+    post.tags().add(&**db, &tags[0]).await.unwrap();
+    post.tags().add(&**db, &tags[1]).await.unwrap();
+    // adding an already related instance is a no-op
+    post.tags().add(&**db, &tags[0]).await.unwrap();
+    let mut related = post.tags().all(&**db).await.unwrap();
+    related.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+    assert_eq!(related, [tags[0].clone(), tags[1].clone()]);
+
+    post.tags().remove(&**db, &tags[0]).await.unwrap();
+    assert_eq!(post.tags().all(&**db).await.unwrap(), [tags[1].clone()]);
+
+    post.tags().add(&**db, &tags[2]).await.unwrap();
+    post.tags().clear(&**db).await.unwrap();
+    assert!(post.tags().all(&**db).await.unwrap().is_empty());
+
+    // no relations remain, so the post can be deleted
+    query!(Post, $id == post.id).delete(&**db).await.unwrap();
+}
+
 // Check different types for the primary key
 #[derive(Debug, PartialEq)]
 #[model]
@@ -932,3 +1625,142 @@ async fn bulk_insert_with_fixed_pk(test_db: &mut TestDatabase) {
         .unwrap();
     assert_eq!(model300.name, "test300");
 }
+
+#[cfg(feature = "storage")]
+#[cot_macros::dbtest]
+async fn stored_file_fields(db: &mut TestDatabase) {
+    use cot::storage::memory::Memory;
+    use cot::storage::{FileStorage, StoredFile, StoredImage};
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[model]
+    struct Attachment {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        file: StoredFile,
+        preview: Option<StoredImage>,
+    }
+
+    const CREATE_ATTACHMENT: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__attachment"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("file"), <StoredFile as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("preview"),
+                <Option<StoredImage> as DatabaseField>::TYPE,
+            )
+            .set_null(<Option<StoredImage> as DatabaseField>::NULLABLE),
+        ])
+        .build();
+
+    run_migrations!(db, CREATE_ATTACHMENT);
+
+    let storage = FileStorage::new(Memory::new());
+    let name = storage
+        .save_bytes("reports/q1.csv", "quarter,total\n")
+        .await
+        .unwrap();
+    let mut attachment = Attachment {
+        id: Auto::auto(),
+        file: StoredFile::new(name),
+        preview: None,
+    };
+    attachment.save(&**db).await.unwrap();
+
+    let from_db = Attachment::get_by_primary_key(&**db, attachment.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(from_db, attachment);
+    assert_eq!(from_db.file.url(&storage), "/media/reports/q1.csv");
+    assert_eq!(
+        from_db.file.open(&storage).await.unwrap(),
+        "quarter,total\n"
+    );
+}
+
+#[cot_macros::dbtest]
+async fn model_factories(db: &mut TestDatabase) {
+    use cot::common_types::Email;
+    use cot::test::factory::{Factory, FactoryBuilder};
+
+    #[derive(Debug, Clone, PartialEq, Factory)]
+    #[model]
+    struct Author {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        name: String,
+        email: Email,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Factory)]
+    #[model]
+    struct Book {
+        #[model(primary_key)]
+        id: Auto<i32>,
+        author: ForeignKey<Author>,
+        title: String,
+        #[factory(default = 100)]
+        pages: i32,
+    }
+
+    const CREATE_AUTHOR: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__author"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("email"), <Email as DatabaseField>::TYPE),
+        ])
+        .build();
+    const CREATE_BOOK: Operation = Operation::create_model()
+        .table_name(Identifier::new("cot__book"))
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(
+                Identifier::new("author"),
+                <ForeignKey<Author> as DatabaseField>::TYPE,
+            )
+            .foreign_key(
+                <Author as Model>::TABLE_NAME,
+                <Author as Model>::PRIMARY_KEY_NAME,
+                ForeignKeyOnDeletePolicy::Cascade,
+                ForeignKeyOnUpdatePolicy::Cascade,
+            ),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("pages"), <i32 as DatabaseField>::TYPE),
+        ])
+        .build();
+
+    run_migrations!(db, CREATE_AUTHOR, CREATE_BOOK);
+
+    // the author is created along with the book
+    let book = Book::factory()
+        .title("The Rust Programming Language")
+        .create(&**db)
+        .await
+        .unwrap();
+    assert_eq!(book.title, "The Rust Programming Language");
+    assert_eq!(book.pages, 100);
+    let author = Author::get_by_primary_key(&**db, *book.author.primary_key())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!author.name.is_empty());
+
+    // an explicitly set foreign key is used as is
+    let book = Book::factory().author(&author).create(&**db).await.unwrap();
+    assert_eq!(book.author.primary_key(), &author.id);
+    assert_eq!(Author::objects().count(db).await.unwrap(), 1);
+
+    let books = Book::create_batch(&**db, 3).await.unwrap();
+    assert_eq!(books.len(), 3);
+    assert_eq!(Book::objects().count(db).await.unwrap(), 5);
+    assert_eq!(Author::objects().count(db).await.unwrap(), 4);
+}
@@ -275,3 +275,167 @@ async fn select_field_context_display() {
     assert!(form_rendered.contains("value=\"medium\""));
     assert!(form_rendered.contains("value=\"high\""));
 }
+
+#[derive(Debug, Form)]
+struct CommentForm {
+    #[form(
+        help_text = "Who are you?",
+        attrs(class = "input", aria_label = "Author")
+    )]
+    author: String,
+    #[form(widget = Textarea, attrs(rows = "5"))]
+    content: String,
+}
+
+#[cot::test]
+async fn field_rendering_overrides() {
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[("author", "Alice"), ("content", "")])
+        .build();
+
+    let form_context = match CommentForm::from_request(&mut request).await.unwrap() {
+        FormResult::ValidationError(context) => context,
+        FormResult::Ok(_) => panic!("Expected a validation error"),
+    };
+
+    let author = form_context.field("author");
+    assert_eq!(
+        author.label().as_str(),
+        "<label for=\"author\">Author</label>"
+    );
+    assert_eq!(author.help_text(), Some("Who are you?"));
+    assert_eq!(
+        author.widget().as_str(),
+        "<input type=\"text\" name=\"author\" id=\"author\" value=\"Alice\" \
+        aria-label=\"Author\" class=\"input\" required/>"
+    );
+    assert!(!author.has_errors());
+
+    let content = form_context.field("content");
+    assert_eq!(content.help_text(), None);
+    assert_eq!(
+        content.widget().as_str(),
+        "<textarea name=\"content\" id=\"content\" rows=\"5\" required></textarea>"
+    );
+    assert_eq!(content.errors(), &[FormFieldValidationError::Required]);
+
+    let ids: Vec<_> = form_context
+        .bound_fields()
+        .map(|field| field.id())
+        .collect();
+    assert_eq!(ids, ["author", "content"]);
+}
+
+#[derive(Debug, Form)]
+struct BookingForm {
+    start: chrono::NaiveDate,
+    #[form(min_from = start)]
+    end: chrono::NaiveDate,
+}
+
+#[cot::test]
+async fn cross_field_constraint() {
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[("start", "2025-05-27"), ("end", "2025-05-28")])
+        .build();
+    let form = BookingForm::from_request(&mut request)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(form.end > form.start);
+
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[("start", "2025-05-27"), ("end", "2025-05-26")])
+        .build();
+    match BookingForm::from_request(&mut request).await {
+        Ok(FormResult::ValidationError(context)) => {
+            assert_eq!(context.errors_for(FormErrorTarget::Field("start")), &[]);
+            assert_eq!(
+                context.errors_for(FormErrorTarget::Field("end")),
+                &[FormFieldValidationError::minimum_value_not_met(
+                    "2025-05-27"
+                )]
+            );
+        }
+        _ => panic!("Expected a validation error"),
+    }
+}
+
+#[derive(Debug, Form)]
+#[form(validate = "check_passwords_match")]
+struct SignupForm {
+    #[form(validate_with = "check_username")]
+    username: String,
+    password: String,
+    password_confirmation: String,
+}
+
+fn check_username(username: &str) -> Result<(), FormFieldValidationError> {
+    if username.chars().all(char::is_alphanumeric) {
+        Ok(())
+    } else {
+        Err(FormFieldValidationError::from_static("invalid username"))
+    }
+}
+
+fn check_passwords_match(form: &SignupForm, context: &mut impl FormContext) {
+    if form.password != form.password_confirmation {
+        context.add_error(
+            FormErrorTarget::Field("password_confirmation"),
+            FormFieldValidationError::from_static("passwords do not match"),
+        );
+    }
+}
+
+#[cot::test]
+async fn custom_validators() {
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[
+            ("username", "john"),
+            ("password", "secret"),
+            ("password_confirmation", "secret"),
+        ])
+        .build();
+    let form = SignupForm::from_request(&mut request)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(form.username, "john");
+
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[
+            ("username", "john doe"),
+            ("password", "secret"),
+            ("password_confirmation", "secret"),
+        ])
+        .build();
+    match SignupForm::from_request(&mut request).await {
+        Ok(FormResult::ValidationError(context)) => {
+            assert_eq!(
+                context.errors_for(FormErrorTarget::Field("username")),
+                &[FormFieldValidationError::from_static("invalid username")]
+            );
+        }
+        _ => panic!("Expected a validation error"),
+    }
+
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[
+            ("username", "john"),
+            ("password", "secret"),
+            ("password_confirmation", "other"),
+        ])
+        .build();
+    match SignupForm::from_request(&mut request).await {
+        Ok(FormResult::ValidationError(context)) => {
+            assert_eq!(context.errors_for(FormErrorTarget::Field("username")), &[]);
+            assert_eq!(
+                context.errors_for(FormErrorTarget::Field("password_confirmation")),
+                &[FormFieldValidationError::from_static(
+                    "passwords do not match"
+                )]
+            );
+        }
+        _ => panic!("Expected a validation error"),
+    }
+}
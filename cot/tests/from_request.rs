@@ -1,6 +1,6 @@
 use cot::http::Request;
-use cot::request::RequestHead;
 use cot::request::extractors::FromRequestHead;
+use cot::request::{PathParams, RequestHead};
 
 #[derive(FromRequestHead)]
 #[expect(dead_code)]
@@ -15,6 +15,30 @@ struct MyUnitStruct;
 #[derive(FromRequestHead)]
 struct MyTupleStruct(DummyExtractor, DummyExtractor);
 
+#[derive(Debug, FromRequestHead)]
+struct MyAttributesStruct {
+    #[from_request(extension)]
+    tenant: TenantId,
+    #[from_request(extension, optional)]
+    user: Option<UserName>,
+    #[from_request(path = "id")]
+    post_id: i32,
+    #[from_request(path, default)]
+    page: u32,
+    #[from_request(path, default = "default_sort")]
+    sort: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TenantId(i32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UserName(String);
+
+fn default_sort() -> String {
+    "date".to_owned()
+}
+
 struct DummyExtractor;
 
 impl FromRequestHead for DummyExtractor {
@@ -43,3 +67,65 @@ async fn test_tuple_struct() {
     let (head, ()) = req.into_parts();
     let _ = MyTupleStruct::from_request_head(&head).await.unwrap();
 }
+
+fn head_with_path_params(params: &[(&str, &str)]) -> RequestHead {
+    let mut path_params = PathParams::new();
+    for (name, value) in params {
+        path_params.insert((*name).to_owned(), (*value).to_owned());
+    }
+
+    let req = Request::builder().uri("/").body(()).unwrap();
+    let (mut head, ()) = req.into_parts();
+    head.extensions.insert(path_params);
+    head
+}
+
+#[cot::test]
+async fn test_field_attributes() {
+    let mut head = head_with_path_params(&[("id", "42"), ("page", "3"), ("sort", "title")]);
+    head.extensions.insert(TenantId(1));
+    head.extensions.insert(UserName("admin".to_owned()));
+
+    let extracted = MyAttributesStruct::from_request_head(&head).await.unwrap();
+
+    assert_eq!(extracted.tenant, TenantId(1));
+    assert_eq!(extracted.user, Some(UserName("admin".to_owned())));
+    assert_eq!(extracted.post_id, 42);
+    assert_eq!(extracted.page, 3);
+    assert_eq!(extracted.sort, "title");
+}
+
+#[cot::test]
+async fn test_field_attributes_optional_and_default() {
+    let mut head = head_with_path_params(&[("id", "42"), ("page", "invalid")]);
+    head.extensions.insert(TenantId(1));
+
+    let extracted = MyAttributesStruct::from_request_head(&head).await.unwrap();
+
+    assert_eq!(extracted.user, None);
+    assert_eq!(extracted.page, 0);
+    assert_eq!(extracted.sort, "date");
+}
+
+#[cot::test]
+async fn test_field_attributes_missing_extension() {
+    let head = head_with_path_params(&[("id", "42")]);
+
+    let error = MyAttributesStruct::from_request_head(&head)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.status_code(), cot::StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[cot::test]
+async fn test_field_attributes_invalid_path_param() {
+    let mut head = head_with_path_params(&[("id", "not-a-number")]);
+    head.extensions.insert(TenantId(1));
+
+    let error = MyAttributesStruct::from_request_head(&head)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.status_code(), cot::StatusCode::BAD_REQUEST);
+}
@@ -1,11 +1,17 @@
+use std::sync::{Arc, Mutex};
+
 use bytes::Bytes;
-use cot::config::ProjectConfig;
+use cot::config::{ProjectConfig, StreamErrorPolicy};
+use cot::db::Database;
+use cot::error::UncaughtPanic;
+use cot::error::stream::{StreamError, StreamErrorHook};
 use cot::html::Html;
 use cot::project::RegisterAppsContext;
 use cot::request::Request;
+use cot::response::Response;
 use cot::router::{Route, Router};
-use cot::test::Client;
-use cot::{App, AppBuilder, Project, StatusCode, reverse};
+use cot::test::{Client, TestDatabase, TestServerBuilder};
+use cot::{App, AppBuilder, Body, Project, StatusCode, reverse};
 
 #[cot::test]
 #[cfg_attr(
@@ -120,3 +126,143 @@ async fn cot_router_reverse_local() {
         Bytes::from("/index2")
     );
 }
+
+#[cot::e2e_test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: can't call foreign function `socket`"
+)]
+async fn streaming_body_panic_is_reported() {
+    async fn events() -> Response {
+        let stream = futures_util::stream::unfold(0, |chunk| async move {
+            if chunk > 0 {
+                // make sure the headers and the first chunk are sent first
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                panic!("stream panicked");
+            }
+            Some((Ok(Bytes::from("data: first\n\n")), chunk + 1))
+        });
+        Response::new(Body::streaming(stream))
+    }
+
+    struct StreamingApp;
+    impl App for StreamingApp {
+        fn name(&self) -> &'static str {
+            "streaming"
+        }
+
+        fn router(&self) -> Router {
+            Router::with_urls([Route::with_handler("/events/", events)])
+        }
+    }
+
+    struct StreamingProject {
+        panics: Arc<Mutex<Vec<String>>>,
+    }
+    impl Project for StreamingProject {
+        fn config(&self, _config_name: &str) -> cot::Result<ProjectConfig> {
+            Ok(ProjectConfig::builder()
+                .register_panic_hook(false)
+                .stream_error_policy(StreamErrorPolicy::Abort)
+                .build())
+        }
+
+        fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+            apps.register_with_views(StreamingApp, "");
+        }
+
+        fn stream_error_hook(&self) -> Option<Arc<dyn StreamErrorHook>> {
+            let panics = Arc::clone(&self.panics);
+            Some(Arc::new(move |error: &StreamError| {
+                assert!(error.error().is::<UncaughtPanic>());
+                panics.lock().unwrap().push(error.uri().to_string());
+            }))
+        }
+    }
+
+    let panics = Arc::new(Mutex::new(Vec::new()));
+    let server = TestServerBuilder::new(StreamingProject {
+        panics: Arc::clone(&panics),
+    })
+    .start()
+    .await;
+
+    let response = reqwest::get(format!("{}/events/", server.url()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.bytes().await.is_err());
+    assert_eq!(*panics.lock().unwrap(), vec!["/events/".to_owned()]);
+
+    server.close().await;
+}
+
+#[cot::e2e_test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: can't call foreign function `socket`"
+)]
+async fn test_servers_with_separate_databases() {
+    async fn status(database: Database) -> Html {
+        Html::new(if database.is_read_only() {
+            "read-only"
+        } else {
+            "read-write"
+        })
+    }
+
+    struct StatusApp;
+    impl App for StatusApp {
+        fn name(&self) -> &'static str {
+            "status"
+        }
+
+        fn router(&self) -> Router {
+            Router::with_urls([Route::with_handler("/status/", status)])
+        }
+    }
+
+    struct StatusProject;
+    impl Project for StatusProject {
+        fn config(&self, config_name: &str) -> cot::Result<ProjectConfig> {
+            panic!("unexpected config load: {config_name}");
+        }
+
+        fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+            apps.register_with_views(StatusApp, "");
+        }
+    }
+
+    let read_only_database = TestDatabase::new_sqlite().await.unwrap();
+    read_only_database.set_read_only(true);
+    let read_write_database = TestDatabase::new_sqlite().await.unwrap();
+
+    let read_only_server = TestServerBuilder::new(StatusProject)
+        .config(ProjectConfig::default())
+        .database(read_only_database.database())
+        .start()
+        .await;
+    let read_write_server = TestServerBuilder::new(StatusProject)
+        .config(ProjectConfig::default())
+        .database(read_write_database.database())
+        .start()
+        .await;
+    assert_ne!(
+        read_only_server.address().port(),
+        read_write_server.address().port()
+    );
+
+    let response = reqwest::get(read_only_server.url_for("/status/"))
+        .await
+        .unwrap();
+    assert_eq!(response.text().await.unwrap(), "read-only");
+    let response = reqwest::get(read_write_server.url_for("status/"))
+        .await
+        .unwrap();
+    assert_eq!(response.text().await.unwrap(), "read-write");
+
+    read_only_server.close().await;
+    read_write_server.close().await;
+    read_only_database.cleanup().await.unwrap();
+    read_write_database.cleanup().await.unwrap();
+}
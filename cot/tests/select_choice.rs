@@ -25,6 +25,15 @@ enum WithOverrides {
     Default,
 }
 
+#[derive(SelectChoice, Debug, PartialEq, Eq)]
+enum Grouped {
+    #[select_choice(group = "Fruits")]
+    Apple,
+    #[select_choice(group = "Vegetables")]
+    Carrot,
+    Other,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,4 +111,12 @@ mod tests {
             assert_eq!(WithOverrides::from_str(&value.id()), Ok(value));
         }
     }
+
+    #[test]
+    fn grouped_groups() {
+        assert_eq!(Grouped::Apple.group().as_deref(), Some("Fruits"));
+        assert_eq!(Grouped::Carrot.group().as_deref(), Some("Vegetables"));
+        assert_eq!(Grouped::Other.group(), None);
+        assert_eq!(Status::Draft.group(), None);
+    }
 }
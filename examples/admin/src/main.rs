@@ -4,7 +4,9 @@ use std::fmt::{Display, Formatter};
 use std::time::Duration;
 
 use async_trait::async_trait;
-use cot::admin::{AdminApp, AdminModel, AdminModelManager, DefaultAdminModelManager};
+use cot::admin::{
+    AdminApp, AdminModel, AdminModelManager, AdminSiteConfig, DefaultAdminModelManager,
+};
 use cot::auth::db::{DatabaseUser, DatabaseUserApp};
 use cot::cli::CliMetadata;
 use cot::config::{
@@ -62,13 +64,17 @@ impl App for HelloApp {
     }
 
     async fn init(&self, context: &mut ProjectContext) -> cot::Result<()> {
-        // TODO use transaction
-        let user = DatabaseUser::get_by_username(context.database(), "admin").await?;
-        if user.is_none() {
-            DatabaseUser::create_user(context.database(), "admin", "admin").await?;
-        }
-
-        Ok(())
+        context
+            .database()
+            .transaction(async |tx| {
+                let user = DatabaseUser::get_by_username(&tx, "admin").await?;
+                if user.is_none() {
+                    DatabaseUser::create_user(&tx, "admin", "admin").await?;
+                }
+
+                Ok(())
+            })
+            .await
     }
 
     fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
@@ -116,7 +122,12 @@ impl Project for AdminProject {
 
     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
         apps.register(DatabaseUserApp::new());
-        apps.register_with_views(AdminApp::new(), "/admin");
+        let admin_config = AdminSiteConfig::new()
+            .with_site_title("Todo Admin")
+            .with_site_header("Todo Administration")
+            .with_group("Todos", ["todo_item"])
+            .with_group("Users", ["database_user"]);
+        apps.register_with_views(AdminApp::new(admin_config), "/admin");
         apps.register_with_views(HelloApp, "");
     }
 
@@ -9,7 +9,7 @@
 use async_trait::async_trait;
 use clap::{CommandFactory, FromArgMatches, Parser};
 use cot::cli::clap::{ArgMatches, Command};
-use cot::cli::{Cli, CliMetadata, CliTask};
+use cot::cli::{Cli, CliMetadata, CliTask, TaskOutcome};
 use cot::config::ProjectConfig;
 use cot::project::WithConfig;
 use cot::{Bootstrapper, Project};
@@ -33,12 +33,10 @@ impl CliTask for Frobnicate {
         &mut self,
         matches: &ArgMatches,
         _bootstrapper: Bootstrapper<WithConfig>,
-    ) -> cot::Result<()> {
+    ) -> cot::Result<TaskOutcome> {
         let command = FrobnicateCommand::from_arg_matches(matches).expect("invalid arguments");
 
-        println!("Frobnicating {}...", command.what);
-
-        Ok(())
+        Ok(TaskOutcome::new().with_message(format!("Frobnicated {}", command.what)))
     }
 }
 
@@ -0,0 +1,210 @@
+mod migrations;
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cot::admin::{AdminApp, AdminModel, AdminModelManager, DefaultAdminModelManager};
+use cot::auth::db::{DatabaseUser, DatabaseUserApp};
+use cot::cache::Cache;
+use cot::cli::CliMetadata;
+use cot::config::{
+    AuthBackendConfig, DatabaseConfig, MiddlewareConfig, ProjectConfig, SessionMiddlewareConfig,
+    Timeout,
+};
+use cot::db::migrations::SyncDynMigration;
+use cot::db::{Auto, Database, LimitedString, Model, model, query};
+use cot::error::NotFound;
+use cot::form::Form;
+use cot::html::Html;
+use cot::middleware::{AuthMiddleware, LiveReloadMiddleware, SessionMiddleware};
+use cot::project::{MiddlewareContext, RegisterAppsContext, RootHandler};
+use cot::request::extractors::Path;
+use cot::router::{Route, Router, Urls};
+use cot::static_files::StaticFilesMiddleware;
+use cot::{App, AppBuilder, Project, ProjectContext, Template};
+
+const MAX_SLUG_LENGTH: u32 = 255;
+/// How long a rendered page is served from the cache before it is read from
+/// the database again, so that the changes made in the admin panel show up.
+const PAGE_CACHE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A page whose content is managed entirely in the admin panel.
+#[derive(Debug, Clone, Form, AdminModel)]
+#[model]
+struct Page {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    #[model(unique)]
+    slug: LimitedString<MAX_SLUG_LENGTH>,
+    title: String,
+    body: String,
+    published: bool,
+}
+
+impl Display for Page {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (/{})", self.title, self.slug)
+    }
+}
+
+#[derive(Debug, Template)]
+#[template(path = "index.html")]
+struct IndexTemplate<'a> {
+    urls: &'a Urls,
+    pages: Vec<Page>,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "page.html")]
+struct PageTemplate<'a> {
+    urls: &'a Urls,
+    page: &'a Page,
+}
+
+async fn index(urls: Urls, db: Database) -> cot::Result<Html> {
+    let pages = query!(Page, $published == true).all(&db).await?;
+    let index_template = IndexTemplate { urls: &urls, pages };
+    let rendered = index_template.render()?;
+
+    Ok(Html::new(rendered))
+}
+
+async fn page(
+    urls: Urls,
+    db: Database,
+    cache: Cache,
+    Path(slug): Path<String>,
+) -> cot::Result<Html> {
+    let cache_key = format!("flatpages:{slug}");
+    if let Some(rendered) = cache.get::<_, String>(&cache_key).await? {
+        return Ok(Html::new(rendered));
+    }
+
+    let page = get_published_page(&db, &slug)
+        .await?
+        .ok_or_else(|| NotFound::with_message(format!("Page `{slug}` not found")))?;
+    let page_template = PageTemplate {
+        urls: &urls,
+        page: &page,
+    };
+    let rendered = page_template.render()?;
+    cache
+        .insert_expiring(cache_key, &rendered, Timeout::After(PAGE_CACHE_TIMEOUT))
+        .await?;
+
+    Ok(Html::new(rendered))
+}
+
+async fn get_published_page(db: &Database, slug: &str) -> cot::Result<Option<Page>> {
+    let Ok(slug) = LimitedString::<MAX_SLUG_LENGTH>::new(slug) else {
+        return Ok(None);
+    };
+    let page = query!(Page, $slug == slug && $published == true)
+        .get(db)
+        .await?;
+
+    Ok(page)
+}
+
+struct FlatpagesApp;
+
+#[async_trait]
+impl App for FlatpagesApp {
+    fn name(&self) -> &'static str {
+        env!("CARGO_PKG_NAME")
+    }
+
+    async fn init(&self, context: &mut ProjectContext) -> cot::Result<()> {
+        let user = DatabaseUser::get_by_username(context.database(), "admin").await?;
+        if user.is_none() {
+            DatabaseUser::create_user(context.database(), "admin", "admin").await?;
+        }
+
+        let about_slug = LimitedString::new("about").expect("slug is not too long");
+        let about_exists = query!(Page, $slug == about_slug.clone())
+            .exists(context.database())
+            .await?;
+        if !about_exists {
+            let mut page = Page {
+                id: Auto::auto(),
+                slug: about_slug,
+                title: "About".to_owned(),
+                body: "<p>This page is stored in the database and can be edited in the \
+                       admin panel.</p>"
+                    .to_owned(),
+                published: true,
+            };
+            page.save(context.database()).await?;
+        }
+
+        Ok(())
+    }
+
+    fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
+        cot::db::migrations::wrap_migrations(migrations::MIGRATIONS)
+    }
+
+    fn admin_model_managers(&self) -> Vec<Box<dyn AdminModelManager>> {
+        vec![Box::new(DefaultAdminModelManager::<Page>::new())]
+    }
+
+    fn router(&self) -> Router {
+        // The `{slug}` route matches any single path segment, so this app should be
+        // registered last to act as a catch-all for the URLs not handled by other
+        // apps.
+        Router::with_urls([
+            Route::with_handler_and_name("/", index, "index"),
+            Route::with_handler_and_name("/{slug}", page, "page"),
+        ])
+    }
+}
+
+struct FlatpagesProject;
+
+impl Project for FlatpagesProject {
+    fn cli_metadata(&self) -> CliMetadata {
+        cot::cli::metadata!()
+    }
+
+    fn config(&self, _config_name: &str) -> cot::Result<ProjectConfig> {
+        Ok(ProjectConfig::builder()
+            .debug(true)
+            .database(
+                DatabaseConfig::builder()
+                    .url("sqlite://db.sqlite3?mode=rwc")
+                    .build(),
+            )
+            .auth_backend(AuthBackendConfig::Database)
+            .middlewares(
+                MiddlewareConfig::builder()
+                    .session(SessionMiddlewareConfig::builder().secure(false).build())
+                    .build(),
+            )
+            .build())
+    }
+
+    fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+        apps.register(DatabaseUserApp::new());
+        apps.register_with_views(AdminApp::default(), "/admin");
+        apps.register_with_views(FlatpagesApp, "");
+    }
+
+    fn middlewares(
+        &self,
+        handler: cot::project::RootHandlerBuilder,
+        context: &MiddlewareContext,
+    ) -> RootHandler {
+        handler
+            .middleware(StaticFilesMiddleware::from_context(context))
+            .middleware(AuthMiddleware::new())
+            .middleware(SessionMiddleware::from_context(context))
+            .middleware(LiveReloadMiddleware::new())
+            .build()
+    }
+}
+
+#[cot::main]
+fn main() -> impl Project {
+    FlatpagesProject
+}
@@ -64,8 +64,9 @@ impl Project for JsonProject {
             .build()
     }
 
-    fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
-        apps.register_with_views(SwaggerUi::new(), "/swagger");
+    fn register_apps(&self, apps: &mut AppBuilder, context: &RegisterAppsContext) {
+        apps.when(context.config().debug || context.is_feature_enabled("api_docs"))
+            .register_with_views(SwaggerUi::new(), "/swagger");
         apps.register_with_views(AddApp, "");
     }
 
@@ -5,7 +5,7 @@ use cot::cli::CliMetadata;
 use cot::config::{DatabaseConfig, ProjectConfig};
 use cot::db::migrations::SyncDynMigration;
 use cot::db::{Auto, Database, Model, model, query};
-use cot::form::Form;
+use cot::form::ModelForm;
 use cot::html::Html;
 use cot::project::{MiddlewareContext, RegisterAppsContext, RootHandler};
 use cot::request::extractors::{Path, RequestForm};
@@ -14,11 +14,12 @@ use cot::router::{Route, Router, Urls};
 use cot::static_files::StaticFilesMiddleware;
 use cot::{App, AppBuilder, Project, Template, reverse_redirect};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ModelForm)]
 #[model]
 struct TodoItem {
     #[model(primary_key)]
     id: Auto<i32>,
+    #[form(opts(max_length = 100))]
     title: String,
 }
 
@@ -40,25 +41,12 @@ async fn index(urls: Urls, db: Database) -> cot::Result<Html> {
     Ok(Html::new(rendered))
 }
 
-#[derive(Debug, Form)]
-struct TodoForm {
-    #[form(opts(max_length = 100))]
-    title: String,
-}
-
 async fn add_todo(
     urls: Urls,
     db: Database,
-    RequestForm(todo_form): RequestForm<TodoForm>,
+    RequestForm(todo_form): RequestForm<TodoItemForm>,
 ) -> cot::Result<Response> {
-    let todo_form = todo_form.unwrap();
-
-    TodoItem {
-        id: Auto::auto(),
-        title: todo_form.title,
-    }
-    .save(&db)
-    .await?;
+    todo_form.unwrap().save(&db).await?;
 
     Ok(reverse_redirect!(urls, "index")?)
 }